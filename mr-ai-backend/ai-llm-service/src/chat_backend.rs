@@ -0,0 +1,126 @@
+//! Where [`LlmServiceProfiles`](crate::service_profiles::LlmServiceProfiles)
+//! sends generation requests.
+//!
+//! - [`ChatBackend::Http`] (default): the Ollama/OpenAI client of each profile.
+//! - `ChatBackend::Replay` (feature `replay`): canned responses keyed by
+//!   [`prompt_hash`], no network. Used to regression-test review output
+//!   against recorded answers.
+//!
+//! Replay fixtures are JSON objects mapping prompt hash → response text:
+//!
+//! ```json
+//! { "3f1c9a0b7d2e4f61": "{\"queries\": [], \"need_paths_like\": [], \"need_symbols_like\": []}" }
+//! ```
+
+#[cfg(any(test, feature = "replay"))]
+use std::{collections::HashMap, path::Path, sync::Mutex};
+
+use sha2::{Digest, Sha256};
+#[cfg(any(test, feature = "replay"))]
+use tracing::warn;
+
+#[cfg(any(test, feature = "replay"))]
+use crate::error_handler::AiLlmError;
+
+/// Source of completions for the `generate_*` calls.
+#[derive(Debug, Default)]
+pub enum ChatBackend {
+    /// Real providers from the profile configs.
+    #[default]
+    Http,
+    /// Canned responses; embeddings are not available.
+    #[cfg(any(test, feature = "replay"))]
+    Replay(ReplayChat),
+}
+
+/// Stable fixture key for a prompt: first 16 hex chars of its SHA-256.
+pub fn prompt_hash(prompt: &str) -> String {
+    let digest = Sha256::digest(prompt.as_bytes());
+    digest[..8].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Replays recorded responses keyed by [`prompt_hash`].
+///
+/// Profiles are not distinguished: the same prompt gets the same answer from
+/// fast and slow. Unknown prompts get the fallback (if set) or an error, and
+/// are remembered in [`ReplayChat::misses`] so fixtures can be completed.
+#[cfg(any(test, feature = "replay"))]
+#[derive(Debug, Default)]
+pub struct ReplayChat {
+    responses: HashMap<String, String>,
+    fallback: Option<String>,
+    misses: Mutex<Vec<String>>,
+}
+
+#[cfg(any(test, feature = "replay"))]
+impl ReplayChat {
+    /// Creates a replay backend from `hash → response` pairs.
+    pub fn new(responses: HashMap<String, String>) -> Self {
+        Self {
+            responses,
+            ..Self::default()
+        }
+    }
+
+    /// Loads `hash → response` pairs from a JSON object file.
+    ///
+    /// # Errors
+    /// [`AiLlmError::Replay`] if the file can't be read or isn't a JSON object of strings.
+    pub fn from_json_file(path: &Path) -> Result<Self, AiLlmError> {
+        let raw = std::fs::read_to_string(path)
+            .map_err(|e| AiLlmError::Replay(format!("read {}: {e}", path.display())))?;
+        let responses = serde_json::from_str(&raw)
+            .map_err(|e| AiLlmError::Replay(format!("parse {}: {e}", path.display())))?;
+        Ok(Self::new(responses))
+    }
+
+    /// Answer used for prompts without a recorded response.
+    pub fn with_fallback(mut self, response: impl Into<String>) -> Self {
+        self.fallback = Some(response.into());
+        self
+    }
+
+    /// Recorded response for `prompt`.
+    ///
+    /// # Errors
+    /// [`AiLlmError::Replay`] naming the prompt hash when nothing is recorded
+    /// and there is no fallback.
+    pub fn respond(&self, prompt: &str) -> Result<String, AiLlmError> {
+        let hash = prompt_hash(prompt);
+        if let Some(out) = self.responses.get(&hash) {
+            return Ok(out.clone());
+        }
+        warn!(prompt_hash = %hash, prompt_len = prompt.len(), "replay miss");
+        self.misses.lock().unwrap().push(hash.clone());
+        self.fallback
+            .clone()
+            .ok_or_else(|| AiLlmError::Replay(format!("no recorded response for prompt {hash}")))
+    }
+
+    /// Hashes of prompts that had no recorded response, in call order.
+    pub fn misses(&self) -> Vec<String> {
+        self.misses.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replays_by_prompt_hash_and_records_misses() {
+        let known = "review this line";
+        let chat = ReplayChat::new(HashMap::from([(prompt_hash(known), "ok".to_string())]));
+
+        assert_eq!(prompt_hash(known).len(), 16);
+        assert_eq!(prompt_hash(known), prompt_hash(known));
+        assert_eq!(chat.respond(known).unwrap(), "ok");
+
+        let err = chat.respond("something else").unwrap_err();
+        assert!(err.to_string().contains(&prompt_hash("something else")));
+        assert_eq!(chat.misses(), vec![prompt_hash("something else")]);
+
+        let chat = chat.with_fallback("{}");
+        assert_eq!(chat.respond("unknown").unwrap(), "{}");
+    }
+}
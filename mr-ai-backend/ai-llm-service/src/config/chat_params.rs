@@ -0,0 +1,147 @@
+//! Per-request sampling parameters forwarded to the provider.
+//!
+//! For Ollama these map to the `options` object of `/api/generate`:
+//! `temperature`, `top_p`, `num_ctx` and `stop`. Unset values are omitted so the
+//! model's own defaults apply.
+
+use crate::error_handler::{AiLlmError, ConfigError};
+
+/// Sampling/context options for a single generation.
+///
+/// Use `temperature = Some(0.0)` for deterministic output (reproducible review
+/// snapshots) and `num_ctx` to widen the model's context window.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChatParams {
+    /// Sampling temperature, `0.0..=2.0`.
+    pub temperature: Option<f32>,
+    /// Nucleus sampling cutoff, `0.0 < top_p <= 1.0`.
+    pub top_p: Option<f32>,
+    /// Context window in tokens (Ollama `num_ctx`), must be > 0.
+    pub num_ctx: Option<u32>,
+    /// Stop sequences; empty strings are rejected.
+    pub stop: Vec<String>,
+}
+
+impl ChatParams {
+    /// Checks value ranges.
+    ///
+    /// # Errors
+    /// - [`ConfigError::OutOfRange`] for temperature/top_p/num_ctx outside their ranges
+    /// - [`ConfigError::InvalidStopSequence`] if any stop sequence is empty
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(t) = self.temperature
+            && !(0.0..=2.0).contains(&t)
+        {
+            return Err(ConfigError::OutOfRange {
+                field: "temperature",
+                detail: "expected 0.0..=2.0",
+            });
+        }
+        if let Some(p) = self.top_p
+            && !(p > 0.0 && p <= 1.0)
+        {
+            return Err(ConfigError::OutOfRange {
+                field: "top_p",
+                detail: "expected 0.0 < top_p <= 1.0",
+            });
+        }
+        if self.num_ctx == Some(0) {
+            return Err(ConfigError::OutOfRange {
+                field: "num_ctx",
+                detail: "expected > 0",
+            });
+        }
+        if self.stop.iter().any(|s| s.is_empty()) {
+            return Err(ConfigError::InvalidStopSequence);
+        }
+        Ok(())
+    }
+
+    /// Overrides fields from the environment where set:
+    /// - `LLM_TEMPERATURE` (f32)
+    /// - `LLM_TOP_P` (f32)
+    /// - `LLM_NUM_CTX` (u32)
+    /// - `LLM_STOP` (a JSON array of strings, or a single stop sequence)
+    ///
+    /// # Errors
+    /// [`ConfigError::InvalidNumber`] / [`ConfigError::InvalidFormat`] on unparsable values.
+    pub fn with_env_overrides(mut self) -> Result<Self, AiLlmError> {
+        if let Some(t) = env_opt_f32("LLM_TEMPERATURE")? {
+            self.temperature = Some(t);
+        }
+        if let Some(p) = env_opt_f32("LLM_TOP_P")? {
+            self.top_p = Some(p);
+        }
+        if let Some(n) = crate::error_handler::env_opt_u32("LLM_NUM_CTX")? {
+            self.num_ctx = Some(n);
+        }
+        if let Ok(raw) = std::env::var("LLM_STOP") {
+            let raw = raw.trim();
+            if raw.starts_with('[') {
+                self.stop = serde_json::from_str(raw).map_err(|_| ConfigError::InvalidFormat {
+                    var: "LLM_STOP",
+                    reason: "expected a JSON array of strings",
+                })?;
+            } else if !raw.is_empty() {
+                self.stop = vec![raw.to_string()];
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Parses an optional `f32` from env (`Ok(None)` if unset/empty).
+fn env_opt_f32(name: &'static str) -> Result<Option<f32>, AiLlmError> {
+    match std::env::var(name) {
+        Ok(v) if !v.trim().is_empty() => v.trim().parse::<f32>().map(Some).map_err(|_| {
+            AiLlmError::from(ConfigError::InvalidNumber {
+                var: name,
+                reason: "expected f32",
+            })
+        }),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_rejects_out_of_range_values() {
+        let ok = ChatParams {
+            temperature: Some(0.0),
+            top_p: Some(1.0),
+            num_ctx: Some(8192),
+            stop: vec!["</json>".into()],
+        };
+        assert!(ok.validate().is_ok());
+        assert!(ChatParams::default().validate().is_ok());
+
+        let bad = [
+            ChatParams {
+                temperature: Some(-0.1),
+                ..Default::default()
+            },
+            ChatParams {
+                temperature: Some(f32::NAN),
+                ..Default::default()
+            },
+            ChatParams {
+                top_p: Some(0.0),
+                ..Default::default()
+            },
+            ChatParams {
+                num_ctx: Some(0),
+                ..Default::default()
+            },
+            ChatParams {
+                stop: vec![String::new()],
+                ..Default::default()
+            },
+        ];
+        for p in bad {
+            assert!(p.validate().is_err(), "{p:?} should be rejected");
+        }
+    }
+}
@@ -16,6 +16,8 @@
 //! Common:
 //! - `LLM_KIND` = provider kind (e.g., `ollama`, `chatgpt`)
 //! - `LLM_MAX_TOKENS` = optional max tokens (u32)
+//! - `LLM_TEMPERATURE`, `LLM_TOP_P`, `LLM_NUM_CTX`, `LLM_STOP` = optional sampling
+//!   overrides for the slow and fast models (see [`crate::config::chat_params::ChatParams::with_env_overrides`])
 //!
 //! Ollama-specific:
 //! - `OLLAMA_URL` or `OLLAMA_PORT` = endpoint (mandatory)
@@ -67,6 +69,7 @@ fn ollama_endpoint() -> Result<String, AiLlmError> {
 /// # Env
 /// - `OLLAMA_MODEL` (required)
 /// - `LLM_MAX_TOKENS` (optional)
+/// - `LLM_TEMPERATURE` / `LLM_TOP_P` / `LLM_NUM_CTX` / `LLM_STOP` (optional overrides)
 ///
 /// # Defaults
 /// - `temperature = Some(0.2)`
@@ -76,7 +79,7 @@ pub fn config_ollama_slow() -> Result<LlmModelConfig, AiLlmError> {
     let model = must_env("OLLAMA_MODEL")?;
     let max_tokens = env_opt_u32("LLM_MAX_TOKENS")?;
 
-    Ok(LlmModelConfig {
+    with_env_sampling(LlmModelConfig {
         provider: LlmProvider::Ollama,
         model,
         endpoint,
@@ -84,6 +87,8 @@ pub fn config_ollama_slow() -> Result<LlmModelConfig, AiLlmError> {
         max_tokens,
         temperature: Some(0.2),
         top_p: None,
+        num_ctx: None,
+        stop: Vec::new(),
         timeout_secs: Some(600),
     })
 }
@@ -95,6 +100,7 @@ pub fn config_ollama_slow() -> Result<LlmModelConfig, AiLlmError> {
 /// # Env
 /// - `OLLAMA_MODEL_FAST_MODEL` or `OLLAMA_MODEL_FAST` (required)
 /// - `LLM_MAX_TOKENS` (optional)
+/// - `LLM_TEMPERATURE` / `LLM_TOP_P` / `LLM_NUM_CTX` / `LLM_STOP` (optional overrides)
 ///
 /// # Defaults
 /// - `temperature = Some(0.7)`
@@ -111,7 +117,7 @@ pub fn config_ollama_fast() -> Result<LlmModelConfig, AiLlmError> {
         ))?;
     let max_tokens = env_opt_u32("LLM_MAX_TOKENS")?;
 
-    Ok(LlmModelConfig {
+    with_env_sampling(LlmModelConfig {
         provider: LlmProvider::Ollama,
         model,
         endpoint,
@@ -119,6 +125,8 @@ pub fn config_ollama_fast() -> Result<LlmModelConfig, AiLlmError> {
         max_tokens,
         temperature: Some(0.7),
         top_p: Some(0.9),
+        num_ctx: None,
+        stop: Vec::new(),
         timeout_secs: Some(600),
     })
 }
@@ -146,6 +154,15 @@ pub fn config_ollama_embedding() -> Result<LlmModelConfig, AiLlmError> {
         max_tokens: None,
         temperature: Some(0.0),
         top_p: None,
+        num_ctx: None,
+        stop: Vec::new(),
         timeout_secs: Some(30),
     })
 }
+
+/// Applies `LLM_TEMPERATURE`/`LLM_TOP_P`/`LLM_NUM_CTX`/`LLM_STOP` and validates the result.
+fn with_env_sampling(cfg: LlmModelConfig) -> Result<LlmModelConfig, AiLlmError> {
+    let params = cfg.chat_params().with_env_overrides()?;
+    params.validate()?;
+    Ok(cfg.with_chat_params(params))
+}
@@ -1,4 +1,4 @@
-use crate::config::llm_provider::LlmProvider;
+use crate::config::{chat_params::ChatParams, llm_provider::LlmProvider};
 
 /// Configuration for an LLM model invocation.
 ///
@@ -14,6 +14,8 @@ use crate::config::llm_provider::LlmProvider;
 /// - `max_tokens`: Maximum number of tokens to generate (if supported).
 /// - `temperature`: Controls randomness (0.0 = deterministic, >1.0 = more random).
 /// - `top_p`: Nucleus sampling cutoff (alternative to temperature).
+/// - `num_ctx`: Context window in tokens (Ollama only).
+/// - `stop`: Stop sequences.
 /// - `timeout_secs`: Optional request timeout in seconds.
 ///
 /// # Examples
@@ -29,6 +31,8 @@ use crate::config::llm_provider::LlmProvider;
 ///     max_tokens: Some(2048),
 ///     temperature: Some(0.7),
 ///     top_p: None,
+///     num_ctx: None,
+///     stop: Vec::new(),
 ///     timeout_secs: Some(30),
 /// };
 /// ```
@@ -55,6 +59,33 @@ pub struct LlmModelConfig {
     /// Nucleus sampling parameter.
     pub top_p: Option<f32>,
 
+    /// Context window size in tokens (Ollama `num_ctx`).
+    pub num_ctx: Option<u32>,
+
+    /// Stop sequences that end generation.
+    pub stop: Vec<String>,
+
     /// Optional request timeout (in seconds).
     pub timeout_secs: Option<u64>,
 }
+
+impl LlmModelConfig {
+    /// Sampling parameters sent with each generation request.
+    pub fn chat_params(&self) -> ChatParams {
+        ChatParams {
+            temperature: self.temperature,
+            top_p: self.top_p,
+            num_ctx: self.num_ctx,
+            stop: self.stop.clone(),
+        }
+    }
+
+    /// Replaces the sampling parameters.
+    pub fn with_chat_params(mut self, params: ChatParams) -> Self {
+        self.temperature = params.temperature;
+        self.top_p = params.top_p;
+        self.num_ctx = params.num_ctx;
+        self.stop = params.stop;
+        self
+    }
+}
@@ -1,3 +1,4 @@
+pub mod chat_params;
 pub mod default_config;
 pub mod llm_model_config;
 pub mod llm_provider;
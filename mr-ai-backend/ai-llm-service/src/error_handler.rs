@@ -45,6 +45,9 @@ pub enum AiLlmError {
 
     /// Operation exceeded the configured timeout.
     Timeout(Duration),
+
+    /// Replay backend had no recorded answer (or fixtures couldn't be loaded).
+    Replay(String),
 }
 
 impl fmt::Display for AiLlmError {
@@ -56,6 +59,7 @@ impl fmt::Display for AiLlmError {
             AiLlmError::Provider(e) => e.to_string(),
             AiLlmError::HttpTransport(e) => format!("transport error: {e}"),
             AiLlmError::Timeout(dur) => format!("operation timed out after {dur:?}"),
+            AiLlmError::Replay(msg) => format!("replay: {msg}"),
         };
         // Append the library suffix centrally (single source of truth).
         write!(f, "{base} [AI LLM Service]")
@@ -69,7 +73,7 @@ impl StdError for AiLlmError {
             AiLlmError::Health(e) => Some(e),
             AiLlmError::Provider(e) => Some(e),
             AiLlmError::HttpTransport(e) => Some(e),
-            AiLlmError::Timeout(_) => None,
+            AiLlmError::Timeout(_) | AiLlmError::Replay(_) => None,
         }
     }
 }
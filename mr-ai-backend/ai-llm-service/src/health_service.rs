@@ -7,7 +7,9 @@
 //! The returned [`HealthStatus`] is JSON-serializable and suitable for a `/health` endpoint.
 //! [`HealthService::check`] is resilient and never fails (errors mapped to `ok=false`).
 //! Provider-specific probes (`try_*`) return strict `Result`.
+//! [`retry_until_healthy`] re-runs a probe with bounded exponential backoff.
 
+use std::future::Future;
 use std::time::{Duration, Instant};
 
 use reqwest::header;
@@ -73,6 +75,43 @@ impl HealthStatus {
     }
 }
 
+/// Upper bound for the delay between [`retry_until_healthy`] attempts.
+pub const MAX_HEALTH_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Runs `probe` until every status is `ok` or `attempts` (at least 1) probes
+/// were made, sleeping `initial_backoff` before the first retry and doubling
+/// it (up to [`MAX_HEALTH_BACKOFF`]) after each one. Returns the last statuses.
+pub async fn retry_until_healthy<F, Fut>(
+    attempts: u32,
+    initial_backoff: Duration,
+    mut probe: F,
+) -> Vec<HealthStatus>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Vec<HealthStatus>>,
+{
+    let attempts = attempts.max(1);
+    let mut backoff = initial_backoff;
+    let mut attempt = 1;
+    loop {
+        let statuses = probe().await;
+        let down = statuses.iter().filter(|s| !s.ok).count();
+        if down == 0 || attempt >= attempts {
+            return statuses;
+        }
+        warn!(
+            attempt,
+            attempts,
+            down,
+            backoff_ms = backoff.as_millis(),
+            "health probes failing; retrying"
+        );
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_HEALTH_BACKOFF);
+        attempt += 1;
+    }
+}
+
 /// A universal health checker that reuses a single HTTP client.
 ///
 /// The client is constructed with a default timeout. Individual probes may
@@ -92,7 +131,9 @@ impl HealthService {
     /// Returns [`AiLlmError::HttpTransport`] if the HTTP client cannot be built.
     pub fn new(timeout_secs: Option<u64>) -> Result<Self, AiLlmError> {
         let timeout = Duration::from_secs(timeout_secs.unwrap_or(10));
-        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        let client =
+            services::http_proxy::apply_proxy_env(reqwest::Client::builder().timeout(timeout))?
+                .build()?;
 
         info!(
             default_timeout_secs = timeout.as_secs(),
@@ -418,3 +459,46 @@ impl HealthService {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(ok: bool) -> HealthStatus {
+        let make = if ok {
+            HealthStatus::ok
+        } else {
+            HealthStatus::fail
+        };
+        make(
+            LlmProvider::Ollama,
+            "http://localhost:11434",
+            Some("qwen"),
+            1,
+            "probe",
+        )
+    }
+
+    #[tokio::test]
+    async fn model_down_at_startup_is_retried_until_it_recovers() {
+        let mut calls = 0;
+        let statuses = retry_until_healthy(5, Duration::from_millis(1), || {
+            calls += 1;
+            let up = calls >= 3;
+            async move { vec![status(true), status(up)] }
+        })
+        .await;
+        assert_eq!(calls, 3);
+        assert!(statuses.iter().all(|s| s.ok));
+
+        // Bounded: a model that never comes up ends with its failing status.
+        let mut calls = 0;
+        let statuses = retry_until_healthy(2, Duration::from_millis(1), || {
+            calls += 1;
+            async { vec![status(false)] }
+        })
+        .await;
+        assert_eq!(calls, 2);
+        assert!(!statuses[0].ok);
+    }
+}
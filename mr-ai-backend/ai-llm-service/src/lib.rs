@@ -1,5 +1,6 @@
+pub mod chat_backend;
 pub mod config;
-mod error_handler;
+pub mod error_handler;
 mod health_service;
 pub mod service_profiles;
 mod services;
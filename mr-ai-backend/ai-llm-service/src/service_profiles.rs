@@ -5,24 +5,30 @@
 //! - Caches underlying HTTP clients per config (endpoint+model+key+timeout).
 //! - Provides convenience methods to generate via fast/slow and to compute embeddings.
 //! - If `slow` profile is not provided, it falls back to `fast`.
+//! - Generation can be served by a replay backend instead (see [`ChatBackend`]).
 
 use std::{
     collections::HashMap,
     hash::{Hash, Hasher},
     sync::Arc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use crate::{
+    chat_backend::ChatBackend,
     config::{llm_model_config::LlmModelConfig, llm_provider::LlmProvider},
     error_handler::AiLlmError,
-    health_service::{HealthService, HealthStatus},
+    health_service::{HealthService, HealthStatus, retry_until_healthy},
     services::{ollama_service::OllamaService, open_ai_service::OpenAiService},
 };
 
+/// How long [`LlmServiceProfiles::readiness`] reuses a snapshot before
+/// probing the profiles again.
+const READINESS_TTL: Duration = Duration::from_secs(10);
+
 /// Shared service that manages three logical LLM profiles: **fast**, **slow**, and **embedding**.
 ///
 /// Internally, it caches Ollama/OpenAI clients keyed by their configuration to
@@ -37,6 +43,10 @@ pub struct LlmServiceProfiles {
     openai: RwLock<HashMap<ClientKey, Arc<OpenAiService>>>,
 
     health: HealthService,
+    /// Statuses from the latest [`Self::health_all`] run.
+    last_health: RwLock<HealthSnapshot>,
+
+    backend: ChatBackend,
 }
 
 impl LlmServiceProfiles {
@@ -75,9 +85,18 @@ impl LlmServiceProfiles {
             ollama: RwLock::new(HashMap::new()),
             openai: RwLock::new(HashMap::new()),
             health: HealthService::new(health_timeout_secs)?,
+            last_health: RwLock::new(HealthSnapshot::default()),
+            backend: ChatBackend::default(),
         })
     }
 
+    /// Routes generation (and embeddings) through `backend` instead of the
+    /// profile providers; e.g. the replay backend for offline tests.
+    pub fn with_chat_backend(mut self, backend: ChatBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
     /// Generates text using the **fast** profile.
     ///
     /// # Arguments
@@ -135,24 +154,52 @@ impl LlmServiceProfiles {
     /// # Errors
     /// Returns [`AiLlmError`] if embedding fails.
     pub async fn embed(&self, input: &str) -> Result<Vec<f32>, AiLlmError> {
+        self.embed_using(&self.embedding, input).await
+    }
+
+    /// Computes embeddings with `model` on the **embedding** profile's
+    /// provider and endpoint, e.g. for a namespace indexed with another model.
+    ///
+    /// # Errors
+    /// Returns [`AiLlmError`] if embedding fails.
+    pub async fn embed_with_model(&self, model: &str, input: &str) -> Result<Vec<f32>, AiLlmError> {
+        if model == self.embedding.model {
+            return self.embed(input).await;
+        }
+        let profile = LlmModelConfig {
+            model: model.to_string(),
+            ..self.embedding.clone()
+        };
+        self.embed_using(&profile, input).await
+    }
+
+    async fn embed_using(
+        &self,
+        profile: &LlmModelConfig,
+        input: &str,
+    ) -> Result<Vec<f32>, AiLlmError> {
+        #[cfg(any(test, feature = "replay"))]
+        if let ChatBackend::Replay(_) = self.backend {
+            return Err(AiLlmError::Replay("embeddings are not replayed".into()));
+        }
         let started = Instant::now();
 
-        let out = match self.embedding.provider {
+        let out = match profile.provider {
             LlmProvider::Ollama => {
-                let cli = self.get_or_init_ollama(&self.embedding).await?;
+                let cli = self.get_or_init_ollama(profile).await?;
                 cli.embeddings(input).await.map_err(AiLlmError::from)
             }
             LlmProvider::OpenAI => {
-                let cli = self.get_or_init_openai(&self.embedding).await?;
+                let cli = self.get_or_init_openai(profile).await?;
                 cli.embeddings(input).await
             }
         };
 
         if out.is_ok() {
             info!(
-                provider = %self.embedding.provider,
-                model = %self.embedding.model,
-                endpoint = %self.embedding.endpoint,
+                provider = %profile.provider,
+                model = %profile.model,
+                endpoint = %profile.endpoint,
                 input_len = input.len(),
                 latency_ms = started.elapsed().as_millis(),
                 "embeddings completed"
@@ -174,7 +221,67 @@ impl LlmServiceProfiles {
             list.push(self.embedding.clone());
         }
         debug!(profiles = list.len(), "running health checks");
-        Ok(self.health.check_many(&list).await)
+        let statuses = self.health.check_many(&list).await;
+        *self.last_health.write().await = HealthSnapshot {
+            statuses: statuses.clone(),
+            checked_at: Some(Instant::now()),
+        };
+        Ok(statuses)
+    }
+
+    /// Startup health check that never fails: runs [`Self::health_all`] up to
+    /// `attempts` times with exponential backoff until every profile is
+    /// healthy, then logs a warning per profile that is still down.
+    ///
+    /// Routes that don't use a down model keep working; [`Self::readiness`]
+    /// reports the model once it comes online.
+    pub async fn health_with_retry(
+        &self,
+        attempts: u32,
+        initial_backoff: Duration,
+    ) -> Vec<HealthStatus> {
+        let statuses = retry_until_healthy(attempts, initial_backoff, || async {
+            self.health_all().await.unwrap_or_default()
+        })
+        .await;
+        for s in statuses.iter().filter(|s| !s.ok) {
+            warn!(
+                provider = %s.provider,
+                endpoint = %s.endpoint,
+                model = %s.model.as_deref().unwrap_or("n/a"),
+                message = %s.message,
+                "LLM profile unhealthy after startup checks"
+            );
+        }
+        if statuses.iter().all(|s| s.ok) {
+            info!(profiles = statuses.len(), "all LLM profiles healthy");
+        }
+        statuses
+    }
+
+    /// Statuses of the latest health check (empty before the first one).
+    pub async fn last_health(&self) -> Vec<HealthStatus> {
+        self.last_health.read().await.statuses.clone()
+    }
+
+    /// Health for readiness probes: the last snapshot if it is less than
+    /// [`READINESS_TTL`] old, otherwise a fresh check. Probes hitting a down
+    /// model don't re-check it each time; models that went down or recovered
+    /// show up within the TTL.
+    pub async fn readiness(&self) -> Vec<HealthStatus> {
+        let last = {
+            let snap = self.last_health.read().await;
+            if snap.fresh_within(READINESS_TTL) {
+                return snap.statuses.clone();
+            }
+            snap.statuses.clone()
+        };
+        self.health_all().await.unwrap_or(last)
+    }
+
+    /// Active generation backend (e.g. to read replay misses).
+    pub fn chat_backend(&self) -> &ChatBackend {
+        &self.backend
     }
 
     /// Returns references to the current profiles `(fast, slow, embedding)`.
@@ -190,12 +297,17 @@ impl LlmServiceProfiles {
         prompt: &str,
         system: Option<&str>,
     ) -> Result<String, AiLlmError> {
+        #[cfg(any(test, feature = "replay"))]
+        if let ChatBackend::Replay(chat) = &self.backend {
+            return chat.respond(prompt);
+        }
         let started = Instant::now();
 
         let res = match cfg.provider {
             LlmProvider::Ollama => {
                 let cli = self.get_or_init_ollama(cfg).await?;
-                cli.generate(prompt).await
+                // Clients are shared per endpoint+model; sampling comes from this profile.
+                cli.generate_with_params(prompt, &cfg.chat_params()).await
             }
             LlmProvider::OpenAI => {
                 let cli = self.get_or_init_openai(cfg).await?;
@@ -203,6 +315,11 @@ impl LlmServiceProfiles {
             }
         };
 
+        // Counted for failures too: latency of timeouts is what operators look for.
+        metrics::counter!("llm_calls_total", "model" => cfg.model.clone()).increment(1);
+        metrics::histogram!("llm_latency_seconds", "model" => cfg.model.clone())
+            .record(started.elapsed().as_secs_f64());
+
         if res.is_ok() {
             info!(
                 provider = %cfg.provider,
@@ -336,3 +453,77 @@ impl Hash for ClientKey {
         self.timeout.hash(state);
     }
 }
+
+/// Result of the latest health check and when it finished.
+#[derive(Debug, Default)]
+struct HealthSnapshot {
+    statuses: Vec<HealthStatus>,
+    checked_at: Option<Instant>,
+}
+
+impl HealthSnapshot {
+    /// A non-empty check finished less than `ttl` ago.
+    fn fresh_within(&self, ttl: Duration) -> bool {
+        let fresh = self.checked_at.is_some_and(|t| t.elapsed() < ttl);
+        fresh && !self.statuses.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unreachable_profile() -> LlmModelConfig {
+        LlmModelConfig {
+            provider: LlmProvider::Ollama,
+            model: "m".into(),
+            endpoint: "http://127.0.0.1:1".into(),
+            api_key: None,
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            num_ctx: None,
+            stop: Vec::new(),
+            timeout_secs: None,
+        }
+    }
+
+    fn snapshot(ok: bool, checked_at: Instant) -> HealthSnapshot {
+        HealthSnapshot {
+            statuses: vec![HealthStatus {
+                provider: "Ollama".into(),
+                endpoint: "http://127.0.0.1:1".into(),
+                model: Some("m".into()),
+                ok,
+                latency_ms: 1,
+                message: "ok".into(),
+            }],
+            checked_at: Some(checked_at),
+        }
+    }
+
+    #[tokio::test]
+    async fn readiness_reuses_fresh_snapshots_and_reprobes_expired_ones() {
+        let svc =
+            LlmServiceProfiles::new(unreachable_profile(), None, unreachable_profile(), Some(1))
+                .unwrap();
+
+        *svc.last_health.write().await = snapshot(true, Instant::now());
+        assert!(svc.readiness().await.iter().all(|s| s.ok));
+
+        // A fresh unhealthy snapshot is served as-is: the probe shows the
+        // recorded message rather than a new connection error.
+        let mut down = snapshot(false, Instant::now());
+        down.statuses[0].message = "recorded".into();
+        *svc.last_health.write().await = down;
+        let statuses = svc.readiness().await;
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].message, "recorded");
+
+        let stale = Instant::now().checked_sub(READINESS_TTL + Duration::from_secs(1));
+        *svc.last_health.write().await = snapshot(true, stale.unwrap());
+        let statuses = svc.readiness().await;
+        assert!(!statuses.is_empty() && statuses.iter().all(|s| !s.ok));
+        assert!(svc.last_health.read().await.fresh_within(READINESS_TTL));
+    }
+}
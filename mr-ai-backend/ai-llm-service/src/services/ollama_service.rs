@@ -18,7 +18,9 @@ use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info};
 
 use crate::{
-    config::{llm_model_config::LlmModelConfig, llm_provider::LlmProvider},
+    config::{
+        chat_params::ChatParams, llm_model_config::LlmModelConfig, llm_provider::LlmProvider,
+    },
     error_handler::{
         AiLlmError, HttpError, Provider, ProviderError, ProviderErrorKind, make_snippet,
     },
@@ -28,7 +30,7 @@ use crate::{
 ///
 /// Constructed from a complete [`LlmModelConfig`]. Internally keeps a
 /// preconfigured `reqwest::Client` (with timeout). Provides two high-level calls:
-/// - [`OllamaService::generate`]   — single, non-streaming text generation
+/// - [`OllamaService::generate_with_params`] — single, non-streaming text generation
 /// - [`OllamaService::embeddings`] — single embeddings vector retrieval
 #[derive(Debug)]
 pub struct OllamaService {
@@ -68,13 +70,18 @@ impl OllamaService {
             .into());
         }
 
-        // 3) HTTP client: timeout only; compression is enabled via crate features.
+        // 3) Sampling parameters must be in range.
+        cfg.chat_params().validate()?;
+
+        // 4) HTTP client: timeout + explicit proxy; compression is enabled via crate features.
         let timeout = cfg
             .timeout_secs
             .map(Duration::from_secs)
             .unwrap_or_else(|| Duration::from_secs(60));
 
-        let client = reqwest::Client::builder().timeout(timeout).build()?;
+        let client =
+            services::http_proxy::apply_proxy_env(reqwest::Client::builder().timeout(timeout))?
+                .build()?;
 
         let base = endpoint.trim_end_matches('/').to_string();
         let url_generate = format!("{}/api/generate", base);
@@ -98,25 +105,34 @@ impl OllamaService {
 
     /// Performs a **non-streaming** generation request via `/api/generate`.
     ///
-    /// The request maps config to Ollama fields as follows:
+    /// The request maps to Ollama fields as follows:
     /// - `model`        ← `self.cfg.model`
     /// - `prompt`       ← `prompt` argument
     /// - `options`:
     ///   - `num_predict`  ← `self.cfg.max_tokens`
-    ///   - `temperature`  ← `self.cfg.temperature`
-    ///   - `top_p`        ← `self.cfg.top_p`
+    ///   - `temperature`  ← `params.temperature`
+    ///   - `top_p`        ← `params.top_p`
+    ///   - `num_ctx`      ← `params.num_ctx`
+    ///   - `stop`         ← `params.stop`
     /// - `stream` is forced to `false` (this method is synchronous)
     ///
     /// # Parameters
     /// - `prompt`: user content to generate from
+    /// - `params`: sampling parameters (`self.cfg.chat_params()` for the config's)
     ///
     /// # Errors
+    /// - [`AiLlmError::Config`] if `params` fail validation
     /// - [`AiLlmError::Provider`] with `HttpStatus` for non-2xx responses
     /// - [`AiLlmError::HttpTransport`] for client/network failures
     /// - [`AiLlmError::Provider`] with `Decode` if the JSON cannot be parsed
-    pub async fn generate(&self, prompt: &str) -> Result<String, AiLlmError> {
+    pub async fn generate_with_params(
+        &self,
+        prompt: &str,
+        params: &ChatParams,
+    ) -> Result<String, AiLlmError> {
+        params.validate()?;
         let started = Instant::now();
-        let body = GenerateRequest::from_cfg(&self.cfg, prompt);
+        let body = GenerateRequest::new(&self.cfg, params, prompt);
 
         debug!(
             model = %self.cfg.model,
@@ -295,12 +311,14 @@ struct GenerateRequest<'a> {
 }
 
 impl<'a> GenerateRequest<'a> {
-    /// Builds a request from config and prompt (forces `stream=false`).
-    fn from_cfg(cfg: &'a LlmModelConfig, prompt: &'a str) -> Self {
+    /// Builds a request from config, sampling params and prompt (forces `stream=false`).
+    fn new(cfg: &'a LlmModelConfig, params: &ChatParams, prompt: &'a str) -> Self {
         let options = GenerateOptions {
-            temperature: cfg.temperature,
-            top_p: cfg.top_p,
+            temperature: params.temperature,
+            top_p: params.top_p,
             num_predict: cfg.max_tokens,
+            num_ctx: params.num_ctx,
+            stop: params.stop.clone(),
         };
 
         Self {
@@ -314,7 +332,7 @@ impl<'a> GenerateRequest<'a> {
 
 /// Subset of Ollama `options`.
 ///
-/// Extend this struct as needed (top_k, stop sequences, penalties, etc.).
+/// Extend this struct as needed (top_k, penalties, etc.).
 #[derive(Debug, Default, Serialize)]
 struct GenerateOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -323,6 +341,10 @@ struct GenerateOptions {
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
 }
 
 /// Response body for `/api/generate`.
@@ -98,10 +98,12 @@ impl OpenAiService {
             header::HeaderValue::from_static("application/json"),
         );
 
-        let client = reqwest::Client::builder()
-            .timeout(timeout)
-            .default_headers(headers)
-            .build()?;
+        let client = services::http_proxy::apply_proxy_env(
+            reqwest::Client::builder()
+                .timeout(timeout)
+                .default_headers(headers),
+        )?
+        .build()?;
 
         let base = endpoint.trim_end_matches('/').to_string();
         let url_chat = format!("{}/v1/chat/completions", base);
@@ -323,6 +325,8 @@ struct ChatCompletionRequest<'a> {
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<&'a [String]>,
 }
 
 impl<'a> ChatCompletionRequest<'a> {
@@ -346,6 +350,7 @@ impl<'a> ChatCompletionRequest<'a> {
             temperature: cfg.temperature,
             top_p: cfg.top_p,
             max_tokens: cfg.max_tokens,
+            stop: (!cfg.stop.is_empty()).then_some(cfg.stop.as_slice()),
         }
     }
 }
@@ -1,6 +1,7 @@
 use std::{env, fmt, sync::Arc};
 
 use ai_llm_service::service_profiles::LlmServiceProfiles;
+use metrics_exporter_prometheus::PrometheusHandle;
 
 /// Application configuration loaded from environment variables.
 #[derive(Clone, Debug)]
@@ -13,6 +14,8 @@ pub struct AppConfig {
     pub git_token: String,
     /// Secret used to protect trigger endpoints.
     pub trigger_secret: String,
+    /// Require `X-Admin-Secret` on `GET /metrics` (`METRICS_REQUIRE_AUTH`, default false).
+    pub metrics_require_auth: bool,
 }
 
 /// Errors that may occur while loading configuration.
@@ -55,6 +58,16 @@ impl AppConfig {
         let git_token = must_var("GIT_TOKEN")?;
         let trigger_secret = must_var("TRIGGER_SECRET")?;
 
+        // Optional: scrapers usually sit on a private network.
+        let metrics_require_auth = env::var("METRICS_REQUIRE_AUTH")
+            .map(|v| {
+                matches!(
+                    v.trim().to_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false);
+
         if !(git_api_base.starts_with("http://") || git_api_base.starts_with("https://")) {
             return Err(ConfigError::InvalidValue {
                 name: "GIT_API_BASE",
@@ -67,6 +80,7 @@ impl AppConfig {
             git_api_base,
             git_token,
             trigger_secret,
+            metrics_require_auth,
         })
     }
 }
@@ -78,14 +92,21 @@ pub struct AppState {
     pub config: Arc<AppConfig>,
     /// LLM service profiles (e.g. Ollama).
     pub llm_profiles: Arc<LlmServiceProfiles>,
+    /// Renders the Prometheus recorder for `GET /metrics`.
+    pub metrics: PrometheusHandle,
 }
 
 impl AppState {
     /// Create state from pre-loaded configuration.
-    pub fn new(config: Arc<AppConfig>, llm_profiles: Arc<LlmServiceProfiles>) -> Self {
+    pub fn new(
+        config: Arc<AppConfig>,
+        llm_profiles: Arc<LlmServiceProfiles>,
+        metrics: PrometheusHandle,
+    ) -> Self {
         Self {
             config,
             llm_profiles,
+            metrics,
         }
     }
 }
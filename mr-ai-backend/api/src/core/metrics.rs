@@ -0,0 +1,98 @@
+//! Prometheus recorder behind the `metrics` facade.
+//!
+//! Library crates only emit through `metrics::counter!`/`histogram!`; this
+//! module installs the process-wide recorder and describes the series so
+//! `GET /metrics` renders `# HELP` lines.
+
+use metrics::{describe_counter, describe_histogram};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+
+/// Buckets for `llm_latency_seconds` and `contextor_latency_seconds`; local
+/// models range from sub-second to minutes.
+const LLM_LATENCY_BUCKETS: &[f64] = &[0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+/// Install the global Prometheus recorder and return a handle for rendering.
+///
+/// Must be called once per process, before any metric is emitted.
+pub fn install() -> Result<PrometheusHandle, BuildError> {
+    let handle = builder()?.install_recorder()?;
+    describe();
+    Ok(handle)
+}
+
+/// Recorder configuration (histogram buckets), without installing it.
+fn builder() -> Result<PrometheusBuilder, BuildError> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full("llm_latency_seconds".into()),
+            LLM_LATENCY_BUCKETS,
+        )?
+        .set_buckets_for_metric(
+            Matcher::Full("contextor_latency_seconds".into()),
+            LLM_LATENCY_BUCKETS,
+        )
+}
+
+/// Describes every series on the current recorder.
+fn describe() {
+    describe_counter!("reviews_total", "Completed MR reviews (steps 1–5).");
+    describe_counter!("llm_calls_total", "LLM generation calls by model.");
+    describe_histogram!(
+        "llm_latency_seconds",
+        metrics::Unit::Seconds,
+        "LLM generation latency by model, failures included."
+    );
+    describe_counter!(
+        "slow_escalations_total",
+        "Step-4 SLOW model calls by reason (pre_route or refine)."
+    );
+    describe_counter!(
+        "comments_posted_total",
+        "Review comments created on the provider (dry-run excluded)."
+    );
+    describe_counter!("search_requests_total", "Code search requests.");
+    describe_counter!(
+        "contextor_calls_total",
+        "Contextor questions answered, by outcome (ok or error)."
+    );
+    describe_histogram!(
+        "contextor_latency_seconds",
+        metrics::Unit::Seconds,
+        "Contextor answer latency (retrieval and chat)."
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_described_series_in_prometheus_text_format() {
+        let recorder = builder().unwrap().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            describe();
+            metrics::counter!("reviews_total").increment(2);
+            metrics::counter!("slow_escalations_total", "reason" => "refine").increment(1);
+            metrics::counter!("contextor_calls_total", "outcome" => "ok").increment(1);
+            metrics::histogram!("contextor_latency_seconds").record(1.5);
+        });
+
+        let text = handle.render();
+        assert!(text.contains("# HELP reviews_total"), "{text}");
+        assert!(text.contains("reviews_total 2"), "{text}");
+        assert!(
+            text.contains("slow_escalations_total{reason=\"refine\"} 1"),
+            "{text}"
+        );
+        assert!(
+            text.contains("contextor_calls_total{outcome=\"ok\"} 1"),
+            "{text}"
+        );
+        // Histograms get the configured buckets, not the summary default.
+        assert!(
+            text.contains("contextor_latency_seconds_bucket{le=\"2.5\"} 1"),
+            "{text}"
+        );
+    }
+}
@@ -1,2 +1,4 @@
 pub mod app_state;
 pub mod http;
+pub mod metrics;
+pub mod prepare_review;
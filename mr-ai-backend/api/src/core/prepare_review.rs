@@ -0,0 +1,284 @@
+//! "Index then review": bring the project checkout and its Qdrant index up to
+//! date, then run `mr_reviewer::run_review`, so RAG context reflects the code
+//! being reviewed instead of whatever was indexed last.
+//!
+//! Freshness is three steps, each reusing the existing pipeline:
+//! 1. `project_code_store::sync_list` for the given repositories (fetch + reset);
+//! 2. `code_indexer::update_project_jsonl` to re-parse the files changed since
+//!    the base ref into the JSONL;
+//! 3. `rag_base::index_changed_since` (incremental).
+//!
+//! When the base ref doesn't resolve (fresh clone, unknown ref) the whole tree
+//! is exported with `code_indexer::index_project_to_jsonl` instead; then, as
+//! when there is no compatible collection yet, the index is rebuilt with
+//! `rag_base::reindex_blue_green`.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use code_indexer::IndexReport;
+use mr_reviewer::{
+    ReviewOptions, RunReview,
+    git_providers::{ChangeRequestId, ProviderConfig, ProviderKind, TlsConfig},
+    publish::PublishConfig,
+    run_review,
+};
+use rag_base::{errors::rag_base_error::RagBaseError, structs::rag_store::IndexStats};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::{
+    core::app_state::{AppConfig, AppState},
+    error_handler::{AppError, AppResult},
+    routes::sync_git::sync_git_response::RepoSyncEntry,
+};
+
+/// Repositories synced concurrently (same as `POST /sync_git`).
+const SYNC_CONCURRENCY: usize = 2;
+
+/// Switches for [`prepare_and_review`].
+#[derive(Debug, Clone)]
+pub struct PrepareOptions {
+    /// Review against the current index without syncing or reindexing.
+    pub skip_freshness: bool,
+    /// Repositories to sync before indexing; empty keeps checkouts as they are.
+    pub repo_urls: Vec<String>,
+    /// Base of the incremental reindex. Default `HEAD@{1}`: each checkout's
+    /// position before the sync moved it.
+    pub base_ref: String,
+    /// Options forwarded to `run_review`.
+    pub review: ReviewOptions,
+}
+
+impl Default for PrepareOptions {
+    fn default() -> Self {
+        Self {
+            skip_freshness: false,
+            repo_urls: Vec::new(),
+            base_ref: "HEAD@{1}".into(),
+            review: ReviewOptions::default(),
+        }
+    }
+}
+
+/// How the Qdrant index was refreshed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum IndexRefresh {
+    /// Only files changed since `base_ref` were re-ingested.
+    Incremental {
+        changed_files: usize,
+        stats: IndexStats,
+    },
+    /// Full blue/green rebuild; `reason` says why incremental was not possible.
+    Full {
+        collection: String,
+        reason: String,
+        stats: IndexStats,
+    },
+}
+
+/// Sync and index stats of the freshness step.
+#[derive(Debug, Clone, Serialize)]
+pub struct FreshnessReport {
+    /// Per synced repository; empty when no `repo_urls` were given. A failed
+    /// sync carries its error and leaves the previous checkout indexed.
+    pub synced: Vec<RepoSyncEntry>,
+    /// Chunks written to the regenerated JSONL.
+    pub chunks: usize,
+    pub index: IndexRefresh,
+}
+
+/// Combined outcome of [`prepare_and_review`].
+#[derive(Debug)]
+pub struct PreparedReview {
+    /// `None` when `PrepareOptions::skip_freshness` was set.
+    pub freshness: Option<FreshnessReport>,
+    pub review: RunReview,
+}
+
+/// GitLab provider settings from the app config (token, API base, TLS, headers).
+pub fn gitlab_provider_config(config: &AppConfig) -> ProviderConfig {
+    let (extra_headers, allow_auth_header_override) = ProviderConfig::extra_headers_from_env();
+    ProviderConfig {
+        kind: ProviderKind::GitLab,
+        base_api: config.git_api_base.clone(),
+        token: config.git_token.clone(),
+        tls: TlsConfig::from_env(),
+        extra_headers,
+        allow_auth_header_override,
+    }
+}
+
+/// Ensure `project` is synced and indexed (unless skipped), then review `id`.
+pub async fn prepare_and_review(
+    state: &AppState,
+    project: &str,
+    id: ChangeRequestId,
+    opts: PrepareOptions,
+) -> AppResult<PreparedReview> {
+    let freshness = if opts.skip_freshness {
+        info!(project, "prepare_and_review: freshness step skipped");
+        None
+    } else {
+        Some(ensure_fresh(project, &opts.repo_urls, &opts.base_ref).await?)
+    };
+
+    let review = run_review(
+        gitlab_provider_config(&state.config),
+        id,
+        Arc::clone(&state.llm_profiles),
+        PublishConfig::default(),
+        opts.review,
+    )
+    .await
+    .map_err(|e| AppError::Http {
+        status: StatusCode::BAD_GATEWAY,
+        code: "REVIEW_FAILED",
+        message: format!("review failed: {e}"),
+    })?;
+
+    Ok(PreparedReview { freshness, review })
+}
+
+/// Sync `repo_urls`, regenerate the JSONL and bring the index up to date.
+async fn ensure_fresh(
+    project: &str,
+    repo_urls: &[String],
+    base_ref: &str,
+) -> AppResult<FreshnessReport> {
+    let synced = if repo_urls.is_empty() {
+        Vec::new()
+    } else {
+        project_code_store::sync_list(repo_urls.to_vec(), SYNC_CONCURRENCY, project, false)
+            .await?
+            .into_iter()
+            .map(RepoSyncEntry::from)
+            .collect()
+    };
+
+    let changed = match rag_base::changed_files_since(project, base_ref).await {
+        Err(e) if !needs_full_rebuild(&e) => return Err(e.into()),
+        changed => changed,
+    };
+    let report = export_jsonl(project, changed.as_ref().ok().cloned()).await?;
+
+    let index = match changed {
+        Ok(_) => match rag_base::index_changed_since(project, base_ref).await {
+            Ok(inc) => IndexRefresh::Incremental {
+                changed_files: inc.changed_files,
+                stats: inc.stats,
+            },
+            Err(e) if needs_full_rebuild(&e) => full_rebuild(project, base_ref, e).await?,
+            Err(e) => return Err(e.into()),
+        },
+        Err(e) => full_rebuild(project, base_ref, e).await?,
+    };
+
+    info!(
+        project,
+        synced = synced.len(),
+        chunks = report.total_chunks,
+        index = ?index,
+        "prepare_and_review: project is fresh"
+    );
+    Ok(FreshnessReport {
+        synced,
+        chunks: report.total_chunks,
+        index,
+    })
+}
+
+/// Rewrites the project's JSONL: only `changed` files when given, else the
+/// whole tree.
+async fn export_jsonl(project: &str, changed: Option<BTreeSet<String>>) -> AppResult<IndexReport> {
+    let name = project.to_string();
+    tokio::task::spawn_blocking(move || match changed {
+        Some(files) => code_indexer::update_project_jsonl(&name, &files, true, None, None),
+        None => code_indexer::index_project_to_jsonl(&name, true, None, None),
+    })
+    .await
+    .map_err(|e| index_error(format!("indexer task failed: {e}")))?
+    .map_err(|e| index_error(format!("indexing {project} failed: {e}")))
+}
+
+/// Blue/green rebuild after the incremental path failed with `reason`.
+async fn full_rebuild(
+    project: &str,
+    base_ref: &str,
+    reason: RagBaseError,
+) -> AppResult<IndexRefresh> {
+    warn!(project, base_ref, error = %reason, "prepare_and_review: full reindex");
+    let bg = rag_base::reindex_blue_green(project).await?;
+    Ok(IndexRefresh::Full {
+        collection: bg.collection,
+        reason: reason.to_string(),
+        stats: bg.stats,
+    })
+}
+
+/// Incremental reindex failures a full rebuild fixes: `base_ref` can't be
+/// resolved (e.g. a fresh clone has no `HEAD@{1}`), the collection is missing
+/// or was built with another embedding model. Outages, other git failures and
+/// config mistakes (bad URL, missing model) go back to the caller.
+fn needs_full_rebuild(err: &RagBaseError) -> bool {
+    matches!(
+        err,
+        RagBaseError::UnresolvedRef { .. }
+            | RagBaseError::CollectionMissing { .. }
+            | RagBaseError::DimensionMismatch { .. }
+    )
+}
+
+fn index_error(message: String) -> AppError {
+    AppError::Http {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        code: "INDEX_FAILED",
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unresolved_refs_and_incompatible_collections_force_a_rebuild() {
+        let (endpoint, collection) = (String::from("http://q:6334"), String::from("code"));
+        let rebuild = [
+            RagBaseError::UnresolvedRef {
+                reference: "HEAD@{1}".into(),
+                repo: "code_data/app".into(),
+                message: "reflog has no entry".into(),
+            },
+            RagBaseError::CollectionMissing {
+                endpoint: endpoint.clone(),
+                collection: collection.clone(),
+            },
+            RagBaseError::DimensionMismatch {
+                endpoint: endpoint.clone(),
+                collection: collection.clone(),
+                got: 768,
+                want: 1024,
+            },
+        ];
+        let keep = [
+            RagBaseError::Git("open code_data/app: permission denied".into()),
+            RagBaseError::Unreachable {
+                endpoint: endpoint.clone(),
+                collection: collection.clone(),
+                message: "connection refused".into(),
+            },
+            RagBaseError::Timeout {
+                endpoint,
+                collection,
+                message: "deadline exceeded".into(),
+            },
+            RagBaseError::InvalidConfig("bad QDRANT_URL".into()),
+        ];
+
+        assert!(rebuild.iter().all(needs_full_rebuild));
+        assert!(!keep.iter().any(needs_full_rebuild));
+    }
+}
@@ -4,6 +4,8 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use project_code_store::errors::GitCloneError;
+use rag_base::errors::rag_base_error::RagBaseError;
+use rag_store::RagError;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -26,6 +28,9 @@ pub enum AppError {
     #[error("server error")]
     Server(#[source] std::io::Error),
 
+    #[error("failed to install metrics recorder")]
+    Metrics(#[source] metrics_exporter_prometheus::BuildError),
+
     // --- Request / routing ---
     #[error("bad request: {0}")]
     BadRequest(String),
@@ -55,7 +60,9 @@ impl AppError {
             AppError::Http { status, .. } => *status,
 
             // 5xx
-            AppError::Bind(_) | AppError::Server(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Bind(_) | AppError::Server(_) | AppError::Metrics(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
         }
     }
 
@@ -65,6 +72,7 @@ impl AppError {
             AppError::Config(_) => "CONFIG_ERROR",
             AppError::Bind(_) => "BIND_ERROR",
             AppError::Server(_) => "SERVER_ERROR",
+            AppError::Metrics(_) => "METRICS_ERROR",
             AppError::BadRequest(_) => "BAD_REQUEST",
             AppError::NotFound => "NOT_FOUND",
             AppError::Http { code, .. } => code,
@@ -105,6 +113,68 @@ impl From<axum::extract::rejection::QueryRejection> for AppError {
     }
 }
 
+/// HTTP status and error code for structured vector-store failures: 503 when
+/// Qdrant is down or slow, 404 for a missing collection, 409 for a collection
+/// built with another embedding model, 400 for a git ref that doesn't resolve.
+/// `None` for other errors.
+pub fn rag_base_status(err: &RagBaseError) -> Option<(StatusCode, &'static str)> {
+    match err {
+        RagBaseError::Unreachable { .. } => {
+            Some((StatusCode::SERVICE_UNAVAILABLE, "QDRANT_UNREACHABLE"))
+        }
+        RagBaseError::Timeout { .. } => Some((StatusCode::SERVICE_UNAVAILABLE, "QDRANT_TIMEOUT")),
+        RagBaseError::CollectionMissing { .. } => {
+            Some((StatusCode::NOT_FOUND, "COLLECTION_NOT_FOUND"))
+        }
+        RagBaseError::DimensionMismatch { .. } => {
+            Some((StatusCode::CONFLICT, "COLLECTION_DIM_MISMATCH"))
+        }
+        RagBaseError::UnresolvedRef { .. } => Some((StatusCode::BAD_REQUEST, "UNKNOWN_GIT_REF")),
+        _ => None,
+    }
+}
+
+/// Convert `RagBaseError` to `AppError::Http` (see [`rag_base_status`]).
+impl From<RagBaseError> for AppError {
+    fn from(err: RagBaseError) -> Self {
+        let (status, code) =
+            rag_base_status(&err).unwrap_or((StatusCode::INTERNAL_SERVER_ERROR, "RAG_ERROR"));
+        AppError::Http {
+            status,
+            code,
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Same mapping as [`rag_base_status`] for the rag-store error type.
+pub fn rag_store_status(err: &RagError) -> Option<(StatusCode, &'static str)> {
+    match err {
+        RagError::Unreachable { .. } => {
+            Some((StatusCode::SERVICE_UNAVAILABLE, "QDRANT_UNREACHABLE"))
+        }
+        RagError::Timeout { .. } => Some((StatusCode::SERVICE_UNAVAILABLE, "QDRANT_TIMEOUT")),
+        RagError::CollectionMissing { .. } => Some((StatusCode::NOT_FOUND, "COLLECTION_NOT_FOUND")),
+        RagError::DimensionMismatch { .. } => {
+            Some((StatusCode::CONFLICT, "COLLECTION_DIM_MISMATCH"))
+        }
+        _ => None,
+    }
+}
+
+/// Convert `RagError` to `AppError::Http` (see [`rag_store_status`]).
+impl From<RagError> for AppError {
+    fn from(err: RagError) -> Self {
+        let (status, code) =
+            rag_store_status(&err).unwrap_or((StatusCode::INTERNAL_SERVER_ERROR, "RAG_ERROR"));
+        AppError::Http {
+            status,
+            code,
+            message: err.to_string(),
+        }
+    }
+}
+
 /// Convert `GitCloneError` to `AppError::Http` with precise HTTP status & code.
 /// Uses text heuristics to avoid importing `git2` types here.
 impl From<GitCloneError> for AppError {
@@ -120,6 +190,16 @@ impl From<GitCloneError> for AppError {
                 code: "JOIN_ERROR",
                 message: format!("Background task failed to complete: {e}"),
             },
+            GitCloneError::Unsupported(msg) => AppError::Http {
+                status: StatusCode::NOT_IMPLEMENTED,
+                code: "GIT_UNSUPPORTED",
+                message: msg,
+            },
+            GitCloneError::CaBundle(msg) => AppError::Http {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                code: "CA_BUNDLE_INVALID",
+                message: format!("Custom CA bundle (GIT_CA_CERT_PATH) is unusable: {msg}"),
+            },
             GitCloneError::Git(e) => {
                 let msg = e.to_string();
                 let lower = msg.to_lowercase();
@@ -9,25 +9,38 @@ use ai_llm_service::service_profiles::LlmServiceProfiles;
 use axum::{
     Router, middleware,
     response::IntoResponse,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 use colored::*;
 use tokio::signal; // for colorful console output
 
 use crate::{
-    core::app_state::{AppConfig, AppState},
+    core::{
+        app_state::{AppConfig, AppState},
+        metrics,
+    },
     error_handler::{AppError, AppResult},
-    middleware_layer::json_extractor::json_error_mapper,
+    middleware_layer::{admin_auth::require_admin_secret, json_extractor::json_error_mapper},
     routes::{
-        ask::ask_question_route::ask_question,
+        ask::{ask_batch_route::ask_question_batch, ask_question_route::ask_question},
+        metrics_route::metrics_route,
         prepare_qdrant_route::prepare_qdrant,
         project_indexer::project_indexer_route::project_indexer_route,
         rag_base::{
+            drop_vector_base_route::drop_vector_base_route,
             search_vector_base_route::search_vector_base_route,
             vector_base_index_route::vector_base_index_route,
         },
+        readiness_route::readiness_route,
+        review_report::{
+            review_artifacts_route::review_artifacts_route, review_sarif_route::review_sarif_route,
+            review_step4_report_route::review_step4_report_route,
+        },
         sync_git::sync_git_route::sync_git_route,
-        trigger_gitlab_mr::trigger_gitlab_mr_route::trigger_gitlab_mr,
+        trigger_gitlab_mr::{
+            prepare_and_review_route::prepare_and_review_route,
+            trigger_gitlab_mr_route::trigger_gitlab_mr,
+        },
     },
 };
 
@@ -45,10 +58,23 @@ pub async fn start(svc: Arc<LlmServiceProfiles>) -> AppResult<()> {
         "✅ AppConfig successfully loaded from environment".green()
     );
 
+    // Metrics recorder must exist before the first request emits anything
+    let metrics_handle = metrics::install().map_err(AppError::Metrics)?;
+    println!("{}", "✅ Prometheus metrics recorder installed".green());
+
     // Build shared state
-    let shared_state = Arc::new(AppState::new(config.clone(), svc));
+    let shared_state = Arc::new(AppState::new(config.clone(), svc, metrics_handle));
     println!("{}", "✅ Shared state initialized".green());
 
+    let metrics_get = if config.metrics_require_auth {
+        get(metrics_route).route_layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            require_admin_secret,
+        ))
+    } else {
+        get(metrics_route)
+    };
+
     // Routes
     let app = Router::new()
         .route("/sync_git", post(sync_git_route))
@@ -57,7 +83,39 @@ pub async fn start(svc: Arc<LlmServiceProfiles>) -> AppResult<()> {
         .route("/search_vector_base", post(search_vector_base_route))
         .route("/prepare_qdrant", get(prepare_qdrant))
         .route("/ask_question", post(ask_question))
+        .route("/ask_question/batch", post(ask_question_batch))
         .route("/trigger_git_mr", post(trigger_gitlab_mr))
+        .route("/trigger_git_mr/prepare", post(prepare_and_review_route))
+        .route("/metrics", metrics_get)
+        .route("/readiness", get(readiness_route))
+        .route(
+            "/reviews/{head_sha}/report",
+            get(review_step4_report_route).route_layer(middleware::from_fn_with_state(
+                shared_state.clone(),
+                require_admin_secret,
+            )),
+        )
+        .route(
+            "/reviews/{head_sha}/sarif",
+            get(review_sarif_route).route_layer(middleware::from_fn_with_state(
+                shared_state.clone(),
+                require_admin_secret,
+            )),
+        )
+        .route(
+            "/reviews/{head_sha}/artifacts",
+            get(review_artifacts_route).route_layer(middleware::from_fn_with_state(
+                shared_state.clone(),
+                require_admin_secret,
+            )),
+        )
+        .route(
+            "/vector_base/{project}",
+            delete(drop_vector_base_route).route_layer(middleware::from_fn_with_state(
+                shared_state.clone(),
+                require_admin_secret,
+            )),
+        )
         .fallback(handler_404)
         .layer(middleware::from_fn(json_error_mapper))
         .with_state(shared_state);
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::core::{app_state::AppState, http::response_envelope::ApiResponse};
+
+/// Header carrying the shared secret for admin/destructive routes.
+pub const ADMIN_SECRET_HEADER: &str = "X-Admin-Secret";
+
+/// Middleware that rejects requests without a valid `X-Admin-Secret` header.
+///
+/// The expected value is `AppConfig::trigger_secret` (same secret as trigger endpoints).
+pub async fn require_admin_secret(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let provided = req
+        .headers()
+        .get(ADMIN_SECRET_HEADER)
+        .and_then(|h| h.to_str().ok());
+
+    if provided != Some(state.config.trigger_secret.as_str()) {
+        return ApiResponse::<()>::error("UNAUTHORIZED", "invalid admin secret", Vec::new())
+            .into_response_with_status(StatusCode::UNAUTHORIZED);
+    }
+
+    next.run(req).await
+}
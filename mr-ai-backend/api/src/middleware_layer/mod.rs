@@ -1 +1,2 @@
+pub mod admin_auth;
 pub mod json_extractor;
@@ -0,0 +1,71 @@
+//! POST /ask_question/batch — answers several questions with RAG context.
+
+use std::sync::Arc;
+
+use axum::{Json, extract::State, http::StatusCode};
+
+use contextor::{AskOptions, ask_batch};
+
+use crate::{
+    core::app_state::AppState,
+    routes::ask::ask_request::{AskBatchItem, AskBatchRequest, AskBatchResponse, CtxItem},
+};
+
+/// Upper bound on questions per request.
+const MAX_BATCH_QUESTIONS: usize = 100;
+
+/// Handler: POST /ask_question/batch
+///
+/// A failing question yields an item with `error` set; the others still
+/// get answers.
+///
+/// # Example
+/// ```bash
+/// curl -X POST http://127.0.0.1:8080/ask_question/batch \
+///   -H 'content-type: application/json' \
+///   -d '{"questions":["Where is gamesIcon defined?","Who calls AuthBloc?"],"top_k":8}'
+/// ```
+pub async fn ask_question_batch(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<AskBatchRequest>,
+) -> Result<Json<AskBatchResponse>, (StatusCode, String)> {
+    if body.questions.len() > MAX_BATCH_QUESTIONS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("at most {MAX_BATCH_QUESTIONS} questions per batch"),
+        ));
+    }
+
+    let mut opts = AskOptions::default();
+    if let Some(k) = body.top_k {
+        opts.top_k = k;
+    }
+    if let Some(k) = body.context_k {
+        opts.context_k = k;
+    }
+
+    let answers = ask_batch(state.llm_profiles.clone(), &body.questions, opts).await;
+    let results = body
+        .questions
+        .into_iter()
+        .zip(answers)
+        .map(|(question, res)| match res {
+            Ok(qa) => AskBatchItem {
+                question,
+                answer: Some(qa.answer),
+                context: qa.context.into_iter().map(CtxItem::from).collect(),
+                dropped_context: qa.dropped_context,
+                error: None,
+            },
+            Err(e) => AskBatchItem {
+                question,
+                answer: None,
+                context: Vec::new(),
+                dropped_context: 0,
+                error: Some(e.to_string()),
+            },
+        })
+        .collect();
+
+    Ok(Json(AskBatchResponse { results }))
+}
@@ -33,25 +33,20 @@ pub async fn ask_question(
     }
 
     // Delegate to contextor (RAG + LLM)
-    let QaAnswer { answer, context } =
-        ask_with_opts(state.llm_profiles.clone(), &body.question, opts)
-            .await
-            .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+    let QaAnswer {
+        answer,
+        context,
+        dropped_context,
+    } = ask_with_opts(state.llm_profiles.clone(), &body.question, opts)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
 
     // Map to API response DTOs
-    let items = context
-        .into_iter()
-        .map(|u| CtxItem {
-            score: u.score,
-            source: u.source,
-            fqn: u.fqn,
-            kind: u.kind,
-            preview: u.text,
-        })
-        .collect();
+    let items = context.into_iter().map(CtxItem::from).collect();
 
     Ok(Json(AskResponse {
         answer,
         context: items,
+        dropped_context,
     }))
 }
@@ -1,3 +1,4 @@
+use contextor::UsedChunk;
 use serde::{Deserialize, Serialize};
 
 /// Request payload for /ask_question.
@@ -20,6 +21,8 @@ pub struct AskResponse {
     pub answer: String,
     /// Minimal transparency on what context was used.
     pub context: Vec<CtxItem>,
+    /// Retrieved chunks left out to fit the prompt token budget.
+    pub dropped_context: usize,
 }
 
 /// Small context snippet descriptor.
@@ -31,4 +34,52 @@ pub struct CtxItem {
     pub kind: Option<String>,
     /// Short preview of the chunk that was given to the model.
     pub preview: String,
+    /// `retrieved`, `mmr_selected` or `neighbor_expanded`.
+    pub origin: &'static str,
+}
+
+impl From<UsedChunk> for CtxItem {
+    fn from(u: UsedChunk) -> Self {
+        Self {
+            score: u.score,
+            source: u.source,
+            fqn: u.fqn,
+            kind: u.kind,
+            preview: u.text,
+            origin: u.origin.as_str(),
+        }
+    }
+}
+
+/// Request payload for /ask_question/batch.
+#[derive(Debug, Deserialize)]
+pub struct AskBatchRequest {
+    /// Questions to answer; results keep this order.
+    pub questions: Vec<String>,
+    /// Optional override applied to every question (see [`AskRequest::top_k`]).
+    #[serde(default)]
+    pub top_k: Option<u64>,
+    /// Optional override applied to every question (see [`AskRequest::context_k`]).
+    #[serde(default)]
+    pub context_k: Option<usize>,
+}
+
+/// Response payload for /ask_question/batch.
+#[derive(Debug, Serialize)]
+pub struct AskBatchResponse {
+    /// One entry per requested question, in request order.
+    pub results: Vec<AskBatchItem>,
+}
+
+/// Outcome of one batch question: either `answer` or `error` is set.
+#[derive(Debug, Serialize)]
+pub struct AskBatchItem {
+    pub question: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub context: Vec<CtxItem>,
+    pub dropped_context: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
@@ -1,2 +1,3 @@
+pub mod ask_batch_route;
 pub mod ask_question_route;
 pub mod ask_request;
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::core::app_state::AppState;
+
+/// `GET /metrics` in Prometheus text exposition format.
+///
+/// Rendering reads in-memory atomics only, so scraping is cheap. Guarded by
+/// `X-Admin-Secret` when `METRICS_REQUIRE_AUTH` is enabled.
+pub async fn metrics_route(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai_llm_service::config::{llm_model_config::LlmModelConfig, llm_provider::LlmProvider};
+    use ai_llm_service::service_profiles::LlmServiceProfiles;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+
+    use crate::core::app_state::AppConfig;
+
+    fn profile() -> LlmModelConfig {
+        LlmModelConfig {
+            provider: LlmProvider::Ollama,
+            model: "m".into(),
+            endpoint: "http://127.0.0.1:1".into(),
+            api_key: None,
+            max_tokens: None,
+            temperature: None,
+            top_p: None,
+            num_ctx: None,
+            stop: Vec::new(),
+            timeout_secs: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn serves_recorded_counters_as_prometheus_text() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        metrics::with_local_recorder(&recorder, || {
+            metrics::counter!("comments_posted_total").increment(3);
+        });
+        let config = AppConfig {
+            project_name: "app".into(),
+            git_api_base: "http://git.local".into(),
+            git_token: "t".into(),
+            trigger_secret: "s".into(),
+            metrics_require_auth: false,
+        };
+        let svc = LlmServiceProfiles::new(profile(), None, profile(), None).unwrap();
+        let state = AppState::new(Arc::new(config), Arc::new(svc), recorder.handle());
+
+        let res = metrics_route(State(Arc::new(state))).await.into_response();
+        assert_eq!(
+            res.headers()[header::CONTENT_TYPE],
+            "text/plain; version=0.0.4"
+        );
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let text = String::from_utf8(body.to_vec()).unwrap();
+        assert!(text.contains("comments_posted_total 3"), "{text}");
+    }
+}
@@ -1,6 +1,9 @@
 pub mod ask;
+pub mod metrics_route;
 pub mod prepare_qdrant_route;
 pub mod project_indexer;
 pub mod rag_base;
+pub mod readiness_route;
+pub mod review_report;
 pub mod sync_git;
 pub mod trigger_gitlab_mr;
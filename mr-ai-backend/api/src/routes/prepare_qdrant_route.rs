@@ -3,48 +3,32 @@ use std::sync::Arc;
 use axum::extract::State;
 use rag_store::{OllamaConfig, OllamaEmbedder, RagConfig, RagStore};
 
-use crate::core::app_state::AppState;
+use crate::{core::app_state::AppState, error_handler::AppResult};
 
-pub async fn prepare_qdrant(State(state): State<Arc<AppState>>) -> &'static str {
+/// Ingests the latest `rag_records.jsonl` into Qdrant; Qdrant failures map to
+/// their HTTP status (see `error_handler::rag_store_status`).
+pub async fn prepare_qdrant(State(state): State<Arc<AppState>>) -> AppResult<&'static str> {
     // 1) Configure store
-    let cfg = RagConfig::from_env();
-
-    let cfg = match cfg {
-        Ok(cfg) => cfg,
-        Err(ex) => {
-            println!("FAILED cfg: {}", ex);
-            return "Hello, World!";
-        }
-    };
-
-    let store = RagStore::new(cfg);
-
-    let store = match store {
-        Ok(store) => store,
-        Err(ex) => {
-            println!("FAILED store: {}", ex);
-            return "Hello, World!";
-        }
-    };
+    let cfg = RagConfig::from_env()?;
+    let windowing = cfg.embed_windowing;
+    let store = RagStore::new(cfg)?;
 
     let ollama = OllamaEmbedder::new(OllamaConfig {
         svc: state.llm_profiles.clone(),
         dim: std::env::var("EMBEDDING_DIM").unwrap().parse().unwrap(),
-    });
+    })
+    .with_windowing(windowing);
 
     // 2) Ingest only `rag_records.jsonl` from the latest timestamp directory
     //    under: code_data/project_x/graphs_data/<YYYYMMDD_HHMMSS>/rag_records.jsonl
-    let count = store.ingest_latest_all_embedded("code_data", &ollama).await;
-
-    let count = match count {
-        Ok(count) => count,
-        Err(ex) => {
-            println!("FAILED count: {}", ex);
-            return "Hello, World!";
-        }
-    };
+    let count = store
+        .ingest_latest_all_embedded(services::data_root::data_root(), &ollama)
+        .await?;
 
-    println!("Ingested points: {count}");
+    println!(
+        "Ingested points: {} (near-duplicates suppressed: {})",
+        count.upserted, count.near_duplicates_suppressed
+    );
 
-    "Hello, World!"
+    Ok("Hello, World!")
 }
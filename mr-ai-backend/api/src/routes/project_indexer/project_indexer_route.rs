@@ -22,10 +22,16 @@ pub async fn project_indexer_route(
     }
 
     // Writes: out/my_flutter_app/micro_chunks.jsonl
-    let result = index_project_to_jsonl(&state.config.project_name, true);
+    let result = index_project_to_jsonl(&state.config.project_name, true, None, None);
 
     match result {
-        Ok(_) => {}
+        Ok(report) => debug!(
+            chunks = report.total_chunks,
+            skipped_oversized = report.skipped_oversized,
+            skipped_undecodable = report.skipped_undecodable.len(),
+            by_language = ?report.by_language,
+            "project indexed"
+        ),
         Err(ex) => println!("Failed {:?}", ex),
     }
 
@@ -0,0 +1,8 @@
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct DropVectorBaseResponse {
+    pub project: String,
+    /// True if the collection existed and was removed.
+    pub existed: bool,
+}
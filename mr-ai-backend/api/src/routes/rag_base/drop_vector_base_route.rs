@@ -0,0 +1,30 @@
+use axum::{extract::Path, http::StatusCode, response::Response};
+use rag_base::drop_index;
+use tracing::{error, info};
+
+use crate::{
+    core::http::response_envelope::ApiResponse, error_handler::rag_base_status,
+    routes::rag_base::drop_vector_base_response::DropVectorBaseResponse,
+};
+
+/// DELETE /vector_base/{project}
+///
+/// Drops the project's Qdrant collection (and payload indexes).
+/// Idempotent: deleting a missing collection still succeeds with `existed=false`.
+pub async fn drop_vector_base_route(Path(project): Path<String>) -> Response {
+    match drop_index(&project).await {
+        Ok(existed) => {
+            info!(%project, existed, "drop_vector_base_route: done");
+            ApiResponse::success(DropVectorBaseResponse { project, existed })
+                .into_response_with_status(StatusCode::OK)
+        }
+        Err(err) => {
+            error!(%project, error = %err, "drop_vector_base_route: failed");
+            let (status, code) = rag_base_status(&err)
+                .unwrap_or((StatusCode::INTERNAL_SERVER_ERROR, "RAG_DROP_FAILED"));
+            let resp: ApiResponse<()> =
+                ApiResponse::error(code, format!("Drop failed: {err}"), Vec::new());
+            resp.into_response_with_status(status)
+        }
+    }
+}
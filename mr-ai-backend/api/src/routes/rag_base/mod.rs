@@ -1,6 +1,8 @@
+mod drop_vector_base_response;
 mod search_vector_base_reqest;
 mod search_vector_base_response;
 mod vector_base_index_response;
 
+pub mod drop_vector_base_route;
 pub mod search_vector_base_route;
 pub mod vector_base_index_route;
@@ -4,4 +4,16 @@ use serde::{Deserialize, Serialize};
 pub struct SearchVectorBaseRequest {
     pub query: String,
     pub k: Option<usize>,
+    /// Include language/kind facet counts in the response.
+    #[serde(default)]
+    pub with_facets: bool,
+    /// Include per-result vector/lexical/fused scores in the response.
+    #[serde(default)]
+    pub with_score_breakdown: bool,
+    /// Include query embedding stats, raw distances and lexical terms.
+    #[serde(default)]
+    pub explain: bool,
+    /// Only return definitions (`is_definition`), not usage sites.
+    #[serde(default)]
+    pub definitions_only: bool,
 }
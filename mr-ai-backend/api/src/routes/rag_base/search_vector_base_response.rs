@@ -1,4 +1,4 @@
-use rag_base::structs::search_result::CodeSearchResult;
+use rag_base::structs::search_result::{CodeSearchResult, ExplainInfo, SearchFacets};
 use serde::Serialize;
 
 #[derive(Debug, Serialize)]
@@ -6,4 +6,8 @@ pub struct SearchVectorBaseResponse {
     pub message: String,
     pub query: String,
     pub results: Vec<CodeSearchResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<SearchFacets>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<ExplainInfo>,
 }
@@ -3,20 +3,27 @@ use std::sync::Arc;
 use axum::{
     Json,
     extract::State,
-    http::{HeaderMap, StatusCode},
-    response::Response,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use rag_base::{
+    errors::rag_base_error::RagBaseError,
+    search_code,
+    structs::search_result::{SearchParams, as_plain_text},
 };
-use rag_base::{errors::rag_base_error::RagBaseError, search_code};
 use tracing::{debug, error};
 
 use crate::{
     core::{app_state::AppState, http::response_envelope::ApiResponse},
+    error_handler::rag_base_status,
     routes::rag_base::{
         search_vector_base_reqest::SearchVectorBaseRequest,
         search_vector_base_response::SearchVectorBaseResponse,
     },
 };
 
+/// `POST` code search. Responds with the JSON envelope by default, or with
+/// the stitched blocks as raw source when the client sends `Accept: text/plain`.
 pub async fn search_vector_base_route(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
@@ -33,21 +40,38 @@ pub async fn search_vector_base_route(
         "search_vector_base_route: start"
     );
 
+    let params = SearchParams {
+        k: p.k,
+        with_facets: p.with_facets,
+        with_score_breakdown: p.with_score_breakdown,
+        explain: p.explain,
+        definitions_only: p.definitions_only,
+    };
     let result: Result<_, RagBaseError> =
-        search_code(&state.config.project_name, &p.query, p.k).await;
+        search_code(&state.config.project_name, &p.query, &params).await;
 
     match result {
-        Ok(results) => {
+        Ok(found) => {
             debug!(
                 request_id = %request_id,
-                hits = results.len(),
+                hits = found.results.len(),
                 "search_vector_base_route: success"
             );
 
+            if accepts_plain_text(&headers) {
+                return (
+                    [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+                    as_plain_text(&found.results),
+                )
+                    .into_response();
+            }
+
             let body = SearchVectorBaseResponse {
                 message: "Search completed successfully".to_string(),
                 query: p.query,
-                results,
+                results: found.results,
+                facets: found.facets,
+                explain: found.explain,
             };
 
             ApiResponse::success(body).into_response_with_status(StatusCode::OK)
@@ -60,10 +84,24 @@ pub async fn search_vector_base_route(
             );
 
             let msg = format!("Search failed: {err}");
+            let (status, code) = rag_base_status(&err)
+                .unwrap_or((StatusCode::INTERNAL_SERVER_ERROR, "RAG_SEARCH_FAILED"));
 
-            let resp: ApiResponse<()> = ApiResponse::error("RAG_SEARCH_FAILED", msg, Vec::new());
+            let resp: ApiResponse<()> = ApiResponse::error(code, msg, Vec::new());
 
-            resp.into_response_with_status(StatusCode::INTERNAL_SERVER_ERROR)
+            resp.into_response_with_status(status)
         }
     }
 }
+
+/// True if `Accept` asks for `text/plain` and not for JSON (JSON stays the default).
+fn accepts_plain_text(headers: &HeaderMap) -> bool {
+    let Some(accept) = headers.get(header::ACCEPT).and_then(|h| h.to_str().ok()) else {
+        return false;
+    };
+    let types: Vec<&str> = accept
+        .split(',')
+        .map(|t| t.split(';').next().unwrap_or("").trim())
+        .collect();
+    types.contains(&"text/plain") && !types.contains(&"application/json")
+}
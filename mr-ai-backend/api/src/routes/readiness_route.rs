@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, response::Response};
+
+use crate::core::{
+    app_state::AppState,
+    http::response_envelope::{ApiErrorDetail, ApiResponse},
+};
+
+/// `GET /readiness`: health of the LLM profiles.
+///
+/// `200` with every profile's status when all are healthy, otherwise `503`
+/// listing the unhealthy ones. Reuses the last startup/readiness snapshot for a
+/// few seconds and re-probes after that, so recovered models turn ready again.
+pub async fn readiness_route(State(state): State<Arc<AppState>>) -> Response {
+    let statuses = state.llm_profiles.readiness().await;
+    let down: Vec<ApiErrorDetail> = statuses
+        .iter()
+        .filter(|s| !s.ok)
+        .map(|s| ApiErrorDetail {
+            path: Some(s.model.clone().unwrap_or_else(|| s.endpoint.clone())),
+            hint: Some(s.message.clone()),
+        })
+        .collect();
+
+    if statuses.is_empty() || !down.is_empty() {
+        let resp: ApiResponse<()> =
+            ApiResponse::error("LLM_UNAVAILABLE", "LLM profiles are not ready", down);
+        return resp.into_response_with_status(StatusCode::SERVICE_UNAVAILABLE);
+    }
+    ApiResponse::success(statuses).into_response_with_status(StatusCode::OK)
+}
@@ -0,0 +1,4 @@
+pub mod review_artifacts_response;
+pub mod review_artifacts_route;
+pub mod review_sarif_route;
+pub mod review_step4_report_route;
@@ -0,0 +1,10 @@
+use mr_reviewer::review::artifacts::ReviewArtifact;
+use serde::Serialize;
+
+/// Debug dumps of one reviewed head commit (`GET /reviews/{head_sha}/artifacts`).
+#[derive(Serialize)]
+pub struct ReviewArtifactsResponse {
+    pub head_sha: String,
+    /// Pre-query, RAG and prompt dumps, relative to `mr_tmp/<head12>/`.
+    pub artifacts: Vec<ReviewArtifact>,
+}
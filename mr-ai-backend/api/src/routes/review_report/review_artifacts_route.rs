@@ -0,0 +1,54 @@
+use axum::{extract::Path, http::StatusCode, response::Response};
+use mr_reviewer::review::artifacts::{is_head_sha, list_artifacts};
+use tracing::{debug, warn};
+
+use crate::{
+    core::http::response_envelope::ApiResponse,
+    routes::review_report::review_artifacts_response::ReviewArtifactsResponse,
+};
+
+/// GET /reviews/{head_sha}/artifacts
+///
+/// Lists the pre-query, RAG and prompt dumps a review of `head_sha` left under
+/// `code_data/mr_tmp`, so operators can debug it without shell access.
+/// Requires `X-Admin-Secret`. 404 if the commit was never reviewed.
+pub async fn review_artifacts_route(Path(head_sha): Path<String>) -> Response {
+    if !is_head_sha(&head_sha) {
+        let resp: ApiResponse<()> = ApiResponse::error(
+            "INVALID_HEAD_SHA",
+            "head_sha must be a hex commit id",
+            Vec::new(),
+        );
+        return resp.into_response_with_status(StatusCode::BAD_REQUEST);
+    }
+
+    let sha = head_sha.clone();
+    let listed = match tokio::task::spawn_blocking(move || list_artifacts(&sha)).await {
+        Ok(listed) => listed,
+        Err(err) => {
+            warn!(%head_sha, error = %err, "review_artifacts_route: listing task failed");
+            let resp: ApiResponse<()> =
+                ApiResponse::error("ARTIFACTS_UNAVAILABLE", err.to_string(), Vec::new());
+            return resp.into_response_with_status(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    match listed {
+        Some(artifacts) => {
+            debug!(%head_sha, count = artifacts.len(), "review_artifacts_route: served");
+            ApiResponse::success(ReviewArtifactsResponse {
+                head_sha,
+                artifacts,
+            })
+            .into_response_with_status(StatusCode::OK)
+        }
+        None => {
+            let resp: ApiResponse<()> = ApiResponse::error(
+                "REVIEW_NOT_FOUND",
+                format!("No review artifacts for {head_sha}"),
+                Vec::new(),
+            );
+            resp.into_response_with_status(StatusCode::NOT_FOUND)
+        }
+    }
+}
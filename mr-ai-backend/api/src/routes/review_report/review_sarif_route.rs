@@ -0,0 +1,43 @@
+use axum::{
+    extract::Path,
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use mr_reviewer::review::{artifacts::is_head_sha, sarif_path};
+use tracing::{debug, warn};
+
+use crate::core::http::response_envelope::ApiResponse;
+
+/// GET /reviews/{head_sha}/sarif
+///
+/// Returns the SARIF 2.1.0 export of the step-4 findings for a reviewed head
+/// commit (`application/sarif+json`), for upload to code-scanning dashboards.
+/// Requires `X-Admin-Secret`. 404 if no review of that commit has been
+/// reported yet.
+pub async fn review_sarif_route(Path(head_sha): Path<String>) -> Response {
+    if !is_head_sha(&head_sha) {
+        let resp: ApiResponse<()> = ApiResponse::error(
+            "INVALID_HEAD_SHA",
+            "head_sha must be a hex commit id",
+            Vec::new(),
+        );
+        return resp.into_response_with_status(StatusCode::BAD_REQUEST);
+    }
+
+    let path = sarif_path(&head_sha);
+    match tokio::fs::read(&path).await {
+        Ok(body) => {
+            debug!(%head_sha, path = %path.display(), "review_sarif_route: served");
+            ([(header::CONTENT_TYPE, "application/sarif+json")], body).into_response()
+        }
+        Err(err) => {
+            warn!(%head_sha, error = %err, "review_sarif_route: no SARIF report");
+            let resp: ApiResponse<()> = ApiResponse::error(
+                "SARIF_NOT_FOUND",
+                format!("No SARIF report for {head_sha}"),
+                Vec::new(),
+            );
+            resp.into_response_with_status(StatusCode::NOT_FOUND)
+        }
+    }
+}
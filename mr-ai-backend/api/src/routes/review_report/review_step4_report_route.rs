@@ -0,0 +1,46 @@
+use axum::{extract::Path, http::StatusCode, response::Response};
+use mr_reviewer::review::artifacts::{is_head_sha, read_report};
+use tracing::{debug, warn};
+
+use crate::core::http::response_envelope::ApiResponse;
+
+/// GET /reviews/{head_sha}/report
+///
+/// Returns the parsed `step4_report.json` of a reviewed head commit.
+/// Requires `X-Admin-Secret`. 404 if no review of that commit was reported.
+pub async fn review_step4_report_route(Path(head_sha): Path<String>) -> Response {
+    if !is_head_sha(&head_sha) {
+        let resp: ApiResponse<()> = ApiResponse::error(
+            "INVALID_HEAD_SHA",
+            "head_sha must be a hex commit id",
+            Vec::new(),
+        );
+        return resp.into_response_with_status(StatusCode::BAD_REQUEST);
+    }
+
+    let sha = head_sha.clone();
+    let report = tokio::task::spawn_blocking(move || read_report(&sha))
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+
+    match report {
+        Ok(Some(report)) => {
+            debug!(%head_sha, "review_step4_report_route: served");
+            ApiResponse::success(report).into_response_with_status(StatusCode::OK)
+        }
+        Ok(None) => {
+            let resp: ApiResponse<()> = ApiResponse::error(
+                "REPORT_NOT_FOUND",
+                format!("No step-4 report for {head_sha}"),
+                Vec::new(),
+            );
+            resp.into_response_with_status(StatusCode::NOT_FOUND)
+        }
+        Err(err) => {
+            warn!(%head_sha, error = %err, "review_step4_report_route: unreadable report");
+            let resp: ApiResponse<()> =
+                ApiResponse::error("REPORT_UNREADABLE", err.to_string(), Vec::new());
+            resp.into_response_with_status(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
@@ -1,3 +1,3 @@
 mod sync_git_request;
-mod sync_git_response;
+pub mod sync_git_response;
 pub mod sync_git_route;
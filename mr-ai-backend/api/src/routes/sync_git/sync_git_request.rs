@@ -3,4 +3,8 @@ use serde::Deserialize;
 #[derive(Deserialize)]
 pub struct GitProjectsRequest {
     pub urls: Vec<String>,
+    /// `false` (default): update existing checkouts with fetch + reset.
+    /// `true`: remove and reclone every repository.
+    #[serde(default)]
+    pub force: bool,
 }
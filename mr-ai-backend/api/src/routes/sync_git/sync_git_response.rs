@@ -1,6 +1,31 @@
+use project_code_store::RepoSyncStatus;
 use serde::Serialize;
 
 #[derive(Serialize)]
 pub struct GitProjectsResponse {
     pub message: String,
+    pub repos: Vec<RepoSyncEntry>,
+}
+
+/// Outcome for one requested repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoSyncEntry {
+    pub url: String,
+    pub repo: String,
+    /// `updated`, `cloned` or `recloned` (what was attempted, see `error`).
+    pub status: &'static str,
+    /// Why the sync failed; absent on success.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<RepoSyncStatus> for RepoSyncEntry {
+    fn from(s: RepoSyncStatus) -> Self {
+        Self {
+            url: s.url,
+            repo: s.repo,
+            status: s.action.as_str(),
+            error: s.error,
+        }
+    }
 }
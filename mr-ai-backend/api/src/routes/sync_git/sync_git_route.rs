@@ -15,7 +15,8 @@ use crate::{
     },
     error_handler::AppError,
     routes::sync_git::{
-        sync_git_request::GitProjectsRequest, sync_git_response::GitProjectsResponse,
+        sync_git_request::GitProjectsRequest,
+        sync_git_response::{GitProjectsResponse, RepoSyncEntry},
     },
 };
 
@@ -72,16 +73,33 @@ pub async fn sync_git_route(
     }
 
     let requested = urls.len();
-    info!(count = requested, "starting clone");
+    info!(count = requested, force = r.force, "starting sync");
 
     // You can make this configurable later.
     let max_concurrency = 2usize;
 
-    match project_code_store::clone_list(urls, max_concurrency, &state.config.project_name).await {
-        Ok(_) => ApiResponse::success(GitProjectsResponse {
-            message: format!("Cloned {} repository(ies)", requested),
-        })
-        .into_response_with_status(StatusCode::OK),
+    match project_code_store::sync_list(urls, max_concurrency, &state.config.project_name, r.force)
+        .await
+    {
+        Ok(statuses) => {
+            let failed = statuses.iter().filter(|s| s.error.is_some()).count();
+            let updated = statuses
+                .iter()
+                .filter(|s| s.error.is_none())
+                .filter(|s| s.action == project_code_store::SyncAction::Updated)
+                .count();
+            ApiResponse::success(GitProjectsResponse {
+                message: format!(
+                    "Synced {} repository(ies): {} updated, {} cloned, {} failed",
+                    requested,
+                    updated,
+                    requested - updated - failed,
+                    failed
+                ),
+                repos: statuses.into_iter().map(RepoSyncEntry::from).collect(),
+            })
+            .into_response_with_status(StatusCode::OK)
+        }
         Err(err) => AppError::into_response(err.into()),
     }
 }
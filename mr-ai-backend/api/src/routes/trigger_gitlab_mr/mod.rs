@@ -1,2 +1,4 @@
+pub mod prepare_and_review_response;
+pub mod prepare_and_review_route;
 pub mod trigger_gitlab_mr_request;
 pub mod trigger_gitlab_mr_route;
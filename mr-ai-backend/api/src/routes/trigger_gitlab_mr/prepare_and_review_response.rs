@@ -0,0 +1,15 @@
+use mr_reviewer::review::Step4Summary;
+use serde::Serialize;
+
+use crate::core::prepare_review::FreshnessReport;
+
+/// Index stats and review results of one `POST /trigger_git_mr/prepare`.
+#[derive(Serialize)]
+pub struct PrepareAndReviewResponse {
+    /// `None` when the freshness step was skipped.
+    pub freshness: Option<FreshnessReport>,
+    /// Step-4 summary of a completed review.
+    pub review: Option<Step4Summary>,
+    /// Why the review was skipped (e.g. draft MR), if it was.
+    pub skipped: Option<String>,
+}
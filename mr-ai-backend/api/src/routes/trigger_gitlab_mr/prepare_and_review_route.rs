@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use mr_reviewer::{RunReview, git_providers::ChangeRequestId};
+use tracing::info;
+
+use crate::{
+    core::{
+        app_state::AppState,
+        http::response_envelope::ApiResponse,
+        prepare_review::{PrepareOptions, PreparedReview, prepare_and_review},
+    },
+    routes::trigger_gitlab_mr::{
+        prepare_and_review_response::PrepareAndReviewResponse,
+        trigger_gitlab_mr_request::PrepareAndReviewRequest,
+    },
+};
+
+/// POST /trigger_git_mr/prepare
+///
+/// Sync and (incrementally) reindex the project, then review the MR, so RAG
+/// context matches the latest code. `skip_freshness` reviews against the
+/// current index. Responds with index stats and the step-4 summary.
+pub async fn prepare_and_review_route(
+    State(state): State<Arc<AppState>>,
+    Json(p): Json<PrepareAndReviewRequest>,
+) -> Response {
+    if p.mr.secret != state.config.trigger_secret {
+        let resp: ApiResponse<()> =
+            ApiResponse::error("UNAUTHORIZED", "invalid secret", Vec::new());
+        return resp.into_response_with_status(StatusCode::UNAUTHORIZED);
+    }
+
+    let id = ChangeRequestId {
+        project: p.mr.project_id,
+        iid: p.mr.mr_iid,
+    };
+    let mut opts = PrepareOptions {
+        skip_freshness: p.skip_freshness,
+        repo_urls: p.repo_urls,
+        ..PrepareOptions::default()
+    };
+    if let Some(base_ref) = p.base_ref.filter(|r| !r.trim().is_empty()) {
+        opts.base_ref = base_ref;
+    }
+
+    match prepare_and_review(&state, &state.config.project_name, id, opts).await {
+        Ok(PreparedReview { freshness, review }) => {
+            let (review, skipped) = match review {
+                RunReview::Completed { report, .. } => (Some(*report), None),
+                RunReview::Skipped { reason } => (None, Some(reason.to_string())),
+            };
+            info!(
+                drafts = review.as_ref().map(|r| r.drafts_total),
+                skipped = skipped.as_deref(),
+                fresh = freshness.is_some(),
+                "prepare_and_review_route: done"
+            );
+            ApiResponse::success(PrepareAndReviewResponse {
+                freshness,
+                review,
+                skipped,
+            })
+            .into_response_with_status(StatusCode::OK)
+        }
+        Err(err) => err.into_response(),
+    }
+}
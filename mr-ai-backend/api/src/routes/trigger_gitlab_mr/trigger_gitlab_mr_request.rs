@@ -10,3 +10,19 @@ pub struct TriggerGitLabPayloadRequest {
     /// Shared secret to authorize the request.
     pub secret: String,
 }
+
+/// Payload of `POST /trigger_git_mr/prepare`: the MR plus freshness switches.
+#[derive(Debug, Deserialize)]
+pub struct PrepareAndReviewRequest {
+    #[serde(flatten)]
+    pub mr: TriggerGitLabPayloadRequest,
+    /// Repositories to sync before indexing; empty keeps checkouts as they are.
+    #[serde(default)]
+    pub repo_urls: Vec<String>,
+    /// Review against the current index without syncing or reindexing.
+    #[serde(default)]
+    pub skip_freshness: bool,
+    /// Base of the incremental reindex (default `HEAD@{1}`).
+    #[serde(default)]
+    pub base_ref: Option<String>,
+}
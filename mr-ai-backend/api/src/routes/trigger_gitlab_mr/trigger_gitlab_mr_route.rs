@@ -2,13 +2,12 @@ use std::sync::Arc;
 
 use axum::{Json, extract::State, http::StatusCode};
 use mr_reviewer::{
-    git_providers::{ChangeRequestId, ProviderConfig, ProviderKind},
-    publish::PublishConfig,
-    run_review,
+    ReviewOptions, RunReview, git_providers::ChangeRequestId, publish::PublishConfig, run_review,
 };
+use tracing::info;
 
 use crate::{
-    core::app_state::AppState,
+    core::{app_state::AppState, prepare_review::gitlab_provider_config},
     routes::trigger_gitlab_mr::trigger_gitlab_mr_request::TriggerGitLabPayloadRequest,
 };
 
@@ -24,11 +23,7 @@ pub async fn trigger_gitlab_mr(
         return Err((StatusCode::UNAUTHORIZED, "invalid secret".into()));
     }
 
-    let cfg = ProviderConfig {
-        kind: ProviderKind::GitLab,
-        base_api: state.config.git_api_base.clone(),
-        token: state.config.git_token.clone(),
-    };
+    let cfg = gitlab_provider_config(&state.config);
 
     let pub_cfg = PublishConfig::default();
     let id = ChangeRequestId {
@@ -36,11 +31,26 @@ pub async fn trigger_gitlab_mr(
         iid: p.mr_iid,
     };
 
-    match run_review(cfg, id, state.llm_profiles.clone(), pub_cfg).await {
-        Ok(_bundle) => {
+    let opts = ReviewOptions::default();
+
+    match run_review(cfg, id, state.llm_profiles.clone(), pub_cfg, opts).await {
+        Ok(RunReview::Completed {
+            report,
+            touched_symbols,
+            ..
+        }) => {
             // TODO: pass bundle to your queue/store; or keep it in cache only.
+            info!(
+                drafts = report.drafts_total,
+                touched_symbols = touched_symbols.len(),
+                parse_failures = report.parse_failures.len(),
+                skipped_files = report.skipped_files.len(),
+                report = ?report.report_path,
+                "trigger_gitlab_mr: review completed"
+            );
             Ok(StatusCode::ACCEPTED)
         }
+        Ok(RunReview::Skipped { .. }) => Ok(StatusCode::OK),
         Err(e) => Err((StatusCode::BAD_GATEWAY, format!("provider error: {e}"))),
     }
 }
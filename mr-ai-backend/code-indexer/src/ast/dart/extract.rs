@@ -645,6 +645,7 @@ fn emit_symbol_chunk(
         hints: Some(hints),
         lsp: Some(lsp_enr),
         extras,
+        low_fidelity: false,
     });
 }
 
@@ -708,6 +709,7 @@ fn emit_varlist_chunks(
             hints: Some(hints),
             lsp: None,
             extras: None,
+            low_fidelity: false,
         });
     }
 }
@@ -748,6 +750,7 @@ fn emit_barrel_file_chunk(out: &mut Vec<CodeChunk>, code: &str, file: &str, impo
         hints: Some(hints),
         lsp: None,
         extras: None,
+        low_fidelity: false,
     });
 }
 
@@ -1,7 +1,6 @@
 //! Public Dart AST provider: parsing + extraction + optional AST dump.
 //!
 //! Responsibilities:
-//! - Read file contents;
 //! - Configure a fresh Tree-sitter `Parser` for Dart;
 //! - Parse and delegate to the high-level extractor;
 //! - Optionally print AST dumps depending on `AstDumpMode`;
@@ -17,12 +16,13 @@ use super::ast_dump::{
 };
 use super::extract::extract_chunks;
 use super::lang::language as dart_language;
-use super::util::{compute_neighbors_in_file, looks_generated};
+use super::util::looks_generated;
 use crate::ast::dart::ast_dump::maybe_dump_on_empty_with_tree;
 use crate::ast::interface::AstProvider;
+use crate::ast::neighbors::compute_neighbors_in_file;
 use crate::errors::{Error, Result};
 use crate::types::{CodeChunk, clamp_snippet};
-use std::{fs, path::Path};
+use std::path::Path;
 use tree_sitter::{Parser, Tree};
 
 /// Dart AST provider (parse + extract).
@@ -53,44 +53,37 @@ impl AstProvider for DartAst {
     ///   * `Full`  – dump after each successful parse;
     ///   * `Error` – dump only when parsing/extraction fails.
     /// - Every emitted chunk gets a bounded `snippet` for retrieval/embedding.
-    fn parse_file(path: &Path) -> Result<Vec<CodeChunk>> {
-        // 1) Read file contents
-        let code = fs::read_to_string(path).map_err(|e| {
-            // No tree available here; print minimal diagnostic in Error mode.
-            maybe_dump_on_parse_error(AST_DUMP_MODE, path, &e, None);
-            e
-        })?;
-
-        // 2) Parse to a tree
-        let tree = match Self::parse(&code) {
+    fn parse_source(path: &Path, code: &str) -> Result<Vec<CodeChunk>> {
+        // 1) Parse to a tree
+        let tree = match Self::parse(code) {
             Ok(t) => t,
             Err(e) => {
                 // Parse failed (no tree) → short diagnostic (+ preview) when mode=Error
-                maybe_dump_on_parse_error(AST_DUMP_MODE, path, &e, Some(&code));
+                maybe_dump_on_parse_error(AST_DUMP_MODE, path, &e, Some(code));
                 return Err(e);
             }
         };
 
-        // 3) Optional full dump for *every* successfully parsed file
-        maybe_dump_on_full(AST_DUMP_MODE, &tree, &code, path);
+        // 2) Optional full dump for *every* successfully parsed file
+        maybe_dump_on_full(AST_DUMP_MODE, &tree, code, path);
 
-        // 4) Extract symbols/chunks
+        // 3) Extract symbols/chunks
         let file = path.to_string_lossy().to_string();
         let is_generated = looks_generated(&file);
-        let mut chunks = match extract_chunks(&tree, &code, &file, is_generated) {
+        let mut chunks = match extract_chunks(&tree, code, &file, is_generated) {
             Ok(cs) => cs,
             Err(e) => {
                 // We *do* have a tree; in Error mode print a full AST to help debugging.
-                maybe_dump_on_error_with_tree(AST_DUMP_MODE, &tree, &code, path, &e);
+                maybe_dump_on_error_with_tree(AST_DUMP_MODE, &tree, code, path, &e);
                 return Err(e);
             }
         };
 
         if chunks.is_empty() {
-            maybe_dump_on_empty_with_tree(AST_DUMP_MODE, &tree, &code, path);
+            maybe_dump_on_empty_with_tree(AST_DUMP_MODE, &tree, code, path);
         }
 
-        // 5) Attach bounded snippets (idempotent)
+        // 4) Attach bounded snippets (idempotent)
         for c in &mut chunks {
             if c.snippet.is_none() {
                 let s = &code[c.span.start_byte..c.span.end_byte];
@@ -99,7 +92,7 @@ impl AstProvider for DartAst {
             }
         }
 
-        // 6) Compute intra-file neighbor links (prev/next + parent/children)
+        // 5) Compute intra-file neighbor links (sibling prev/next + parent/children)
         compute_neighbors_in_file(&mut chunks);
 
         Ok(chunks)
@@ -5,7 +5,7 @@
 
 use crate::types::{Anchor, ChunkFeatures, CodeChunk, GraphEdges, RetrievalHints, Span};
 use sha2::{Digest, Sha256};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashSet};
 use tree_sitter::Node;
 
 /// Build a `Span` from a node.
@@ -29,15 +29,7 @@ pub fn sha_hex(bytes: &[u8]) -> String {
     format!("{:x}", h.finalize())
 }
 
-/// Stable chunk id based on file + symbol path + byte span.
-pub fn make_id(file: &str, symbol_path: &str, sp: &Span) -> String {
-    let mut h = Sha256::new();
-    h.update(file.as_bytes());
-    h.update(symbol_path.as_bytes());
-    h.update(sp.start_byte.to_le_bytes());
-    h.update(sp.end_byte.to_le_bytes());
-    format!("{:x}", h.finalize())
-}
+pub use crate::util::chunk_id::make_id;
 
 /// Read raw text from a node (lossy to UTF-8).
 pub fn read_text(code: &str, n: Node) -> String {
@@ -75,55 +67,6 @@ pub fn first_line(s: &str, max_chars: usize) -> String {
     out.trim().to_string()
 }
 
-/// Compute sequential and hierarchical neighbors within the same file.
-///
-/// - Assign `prev_id`/`next_id` in lexical (byte) order.
-/// - Derive `parent_id`/`children_ids` from `symbol_path` hierarchy.
-pub fn compute_neighbors_in_file(chunks: &mut [CodeChunk]) {
-    chunks.sort_by_key(|c| c.span.start_byte);
-
-    for i in 0..chunks.len() {
-        let prev = if i > 0 {
-            Some(chunks[i - 1].id.clone())
-        } else {
-            None
-        };
-        let next = if i + 1 < chunks.len() {
-            Some(chunks[i + 1].id.clone())
-        } else {
-            None
-        };
-        let entry = chunks[i].neighbors.get_or_insert_default();
-        entry.prev_id = prev;
-        entry.next_id = next;
-    }
-
-    let mut by_path = HashMap::<String, usize>::new();
-    for (i, c) in chunks.iter().enumerate() {
-        by_path.insert(c.symbol_path.clone(), i);
-    }
-    for i in 0..chunks.len() {
-        if let Some(pp) = parent_path_of(&chunks[i].symbol_path) {
-            if let Some(&pi) = by_path.get(&pp) {
-                let pid = chunks[pi].id.clone();
-                let entry = chunks[i].neighbors.get_or_insert_default();
-                entry.parent_id = Some(pid.clone());
-                let pe = chunks[pi].neighbors.get_or_insert_default();
-                pe.children_ids.push(chunks[i].id.clone());
-            }
-        }
-    }
-}
-
-/// Return parent symbol path if exists (`file::A::B` -> `file::A`).
-pub fn parent_path_of(sym_path: &str) -> Option<String> {
-    let parts: Vec<&str> = sym_path.split("::").collect();
-    if parts.len() <= 2 {
-        return None;
-    }
-    Some(parts[..parts.len() - 1].join("::"))
-}
-
 /// Heuristic to decide if a path looks like a generated Dart file.
 pub fn looks_generated(path: &str) -> bool {
     path.ends_with(".g.dart") || path.ends_with(".freezed.dart") || path.ends_with(".gr.dart")
@@ -17,7 +17,7 @@ use crate::types::{
 };
 use regex::Regex;
 use sha2::{Digest, Sha256};
-use std::{fs, path::Path};
+use std::path::Path;
 
 /// Generic provider that emits a single text chunk per file (best effort).
 pub struct GenericTextAst;
@@ -29,7 +29,7 @@ impl GenericTextAst {
     /// - The enum is intentionally broad (app, web, systems, config/data).
     /// - Unknown/unsupported extensions fall back to `Other`.
     #[inline]
-    fn guess_language(file: &str) -> LanguageKind {
+    pub(crate) fn guess_language(file: &str) -> LanguageKind {
         let f = file.to_ascii_lowercase();
 
         // App/backend
@@ -121,13 +121,8 @@ impl GenericTextAst {
 
     /// Compute a stable chunk id from (file, symbol_path, span).
     #[inline]
-    fn make_id(file: &str, symbol_path: &str, sp: &Span) -> String {
-        let mut h = Sha256::new();
-        h.update(file.as_bytes());
-        h.update(symbol_path.as_bytes());
-        h.update(sp.start_byte.to_le_bytes());
-        h.update(sp.end_byte.to_le_bytes());
-        format!("{:x}", h.finalize())
+    pub(crate) fn make_id(file: &str, symbol_path: &str, sp: &Span) -> String {
+        crate::util::chunk_id::make_id(file, symbol_path, sp)
     }
 
     /// Extract identifier-like tokens and produce BM25-friendly keywords.
@@ -139,7 +134,7 @@ impl GenericTextAst {
     /// - Cap to 128 tokens to keep the record compact.
     ///
     /// Returns `(identifiers, keywords)`; here `keywords == identifiers`.
-    fn plain_identifiers_and_keywords(s: &str) -> (Vec<String>, Vec<String>) {
+    pub(crate) fn plain_identifiers_and_keywords(s: &str) -> (Vec<String>, Vec<String>) {
         let mut idents = Vec::<String>::new();
         let mut seen = std::collections::HashSet::<String>::new();
 
@@ -238,9 +233,8 @@ impl GenericTextAst {
 
 impl AstProvider for GenericTextAst {
     /// Parse a file into a single `CodeChunk`. No real AST is produced.
-    fn parse_file(path: &Path) -> Result<Vec<CodeChunk>> {
+    fn parse_source(path: &Path, text: &str) -> Result<Vec<CodeChunk>> {
         let file = path.to_string_lossy().to_string();
-        let text = fs::read_to_string(path)?;
         let lang = Self::guess_language(&file);
         let bytes = text.as_bytes();
 
@@ -266,7 +260,7 @@ impl AstProvider for GenericTextAst {
         let id = Self::make_id(&file, &symbol_path, &span);
 
         // Clamp after hashing, for display/embedding.
-        let snippet = clamp_snippet(&text, 2400, 120);
+        let snippet = clamp_snippet(text, 2400, 120);
 
         // Basic features.
         let features = ChunkFeatures {
@@ -285,7 +279,7 @@ impl AstProvider for GenericTextAst {
         };
 
         // Naive import references for graph hints.
-        let imports_out = Self::naive_imports(&text);
+        let imports_out = Self::naive_imports(text);
 
         let graph = GraphEdges {
             calls_out: Vec::new(),
@@ -323,6 +317,7 @@ impl AstProvider for GenericTextAst {
             lsp: None,
             // No per-language extras in the generic provider.
             extras: None,
+            low_fidelity: false,
         }])
     }
 }
@@ -3,6 +3,7 @@ use crate::types::CodeChunk;
 use std::path::Path;
 
 pub trait AstProvider {
-    /// Parse a single file and return language agnostic chunks.
-    fn parse_file(path: &Path) -> Result<Vec<CodeChunk>>;
+    /// Parse `code`, the contents of a single file at `path` (read once by
+    /// the caller), and return language agnostic chunks.
+    fn parse_source(path: &Path, code: &str) -> Result<Vec<CodeChunk>>;
 }
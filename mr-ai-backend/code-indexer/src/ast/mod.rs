@@ -2,6 +2,8 @@ pub mod dart;
 pub mod generic_text;
 pub mod interface;
 pub mod javascript;
+pub mod neighbors;
+pub mod regex_fallback;
 pub mod router;
 pub mod rust;
 pub mod typescript;
@@ -0,0 +1,68 @@
+//! Language-agnostic neighbor links between chunks of one file.
+//!
+//! Links are structural, so RAG neighbor expansion can follow them instead of
+//! matching source/FQN strings:
+//! - `prev_id`/`next_id`: previous/next symbol in the same owner scope
+//!   (methods of one class, or top-level declarations of the file);
+//! - `parent_id`/`children_ids`: derived from the `symbol_path` hierarchy.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::types::CodeChunk;
+
+/// Compute sibling and hierarchical neighbors within the same file.
+///
+/// Chunks are sorted by start byte; sibling order follows that (lexical) order.
+pub fn compute_neighbors_in_file(chunks: &mut [CodeChunk]) {
+    chunks.sort_by_key(|c| c.span.start_byte);
+
+    let mut scopes = BTreeMap::<&[String], Vec<usize>>::new();
+    for (i, c) in chunks.iter().enumerate() {
+        scopes.entry(c.owner_path.as_slice()).or_default().push(i);
+    }
+    let links: Vec<(usize, Option<String>, Option<String>)> = scopes
+        .values()
+        .flat_map(|idx| {
+            idx.iter().enumerate().map(|(k, &i)| {
+                let prev = k.checked_sub(1).map(|p| chunks[idx[p]].id.clone());
+                let next = idx.get(k + 1).map(|&n| chunks[n].id.clone());
+                (i, prev, next)
+            })
+        })
+        .collect();
+    for (i, prev, next) in links {
+        let entry = chunks[i].neighbors.get_or_insert_default();
+        entry.prev_id = prev;
+        entry.next_id = next;
+    }
+
+    let mut by_path = HashMap::<String, usize>::new();
+    for (i, c) in chunks.iter().enumerate() {
+        by_path.insert(c.symbol_path.clone(), i);
+    }
+    for i in 0..chunks.len() {
+        let Some(pi) = parent_path_of(&chunks[i].symbol_path).and_then(|pp| by_path.get(&pp))
+        else {
+            continue;
+        };
+        let pi = *pi;
+        let pid = chunks[pi].id.clone();
+        let entry = chunks[i].neighbors.get_or_insert_default();
+        entry.parent_id = Some(pid);
+        let child = chunks[i].id.clone();
+        chunks[pi]
+            .neighbors
+            .get_or_insert_default()
+            .children_ids
+            .push(child);
+    }
+}
+
+/// Return parent symbol path if exists (`file::A::B` -> `file::A`).
+pub fn parent_path_of(sym_path: &str) -> Option<String> {
+    let parts: Vec<&str> = sym_path.split("::").collect();
+    if parts.len() <= 2 {
+        return None;
+    }
+    Some(parts[..parts.len() - 1].join("::"))
+}
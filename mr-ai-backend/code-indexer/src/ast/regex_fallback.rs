@@ -0,0 +1,288 @@
+//! Regex-based fallback extractor for Kotlin and Swift.
+//!
+//! Until dedicated tree-sitter providers exist, these languages would only get
+//! the single whole-file chunk from `GenericTextAst`. This provider keeps that
+//! file chunk and appends coarse declaration chunks detected by regex:
+//! - Kotlin: `class`, `interface`, `object`, `fun`
+//! - Swift:  `class`, `struct`, `func`
+//!
+//! Spans are approximated by brace matching from the declaration line (strings
+//! and comments are not understood). Every emitted declaration chunk carries
+//! `low_fidelity = true` so downstream can rank and anchor it cautiously.
+
+use crate::ast::{
+    generic_text::GenericTextAst, interface::AstProvider, neighbors::compute_neighbors_in_file,
+};
+use crate::errors::Result;
+use crate::types::{
+    ChunkFeatures, CodeChunk, GraphEdges, LanguageKind, RetrievalHints, Span, SymbolKind,
+    clamp_snippet,
+};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Kotlin declarations: modifiers, keyword, name.
+static KOTLIN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)^[ \t]*((?:(?:public|private|protected|internal|open|abstract|sealed|data|enum|inner|annotation|value|final|override|suspend|inline|operator|infix|tailrec|external|companion|actual|expect)\s+)*)(class|interface|object|fun)\s+(?:<[^>\n]*>\s*)?(?:[A-Za-z_][\w.<>?, ]*\.)?([A-Za-z_]\w*)",
+    )
+    .expect("valid kotlin regex")
+});
+
+/// Swift declarations: attributes and modifiers, keyword, name.
+static SWIFT_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)^[ \t]*((?:@\w+(?:\([^)\n]*\))?\s+)*(?:(?:public|private|fileprivate|internal|open|final|static|class|override|mutating|nonmutating|convenience|required|dynamic|indirect)\s+)*)(class|struct|func)\s+([A-Za-z_]\w*)",
+    )
+    .expect("valid swift regex")
+});
+
+/// Coarse Kotlin/Swift provider (file chunk + regex-detected declarations).
+pub struct RegexFallbackAst;
+
+/// One regex hit with its approximated byte range.
+struct Decl {
+    name: String,
+    kind: SymbolKind,
+    start: usize,
+    end: usize,
+    signature: String,
+}
+
+impl RegexFallbackAst {
+    /// Map `(language, modifiers, keyword)` to a symbol kind. Functions are
+    /// refined to methods later when nested in a type.
+    fn kind_of(lang: LanguageKind, modifiers: &str, keyword: &str) -> SymbolKind {
+        match (lang, keyword) {
+            (LanguageKind::Kotlin, "class") if modifiers.contains("enum") => SymbolKind::Enum,
+            (LanguageKind::Kotlin, "interface") => SymbolKind::Interface,
+            (_, "fun" | "func") => SymbolKind::Function,
+            _ => SymbolKind::Class,
+        }
+    }
+
+    /// End of a declaration starting at `start`: brace-balanced body if a `{`
+    /// opens before the end of the signature line, otherwise end of that line.
+    fn decl_end(code: &str, start: usize) -> usize {
+        let bytes = code.as_bytes();
+        let line_end = code[start..].find('\n').map_or(code.len(), |i| start + i);
+
+        let Some(open) = code[start..line_end].find('{').map(|i| start + i) else {
+            return line_end;
+        };
+
+        let mut depth = 0usize;
+        for (i, &b) in bytes.iter().enumerate().skip(open) {
+            match b {
+                b'{' => depth += 1,
+                b'}' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        code.len()
+    }
+
+    /// Scan `code` for declarations of `lang`. Unsupported languages yield nothing.
+    fn scan(code: &str, lang: LanguageKind) -> Vec<Decl> {
+        let re = match lang {
+            LanguageKind::Kotlin => &*KOTLIN_RE,
+            LanguageKind::Swift => &*SWIFT_RE,
+            _ => return Vec::new(),
+        };
+
+        re.captures_iter(code)
+            .filter_map(|cap| {
+                let whole = cap.get(0)?;
+                let modifiers = cap.get(1).map_or("", |m| m.as_str());
+                let keyword = cap.get(2)?.as_str();
+                // Skip leading indentation so the span starts at the declaration.
+                let start = whole.start() + (whole.len() - whole.as_str().trim_start().len());
+                let end = Self::decl_end(code, start);
+                let sig_end = code[start..end]
+                    .find(['{', '\n'])
+                    .map_or(end, |i| start + i);
+                Some(Decl {
+                    name: cap.get(3)?.as_str().to_string(),
+                    kind: Self::kind_of(lang, modifiers, keyword),
+                    start,
+                    end,
+                    signature: code[start..sig_end].trim().to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// 0-based (row, col) for a byte offset; col in UTF-8 bytes.
+    fn row_col(code: &str, byte: usize) -> (usize, usize) {
+        let before = &code[..byte];
+        let row = before.matches('\n').count();
+        let col = before.rfind('\n').map_or(byte, |i| byte - i - 1);
+        (row, col)
+    }
+
+    /// Build one low-fidelity chunk per declaration, nesting owners by span containment.
+    fn decl_chunks(code: &str, file: &str, lang: LanguageKind) -> Vec<CodeChunk> {
+        let decls = Self::scan(code, lang);
+
+        decls
+            .iter()
+            .map(|d| {
+                let owner_path: Vec<String> = decls
+                    .iter()
+                    .filter(|o| {
+                        !matches!(o.kind, SymbolKind::Function)
+                            && o.start < d.start
+                            && d.end <= o.end
+                    })
+                    .map(|o| o.name.clone())
+                    .collect();
+
+                let kind = if matches!(d.kind, SymbolKind::Function) && !owner_path.is_empty() {
+                    SymbolKind::Method
+                } else {
+                    d.kind.clone()
+                };
+
+                let symbol_path = if owner_path.is_empty() {
+                    format!("{file}::{}", d.name)
+                } else {
+                    format!("{file}::{}::{}", owner_path.join("::"), d.name)
+                };
+
+                let (start_row, start_col) = Self::row_col(code, d.start);
+                let (end_row, end_col) = Self::row_col(code, d.end);
+                let span = Span {
+                    start_byte: d.start,
+                    end_byte: d.end,
+                    start_row,
+                    start_col,
+                    end_row,
+                    end_col,
+                };
+
+                let body = &code[d.start..d.end];
+                let mut h = Sha256::new();
+                h.update(body.as_bytes());
+
+                let snippet = clamp_snippet(body, 2400, 120);
+                let (identifiers, keywords) =
+                    GenericTextAst::plain_identifiers_and_keywords(&snippet);
+
+                CodeChunk {
+                    id: GenericTextAst::make_id(file, &symbol_path, &span),
+                    language: lang,
+                    file: file.to_string(),
+                    symbol: d.name.clone(),
+                    symbol_path,
+                    kind,
+                    span,
+                    owner_path,
+                    doc: None,
+                    annotations: Vec::new(),
+                    imports: Vec::new(),
+                    signature: Some(d.signature.clone()),
+                    is_definition: true,
+                    is_generated: false,
+                    snippet: Some(snippet),
+                    features: ChunkFeatures {
+                        byte_len: body.len(),
+                        line_count: body.lines().count(),
+                        has_doc: false,
+                        has_annotations: false,
+                    },
+                    content_sha256: format!("{:x}", h.finalize()),
+                    neighbors: None,
+                    identifiers,
+                    anchors: Vec::new(),
+                    graph: Some(GraphEdges {
+                        defines_types: if matches!(d.kind, SymbolKind::Function) {
+                            Vec::new()
+                        } else {
+                            vec![d.name.clone()]
+                        },
+                        ..Default::default()
+                    }),
+                    hints: Some(RetrievalHints {
+                        keywords,
+                        category: None,
+                        title: None,
+                    }),
+                    lsp: None,
+                    extras: None,
+                    low_fidelity: true,
+                }
+            })
+            .collect()
+    }
+}
+
+impl AstProvider for RegexFallbackAst {
+    /// Whole-file chunk from `GenericTextAst` followed by regex declaration chunks
+    /// (linked as neighbors among themselves).
+    fn parse_source(path: &Path, code: &str) -> Result<Vec<CodeChunk>> {
+        let mut chunks = GenericTextAst::parse_source(path, code)?;
+        let file = path.to_string_lossy().to_string();
+        let lang = GenericTextAst::guess_language(&file);
+        let mut decls = Self::decl_chunks(code, &file, lang);
+        compute_neighbors_in_file(&mut decls);
+        chunks.extend(decls);
+        Ok(chunks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kotlin_declarations_are_detected_and_nested() {
+        let code = "package app\n\ndata class User(val id: Int)\n\ninterface Repo {\n    fun load(): User\n}\n\nobject Registry {\n    fun register(r: Repo) {\n        println(r)\n    }\n}\n\nsuspend fun main() {}\n";
+        let chunks = RegexFallbackAst::decl_chunks(code, "Main.kt", LanguageKind::Kotlin);
+        let names: Vec<_> = chunks.iter().map(|c| c.symbol_path.as_str()).collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "Main.kt::User",
+                "Main.kt::Repo",
+                "Main.kt::Repo::load",
+                "Main.kt::Registry",
+                "Main.kt::Registry::register",
+                "Main.kt::main",
+            ]
+        );
+        assert!(chunks.iter().all(|c| c.low_fidelity));
+        let register = &chunks[4];
+        assert_eq!(register.kind, SymbolKind::Method);
+        assert_eq!(register.span.start_row, 9);
+        assert_eq!(register.span.end_row, 11);
+        assert_eq!(chunks[1].kind, SymbolKind::Interface);
+    }
+
+    #[test]
+    fn swift_declarations_are_detected() {
+        let code = "import UIKit\n\nfinal class ViewController: UIViewController {\n    override func viewDidLoad() {\n        super.viewDidLoad()\n    }\n}\n\nstruct Point { var x: Int }\n";
+        let chunks = RegexFallbackAst::decl_chunks(code, "VC.swift", LanguageKind::Swift);
+
+        let kinds: Vec<_> = chunks
+            .iter()
+            .map(|c| (c.symbol.as_str(), c.kind.clone()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("ViewController", SymbolKind::Class),
+                ("viewDidLoad", SymbolKind::Method),
+                ("Point", SymbolKind::Class),
+            ]
+        );
+        assert_eq!(chunks[2].span.start_row, 8);
+    }
+}
@@ -2,7 +2,7 @@
 
 use super::{
     dart::DartAst, generic_text::GenericTextAst, interface::AstProvider, javascript::JavascriptAst,
-    rust::RustAst, typescript::TypescriptAst,
+    regex_fallback::RegexFallbackAst, rust::RustAst, typescript::TypescriptAst,
 };
 use crate::errors::Result;
 use crate::types::CodeChunk;
@@ -12,8 +12,9 @@ use tracing::{debug, error, info, warn};
 pub struct RouterAst;
 
 impl RouterAst {
-    /// Parse a file by extension. On failure falls back to GenericTextAst.
-    pub fn parse_file(path: &Path) -> Result<Vec<CodeChunk>> {
+    /// Parse `code` (the contents of `path`) by extension. On failure falls
+    /// back to GenericTextAst.
+    pub fn parse_source(path: &Path, code: &str) -> Result<Vec<CodeChunk>> {
         let ext = path
             .extension()
             .and_then(|s| s.to_str())
@@ -27,29 +28,34 @@ impl RouterAst {
         let primary = match ext.as_str() {
             "dart" => {
                 debug!(target: "router", file = %path.display(), "RouterAst: using DartAst");
-                DartAst::parse_file(path)
+                DartAst::parse_source(path, code)
             }
             "rs" => {
                 debug!(target: "router", file = %path.display(), "RouterAst: using RustAst");
-                RustAst::parse_file(path)
+                RustAst::parse_source(path, code)
             }
             "js" | "jsx" => {
                 debug!(target: "router", file = %path.display(), "RouterAst: using JavascriptAst");
-                JavascriptAst::parse_file(path)
+                JavascriptAst::parse_source(path, code)
             }
             "ts" | "tsx" => {
                 debug!(target: "router", file = %path.display(), "RouterAst: using TypescriptAst");
-                TypescriptAst::parse_file(path)
+                TypescriptAst::parse_source(path, code)
+            }
+            // No tree-sitter grammar yet: coarse regex declarations (low fidelity)
+            "kt" | "kts" | "swift" => {
+                debug!(target: "router", file = %path.display(), %ext, "RouterAst: using RegexFallbackAst");
+                RegexFallbackAst::parse_source(path, code)
             }
             // Known config and unknown but useful files go via GenericTextAst
             "yaml" | "yml" | "json" | "arb" | "xml" | "plist" | "toml" | "gradle"
-            | "properties" | "kt" | "kts" | "swift" | "java" => {
+            | "properties" | "java" => {
                 debug!(target: "router", file = %path.display(), %ext, "RouterAst: using GenericTextAst (known config)");
-                GenericTextAst::parse_file(path)
+                GenericTextAst::parse_source(path, code)
             }
             _ => {
                 debug!(target: "router", file = %path.display(), %ext, "RouterAst: using GenericTextAst (fallback by ext)");
-                GenericTextAst::parse_file(path)
+                GenericTextAst::parse_source(path, code)
             }
         };
 
@@ -78,7 +84,7 @@ impl RouterAst {
                     "RouterAst: primary provider failed, falling back to GenericTextAst"
                 );
                 let fb_started = Instant::now();
-                match GenericTextAst::parse_file(path) {
+                match GenericTextAst::parse_source(path, code) {
                     Ok(fb_chunks) => {
                         info!(
                             target: "router",
@@ -8,7 +8,7 @@ use std::path::Path;
 pub struct RustAst;
 
 impl crate::ast::interface::AstProvider for RustAst {
-    fn parse_file(_path: &Path) -> Result<Vec<CodeChunk>> {
+    fn parse_source(_path: &Path, _code: &str) -> Result<Vec<CodeChunk>> {
         Err(Error::InvalidState("Rust AST provider is not implemented"))
     }
 }
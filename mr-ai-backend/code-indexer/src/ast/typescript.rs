@@ -8,7 +8,7 @@ use std::path::Path;
 pub struct TypescriptAst;
 
 impl crate::ast::interface::AstProvider for TypescriptAst {
-    fn parse_file(_path: &Path) -> Result<Vec<CodeChunk>> {
+    fn parse_source(_path: &Path, _code: &str) -> Result<Vec<CodeChunk>> {
         Err(Error::InvalidState(
             "TypeScript AST provider is not implemented",
         ))
@@ -0,0 +1,371 @@
+//! Bulk indexing of every project cloned under `code_data`.
+//!
+//! Each direct subdirectory of `code_data` (except the `out` output folder) is a
+//! project. Projects are indexed independently with bounded parallelism; a failure
+//! is recorded in the report and does not stop the remaining projects.
+//!
+//! Each project logs a start/finish line with timing; a [`Progress`] reporter
+//! gets one step per finished project, and [`BulkIndexReport::summary_table`]
+//! renders the final `{repo, status, duration_ms, chunks}` table.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use tracing::{info, warn};
+
+use crate::errors::Result;
+use crate::progress::{NoopProgress, Progress};
+use crate::{DEFAULT_MAX_FILE_BYTES, ExportCounts, SkippedFile, export_chunks_jsonl, util};
+
+/// Projects indexed at the same time by [`index_all_projects`].
+pub const DEFAULT_BULK_CONCURRENCY: usize = 4;
+
+/// Output folder under `code_data`; never treated as a project.
+const OUT_DIR: &str = "out";
+
+/// Result of indexing one project.
+#[derive(Debug, Clone)]
+pub struct ProjectIndexOutcome {
+    pub project: String,
+    /// `code_data/out/{project}/code_chunks.jsonl` on success.
+    pub output: Option<PathBuf>,
+    /// Error message on failure.
+    pub error: Option<String>,
+    /// Files skipped for exceeding the size limit.
+    pub skipped_oversized: usize,
+    /// Files skipped because they aren't valid UTF-8.
+    pub skipped_undecodable: Vec<SkippedFile>,
+    /// Chunks written (0 on failure).
+    pub chunks: usize,
+    /// Wall time spent on this project.
+    pub duration_ms: u128,
+}
+
+/// Summary of [`index_all_projects`].
+#[derive(Debug, Clone, Default)]
+pub struct BulkIndexReport {
+    /// One entry per matching project, ordered by project name.
+    pub outcomes: Vec<ProjectIndexOutcome>,
+    /// Project directories excluded by the filter.
+    pub skipped: Vec<String>,
+}
+
+impl BulkIndexReport {
+    /// `(project, jsonl path)` for every successfully indexed project.
+    pub fn indexed(&self) -> Vec<(String, PathBuf)> {
+        self.outcomes
+            .iter()
+            .filter_map(|o| o.output.clone().map(|p| (o.project.clone(), p)))
+            .collect()
+    }
+
+    /// `(project, error)` for every failed project.
+    pub fn failed(&self) -> Vec<(&str, &str)> {
+        self.outcomes
+            .iter()
+            .filter_map(|o| o.error.as_deref().map(|e| (o.project.as_str(), e)))
+            .collect()
+    }
+
+    /// Plain-text `repo | status | duration_ms | chunks` table, one row per project.
+    pub fn summary_table(&self) -> String {
+        let width = self
+            .outcomes
+            .iter()
+            .map(|o| o.project.len())
+            .chain(["repo".len()])
+            .max()
+            .unwrap_or(0);
+        let mut out = format!(
+            "{:<width$}  {:<6}  {:>11}  {:>7}\n",
+            "repo", "status", "duration_ms", "chunks"
+        );
+        for o in &self.outcomes {
+            let status = if o.error.is_some() { "failed" } else { "ok" };
+            out.push_str(&format!(
+                "{:<width$}  {:<6}  {:>11}  {:>7}\n",
+                o.project, status, o.duration_ms, o.chunks
+            ));
+        }
+        out
+    }
+}
+
+/// Index every project under `code_data` whose directory name matches `filter_glob`
+/// (`*` and `?` wildcards; `None` = all) into `code_data/out/{project}/code_chunks.jsonl`.
+///
+/// Only failing to list `code_data` is an error; per-project failures are reported
+/// in [`BulkIndexReport::failed`].
+pub fn index_all_projects(filter_glob: Option<&str>, enable_lsp: bool) -> Result<BulkIndexReport> {
+    index_all_projects_with(
+        filter_glob,
+        enable_lsp,
+        DEFAULT_BULK_CONCURRENCY,
+        &NoopProgress,
+    )
+}
+
+/// Same as [`index_all_projects`] with explicit parallelism (`0` is treated as 1)
+/// and a progress reporter (e.g. [`crate::IndicatifProgress`] for CLIs).
+pub fn index_all_projects_with(
+    filter_glob: Option<&str>,
+    enable_lsp: bool,
+    concurrency: usize,
+    progress: &dyn Progress,
+) -> Result<BulkIndexReport> {
+    index_all_in(
+        &services::data_root::data_root(),
+        filter_glob,
+        enable_lsp,
+        concurrency,
+        progress,
+    )
+}
+
+/// [`index_all_projects_with`] rooted at `root` instead of `code_data`.
+fn index_all_in(
+    root: &Path,
+    filter_glob: Option<&str>,
+    enable_lsp: bool,
+    concurrency: usize,
+    progress: &dyn Progress,
+) -> Result<BulkIndexReport> {
+    let mut projects = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in std::fs::read_dir(root)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == OUT_DIR || name.starts_with('.') {
+            continue;
+        }
+        if filter_glob.is_none_or(|g| glob_match(g, &name)) {
+            projects.push(name);
+        } else {
+            skipped.push(name);
+        }
+    }
+    projects.sort();
+    skipped.sort();
+
+    info!(
+        root = %root.display(),
+        projects = projects.len(),
+        skipped = skipped.len(),
+        concurrency,
+        "bulk index: start"
+    );
+    progress.set_total(projects.len() as u64);
+
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(projects.len()));
+    let workers = concurrency.min(projects.len()).max(1);
+
+    std::thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(project) = projects.get(i) else {
+                        break;
+                    };
+                    info!(project = %project, "bulk index: project start");
+                    progress.message(&format!("indexing {project}"));
+                    let started = Instant::now();
+                    let res = index_one(root, project, enable_lsp);
+                    let duration_ms = started.elapsed().as_millis();
+                    let outcome = match res {
+                        Ok((path, counts)) => {
+                            info!(
+                                project = %project,
+                                chunks = counts.chunks,
+                                duration_ms,
+                                "bulk index: project done"
+                            );
+                            ProjectIndexOutcome {
+                                project: project.clone(),
+                                output: Some(path),
+                                error: None,
+                                skipped_oversized: counts.skipped_oversized,
+                                skipped_undecodable: counts.skipped_undecodable,
+                                chunks: counts.chunks,
+                                duration_ms,
+                            }
+                        }
+                        Err(e) => {
+                            warn!(
+                                project = %project,
+                                error = %e,
+                                duration_ms,
+                                "bulk index: project failed"
+                            );
+                            ProjectIndexOutcome {
+                                project: project.clone(),
+                                output: None,
+                                error: Some(e.to_string()),
+                                skipped_oversized: 0,
+                                skipped_undecodable: Vec::new(),
+                                chunks: 0,
+                                duration_ms,
+                            }
+                        }
+                    };
+                    let status = if outcome.error.is_some() {
+                        "failed"
+                    } else {
+                        "ok"
+                    };
+                    progress.step(&format!("{project} {status} ({duration_ms} ms)"));
+                    outcomes
+                        .lock()
+                        .unwrap_or_else(|p| p.into_inner())
+                        .push(outcome);
+                }
+            });
+        }
+    });
+
+    let mut outcomes = outcomes.into_inner().unwrap_or_else(|p| p.into_inner());
+    outcomes.sort_by(|a, b| a.project.cmp(&b.project));
+    let report = BulkIndexReport { outcomes, skipped };
+
+    info!(
+        indexed = report.indexed().len(),
+        failed = report.failed().len(),
+        skipped = report.skipped.len(),
+        "bulk index: finished\n{}",
+        report.summary_table()
+    );
+    progress.finish(&format!(
+        "indexed {}/{} project(s)",
+        report.indexed().len(),
+        report.outcomes.len()
+    ));
+    Ok(report)
+}
+
+/// Index `root/{project}` into `root/out/{project}/code_chunks.jsonl`.
+///
+/// Returns the output path and the export counts.
+fn index_one(root: &Path, project: &str, enable_lsp: bool) -> Result<(PathBuf, ExportCounts)> {
+    let out_dir = root.join(OUT_DIR).join(project);
+    util::ensure_dir(&out_dir)?;
+    let out_path = out_dir.join("code_chunks.jsonl");
+    let counts = export_chunks_jsonl(
+        &root.join(project),
+        &out_path,
+        enable_lsp,
+        None,
+        DEFAULT_MAX_FILE_BYTES,
+    )?;
+    Ok((out_path, counts))
+}
+
+/// Shell-style match of a whole name: `*` = any run, `?` = any single char.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let (p, n): (Vec<char>, Vec<char>) = (pattern.chars().collect(), name.chars().collect());
+    let (mut pi, mut ni) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ni));
+            pi += 1;
+        } else if let Some((sp, sn)) = star {
+            pi = sp + 1;
+            ni = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_whole_names() {
+        assert!(glob_match("*", "app"));
+        assert!(glob_match("mobile-*", "mobile-ios"));
+        assert!(glob_match("*-api", "billing-api"));
+        assert!(glob_match("svc-?", "svc-a"));
+        assert!(!glob_match("svc-?", "svc-ab"));
+        assert!(!glob_match("mobile-*", "web-app"));
+    }
+
+    #[test]
+    fn one_failing_project_does_not_fail_the_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("app-ok/lib")).unwrap();
+        std::fs::create_dir_all(root.join("app-bad")).unwrap();
+        std::fs::create_dir_all(root.join("tools")).unwrap();
+        std::fs::write(root.join("app-ok/lib/main.dart"), "void main() {}\n").unwrap();
+        // Output path occupied by a file → export fails for this project only.
+        std::fs::create_dir_all(root.join("out")).unwrap();
+        std::fs::write(root.join("out/app-bad"), "not a dir").unwrap();
+
+        let progress = RecordingProgress::default();
+        let report = index_all_in(root, Some("app-*"), false, 2, &progress).unwrap();
+
+        let indexed = report.indexed();
+        assert_eq!(indexed.len(), 1);
+        assert_eq!(indexed[0].0, "app-ok");
+        assert!(indexed[0].1.ends_with("out/app-ok/code_chunks.jsonl"));
+        assert_eq!(report.failed().len(), 1);
+        assert_eq!(report.failed()[0].0, "app-bad");
+        assert_eq!(report.skipped, vec!["tools".to_string()]);
+
+        let ok = &report.outcomes[1];
+        assert_eq!(ok.project, "app-ok");
+        assert!(ok.chunks > 0);
+        assert_eq!(report.outcomes[0].chunks, 0);
+        let table = report.summary_table();
+        assert!(table.starts_with("repo"));
+        assert!(
+            table
+                .lines()
+                .any(|l| l.starts_with("app-bad") && l.contains("failed"))
+        );
+        assert!(
+            table
+                .lines()
+                .any(|l| l.starts_with("app-ok") && l.contains("ok"))
+        );
+
+        let events = progress.events.into_inner().unwrap();
+        assert_eq!(events.first().map(String::as_str), Some("total 2"));
+        assert_eq!(events.iter().filter(|e| e.starts_with("step ")).count(), 2);
+        assert_eq!(
+            events.last().map(String::as_str),
+            Some("finish indexed 1/2 project(s)")
+        );
+    }
+
+    #[derive(Default)]
+    struct RecordingProgress {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl Progress for RecordingProgress {
+        fn set_total(&self, n: u64) {
+            self.events.lock().unwrap().push(format!("total {n}"));
+        }
+        fn step(&self, msg: &str) {
+            self.events.lock().unwrap().push(format!("step {msg}"));
+        }
+        fn finish(&self, msg: &str) {
+            self.events.lock().unwrap().push(format!("finish {msg}"));
+        }
+    }
+}
@@ -1,41 +1,216 @@
 //! Public entrypoints for cross-platform code indexing with AST and optional LSP enrichment.
 
 mod ast;
+mod bulk;
 pub mod errors;
 mod lsp;
+pub use services::progress;
 pub mod types;
 mod util;
 
+use crate::ast::generic_text::GenericTextAst;
 use crate::lsp::{dart::DartLsp, interface::LspProvider}; // bring trait into scope for ::enrich
+pub use bulk::{
+    BulkIndexReport, DEFAULT_BULK_CONCURRENCY, ProjectIndexOutcome, index_all_projects,
+    index_all_projects_with,
+};
 pub use errors::{Error, Result};
+pub use progress::{IndicatifProgress, NoopProgress, Progress};
+pub use services::skipped_file::SkippedFile;
 pub use types::{CodeChunk, LanguageKind};
+pub use util::fs_scan::DEFAULT_MAX_FILE_BYTES;
 
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 
+use serde::Serialize;
+use tracing::{info, warn};
+
+/// Chunks of one project and the files skipped for size or encoding.
+pub(crate) struct ProjectIndex {
+    pub chunks: Vec<CodeChunk>,
+    pub skipped_oversized: usize,
+    pub skipped_undecodable: Vec<SkippedFile>,
+}
+
 /// Internal helper:
 /// Recursively scans `base_dir`, parses all supported files into `CodeChunk`s,
 /// and optionally enriches Dart code with LSP.
 ///
+/// `languages` restricts parsing to files of the given languages (`None` = all supported).
+/// Dart LSP enrichment is skipped when Dart is not in the allowlist.
+/// Chunk ids are computed from repo-relative paths, so they don't depend on how
+/// `base_dir` is spelled (see [`util::chunk_id`]).
+/// Files larger than `max_file_bytes` are skipped with a warning and counted.
+/// Files that aren't valid UTF-8 are skipped and recorded with the reason,
+/// or decoded lossily when `CODE_INDEXER_LOSSY_DECODE` is set (see [`util::text`]).
+///
 /// Not public API; used internally by the public entrypoints.
-pub(crate) fn index_project(base_dir: &Path, enable_lsp: bool) -> Result<Vec<CodeChunk>> {
-    let files = util::fs_scan::scan_project_files(base_dir);
+pub(crate) fn index_project(
+    base_dir: &Path,
+    enable_lsp: bool,
+    languages: Option<&[LanguageKind]>,
+    max_file_bytes: u64,
+) -> Result<ProjectIndex> {
+    index_project_files(base_dir, enable_lsp, languages, max_file_bytes, |_| true)
+}
+
+/// [`index_project`] restricted to the scanned files `keep` accepts.
+fn index_project_files(
+    base_dir: &Path,
+    enable_lsp: bool,
+    languages: Option<&[LanguageKind]>,
+    max_file_bytes: u64,
+    keep: impl Fn(&Path) -> bool,
+) -> Result<ProjectIndex> {
+    let allows = |lang: LanguageKind| languages.is_none_or(|l| l.contains(&lang));
+
+    let scan = util::fs_scan::scan_project_files(base_dir, max_file_bytes);
+    let files = scan
+        .files
+        .into_iter()
+        .filter(|f| keep(f))
+        .filter(|f| allows(GenericTextAst::guess_language(&f.to_string_lossy())));
     let mut chunks = Vec::<CodeChunk>::new();
+    let mut skipped_undecodable = Vec::<SkippedFile>::new();
+    let lossy = util::text::lossy_decode_enabled();
 
     for f in files {
-        let mut c = ast::router::RouterAst::parse_file(&f)?;
+        let (code, undecodable) = util::text::decode_source(std::fs::read(&f)?);
+        if let Some(reason) = undecodable {
+            if !lossy {
+                warn!(file = %f.display(), reason, "index: skipping undecodable file");
+                skipped_undecodable.push(SkippedFile::new(
+                    util::chunk_id::canonical_rel_path(base_dir, &f.to_string_lossy()),
+                    reason,
+                ));
+                continue;
+            }
+            warn!(file = %f.display(), reason, "index: decoding lossily");
+        }
+        let mut c = ast::router::RouterAst::parse_source(&f, &code)?;
+        util::chunk_id::rekey_chunks(base_dir, &mut c);
         chunks.append(&mut c);
     }
 
-    if enable_lsp {
+    if enable_lsp && allows(LanguageKind::Dart) {
         DartLsp::enrich(base_dir, &mut chunks)?;
     }
 
-    Ok(chunks)
+    sort_chunks(&mut chunks);
+    Ok(ProjectIndex {
+        chunks,
+        skipped_oversized: scan.skipped_oversized,
+        skipped_undecodable,
+    })
+}
+
+/// Deterministic export order: `(file, span.start_byte)`, ties broken by end byte and id.
+fn sort_chunks(chunks: &mut [CodeChunk]) {
+    chunks.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then(a.span.start_byte.cmp(&b.span.start_byte))
+            .then(a.span.end_byte.cmp(&b.span.end_byte))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+/// Counts from one [`export_chunks_jsonl`] run.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExportCounts {
+    pub chunks: usize,
+    pub skipped_oversized: usize,
+    pub skipped_undecodable: Vec<SkippedFile>,
+    pub by_language: HashMap<LanguageKind, usize>,
+}
+
+/// Result of [`index_project_to_jsonl`].
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexReport {
+    /// The generated `code_chunks.jsonl`.
+    pub out_path: PathBuf,
+    /// Chunks written.
+    pub total_chunks: usize,
+    /// Chunks per language; a large `other` count means files fell through to
+    /// the fallback parser.
+    pub by_language: HashMap<LanguageKind, usize>,
+    /// Files skipped for exceeding `max_file_bytes`.
+    pub skipped_oversized: usize,
+    /// Files skipped because they aren't valid UTF-8, with the reason.
+    pub skipped_undecodable: Vec<SkippedFile>,
+}
+
+/// Chunk count per [`CodeChunk::language`].
+fn count_by_language(chunks: &[CodeChunk]) -> HashMap<LanguageKind, usize> {
+    let mut out = HashMap::new();
+    for c in chunks {
+        *out.entry(c.language).or_default() += 1;
+    }
+    out
+}
+
+/// Index `base_dir` and write chunks as JSONL to `out_path` (one object per line).
+///
+/// Returns the number of chunks written (in total and per language), the
+/// number of files skipped for exceeding `max_file_bytes` and the files
+/// skipped as undecodable.
+fn export_chunks_jsonl(
+    base_dir: &Path,
+    out_path: &Path,
+    enable_lsp: bool,
+    languages: Option<&[LanguageKind]>,
+    max_file_bytes: u64,
+) -> Result<ExportCounts> {
+    let index = index_project(base_dir, enable_lsp, languages, max_file_bytes)?;
+    let mut w = util::jsonl::JsonlWriter::open(out_path)?;
+    for c in &index.chunks {
+        w.write_obj(c)?;
+    }
+    w.finish()?;
+    if index.skipped_oversized > 0 {
+        info!(
+            base_dir = %base_dir.display(),
+            skipped = index.skipped_oversized,
+            max_file_bytes,
+            "index: oversized files skipped"
+        );
+    }
+    if !index.skipped_undecodable.is_empty() {
+        info!(
+            base_dir = %base_dir.display(),
+            skipped = index.skipped_undecodable.len(),
+            "index: undecodable files skipped"
+        );
+    }
+    Ok(ExportCounts {
+        chunks: index.chunks.len(),
+        skipped_oversized: index.skipped_oversized,
+        skipped_undecodable: index.skipped_undecodable,
+        by_language: count_by_language(&index.chunks),
+    })
 }
 
-/// Build canonical base directory: `code_data/{project_name}` (internal).
+/// Build canonical base directory: `<data root>/{project_name}` (internal).
+///
+/// The root is `MRAI_DATA_ROOT` (default `code_data`), see [`services::data_root`].
 fn project_base_dir(project_name: &str) -> PathBuf {
-    PathBuf::from(format!("code_data/{project_name}"))
+    services::data_root::project_dir(project_name)
+}
+
+/// `<data root>/out/{project_name}/code_chunks.jsonl`, creating its directory.
+fn project_jsonl_path(project_name: &str) -> Result<PathBuf> {
+    let out_dir = services::data_root::data_root()
+        .join("out")
+        .join(project_name);
+    util::ensure_dir(&out_dir)?;
+    Ok(out_dir.join("code_chunks.jsonl"))
+}
+
+/// `CodeChunk::file` in the form change lists use: `/`-separated, no leading `./`.
+fn chunk_file_key(file: &str) -> String {
+    let s = file.replace('\\', "/");
+    s.strip_prefix("./").map(str::to_string).unwrap_or(s)
 }
 
 /* -------------------------------------------------------------------------- */
@@ -48,16 +223,23 @@ fn project_base_dir(project_name: &str) -> PathBuf {
 /// - Resolves the project root to `code_data/{project_name}` (creates if missing).
 /// - Recursively scans the project for supported files (Dart, Kotlin/Swift/JS/TS, YAML/JSON/XML/etc).
 /// - Builds language-agnostic [`CodeChunk`] items via AST providers (Dart via tree-sitter,
-///   others are safe fallbacks until dedicated parsers are added).
+///   others are safe fallbacks until dedicated parsers are added; Kotlin/Swift additionally get
+///   coarse regex declaration chunks flagged `low_fidelity`).
 /// - Optionally runs Dart LSP enrichment (document symbols/outline, etc.), keeping chunk identity stable.
 /// - Writes all chunks as JSONL (one JSON object per line) to `out/{project_name}/code_chunks.jsonl`.
 ///
 /// # Arguments
 /// * `project_name` — Logical project identifier; used to resolve `code_data/{project_name}` and `out/{project_name}`.
 /// * `enable_lsp` — Set `true` to run the additional Dart LSP pass.
+/// * `languages` — Optional allowlist (e.g. `Some(vec![LanguageKind::Dart])`); `None` indexes
+///   all supported languages. Filtering happens before parsing.
+/// * `max_file_bytes` — Files larger than this are skipped with a warning (generated bundles,
+///   lockfiles); `None` uses [`DEFAULT_MAX_FILE_BYTES`] (1 MiB).
 ///
 /// # Output
-/// On success returns the absolute path to the generated JSONL file.
+/// On success returns an [`IndexReport`]: the path to the generated JSONL file,
+/// the chunk count in total and per language, the number of oversized files
+/// skipped and the files skipped as non-UTF-8 (with the reason).
 ///
 /// # Errors
 /// Returns [`Error`] if scanning, parsing, LSP communication, or file I/O fails.
@@ -69,27 +251,372 @@ fn project_base_dir(project_name: &str) -> PathBuf {
 /// fn main() -> mr_reviewer::Result<()> {
 ///     // Will read from:  code_data/my_flutter_app
 ///     // Will write into: out/my_flutter_app/code_chunks.jsonl
-///     let out_path = index_project_to_jsonl("my_flutter_app", true)?;
-///     println!("Wrote chunks to {}", out_path.display());
+///     let report = index_project_to_jsonl("my_flutter_app", true, None, None)?;
+///     println!("Wrote {} chunks to {}", report.total_chunks, report.out_path.display());
 ///     Ok(())
 /// }
 /// ```
-pub fn index_project_to_jsonl(project_name: &str, enable_lsp: bool) -> Result<PathBuf> {
+pub fn index_project_to_jsonl(
+    project_name: &str,
+    enable_lsp: bool,
+    languages: Option<Vec<LanguageKind>>,
+    max_file_bytes: Option<u64>,
+) -> Result<IndexReport> {
     // Resolve input/output locations
     let base_dir = project_base_dir(project_name);
     util::ensure_dir(&base_dir)?;
 
-    let out_dir = PathBuf::from(format!("code_data/out/{project_name}"));
-    util::ensure_dir(&out_dir)?;
-    let out_path = out_dir.join("code_chunks.jsonl");
+    let out_path = project_jsonl_path(project_name)?;
+
+    // Build chunks and export (sorted, so identical trees yield identical files)
+    let counts = export_chunks_jsonl(
+        &base_dir,
+        &out_path,
+        enable_lsp,
+        languages.as_deref(),
+        max_file_bytes.unwrap_or(DEFAULT_MAX_FILE_BYTES),
+    )?;
+    info!(
+        project = project_name,
+        chunks = counts.chunks,
+        by_language = ?counts.by_language,
+        "index: export done"
+    );
+
+    Ok(IndexReport {
+        out_path,
+        total_chunks: counts.chunks,
+        by_language: counts.by_language,
+        skipped_oversized: counts.skipped_oversized,
+        skipped_undecodable: counts.skipped_undecodable,
+    })
+}
+
+/// Update the project's JSONL (see [`index_project_to_jsonl`]) for `changed_files` only.
+///
+/// `changed_files` are paths in the [`CodeChunk::file`] form, e.g.
+/// `code_data/app/repo/lib/a.dart` (as `rag_base::changed_files_since` lists
+/// them). Their chunks are replaced by a fresh parse, chunks of files that no
+/// longer exist are dropped, and every other line is kept as is. Without an
+/// existing JSONL this is a full [`index_project_to_jsonl`].
+///
+/// The report covers the whole file, except `skipped_undecodable`, which only
+/// lists changed files.
+///
+/// # Errors
+/// Returns [`Error`] if the existing JSONL can't be read, or as for
+/// [`index_project_to_jsonl`].
+pub fn update_project_jsonl(
+    project_name: &str,
+    changed_files: &BTreeSet<String>,
+    enable_lsp: bool,
+    languages: Option<Vec<LanguageKind>>,
+    max_file_bytes: Option<u64>,
+) -> Result<IndexReport> {
+    let out_path = project_jsonl_path(project_name)?;
+    if !out_path.exists() {
+        return index_project_to_jsonl(project_name, enable_lsp, languages, max_file_bytes);
+    }
+
+    let changed = |file: &str| changed_files.contains(&chunk_file_key(file));
+    let mut chunks = Vec::<CodeChunk>::new();
+    for line in std::fs::read_to_string(&out_path)?.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let chunk: CodeChunk = serde_json::from_str(line)?;
+        if !changed(&chunk.file) {
+            chunks.push(chunk);
+        }
+    }
+    let kept = chunks.len();
+
+    let fresh = index_project_files(
+        &project_base_dir(project_name),
+        enable_lsp,
+        languages.as_deref(),
+        max_file_bytes.unwrap_or(DEFAULT_MAX_FILE_BYTES),
+        |f| changed(&f.to_string_lossy()),
+    )?;
+    chunks.extend(fresh.chunks);
+    sort_chunks(&mut chunks);
 
-    // Build chunks and export
-    let chunks: Vec<CodeChunk> = index_project(&base_dir, enable_lsp)?;
     let mut w = util::jsonl::JsonlWriter::open(&out_path)?;
     for c in &chunks {
         w.write_obj(c)?;
     }
     w.finish()?;
+    info!(
+        project = project_name,
+        changed_files = changed_files.len(),
+        kept,
+        chunks = chunks.len(),
+        "index: jsonl updated"
+    );
+
+    Ok(IndexReport {
+        out_path,
+        total_chunks: chunks.len(),
+        by_language: count_by_language(&chunks),
+        skipped_oversized: fresh.skipped_oversized,
+        skipped_undecodable: fresh.skipped_undecodable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dart_only_filter_skips_other_languages() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::create_dir_all(root.join("android")).unwrap();
+        std::fs::write(
+            root.join("lib/main.dart"),
+            "class App {\n  void run() {}\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("android/Main.kt"), "class Main {}\n").unwrap();
+        std::fs::write(root.join("pubspec.yaml"), "name: app\n").unwrap();
+
+        let all = index_project(root, false, None, DEFAULT_MAX_FILE_BYTES)
+            .unwrap()
+            .chunks;
+        let dart_only = index_project(
+            root,
+            false,
+            Some(&[LanguageKind::Dart]),
+            DEFAULT_MAX_FILE_BYTES,
+        )
+        .unwrap()
+        .chunks;
+
+        assert!(all.iter().any(|c| c.language != LanguageKind::Dart));
+        assert!(!dart_only.is_empty());
+        assert!(dart_only.iter().all(|c| c.language == LanguageKind::Dart));
+        assert!(dart_only.iter().all(|c| c.file.ends_with("main.dart")));
+    }
+
+    #[test]
+    fn export_is_byte_identical_across_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("lib/b")).unwrap();
+        std::fs::create_dir_all(root.join("lib/a")).unwrap();
+        std::fs::write(
+            root.join("lib/b/widget.dart"),
+            "class W {\n  void a() {}\n  void b() {}\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("lib/a/util.dart"), "int twice(int x) => x * 2;\n").unwrap();
+        std::fs::write(root.join("pubspec.yaml"), "name: app\n").unwrap();
+
+        let out1 = root.join("run1.jsonl");
+        let out2 = root.join("run2.jsonl");
+        export_chunks_jsonl(
+            &root.join("lib"),
+            &out1,
+            false,
+            None,
+            DEFAULT_MAX_FILE_BYTES,
+        )
+        .unwrap();
+        export_chunks_jsonl(
+            &root.join("lib"),
+            &out2,
+            false,
+            None,
+            DEFAULT_MAX_FILE_BYTES,
+        )
+        .unwrap();
+        let (a, b) = (std::fs::read(&out1).unwrap(), std::fs::read(&out2).unwrap());
+
+        assert!(!a.is_empty());
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn export_counts_chunks_per_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::create_dir_all(root.join("android")).unwrap();
+        std::fs::write(
+            root.join("lib/main.dart"),
+            "class App {\n  void run() {}\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("lib/util.dart"), "int twice(int x) => x * 2;\n").unwrap();
+        std::fs::write(root.join("android/Main.kt"), "class Main {}\n").unwrap();
+        std::fs::write(root.join("pubspec.yaml"), "name: app\n").unwrap();
+
+        let out = root.join("out.jsonl");
+        let counts = export_chunks_jsonl(root, &out, false, None, DEFAULT_MAX_FILE_BYTES).unwrap();
+        let written: Vec<CodeChunk> = std::fs::read_to_string(&out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+
+        assert_eq!(counts.chunks, written.len());
+        assert_eq!(counts.by_language.values().sum::<usize>(), counts.chunks);
+        assert_eq!(counts.by_language, count_by_language(&written));
+        for lang in [LanguageKind::Dart, LanguageKind::Kotlin, LanguageKind::Yaml] {
+            assert!(counts.by_language[&lang] > 0, "no {lang:?} chunks");
+        }
+        let dart_files = written
+            .iter()
+            .filter(|c| c.language == LanguageKind::Dart)
+            .map(|c| c.file.as_str())
+            .collect::<std::collections::BTreeSet<_>>();
+        assert_eq!(dart_files.len(), 2);
+        assert_eq!(counts.skipped_oversized, 0);
+    }
+
+    #[test]
+    fn oversized_files_are_skipped_and_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(root.join("lib/main.dart"), "void main() {}\n").unwrap();
+        // Single-line generated bundle well over the limit.
+        let bundle = format!("var a = [{}];\n", "1,".repeat(4096));
+        std::fs::write(root.join("lib/bundle.js"), bundle).unwrap();
 
-    Ok(out_path)
+        let index = index_project(root, false, None, 1024).unwrap();
+
+        assert_eq!(index.skipped_oversized, 1);
+        assert!(!index.chunks.is_empty());
+        assert!(index.chunks.iter().all(|c| !c.file.ends_with("bundle.js")));
+    }
+
+    #[test]
+    fn latin1_files_are_skipped_with_reason() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(root.join("lib/main.dart"), "void main() {}\n").unwrap();
+        // "// Café déjà vu" in Latin-1: accented letters are single invalid bytes.
+        std::fs::write(
+            root.join("lib/legacy.dart"),
+            b"// Caf\xe9 d\xe9j\xe0 vu\nclass Legacy {}\n",
+        )
+        .unwrap();
+
+        let index = index_project(root, false, None, DEFAULT_MAX_FILE_BYTES).unwrap();
+
+        assert_eq!(
+            index.skipped_undecodable,
+            vec![SkippedFile {
+                path: "lib/legacy.dart".into(),
+                reason: "non-utf8".into(),
+            }]
+        );
+        assert!(!index.chunks.is_empty());
+        assert!(
+            index
+                .chunks
+                .iter()
+                .all(|c| !c.file.ends_with("legacy.dart"))
+        );
+    }
+
+    #[test]
+    fn adjacent_methods_are_linked_as_neighbors() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("svc.dart"),
+            "class Svc {\n  void a() {}\n  void b() {}\n  void c() {}\n}\n\nvoid top() {}\n",
+        )
+        .unwrap();
+
+        let chunks = index_project(root, false, None, DEFAULT_MAX_FILE_BYTES)
+            .unwrap()
+            .chunks;
+
+        let by_symbol = |s: &str| chunks.iter().find(|c| c.symbol == s).unwrap();
+        let (svc, a, b, c) = (
+            by_symbol("Svc"),
+            by_symbol("a"),
+            by_symbol("b"),
+            by_symbol("c"),
+        );
+        let nb = |c: &CodeChunk| c.neighbors.clone().unwrap();
+
+        // Siblings within the class scope only; the class is the parent, not `prev`.
+        assert_eq!(nb(a).prev_id, None);
+        assert_eq!(nb(a).next_id.as_deref(), Some(b.id.as_str()));
+        assert_eq!(nb(b).prev_id.as_deref(), Some(a.id.as_str()));
+        assert_eq!(nb(b).next_id.as_deref(), Some(c.id.as_str()));
+        assert_eq!(nb(c).next_id, None);
+        assert_eq!(nb(b).parent_id.as_deref(), Some(svc.id.as_str()));
+        assert_eq!(nb(svc).children_ids.len(), 3);
+        assert_eq!(
+            nb(svc).next_id.as_deref(),
+            Some(by_symbol("top").id.as_str())
+        );
+    }
+
+    #[test]
+    fn chunk_ids_do_not_depend_on_root_spelling() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("lib")).unwrap();
+        std::fs::write(
+            root.join("lib/svc.dart"),
+            "class Svc {\n  void a() {}\n  void b() {}\n}\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("pubspec.yaml"), "name: app\n").unwrap();
+
+        let cwd = std::env::current_dir().unwrap();
+        let relative = pathdiff::diff_paths(root, &cwd).unwrap();
+        assert!(relative.is_relative());
+        let ids = |base: &Path| {
+            index_project(base, false, None, DEFAULT_MAX_FILE_BYTES)
+                .unwrap()
+                .chunks
+                .into_iter()
+                .map(|c| (c.id, c.neighbors.map(|n| n.next_id)))
+                .collect::<Vec<_>>()
+        };
+        let (abs_ids, rel_ids) = (ids(root), ids(&relative));
+
+        assert!(abs_ids.len() >= 3);
+        assert_eq!(abs_ids, rel_ids);
+    }
+
+    #[test]
+    fn update_matches_a_full_export() {
+        static DATA_ROOT: std::sync::LazyLock<tempfile::TempDir> =
+            std::sync::LazyLock::new(|| tempfile::tempdir().unwrap());
+        let root = services::data_root::pin_data_root(DATA_ROOT.path().to_path_buf());
+        let app = root.join("update_app");
+        std::fs::create_dir_all(app.join("conf")).unwrap();
+        std::fs::write(app.join("conf/a.yaml"), "name: a\n").unwrap();
+        std::fs::write(app.join("conf/b.yaml"), "name: b\n").unwrap();
+        std::fs::write(app.join("conf/keep.yaml"), "name: keep\n").unwrap();
+        index_project_to_jsonl("update_app", false, None, None).unwrap();
+
+        std::fs::write(app.join("conf/a.yaml"), "name: a2\nversion: 2\n").unwrap();
+        std::fs::remove_file(app.join("conf/b.yaml")).unwrap();
+        std::fs::write(app.join("conf/c.yaml"), "name: c\n").unwrap();
+        let changed: BTreeSet<String> = ["a.yaml", "b.yaml", "c.yaml"]
+            .iter()
+            .map(|f| chunk_file_key(&app.join("conf").join(f).to_string_lossy()))
+            .collect();
+
+        let updated = update_project_jsonl("update_app", &changed, false, None, None).unwrap();
+        let incremental = std::fs::read(&updated.out_path).unwrap();
+        let full = index_project_to_jsonl("update_app", false, None, None).unwrap();
+
+        assert_eq!(updated.total_chunks, full.total_chunks);
+        assert_eq!(incremental, std::fs::read(&full.out_path).unwrap());
+        let text = String::from_utf8(incremental).unwrap();
+        assert!(text.contains("a2") && text.contains("c.yaml") && text.contains("keep.yaml"));
+        assert!(!text.contains("b.yaml"));
+    }
 }
@@ -5,21 +5,23 @@
 mod client;
 mod merge;
 mod parse;
+mod pub_get;
 mod util;
 
 use crate::errors::{Error, Result};
 use crate::lsp::dart::client::{LspProcess, RpcMessage};
 use crate::lsp::dart::merge::merge_file_enrichment_into_chunks;
 use crate::lsp::dart::parse::{LspSymbolInfo, collect_from_document_symbol};
+use crate::lsp::dart::pub_get::{PubGetCache, force_pub_get_enabled, run_pub_get_all};
 use crate::lsp::dart::util::{
     abs_canonical, build_workspace_folders_json_abs, file_uri_abs, normalize_to_repo_key,
     parent_folder_set, repo_rel_key, uri_to_abs_path,
 };
 use crate::lsp::interface::LspProvider;
 use crate::types::CodeChunk;
+use crate::util::text::read_source;
 use serde_json::{Value, json};
 use std::collections::HashMap;
-use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
@@ -72,7 +74,7 @@ impl LspProvider for DartLsp {
 
         info!(unique_files = files_keys.len(), "DartLsp: files ready");
 
-        // 2) Discover workspaces and run `pub get`
+        // 2) Discover workspaces and run `pub get` (skipped while dependencies are unchanged)
         let workspaces = discover_workspaces_from_files(&files_abs_sorted);
         if workspaces.is_empty() {
             warn!("No `pubspec.yaml` found near chunk files; LSP may lack full context");
@@ -82,8 +84,17 @@ impl LspProvider for DartLsp {
                 debug!(workspace = %ws.display(), "workspace");
             }
         }
-        run_pub_get_all(&workspaces)?;
-        info!(workspaces = workspaces.len(), "pub get finished");
+        let pub_get = run_pub_get_all(&workspaces, force_pub_get_enabled())?;
+        let cache_hits = pub_get
+            .iter()
+            .filter(|(_, c)| *c == PubGetCache::Hit)
+            .count();
+        info!(
+            workspaces = workspaces.len(),
+            cache_hits,
+            cache_misses = pub_get.len() - cache_hits,
+            "pub get finished"
+        );
 
         // 3) Initialize LSP
         let root_uri = file_uri_abs(&repo_root_abs);
@@ -118,7 +129,7 @@ impl LspProvider for DartLsp {
                 .get(key)
                 .cloned()
                 .unwrap_or_else(|| file_uri_abs(abs));
-            let text = fs::read_to_string(abs).map_err(Error::from)?;
+            let text = read_source(abs)?;
 
             debug!(file=%key, uri = %uri, len = text.len(), "didOpen");
             client.send(&json!({
@@ -230,43 +241,6 @@ fn discover_workspaces_from_files(files_abs: &[PathBuf]) -> Vec<PathBuf> {
     out
 }
 
-fn run_pub_get_all(workspaces: &[PathBuf]) -> Result<()> {
-    if workspaces.is_empty() {
-        return Ok(());
-    }
-    for dir in workspaces {
-        let dir = util::abs_path(dir);
-        info!("pub get: {}", dir.display());
-
-        let flutter_ok = std::process::Command::new("flutter")
-            .arg("pub")
-            .arg("get")
-            .current_dir(&dir)
-            .status()
-            .map(|s| s.success())
-            .unwrap_or(false);
-
-        if !flutter_ok {
-            warn!(
-                "flutter pub get failed in {}, trying `dart pub get`",
-                dir.display()
-            );
-            let dart_ok = std::process::Command::new("dart")
-                .arg("pub")
-                .arg("get")
-                .current_dir(&dir)
-                .status()
-                .map(|s| s.success())
-                .unwrap_or(false);
-
-            if !dart_ok {
-                return Err(Error::LspProtocol("pub get failed"));
-            }
-        }
-    }
-    Ok(())
-}
-
 fn lsp_initialize(
     client: &mut LspProcess,
     root_uri: Option<String>,
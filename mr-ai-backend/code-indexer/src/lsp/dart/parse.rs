@@ -16,31 +16,54 @@ pub struct LspSymbolInfo {
     pub range: ByteRange, // absolute byte range in the file
 }
 
-// Convert (line, UTF-16 column) coming from LSP into a UTF-8 byte offset.
-fn line_col_utf16_to_byte_offset(text: &str, line: usize, col_utf16: usize) -> usize {
+/// Convert an LSP position (0-based line, UTF-16 code-unit column) into a UTF-8
+/// byte offset in `text`.
+///
+/// - Astral-plane characters (emoji etc.) count as two UTF-16 units.
+/// - A column pointing inside a surrogate pair snaps to the start of that char,
+///   so the result is always a valid `char` boundary.
+/// - Columns past the end of line clamp to the line end (before `\n`);
+///   lines past EOF clamp to `text.len()`.
+pub fn lsp_pos_to_byte(text: &str, line: usize, col_utf16: usize) -> usize {
     let mut byte_offs = 0usize;
     for (i, l) in text.split_inclusive('\n').enumerate() {
         if i == line {
-            let line_str = if l.ends_with('\n') {
-                &l[..l.len() - 1]
-            } else {
-                l
-            };
+            let line_str = l.strip_suffix('\n').unwrap_or(l);
             let mut u16_count = 0usize;
             for (byte_idx, ch) in line_str.char_indices() {
-                if u16_count >= col_utf16 {
+                let next = u16_count + ch.len_utf16();
+                if col_utf16 < next {
                     return byte_offs + byte_idx;
                 }
-                u16_count += ch.len_utf16();
+                u16_count = next;
             }
             return byte_offs + line_str.len();
         } else {
-            byte_offs += l.as_bytes().len();
+            byte_offs += l.len();
         }
     }
     text.len()
 }
 
+/// Convert an LSP `Range` JSON (`{start:{line,character}, end:{line,character}}`)
+/// into an absolute byte range. Missing end falls back to start.
+pub fn lsp_range_to_span(text: &str, range: &Value) -> ByteRange {
+    let get = |ptr: &str| {
+        range
+            .pointer(ptr)
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+    };
+    let sl = get("/start/line").unwrap_or(0);
+    let sc = get("/start/character").unwrap_or(0);
+    let el = get("/end/line").unwrap_or(sl);
+    let ec = get("/end/character").unwrap_or(sc);
+    ByteRange {
+        start_byte: lsp_pos_to_byte(text, sl, sc),
+        end_byte: lsp_pos_to_byte(text, el, ec),
+    }
+}
+
 /// Flatten DocumentSymbol result into a simple list.
 pub fn collect_from_document_symbol(res: &Value, text: &str, file_key: &str) -> Vec<LspSymbolInfo> {
     let mut out = Vec::<LspSymbolInfo>::new();
@@ -66,38 +89,18 @@ pub fn collect_from_document_symbol(res: &Value, text: &str, file_key: &str) ->
 
         let sig = detail.map(|d| crate::lsp::dart::util::first_line(d, 240));
 
-        // byte range from range (line/character → byte offset)
-        let (start_b, end_b) = if let Some(rr) = full {
-            let sl = rr
-                .pointer("/start/line")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as usize;
-            let sc = rr
-                .pointer("/start/character")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0) as usize;
-            let el = rr
-                .pointer("/end/line")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(sl as u64) as usize;
-            let ec = rr
-                .pointer("/end/character")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(sc as u64) as usize;
-            let sb = line_col_utf16_to_byte_offset(text, sl, sc);
-            let eb = line_col_utf16_to_byte_offset(text, el, ec);
-            (sb, eb)
-        } else {
-            (0, 0)
-        };
+        // byte range from range (line/UTF-16 character → byte offset)
+        let range = full
+            .map(|rr| lsp_range_to_span(text, rr))
+            .unwrap_or(ByteRange {
+                start_byte: 0,
+                end_byte: 0,
+            });
 
         out.push(LspSymbolInfo {
             name,
             signature: sig,
-            range: ByteRange {
-                start_byte: start_b,
-                end_byte: end_b,
-            },
+            range,
         });
 
         if let Some(children) = node.get("children") {
@@ -111,3 +114,65 @@ pub fn collect_from_document_symbol(res: &Value, text: &str, file_key: &str) ->
     trace!(file=%file_key, collected = out.len(), "documentSymbol flatten done");
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// UTF-16 column of `needle` on `line` (test helper mirroring what an LSP server sends).
+    fn utf16_col(text: &str, line: usize, needle: &str) -> usize {
+        let l = text.lines().nth(line).unwrap();
+        let byte = l.find(needle).unwrap();
+        l[..byte].encode_utf16().count()
+    }
+
+    #[test]
+    fn emoji_positions_map_to_char_boundaries() {
+        let text = "// 🚀 launch\nfinal s = '😀😀'; int x = 1;\n";
+        let col = utf16_col(text, 1, "int x");
+        let b = lsp_pos_to_byte(text, 1, col);
+        assert!(text[b..].starts_with("int x"));
+
+        // Column inside a surrogate pair snaps to the emoji start.
+        let emoji_col = utf16_col(text, 0, "🚀");
+        let b = lsp_pos_to_byte(text, 0, emoji_col + 1);
+        assert!(text.is_char_boundary(b));
+        assert!(text[b..].starts_with("🚀"));
+    }
+
+    #[test]
+    fn document_symbol_spans_align_with_source_bytes() {
+        let text = "/// Says hi 👋 to the 🌍\nclass Greeter {\n  String emoji = '🎉🎉🎉';\n  void greet() { print('hi 😀'); }\n}\n";
+        let greet_start = utf16_col(text, 3, "void greet");
+        let greet_end = text.lines().nth(3).unwrap().encode_utf16().count();
+        let res = json!([{
+            "name": "Greeter",
+            "range": {
+                "start": { "line": 1, "character": 0 },
+                "end": { "line": 4, "character": 1 }
+            },
+            "children": [{
+                "name": "greet",
+                "detail": "void greet()",
+                "range": {
+                    "start": { "line": 3, "character": greet_start },
+                    "end": { "line": 3, "character": greet_end }
+                }
+            }]
+        }]);
+
+        let syms = collect_from_document_symbol(&res, text, "lib/greeter.dart");
+        assert_eq!(syms.len(), 2);
+
+        let class = &syms[0].range;
+        assert!(text[class.start_byte..class.end_byte].starts_with("class Greeter {"));
+        assert!(text[class.start_byte..class.end_byte].ends_with('}'));
+
+        let greet = &syms[1].range;
+        assert_eq!(
+            &text[greet.start_byte..greet.end_byte],
+            "void greet() { print('hi 😀'); }"
+        );
+    }
+}
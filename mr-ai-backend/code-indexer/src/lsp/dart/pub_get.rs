@@ -0,0 +1,157 @@
+//! `pub get` per Dart workspace, cached across enrichment runs.
+//!
+//! A workspace is skipped when `.dart_tool/package_config.json` exists and the
+//! SHA-256 of `pubspec.yaml` + `pubspec.lock` matches the stamp written by the
+//! last successful `pub get` (`.dart_tool/mrai_pub_get.sha256`). Without a
+//! `pubspec.lock` there is nothing to compare, so `pub get` always runs.
+//! `CODE_INDEXER_FORCE_PUB_GET=true` runs it regardless of the cache.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use crate::errors::{Error, Result};
+use crate::lsp::dart::util;
+
+/// Env switch that bypasses the `pub get` cache.
+pub const FORCE_PUB_GET_ENV: &str = "CODE_INDEXER_FORCE_PUB_GET";
+
+/// Stamp file under `.dart_tool/` holding the dependency fingerprint.
+const STAMP_FILE: &str = "mrai_pub_get.sha256";
+
+/// Cache outcome of one workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PubGetCache {
+    /// Dependencies unchanged since the last run; `pub get` skipped.
+    Hit,
+    /// `pub get` ran (no stamp, changed lock, missing package config or forced).
+    Miss,
+}
+
+/// Whether [`FORCE_PUB_GET_ENV`] is set to `1`/`true`/`yes`/`on`.
+pub fn force_pub_get_enabled() -> bool {
+    std::env::var(FORCE_PUB_GET_ENV).is_ok_and(|v| {
+        matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// Runs `flutter pub get` (falling back to `dart pub get`) in every workspace
+/// whose dependencies changed; `force_pub_get` ignores the cache.
+pub fn run_pub_get_all(
+    workspaces: &[PathBuf],
+    force_pub_get: bool,
+) -> Result<Vec<(PathBuf, PubGetCache)>> {
+    run_pub_get_all_with(workspaces, force_pub_get, pub_get)
+}
+
+fn run_pub_get_all_with(
+    workspaces: &[PathBuf],
+    force_pub_get: bool,
+    mut run: impl FnMut(&Path) -> Result<()>,
+) -> Result<Vec<(PathBuf, PubGetCache)>> {
+    let mut out = Vec::with_capacity(workspaces.len());
+    for dir in workspaces {
+        let dir = util::abs_path(dir);
+        let fresh = dir.join(".dart_tool/package_config.json").exists()
+            && deps_fingerprint(&dir).is_some_and(|fp| read_stamp(&dir) == Some(fp));
+        if fresh && !force_pub_get {
+            info!(workspace = %dir.display(), "pub get: cache hit");
+            out.push((dir, PubGetCache::Hit));
+            continue;
+        }
+
+        info!(workspace = %dir.display(), force = force_pub_get, "pub get: cache miss");
+        run(&dir)?;
+        // `pub get` may have created or rewritten the lock; stamp what it left.
+        let stamped = deps_fingerprint(&dir)
+            .map(|fp| std::fs::write(dir.join(".dart_tool").join(STAMP_FILE), fp));
+        if let Some(Err(e)) = stamped {
+            warn!(workspace = %dir.display(), error = %e, "pub get: stamp not written");
+        }
+        out.push((dir, PubGetCache::Miss));
+    }
+    Ok(out)
+}
+
+/// `flutter pub get`, or `dart pub get` if flutter is missing or fails.
+fn pub_get(dir: &Path) -> Result<()> {
+    info!("pub get: {}", dir.display());
+    let ok = |tool: &str| {
+        Command::new(tool)
+            .arg("pub")
+            .arg("get")
+            .current_dir(dir)
+            .status()
+            .map(|s| s.success())
+            .unwrap_or(false)
+    };
+    if ok("flutter") {
+        return Ok(());
+    }
+    warn!(
+        "flutter pub get failed in {}, trying `dart pub get`",
+        dir.display()
+    );
+    if ok("dart") {
+        Ok(())
+    } else {
+        Err(Error::LspProtocol("pub get failed"))
+    }
+}
+
+/// Hex SHA-256 of `pubspec.yaml` and `pubspec.lock`; `None` without a lock.
+fn deps_fingerprint(dir: &Path) -> Option<String> {
+    let lock = std::fs::read(dir.join("pubspec.lock")).ok()?;
+    let mut h = Sha256::new();
+    h.update(std::fs::read(dir.join("pubspec.yaml")).unwrap_or_default());
+    h.update([0u8]);
+    h.update(lock);
+    Some(format!("{:x}", h.finalize()))
+}
+
+fn read_stamp(dir: &Path) -> Option<String> {
+    std::fs::read_to_string(dir.join(".dart_tool").join(STAMP_FILE))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_run_skips_pub_get_while_lock_is_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = dir.path();
+        std::fs::write(ws.join("pubspec.yaml"), "name: app\n").unwrap();
+        std::fs::write(ws.join("pubspec.lock"), "packages: {}\n").unwrap();
+
+        let mut calls = 0;
+        let mut fake_pub_get = |dir: &Path| {
+            calls += 1;
+            std::fs::create_dir_all(dir.join(".dart_tool")).unwrap();
+            std::fs::write(dir.join(".dart_tool/package_config.json"), "{}").unwrap();
+            Ok(())
+        };
+        let workspaces = vec![ws.to_path_buf()];
+        let outcome = |r: Result<Vec<(PathBuf, PubGetCache)>>| r.unwrap()[0].1;
+
+        let first = run_pub_get_all_with(&workspaces, false, &mut fake_pub_get);
+        assert_eq!(outcome(first), PubGetCache::Miss);
+        let second = run_pub_get_all_with(&workspaces, false, &mut fake_pub_get);
+        assert_eq!(outcome(second), PubGetCache::Hit);
+
+        std::fs::write(ws.join("pubspec.lock"), "packages: {http: 1.2.0}\n").unwrap();
+        let changed = run_pub_get_all_with(&workspaces, false, &mut fake_pub_get);
+        assert_eq!(outcome(changed), PubGetCache::Miss);
+
+        let forced = run_pub_get_all_with(&workspaces, true, &mut fake_pub_get);
+        assert_eq!(outcome(forced), PubGetCache::Miss);
+        assert_eq!(calls, 3);
+    }
+}
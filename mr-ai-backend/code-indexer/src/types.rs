@@ -25,7 +25,7 @@ use std::collections::{BTreeMap, BTreeSet};
 /// Language discriminator for chunks/files.
 ///
 /// Keep it stable. If a language is missing, use `Other` and pass details in `extras`.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LanguageKind {
     // Common app/backend
@@ -371,6 +371,12 @@ pub struct CodeChunk {
     /// - Use namespaced keys, e.g., "dart.is_widget", "rust.unsafe_blocks", "python.decorators".
     /// - Keep it small and essential for retrieval/explainability.
     pub extras: Option<serde_json::Value>,
+
+    /// True when the chunk comes from a coarse heuristic extractor (e.g. the
+    /// Kotlin/Swift regex fallback) rather than a real parser. Spans and kinds
+    /// are approximate; downstream should rank/anchor such chunks cautiously.
+    #[serde(default)]
+    pub low_fidelity: bool,
 }
 
 /// Secondary slicing for long bodies (optional, language-agnostic).
@@ -0,0 +1,97 @@
+//! Chunk ids that don't depend on how a file path was spelled.
+//!
+//! Providers only see the path they were handed, which may be absolute,
+//! relative to the working directory, `./`-prefixed or `\`-separated. Ids are
+//! therefore recomputed from the canonical repo-relative path (see
+//! [`canonical_rel_path`]) once the repo root is known, so incremental ingest
+//! sees the same id for the same symbol.
+
+use std::collections::HashMap;
+use std::path::{Component, Path};
+
+use sha2::{Digest, Sha256};
+
+use crate::types::{CodeChunk, Span};
+
+/// Stable chunk id based on file + symbol path + byte span.
+pub fn make_id(file: &str, symbol_path: &str, sp: &Span) -> String {
+    let mut h = Sha256::new();
+    h.update(file.as_bytes());
+    h.update(symbol_path.as_bytes());
+    h.update(sp.start_byte.to_le_bytes());
+    h.update(sp.end_byte.to_le_bytes());
+    format!("{:x}", h.finalize())
+}
+
+/// Repo-relative, `/`-separated form of `file` (e.g. `lib/main.dart`).
+///
+/// Tries a lexical prefix match against `root` first, then the canonicalized
+/// paths (symlinks, `..`). Paths outside `root` keep their own components.
+pub fn canonical_rel_path(root: &Path, file: &str) -> String {
+    let p = Path::new(file);
+    let rel = p
+        .strip_prefix(root)
+        .ok()
+        .map(Path::to_path_buf)
+        .or_else(|| {
+            let root = std::fs::canonicalize(root).ok()?;
+            let abs = std::fs::canonicalize(p).ok()?;
+            abs.strip_prefix(&root).ok().map(Path::to_path_buf)
+        });
+    let rel = rel.as_deref().unwrap_or(p);
+    rel.components()
+        .filter_map(|c| match c {
+            Component::Normal(s) => Some(s.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+        .replace('\\', "/")
+}
+
+/// Recomputes ids of one file's chunks from the canonical path under `root`.
+///
+/// `symbol_path` values that start with the raw file path are hashed with the
+/// canonical path instead. Neighbor links are rewritten to the new ids.
+pub fn rekey_chunks(root: &Path, chunks: &mut [CodeChunk]) {
+    let mut renamed = HashMap::<String, String>::new();
+    for c in chunks.iter_mut() {
+        let rel = canonical_rel_path(root, &c.file);
+        let symbol_path = match c.symbol_path.strip_prefix(c.file.as_str()) {
+            Some(rest) => format!("{rel}{rest}"),
+            None => c.symbol_path.clone(),
+        };
+        let id = make_id(&rel, &symbol_path, &c.span);
+        renamed.insert(std::mem::replace(&mut c.id, id.clone()), id);
+    }
+
+    let remap = |id: &mut String| {
+        if let Some(new) = renamed.get(id.as_str()) {
+            id.clone_from(new);
+        }
+    };
+    for nb in chunks.iter_mut().filter_map(|c| c.neighbors.as_mut()) {
+        nb.parent_id.iter_mut().for_each(remap);
+        nb.prev_id.iter_mut().for_each(remap);
+        nb.next_id.iter_mut().for_each(remap);
+        nb.children_ids.iter_mut().for_each(remap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings_of_one_file_share_a_canonical_path() {
+        let root = Path::new("/repo");
+        assert_eq!(canonical_rel_path(root, "/repo/lib/a.dart"), "lib/a.dart");
+        assert_eq!(canonical_rel_path(root, "/repo/./lib/a.dart"), "lib/a.dart");
+        assert_eq!(canonical_rel_path(root, "lib\\a.dart"), "lib/a.dart");
+        assert_eq!(canonical_rel_path(root, "./lib/a.dart"), "lib/a.dart");
+        assert_eq!(
+            canonical_rel_path(Path::new("./repo"), "./repo/lib/a.dart"),
+            "lib/a.dart"
+        );
+    }
+}
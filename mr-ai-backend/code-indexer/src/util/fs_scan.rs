@@ -3,9 +3,24 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use tracing::warn;
 use walkdir::WalkDir;
 
-pub fn scan_project_files(root: &Path) -> Vec<PathBuf> {
+/// Default size limit for scanned files (1 MiB). Larger files are usually
+/// generated (bundles, lockfiles) and can stall tree-sitter.
+pub const DEFAULT_MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Files selected for indexing plus the number skipped for size.
+#[derive(Debug, Clone, Default)]
+pub struct ScanOutcome {
+    pub files: Vec<PathBuf>,
+    /// Supported files larger than the size limit (logged as warnings).
+    pub skipped_oversized: usize,
+}
+
+/// Recursively collect supported code/config files under `root`, skipping
+/// excluded directories, generated Dart files and files over `max_file_bytes`.
+pub fn scan_project_files(root: &Path, max_file_bytes: u64) -> ScanOutcome {
     const CODE_EXT: &[&str] = &[
         "dart", "kt", "kts", "swift", "ts", "tsx", "js", "jsx", "java",
     ];
@@ -34,8 +49,13 @@ pub fn scan_project_files(root: &Path) -> Vec<PathBuf> {
         ".gradle",
     ];
 
-    let mut out = Vec::new();
-    for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+    let mut out = ScanOutcome::default();
+    // Sorted walk: scan order must not depend on the platform/filesystem.
+    for entry in WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(Result::ok)
+    {
         if !entry.file_type().is_file() {
             continue;
         }
@@ -62,9 +82,22 @@ pub fn scan_project_files(root: &Path) -> Vec<PathBuf> {
         }
 
         let ext = p.extension().and_then(|x| x.to_str()).unwrap_or("");
-        if CODE_EXT.contains(&ext) || CONF_EXT.contains(&ext) {
-            out.push(p.to_path_buf());
+        if !(CODE_EXT.contains(&ext) || CONF_EXT.contains(&ext)) {
+            continue;
+        }
+
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        if size > max_file_bytes {
+            warn!(
+                file = %p.display(),
+                size,
+                max_file_bytes,
+                "scan: skipping oversized file"
+            );
+            out.skipped_oversized += 1;
+            continue;
         }
+        out.files.push(p.to_path_buf());
     }
     out
 }
@@ -1,6 +1,8 @@
+pub mod chunk_id;
 pub mod fs_scan;
 pub mod jsonl;
 pub mod microchunk;
+pub mod text;
 
 use crate::errors::{Error, Result};
 use std::path::Path;
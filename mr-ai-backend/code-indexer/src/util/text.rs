@@ -0,0 +1,40 @@
+//! Reading source files as text.
+//!
+//! Files that aren't valid UTF-8 (e.g. Latin-1 sources) are skipped by
+//! [`crate::index_project`] and reported with a reason, unless
+//! `CODE_INDEXER_LOSSY_DECODE=true` asks for best-effort indexing; providers
+//! then see invalid bytes replaced with U+FFFD.
+
+use std::path::Path;
+
+use crate::errors::Result;
+
+/// Env switch for lossy decoding of non-UTF-8 files.
+pub const LOSSY_DECODE_ENV: &str = "CODE_INDEXER_LOSSY_DECODE";
+
+/// Whether [`LOSSY_DECODE_ENV`] is set to `1`/`true`/`yes`/`on`.
+pub fn lossy_decode_enabled() -> bool {
+    std::env::var(LOSSY_DECODE_ENV).is_ok_and(|v| {
+        matches!(
+            v.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+/// `bytes` as text, with invalid UTF-8 sequences replaced by U+FFFD, and
+/// why they aren't valid UTF-8, if so.
+pub fn decode_source(bytes: Vec<u8>) -> (String, Option<&'static str>) {
+    match String::from_utf8(bytes) {
+        Ok(s) => (s, None),
+        Err(e) => (
+            String::from_utf8_lossy(e.as_bytes()).into_owned(),
+            Some("non-utf8"),
+        ),
+    }
+}
+
+/// File contents as text; invalid UTF-8 sequences become U+FFFD.
+pub fn read_source(path: &Path) -> Result<String> {
+    Ok(decode_source(std::fs::read(path)?).0)
+}
@@ -4,7 +4,8 @@
 //! - Read file contents safely (size was already checked during scanning);
 //! - Initialize a Tree-sitter parser for the selected language;
 //! - Parse and delegate to the language-specific extractor;
-//! - Append extracted nodes into `out`.
+//! - Append extracted nodes into `out`;
+//! - Report syntax errors Tree-sitter recovered from (see [`ParseReport`]).
 //!
 //! Note: we create a new `Parser` per call for simplicity. If needed later,
 //! add a small per-thread parser pool.
@@ -17,15 +18,24 @@ use crate::{
 };
 use anyhow::{Context, Result};
 use std::fs;
-use tracing::{debug, error, info};
-use tree_sitter::Parser;
+use tracing::{debug, error, info, warn};
+use tree_sitter::{Node, Parser};
+
+/// Outcome of a successful [`parse_and_extract`] call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    /// 1-based line of the first syntax error. Tree-sitter recovers and
+    /// extraction still runs, but nodes near the error may be missing or
+    /// have imprecise spans.
+    pub first_error_line: Option<usize>,
+}
 
 pub fn parse_and_extract(
     file: &ScannedFile,
     lang: LanguageKind,
     out: &mut Vec<AstNode>,
     config: &GraphConfig,
-) -> Result<()> {
+) -> Result<ParseReport> {
     debug!("parse: reading {}", file.path.display());
     let code = fs::read_to_string(&file.path)
         .with_context(|| format!("read_to_string {}", file.path.display()))?;
@@ -68,7 +78,31 @@ pub fn parse_and_extract(
     if let Err(e) = &res {
         error!("extract: failed for {}: {}", file.path.display(), e);
     }
-    res
+    res?;
+
+    let first_error_line = first_error_line(tree.root_node());
+    if let Some(line) = first_error_line {
+        warn!(
+            "parse: syntax error in {} at line {} (recovered)",
+            file.path.display(),
+            line
+        );
+    }
+    Ok(ParseReport { first_error_line })
+}
+
+/// Line (1-based) of the first ERROR/MISSING node under `node`, if any.
+fn first_error_line(node: Node) -> Option<usize> {
+    if !node.has_error() {
+        return None;
+    }
+    if node.is_error() || node.is_missing() {
+        return Some(node.start_position().row + 1);
+    }
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .find_map(first_error_line)
+        .or(Some(node.start_position().row + 1))
 }
 
 fn set_language(parser: &mut Parser, lang: LanguageKind) -> Result<()> {
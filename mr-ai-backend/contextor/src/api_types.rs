@@ -20,17 +20,41 @@ pub struct AskOptions {
     pub context_k: usize,
 }
 
+/// Which selection stage put a chunk into the context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkOrigin {
+    /// Among the top-scoring candidates; MMR kept it on relevance alone.
+    Retrieved,
+    /// Picked by MMR for diversity over a higher-scoring candidate.
+    MmrSelected,
+    /// Added by neighbor expansion around a selected chunk.
+    NeighborExpanded,
+}
+
+impl ChunkOrigin {
+    /// Stable snake_case name for logs and API responses.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChunkOrigin::Retrieved => "retrieved",
+            ChunkOrigin::MmrSelected => "mmr_selected",
+            ChunkOrigin::NeighborExpanded => "neighbor_expanded",
+        }
+    }
+}
+
 /// A compact record of a context chunk that was fed to the LLM.
 ///
 /// # Example
 /// ```
-/// use contextor::UsedChunk;
+/// use contextor::{ChunkOrigin, UsedChunk};
 /// let c = UsedChunk {
 ///     score: 0.92,
 ///     source: Some("path/file.dart".into()),
 ///     fqn: Some("BaseHomePage::build".into()),
 ///     kind: Some("Method".into()),
+///     snippet: None,
 ///     text: "Widget build(BuildContext ctx) { ... }".into(),
+///     origin: ChunkOrigin::Retrieved,
 /// };
 /// assert!(c.score > 0.0);
 /// ```
@@ -42,18 +66,22 @@ pub struct UsedChunk {
     pub kind: Option<String>,
     pub snippet: Option<String>,
     pub text: String,
+    /// Why this chunk is in the context (retrieval, MMR or neighbor expansion).
+    pub origin: ChunkOrigin,
 }
 
 /// Final answer together with the exact context passed to the model.
 ///
 /// # Example
 /// ```
-/// use contextor::{QaAnswer, UsedChunk};
+/// use contextor::{ChunkOrigin, QaAnswer, UsedChunk};
 /// let qa = QaAnswer {
 ///     answer: "It is defined in BaseHomePage".into(),
 ///     context: vec![UsedChunk {
-///         score: 0.9, source: None, fqn: None, kind: None, text: "..." .into()
+///         score: 0.9, source: None, fqn: None, kind: None, snippet: None, text: "..." .into(),
+///         origin: ChunkOrigin::Retrieved,
 ///     }],
+///     dropped_context: 0,
 /// };
 /// assert!(!qa.answer.is_empty());
 /// ```
@@ -61,4 +89,6 @@ pub struct UsedChunk {
 pub struct QaAnswer {
     pub answer: String,
     pub context: Vec<UsedChunk>,
+    /// Retrieved chunks left out of the prompt to fit the token budget.
+    pub dropped_context: usize,
 }
@@ -2,9 +2,12 @@
 
 use std::sync::Arc;
 
+use crate::prompt;
 use ai_llm_service::service_profiles::LlmServiceProfiles;
 use rag_store::{DistanceKind, RagConfig, RagFilter};
 use serde_json::Value;
+use services::namespaces::{self, EmbeddingNamespace};
+use tracing::warn;
 
 /// Config bag for the gateway. All fields have defaults via `from_env`.
 #[derive(Clone, Debug)]
@@ -18,7 +21,12 @@ pub struct ContextorConfig {
     pub expand_neighbors: bool,
     pub neighbor_k: u64,
     pub score_floor: f32,
-    pub max_ctx_chars: usize,
+    /// Token budget for the whole prompt (system + question + context).
+    pub max_ctx_tokens: usize,
+    /// Capacity of the process-wide embedding LRU cache (0 disables it).
+    pub embed_cache_capacity: usize,
+    /// Questions answered at the same time by `ask_batch`.
+    pub batch_concurrency: usize,
 
     // Optional filter applied at first retrieval
     pub initial_filter: Option<RagFilter>,
@@ -27,6 +35,8 @@ pub struct ContextorConfig {
     pub qdrant_url: String,
     pub qdrant_collection: String,
     pub rag_exact: bool,
+    /// Embedding namespaces (`RAG_NAMESPACES`); empty = `qdrant_collection` only.
+    pub namespaces: Vec<EmbeddingNamespace>,
 }
 
 impl ContextorConfig {
@@ -60,13 +70,23 @@ impl ContextorConfig {
             expand_neighbors: env("EXPAND_NEIGHBORS", "true") == "true",
             neighbor_k: parse("NEIGHBOR_K", 6),
             score_floor: parse("SCORE_FLOOR", 0.0f32),
-            max_ctx_chars: parse("MAX_CTX_CHARS", 8500usize),
+            // `MAX_CTX_CHARS` is still honoured as a fallback, converted to tokens.
+            max_ctx_tokens: parse(
+                "MAX_CTX_TOKENS",
+                parse("MAX_CTX_CHARS", 8500usize) / prompt::CHARS_PER_TOKEN,
+            ),
+            embed_cache_capacity: parse("EMBED_CACHE_CAPACITY", 512usize),
+            batch_concurrency: parse("ASK_BATCH_CONCURRENCY", 4usize),
 
             initial_filter,
 
             qdrant_url: env("QDRANT_URL", "http://127.0.0.1:6333"),
             qdrant_collection: env("QDRANT_COLLECTION", "code_chunks"),
             rag_exact: env("RAG_EXACT_SEARCH", "false") == "true",
+            namespaces: namespaces::from_env().unwrap_or_else(|raw| {
+                warn!(value = %raw, "malformed RAG_NAMESPACES, using a single collection");
+                Vec::new()
+            }),
         }
     }
 
@@ -108,6 +128,10 @@ impl ContextorConfig {
             exact_search: self.rag_exact,
             embedding_dim,
             embedding_concurrency,
+            // Retrieval-only config: ingest-time dedup is not used here.
+            near_dup_threshold: None,
+            // Queries are short; windowing only matters for ingestion.
+            embed_windowing: Default::default(),
         }
     }
 }
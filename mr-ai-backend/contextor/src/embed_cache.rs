@@ -0,0 +1,242 @@
+//! Process-wide bounded LRU cache for query/candidate embeddings.
+//!
+//! `ask_with_opts` embeds the question and every MMR candidate without stored
+//! vectors on each call; follow-up questions in a chat repeat most of that text.
+//! [`CachedEmbedder`] wraps any provider and serves repeats from memory.
+//!
+//! Entries are keyed by model, dimension and text: namespaces embed the same
+//! text with different models, and their vectors must never be mixed.
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use rag_store::{EmbeddingsProvider, RagError};
+use tracing::debug;
+
+/// Bounded LRU keyed by a hash of (model, dimension, text).
+pub struct EmbedCache {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    capacity: usize,
+    /// key → (vector, last-use tick)
+    map: HashMap<u64, (Vec<f32>, u64)>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl EmbedCache {
+    /// Creates a cache holding at most `capacity` vectors (`0` disables caching).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                capacity,
+                ..Inner::default()
+            }),
+        }
+    }
+
+    /// Current capacity (`0` = disabled).
+    pub fn capacity(&self) -> usize {
+        self.inner.lock().unwrap().capacity
+    }
+
+    /// Changes the capacity, evicting least recently used entries past it.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut g = self.inner.lock().unwrap();
+        g.capacity = capacity;
+        while g.map.len() > capacity {
+            g.evict_lru();
+        }
+    }
+
+    fn get(&self, key: u64) -> Option<Vec<f32>> {
+        let mut g = self.inner.lock().unwrap();
+        g.tick += 1;
+        let tick = g.tick;
+        match g.map.get_mut(&key) {
+            Some((v, used)) => {
+                *used = tick;
+                let v = v.clone();
+                g.hits += 1;
+                Some(v)
+            }
+            None => {
+                g.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn put(&self, key: u64, v: Vec<f32>) {
+        let mut g = self.inner.lock().unwrap();
+        if g.capacity == 0 {
+            return;
+        }
+        if !g.map.contains_key(&key) && g.map.len() >= g.capacity {
+            g.evict_lru();
+        }
+        g.tick += 1;
+        let tick = g.tick;
+        g.map.insert(key, (v, tick));
+    }
+
+    /// Returns `(hits, misses)` since process start.
+    pub fn stats(&self) -> (u64, u64) {
+        let g = self.inner.lock().unwrap();
+        (g.hits, g.misses)
+    }
+
+    /// Logs the cumulative hit rate at debug level.
+    pub fn log_stats(&self) {
+        let capacity = self.capacity();
+        let (hits, misses) = self.stats();
+        let total = hits + misses;
+        let rate = if total == 0 {
+            0.0
+        } else {
+            hits as f64 / total as f64
+        };
+        debug!(
+            "embed cache: hits={} misses={} hit_rate={:.2} capacity={}",
+            hits, misses, rate, capacity
+        );
+    }
+}
+
+impl Inner {
+    /// Drops the least recently used entry (capacity is small; linear scan is fine).
+    fn evict_lru(&mut self) {
+        if let Some(old) = self
+            .map
+            .iter()
+            .min_by_key(|(_, (_, t))| *t)
+            .map(|(k, _)| *k)
+        {
+            self.map.remove(&old);
+        }
+    }
+}
+
+/// Returns the process-wide cache sized to `capacity` (the configured value
+/// of the latest caller; shrinking evicts the oldest entries).
+pub fn shared_cache(capacity: usize) -> Arc<EmbedCache> {
+    static CACHE: OnceLock<Arc<EmbedCache>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Arc::new(EmbedCache::new(capacity)));
+    if cache.capacity() != capacity {
+        cache.set_capacity(capacity);
+    }
+    cache.clone()
+}
+
+/// Embedding provider decorator that consults an [`EmbedCache`] first.
+pub struct CachedEmbedder<E> {
+    inner: E,
+    cache: Arc<EmbedCache>,
+    model: String,
+    dim: usize,
+}
+
+impl<E> CachedEmbedder<E> {
+    /// Wraps `inner`, which embeds with `model` into `dim`-sized vectors.
+    pub fn new(inner: E, cache: Arc<EmbedCache>, model: impl Into<String>, dim: usize) -> Self {
+        Self {
+            inner,
+            cache,
+            model: model.into(),
+            dim,
+        }
+    }
+
+    pub fn cache(&self) -> &EmbedCache {
+        &self.cache
+    }
+}
+
+fn key_of(model: &str, dim: usize, text: &str) -> u64 {
+    let mut h = DefaultHasher::new();
+    (model, dim, text).hash(&mut h);
+    h.finish()
+}
+
+impl<E: EmbeddingsProvider> EmbeddingsProvider for CachedEmbedder<E> {
+    fn embed<'a>(
+        &'a self,
+        text: &'a str,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<f32>, RagError>> + Send + 'a>>
+    {
+        Box::pin(async move {
+            if self.cache.capacity() == 0 {
+                return self.inner.embed(text).await;
+            }
+            let key = key_of(&self.model, self.dim, text);
+            if let Some(v) = self.cache.get(key) {
+                return Ok(v);
+            }
+            let v = self.inner.embed(text).await?;
+            self.cache.put(key, v.clone());
+            Ok(v)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let c = EmbedCache::new(2);
+        c.put(1, vec![1.0]);
+        c.put(2, vec![2.0]);
+        assert!(c.get(1).is_some()); // 1 is now most recent
+        c.put(3, vec![3.0]); // evicts 2
+        assert!(c.get(2).is_none());
+        assert_eq!(c.get(1), Some(vec![1.0]));
+        assert_eq!(c.get(3), Some(vec![3.0]));
+        assert_eq!(c.stats(), (3, 1));
+
+        c.set_capacity(1);
+        assert!(c.get(1).is_none());
+        assert_eq!(c.get(3), Some(vec![3.0]));
+    }
+
+    /// Echoes a per-model marker so cached vectors reveal which model made them.
+    struct Fixed(f32);
+
+    impl EmbeddingsProvider for Fixed {
+        fn embed<'a>(
+            &'a self,
+            _text: &'a str,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<Vec<f32>, RagError>> + Send + 'a>,
+        > {
+            Box::pin(async move { Ok(vec![self.0]) })
+        }
+    }
+
+    #[tokio::test]
+    async fn same_text_is_cached_per_model_and_dim() {
+        let cache = Arc::new(EmbedCache::new(8));
+        let code = CachedEmbedder::new(Fixed(1.0), cache.clone(), "bge-m3", 1024);
+        let docs = CachedEmbedder::new(Fixed(2.0), cache.clone(), "nomic-embed-text", 768);
+        let small = CachedEmbedder::new(Fixed(3.0), cache.clone(), "bge-m3", 512);
+
+        assert_eq!(code.embed("fn main").await.unwrap(), vec![1.0]);
+        assert_eq!(docs.embed("fn main").await.unwrap(), vec![2.0]);
+        assert_eq!(small.embed("fn main").await.unwrap(), vec![3.0]);
+        assert_eq!(code.embed("fn main").await.unwrap(), vec![1.0]);
+        assert_eq!(cache.stats(), (1, 3));
+    }
+
+    #[test]
+    fn shared_cache_follows_the_configured_capacity() {
+        assert_eq!(shared_cache(4).capacity(), 4);
+        assert_eq!(shared_cache(16).capacity(), 16);
+    }
+}
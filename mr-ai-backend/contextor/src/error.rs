@@ -16,6 +16,14 @@ pub enum ContextorError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// Chat completion failed.
+    #[error("LLM error: {0}")]
+    Llm(String),
+
+    /// A batch question could not run (shared setup failed or its task died).
+    #[error("batch error: {0}")]
+    Batch(String),
+
     /// Generic IO if needed by future extensions.
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
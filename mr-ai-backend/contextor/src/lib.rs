@@ -1,12 +1,17 @@
 //! RAG + LLM gateway with a single public function.
 //!
-//! Public API: [`ask`]. It embeds the question, retrieves top-K context from
+//! Public API: [`ask`] (and [`ask_batch`] for several questions). It embeds the question, retrieves top-K context from
 //! `rag-store`, runs MMR selection (keeps strong #2), optionally expands with
 //! neighbors from the same source/FQN, builds a compact prompt, calls Ollama,
 //! and returns the model answer.
+//!
+//! With `RAG_NAMESPACES`, each namespace collection is queried with the
+//! embedding model and dimension recorded on it, and the best picks of all
+//! namespaces make up the context.
 
 mod api_types;
 mod cfg;
+mod embed_cache;
 mod error;
 mod progress;
 mod prompt;
@@ -14,17 +19,23 @@ mod retrieve;
 mod select;
 
 use std::sync::Arc;
+use std::time::Instant;
+
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
 
 use ai_llm_service::service_profiles::LlmServiceProfiles;
-pub use api_types::{AskOptions, QaAnswer, UsedChunk};
+pub use api_types::{AskOptions, ChunkOrigin, QaAnswer, UsedChunk};
 pub use error::ContextorError;
 pub use progress::{IndicatifProgress, NoopProgress, Progress};
 
 use cfg::ContextorConfig;
+use embed_cache::{CachedEmbedder, shared_cache};
 use rag_store::{
-    RagQuery, RagStore,
+    CollectionInfo, RagHit, RagQuery, RagStore,
     embed::ollama::{OllamaConfig, OllamaEmbedder},
 };
+use services::namespaces::EmbeddingNamespace;
 
 pub use retrieve::{RetrieveOptions, retrieve_with_opts};
 
@@ -77,96 +88,405 @@ pub async fn ask_with_opts(
 ) -> Result<QaAnswer, ContextorError> {
     let prog = IndicatifProgress::spinner();
 
-    // 1) Load config from env
+    // 1) Load config from env and create facades
     prog.message("loading config");
-    let gcfg = ContextorConfig::new(svc.clone());
+    let session = AskSession::new(svc).await?;
+    session.answer(question, &opts, &prog).await
+}
 
-    // Resolve effective knobs (0 => use env default)
-    let top_k = if opts.top_k == 0 {
-        gcfg.initial_top_k
-    } else {
-        opts.top_k
-    };
-    let context_k = if opts.context_k == 0 {
-        gcfg.context_k
-    } else {
-        opts.context_k
+/// Answer several questions with one store/embedder/chat setup.
+///
+/// Questions run concurrently, at most `ASK_BATCH_CONCURRENCY` (default 4)
+/// at a time. Results come back in input order, one per question, so a
+/// failing question doesn't affect the others. If the shared setup fails,
+/// every question gets that error.
+///
+/// # Example
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use contextor::{ask_batch, AskOptions};
+/// # async fn run(svc: Arc<ai_llm_service::service_profiles::LlmServiceProfiles>) {
+/// let questions = vec!["Where is gamesIcon defined?".to_string()];
+/// for res in ask_batch(svc, &questions, AskOptions::default()).await {
+///     println!("{:?}", res.map(|qa| qa.answer));
+/// }
+/// # }
+/// ```
+pub async fn ask_batch(
+    svc: Arc<LlmServiceProfiles>,
+    questions: &[String],
+    opts: AskOptions,
+) -> Vec<Result<QaAnswer, ContextorError>> {
+    let session = match AskSession::new(svc).await {
+        Ok(s) => Arc::new(s),
+        Err(e) => {
+            warn!(error = %e, "ask_batch: setup failed");
+            let msg = e.to_string();
+            return questions
+                .iter()
+                .map(|_| Err(ContextorError::Batch(msg.clone())))
+                .collect();
+        }
     };
 
-    // 2) Create facades
-    prog.step("creating store and clients");
-    let store = RagStore::new(gcfg.make_rag_config())?;
-    let emb_cfg = OllamaConfig {
-        svc: svc,
-        dim: gcfg.make_rag_config().embedding_dim.unwrap_or(1024),
-    };
-    let embedder = OllamaEmbedder::new(emb_cfg.clone());
-
-    // 3) Retrieve
-    prog.step("embedding + retrieving from qdrant");
-    let query = RagQuery {
-        text: question,
-        top_k,
-        filter: gcfg.initial_filter.clone(),
-    };
-    let mut hits = store.rag_context(query, &embedder).await?;
-
-    // 4) MMR selection
-    prog.step("MMR selecting context");
-    let selected =
-        select::mmr_select(question, &embedder, &mut hits, context_k, gcfg.mmr_lambda).await?;
-
-    // 5) Optional neighbor expansion
-    let expanded = if gcfg.expand_neighbors {
-        select::maybe_expand_neighbors(
-            &store,
-            &embedder,
-            &selected,
-            gcfg.neighbor_k,
-            gcfg.score_floor,
-        )
-        .await?
-    } else {
-        selected
-    };
+    let out = answer_all(questions, session.gcfg.batch_concurrency, |question| {
+        let session = session.clone();
+        let opts = opts.clone();
+        async move { session.answer(&question, &opts, &NoopProgress).await }
+    })
+    .await;
+    session.log_cache_stats();
+    info!(
+        questions = out.len(),
+        failed = out.iter().filter(|r| r.is_err()).count(),
+        "ask_batch: done"
+    );
+    out
+}
 
-    // 6) Build prompts + chat
-    prog.step("building prompts");
-    let system_prompt = prompt::DEFAULT_SYSTEM;
-    let user_prompt = prompt::build_user_prompt(question, &expanded, gcfg.max_ctx_chars);
-    prog.step("chatting with model");
-    let prompt = format!("{}\n{}", system_prompt, &user_prompt);
-    let answer = emb_cfg
-        .svc
-        .generate_slow(&prompt, None)
-        .await
-        .expect("Failed to ask");
-
-    // 7) Convert used context for callers
-    prog.finish("done");
-    let context = expanded
-        .into_iter()
-        .map(|h| {
-            // Prefer snippet if present, otherwise `text`. Clamp for transport/UI.
-            let snippet = if h.snippet.is_some() {
-                Some(rag_store::record::clamp_snippet(
-                    &h.snippet.unwrap(),
-                    800,
-                    20,
-                ))
-            } else {
-                None
+/// A collection to retrieve from, with the embedder its vectors were built with.
+struct Scope {
+    store: RagStore,
+    embedder: CachedEmbedder<OllamaEmbedder>,
+}
+
+/// Facades shared by every question of an [`ask_with_opts`] / [`ask_batch`] call.
+pub(crate) struct AskSession {
+    pub(crate) gcfg: ContextorConfig,
+    /// One scope per namespace, or just the configured collection.
+    scopes: Vec<Scope>,
+}
+
+impl AskSession {
+    pub(crate) async fn new(svc: Arc<LlmServiceProfiles>) -> Result<Self, ContextorError> {
+        let gcfg = ContextorConfig::new(svc.clone());
+        let base = gcfg.make_rag_config();
+        let embedder = |dim: usize, model: Option<String>| {
+            let mut e = OllamaEmbedder::new(OllamaConfig {
+                svc: svc.clone(),
+                dim,
+            });
+            let key_model = match model {
+                Some(model) => {
+                    e = e.with_model(model.clone());
+                    model
+                }
+                None => svc.profiles().2.model.clone(),
+            };
+            let cache = shared_cache(gcfg.embed_cache_capacity);
+            CachedEmbedder::new(e, cache, key_model, dim)
+        };
+
+        let mut scopes = Vec::new();
+        if gcfg.namespaces.is_empty() {
+            scopes.push(Scope {
+                store: RagStore::new(base.clone())?,
+                embedder: embedder(base.embedding_dim.unwrap_or(1024), None),
+            });
+        }
+        for ns in &gcfg.namespaces {
+            let mut cfg = base.clone();
+            cfg.collection = ns.collection(&base.collection);
+            cfg.embedding_dim = Some(ns.dim);
+            let store = RagStore::new(cfg)?;
+            let (model, dim) = namespace_embedder(ns, &store.collection_info().await?);
+            info!(namespace = %ns.name, %model, dim, "ask: namespace embedder");
+            scopes.push(Scope {
+                store,
+                embedder: embedder(dim, Some(model)),
+            });
+        }
+
+        Ok(Self { gcfg, scopes })
+    }
+
+    fn log_cache_stats(&self) {
+        for scope in &self.scopes {
+            scope.embedder.cache().log_stats();
+        }
+    }
+
+    /// Retrieves `top_k` candidates per scope, MMR-selects `context_k` of them
+    /// and expands neighbors if enabled.
+    pub(crate) async fn retrieve(
+        &self,
+        question: &str,
+        top_k: u64,
+        context_k: usize,
+        prog: &dyn Progress,
+    ) -> Result<Vec<(RagHit, ChunkOrigin)>, ContextorError> {
+        let gcfg = &self.gcfg;
+
+        prog.step("embedding + retrieving from qdrant");
+        let mut selected = Vec::with_capacity(self.scopes.len());
+        for scope in &self.scopes {
+            let query = RagQuery {
+                text: question,
+                top_k,
+                filter: gcfg.initial_filter.clone(),
             };
-            api_types::UsedChunk {
-                score: h.score,
-                source: h.source,
-                fqn: h.fqn,
-                kind: h.kind,
-                snippet: snippet,
-                text: rag_store::record::clamp_snippet(&h.text, 800, 20),
+            let mut hits = scope.store.rag_context(query, &scope.embedder).await?;
+
+            prog.step("MMR selecting context");
+            selected.push(
+                select::mmr_select(
+                    question,
+                    &scope.embedder,
+                    &mut hits,
+                    context_k,
+                    gcfg.mmr_lambda,
+                )
+                .await?,
+            );
+        }
+        if selected.len() > 1 {
+            selected = select::keep_best_across(selected, context_k);
+        }
+
+        let mut out = Vec::new();
+        for (scope, picks) in self.scopes.iter().zip(selected) {
+            if gcfg.expand_neighbors {
+                out.extend(
+                    select::maybe_expand_neighbors(
+                        &scope.store,
+                        &scope.embedder,
+                        &picks,
+                        gcfg.neighbor_k,
+                        gcfg.score_floor,
+                    )
+                    .await?,
+                );
+            } else {
+                out.extend(picks);
             }
+        }
+        self.log_cache_stats();
+        Ok(out)
+    }
+
+    /// [`Self::answer_question`], counted in `contextor_calls_total{outcome}`
+    /// and timed in `contextor_latency_seconds`.
+    async fn answer(
+        &self,
+        question: &str,
+        opts: &AskOptions,
+        prog: &dyn Progress,
+    ) -> Result<QaAnswer, ContextorError> {
+        let started = Instant::now();
+        let res = self.answer_question(question, opts, prog).await;
+        let outcome = if res.is_ok() { "ok" } else { "error" };
+        metrics::counter!("contextor_calls_total", "outcome" => outcome).increment(1);
+        metrics::histogram!("contextor_latency_seconds").record(started.elapsed().as_secs_f64());
+        res
+    }
+
+    async fn answer_question(
+        &self,
+        question: &str,
+        opts: &AskOptions,
+        prog: &dyn Progress,
+    ) -> Result<QaAnswer, ContextorError> {
+        let gcfg = &self.gcfg;
+
+        // Resolve effective knobs (0 => use env default)
+        let top_k = if opts.top_k == 0 {
+            gcfg.initial_top_k
+        } else {
+            opts.top_k
+        };
+        let context_k = if opts.context_k == 0 {
+            gcfg.context_k
+        } else {
+            opts.context_k
+        };
+
+        // 2-4) Retrieve, MMR select, optional neighbor expansion
+        let expanded = self.retrieve(question, top_k, context_k, prog).await?;
+
+        let (expanded, origins): (Vec<_>, Vec<_>) = expanded.into_iter().unzip();
+
+        // 5) Build prompts + chat
+        prog.step("building prompts");
+        let system_prompt = prompt::DEFAULT_SYSTEM;
+        let built = prompt::build_user_prompt(
+            question,
+            &expanded,
+            gcfg.max_ctx_tokens,
+            prompt::estimate_tokens(system_prompt),
+        );
+        if !built.dropped.is_empty() {
+            info!(
+                kept = built.kept.len(),
+                dropped = built.dropped.len(),
+                budget_tokens = gcfg.max_ctx_tokens,
+                "context over budget; dropped lowest-score chunks"
+            );
+        }
+        prog.step("chatting with model");
+        let prompt = format!("{}\n{}", system_prompt, &built.prompt);
+        let answer = gcfg
+            .svc
+            .generate_slow(&prompt, None)
+            .await
+            .map_err(|e| ContextorError::Llm(e.to_string()))?;
+
+        // 6) Convert used context (only chunks that made it into the prompt) for callers
+        prog.finish("done");
+        let dropped_context = built.dropped.len();
+        let context = expanded
+            .into_iter()
+            .zip(origins)
+            .enumerate()
+            .filter(|(i, _)| built.kept.contains(i))
+            .map(|(_, (h, origin))| {
+                // Prefer snippet if present, otherwise `text`. Clamp for transport/UI.
+                let snippet = h
+                    .snippet
+                    .as_deref()
+                    .map(|s| rag_store::record::clamp_snippet(s, 800, 20));
+                api_types::UsedChunk {
+                    score: h.score,
+                    source: h.source,
+                    fqn: h.fqn,
+                    kind: h.kind,
+                    snippet,
+                    text: rag_store::record::clamp_snippet(&h.text, 800, 20),
+                    origin,
+                }
+            })
+            .collect();
+
+        Ok(api_types::QaAnswer {
+            answer,
+            context,
+            dropped_context,
+        })
+    }
+}
+
+/// Model and dimension to embed queries for `ns` with: what its collection
+/// recorded at index time, falling back to the namespace config.
+/// Runs `answer` for every question on its own task, at most `limit` at a
+/// time. Results keep input order; a failed or panicked task only affects
+/// its own entry.
+async fn answer_all<F, Fut>(
+    questions: &[String],
+    limit: usize,
+    answer: F,
+) -> Vec<Result<QaAnswer, ContextorError>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<QaAnswer, ContextorError>> + Send + 'static,
+{
+    let limit = Arc::new(Semaphore::new(limit.max(1)));
+    let handles: Vec<_> = questions
+        .iter()
+        .map(|q| {
+            let limit = limit.clone();
+            let fut = answer(q.clone());
+            tokio::spawn(async move {
+                let _permit = limit.acquire_owned().await.expect("semaphore never closed");
+                fut.await
+            })
         })
         .collect();
 
-    Ok(api_types::QaAnswer { answer, context })
+    let mut out = Vec::with_capacity(handles.len());
+    for h in handles {
+        out.push(match h.await {
+            Ok(res) => res,
+            Err(e) => Err(ContextorError::Batch(format!("question task failed: {e}"))),
+        });
+    }
+    out
+}
+
+fn namespace_embedder(ns: &EmbeddingNamespace, info: &CollectionInfo) -> (String, usize) {
+    (
+        info.model.clone().unwrap_or_else(|| ns.model.clone()),
+        info.dim.unwrap_or(ns.dim),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn batch_keeps_order_bounds_concurrency_and_isolates_failures() {
+        const LIMIT: usize = 2;
+        let questions: Vec<String> = ["a", "fail", "c", "panic", "e"]
+            .iter()
+            .map(|q| q.to_string())
+            .collect();
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let out = answer_all(&questions, LIMIT, |q| {
+            let (running, peak) = (running.clone(), peak.clone());
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                match q.as_str() {
+                    "fail" => Err(ContextorError::Llm("model down".into())),
+                    "panic" => panic!("task died"),
+                    _ => Ok(QaAnswer {
+                        answer: q.to_uppercase(),
+                        context: Vec::new(),
+                        dropped_context: 0,
+                    }),
+                }
+            }
+        })
+        .await;
+
+        let answers: Vec<Option<String>> = out
+            .iter()
+            .map(|r| r.as_ref().ok().map(|qa| qa.answer.clone()))
+            .collect();
+        assert_eq!(
+            answers,
+            vec![
+                Some("A".into()),
+                None,
+                Some("C".into()),
+                None,
+                Some("E".into())
+            ]
+        );
+        assert!(matches!(out[1], Err(ContextorError::Llm(_))));
+        assert!(matches!(out[3], Err(ContextorError::Batch(_))));
+        assert_eq!(peak.load(Ordering::SeqCst), LIMIT);
+    }
+
+    #[test]
+    fn namespaces_embed_with_the_recorded_model_and_dim() {
+        let ns = services::namespaces::parse("code|bge-m3|1024;docs|nomic-embed-text|768|markdown")
+            .unwrap();
+        let info = |model: Option<&str>, dim| CollectionInfo {
+            points: 1,
+            dim,
+            distance: None,
+            model: model.map(str::to_owned),
+        };
+
+        assert_eq!(
+            namespace_embedder(&ns[0], &info(Some("bge-m3"), Some(1024))),
+            ("bge-m3".to_string(), 1024)
+        );
+        // The docs collection was indexed with a newer model than configured.
+        assert_eq!(
+            namespace_embedder(&ns[1], &info(Some("nomic-embed-text:v1.5"), Some(768))),
+            ("nomic-embed-text:v1.5".to_string(), 768)
+        );
+        // Collections without metadata fall back to the namespace config.
+        assert_eq!(
+            namespace_embedder(&ns[1], &info(None, None)),
+            ("nomic-embed-text".to_string(), 768)
+        );
+    }
 }
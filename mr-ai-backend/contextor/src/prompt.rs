@@ -10,77 +10,163 @@ You are a precise code assistant. Be concise, cite files and symbol names when r
 Use the provided context as ground truth; if it is insufficient, say so and propose next steps.
 "#;
 
-/// Build final user prompt with a labeled context section and char budget.
+/// Rough characters-per-token ratio used by [`estimate_tokens`].
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// User prompt together with the chunks that made it in.
+#[derive(Debug, Clone)]
+pub struct BuiltPrompt {
+    pub prompt: String,
+    /// Indices into the input `hits` included in the prompt, in ranking order.
+    pub kept: Vec<usize>,
+    /// Indices into the input `hits` dropped to fit the budget.
+    pub dropped: Vec<usize>,
+}
+
+/// Approximate token count (`chars / CHARS_PER_TOKEN`, rounded up).
+///
+/// Deliberately pessimistic for code, which tokenizes denser than prose.
+pub fn estimate_tokens(s: &str) -> usize {
+    s.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Build final user prompt with a labeled context section and a token budget.
 ///
-/// The function compacts the context into at most `max_chars`, preserving
-/// the ranking order. For each hit, it shows a header with FQN and source,
-/// then includes `snippet` if available, otherwise `text`.
+/// `max_tokens` is the budget for the whole request; `reserved_tokens` covers
+/// what is sent alongside the user prompt (system instructions). The question
+/// and instructions are never truncated. Context chunks are kept or dropped
+/// whole: while the prompt is over budget, the lowest-score chunk is removed.
+/// Kept chunks stay in ranking order. For each hit it shows a header with FQN
+/// and source, then `snippet` if available, otherwise `text`.
 ///
 /// # Example
 /// ```
 /// # use rag_store::RagHit;
 /// # use contextor::prompt::build_user_prompt;
 /// let hits: Vec<RagHit> = vec![];
-/// let prompt = build_user_prompt("How to X?", &hits, 2000);
-/// assert!(prompt.contains("Question:"));
+/// let built = build_user_prompt("How to X?", &hits, 2000, 0);
+/// assert!(built.prompt.contains("Question:"));
 /// ```
-pub fn build_user_prompt(question: &str, hits: &[RagHit], max_chars: usize) -> String {
-    let mut out = String::new();
-    out.push_str("Question:\n");
-    out.push_str(question.trim());
-    out.push_str("\n\n");
-
-    if !hits.is_empty() {
-        out.push_str("Context (top-ranked):\n");
-        let mut budget = max_chars;
-
-        for (i, h) in hits.iter().enumerate() {
-            let header = format!(
-                "==[{}]== {} :: {} (score {:.3})\n",
-                i + 1,
-                h.fqn.as_deref().unwrap_or(""),
-                h.source.as_deref().unwrap_or(""),
-                h.score
-            );
-            let text = h
-                .snippet
-                .as_deref()
-                .unwrap_or_else(|| h.text.as_str())
-                .trim();
-
-            // stop if we exceed budget
-            if header.len() >= budget {
-                break;
-            }
-            out.push_str(&header);
-            budget -= header.len();
-
-            let take = budget.saturating_sub(2);
-            if text.len() > take {
-                out.push_str(safe_truncate(text, take));
-                out.push_str("\n…\n");
-                break;
-            } else {
-                out.push_str(text);
-                out.push('\n');
-                budget -= text.len() + 1;
-            }
+pub fn build_user_prompt(
+    question: &str,
+    hits: &[RagHit],
+    max_tokens: usize,
+    reserved_tokens: usize,
+) -> BuiltPrompt {
+    let mut head = String::new();
+    head.push_str("Question:\n");
+    head.push_str(question.trim());
+    head.push_str("\n\n");
+
+    if hits.is_empty() {
+        return BuiltPrompt {
+            prompt: head,
+            kept: Vec::new(),
+            dropped: Vec::new(),
+        };
+    }
+
+    const CONTEXT_LABEL: &str = "Context (top-ranked):\n";
+    const TRAILER: &str = "\nAnswer using only the context above when possible.\n";
+
+    let blocks: Vec<String> = hits.iter().enumerate().map(|(i, h)| render(i, h)).collect();
+    let fixed = reserved_tokens
+        + estimate_tokens(&head)
+        + estimate_tokens(CONTEXT_LABEL)
+        + estimate_tokens(TRAILER);
+    let mut used = fixed + blocks.iter().map(|b| estimate_tokens(b)).sum::<usize>();
+
+    // Lowest score first; ties drop the later-ranked chunk first.
+    let mut by_score: Vec<usize> = (0..hits.len()).collect();
+    by_score.sort_by(|&a, &b| hits[a].score.total_cmp(&hits[b].score).then(b.cmp(&a)));
+
+    let mut keep = vec![true; hits.len()];
+    let mut dropped = Vec::new();
+    for i in by_score {
+        if used <= max_tokens {
+            break;
         }
-        out.push('\n');
-        out.push_str("Answer using only the context above when possible.\n");
+        keep[i] = false;
+        used -= estimate_tokens(&blocks[i]);
+        dropped.push(i);
     }
+    dropped.sort_unstable();
 
-    out
+    let kept: Vec<usize> = (0..hits.len()).filter(|&i| keep[i]).collect();
+    let mut out = head;
+    if !kept.is_empty() {
+        out.push_str(CONTEXT_LABEL);
+        for (n, &i) in kept.iter().enumerate() {
+            // Renumber so labels stay contiguous after drops.
+            out.push_str(&render(n, &hits[i]));
+        }
+        out.push_str(TRAILER);
+    }
+
+    BuiltPrompt {
+        prompt: out,
+        kept,
+        dropped,
+    }
+}
+
+/// Header + body of one context chunk (`pos` is zero-based).
+fn render(pos: usize, h: &RagHit) -> String {
+    let text = h.snippet.as_deref().unwrap_or(h.text.as_str()).trim();
+    format!(
+        "==[{}]== {} :: {} (score {:.3})\n{}\n",
+        pos + 1,
+        h.fqn.as_deref().unwrap_or(""),
+        h.source.as_deref().unwrap_or(""),
+        h.score,
+        text
+    )
 }
 
-fn safe_truncate(s: &str, max: usize) -> &str {
-    if s.len() <= max {
-        s
-    } else {
-        let mut end = max;
-        while end > 0 && !s.is_char_boundary(end) {
-            end -= 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(score: f32, fqn: &str, body_len: usize) -> RagHit {
+        RagHit {
+            score,
+            text: "x".repeat(body_len),
+            snippet: None,
+            source: Some("lib/a.dart".into()),
+            language: None,
+            kind: None,
+            fqn: Some(fqn.into()),
+            tags: Vec::new(),
+            neighbors: Vec::new(),
+            metrics: None,
+            raw_payload: serde_json::Value::Null,
+            score_breakdown: None,
         }
-        &s[..end]
+    }
+
+    #[test]
+    fn oversized_context_drops_lowest_score_chunks_first() {
+        // Ranking order differs from score order to show drops follow score.
+        let hits = vec![
+            hit(0.90, "A", 400),
+            hit(0.40, "Low", 400),
+            hit(0.80, "B", 400),
+            hit(0.50, "Mid", 400),
+        ];
+        let question = "Where is the token refreshed?";
+        let system_tokens = estimate_tokens(DEFAULT_SYSTEM);
+
+        // Room for roughly two 400-char chunks (~110 tokens each with header).
+        let built = build_user_prompt(question, &hits, system_tokens + 300, system_tokens);
+
+        assert_eq!(built.dropped, vec![1, 3]);
+        assert_eq!(built.kept, vec![0, 2]);
+        assert!(built.prompt.contains(question));
+        assert!(built.prompt.contains("==[1]== A ::"));
+        assert!(built.prompt.contains("==[2]== B ::"));
+        assert!(!built.prompt.contains("Low"));
+        assert!(!built.prompt.contains("Mid"));
+        // Kept chunks are whole, never cut mid-text.
+        assert_eq!(built.prompt.matches(&"x".repeat(400)).count(), 2);
     }
 }
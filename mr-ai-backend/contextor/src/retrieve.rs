@@ -5,15 +5,11 @@
 
 use std::sync::Arc;
 
+use crate::AskSession;
 use crate::api_types::UsedChunk;
-use crate::cfg::ContextorConfig;
 use crate::error::ContextorError;
-use crate::select;
+use crate::progress::NoopProgress;
 use ai_llm_service::service_profiles::LlmServiceProfiles;
-use rag_store::{
-    RagQuery, RagStore,
-    embed::ollama::{OllamaConfig, OllamaEmbedder},
-};
 
 /// Options to control retrieval behavior. Zero values are replaced by env defaults.
 #[derive(Debug, Clone, Default)]
@@ -27,14 +23,15 @@ pub struct RetrieveOptions {
 /// Retrieve top context chunks (MMR-selected, optionally neighbor-expanded), no chat.
 ///
 /// This uses the same environment-driven config as `ask_with_opts`, including
-/// the embedder model and store connection.
+/// the embedder model, store connection and namespaces.
 pub async fn retrieve_with_opts(
     query_text: &str,
     opts: RetrieveOptions,
     svc: Arc<LlmServiceProfiles>,
 ) -> Result<Vec<UsedChunk>, ContextorError> {
-    // 1) Config
-    let gcfg = ContextorConfig::new(svc);
+    // 1) Config + facades (one store/embedder per namespace)
+    let session = AskSession::new(svc).await?;
+    let gcfg = &session.gcfg;
     let top_k = if opts.top_k == 0 {
         gcfg.initial_top_k
     } else {
@@ -46,44 +43,15 @@ pub async fn retrieve_with_opts(
         opts.context_k
     };
 
-    // 2) Facades
-    let store = RagStore::new(gcfg.make_rag_config())?;
-    let emb_cfg = OllamaConfig {
-        svc: gcfg.svc.clone(),
-        dim: gcfg.make_rag_config().embedding_dim.unwrap_or(1024),
-    };
-    let embedder = OllamaEmbedder::new(emb_cfg);
-
-    // 3) Retrieve
-    let query = RagQuery {
-        text: query_text,
-        top_k,
-        filter: gcfg.initial_filter.clone(),
-    };
-    let mut hits = store.rag_context(query, &embedder).await?;
-
-    // 4) MMR select
-    let selected =
-        select::mmr_select(query_text, &embedder, &mut hits, context_k, gcfg.mmr_lambda).await?;
-
-    // 5) Optional neighbor expansion
-    let expanded = if gcfg.expand_neighbors {
-        select::maybe_expand_neighbors(
-            &store,
-            &embedder,
-            &selected,
-            gcfg.neighbor_k,
-            gcfg.score_floor,
-        )
-        .await?
-    } else {
-        selected
-    };
+    // 2-5) Retrieve, MMR select, optional neighbor expansion
+    let expanded = session
+        .retrieve(query_text, top_k, context_k, &NoopProgress)
+        .await?;
 
     // 6) Convert for callers (clamped body)
     let items = expanded
         .into_iter()
-        .map(|h| {
+        .map(|(h, origin)| {
             let snippet = if h.snippet.is_some() {
                 Some(h.snippet.unwrap().clone())
                 // Some(rag_store::record::clamp_snippet(
@@ -101,6 +69,7 @@ pub async fn retrieve_with_opts(
                 kind: h.kind,
                 snippet: snippet,
                 text: rag_store::record::clamp_snippet(&h.text, 800, 100),
+                origin,
             }
         })
         .collect();
@@ -1,5 +1,6 @@
 //! Candidate selection (MMR) and neighbor expansion using rag-store.
 
+use crate::api_types::ChunkOrigin;
 use crate::error::ContextorError;
 use rag_store::{EmbeddingsProvider, RagFilter, RagHit, RagStore};
 use serde_json::json;
@@ -11,6 +12,10 @@ use serde_json::json;
 /// Setting `lambda` closer to 1.0 prefers relevance; closer to 0.0 prefers
 /// diversity.
 ///
+/// Each pick is tagged [`ChunkOrigin::Retrieved`] if it is among the top-N by
+/// score anyway, or [`ChunkOrigin::MmrSelected`] if MMR chose it over a
+/// higher-scoring candidate.
+///
 /// # Errors
 /// Propagates embedding errors from the provider.
 ///
@@ -35,7 +40,7 @@ pub async fn mmr_select(
     hits: &mut [RagHit],
     n: usize,
     lambda: f32,
-) -> Result<Vec<RagHit>, ContextorError> {
+) -> Result<Vec<(RagHit, ChunkOrigin)>, ContextorError> {
     let qvec = provider.embed(question).await?;
 
     // Precompute/collect candidate embeddings.
@@ -78,9 +83,57 @@ pub async fn mmr_select(
         remaining.retain(|&x| x != best);
     }
 
-    // Keep order by original score among selected.
+    // Keep order by original score among selected. `hits` is sorted by score,
+    // so any index past the pick count was promoted by MMR.
     selected.sort_by_key(|&i| std::cmp::Reverse((hits[i].score.to_bits(), i)));
-    Ok(selected.into_iter().map(|i| hits[i].clone()).collect())
+    let picked = selected.len();
+    Ok(selected
+        .into_iter()
+        .map(|i| {
+            let origin = if i < picked {
+                ChunkOrigin::Retrieved
+            } else {
+                ChunkOrigin::MmrSelected
+            };
+            (hits[i].clone(), origin)
+        })
+        .collect())
+}
+
+/// Keeps the `n` best-scoring picks across several namespaces' MMR
+/// selections, returned per namespace in their original order.
+///
+/// Each namespace runs MMR with its own embedder (vectors of different
+/// models can't be compared), so only the final scores are merged.
+pub fn keep_best_across(
+    per_namespace: Vec<Vec<(RagHit, ChunkOrigin)>>,
+    n: usize,
+) -> Vec<Vec<(RagHit, ChunkOrigin)>> {
+    let mut ranked: Vec<(usize, usize, f32)> = per_namespace
+        .iter()
+        .enumerate()
+        .flat_map(|(ns, picks)| {
+            picks
+                .iter()
+                .enumerate()
+                .map(move |(i, (h, _))| (ns, i, h.score))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.2.total_cmp(&a.2));
+    ranked.truncate(n);
+
+    per_namespace
+        .into_iter()
+        .enumerate()
+        .map(|(ns, picks)| {
+            picks
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| ranked.iter().any(|&(rn, ri, _)| rn == ns && ri == *i))
+                .map(|(_, p)| p)
+                .collect()
+        })
+        .collect()
 }
 
 fn mmr_gain(
@@ -131,7 +184,8 @@ fn cosine(a: &[f32], b: &[f32]) -> f32 {
 /// filter `source == <same>` or `fqn == <same>`.
 ///
 /// The result is deduplicated (by `{source,fqn,text}`), re-sorted by score,
-/// and trimmed to `~2 * selected.len()`.
+/// and trimmed to `~2 * selected.len()`. Selected hits keep their origin;
+/// added ones are tagged [`ChunkOrigin::NeighborExpanded`].
 ///
 /// # Errors
 /// Propagates `rag-store` errors from `search_by_vector` and embedding.
@@ -153,14 +207,14 @@ fn cosine(a: &[f32], b: &[f32]) -> f32 {
 pub async fn maybe_expand_neighbors(
     store: &RagStore,
     provider: &dyn EmbeddingsProvider,
-    selected: &[RagHit],
+    selected: &[(RagHit, ChunkOrigin)],
     neighbor_k: u64,
     score_floor: f32,
-) -> Result<Vec<RagHit>, ContextorError> {
+) -> Result<Vec<(RagHit, ChunkOrigin)>, ContextorError> {
     let mut out = Vec::new();
 
-    for h in selected {
-        out.push(h.clone());
+    for (h, origin) in selected {
+        out.push((h.clone(), *origin));
         if h.score < score_floor {
             continue;
         }
@@ -199,27 +253,33 @@ pub async fn maybe_expand_neighbors(
             .search_by_vector(vec, neighbor_k, filter, /*with_payload*/ true)
             .await?;
 
-        for (score, payload) in neighs {
-            let mut nh = payload_to_hit(payload);
-            nh.score = score;
-
-            // Dedup by tuple (source, fqn, text).
-            if out
-                .iter()
-                .any(|x| x.source == nh.source && x.fqn == nh.fqn && x.text == nh.text)
-            {
-                continue;
-            }
-            out.push(nh);
-        }
+        push_neighbors(&mut out, neighs);
     }
 
     // Sort by score and trim.
-    out.sort_by(|a, b| b.score.total_cmp(&a.score));
+    out.sort_by(|a, b| b.0.score.total_cmp(&a.0.score));
     out.truncate((selected.len() * 2).max(selected.len()));
     Ok(out)
 }
 
+/// Appends search results as [`ChunkOrigin::NeighborExpanded`] hits, skipping
+/// any already in `out` (by `{source,fqn,text}`) so selected chunks keep their tag.
+fn push_neighbors(out: &mut Vec<(RagHit, ChunkOrigin)>, neighs: Vec<(f32, serde_json::Value)>) {
+    for (score, payload) in neighs {
+        let mut nh = payload_to_hit(payload);
+        nh.score = score;
+
+        // Dedup by tuple (source, fqn, text).
+        if out
+            .iter()
+            .any(|(x, _)| x.source == nh.source && x.fqn == nh.fqn && x.text == nh.text)
+        {
+            continue;
+        }
+        out.push((nh, ChunkOrigin::NeighborExpanded));
+    }
+}
+
 fn payload_to_hit(payload: serde_json::Value) -> RagHit {
     use serde_json::Value as J;
 
@@ -277,5 +337,61 @@ fn payload_to_hit(payload: serde_json::Value) -> RagHit {
         neighbors: Vec::new(),
         metrics,
         raw_payload: payload,
+        score_breakdown: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expanded_neighbors_are_tagged_apart_from_selection() {
+        let payload = |text: &str| json!({"text": text, "source": "lib/a.dart", "fqn": "A::build"});
+        let selected = |text: &str, score: f32| {
+            let mut h = payload_to_hit(payload(text));
+            h.score = score;
+            h
+        };
+        let mut out = vec![
+            (selected("top", 0.9), ChunkOrigin::Retrieved),
+            (selected("diverse", 0.5), ChunkOrigin::MmrSelected),
+        ];
+
+        push_neighbors(
+            &mut out,
+            vec![(0.8, payload("diverse")), (0.7, payload("sibling"))],
+        );
+
+        let origins: Vec<_> = out.iter().map(|(h, o)| (h.text.as_str(), *o)).collect();
+        assert_eq!(
+            origins,
+            vec![
+                ("top", ChunkOrigin::Retrieved),
+                // Found again as a neighbor, but still reported as MMR's pick.
+                ("diverse", ChunkOrigin::MmrSelected),
+                ("sibling", ChunkOrigin::NeighborExpanded),
+            ]
+        );
+        assert_eq!(ChunkOrigin::NeighborExpanded.as_str(), "neighbor_expanded");
+    }
+
+    #[test]
+    fn best_picks_are_kept_across_namespaces() {
+        let pick = |text: &str, score: f32| {
+            let mut h = payload_to_hit(json!({"text": text, "source": text}));
+            h.score = score;
+            (h, ChunkOrigin::Retrieved)
+        };
+        // Scores from a 1024-dim code model and a 768-dim docs model.
+        let code = vec![pick("fn a", 0.9), pick("fn b", 0.3)];
+        let docs = vec![pick("readme", 0.6), pick("guide", 0.5)];
+
+        let kept = keep_best_across(vec![code, docs], 3);
+        let texts: Vec<Vec<&str>> = kept
+            .iter()
+            .map(|picks| picks.iter().map(|(h, _)| h.text.as_str()).collect())
+            .collect();
+        assert_eq!(texts, vec![vec!["fn a"], vec!["readme", "guide"]]);
     }
 }
@@ -0,0 +1,19 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+/// Runs `op` until it succeeds or `attempts` runs out.
+pub fn retry<T, E>(attempts: u32, mut op: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+    let mut n = 0;
+    loop {
+        match op() {
+            Ok(v) => return Ok(v),
+            Err(e) => {
+                n += 1;
+                if n >= attempts {
+                    return Err(e);
+                }
+                sleep(Duration::from_millis(100 * 2u64.pow(n)));
+            }
+        }
+    }
+}
@@ -1,25 +1,342 @@
-//! File-based cache for large diffs (JSON on disk).
+//! Cache for large diffs, with pluggable storage.
 //!
 //! Why cache?
 //! - Large MRs consume provider API limits and take time to parse.
 //! - Re-running the pipeline on the same `head_sha` should be O(1).
 //!
 //! Key (stable across re-runs): SHA256("{provider}:{project}:{iid}:{head_sha}")
-//! Layout: $MR_REVIEWER_CACHE_DIR/<provider>/<project_sanitized>/<iid>-<hash12>.json
-//! Default cache dir: "code_data/mr_cache" (co-located with your project artifacts).
+//!
+//! Backends (`MR_REVIEWER_CACHE_BACKEND`):
+//! - `fs` (default): JSON on disk.
+//!   Layout: $MR_REVIEWER_CACHE_DIR/<provider>/<project_sanitized>/<iid>-<hash12>.json
+//!   Default cache dir: "code_data/mr_cache" (co-located with your project artifacts).
+//! - `redis`: JSON values shared by every reviewer instance.
+//!   Key: `mr_reviewer:bundle:<provider>:<project>:<iid>-<hash12>`, expiring after
+//!   `MR_REVIEWER_CACHE_TTL_SECS` (default 7 days). Server: `MR_REVIEWER_CACHE_REDIS_URL`
+//!   (default `redis://127.0.0.1:6379`). One client and connection per URL is
+//!   shared by the whole process.
+//!
+//! An entry that no longer decodes (e.g. written by an older version) is a miss
+//! on either backend.
 
-use crate::errors::MrResult;
+use crate::errors::{CacheError, Error, MrResult};
 use crate::git_providers::types::CrBundle;
 use crate::git_providers::{ChangeRequestId, ProviderKind};
+use redis::AsyncCommands;
+use redis::aio::MultiplexedConnection;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
 use tokio::fs;
+use tracing::{debug, warn};
+
+/// Default expiry for remote cache entries (7 days).
+const DEFAULT_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Storage for step-1 bundles keyed by `(provider_kind, id, head_sha)`.
+///
+/// `store` always writes; the "is it large enough" decision stays in
+/// [`maybe_store_bundle`].
+pub trait BundleCache {
+    /// Returns the cached bundle, or `None` on a miss.
+    fn load(
+        &self,
+        kind: &ProviderKind,
+        id: &ChangeRequestId,
+        head_sha: &str,
+    ) -> impl Future<Output = MrResult<Option<CrBundle>>> + Send;
+
+    /// Stores (or overwrites) the bundle.
+    fn store(
+        &self,
+        kind: &ProviderKind,
+        id: &ChangeRequestId,
+        head_sha: &str,
+        bundle: &CrBundle,
+    ) -> impl Future<Output = MrResult<()>> + Send;
+}
+
+/// Filesystem backend (one JSON file per key).
+#[derive(Debug, Clone)]
+pub struct FsBundleCache {
+    root: PathBuf,
+}
+
+impl FsBundleCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Root from `MR_REVIEWER_CACHE_DIR` (default: `code_data/mr_cache`).
+    pub fn from_env() -> Self {
+        Self::new(cache_root())
+    }
+
+    /// Computes deterministic cache path for the bundle.
+    fn key_path(&self, kind: &ProviderKind, id: &ChangeRequestId, head_sha: &str) -> PathBuf {
+        self.root
+            .join(provider_dir(kind))
+            .join(sanitize(&id.project))
+            .join(format!(
+                "{}-{}.json",
+                id.iid,
+                &digest(kind, id, head_sha)[..12]
+            ))
+    }
+}
+
+impl BundleCache for FsBundleCache {
+    async fn load(
+        &self,
+        kind: &ProviderKind,
+        id: &ChangeRequestId,
+        head_sha: &str,
+    ) -> MrResult<Option<CrBundle>> {
+        let path = self.key_path(kind, id, head_sha);
+        if !Path::new(&path).exists() {
+            return Ok(None);
+        }
+        let data = fs::read(&path).await?;
+        Ok(decode_entry(&path.to_string_lossy(), &data))
+    }
+
+    async fn store(
+        &self,
+        kind: &ProviderKind,
+        id: &ChangeRequestId,
+        head_sha: &str,
+        bundle: &CrBundle,
+    ) -> MrResult<()> {
+        let path = self.key_path(kind, id, head_sha);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        let json = serde_json::to_vec(bundle)?;
+        fs::write(path, json).await?;
+        Ok(())
+    }
+}
+
+/// Redis backend, shared across reviewer instances.
+///
+/// Connection/command failures and undecodable entries are logged and treated
+/// as a miss (load) or a no-op (store), so the cache never fails a review.
+/// Clones share the client and its multiplexed connection, which is opened on
+/// first use and reopened after a failure.
+#[derive(Debug, Clone)]
+pub struct RedisBundleCache {
+    client: redis::Client,
+    conn: Arc<tokio::sync::Mutex<Option<MultiplexedConnection>>>,
+    ttl_secs: u64,
+}
+
+/// Redis caches built by [`RedisBundleCache::shared`], keyed by URL.
+static REDIS_CACHES: LazyLock<Mutex<HashMap<String, RedisBundleCache>>> =
+    LazyLock::new(Mutex::default);
+
+impl RedisBundleCache {
+    /// # Errors
+    /// [`CacheError::Redis`] if `url` is not a valid Redis URL.
+    pub fn new(url: &str, ttl_secs: u64) -> MrResult<Self> {
+        let client = redis::Client::open(url).map_err(CacheError::from)?;
+        Ok(Self {
+            client,
+            conn: Arc::default(),
+            ttl_secs,
+        })
+    }
+
+    /// The process-wide cache for `url` (built on first use), with `ttl_secs`.
+    ///
+    /// # Errors
+    /// [`CacheError::Redis`] if `url` is not a valid Redis URL.
+    pub fn shared(url: &str, ttl_secs: u64) -> MrResult<Self> {
+        let mut caches = REDIS_CACHES.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = match caches.get(url) {
+            Some(cache) => cache.clone(),
+            None => {
+                let cache = Self::new(url, ttl_secs)?;
+                caches.insert(url.to_string(), cache.clone());
+                cache
+            }
+        };
+        Ok(Self { ttl_secs, ..cache })
+    }
+
+    /// The shared connection, opened if there is none yet.
+    async fn connection(&self) -> Result<MultiplexedConnection, redis::RedisError> {
+        let mut slot = self.conn.lock().await;
+        if let Some(conn) = slot.as_ref() {
+            return Ok(conn.clone());
+        }
+        let conn = self.client.get_multiplexed_async_connection().await?;
+        *slot = Some(conn.clone());
+        Ok(conn)
+    }
+
+    /// Drops the shared connection so the next call reconnects.
+    async fn reset(&self) {
+        *self.conn.lock().await = None;
+    }
+
+    fn key(kind: &ProviderKind, id: &ChangeRequestId, head_sha: &str) -> String {
+        format!(
+            "mr_reviewer:bundle:{}:{}:{}-{}",
+            provider_dir(kind),
+            id.project,
+            id.iid,
+            &digest(kind, id, head_sha)[..12]
+        )
+    }
+
+    async fn try_load(&self, key: &str) -> Result<Option<Vec<u8>>, redis::RedisError> {
+        let mut conn = self.connection().await?;
+        let res = conn.get(key).await;
+        if res.is_err() {
+            self.reset().await;
+        }
+        res
+    }
+
+    async fn try_store(&self, key: &str, json: Vec<u8>) -> Result<(), redis::RedisError> {
+        let mut conn = self.connection().await?;
+        let res = conn.set_ex(key, json, self.ttl_secs).await;
+        if res.is_err() {
+            self.reset().await;
+        }
+        res
+    }
+}
+
+/// Bundle stored under `key` (a Redis key or a cache file); an undecodable
+/// entry (e.g. written by an older version) is logged and treated as a miss,
+/// so the bundle is refetched.
+fn decode_entry(key: &str, data: &[u8]) -> Option<CrBundle> {
+    match serde_json::from_slice(data) {
+        Ok(bundle) => Some(bundle),
+        Err(e) => {
+            warn!(
+                "cache: entry undecodable key={} err={} (treated as miss)",
+                key, e
+            );
+            None
+        }
+    }
+}
+
+impl BundleCache for RedisBundleCache {
+    async fn load(
+        &self,
+        kind: &ProviderKind,
+        id: &ChangeRequestId,
+        head_sha: &str,
+    ) -> MrResult<Option<CrBundle>> {
+        let key = Self::key(kind, id, head_sha);
+        match self.try_load(&key).await {
+            Ok(Some(data)) => Ok(decode_entry(&key, &data)),
+            Ok(None) => Ok(None),
+            Err(e) => {
+                warn!(
+                    "cache: redis load failed key={} err={} (treated as miss)",
+                    key, e
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    async fn store(
+        &self,
+        kind: &ProviderKind,
+        id: &ChangeRequestId,
+        head_sha: &str,
+        bundle: &CrBundle,
+    ) -> MrResult<()> {
+        let key = Self::key(kind, id, head_sha);
+        let json = serde_json::to_vec(bundle)?;
+        if let Err(e) = self.try_store(&key, json).await {
+            warn!("cache: redis store failed key={} err={} (skipped)", key, e);
+        }
+        Ok(())
+    }
+}
+
+/// Backend selected by configuration (enum dispatch, no trait objects).
+#[derive(Debug, Clone)]
+pub enum BundleCacheBackend {
+    Fs(FsBundleCache),
+    Redis(RedisBundleCache),
+}
+
+impl BundleCacheBackend {
+    /// Environment variables:
+    /// - `MR_REVIEWER_CACHE_BACKEND`: `fs` (default) or `redis`
+    /// - `MR_REVIEWER_CACHE_DIR` (fs)
+    /// - `MR_REVIEWER_CACHE_REDIS_URL`, `MR_REVIEWER_CACHE_TTL_SECS` (redis)
+    ///
+    /// # Errors
+    /// [`Error::Validation`] for an unknown backend, [`CacheError::Redis`] for a bad URL.
+    pub fn from_env() -> MrResult<Self> {
+        let backend = std::env::var("MR_REVIEWER_CACHE_BACKEND").unwrap_or_default();
+        match backend.trim().to_ascii_lowercase().as_str() {
+            "" | "fs" | "file" => Ok(Self::Fs(FsBundleCache::from_env())),
+            "redis" => {
+                let url = std::env::var("MR_REVIEWER_CACHE_REDIS_URL")
+                    .unwrap_or_else(|_| "redis://127.0.0.1:6379".into());
+                let ttl_secs = std::env::var("MR_REVIEWER_CACHE_TTL_SECS")
+                    .ok()
+                    .and_then(|v| v.trim().parse().ok())
+                    .unwrap_or(DEFAULT_TTL_SECS);
+                Ok(Self::Redis(RedisBundleCache::shared(&url, ttl_secs)?))
+            }
+            other => Err(Error::Validation(format!(
+                "MR_REVIEWER_CACHE_BACKEND={other} (expected fs|redis)"
+            ))),
+        }
+    }
+
+    /// Short backend name for logs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Fs(_) => "fs",
+            Self::Redis(_) => "redis",
+        }
+    }
+}
+
+impl BundleCache for BundleCacheBackend {
+    async fn load(
+        &self,
+        kind: &ProviderKind,
+        id: &ChangeRequestId,
+        head_sha: &str,
+    ) -> MrResult<Option<CrBundle>> {
+        match self {
+            Self::Fs(c) => c.load(kind, id, head_sha).await,
+            Self::Redis(c) => c.load(kind, id, head_sha).await,
+        }
+    }
+
+    async fn store(
+        &self,
+        kind: &ProviderKind,
+        id: &ChangeRequestId,
+        head_sha: &str,
+        bundle: &CrBundle,
+    ) -> MrResult<()> {
+        match self {
+            Self::Fs(c) => c.store(kind, id, head_sha, bundle).await,
+            Self::Redis(c) => c.store(kind, id, head_sha, bundle).await,
+        }
+    }
+}
 
 /// Returns the root directory for cache (env-overridable).
 fn cache_root() -> PathBuf {
     std::env::var("MR_REVIEWER_CACHE_DIR")
         .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("code_data/mr_cache"))
+        .unwrap_or_else(|_| services::data_root::data_root().join("mr_cache"))
 }
 
 /// Filesystem-safe replacement for project path (slashes → underscores).
@@ -27,37 +344,33 @@ fn sanitize(s: &str) -> String {
     s.replace('/', "_")
 }
 
-/// Computes deterministic cache path for the bundle.
-fn key_path(kind: &ProviderKind, id: &ChangeRequestId, head_sha: &str) -> PathBuf {
+fn provider_dir(kind: &ProviderKind) -> &'static str {
+    match kind {
+        ProviderKind::GitLab => "gitlab",
+        ProviderKind::GitHub => "github",
+        ProviderKind::Bitbucket => "bitbucket",
+    }
+}
+
+/// Hex SHA256 of the cache key tuple.
+fn digest(kind: &ProviderKind, id: &ChangeRequestId, head_sha: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(format!("{:?}:{}:{}:{}", kind, id.project, id.iid, head_sha));
-    let digest = format!("{:x}", hasher.finalize());
-    cache_root()
-        .join(match kind {
-            ProviderKind::GitLab => "gitlab",
-            ProviderKind::GitHub => "github",
-            ProviderKind::Bitbucket => "bitbucket",
-        })
-        .join(sanitize(&id.project))
-        .join(format!("{}-{}.json", id.iid, &digest[..12]))
+    format!("{:x}", hasher.finalize())
 }
 
-/// Loads bundle from cache if present.
+/// Loads bundle from the configured cache backend if present.
 pub async fn load_bundle(
     kind: &ProviderKind,
     id: &ChangeRequestId,
     head_sha: &str,
 ) -> MrResult<Option<CrBundle>> {
-    let path = key_path(kind, id, head_sha);
-    if !Path::new(&path).exists() {
-        return Ok(None);
-    }
-    let data = fs::read(&path).await?;
-    let bundle: CrBundle = serde_json::from_slice(&data)?;
-    Ok(Some(bundle))
+    BundleCacheBackend::from_env()?
+        .load(kind, id, head_sha)
+        .await
 }
 
-/// Stores bundle if considered "large".
+/// Stores bundle in the configured cache backend if considered "large".
 ///
 /// Heuristics:
 /// - many files (e.g. > 200)
@@ -82,11 +395,112 @@ pub async fn maybe_store_bundle(
         return Ok(());
     }
 
-    let path = key_path(kind, id, head_sha);
-    if let Some(dir) = path.parent() {
-        fs::create_dir_all(dir).await?;
+    let backend = BundleCacheBackend::from_env()?;
+    debug!(
+        "cache: store bundle files={} bytes={} backend={}",
+        files,
+        bytes,
+        backend.name()
+    );
+    backend.store(kind, id, head_sha, bundle).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backends_share_the_key_digest() {
+        let id = ChangeRequestId {
+            project: "group/app".into(),
+            iid: 42,
+        };
+        let fs = FsBundleCache::new("/tmp/mr_cache");
+        let path = fs.key_path(&ProviderKind::GitLab, &id, "abc");
+        let key = RedisBundleCache::key(&ProviderKind::GitLab, &id, "abc");
+
+        let file = path.file_name().unwrap().to_string_lossy().to_string();
+        assert_eq!(
+            path.parent().unwrap(),
+            Path::new("/tmp/mr_cache/gitlab/group_app")
+        );
+        assert_eq!(
+            format!(
+                "mr_reviewer:bundle:gitlab:group/app:{}",
+                file.trim_end_matches(".json")
+            ),
+            key
+        );
+        assert_ne!(
+            key,
+            RedisBundleCache::key(&ProviderKind::GitLab, &id, "def")
+        );
+    }
+
+    #[test]
+    fn garbage_redis_entry_is_a_miss() {
+        assert!(decode_entry("k", b"\x00not json").is_none());
+        assert!(decode_entry("k", br#"{"meta": 1}"#).is_none());
+    }
+
+    fn bundle() -> CrBundle {
+        use crate::git_providers::types::{ChangeRequest, ChangeSet, DiffRefs};
+        CrBundle {
+            meta: ChangeRequest::commit_range(
+                ProviderKind::GitLab,
+                "group/app",
+                "main",
+                "feature",
+                DiffRefs {
+                    base_sha: "a".into(),
+                    start_sha: None,
+                    head_sha: "b".into(),
+                },
+                String::new(),
+            ),
+            commits: Vec::new(),
+            changes: ChangeSet {
+                files: Vec::new(),
+                is_truncated: true,
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn fs_cache_round_trips_and_misses_on_garbage() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = FsBundleCache::new(dir.path());
+        let id = ChangeRequestId {
+            project: "group/app".into(),
+            iid: 42,
+        };
+        let kind = ProviderKind::GitLab;
+
+        assert!(cache.load(&kind, &id, "b").await.unwrap().is_none());
+        cache.store(&kind, &id, "b", &bundle()).await.unwrap();
+        let hit = cache.load(&kind, &id, "b").await.unwrap().unwrap();
+        assert!(hit.changes.is_truncated);
+
+        std::fs::write(cache.key_path(&kind, &id, "b"), b"{\"meta\": 1}").unwrap();
+        assert!(cache.load(&kind, &id, "b").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn redis_caches_share_a_client_and_miss_when_unreachable() {
+        // Nothing listens on port 1: every call fails fast.
+        let url = "redis://127.0.0.1:1";
+        let a = RedisBundleCache::shared(url, 60).unwrap();
+        let b = RedisBundleCache::shared(url, 120).unwrap();
+        assert!(Arc::ptr_eq(&a.conn, &b.conn));
+        assert_eq!(b.ttl_secs, 120);
+
+        let id = ChangeRequestId {
+            project: "group/app".into(),
+            iid: 42,
+        };
+        let kind = ProviderKind::GitLab;
+        b.store(&kind, &id, "b", &bundle()).await.unwrap();
+        assert!(b.load(&kind, &id, "b").await.unwrap().is_none());
+        assert!(a.conn.lock().await.is_none());
     }
-    let json = serde_json::to_vec(bundle)?;
-    fs::write(path, json).await?;
-    Ok(())
 }
@@ -33,6 +33,20 @@ pub enum Error {
     #[error("validation error: {0}")]
     Validation(String),
 
+    /// The model returned no usable content (empty/whitespace-only or a stream
+    /// cut mid-response), even after one retry with a shorter prompt.
+    #[error("llm returned an empty response: {0}")]
+    LlmEmptyResponse(String),
+
+    /// A model call exceeded the per-call timeout (`ReviewOptions::llm_call_timeout`).
+    #[error("llm call timed out: {0}")]
+    LlmTimeout(String),
+
+    /// The change request head moved between planning and publishing; inline
+    /// positions from the plan would target a stale diff.
+    #[error("change request head moved from {expected} to {actual} since the review was planned")]
+    HeadMoved { expected: String, actual: String },
+
     /// Generic catch-all error when nothing else fits.
     #[error("other error: {0}")]
     Other(String),
@@ -86,7 +100,7 @@ pub enum ProviderError {
     Unsupported,
 }
 
-/// File cache related errors.
+/// Bundle cache related errors (filesystem or Redis backend).
 #[derive(Debug, Error)]
 pub enum CacheError {
     #[error("io error: {0}")]
@@ -94,6 +108,9 @@ pub enum CacheError {
 
     #[error("serde error: {0}")]
     Serde(#[from] serde_json::Error),
+
+    #[error("redis error: {0}")]
+    Redis(#[from] redis::RedisError),
 }
 
 /// Unified diff parser errors.
@@ -117,6 +134,21 @@ pub enum ConfigError {
 
     #[error("invalid base api url: {0}")]
     InvalidBaseUrl(String),
+
+    #[error("invalid CA certificate bundle: {0}")]
+    InvalidCaCert(String),
+
+    #[error("cannot infer provider kind from base api url: {0} (set the kind explicitly)")]
+    UnknownProviderKind(String),
+
+    #[error("invalid extra provider header: {0}")]
+    InvalidHeader(String),
+
+    #[error("missing required environment variable(s): {}", .0.join(", "))]
+    MissingEnv(Vec<String>),
+
+    #[error("invalid llm config: {0}")]
+    Llm(String),
 }
 
 // ===== Conversions for `?` ergonomics =====
@@ -4,16 +4,36 @@
 //! - GET /2.0/repositories/{workspace}/{repo_slug}/pullrequests/{id}
 //! - GET /2.0/.../pullrequests/{id}/commits
 //! - GET /2.0/.../pullrequests/{id}/diff  (unified text), or /patch
+//!
+//! Implemented:
+//! - GET/POST /2.0/.../pullrequests/{id}/comments (general comments)
+//! - GET /2.0/.../commit/{ref}, /merge-base/{a}..{b}, /diff/{head}..{base},
+//!   /commits?include=&exclude= (commit-range review)
 
 use crate::errors::{MrResult, ProviderError};
+use crate::git_providers::ProviderKind;
+use crate::git_providers::github::{commit_from_parts, file_change, split_diff_by_file};
+use crate::git_providers::rate_limit::{RateLimiter, SendThrottled};
 use crate::git_providers::types::*;
-use reqwest::Client;
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// Upper bound on pages read from one list endpoint.
+const MAX_PAGES: usize = 20;
 
 #[derive(Debug, Clone)]
 pub struct BitbucketClient {
     http: Client,
     base_api: String, // "https://api.bitbucket.org/2.0"
     token: String,    // "Bearer <token>"
+    /// `ProviderConfig::extra_headers`, applied after auth.
+    extra_headers: HeaderMap,
+    /// Host rate limiter every request waits for (`None`: unlimited).
+    limiter: Option<Arc<RateLimiter>>,
 }
 
 impl BitbucketClient {
@@ -22,9 +42,23 @@ impl BitbucketClient {
             http,
             base_api,
             token,
+            extra_headers: HeaderMap::new(),
+            limiter: None,
         }
     }
 
+    /// Adds headers to every request; they replace same-named defaults.
+    pub fn with_extra_headers(mut self, headers: HeaderMap) -> Self {
+        self.extra_headers = headers;
+        self
+    }
+
+    /// Shares `limiter` (see [`super::rate_limit::for_url`]); every request takes a token.
+    pub fn with_limiter(mut self, limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
     pub async fn get_meta(&self, _id: &ChangeRequestId) -> MrResult<ChangeRequest> {
         // TODO
         Err(ProviderError::Unsupported.into())
@@ -45,6 +79,67 @@ impl BitbucketClient {
         Err(ProviderError::Unsupported.into())
     }
 
+    /// Commit range `base_ref..head_ref` as a bundle with synthetic metadata
+    /// (see [`ChangeRequest::commit_range`]); `project` is `workspace/repo_slug`.
+    ///
+    /// The diff is taken from the merge base, like a PR.
+    pub async fn get_compare(
+        &self,
+        project: &str,
+        base_ref: &str,
+        head_ref: &str,
+    ) -> MrResult<CrBundle> {
+        let repo = self.repo_url(project);
+        let head: BitbucketCommit = self
+            .get_json(format!("{repo}/commit/{}", urlencoding::encode(head_ref)))
+            .await?;
+        let merge_base: BitbucketCommit = self
+            .get_json(format!(
+                "{repo}/merge-base/{}..{}",
+                head.hash,
+                urlencoding::encode(base_ref)
+            ))
+            .await?;
+        let raw = self
+            .request(
+                Method::GET,
+                format!("{repo}/diff/{}..{}", head.hash, merge_base.hash),
+            )
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let mut url = format!(
+            "{repo}/commits?include={}&exclude={}&pagelen=100",
+            head.hash,
+            urlencoding::encode(base_ref)
+        );
+        let mut commits = Vec::new();
+        for _ in 0..MAX_PAGES {
+            let page: BitbucketPage<BitbucketCommit> = self.get_json(&url).await?;
+            commits.extend(page.values);
+            match page.next {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+
+        Ok(compare_bundle(
+            project,
+            base_ref,
+            head_ref,
+            DiffRefs {
+                base_sha: merge_base.hash.clone(),
+                start_sha: Some(merge_base.hash),
+                head_sha: head.hash,
+            },
+            &raw,
+            commits,
+        ))
+    }
+
     pub async fn get_file_raw(
         &self,
         _id: &ChangeRequestId,
@@ -54,4 +149,226 @@ impl BitbucketClient {
         // TODO: implement via "Get repository content" with media type raw.
         Err(ProviderError::Unsupported.into())
     }
+
+    /// Bodies of the PR's comments (up to [`MAX_PAGES`] pages of 100), for
+    /// marker lookups.
+    pub async fn list_note_bodies(&self, id: &ChangeRequestId) -> MrResult<Vec<String>> {
+        let mut url = format!("{}?pagelen=100", self.comments_url(id));
+        let mut out = Vec::new();
+        for _ in 0..MAX_PAGES {
+            let page: BitbucketPage<BitbucketComment> = self.get_json(&url).await?;
+            out.extend(
+                page.values
+                    .into_iter()
+                    .filter_map(|c| c.content.and_then(|c| c.raw)),
+            );
+            match page.next {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+        Ok(out)
+    }
+
+    /// Posts a general (non-inline) PR comment.
+    pub async fn post_note(&self, id: &ChangeRequestId, body: &str) -> MrResult<()> {
+        self.request(Method::POST, self.comments_url(id))
+            .json(&serde_json::json!({ "content": { "raw": body } }))
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: impl reqwest::IntoUrl) -> MrResult<T> {
+        Ok(self
+            .request(Method::GET, url)
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Authenticated request carrying the extra headers.
+    pub(super) fn request(&self, method: Method, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.http
+            .request(method, url)
+            .bearer_auth(&self.token)
+            .headers(self.extra_headers.clone())
+    }
+
+    /// `id.project` is `workspace/repo_slug`.
+    fn comments_url(&self, id: &ChangeRequestId) -> String {
+        format!(
+            "{}/pullrequests/{}/comments",
+            self.repo_url(&id.project),
+            id.iid
+        )
+    }
+
+    fn repo_url(&self, project: &str) -> String {
+        format!(
+            "{}/repositories/{}",
+            self.base_api.trim_end_matches('/'),
+            project.trim_matches('/')
+        )
+    }
+}
+
+/// Bundle from a `/diff` text and `/commits` pages (newest first).
+fn compare_bundle(
+    project: &str,
+    base_ref: &str,
+    head_ref: &str,
+    diff_refs: DiffRefs,
+    raw_diff: &str,
+    commits: Vec<BitbucketCommit>,
+) -> CrBundle {
+    let files = split_diff_by_file(raw_diff)
+        .into_iter()
+        .map(|c| {
+            let status = c.status();
+            file_change(c.new_path, Some(c.old_path), status, c.patch)
+        })
+        .collect();
+    let commits = commits
+        .into_iter()
+        .rev()
+        .map(|c| {
+            let author = c
+                .author
+                .and_then(|a| a.user.and_then(|u| u.display_name).or(a.raw));
+            commit_from_parts(
+                c.hash,
+                c.message.unwrap_or_default(),
+                author,
+                c.date,
+                c.links.and_then(|l| l.html).map(|h| h.href),
+            )
+        })
+        .collect();
+    CrBundle {
+        meta: ChangeRequest::commit_range(
+            ProviderKind::Bitbucket,
+            project,
+            base_ref,
+            head_ref,
+            diff_refs,
+            String::new(),
+        ),
+        commits,
+        changes: ChangeSet {
+            files,
+            is_truncated: false,
+        },
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketPage<T> {
+    #[serde(default = "Vec::new")]
+    values: Vec<T>,
+    /// Absolute URL of the next page, absent on the last one.
+    #[serde(default)]
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketCommit {
+    hash: String,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    date: Option<DateTime<Utc>>,
+    #[serde(default)]
+    author: Option<BitbucketAuthor>,
+    #[serde(default)]
+    links: Option<BitbucketLinks>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketAuthor {
+    /// `Name <email>` as recorded in git.
+    #[serde(default)]
+    raw: Option<String>,
+    #[serde(default)]
+    user: Option<BitbucketUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketUser {
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketLinks {
+    #[serde(default)]
+    html: Option<BitbucketHref>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketHref {
+    href: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketComment {
+    #[serde(default)]
+    content: Option<BitbucketContent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitbucketContent {
+    #[serde(default)]
+    raw: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_pages_become_an_oldest_first_bundle() {
+        let page: BitbucketPage<BitbucketCommit> = serde_json::from_str(
+            r#"{
+                "values": [
+                    {"hash": "ccc333", "message": "Second\n", "date": "2025-01-02T00:00:00+00:00",
+                     "author": {"raw": "Dev <dev@example.com>", "user": {"display_name": "Dev"}},
+                     "links": {"html": {"href": "https://bitbucket.org/w/r/commits/ccc333"}}},
+                    {"hash": "bbb222", "message": "First\n",
+                     "author": {"raw": "Bot <bot@example.com>"}}
+                ],
+                "next": "https://api.bitbucket.org/2.0/repositories/w/r/commits?page=2"
+            }"#,
+        )
+        .unwrap();
+        assert!(page.next.is_some());
+
+        let raw = "diff --git a/src/a.rs b/src/a.rs\n\
+--- a/src/a.rs\n\
++++ b/src/a.rs\n\
+@@ -1,1 +1,2 @@\n\
+ fn a() {}\n\
++fn b() {}\n";
+        let refs = DiffRefs {
+            base_sha: "aaa111".into(),
+            start_sha: Some("aaa111".into()),
+            head_sha: "ccc333".into(),
+        };
+        let bundle = compare_bundle("w/r", "main", "feature", refs, raw, page.values);
+
+        assert_eq!(bundle.meta.provider, ProviderKind::Bitbucket);
+        assert_eq!(bundle.meta.diff_refs.base_sha, "aaa111");
+        assert_eq!(bundle.commits[0].id, "bbb222");
+        assert_eq!(
+            bundle.commits[0].author_name.as_deref(),
+            Some("Bot <bot@example.com>")
+        );
+        assert_eq!(bundle.commits[1].title, "Second");
+        assert_eq!(bundle.commits[1].author_name.as_deref(), Some("Dev"));
+        assert_eq!(bundle.changes.files.len(), 1);
+        assert_eq!(bundle.changes.files[0].hunks.len(), 1);
+    }
 }
@@ -1,57 +1,1093 @@
-//! GitHub provider skeleton (TODO).
+//! GitHub provider (REST v3 + optional GraphQL v4) for PR metadata/commits/diffs.
 //!
-//! Endpoints to implement next:
+//! REST endpoints used:
 //! - GET /repos/{owner}/{repo}/pulls/{number}
-//! - GET /repos/{owner}/{repo}/pulls/{number}/commits
-//! - GET /repos/{owner}/{repo}/pulls/{number}/files  (field "patch" is unified diff)
+//! - GET /repos/{owner}/{repo}/pulls/{number}/commits  (paginated, max 250)
+//! - GET /repos/{owner}/{repo}/pulls/{number}/files    (paginated; field "patch" is unified diff)
+//! - GET /repos/{owner}/{repo}/pulls/{number}          (Accept: diff; enrichment)
+//! - GET /repos/{owner}/{repo}/contents/{path}?ref=    (Accept: raw)
+//! - GET /repos/{owner}/{repo}/compare/{base}...{head} (commit-range review)
+//! - GET/POST /repos/{owner}/{repo}/issues/{number}/comments (general comments)
+//!
+//! GraphQL path (opt-in, `MR_REVIEWER_GITHUB_GRAPHQL=true`):
+//! one query for PR metadata + commits + changed files, plus one diff request
+//! for patches (GraphQL does not expose them). Any failure — including PRs
+//! exceeding a single GraphQL page — falls back to REST. Both paths produce
+//! the same normalized `CrBundle`.
 
 use crate::errors::{MrResult, ProviderError};
+use crate::git_providers::ProviderKind;
+use crate::git_providers::rate_limit::{RateLimiter, SendThrottled};
 use crate::git_providers::types::*;
-use reqwest::Client;
+use crate::parser::{looks_like_binary_patch, parse_unified_diff_advanced};
+use chrono::{DateTime, Utc};
+use reqwest::header::HeaderMap;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// REST page size (GitHub maximum).
+const PER_PAGE: usize = 100;
+/// REST `/pulls/{n}/files` hard limit; more files means the list is truncated.
+const MAX_REST_FILES: usize = 3000;
+/// Compare API file limit; more files means the list is truncated.
+const MAX_COMPARE_FILES: usize = 300;
+/// GraphQL page size for commits/files; larger PRs fall back to REST.
+const GRAPHQL_PAGE: usize = 100;
+
+const PR_QUERY: &str = r#"
+query($owner: String!, $name: String!, $number: Int!) {
+  repository(owner: $owner, name: $name) {
+    pullRequest(number: $number) {
+      title
+      body
+      state
+      isDraft
+      url
+      labels(first: 100) { nodes { name } }
+      createdAt
+      updatedAt
+      headRefName
+      baseRefName
+      headRefOid
+      baseRefOid
+      author { login url avatarUrl ... on User { databaseId } ... on Bot { databaseId } }
+      commits(first: 100) {
+        pageInfo { hasNextPage }
+        nodes { commit { oid message authoredDate url author { name } } }
+      }
+      files(first: 100) {
+        pageInfo { hasNextPage }
+        nodes { path changeType }
+      }
+    }
+  }
+}
+"#;
 
 #[derive(Debug, Clone)]
 pub struct GitHubClient {
     http: Client,
-    base_api: String, // "https://api.github.com"
-    token: String,    // "token <PAT>"
+    base_api: String, // "https://api.github.com" or "https://host/api/v3"
+    token: String,    // PAT or app installation token
+    use_graphql: bool,
+    /// `ProviderConfig::extra_headers`, applied after auth and API headers.
+    extra_headers: HeaderMap,
+    /// Host rate limiter every request waits for (`None`: unlimited).
+    limiter: Option<Arc<RateLimiter>>,
 }
 
 impl GitHubClient {
     pub fn new(http: Client, base_api: String, token: String) -> Self {
         Self {
             http,
-            base_api,
+            base_api: base_api.trim_end_matches('/').to_string(),
             token,
+            use_graphql: false,
+            extra_headers: HeaderMap::new(),
+            limiter: None,
+        }
+    }
+
+    /// Enables the batched GraphQL fetch path (REST stays as fallback).
+    pub fn with_graphql(mut self, enabled: bool) -> Self {
+        self.use_graphql = enabled;
+        self
+    }
+
+    /// Adds headers to every request; they replace same-named defaults.
+    pub fn with_extra_headers(mut self, headers: HeaderMap) -> Self {
+        self.extra_headers = headers;
+        self
+    }
+
+    /// Shares `limiter` (see [`super::rate_limit::for_url`]); every request takes a token.
+    pub fn with_limiter(mut self, limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Fetches meta + commits + changes, via GraphQL when enabled, else REST.
+    ///
+    /// A `known_meta` the caller already fetched is reused on the REST path
+    /// instead of requesting the PR again.
+    pub async fn fetch_all(
+        &self,
+        id: &ChangeRequestId,
+        known_meta: Option<ChangeRequest>,
+    ) -> MrResult<CrBundle> {
+        if self.use_graphql {
+            match self.fetch_all_graphql(id).await {
+                Ok(bundle) => return Ok(bundle),
+                Err(e) => warn!("github: graphql fetch failed, falling back to REST: {}", e),
+            }
+        }
+        let meta = match known_meta {
+            Some(meta) => meta,
+            None => self.get_meta(id).await?,
+        };
+        let commits = self.get_commits(id).await?;
+        let changes = self.get_changeset(id).await?;
+        Ok(CrBundle {
+            meta,
+            commits,
+            changes,
+        })
+    }
+
+    /// GET /repos/{owner}/{repo}/pulls/{number}
+    pub async fn get_meta(&self, id: &ChangeRequestId) -> MrResult<ChangeRequest> {
+        let url = format!("{}/pulls/{}", self.repo_url(id), id.iid);
+        let pr: GitHubPr = self
+            .get(url)
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(ChangeRequest {
+            provider: ProviderKind::GitHub,
+            id: id.clone(),
+            title: pr.title,
+            description: pr.body,
+            author: AuthorInfo {
+                id: pr.user.id.to_string(),
+                username: Some(pr.user.login),
+                name: None,
+                web_url: pr.user.html_url,
+                avatar_url: pr.user.avatar_url,
+            },
+            state: pr.state,
+            web_url: pr.html_url,
+            created_at: pr.created_at,
+            updated_at: pr.updated_at,
+            source_branch: Some(pr.head.ref_name),
+            target_branch: Some(pr.base.ref_name),
+            diff_refs: DiffRefs {
+                base_sha: pr.base.sha,
+                start_sha: None,
+                head_sha: pr.head.sha,
+            },
+            is_draft: pr.draft,
+            labels: pr.labels.into_iter().map(|l| l.name).collect(),
+        })
+    }
+
+    /// GET /repos/{owner}/{repo}/pulls/{number}/commits (all pages).
+    pub async fn get_commits(&self, id: &ChangeRequestId) -> MrResult<Vec<CrCommit>> {
+        let url = format!("{}/pulls/{}/commits", self.repo_url(id), id.iid);
+        let mut out = Vec::new();
+        for page in 1.. {
+            let batch: Vec<GitHubCommit> = self
+                .get(&url)
+                .query(&[("per_page", PER_PAGE), ("page", page)])
+                .send_throttled(&self.limiter)
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let n = batch.len();
+            out.extend(batch.into_iter().map(|c| {
+                commit_from_parts(
+                    c.sha,
+                    c.commit.message,
+                    c.commit.author.as_ref().and_then(|a| a.name.clone()),
+                    c.commit.author.and_then(|a| a.date),
+                    c.html_url,
+                )
+            }));
+            if n < PER_PAGE {
+                break;
+            }
         }
+        Ok(out)
     }
 
-    pub async fn get_meta(&self, _id: &ChangeRequestId) -> MrResult<ChangeRequest> {
-        // TODO
-        Err(ProviderError::Unsupported.into())
+    /// GET /repos/{owner}/{repo}/pulls/{number}/files (all pages).
+    ///
+    /// Files without `patch` (binary or too large) are marked binary.
+    pub async fn get_changeset(&self, id: &ChangeRequestId) -> MrResult<ChangeSet> {
+        let url = format!("{}/pulls/{}/files", self.repo_url(id), id.iid);
+        let mut files = Vec::new();
+        for page in 1.. {
+            let batch: Vec<GitHubPrFile> = self
+                .get(&url)
+                .query(&[("per_page", PER_PAGE), ("page", page)])
+                .send_throttled(&self.limiter)
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let n = batch.len();
+            files.extend(batch.into_iter().map(|f| {
+                file_change(
+                    f.filename,
+                    f.previous_filename,
+                    &f.status.to_ascii_uppercase(),
+                    f.patch,
+                )
+            }));
+            if n < PER_PAGE {
+                break;
+            }
+        }
+
+        let is_truncated = files.len() >= MAX_REST_FILES;
+        Ok(ChangeSet {
+            files,
+            is_truncated,
+        })
     }
 
-    pub async fn get_commits(&self, _id: &ChangeRequestId) -> MrResult<Vec<CrCommit>> {
-        // TODO
-        Err(ProviderError::Unsupported.into())
+    /// GET /repos/{owner}/{repo}/compare/{base}...{head} as a bundle with
+    /// synthetic metadata (see [`ChangeRequest::commit_range`]).
+    ///
+    /// The diff is against the merge base, like a PR. A file list at the API
+    /// cap is replaced by the full compare diff.
+    pub async fn get_compare(
+        &self,
+        project: &str,
+        base_ref: &str,
+        head_ref: &str,
+    ) -> MrResult<CrBundle> {
+        let url = compare_url(&self.base_api, project, base_ref, head_ref);
+        let cmp: GitHubCompare = self
+            .get(&url)
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let head_sha = match cmp.listed_head() {
+            Some(sha) => sha.to_string(),
+            None => self.resolve_sha(project, head_ref).await?,
+        };
+        let diff_refs = DiffRefs {
+            base_sha: cmp.merge_base_commit.sha,
+            start_sha: None,
+            head_sha,
+        };
+        let files = if cmp.files.len() >= MAX_COMPARE_FILES {
+            debug!(
+                "github: compare lists {} files, fetching full diff",
+                cmp.files.len()
+            );
+            let raw = self
+                .get(&url)
+                .header("Accept", "application/vnd.github.diff")
+                .send_throttled(&self.limiter)
+                .await?
+                .error_for_status()?
+                .text()
+                .await?;
+            split_diff_by_file(&raw)
+                .into_iter()
+                .map(|c| {
+                    let status = c.status();
+                    file_change(c.new_path, Some(c.old_path), status, c.patch)
+                })
+                .collect()
+        } else {
+            cmp.files
+                .into_iter()
+                .map(|f| {
+                    file_change(
+                        f.filename,
+                        f.previous_filename,
+                        &f.status.to_ascii_uppercase(),
+                        f.patch,
+                    )
+                })
+                .collect()
+        };
+
+        let commits = cmp
+            .commits
+            .into_iter()
+            .map(|c| {
+                commit_from_parts(
+                    c.sha,
+                    c.commit.message,
+                    c.commit.author.as_ref().and_then(|a| a.name.clone()),
+                    c.commit.author.and_then(|a| a.date),
+                    c.html_url,
+                )
+            })
+            .collect();
+        Ok(CrBundle {
+            meta: ChangeRequest::commit_range(
+                ProviderKind::GitHub,
+                project,
+                base_ref,
+                head_ref,
+                diff_refs,
+                cmp.html_url,
+            ),
+            commits,
+            changes: ChangeSet {
+                files,
+                is_truncated: false,
+            },
+        })
     }
 
-    pub async fn get_changeset(&self, _id: &ChangeRequestId) -> MrResult<ChangeSet> {
-        // TODO
-        Err(ProviderError::Unsupported.into())
+    /// GET /repos/{owner}/{repo}/commits/{ref} (sha media type): ref → commit SHA.
+    async fn resolve_sha(&self, project: &str, git_ref: &str) -> MrResult<String> {
+        let url = format!(
+            "{}/repos/{}/commits/{}",
+            self.base_api,
+            project,
+            urlencoding::encode(git_ref)
+        );
+        let sha = self
+            .get(url)
+            .header("Accept", "application/vnd.github.sha")
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        Ok(sha.trim().to_string())
     }
 
-    pub async fn try_enrich_changeset(&self, _id: &ChangeRequestId) -> MrResult<Option<ChangeSet>> {
-        // Optional: use raw patch via Accept headers
-        Err(ProviderError::Unsupported.into())
+    /// Re-fetches the whole PR as a unified diff (no file-count cap).
+    pub async fn try_enrich_changeset(&self, id: &ChangeRequestId) -> MrResult<Option<ChangeSet>> {
+        let raw = self.get_pr_diff(id).await?;
+        let files = split_diff_by_file(&raw)
+            .into_iter()
+            .map(|c| {
+                let status = c.status();
+                file_change(c.new_path, Some(c.old_path), status, c.patch)
+            })
+            .collect();
+        Ok(Some(ChangeSet {
+            files,
+            is_truncated: false,
+        }))
     }
 
+    /// GET /repos/{owner}/{repo}/contents/{path}?ref={ref} (raw media type).
+    /// Returns `None` on 404.
     pub async fn get_file_raw(
         &self,
-        _id: &ChangeRequestId,
-        _repo_relative_path: &str,
-        _git_ref: &str,
+        id: &ChangeRequestId,
+        repo_relative_path: &str,
+        git_ref: &str,
     ) -> MrResult<Option<Vec<u8>>> {
-        // TODO: implement via "Get repository content" with media type raw.
-        Err(ProviderError::Unsupported.into())
+        let path = repo_relative_path
+            .split('/')
+            .map(|s| urlencoding::encode(s).into_owned())
+            .collect::<Vec<_>>()
+            .join("/");
+        let url = format!("{}/contents/{}", self.repo_url(id), path);
+        let resp = self
+            .get(url)
+            .header("Accept", "application/vnd.github.raw")
+            .query(&[("ref", git_ref)])
+            .send_throttled(&self.limiter)
+            .await?;
+
+        if resp.status().as_u16() == 404 {
+            return Ok(None);
+        }
+        let bytes = resp.error_for_status()?.bytes().await?;
+        Ok(Some(bytes.to_vec()))
+    }
+
+    /// One GraphQL query (meta + commits + files) and one diff request (patches).
+    async fn fetch_all_graphql(&self, id: &ChangeRequestId) -> MrResult<CrBundle> {
+        let (owner, name) = id.project.split_once('/').ok_or_else(|| {
+            ProviderError::InvalidResponse(format!("bad GitHub repo '{}'", id.project))
+        })?;
+        let body = serde_json::json!({
+            "query": PR_QUERY,
+            "variables": { "owner": owner, "name": name, "number": id.iid },
+        });
+        let resp: GqlResponse = self
+            .http
+            .post(self.graphql_url())
+            .bearer_auth(&self.token)
+            .headers(self.extra_headers.clone())
+            .json(&body)
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if let Some(errs) = resp.errors.filter(|e| !e.is_empty()) {
+            let msg = errs
+                .into_iter()
+                .map(|e| e.message)
+                .collect::<Vec<_>>()
+                .join("; ");
+            return Err(ProviderError::InvalidResponse(format!("graphql: {msg}")).into());
+        }
+        let pr = resp
+            .data
+            .and_then(|d| d.repository)
+            .and_then(|r| r.pull_request)
+            .ok_or(ProviderError::NotFound)?;
+
+        if pr.commits.page_info.has_next_page || pr.files.page_info.has_next_page {
+            return Err(ProviderError::InvalidResponse(format!(
+                "graphql: PR exceeds {GRAPHQL_PAGE} commits/files"
+            ))
+            .into());
+        }
+
+        let raw = self.get_pr_diff(id).await?;
+        let mut chunks: HashMap<String, DiffChunk> = split_diff_by_file(&raw)
+            .into_iter()
+            .map(|c| (c.new_path.clone(), c))
+            .collect();
+
+        let files: Vec<FileChange> = pr
+            .files
+            .nodes
+            .into_iter()
+            .map(|f| {
+                let chunk = chunks.remove(&f.path);
+                let (old_path, patch) = match chunk {
+                    Some(c) => (Some(c.old_path), c.patch),
+                    None => (None, None),
+                };
+                file_change(f.path, old_path, &f.change_type, patch)
+            })
+            .collect();
+
+        let commits: Vec<CrCommit> = pr
+            .commits
+            .nodes
+            .into_iter()
+            .map(|n| {
+                let c = n.commit;
+                commit_from_parts(
+                    c.oid,
+                    c.message,
+                    c.author.and_then(|a| a.name),
+                    c.authored_date,
+                    Some(c.url),
+                )
+            })
+            .collect();
+
+        let author = pr.author.unwrap_or_default();
+        let meta = ChangeRequest {
+            provider: ProviderKind::GitHub,
+            id: id.clone(),
+            title: pr.title,
+            description: Some(pr.body).filter(|b| !b.is_empty()),
+            author: AuthorInfo {
+                id: author
+                    .database_id
+                    .map(|v| v.to_string())
+                    .unwrap_or_default(),
+                username: Some(author.login),
+                name: None,
+                web_url: author.url,
+                avatar_url: author.avatar_url,
+            },
+            // REST reports merged PRs as "closed".
+            state: match pr.state.as_str() {
+                "OPEN" => "open".to_string(),
+                _ => "closed".to_string(),
+            },
+            web_url: pr.url,
+            created_at: pr.created_at,
+            updated_at: pr.updated_at,
+            source_branch: Some(pr.head_ref_name),
+            target_branch: Some(pr.base_ref_name),
+            diff_refs: DiffRefs {
+                base_sha: pr.base_ref_oid,
+                start_sha: None,
+                head_sha: pr.head_ref_oid,
+            },
+            is_draft: pr.is_draft,
+            labels: pr.labels.nodes.into_iter().map(|l| l.name).collect(),
+        };
+
+        debug!(
+            "github: graphql bundle ok, commits={}, files={}",
+            commits.len(),
+            files.len()
+        );
+        Ok(CrBundle {
+            meta,
+            commits,
+            changes: ChangeSet {
+                files,
+                is_truncated: false,
+            },
+        })
+    }
+
+    /// Bodies of the PR's conversation comments (all pages), for marker lookups.
+    pub async fn list_note_bodies(&self, id: &ChangeRequestId) -> MrResult<Vec<String>> {
+        let url = self.issue_comments_url(id);
+        let mut out = Vec::new();
+        for page in 1.. {
+            let batch: Vec<GitHubIssueComment> = self
+                .get(&url)
+                .query(&[("per_page", PER_PAGE), ("page", page)])
+                .send_throttled(&self.limiter)
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let n = batch.len();
+            out.extend(batch.into_iter().filter_map(|c| c.body));
+            if n < PER_PAGE {
+                break;
+            }
+        }
+        Ok(out)
+    }
+
+    /// Posts a conversation (issue) comment on the PR.
+    pub async fn post_note(&self, id: &ChangeRequestId, body: &str) -> MrResult<()> {
+        self.http
+            .post(self.issue_comments_url(id))
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .headers(self.extra_headers.clone())
+            .json(&serde_json::json!({ "body": body }))
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn issue_comments_url(&self, id: &ChangeRequestId) -> String {
+        format!("{}/issues/{}/comments", self.repo_url(id), id.iid)
+    }
+
+    /// GET /repos/{owner}/{repo}/pulls/{number} as `application/vnd.github.diff`.
+    async fn get_pr_diff(&self, id: &ChangeRequestId) -> MrResult<String> {
+        let url = format!("{}/pulls/{}", self.repo_url(id), id.iid);
+        Ok(self
+            .get(url)
+            .header("Accept", "application/vnd.github.diff")
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    }
+
+    fn get(&self, url: impl reqwest::IntoUrl) -> RequestBuilder {
+        self.http
+            .get(url)
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .headers(self.extra_headers.clone())
+    }
+
+    fn repo_url(&self, id: &ChangeRequestId) -> String {
+        format!("{}/repos/{}", self.base_api, id.project)
+    }
+
+    /// `https://api.github.com/graphql`, or `https://host/api/graphql` on GHES.
+    fn graphql_url(&self) -> String {
+        match self.base_api.strip_suffix("/api/v3") {
+            Some(host) => format!("{host}/api/graphql"),
+            None => format!("{}/graphql", self.base_api),
+        }
+    }
+}
+
+/// Compare URL with both refs percent-encoded (branch names may hold `/`, `#`, …).
+fn compare_url(base_api: &str, project: &str, base_ref: &str, head_ref: &str) -> String {
+    format!(
+        "{}/repos/{}/compare/{}...{}",
+        base_api,
+        project,
+        urlencoding::encode(base_ref),
+        urlencoding::encode(head_ref)
+    )
+}
+
+/// Normalizes a commit; the title is the first line of the message (as in REST).
+pub(super) fn commit_from_parts(
+    sha: String,
+    message: String,
+    author_name: Option<String>,
+    authored_at: Option<DateTime<Utc>>,
+    web_url: Option<String>,
+) -> CrCommit {
+    CrCommit {
+        id: sha,
+        title: message.lines().next().unwrap_or("").to_string(),
+        message: Some(message),
+        author_name,
+        authored_at,
+        web_url,
+    }
+}
+
+/// Builds a `FileChange` from a path, an upper-case status
+/// (`ADDED|REMOVED|DELETED|RENAMED|MODIFIED|...`) and an optional hunk-only patch.
+///
+/// A missing patch means a binary (or oversized) file, except for a rename,
+/// where it means the content did not change.
+pub(super) fn file_change(
+    path: String,
+    previous: Option<String>,
+    status: &str,
+    patch: Option<String>,
+) -> FileChange {
+    let is_binary = match patch.as_deref() {
+        Some(p) => looks_like_binary_patch(p),
+        None => status != "RENAMED",
+    };
+    let hunks = match &patch {
+        Some(p) if !is_binary => parse_unified_diff_advanced(p),
+        _ => Vec::new(),
+    };
+    FileChange {
+        old_path: Some(previous.unwrap_or_else(|| path.clone())),
+        new_path: Some(path),
+        is_new: status == "ADDED",
+        is_deleted: status == "REMOVED" || status == "DELETED",
+        is_renamed: status == "RENAMED",
+        is_binary,
+        hunks,
+        raw_unidiff: patch,
+    }
+}
+
+/// One file section of a `git diff` text.
+#[derive(Debug)]
+pub(super) struct DiffChunk {
+    pub(super) old_path: String,
+    pub(super) new_path: String,
+    is_new: bool,
+    is_deleted: bool,
+    /// Hunk text starting at the first `@@` (same shape as REST `patch`).
+    pub(super) patch: Option<String>,
+}
+
+impl DiffChunk {
+    pub(super) fn status(&self) -> &'static str {
+        if self.is_new {
+            "ADDED"
+        } else if self.is_deleted {
+            "REMOVED"
+        } else if self.old_path != self.new_path {
+            "RENAMED"
+        } else {
+            "MODIFIED"
+        }
+    }
+}
+
+/// Splits a multi-file `git diff` into per-file chunks keyed by header paths.
+pub(super) fn split_diff_by_file(raw: &str) -> Vec<DiffChunk> {
+    let mut out = Vec::new();
+    let raw = format!("\n{raw}");
+    for part in raw.split("\ndiff --git ").filter(|p| !p.trim().is_empty()) {
+        let mut lines = part.lines();
+        let header = lines.next().unwrap_or("");
+        let (mut old_path, mut new_path) = match header.split_once(" b/") {
+            Some((a, b)) => (a.trim_start_matches("a/").to_string(), b.to_string()),
+            None => continue,
+        };
+        let (mut is_new, mut is_deleted) = (false, false);
+        for l in part.lines().take_while(|l| !l.starts_with("@@")) {
+            if l.starts_with("new file mode") {
+                is_new = true;
+            } else if l.starts_with("deleted file mode") {
+                is_deleted = true;
+            } else if let Some(p) = l.strip_prefix("rename from ") {
+                old_path = p.to_string();
+            } else if let Some(p) = l.strip_prefix("rename to ") {
+                new_path = p.to_string();
+            }
+        }
+        let patch = part
+            .find("\n@@")
+            .map(|i| part[i + 1..].trim_end_matches('\n').to_string());
+        out.push(DiffChunk {
+            old_path,
+            new_path,
+            is_new,
+            is_deleted,
+            patch,
+        });
+    }
+    out
+}
+
+/// --- GitHub REST response shapes (subset of fields we actually use) ---
+
+#[derive(Debug, Deserialize)]
+struct GitHubPr {
+    title: String,
+    body: Option<String>,
+    state: String,
+    html_url: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    labels: Vec<GitHubLabel>,
+    user: GitHubUser,
+    head: GitHubRef,
+    base: GitHubRef,
+}
+
+/// REST and GraphQL label shape (only the name is used).
+#[derive(Debug, Deserialize)]
+struct GitHubLabel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubRef {
+    #[serde(rename = "ref")]
+    ref_name: String,
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubUser {
+    id: u64,
+    login: String,
+    html_url: Option<String>,
+    avatar_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCommit {
+    sha: String,
+    html_url: Option<String>,
+    commit: GitHubCommitInner,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCommitInner {
+    message: String,
+    author: Option<GitHubCommitAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCommitAuthor {
+    name: Option<String>,
+    date: Option<DateTime<Utc>>,
+}
+
+/// Issue comment; only the body is needed.
+#[derive(Debug, Deserialize)]
+struct GitHubIssueComment {
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// Compare response; `commits` is oldest-first, so the last one is the head.
+#[derive(Debug, Deserialize)]
+struct GitHubCompare {
+    html_url: String,
+    merge_base_commit: GitHubCompareBase,
+    /// Commits in the range; `commits` holds at most 250 of them.
+    #[serde(default)]
+    total_commits: usize,
+    #[serde(default)]
+    commits: Vec<GitHubCommit>,
+    #[serde(default)]
+    files: Vec<GitHubPrFile>,
+}
+
+impl GitHubCompare {
+    /// Head SHA when the response lists every commit of the range.
+    fn listed_head(&self) -> Option<&str> {
+        self.commits
+            .last()
+            .filter(|_| self.total_commits == self.commits.len())
+            .map(|c| c.sha.as_str())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubCompareBase {
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubPrFile {
+    filename: String,
+    status: String,
+    #[serde(default)]
+    previous_filename: Option<String>,
+    #[serde(default)]
+    patch: Option<String>,
+}
+
+/// --- GitHub GraphQL response shapes ---
+
+#[derive(Debug, Deserialize)]
+struct GqlResponse {
+    data: Option<GqlData>,
+    errors: Option<Vec<GqlError>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GqlError {
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GqlData {
+    repository: Option<GqlRepository>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlRepository {
+    pull_request: Option<GqlPr>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlPr {
+    title: String,
+    body: String,
+    state: String,
+    is_draft: bool,
+    url: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    head_ref_name: String,
+    base_ref_name: String,
+    head_ref_oid: String,
+    base_ref_oid: String,
+    author: Option<GqlActor>,
+    #[serde(default)]
+    labels: GqlNodes<GitHubLabel>,
+    commits: GqlConnection<GqlCommitNode>,
+    files: GqlConnection<GqlFile>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlActor {
+    login: String,
+    url: Option<String>,
+    avatar_url: Option<String>,
+    #[serde(default)]
+    database_id: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlConnection<T> {
+    page_info: GqlPageInfo,
+    nodes: Vec<T>,
+}
+
+/// Connection read without paging (labels: first 100 are enough).
+#[derive(Debug, Deserialize)]
+struct GqlNodes<T> {
+    nodes: Vec<T>,
+}
+
+impl<T> Default for GqlNodes<T> {
+    fn default() -> Self {
+        Self { nodes: Vec::new() }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlPageInfo {
+    has_next_page: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GqlCommitNode {
+    commit: GqlCommit,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlCommit {
+    oid: String,
+    message: String,
+    authored_date: Option<DateTime<Utc>>,
+    url: String,
+    author: Option<GqlGitActor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GqlGitActor {
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlFile {
+    path: String,
+    change_type: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_diff_matches_rest_patch_shape() {
+        let raw = "diff --git a/src/a.rs b/src/a.rs\n\
+index 1..2 100644\n\
+--- a/src/a.rs\n\
++++ b/src/a.rs\n\
+@@ -1,1 +1,2 @@\n\
+ fn a() {}\n\
++fn b() {}\n\
+diff --git a/old.txt b/new.txt\n\
+similarity index 100%\n\
+rename from old.txt\n\
+rename to new.txt\n\
+diff --git a/img.png b/img.png\n\
+new file mode 100644\n\
+Binary files /dev/null and b/img.png differ\n";
+
+        let chunks = split_diff_by_file(raw);
+        assert_eq!(chunks.len(), 3);
+
+        assert_eq!(chunks[0].new_path, "src/a.rs");
+        assert_eq!(chunks[0].status(), "MODIFIED");
+        assert!(chunks[0].patch.as_deref().unwrap().starts_with("@@ -1,1"));
+
+        assert_eq!(chunks[1].old_path, "old.txt");
+        assert_eq!(chunks[1].new_path, "new.txt");
+        assert_eq!(chunks[1].status(), "RENAMED");
+        assert!(chunks[1].patch.is_none());
+
+        assert_eq!(chunks[2].status(), "ADDED");
+        let fc = file_change(chunks[2].new_path.clone(), None, "ADDED", None);
+        assert!(fc.is_binary && fc.is_new);
+
+        let renamed = file_change("new.txt".into(), Some("old.txt".into()), "RENAMED", None);
+        assert!(renamed.is_renamed && !renamed.is_binary && renamed.hunks.is_empty());
+    }
+
+    /// Serves one GraphQL PR (`o/r#7`) and its `.diff`.
+    async fn mock_graphql_pr() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let pr = serde_json::json!({ "data": { "repository": { "pullRequest": {
+                "title": "Add b", "body": "", "state": "MERGED", "isDraft": false,
+                "url": "https://github.com/o/r/pull/7",
+                "createdAt": "2025-01-01T00:00:00Z", "updatedAt": "2025-01-02T00:00:00Z",
+                "headRefName": "feature", "baseRefName": "main",
+                "headRefOid": "bbb222", "baseRefOid": "aaa111",
+                "author": { "login": "dev", "url": null, "avatarUrl": null, "databaseId": 42 },
+                "labels": { "nodes": [{ "name": "backend" }] },
+                "commits": { "pageInfo": { "hasNextPage": false }, "nodes": [
+                    { "commit": { "oid": "bbb222", "message": "Add b\n\nbody",
+                                  "authoredDate": "2025-01-02T00:00:00Z",
+                                  "url": "https://github.com/o/r/commit/bbb222",
+                                  "author": { "name": "Dev" } } }
+                ] },
+                "files": { "pageInfo": { "hasNextPage": false }, "nodes": [
+                    { "path": "src/a.rs", "changeType": "MODIFIED" },
+                    { "path": "new.txt", "changeType": "RENAMED" }
+                ] }
+            } } } })
+            .to_string();
+            let diff = "diff --git a/src/a.rs b/src/a.rs\n\
+--- a/src/a.rs\n\
++++ b/src/a.rs\n\
+@@ -1,1 +1,2 @@\n\
+ fn a() {}\n\
++fn b() {}\n\
+diff --git a/old.txt b/new.txt\n\
+similarity index 100%\n\
+rename from old.txt\n\
+rename to new.txt\n";
+            loop {
+                let Ok((mut sock, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut buf = vec![0u8; 8192];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let req = String::from_utf8_lossy(&buf[..n]);
+                let body = match req.split_whitespace().nth(1) {
+                    Some("/graphql") => pr.clone(),
+                    Some("/repos/o/r/pulls/7") => diff.to_string(),
+                    _ => String::new(),
+                };
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = sock.write_all(resp.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn graphql_bundle_takes_patches_from_the_diff() {
+        let client = GitHubClient::new(Client::new(), mock_graphql_pr().await, "t".into());
+        let id = ChangeRequestId {
+            project: "o/r".into(),
+            iid: 7,
+        };
+        let bundle = client.fetch_all_graphql(&id).await.unwrap();
+
+        assert_eq!(bundle.meta.title, "Add b");
+        assert_eq!(bundle.meta.state, "closed");
+        assert_eq!(bundle.meta.author.id, "42");
+        assert_eq!(bundle.meta.labels, vec!["backend"]);
+        assert_eq!(bundle.meta.diff_refs.head_sha, "bbb222");
+        assert_eq!(bundle.commits.len(), 1);
+        assert_eq!(bundle.commits[0].title, "Add b");
+
+        let files = &bundle.changes.files;
+        assert_eq!(files.len(), 2);
+        assert!(!files[0].is_binary);
+        assert_eq!(files[0].hunks.len(), 1);
+        assert_eq!(files[1].old_path.as_deref(), Some("old.txt"));
+        assert!(files[1].is_renamed && !files[1].is_binary);
+    }
+
+    #[test]
+    fn compare_response_uses_merge_base_and_encoded_refs() {
+        let url = compare_url("https://api.github.com", "o/r", "release/1.2", "feat#7");
+        assert_eq!(
+            url,
+            "https://api.github.com/repos/o/r/compare/release%2F1.2...feat%237"
+        );
+
+        let raw = r#"{
+            "html_url": "https://github.com/o/r/compare/main...feature",
+            "base_commit": {"sha": "ccc333"},
+            "merge_base_commit": {"sha": "aaa111"},
+            "total_commits": 1,
+            "commits": [{"sha": "bbb222", "html_url": null,
+                         "commit": {"message": "Add b\n\nbody",
+                                    "author": {"name": "Dev", "date": "2025-01-02T03:04:05Z"}}}],
+            "files": [{"filename": "src/a.rs", "status": "modified",
+                       "patch": "@@ -1,1 +1,2 @@\n fn a() {}\n+fn b() {}"}]
+        }"#;
+        let cmp: GitHubCompare = serde_json::from_str(raw).unwrap();
+        assert_eq!(cmp.merge_base_commit.sha, "aaa111");
+        assert_eq!(cmp.listed_head(), Some("bbb222"));
+
+        let truncated = GitHubCompare {
+            total_commits: 300,
+            ..cmp
+        };
+        assert_eq!(truncated.listed_head(), None);
     }
 }
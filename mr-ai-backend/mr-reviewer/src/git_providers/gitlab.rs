@@ -5,20 +5,39 @@
 //! - GET /projects/:id/merge_requests/:iid/commits
 //! - GET /projects/:id/merge_requests/:iid/diffs      (preferred over deprecated /changes)
 //! - GET /projects/:id/merge_requests/:iid/raw_diffs  (optional enrichment)
+//! - GET /projects/:id/repository/compare?from=&to=   (commit-range review)
+//! - GET /projects/:id/repository/merge_base?refs[]=   (commit-range base SHA)
+//! - GET/POST /projects/:id/merge_requests/:iid/notes  (general comments)
+//!
+//! `:id` may be a numeric project id or a `group/sub/repo` path; both are
+//! normalized by [`project_segment`] before they reach the API.
 
 use crate::errors::MrResult;
 use crate::git_providers::ProviderKind;
+use crate::git_providers::rate_limit::{RateLimiter, SendThrottled};
 use crate::git_providers::types::*;
 use crate::parser::{looks_like_binary_patch, parse_unified_diff_advanced};
 use chrono::{DateTime, Utc};
 use reqwest::Client;
+use reqwest::header::HeaderMap;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Upper bound on pages read by [`GitLabClient::list_note_bodies`].
+const MAX_NOTE_PAGES: usize = 20;
 
 #[derive(Debug, Clone)]
 pub struct GitLabClient {
     http: Client,
     base_api: String, // e.g. "https://gitlab.com/api/v4"
     token: String,    // "PRIVATE-TOKEN"
+    /// `ProviderConfig::extra_headers`, applied after the token header.
+    extra_headers: HeaderMap,
+    /// Per-run cache of [`project_segment`] results; clones share it.
+    projects: Arc<Mutex<HashMap<String, String>>>,
+    /// Host rate limiter every request waits for (`None`: unlimited).
+    limiter: Option<Arc<RateLimiter>>,
 }
 
 impl GitLabClient {
@@ -28,9 +47,33 @@ impl GitLabClient {
             http,
             base_api,
             token,
+            extra_headers: HeaderMap::new(),
+            projects: Arc::default(),
+            limiter: None,
         }
     }
 
+    /// `:id` path segment for `project` (see [`project_segment`]), cached per run.
+    pub fn resolve_project(&self, project: &str) -> String {
+        let mut cache = self.projects.lock().unwrap();
+        cache
+            .entry(project.to_string())
+            .or_insert_with(|| project_segment(project))
+            .clone()
+    }
+
+    /// Adds headers to every request; they replace same-named defaults.
+    pub fn with_extra_headers(mut self, headers: HeaderMap) -> Self {
+        self.extra_headers = headers;
+        self
+    }
+
+    /// Shares `limiter` (see [`super::rate_limit::for_url`]); every request takes a token.
+    pub fn with_limiter(mut self, limiter: Option<Arc<RateLimiter>>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
     /// Convenience method to fetch all parts (meta + commits + changes).
     pub async fn fetch_all(&self, id: &ChangeRequestId) -> MrResult<CrBundle> {
         let meta = self.get_meta(id).await?;
@@ -48,14 +91,15 @@ impl GitLabClient {
         let url = format!(
             "{}/projects/{}/merge_requests/{}",
             self.base_api,
-            urlencoding::encode(&id.project),
+            self.resolve_project(&id.project),
             id.iid
         );
         let resp: GitLabMr = self
             .http
             .get(url)
             .header("PRIVATE-TOKEN", &self.token)
-            .send()
+            .headers(self.extra_headers.clone())
+            .send_throttled(&self.limiter)
             .await?
             .error_for_status()?
             .json()
@@ -88,6 +132,8 @@ impl GitLabClient {
             source_branch: Some(resp.source_branch),
             target_branch: Some(resp.target_branch),
             diff_refs,
+            is_draft: resp.draft || resp.work_in_progress,
+            labels: resp.labels,
         })
     }
 
@@ -96,32 +142,21 @@ impl GitLabClient {
         let url = format!(
             "{}/projects/{}/merge_requests/{}/commits",
             self.base_api,
-            urlencoding::encode(&id.project),
+            self.resolve_project(&id.project),
             id.iid
         );
         let raw: Vec<GitLabMrCommit> = self
             .http
             .get(url)
             .header("PRIVATE-TOKEN", &self.token)
-            .send()
+            .headers(self.extra_headers.clone())
+            .send_throttled(&self.limiter)
             .await?
             .error_for_status()?
             .json()
             .await?;
 
-        let commits = raw
-            .into_iter()
-            .map(|c| CrCommit {
-                id: c.id,
-                title: c.title,
-                message: Some(c.message),
-                author_name: Some(c.author_name),
-                authored_at: c.created_at,
-                web_url: c.web_url,
-            })
-            .collect();
-
-        Ok(commits)
+        Ok(raw.into_iter().map(GitLabMrCommit::into_commit).collect())
     }
 
     /// Fetches file-level diffs. We parse unified text into hunks/lines.
@@ -131,54 +166,77 @@ impl GitLabClient {
         let url = format!(
             "{}/projects/{}/merge_requests/{}/diffs",
             self.base_api,
-            urlencoding::encode(&id.project),
+            self.resolve_project(&id.project),
             id.iid
         );
         let files: Vec<GitLabMrDiffFile> = self
             .http
             .get(url)
             .header("PRIVATE-TOKEN", &self.token)
-            .send()
+            .headers(self.extra_headers.clone())
+            .send_throttled(&self.limiter)
             .await?
             .error_for_status()?
             .json()
             .await?;
 
-        let mut changes = Vec::with_capacity(files.len());
-        for f in files.iter().clone() {
-            let mut is_binary = f.diff.is_none();
-            let raw = f.diff.clone();
-
-            if let Some(d) = &f.diff {
-                if looks_like_binary_patch(d) {
-                    is_binary = true;
-                }
-            }
+        Ok(changeset_from_diffs(files))
+    }
 
-            let hunks = match &f.diff {
-                Some(d) if !is_binary => parse_unified_diff_advanced(d),
-                _ => Vec::new(),
-            };
+    /// Fetches a raw commit range `base_ref..head_ref` as a bundle with
+    /// synthetic metadata (see [`ChangeRequest::commit_range`]).
+    ///
+    /// Uses the merge-base diff (`straight=false`), like an MR would show.
+    /// `head_sha` is the resolved head commit; `base_sha` stays `base_ref`.
+    pub async fn get_compare(
+        &self,
+        project: &str,
+        base_ref: &str,
+        head_ref: &str,
+    ) -> MrResult<CrBundle> {
+        let url = format!(
+            "{}/projects/{}/repository/compare",
+            self.base_api,
+            self.resolve_project(project),
+        );
+        let resp: GitLabCompare = self
+            .http
+            .get(url)
+            .query(&[("from", base_ref), ("to", head_ref), ("straight", "false")])
+            .header("PRIVATE-TOKEN", &self.token)
+            .headers(self.extra_headers.clone())
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
 
-            changes.push(FileChange {
-                old_path: Some(f.old_path.clone()),
-                new_path: Some(f.new_path.clone()),
-                is_new: f.new_file,
-                is_deleted: f.deleted_file,
-                is_renamed: f.renamed_file,
-                is_binary,
-                hunks,
-                raw_unidiff: raw,
-            });
-        }
+        // The non-straight compare diffs against the merge base; positions and
+        // base-side reads need its SHA, not the (possibly moving) base ref.
+        let url = format!(
+            "{}/projects/{}/repository/merge_base",
+            self.base_api,
+            self.resolve_project(project),
+        );
+        let merge_base: GitLabCommitId = self
+            .http
+            .get(url)
+            .query(&[("refs[]", base_ref), ("refs[]", head_ref)])
+            .header("PRIVATE-TOKEN", &self.token)
+            .headers(self.extra_headers.clone())
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
 
-        let is_truncated = files
-            .iter()
-            .any(|f| f.too_large.unwrap_or(false) || f.generated_file.unwrap_or(false));
-        Ok(ChangeSet {
-            files: changes,
-            is_truncated,
-        })
+        Ok(compare_bundle(
+            project,
+            base_ref,
+            head_ref,
+            merge_base.id,
+            resp,
+        ))
     }
 
     /// Attempts to enrich truncated diffs by fetching raw unified text
@@ -188,14 +246,15 @@ impl GitLabClient {
         let url = format!(
             "{}/projects/{}/merge_requests/{}/raw_diffs",
             self.base_api,
-            urlencoding::encode(&id.project),
+            self.resolve_project(&id.project),
             id.iid
         );
         let raw = self
             .http
             .get(url)
             .header("PRIVATE-TOKEN", &self.token)
-            .send()
+            .headers(self.extra_headers.clone())
+            .send_throttled(&self.limiter)
             .await?
             .error_for_status()?
             .text()
@@ -257,6 +316,53 @@ impl GitLabClient {
         }))
     }
 
+    /// Bodies of the MR's notes (up to [`MAX_NOTE_PAGES`] pages of 100), for
+    /// marker lookups.
+    pub async fn list_note_bodies(&self, id: &ChangeRequestId) -> MrResult<Vec<String>> {
+        let mut url = format!("{}?per_page=100", self.notes_url(id));
+        let mut out = Vec::new();
+        for _ in 0..MAX_NOTE_PAGES {
+            let resp = self
+                .http
+                .get(&url)
+                .header("PRIVATE-TOKEN", &self.token)
+                .headers(self.extra_headers.clone())
+                .send_throttled(&self.limiter)
+                .await?
+                .error_for_status()?;
+            let next = next_page_url(&url, resp.headers());
+            let notes: Vec<GitLabNote> = resp.json().await?;
+            out.extend(notes.into_iter().filter_map(|n| n.body));
+            match next {
+                Some(next) => url = next,
+                None => break,
+            }
+        }
+        Ok(out)
+    }
+
+    /// Posts a general (non-diff) note on the MR.
+    pub async fn post_note(&self, id: &ChangeRequestId, body: &str) -> MrResult<()> {
+        self.http
+            .post(self.notes_url(id))
+            .header("PRIVATE-TOKEN", &self.token)
+            .headers(self.extra_headers.clone())
+            .json(&serde_json::json!({ "body": body }))
+            .send_throttled(&self.limiter)
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn notes_url(&self, id: &ChangeRequestId) -> String {
+        format!(
+            "{}/projects/{}/merge_requests/{}/notes",
+            self.base_api,
+            self.resolve_project(&id.project),
+            id.iid
+        )
+    }
+
     /// GET /projects/:id/repository/files/:path/raw?ref=:ref
     /// Returns `None` on 404; otherwise raw bytes of the file at the given ref.
     pub async fn get_file_raw(
@@ -268,7 +374,7 @@ impl GitLabClient {
         let url = format!(
             "{}/projects/{}/repository/files/{}/raw",
             self.base_api,
-            urlencoding::encode(&id.project),
+            self.resolve_project(&id.project),
             urlencoding::encode(repo_relative_path),
         );
 
@@ -277,7 +383,8 @@ impl GitLabClient {
             .get(url)
             .query(&[("ref", git_ref)])
             .header("PRIVATE-TOKEN", &self.token)
-            .send()
+            .headers(self.extra_headers.clone())
+            .send_throttled(&self.limiter)
             .await?;
 
         if resp.status().as_u16() == 404 {
@@ -290,6 +397,133 @@ impl GitLabClient {
     }
 }
 
+/// URL of the page after `url`, from `Link` (`rel="next"`) or `X-Next-Page`.
+pub(crate) fn next_page_url(url: &str, headers: &HeaderMap) -> Option<String> {
+    let link = headers
+        .get(reqwest::header::LINK)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| {
+            v.split(',').find_map(|part| {
+                let (target, params) = part.split_once(';')?;
+                params
+                    .contains("rel=\"next\"")
+                    .then(|| target.trim().trim_start_matches('<').trim_end_matches('>'))
+            })
+        });
+    if let Some(link) = link {
+        return Some(link.to_string());
+    }
+
+    let page = headers
+        .get("x-next-page")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|p| !p.is_empty())?;
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let mut params: Vec<String> = query
+        .split('&')
+        .filter(|kv| !kv.is_empty() && !kv.starts_with("page="))
+        .map(str::to_string)
+        .collect();
+    params.push(format!("page={page}"));
+    Some(format!("{}?{}", path, params.join("&")))
+}
+
+/// Normalizes a project reference into the `:id` segment of API URLs.
+///
+/// Numeric ids pass through unchanged. Paths are accepted as `group/sub/repo`,
+/// already URL-encoded (`group%2Fsub%2Frepo`), with a `.git` suffix or
+/// surrounding slashes, or as a pasted web URL (`https://host/group/repo/-/…`),
+/// and are encoded exactly once.
+pub fn project_segment(project: &str) -> String {
+    let mut p = project.trim();
+    if let Some(rest) = p
+        .strip_prefix("https://")
+        .or_else(|| p.strip_prefix("http://"))
+    {
+        p = rest.split_once('/').map_or("", |(_, path)| path);
+    }
+    if let Some((path, _)) = p.split_once("/-/") {
+        p = path;
+    }
+    let p = p.trim_matches('/');
+    let p = p.strip_suffix(".git").unwrap_or(p);
+    if p.bytes().all(|b| b.is_ascii_digit()) {
+        return p.to_string();
+    }
+    let decoded = urlencoding::decode(p).map_or_else(|_| p.to_string(), |d| d.into_owned());
+    urlencoding::encode(&decoded).into_owned()
+}
+
+/// Bundle for a compare response whose diff starts at `merge_base`.
+fn compare_bundle(
+    project: &str,
+    base_ref: &str,
+    head_ref: &str,
+    merge_base: String,
+    resp: GitLabCompare,
+) -> CrBundle {
+    let diff_refs = DiffRefs {
+        base_sha: merge_base.clone(),
+        start_sha: Some(merge_base),
+        head_sha: resp
+            .commit
+            .as_ref()
+            .map_or_else(|| head_ref.to_string(), |c| c.id.clone()),
+    };
+    let mut changes = changeset_from_diffs(resp.diffs);
+    changes.is_truncated |= resp.compare_timeout;
+    CrBundle {
+        meta: ChangeRequest::commit_range(
+            ProviderKind::GitLab,
+            project,
+            base_ref,
+            head_ref,
+            diff_refs,
+            resp.web_url.unwrap_or_default(),
+        ),
+        commits: resp
+            .commits
+            .into_iter()
+            .map(GitLabMrCommit::into_commit)
+            .collect(),
+        changes,
+    }
+}
+
+/// Normalizes `/diffs` (or compare `diffs`) entries into a change set.
+///
+/// Binary patches get no hunks; `too_large`/`generated_file` mark truncation.
+fn changeset_from_diffs(files: Vec<GitLabMrDiffFile>) -> ChangeSet {
+    let is_truncated = files
+        .iter()
+        .any(|f| f.too_large.unwrap_or(false) || f.generated_file.unwrap_or(false));
+    let files = files
+        .into_iter()
+        .map(|f| {
+            let is_binary = f.diff.as_deref().is_none_or(looks_like_binary_patch);
+            let hunks = match &f.diff {
+                Some(d) if !is_binary => parse_unified_diff_advanced(d),
+                _ => Vec::new(),
+            };
+            FileChange {
+                old_path: Some(f.old_path),
+                new_path: Some(f.new_path),
+                is_new: f.new_file,
+                is_deleted: f.deleted_file,
+                is_renamed: f.renamed_file,
+                is_binary,
+                hunks,
+                raw_unidiff: f.diff,
+            }
+        })
+        .collect();
+    ChangeSet {
+        files,
+        is_truncated,
+    }
+}
+
 /// --- GitLab response shapes (subset of fields we actually use) ---
 
 #[derive(Debug, Deserialize)]
@@ -303,6 +537,14 @@ struct GitLabMr {
     source_branch: String,
     target_branch: String,
     sha: String,
+    /// `draft` replaced `work_in_progress` in GitLab 13.x; accept both.
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    work_in_progress: bool,
+    /// Label names (the default, non-`with_labels_details` shape).
+    #[serde(default)]
+    labels: Vec<String>,
     diff_refs: GitLabDiffRefs,
     author: GitLabUser,
 }
@@ -336,6 +578,40 @@ struct GitLabMrCommit {
     web_url: Option<String>,
 }
 
+impl GitLabMrCommit {
+    fn into_commit(self) -> CrCommit {
+        CrCommit {
+            id: self.id,
+            title: self.title,
+            message: Some(self.message),
+            author_name: Some(self.author_name),
+            authored_at: self.created_at,
+            web_url: self.web_url,
+        }
+    }
+}
+
+/// `GET /repository/merge_base` (only the commit SHA is used).
+#[derive(Debug, Deserialize)]
+struct GitLabCommitId {
+    id: String,
+}
+
+/// `GET /repository/compare`; `commit` is the head commit (null for equal refs).
+#[derive(Debug, Deserialize)]
+struct GitLabCompare {
+    #[serde(default)]
+    commit: Option<GitLabMrCommit>,
+    #[serde(default)]
+    commits: Vec<GitLabMrCommit>,
+    #[serde(default)]
+    diffs: Vec<GitLabMrDiffFile>,
+    #[serde(default)]
+    compare_timeout: bool,
+    #[serde(default)]
+    web_url: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct GitLabMrDiffFile {
     old_path: String,
@@ -350,3 +626,88 @@ struct GitLabMrDiffFile {
     #[serde(default)]
     diff: Option<String>, // unified diff; None for binary/too large
 }
+
+/// `GET /merge_requests/:iid/notes`; only the body is needed.
+#[derive(Debug, Deserialize)]
+struct GitLabNote {
+    #[serde(default)]
+    body: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_and_path_projects_map_to_encoded_urls() {
+        assert_eq!(project_segment("4242"), "4242");
+        assert_eq!(project_segment("group/sub/repo"), "group%2Fsub%2Frepo");
+        for same in [
+            "group%2Fsub%2Frepo",
+            " /group/sub/repo.git/ ",
+            "https://gitlab.example/group/sub/repo/-/merge_requests/7",
+        ] {
+            assert_eq!(project_segment(same), "group%2Fsub%2Frepo", "{same}");
+        }
+
+        let client = GitLabClient::new(
+            Client::new(),
+            "https://gitlab.example/api/v4".into(),
+            "t".into(),
+        );
+        let id = |project: &str| ChangeRequestId {
+            project: project.into(),
+            iid: 7,
+        };
+        assert_eq!(
+            client.notes_url(&id("4242")),
+            "https://gitlab.example/api/v4/projects/4242/merge_requests/7/notes"
+        );
+        assert_eq!(
+            client.notes_url(&id("group/sub/repo")),
+            "https://gitlab.example/api/v4/projects/group%2Fsub%2Frepo/merge_requests/7/notes"
+        );
+        assert_eq!(
+            client.notes_url(&id("group%2Fsub%2Frepo")),
+            client.notes_url(&id("group/sub/repo"))
+        );
+        assert_eq!(client.projects.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn compare_response_normalizes_like_mr_diffs() {
+        let raw = r#"{
+            "commit": {"id": "bbb222", "short_id": "bbb", "title": "Add b",
+                       "message": "Add b\n", "author_name": "Dev"},
+            "commits": [{"id": "bbb222", "short_id": "bbb", "title": "Add b",
+                         "message": "Add b\n", "author_name": "Dev"}],
+            "diffs": [{"old_path": "src/a.rs", "new_path": "src/a.rs",
+                       "new_file": false, "renamed_file": false, "deleted_file": false,
+                       "diff": "@@ -1,1 +1,2 @@\n fn a() {}\n+fn b() {}\n"}],
+            "compare_timeout": false,
+            "web_url": "https://gitlab.example/g/app/-/compare/main...feature"
+        }"#;
+        let cmp: GitLabCompare = serde_json::from_str(raw).unwrap();
+        let base: GitLabCommitId =
+            serde_json::from_str(r#"{"id": "aaa111", "short_id": "aaa", "title": "Base"}"#)
+                .unwrap();
+
+        let bundle = compare_bundle("g/app", "main", "feature", base.id, cmp);
+        let changes = &bundle.changes;
+        assert!(!changes.is_truncated);
+        assert_eq!(changes.files.len(), 1);
+        assert!(!changes.files[0].is_binary);
+        assert_eq!(changes.files[0].hunks.len(), 1);
+        assert_eq!(bundle.commits.len(), 1);
+
+        let meta = bundle.meta;
+        assert_eq!(meta.diff_refs.base_sha, "aaa111");
+        assert_eq!(meta.diff_refs.start_sha.as_deref(), Some("aaa111"));
+        assert_eq!(meta.diff_refs.head_sha, "bbb222");
+        assert!(meta.web_url.ends_with("/compare/main...feature"));
+        assert_eq!(meta.id.iid, 0);
+        assert_eq!(meta.title, "main..feature");
+        assert_eq!(meta.target_branch.as_deref(), Some("main"));
+        assert!(!meta.is_draft);
+    }
+}
@@ -1,7 +1,16 @@
 //! Provider facade w/o async-trait or dynamic trait objects.
 //!
-//! We expose an enum `ProviderClient` with concrete implementations per provider.
-//! This keeps async fns simple and avoids boxing futures.
+//! `ProviderClient` wraps an enum `ProviderBackend` with concrete implementations
+//! per provider. This keeps async fns simple and avoids boxing futures.
+//!
+//! The client also keeps a per-run cache of raw file fetches keyed by
+//! `(path, git_ref)`; clones share it, so steps 1–4 fetch each file at most once.
+//!
+//! Besides reads, it can post a single general comment (e.g. a status note)
+//! without going through the step-5 publisher.
+//!
+//! Every network call first waits for the host's shared rate limiter (see
+//! [`rate_limit`]), so concurrent reviews in one process respect one budget.
 
 pub mod types;
 pub use types::*;
@@ -9,54 +18,381 @@ pub use types::*;
 pub mod bitbucket;
 pub mod github;
 pub mod gitlab;
+pub mod rate_limit;
+#[cfg(any(test, feature = "replay"))]
+pub mod recorded;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use services::http_proxy::apply_proxy_env;
+use tracing::{debug, warn};
 
-use crate::errors::MrResult;
+use crate::errors::{ConfigError, MrResult, ProviderError};
+
+/// Headers that carry credentials; extra headers may only set them with
+/// [`ProviderConfig::allow_auth_header_override`].
+const AUTH_HEADERS: &[&str] = &["authorization", "proxy-authorization", "private-token"];
 
 /// Runtime configuration for any provider client.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct ProviderConfig {
     pub kind: ProviderKind,
     /// API base, e.g. "https://gitlab.com/api/v4" or "https://api.github.com"
     pub base_api: String,
     /// Access token for the provider (PAT or app token).
     pub token: String,
+    /// TLS trust settings (custom CA for on-prem instances).
+    pub tls: TlsConfig,
+    /// Extra `(name, value)` headers sent with every provider request and by the
+    /// publisher, e.g. GitLab `Sudo` or an auth-gateway header. Values are
+    /// never logged.
+    pub extra_headers: Vec<(String, String)>,
+    /// Allow `extra_headers` to set credential headers (`Authorization`,
+    /// `Proxy-Authorization`, `PRIVATE-TOKEN`), replacing the token-based ones.
+    pub allow_auth_header_override: bool,
+}
+
+impl std::fmt::Debug for ProviderConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let header_names: Vec<&str> = self.extra_headers.iter().map(|(n, _)| n.as_str()).collect();
+        f.debug_struct("ProviderConfig")
+            .field("kind", &self.kind)
+            .field("base_api", &self.base_api)
+            .field("token", &"<redacted>")
+            .field("tls", &self.tls)
+            .field("extra_headers", &header_names)
+            .field(
+                "allow_auth_header_override",
+                &self.allow_auth_header_override,
+            )
+            .finish()
+    }
+}
+
+impl ProviderConfig {
+    /// Builds a config; an explicit `kind` is authoritative, otherwise it is
+    /// inferred from `base_api` (see [`ProviderConfig::infer_kind`]).
+    pub fn new(
+        kind: Option<ProviderKind>,
+        base_api: String,
+        token: String,
+        tls: TlsConfig,
+    ) -> Result<Self, ConfigError> {
+        let kind = match kind {
+            Some(k) => k,
+            None => Self::infer_kind(&base_api)
+                .ok_or_else(|| ConfigError::UnknownProviderKind(base_api.clone()))?,
+        };
+        debug!("provider config: kind={:?} base_api={}", kind, base_api);
+        Ok(Self {
+            kind,
+            base_api,
+            token,
+            tls,
+            extra_headers: Vec::new(),
+            allow_auth_header_override: false,
+        })
+    }
+
+    /// Sets [`ProviderConfig::extra_headers`] (validated when a client is built).
+    pub fn with_extra_headers(
+        mut self,
+        headers: Vec<(String, String)>,
+        allow_auth_header_override: bool,
+    ) -> Self {
+        self.extra_headers = headers;
+        self.allow_auth_header_override = allow_auth_header_override;
+        self
+    }
+
+    /// Validated `extra_headers` as a header map; values are marked sensitive.
+    ///
+    /// # Errors
+    /// [`ConfigError::InvalidHeader`] for a malformed name or value, or for a
+    /// credential header without `allow_auth_header_override`. Messages name
+    /// the header, never its value.
+    pub fn extra_header_map(&self) -> Result<HeaderMap, ConfigError> {
+        let mut map = HeaderMap::with_capacity(self.extra_headers.len());
+        for (name, value) in &self.extra_headers {
+            let name = name.trim();
+            let header = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| ConfigError::InvalidHeader(format!("bad header name '{name}'")))?;
+            if AUTH_HEADERS.contains(&header.as_str()) && !self.allow_auth_header_override {
+                return Err(ConfigError::InvalidHeader(format!(
+                    "'{name}' carries credentials; set allow_auth_header_override to replace it"
+                )));
+            }
+            let mut value = HeaderValue::from_str(value.trim()).map_err(|_| {
+                ConfigError::InvalidHeader(format!("bad value for header '{name}'"))
+            })?;
+            value.set_sensitive(true);
+            map.append(header, value);
+        }
+        Ok(map)
+    }
+
+    /// Parses `GIT_EXTRA_HEADERS` (`Name: value` pairs separated by `;`) and
+    /// `GIT_EXTRA_HEADERS_ALLOW_AUTH` (default: false).
+    pub fn extra_headers_from_env() -> (Vec<(String, String)>, bool) {
+        let headers = std::env::var("GIT_EXTRA_HEADERS")
+            .map(|v| parse_header_list(&v))
+            .unwrap_or_default();
+        let allow_auth = std::env::var("GIT_EXTRA_HEADERS_ALLOW_AUTH")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        (headers, allow_auth)
+    }
+
+    /// Guesses the provider from the API base URL:
+    /// - `api.github.com`, or a GitHub Enterprise `/api/v3` path → GitHub;
+    /// - `api.bitbucket.org` → Bitbucket;
+    /// - `gitlab.com`, or any self-hosted `/api/v4` path → GitLab.
+    pub fn infer_kind(base_api: &str) -> Option<ProviderKind> {
+        let rest = base_api
+            .trim()
+            .split_once("://")
+            .map_or(base_api.trim(), |(_, r)| r);
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let host = authority
+            .rsplit('@')
+            .next()
+            .unwrap_or(authority)
+            .split(':')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let path = format!("/{}", path.trim_end_matches('/').to_ascii_lowercase());
+
+        if host == "api.github.com" || path.ends_with("/api/v3") {
+            Some(ProviderKind::GitHub)
+        } else if host == "api.bitbucket.org" {
+            Some(ProviderKind::Bitbucket)
+        } else if host == "gitlab.com" || host.ends_with(".gitlab.com") || path.ends_with("/api/v4")
+        {
+            Some(ProviderKind::GitLab)
+        } else {
+            None
+        }
+    }
 }
 
-/// Concrete provider client (enum-dispatch).
+/// Splits `Name: value; Other: value` into pairs; entries without `:` are
+/// skipped (logged by position, since they may hold a bare secret).
+fn parse_header_list(raw: &str) -> Vec<(String, String)> {
+    raw.split(';')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .enumerate()
+        .filter_map(|(i, e)| match e.split_once(':') {
+            Some((n, v)) => Some((n.trim().to_string(), v.trim().to_string())),
+            None => {
+                warn!(
+                    "provider: extra header #{} has no ':' separator, skipped",
+                    i + 1
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// TLS trust settings for self-hosted providers behind an internal PKI.
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    /// PEM bundle with extra root CA certificates, trusted in addition to system roots.
+    pub ca_cert_path: Option<PathBuf>,
+    /// **DANGER**: disables certificate and hostname validation entirely.
+    /// Only for local debugging; prefer `ca_cert_path`. Off by default.
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl TlsConfig {
+    /// Reads TLS settings from the environment:
+    /// - `GIT_CA_CERT_PATH` — PEM bundle path (default: unset);
+    /// - `GIT_DANGER_ACCEPT_INVALID_CERTS` — `true` to skip validation (default: false).
+    pub fn from_env() -> Self {
+        Self {
+            ca_cert_path: std::env::var("GIT_CA_CERT_PATH")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from),
+            danger_accept_invalid_certs: std::env::var("GIT_DANGER_ACCEPT_INVALID_CERTS")
+                .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+        }
+    }
+
+    /// Applies these settings to a `reqwest` client builder.
+    pub fn apply(&self, mut builder: reqwest::ClientBuilder) -> MrResult<reqwest::ClientBuilder> {
+        if let Some(path) = &self.ca_cert_path {
+            let pem = std::fs::read(path)
+                .map_err(|e| ConfigError::InvalidCaCert(format!("{}: {}", path.display(), e)))?;
+            let certs = reqwest::Certificate::from_pem_bundle(&pem)
+                .map_err(|e| ConfigError::InvalidCaCert(format!("{}: {}", path.display(), e)))?;
+            debug!(
+                "tls: trusting {} extra root cert(s) from {}",
+                certs.len(),
+                path.display()
+            );
+            for cert in certs {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if self.danger_accept_invalid_certs {
+            tracing::warn!(
+                "tls: certificate validation DISABLED (GIT_DANGER_ACCEPT_INVALID_CERTS)"
+            );
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        Ok(builder)
+    }
+}
+
+/// Concrete provider backend (enum-dispatch).
 #[derive(Debug, Clone)]
-pub enum ProviderClient {
+pub enum ProviderBackend {
     GitLab(gitlab::GitLabClient),
     GitHub(github::GitHubClient),
     Bitbucket(bitbucket::BitbucketClient),
+    /// Recorded bundle + files; no network (snapshot tests).
+    #[cfg(any(test, feature = "replay"))]
+    Recorded(Box<recorded::RecordedClient>),
+}
+
+/// Per-run cache of raw file bytes: `(path, git_ref) -> Some(bytes) | None (404)`.
+type RawFileCache = Arc<Mutex<HashMap<(String, String), Option<Vec<u8>>>>>;
+
+/// Provider client: backend (holding the host rate limiter) + shared per-run raw file cache.
+#[derive(Debug, Clone)]
+pub struct ProviderClient {
+    backend: ProviderBackend,
+    raw_cache: RawFileCache,
 }
 
 impl ProviderClient {
     /// Constructs a concrete client from generic config.
     pub fn from_config(cfg: ProviderConfig) -> MrResult<Self> {
-        let client = reqwest::Client::builder()
-            .user_agent("mr-reviewer/0.1")
+        let extra = cfg.extra_header_map()?;
+        if !extra.is_empty() {
+            let names: Vec<&str> = extra.keys().map(|k| k.as_str()).collect();
+            debug!("provider: extra headers {:?}", names);
+        }
+        let client = cfg
+            .tls
+            .apply(apply_proxy_env(
+                reqwest::Client::builder().user_agent("mr-reviewer/0.1"),
+            )?)?
             .build()?;
-        Ok(match cfg.kind {
-            ProviderKind::GitLab => {
-                Self::GitLab(gitlab::GitLabClient::new(client, cfg.base_api, cfg.token))
+        let limiter = rate_limit::for_url(&cfg.base_api);
+        let backend = match cfg.kind {
+            ProviderKind::GitLab => ProviderBackend::GitLab(
+                gitlab::GitLabClient::new(client, cfg.base_api, cfg.token)
+                    .with_extra_headers(extra)
+                    .with_limiter(limiter),
+            ),
+            ProviderKind::GitHub => ProviderBackend::GitHub(
+                github::GitHubClient::new(client, cfg.base_api, cfg.token)
+                    .with_graphql(github_graphql_enabled())
+                    .with_extra_headers(extra)
+                    .with_limiter(limiter),
+            ),
+            ProviderKind::Bitbucket => ProviderBackend::Bitbucket(
+                bitbucket::BitbucketClient::new(client, cfg.base_api, cfg.token)
+                    .with_extra_headers(extra)
+                    .with_limiter(limiter),
+            ),
+        };
+        Ok(Self {
+            backend,
+            raw_cache: RawFileCache::default(),
+        })
+    }
+
+    #[cfg(any(test, feature = "replay"))]
+    /// Client over a recorded bundle and head file contents (no network).
+    pub fn from_recorded(bundle: types::CrBundle, files: HashMap<String, Vec<u8>>) -> Self {
+        Self {
+            backend: ProviderBackend::Recorded(Box::new(recorded::RecordedClient::new(
+                bundle, files,
+            ))),
+            raw_cache: RawFileCache::default(),
+        }
+    }
+
+    #[cfg(any(test, feature = "replay"))]
+    /// Client replaying a recording saved with [`recorded::RecordedBundle::save`]
+    /// (or a bare serialized `CrBundle`); no network.
+    ///
+    /// # Errors
+    /// See [`recorded::RecordedBundle::load`].
+    pub fn from_recorded_file(path: &std::path::Path) -> MrResult<Self> {
+        let rec = recorded::RecordedBundle::load(path)?;
+        let files = rec
+            .files
+            .into_iter()
+            .map(|(path, text)| (path, text.into_bytes()))
+            .collect();
+        Ok(Self::from_recorded(rec.bundle, files))
+    }
+
+    #[cfg(any(test, feature = "replay"))]
+    /// Captures `id` for offline replay: the bundle plus the head text of
+    /// every changed file that isn't deleted or binary. Files that are missing
+    /// at head or aren't UTF-8 are left out.
+    pub async fn record(&self, id: &types::ChangeRequestId) -> MrResult<recorded::RecordedBundle> {
+        let bundle = self.fetch_all(id).await?;
+        let head_sha = bundle.meta.diff_refs.head_sha.clone();
+        let mut files = std::collections::BTreeMap::new();
+        for fc in &bundle.changes.files {
+            if fc.is_deleted || fc.is_binary {
+                continue;
             }
-            ProviderKind::GitHub => {
-                Self::GitHub(github::GitHubClient::new(client, cfg.base_api, cfg.token))
+            let Some(path) = fc.new_path.as_ref().or(fc.old_path.as_ref()) else {
+                continue;
+            };
+            let bytes = self.fetch_file_raw_at_ref(id, path, &head_sha).await?;
+            if let Some(text) = bytes.and_then(|b| String::from_utf8(b).ok()) {
+                files.insert(path.clone(), text);
             }
-            ProviderKind::Bitbucket => Self::Bitbucket(bitbucket::BitbucketClient::new(
-                client,
-                cfg.base_api,
-                cfg.token,
-            )),
-        })
+        }
+        debug!(
+            "provider: recorded {} file(s) for {}!{}",
+            files.len(),
+            id.project,
+            id.iid
+        );
+        Ok(recorded::RecordedBundle { bundle, files })
+    }
+
+    /// Concrete backend (for provider-specific calls).
+    pub fn backend(&self) -> &ProviderBackend {
+        &self.backend
+    }
+
+    /// Project reference as it goes into API URLs.
+    ///
+    /// GitLab takes a numeric id or a `group/sub/repo` path, normalized and
+    /// URL-encoded once (see [`gitlab::project_segment`]) and cached per run.
+    /// Other providers use `owner/repo` as given.
+    pub fn resolve_project(&self, project: &str) -> String {
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.resolve_project(project),
+            _ => project.to_string(),
+        }
     }
 
     /// Fetch only metadata (cheap; gives head/base SHAs for cache key).
     pub async fn fetch_meta(&self, id: &types::ChangeRequestId) -> MrResult<types::ChangeRequest> {
-        match self {
-            Self::GitLab(c) => c.get_meta(id).await,
-            Self::GitHub(c) => c.get_meta(id).await,
-            Self::Bitbucket(c) => c.get_meta(id).await,
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.get_meta(id).await,
+            ProviderBackend::GitHub(c) => c.get_meta(id).await,
+            ProviderBackend::Bitbucket(c) => c.get_meta(id).await,
+            #[cfg(any(test, feature = "replay"))]
+            ProviderBackend::Recorded(c) => c.get_meta(id).await,
         }
     }
 
@@ -65,19 +401,23 @@ impl ProviderClient {
         &self,
         id: &types::ChangeRequestId,
     ) -> MrResult<Vec<types::CrCommit>> {
-        match self {
-            Self::GitLab(c) => c.get_commits(id).await,
-            Self::GitHub(c) => c.get_commits(id).await,
-            Self::Bitbucket(c) => c.get_commits(id).await,
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.get_commits(id).await,
+            ProviderBackend::GitHub(c) => c.get_commits(id).await,
+            ProviderBackend::Bitbucket(c) => c.get_commits(id).await,
+            #[cfg(any(test, feature = "replay"))]
+            ProviderBackend::Recorded(c) => c.get_commits(id).await,
         }
     }
 
     /// Fetch normalized change set (unified into hunks/lines).
     pub async fn fetch_changes(&self, id: &types::ChangeRequestId) -> MrResult<types::ChangeSet> {
-        match self {
-            Self::GitLab(c) => c.get_changeset(id).await,
-            Self::GitHub(c) => c.get_changeset(id).await,
-            Self::Bitbucket(c) => c.get_changeset(id).await,
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.get_changeset(id).await,
+            ProviderBackend::GitHub(c) => c.get_changeset(id).await,
+            ProviderBackend::Bitbucket(c) => c.get_changeset(id).await,
+            #[cfg(any(test, feature = "replay"))]
+            ProviderBackend::Recorded(c) => c.get_changeset(id).await,
         }
     }
 
@@ -86,15 +426,22 @@ impl ProviderClient {
         &self,
         id: &types::ChangeRequestId,
     ) -> MrResult<Option<types::ChangeSet>> {
-        match self {
-            Self::GitLab(c) => c.try_enrich_changeset(id).await,
-            Self::GitHub(c) => c.try_enrich_changeset(id).await,
-            Self::Bitbucket(c) => c.try_enrich_changeset(id).await,
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.try_enrich_changeset(id).await,
+            ProviderBackend::GitHub(c) => c.try_enrich_changeset(id).await,
+            ProviderBackend::Bitbucket(c) => c.try_enrich_changeset(id).await,
+            #[cfg(any(test, feature = "replay"))]
+            ProviderBackend::Recorded(c) => c.try_enrich_changeset(id).await,
         }
     }
 
     /// Convenience all-in-one fetch (meta + commits + changes).
+    ///
+    /// GitHub may serve this from a single batched GraphQL query (see `github`).
     pub async fn fetch_all(&self, id: &types::ChangeRequestId) -> MrResult<types::CrBundle> {
+        if let ProviderBackend::GitHub(c) = &self.backend {
+            return c.fetch_all(id, None).await;
+        }
         let meta = self.fetch_meta(id).await?;
         let commits = self.fetch_commits(id).await?;
         let changes = self.fetch_changes(id).await?;
@@ -105,19 +452,417 @@ impl ProviderClient {
         })
     }
 
+    /// Fetch a raw commit range `base_ref..head_ref` (no MR/PR) via the
+    /// provider's compare API, with synthetic metadata (`iid` 0).
+    ///
+    /// Not supported for Bitbucket yet.
+    pub async fn fetch_compare(
+        &self,
+        project: &str,
+        base_ref: &str,
+        head_ref: &str,
+    ) -> MrResult<types::CrBundle> {
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.get_compare(project, base_ref, head_ref).await,
+            ProviderBackend::GitHub(c) => c.get_compare(project, base_ref, head_ref).await,
+            ProviderBackend::Bitbucket(c) => c.get_compare(project, base_ref, head_ref).await,
+            #[cfg(any(test, feature = "replay"))]
+            ProviderBackend::Recorded(c) => c.get_compare(project, base_ref, head_ref).await,
+        }
+    }
+
+    /// Fetch commits and changes together for an already fetched `meta`.
+    ///
+    /// Lets GitHub use its batched GraphQL path (its REST fallback reuses
+    /// `meta`); other providers fetch both parts.
+    pub async fn fetch_commits_and_changes(
+        &self,
+        meta: &types::ChangeRequest,
+    ) -> MrResult<(Vec<types::CrCommit>, types::ChangeSet)> {
+        let id = &meta.id;
+        if let ProviderBackend::GitHub(c) = &self.backend {
+            let bundle = c.fetch_all(id, Some(meta.clone())).await?;
+            return Ok((bundle.commits, bundle.changes));
+        }
+        let commits = self.fetch_commits(id).await?;
+        let changes = self.fetch_changes(id).await?;
+        Ok((commits, changes))
+    }
+
+    /// Post one general (non-inline) comment on the change request: a GitLab
+    /// note, a GitHub issue comment or a Bitbucket PR comment.
+    ///
+    /// With `marker`, the body gets a hidden `<!-- mrai:note=<marker> -->` tag
+    /// and nothing is posted if a comment with that tag already exists (every
+    /// page of comments is checked). Returns `true` if a comment was posted.
+    ///
+    /// # Errors
+    /// `ProviderError::Unsupported` for commit-range metadata (`iid` 0) and
+    /// recorded clients; HTTP errors otherwise.
+    pub async fn post_general_comment(
+        &self,
+        meta: &types::ChangeRequest,
+        body: &str,
+        marker: Option<&str>,
+    ) -> MrResult<bool> {
+        let id = &meta.id;
+        if id.iid == 0 {
+            return Err(ProviderError::Unsupported.into());
+        }
+        let tag = marker.map(note_marker);
+        if let Some(tag) = &tag {
+            let existing = self.list_note_bodies(id).await?;
+            if existing.iter().any(|b| b.contains(tag.as_str())) {
+                debug!("provider: general comment {} already present", tag);
+                return Ok(false);
+            }
+        }
+        let body = match &tag {
+            Some(tag) => format!("{body}\n\n{tag}"),
+            None => body.to_string(),
+        };
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.post_note(id, &body).await?,
+            ProviderBackend::GitHub(c) => c.post_note(id, &body).await?,
+            ProviderBackend::Bitbucket(c) => c.post_note(id, &body).await?,
+            #[cfg(any(test, feature = "replay"))]
+            ProviderBackend::Recorded(c) => c.post_note(id, &body).await?,
+        }
+        Ok(true)
+    }
+
+    async fn list_note_bodies(&self, id: &types::ChangeRequestId) -> MrResult<Vec<String>> {
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.list_note_bodies(id).await,
+            ProviderBackend::GitHub(c) => c.list_note_bodies(id).await,
+            ProviderBackend::Bitbucket(c) => c.list_note_bodies(id).await,
+            #[cfg(any(test, feature = "replay"))]
+            ProviderBackend::Recorded(c) => c.list_note_bodies(id).await,
+        }
+    }
+
     /// Fetch raw file bytes at a specific git ref (e.g., MR head SHA).
     ///
     /// Returns `Ok(Some(bytes))` on success, `Ok(None)` if 404 (not found at ref).
+    ///
+    /// Lookup order: per-run cache → file materialized by step 2 under
+    /// `code_data/mr_tmp/<sha12>/` (SHA refs only) → provider API.
     pub async fn fetch_file_raw_at_ref(
         &self,
         id: &types::ChangeRequestId,
         repo_relative_path: &str,
         git_ref: &str,
     ) -> MrResult<Option<Vec<u8>>> {
-        match self {
-            Self::GitLab(c) => c.get_file_raw(id, repo_relative_path, git_ref).await,
-            Self::GitHub(c) => c.get_file_raw(id, repo_relative_path, git_ref).await,
-            Self::Bitbucket(c) => c.get_file_raw(id, repo_relative_path, git_ref).await,
+        let key = (repo_relative_path.to_string(), git_ref.to_string());
+        if let Some(hit) = self.raw_cache.lock().unwrap().get(&key) {
+            debug!("raw cache hit: {}@{}", repo_relative_path, git_ref);
+            return Ok(hit.clone());
+        }
+
+        if let Some(bytes) = read_materialized(repo_relative_path, git_ref) {
+            debug!("raw materialized hit: {}@{}", repo_relative_path, git_ref);
+            self.raw_cache
+                .lock()
+                .unwrap()
+                .insert(key, Some(bytes.clone()));
+            return Ok(Some(bytes));
+        }
+
+        let fetched = self
+            .fetch_file_raw_uncached(id, repo_relative_path, git_ref)
+            .await?;
+        self.raw_cache.lock().unwrap().insert(key, fetched.clone());
+        Ok(fetched)
+    }
+
+    async fn fetch_file_raw_uncached(
+        &self,
+        id: &types::ChangeRequestId,
+        repo_relative_path: &str,
+        git_ref: &str,
+    ) -> MrResult<Option<Vec<u8>>> {
+        match &self.backend {
+            ProviderBackend::GitLab(c) => c.get_file_raw(id, repo_relative_path, git_ref).await,
+            ProviderBackend::GitHub(c) => c.get_file_raw(id, repo_relative_path, git_ref).await,
+            ProviderBackend::Bitbucket(c) => c.get_file_raw(id, repo_relative_path, git_ref).await,
+            #[cfg(any(test, feature = "replay"))]
+            ProviderBackend::Recorded(c) => c.get_file_raw(id, repo_relative_path, git_ref).await,
         }
     }
 }
+
+/// Reads a file previously materialized by step 2 at `code_data/mr_tmp/<sha12>/<path>`.
+///
+/// Only commit SHAs are considered: branch names could move between runs.
+fn read_materialized(repo_relative_path: &str, git_ref: &str) -> Option<Vec<u8>> {
+    read_materialized_in(
+        &services::data_root::mr_tmp_dir(),
+        repo_relative_path,
+        git_ref,
+    )
+}
+
+/// [`read_materialized`] below `root`. The resolved file must stay under
+/// `root/<sha12>`: absolute paths, `..` and symlinks leading elsewhere are
+/// ignored.
+fn read_materialized_in(root: &Path, repo_relative_path: &str, git_ref: &str) -> Option<Vec<u8>> {
+    let is_sha = git_ref.len() >= 12 && git_ref.chars().all(|c| c.is_ascii_hexdigit());
+    if !is_sha {
+        return None;
+    }
+    let dir = root.join(&git_ref[..12]).canonicalize().ok()?;
+    let path = dir.join(repo_relative_path).canonicalize().ok()?;
+    if !path.starts_with(&dir) {
+        return None;
+    }
+    std::fs::read(path).ok()
+}
+
+/// Hidden idempotency tag for [`ProviderClient::post_general_comment`].
+fn note_marker(marker: &str) -> String {
+    format!("<!-- mrai:note={} -->", marker.trim())
+}
+
+/// Reads `MR_REVIEWER_GITHUB_GRAPHQL` (default: false).
+fn github_graphql_enabled() -> bool {
+    std::env::var("MR_REVIEWER_GITHUB_GRAPHQL")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_kind_from_base_api() {
+        let cases = [
+            ("https://gitlab.com/api/v4", Some(ProviderKind::GitLab)),
+            (
+                "https://git.corp.local:8443/api/v4/",
+                Some(ProviderKind::GitLab),
+            ),
+            (
+                "https://code.example.org/gitlab/api/v4",
+                Some(ProviderKind::GitLab),
+            ),
+            ("https://api.github.com", Some(ProviderKind::GitHub)),
+            ("https://ghe.corp.local/api/v3", Some(ProviderKind::GitHub)),
+            (
+                "https://api.bitbucket.org/2.0",
+                Some(ProviderKind::Bitbucket),
+            ),
+            ("https://scm.corp.local/rest", None),
+        ];
+        for (url, want) in cases {
+            assert_eq!(ProviderConfig::infer_kind(url), want, "{url}");
+        }
+    }
+
+    #[test]
+    fn explicit_kind_wins_and_unknown_errors() {
+        let cfg = ProviderConfig::new(
+            Some(ProviderKind::GitHub),
+            "https://gitlab.com/api/v4".into(),
+            "t".into(),
+            TlsConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(cfg.kind, ProviderKind::GitHub);
+
+        let err = ProviderConfig::new(
+            None,
+            "https://scm.corp.local".into(),
+            "t".into(),
+            TlsConfig::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ConfigError::UnknownProviderKind(_)));
+    }
+
+    #[test]
+    fn extra_headers_are_validated_and_redacted() {
+        let base = ProviderConfig::new(
+            Some(ProviderKind::GitLab),
+            "https://gitlab.com/api/v4".into(),
+            "t".into(),
+            TlsConfig::default(),
+        )
+        .unwrap();
+
+        let cfg = base
+            .clone()
+            .with_extra_headers(parse_header_list("Sudo: alice; X-Gateway: s3cr3t"), false);
+        let map = cfg.extra_header_map().unwrap();
+        assert_eq!(map.get("sudo").unwrap(), "alice");
+        assert!(map.get("x-gateway").unwrap().is_sensitive());
+        let shown = format!("{cfg:?} {map:?}");
+        assert!(shown.contains("X-Gateway") && !shown.contains("s3cr3t"));
+
+        let bad_name = base
+            .clone()
+            .with_extra_headers(vec![("Bad Name".into(), "v".into())], false);
+        assert!(matches!(
+            bad_name.extra_header_map(),
+            Err(ConfigError::InvalidHeader(_))
+        ));
+
+        let auth = vec![("Authorization".into(), "Bearer gw".into())];
+        let denied = base.clone().with_extra_headers(auth.clone(), false);
+        let err = denied.extra_header_map().unwrap_err().to_string();
+        assert!(err.contains("Authorization") && !err.contains("gw"));
+        let allowed = base.with_extra_headers(auth, true);
+        assert_eq!(allowed.extra_header_map().unwrap().len(), 1);
+
+        let bitbucket = bitbucket::BitbucketClient::new(
+            reqwest::Client::new(),
+            "https://bb".into(),
+            "t".into(),
+        )
+        .with_extra_headers(map);
+        let req = bitbucket
+            .request(reqwest::Method::GET, "https://bb/repositories")
+            .build()
+            .unwrap();
+        assert_eq!(req.headers().get("sudo").unwrap(), "alice");
+        assert_eq!(req.headers().get("x-gateway").unwrap(), "s3cr3t");
+        assert_eq!(req.headers().get("authorization").unwrap(), "Bearer t");
+    }
+
+    #[test]
+    fn general_comment_marker_is_hidden_and_trimmed() {
+        let tag = note_marker(" review-status ");
+        assert_eq!(tag, "<!-- mrai:note=review-status -->");
+        let body = format!("AI review in progress\n\n{tag}");
+        assert!(body.contains(&note_marker("review-status")));
+        assert!(!body.contains(&note_marker("review-summary")));
+    }
+
+    /// Serves two pages of comments for every provider; the marker is on page 2.
+    async fn mock_comment_pages() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let tagged = serde_json::json!(format!("done\n\n{}", note_marker("review-status")));
+            loop {
+                let Ok((mut sock, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut buf = vec![0u8; 8192];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let req = String::from_utf8_lossy(&buf[..n]);
+                let target = req.split_whitespace().nth(1).unwrap_or("").to_string();
+                let last = target.contains("page=2");
+                let body = if last {
+                    tagged.clone()
+                } else {
+                    "unrelated".into()
+                };
+                let (extra, json) = if target.contains("/issues/") {
+                    // GitHub: a short page ends the listing.
+                    let count = if last { 1 } else { 100 };
+                    let page: Vec<_> = (0..count)
+                        .map(|_| serde_json::json!({ "body": body }))
+                        .collect();
+                    (String::new(), serde_json::json!(page))
+                } else if target.contains("/notes") {
+                    let next = if last { "" } else { "2" };
+                    (
+                        format!("X-Next-Page: {next}\r\n"),
+                        serde_json::json!([{ "body": body }]),
+                    )
+                } else {
+                    let next = (!last).then(|| format!("http://{addr}{target}&page=2"));
+                    (
+                        String::new(),
+                        serde_json::json!({ "values": [{ "content": { "raw": body } }], "next": next }),
+                    )
+                };
+                let json = json.to_string();
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n{extra}Content-Length: {}\r\nConnection: close\r\n\r\n{json}",
+                    json.len()
+                );
+                let _ = sock.write_all(resp.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn marker_on_a_later_page_prevents_a_second_comment() {
+        let host = mock_comment_pages().await;
+        let cases = [
+            (ProviderKind::GitLab, format!("{host}/api/v4")),
+            (ProviderKind::GitHub, host.clone()),
+            (ProviderKind::Bitbucket, format!("{host}/2.0")),
+        ];
+        for (kind, base) in cases {
+            let cfg =
+                ProviderConfig::new(Some(kind), base, "t".into(), TlsConfig::default()).unwrap();
+            let client = ProviderClient::from_config(cfg).unwrap();
+            let mut meta = types::ChangeRequest::commit_range(
+                kind,
+                "o/r",
+                "main",
+                "feature",
+                types::DiffRefs {
+                    base_sha: "a".into(),
+                    start_sha: None,
+                    head_sha: "b".into(),
+                },
+                String::new(),
+            );
+            meta.id.iid = 7;
+
+            let posted = client
+                .post_general_comment(&meta, "done", Some("review-status"))
+                .await
+                .unwrap();
+            assert!(!posted, "{kind:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn every_page_takes_a_rate_limit_token() {
+        let host = mock_comment_pages().await;
+        let limiter = Arc::new(rate_limit::RateLimiter::new(10.0, 1));
+        let client = github::GitHubClient::new(reqwest::Client::new(), host, "t".into())
+            .with_limiter(Some(limiter));
+        let id = types::ChangeRequestId {
+            project: "o/r".into(),
+            iid: 7,
+        };
+
+        let start = std::time::Instant::now();
+        let bodies = client.list_note_bodies(&id).await.unwrap();
+        assert_eq!(bodies.len(), 101);
+        client.list_note_bodies(&id).await.unwrap();
+        // Four requests: the first is free, the other three wait 100ms each.
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= std::time::Duration::from_millis(280),
+            "{elapsed:?}"
+        );
+    }
+
+    #[test]
+    fn materialized_reads_stay_under_the_review_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        std::fs::create_dir_all(root.join("0123456789ab/lib")).unwrap();
+        std::fs::write(root.join("0123456789ab/lib/a.dart"), "void main() {}").unwrap();
+        let secret = root.join("secret.txt");
+        std::fs::write(&secret, "outside the review").unwrap();
+
+        let read = |path: &str| read_materialized_in(root, path, sha);
+        assert_eq!(read("lib/a.dart").as_deref(), Some(&b"void main() {}"[..]));
+        assert!(read(secret.to_str().unwrap()).is_none());
+        assert!(read("../secret.txt").is_none());
+        assert!(read("lib/../../secret.txt").is_none());
+        assert!(read_materialized_in(root, "lib/a.dart", "main").is_none());
+    }
+}
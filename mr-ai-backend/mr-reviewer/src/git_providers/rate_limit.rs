@@ -0,0 +1,226 @@
+//! Process-wide request rate limiting per provider host.
+//!
+//! Concurrent reviews in one process share a token bucket per host (e.g.
+//! `gitlab.corp.local:8443`), so together they stay under the provider's
+//! org-wide rate limit. Every request a provider client sends (each page of a
+//! listing, a GraphQL query and its REST fallback alike, see
+//! [`SendThrottled`]) and every step-5 publish attempt (retries included)
+//! takes a token first.
+//!
+//! Configured by `MR_REVIEWER_PROVIDER_RPS` (requests per second; unset or `0`
+//! disables limiting) and `MR_REVIEWER_PROVIDER_BURST` (bucket size, default 1).
+//! Both are re-read whenever a client is built, and a changed value retunes the
+//! host's shared bucket. Callers that have to wait get a little jitter so they
+//! don't wake in lockstep.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use reqwest::{RequestBuilder, Response};
+use tracing::debug;
+
+/// Largest jitter added to a throttled wait, as a fraction of one interval.
+const JITTER_FRACTION: f64 = 0.1;
+
+lazy_static::lazy_static! {
+    /// Limiters shared by every client in the process, keyed by host.
+    static ref LIMITERS: Mutex<HashMap<String, Arc<RateLimiter>>> = Mutex::new(HashMap::new());
+}
+
+/// Token bucket refilled at `rate` tokens per second, holding at most `burst`.
+///
+/// Callers reserve a token up front: the balance may go negative, and each
+/// caller sleeps until its own token is due. Waiters are served in arrival
+/// order and the rate holds however many tasks call at once.
+#[derive(Debug)]
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    burst: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    /// `rate` requests per second with bursts of up to `burst` (at least 1).
+    pub fn new(rate: f64, burst: u32) -> Self {
+        let burst = f64::from(burst.max(1));
+        Self {
+            bucket: Mutex::new(Bucket {
+                rate,
+                burst,
+                tokens: burst,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    /// Switches to `rate` and `burst`; reserved tokens stay reserved.
+    pub fn reconfigure(&self, rate: f64, burst: u32) {
+        let mut b = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        b.rate = rate;
+        b.burst = f64::from(burst.max(1));
+        b.tokens = b.tokens.min(b.burst);
+    }
+
+    /// Waits until a request may be sent.
+    pub async fn acquire(&self) {
+        let (wait, rate) = self.reserve(Instant::now());
+        if !wait.is_zero() {
+            let wait = wait + jitter(Duration::from_secs_f64(JITTER_FRACTION / rate));
+            debug!("provider rate limit: waiting {}ms", wait.as_millis());
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Takes one token at `now`; returns how long until it is due and the
+    /// rate it was reserved at.
+    fn reserve(&self, now: Instant) -> (Duration, f64) {
+        let mut b = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let elapsed = now.saturating_duration_since(b.last).as_secs_f64();
+        b.tokens = (b.tokens + elapsed * b.rate).min(b.burst) - 1.0;
+        b.last = now;
+        let wait = if b.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-b.tokens / b.rate)
+        };
+        (wait, b.rate)
+    }
+}
+
+/// [`RequestBuilder::send`] after a token from the client's limiter, if any.
+/// Provider clients send every request through it.
+pub(crate) trait SendThrottled {
+    async fn send_throttled(self, limiter: &Option<Arc<RateLimiter>>) -> reqwest::Result<Response>;
+}
+
+impl SendThrottled for RequestBuilder {
+    async fn send_throttled(self, limiter: &Option<Arc<RateLimiter>>) -> reqwest::Result<Response> {
+        if let Some(limiter) = limiter {
+            limiter.acquire().await;
+        }
+        self.send().await
+    }
+}
+
+/// Shared limiter for the host of `url`, or `None` when limiting is disabled
+/// or the URL has no host. An existing limiter is retuned to the current
+/// settings.
+pub fn for_url(url: &str) -> Option<Arc<RateLimiter>> {
+    let rate = requests_per_second()?;
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = match parsed.port() {
+        Some(port) => format!("{}:{port}", parsed.host_str()?),
+        None => parsed.host_str()?.to_string(),
+    };
+    let burst = burst();
+    let mut map = LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+    let limiter = map
+        .entry(host)
+        .or_insert_with_key(|host| {
+            debug!("provider rate limit: {host} at {rate} req/s");
+            Arc::new(RateLimiter::new(rate, burst))
+        })
+        .clone();
+    limiter.reconfigure(rate, burst);
+    Some(limiter)
+}
+
+/// Waits for the shared limiter of `url`'s host, if limiting is enabled.
+pub async fn acquire_for(url: &str) {
+    if let Some(limiter) = for_url(url) {
+        limiter.acquire().await;
+    }
+}
+
+/// Reads `MR_REVIEWER_PROVIDER_RPS`; `None` when unset, invalid or not positive.
+fn requests_per_second() -> Option<f64> {
+    std::env::var("MR_REVIEWER_PROVIDER_RPS")
+        .ok()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|r| r.is_finite() && *r > 0.0)
+}
+
+/// Reads `MR_REVIEWER_PROVIDER_BURST` (default 1).
+fn burst() -> u32 {
+    std::env::var("MR_REVIEWER_PROVIDER_BURST")
+        .ok()
+        .and_then(|v| v.trim().parse::<u32>().ok())
+        .unwrap_or(1)
+}
+
+/// Pseudo-random duration in `[0, max]` from the clock's sub-second nanos.
+fn jitter(max: Duration) -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    max.mul_f64(f64::from(nanos % 1_000) / 1_000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reservations_follow_the_configured_rate() {
+        let limiter = RateLimiter::new(10.0, 2);
+        let t0 = Instant::now();
+        // Burst of two, then one token every 100ms.
+        let wait = |at: Instant| limiter.reserve(at).0;
+        assert_eq!(wait(t0), Duration::ZERO);
+        assert_eq!(wait(t0), Duration::ZERO);
+        let third = wait(t0);
+        let fourth = wait(t0);
+        assert!((third.as_secs_f64() - 0.1).abs() < 1e-6, "{third:?}");
+        assert!((fourth.as_secs_f64() - 0.2).abs() < 1e-6, "{fourth:?}");
+        // Idle time refills the bucket, capped at the burst size.
+        let later = t0 + Duration::from_secs(10);
+        assert_eq!(wait(later), Duration::ZERO);
+        assert_eq!(wait(later), Duration::ZERO);
+        assert!(wait(later) > Duration::ZERO);
+    }
+
+    #[test]
+    fn reconfigure_changes_rate_and_burst() {
+        let limiter = RateLimiter::new(10.0, 1);
+        let t0 = Instant::now();
+        assert_eq!(limiter.reserve(t0).0, Duration::ZERO);
+
+        limiter.reconfigure(2.0, 3);
+        // One token per 500ms now; the bucket refills up to three.
+        let (next, rate) = limiter.reserve(t0);
+        assert_eq!(rate, 2.0);
+        assert!((next.as_secs_f64() - 0.5).abs() < 1e-6, "{next:?}");
+        let later = t0 + Duration::from_secs(10);
+        for _ in 0..3 {
+            assert_eq!(limiter.reserve(later).0, Duration::ZERO);
+        }
+        assert!(limiter.reserve(later).0 > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn concurrent_calls_are_throttled_to_the_rate() {
+        let limiter = Arc::new(RateLimiter::new(20.0, 1));
+        let start = Instant::now();
+        let tasks: Vec<_> = (0..6)
+            .map(|_| {
+                let limiter = limiter.clone();
+                tokio::spawn(async move { limiter.acquire().await })
+            })
+            .collect();
+        for t in tasks {
+            t.await.unwrap();
+        }
+        // First call is free, the other five wait 50ms each in turn.
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(250), "{elapsed:?}");
+        assert!(elapsed < Duration::from_secs(2), "{elapsed:?}");
+    }
+}
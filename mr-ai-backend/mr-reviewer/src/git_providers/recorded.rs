@@ -0,0 +1,208 @@
+//! Offline provider serving a recorded `CrBundle` and head file contents.
+//!
+//! Lets steps 1–4 run without network access (see `crate::snapshot`).
+//! Only the head commit is available; other refs behave like a 404.
+//!
+//! A [`RecordedBundle`] is the single-file form of a recording: capture one
+//! with [`ProviderClient::record`](super::ProviderClient::record), attach
+//! the JSON to an issue, and replay it with
+//! [`ProviderClient::from_recorded_file`](super::ProviderClient::from_recorded_file).
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{MrResult, ProviderError};
+use crate::git_providers::types::*;
+
+/// A change request captured for offline replay: the step-1 bundle plus the
+/// text of changed files at the head commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedBundle {
+    pub bundle: CrBundle,
+    /// Repo-relative path → content at `bundle.meta.diff_refs.head_sha`.
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl RecordedBundle {
+    /// Reads a recording from `path`; a bare serialized `CrBundle` (e.g. from
+    /// the large-diff cache) is accepted too and replays without file contents.
+    ///
+    /// # Errors
+    /// I/O errors, or JSON that is neither a recording nor a bundle.
+    pub fn load(path: &Path) -> MrResult<Self> {
+        let raw = std::fs::read(path)?;
+        match serde_json::from_slice::<Self>(&raw) {
+            Ok(rec) => Ok(rec),
+            Err(_) => Ok(Self {
+                bundle: serde_json::from_slice(&raw)?,
+                files: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Writes the recording to `path` as pretty JSON.
+    ///
+    /// # Errors
+    /// Serialization or I/O errors.
+    pub fn save(&self, path: &Path) -> MrResult<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedClient {
+    bundle: CrBundle,
+    /// Repo-relative path → bytes at `bundle.meta.diff_refs.head_sha`.
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl RecordedClient {
+    pub fn new(bundle: CrBundle, files: HashMap<String, Vec<u8>>) -> Self {
+        Self { bundle, files }
+    }
+
+    pub async fn get_meta(&self, _id: &ChangeRequestId) -> MrResult<ChangeRequest> {
+        Ok(self.bundle.meta.clone())
+    }
+
+    pub async fn get_commits(&self, _id: &ChangeRequestId) -> MrResult<Vec<CrCommit>> {
+        Ok(self.bundle.commits.clone())
+    }
+
+    pub async fn get_changeset(&self, _id: &ChangeRequestId) -> MrResult<ChangeSet> {
+        Ok(self.bundle.changes.clone())
+    }
+
+    /// Recordings are complete by construction; nothing to enrich.
+    pub async fn try_enrich_changeset(&self, _id: &ChangeRequestId) -> MrResult<Option<ChangeSet>> {
+        Ok(None)
+    }
+
+    /// The recording stands in for any range; refs are not checked.
+    pub async fn get_compare(
+        &self,
+        _project: &str,
+        _base_ref: &str,
+        _head_ref: &str,
+    ) -> MrResult<CrBundle> {
+        Ok(self.bundle.clone())
+    }
+
+    /// Nothing is posted from a recording.
+    pub async fn list_note_bodies(&self, _id: &ChangeRequestId) -> MrResult<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Recordings are read-only.
+    pub async fn post_note(&self, _id: &ChangeRequestId, _body: &str) -> MrResult<()> {
+        Err(ProviderError::Unsupported.into())
+    }
+
+    pub async fn get_file_raw(
+        &self,
+        _id: &ChangeRequestId,
+        repo_relative_path: &str,
+        git_ref: &str,
+    ) -> MrResult<Option<Vec<u8>>> {
+        if git_ref != self.bundle.meta.diff_refs.head_sha {
+            return Ok(None);
+        }
+        Ok(self.files.get(repo_relative_path).cloned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_providers::ProviderClient;
+
+    fn recording() -> RecordedBundle {
+        let bundle: CrBundle = serde_json::from_value(serde_json::json!({
+            "meta": {
+                "provider": "GitLab",
+                "id": { "project": "g/app", "iid": 42 },
+                "title": "Add retries", "description": "Retry flaky calls",
+                "author": { "id": "1", "username": "dev", "name": null, "web_url": null, "avatar_url": null },
+                "state": "opened", "web_url": "https://gl/g/app/-/merge_requests/42",
+                "created_at": "2025-01-01T00:00:00Z", "updated_at": "2025-01-02T00:00:00Z",
+                "source_branch": "feat/retry", "target_branch": "main",
+                "diff_refs": { "base_sha": "b1", "start_sha": "s1", "head_sha": "replay-test-head" },
+                "is_draft": false, "labels": ["backend"]
+            },
+            "commits": [{
+                "id": "c1", "title": "Add retries", "message": null, "author_name": "dev",
+                "authored_at": "2025-01-01T12:00:00Z", "web_url": null
+            }],
+            "changes": { "files": [
+                {
+                    "old_path": "lib/a.dart", "new_path": "lib/a.dart",
+                    "is_new": false, "is_deleted": false, "is_renamed": false, "is_binary": false,
+                    "hunks": [{
+                        "old_start": 1, "old_lines": 1, "new_start": 1, "new_lines": 2,
+                        "lines": [
+                            { "Context": { "old_line": 1, "new_line": 1, "content": "void main() {" } },
+                            { "Removed": { "old_line": 2, "content": "  run();" } },
+                            { "Added": { "new_line": 2, "content": "  retry(run);" } }
+                        ]
+                    }],
+                    "raw_unidiff": null
+                },
+                {
+                    "old_path": "lib/old.dart", "new_path": null,
+                    "is_new": false, "is_deleted": true, "is_renamed": false, "is_binary": false,
+                    "hunks": [], "raw_unidiff": null
+                }
+            ], "is_truncated": false }
+        }))
+        .unwrap();
+        RecordedBundle {
+            bundle,
+            files: BTreeMap::from([(
+                "lib/a.dart".to_string(),
+                "void main() {\n  retry(run);\n}\n".to_string(),
+            )]),
+        }
+    }
+
+    #[tokio::test]
+    async fn recording_round_trips_through_file_and_replay_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.json");
+        let rec = recording();
+        rec.save(&path).unwrap();
+
+        let loaded = RecordedBundle::load(&path).unwrap();
+        let json = |r: &RecordedBundle| serde_json::to_value(r).unwrap();
+        assert_eq!(json(&loaded), json(&rec));
+
+        // The replay client serves the recording and records it back unchanged.
+        let client = ProviderClient::from_recorded_file(&path).unwrap();
+        let id = rec.bundle.meta.id.clone();
+        assert_eq!(
+            client.fetch_meta(&id).await.unwrap().labels,
+            vec!["backend"]
+        );
+        assert_eq!(client.fetch_changes(&id).await.unwrap().files.len(), 2);
+        let raw = client
+            .fetch_file_raw_at_ref(&id, "lib/a.dart", "replay-test-head")
+            .await
+            .unwrap();
+        assert_eq!(raw.as_deref(), Some(rec.files["lib/a.dart"].as_bytes()));
+        let other_ref = client.fetch_file_raw_at_ref(&id, "lib/a.dart", "b1").await;
+        assert!(other_ref.unwrap().is_none());
+        assert_eq!(json(&client.record(&id).await.unwrap()), json(&rec));
+
+        // A bare bundle (e.g. from the large-diff cache) replays without files.
+        std::fs::write(&path, serde_json::to_vec(&rec.bundle).unwrap()).unwrap();
+        let bare = RecordedBundle::load(&path).unwrap();
+        assert!(bare.files.is_empty());
+        assert_eq!(
+            serde_json::to_value(&bare.bundle).unwrap(),
+            serde_json::to_value(&rec.bundle).unwrap()
+        );
+    }
+}
@@ -18,7 +18,8 @@ pub enum ProviderKind {
 ///
 /// * `project` – GitLab: numeric ID or "group/project";
 ///                GitHub: "owner/repo"; Bitbucket: "workspace/repo_slug".
-/// * `iid`     – GitLab MR IID or GitHub/Bitbucket PR number.
+/// * `iid`     – GitLab MR IID or GitHub/Bitbucket PR number; 0 for a
+///                commit-range review (see [`ChangeRequest::commit_range`]).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChangeRequestId {
     pub project: String,
@@ -64,6 +65,13 @@ pub struct ChangeRequest {
     pub source_branch: Option<String>,
     pub target_branch: Option<String>,
     pub diff_refs: DiffRefs,
+    /// True if the provider marks the change request as draft/WIP.
+    #[serde(default)]
+    pub is_draft: bool,
+    /// Provider labels as shown in the UI (e.g. `skip-ai-review`); drive
+    /// per-MR gating via `ReviewOptions::skip_labels` / `security_labels`.
+    #[serde(default)]
+    pub labels: Vec<String>,
 }
 
 /// A single commit belonging to the MR/PR.
@@ -134,3 +142,45 @@ pub struct CrBundle {
     pub commits: Vec<CrCommit>,
     pub changes: ChangeSet,
 }
+
+impl ChangeRequest {
+    /// Synthetic metadata for reviewing a raw commit range (no MR/PR).
+    ///
+    /// `id.iid` is 0; the title is `base..head` and the refs double as
+    /// target/source branch so prompts and logs still read naturally.
+    pub fn commit_range(
+        provider: ProviderKind,
+        project: &str,
+        base_ref: &str,
+        head_ref: &str,
+        diff_refs: DiffRefs,
+        web_url: String,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            provider,
+            id: ChangeRequestId {
+                project: project.to_string(),
+                iid: 0,
+            },
+            title: format!("{base_ref}..{head_ref}"),
+            description: None,
+            author: AuthorInfo {
+                id: String::new(),
+                username: None,
+                name: None,
+                web_url: None,
+                avatar_url: None,
+            },
+            state: "commit_range".into(),
+            web_url,
+            created_at: now,
+            updated_at: now,
+            source_branch: Some(head_ref.to_string()),
+            target_branch: Some(base_ref.to_string()),
+            diff_refs,
+            is_draft: false,
+            labels: Vec::new(),
+        }
+    }
+}
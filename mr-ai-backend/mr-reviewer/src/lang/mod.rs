@@ -8,6 +8,10 @@
 //! The resulting `SymbolIndex` is used by targeting to map diff lines to their
 //! owning symbol, enabling precise Symbol/Range/Line comments and focused LLM
 //! prompts. Global RAG (built on master) remains unchanged.
+//!
+//! Dart libraries split with `part` / `part of` are linked among the changed
+//! files ([`SymbolIndex::library_of`]), so a part's members are looked up
+//! together with their library's.
 
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
@@ -29,6 +33,8 @@ use tracing::{debug, warn};
 use crate::errors::MrResult;
 use crate::git_providers::types::{CrBundle, DiffLine};
 use crate::git_providers::{ChangeRequestId, ProviderClient};
+use crate::map::PathScope;
+pub use services::skipped_file::SkippedFile;
 
 /// Linear byte span inside a file. Always available as a fallback.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -117,16 +123,18 @@ fn kind_label(k: SymbolKind) -> &'static str {
     }
 }
 
-/// Render a compact, human-friendly snippet for prompts/logs.
-/// We avoid loading full file contents (keeps it cheap).
-fn synth_snippet(s: &SymbolRecord) -> String {
+/// Max source lines taken as a symbol's declaration in prompt snippets.
+const DECL_MAX_LINES: usize = 6;
+
+/// `kind name  (lines: a..b)` + `file: path` header shared by all snippets.
+fn snippet_header(s: &SymbolRecord) -> String {
     let lines = s
         .body_span
         .lines
         .map(|l| format!("{}..{}", l.start_line, l.end_line))
         .unwrap_or_else(|| "-".into());
     format!(
-        "{} {}  (lines: {})\nfile: {}\n// synthetic header only",
+        "{} {}  (lines: {})\nfile: {}",
         kind_label(s.kind),
         s.name,
         lines,
@@ -134,6 +142,37 @@ fn synth_snippet(s: &SymbolRecord) -> String {
     )
 }
 
+/// Render a compact, human-friendly snippet for prompts/logs when the file
+/// text isn't available.
+fn synth_snippet(s: &SymbolRecord) -> String {
+    format!("{}\n// synthetic header only", snippet_header(s))
+}
+
+/// Declaration lines of `s` in `text`: from the declaration line up to the
+/// first line outside the parameter list that opens a body (`{`, `=>`, `:`)
+/// or ends the statement (`;`), at most [`DECL_MAX_LINES`] and never past the
+/// symbol's body.
+fn decl_lines(text: &str, s: &SymbolRecord) -> Option<String> {
+    let start = s.decl_span.lines?.start_line as usize;
+    let end = s.body_span.lines.map_or(start, |l| l.end_line as usize);
+    let mut out: Vec<&str> = Vec::new();
+    let mut parens = 0i32;
+    for line in text
+        .lines()
+        .skip(start.checked_sub(1)?)
+        .take((end.max(start) + 1 - start).min(DECL_MAX_LINES))
+    {
+        let line = line.trim_end();
+        out.push(line);
+        parens += line.matches('(').count() as i32 - line.matches(')').count() as i32;
+        let opens = line.contains('{') || line.contains("=>") || line.ends_with(':');
+        if parens <= 0 && (opens || line.ends_with(';')) {
+            break;
+        }
+    }
+    (!out.iter().all(|l| l.trim().is_empty())).then(|| out.join("\n"))
+}
+
 /// Case-insensitive "contains" helper.
 fn contains_ci(hay: &str, needle: &str) -> bool {
     hay.to_ascii_lowercase()
@@ -151,9 +190,30 @@ pub struct SymbolIndex {
     pub by_name: BTreeMap<String, Vec<usize>>,
     /// Map: `symbol_id -> index` into `symbols`.
     pub by_id: HashMap<String, usize>,
+    /// Changed files whose parse failed or hit syntax errors. Symbols from
+    /// these are missing or approximate, so targeting on them may be imprecise.
+    pub parse_failures: Vec<String>,
+    /// Where changed files were materialized (`mr_tmp/<head12>`), if on disk.
+    pub tmp_root: Option<PathBuf>,
+    /// Changed files that were not indexed at all (missing at the ref,
+    /// binary-looking or not decodable as UTF-8). They get no symbols.
+    pub skipped_files: Vec<SkippedFile>,
+    /// Map: Dart `part` file path -> its library's path (both changed files).
+    pub library_of: BTreeMap<String, String>,
 }
 
 impl SymbolIndex {
+    /// Prompt snippet for `s`: its real declaration/signature lines when the
+    /// file is materialized under [`Self::tmp_root`], else the synthetic header.
+    pub fn symbol_snippet(&self, s: &SymbolRecord) -> String {
+        self.tmp_root
+            .as_ref()
+            .and_then(|root| fs::read_to_string(root.join(&s.path)).ok())
+            .and_then(|text| decl_lines(&text, s))
+            .map(|decl| format!("{}\n{}", snippet_header(s), decl))
+            .unwrap_or_else(|| synth_snippet(s))
+    }
+
     /// Search by **symbol name** (highest precision here).
     pub async fn search_symbol(&self, needle: &str) -> MrResult<Vec<RagDoc>> {
         debug!("rag_shim.search_symbol: needle={}", needle);
@@ -166,7 +226,7 @@ impl SymbolIndex {
                 out.push(RagDoc {
                     path: s.path.clone(),
                     language: format!("{:?}", s.language),
-                    snippet: synth_snippet(s),
+                    snippet: self.symbol_snippet(s),
                     symbol: Some(s.name.clone()),
                 });
             }
@@ -181,7 +241,7 @@ impl SymbolIndex {
                         out.push(RagDoc {
                             path: s.path.clone(),
                             language: format!("{:?}", s.language),
-                            snippet: synth_snippet(s),
+                            snippet: self.symbol_snippet(s),
                             symbol: Some(s.name.clone()),
                         });
                     }
@@ -206,7 +266,7 @@ impl SymbolIndex {
                     out.push(RagDoc {
                         path: s.path.clone(),
                         language: format!("{:?}", s.language),
-                        snippet: synth_snippet(s),
+                        snippet: self.symbol_snippet(s),
                         symbol: Some(s.name.clone()),
                     });
                 }
@@ -217,7 +277,7 @@ impl SymbolIndex {
         Ok(out)
     }
 
-    /// Fallback "text" search across name + path (cheap; no file bodies).
+    /// Fallback "text" search across name + path (no full file bodies).
     pub async fn search_text(&self, q: &str) -> MrResult<Vec<RagDoc>> {
         debug!("rag_shim.search_text: q={}", q);
         let mut out = Vec::new();
@@ -230,7 +290,7 @@ impl SymbolIndex {
                     out.push(RagDoc {
                         path: s.path.clone(),
                         language: format!("{:?}", s.language),
-                        snippet: synth_snippet(s),
+                        snippet: self.symbol_snippet(s),
                         symbol: Some(s.name.clone()),
                     });
                 }
@@ -245,7 +305,7 @@ impl SymbolIndex {
                     out.push(RagDoc {
                         path: s.path.clone(),
                         language: format!("{:?}", s.language),
-                        snippet: synth_snippet(s),
+                        snippet: self.symbol_snippet(s),
                         symbol: Some(s.name.clone()),
                     });
                 }
@@ -272,6 +332,22 @@ impl SymbolIndex {
             .and_then(|&i| self.symbols.get(i))
     }
 
+    /// Library `path` belongs to: its `part of` library, or `path` itself.
+    pub fn library_path<'a>(&'a self, path: &'a str) -> &'a str {
+        self.library_of.get(path).map_or(path, String::as_str)
+    }
+
+    /// Indices of all symbols of the library containing `path`: the library
+    /// file first, then its parts (in path order).
+    pub fn symbols_in_library(&self, path: &str) -> Vec<usize> {
+        let library = self.library_path(path);
+        let mut out = self.symbols_in_file(library).to_vec();
+        for (part, _) in self.library_of.iter().filter(|(_, l)| *l == library) {
+            out.extend_from_slice(self.symbols_in_file(part));
+        }
+        out
+    }
+
     /// Finds the smallest enclosing symbol by **line number**.
     ///
     /// If line spans are missing, this returns `None` and downstream can
@@ -313,52 +389,230 @@ impl SymbolIndex {
 /// - fetch raw text at `head_sha`,
 /// - parse & extract declarative symbols,
 /// - build in-memory maps for fast lookup.
+///
+/// Pass the step-1 `client` so raw fetches share its per-run cache. Files
+/// outside `scope` are not fetched or parsed. Files that can't be read as
+/// text are listed in [`SymbolIndex::skipped_files`]; with `lossy_decode`
+/// mostly non-UTF-8 text is decoded lossily instead of skipped. With
+/// `with_removals`, files with only removed lines are indexed too (see
+/// `ReviewOptions::review_removals`).
 pub async fn build_delta_symbol_index_for_changed_files(
-    cfg: &crate::git_providers::ProviderConfig,
+    client: &ProviderClient,
     id: &ChangeRequestId,
     bundle: &CrBundle,
+    scope: &PathScope,
+    lossy_decode: bool,
+    with_removals: bool,
 ) -> MrResult<SymbolIndex> {
     debug!(
         "step2: building delta index for head_sha={}",
         bundle.meta.diff_refs.head_sha
     );
 
-    let client = ProviderClient::from_config(cfg.clone())?;
     let head_sha = &bundle.meta.diff_refs.head_sha;
     let tmp_root = tmp_root_for(head_sha);
     fs::create_dir_all(&tmp_root)?;
 
-    let paths = collect_candidate_paths(bundle);
-    let parse_cfg = GraphConfig::default();
+    let paths = collect_candidate_paths(bundle, scope, with_removals);
 
-    let mut all: Vec<SymbolRecord> = Vec::new();
+    let mut files: Vec<(String, String)> = Vec::with_capacity(paths.len());
+    let mut skipped: Vec<SkippedFile> = Vec::new();
     for p in paths {
-        if let Some(text) = fetch_text_at_ref(&client, id, &p, head_sha).await? {
-            if let Some(lang) = detect_language(Path::new(&p)) {
-                if let Some(mut recs) =
-                    parse_one_file_and_extract(&tmp_root, &p, &text, lang, &parse_cfg)?
-                {
-                    all.append(&mut recs);
+        let raw = client.fetch_file_raw_at_ref(id, &p, head_sha).await?;
+        match decode_source(&p, raw, lossy_decode) {
+            Ok(text) => files.push((p, text)),
+            Err(s) => skipped.push(s),
+        }
+    }
+
+    let mut index = parse_files(&tmp_root, files, &GraphConfig::default())?;
+    skipped.append(&mut index.skipped_files);
+    index.skipped_files = skipped;
+    debug!(
+        "step2: delta index built, symbols={} parse_failures={} skipped_files={}",
+        index.symbols.len(),
+        index.parse_failures.len(),
+        index.skipped_files.len()
+    );
+    Ok(index)
+}
+
+/// Parse fetched `(path, text)` pairs and build the index.
+///
+/// Failures are per file: the file is recorded in
+/// [`SymbolIndex::parse_failures`] and the rest of the MR is still indexed.
+fn parse_files(
+    tmp_root: &Path,
+    files: Vec<(String, String)>,
+    cfg: &GraphConfig,
+) -> MrResult<SymbolIndex> {
+    let total = files.len();
+    let mut all: Vec<SymbolRecord> = Vec::new();
+    let mut failures: Vec<(String, LanguageKind)> = Vec::new();
+    let parsed: BTreeSet<String> = files.iter().map(|(p, _)| p.clone()).collect();
+    let mut skipped: Vec<SkippedFile> = Vec::new();
+    let mut part_links: Vec<(String, PartLink)> = Vec::new();
+    for (p, text) in files {
+        let Some(lang) = detect_language(Path::new(&p)) else {
+            warn!("step2: unknown language for {}", p);
+            continue;
+        };
+        match parse_one_file_and_extract(tmp_root, &p, &text, lang, cfg)? {
+            FileParse::Parsed {
+                mut symbols,
+                syntax_error_line,
+                parts,
+            } => {
+                part_links.extend(parts.into_iter().map(|l| (p.clone(), l)));
+                if let Some(line) = syntax_error_line {
+                    warn!(
+                        "step2: {} ({:?}) has a syntax error at line {}; kept {} symbol(s)",
+                        p,
+                        lang,
+                        line,
+                        symbols.len()
+                    );
+                    failures.push((p, lang));
                 }
-            } else {
-                warn!("step2: unknown language for {}", p);
+                all.append(&mut symbols);
             }
-        } else {
-            warn!("step2: missing file at ref or non-UTF8 {}", p);
+            FileParse::Failed => failures.push((p, lang)),
+            FileParse::Skipped(reason) => skipped.push(SkippedFile {
+                path: p,
+                reason: reason.into(),
+            }),
         }
     }
 
-    let index = build_index_maps(all);
-    debug!("step2: delta index built, symbols={}", index.symbols.len());
+    if !failures.is_empty() {
+        warn!(
+            "step2: {} of {} file(s) couldn't be parsed cleanly; comments on them may be imprecise: {:?}",
+            failures.len(),
+            total,
+            failures
+        );
+    }
+    let parse_failures = failures.into_iter().map(|(p, _)| p).collect();
+    let mut index = build_index_maps(all, parse_failures);
+    index.tmp_root = Some(tmp_root.to_path_buf());
+    index.skipped_files = skipped;
+    index.library_of = link_parts(&part_links, &parsed);
     Ok(index)
 }
 
+/// Dart `part` / `part of` directive, with the repo-relative path it points to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PartLink {
+    /// `part 'x.dart';` in a library file.
+    Part(String),
+    /// `part of 'lib.dart';` in a part file.
+    PartOf(String),
+}
+
+/// Part file -> library, for links whose both ends are in `parsed`.
+///
+/// Either side of a link is enough (a library's `part` or a part's
+/// `part of`); the `part` directive wins if they disagree.
+fn link_parts(links: &[(String, PartLink)], parsed: &BTreeSet<String>) -> BTreeMap<String, String> {
+    let mut library_of = BTreeMap::new();
+    for (from, link) in links {
+        if let PartLink::PartOf(library) = link {
+            library_of.insert(from.clone(), library.clone());
+        }
+    }
+    for (from, link) in links {
+        if let PartLink::Part(part) = link {
+            library_of.insert(part.clone(), from.clone());
+        }
+    }
+    library_of.retain(|part, library| {
+        part != library && parsed.contains(part) && parsed.contains(library)
+    });
+    library_of
+}
+
+/// Repo-relative target of a relative Dart URI in `from`
+/// (`package:` / `dart:` URIs and paths escaping the repo give `None`).
+fn resolve_dart_uri(from: &str, uri: &str) -> Option<String> {
+    if uri.contains(':') {
+        return None;
+    }
+    let mut segs: Vec<&str> = from.split('/').collect();
+    segs.pop();
+    for seg in uri.split('/') {
+        match seg {
+            "" | "." => {}
+            ".." => {
+                segs.pop()?;
+            }
+            s => segs.push(s),
+        }
+    }
+    Some(segs.join("/"))
+}
+
 // --- helpers ---------------------------------------------------------------
 
+/// Dependency lockfiles: generated text with nothing to review.
+const LOCKFILE_NAMES: &[&str] = &[
+    "pubspec.lock",
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "Podfile.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "poetry.lock",
+    "go.sum",
+];
+
+/// True if step 2 would index at least one changed file, i.e. the change
+/// request has more than binary files, lockfiles and deletions in `scope`.
+/// With `with_removals`, removed lines in a kept file count as well.
+pub fn has_reviewable_changes(bundle: &CrBundle, scope: &PathScope, with_removals: bool) -> bool {
+    !collect_candidate_paths(bundle, scope, with_removals).is_empty()
+}
+
+/// Added lines in the files step 2 indexes (the change size for
+/// `ReviewOptions::min_changed_lines`); binary files, lockfiles, deletions
+/// and paths outside `scope` don't count.
+pub fn added_line_count(bundle: &CrBundle, scope: &PathScope) -> usize {
+    let paths: BTreeSet<String> = collect_candidate_paths(bundle, scope, false)
+        .into_iter()
+        .collect();
+    bundle
+        .changes
+        .files
+        .iter()
+        .filter(|f| !f.is_binary && !f.is_deleted)
+        .filter(|f| {
+            f.new_path
+                .as_ref()
+                .or(f.old_path.as_ref())
+                .is_some_and(|p| paths.contains(p))
+        })
+        .flat_map(|f| &f.hunks)
+        .flat_map(|h| &h.lines)
+        .filter(|ln| matches!(ln, DiffLine::Added { .. }))
+        .count()
+}
+
+/// True for dependency lockfiles ([`LOCKFILE_NAMES`]), matched by file name.
+pub(crate) fn is_lockfile(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    LOCKFILE_NAMES.contains(&name)
+}
+
 /// Collect repository-relative paths of changed **text** files.
-/// Skips: binary files, deleted files. Requires at least one added line
-/// to reduce unnecessary parsing for pure removals (can be relaxed).
-fn collect_candidate_paths(bundle: &CrBundle) -> Vec<String> {
+/// Skips: binary files, deleted files, lockfiles, paths outside `scope`. Requires at least one
+/// added line to reduce unnecessary parsing for pure removals, or, with `with_removals`,
+/// at least one removed line.
+fn collect_candidate_paths(
+    bundle: &CrBundle,
+    scope: &PathScope,
+    with_removals: bool,
+) -> Vec<String> {
     let mut out = Vec::new();
     let mut seen = BTreeSet::<String>::new();
 
@@ -366,16 +620,16 @@ fn collect_candidate_paths(bundle: &CrBundle) -> Vec<String> {
         if f.is_binary || f.is_deleted {
             continue;
         }
-        let has_added = f
-            .hunks
-            .iter()
-            .flat_map(|h| &h.lines)
-            .any(|ln| matches!(ln, DiffLine::Added { .. }));
+        let has_added = f.hunks.iter().flat_map(|h| &h.lines).any(|ln| match ln {
+            DiffLine::Added { .. } => true,
+            DiffLine::Removed { .. } => with_removals,
+            DiffLine::Context { .. } => false,
+        });
         if !has_added {
             continue;
         }
         if let Some(path) = f.new_path.as_ref().or(f.old_path.as_ref()) {
-            if seen.insert(path.clone()) {
+            if !is_lockfile(path) && scope.allows(path) && seen.insert(path.clone()) {
                 out.push(path.clone());
             }
         }
@@ -383,27 +637,102 @@ fn collect_candidate_paths(bundle: &CrBundle) -> Vec<String> {
     out
 }
 
-/// Fetch UTF-8 text of `repo_relative_path` at a specific git `ref`.
-/// Returns `Ok(Some(text))` on success, `Ok(None)` for 404/non-UTF8 files.
-async fn fetch_text_at_ref(
-    client: &ProviderClient,
-    id: &ChangeRequestId,
+/// Bytes inspected by the binary-content heuristic.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Max share of invalid UTF-8 bytes still decoded (lossily) as text.
+const MAX_NON_UTF8_RATIO: f64 = 0.01;
+
+/// Max share of control characters (other than tab/CR/LF/FF) in the sniffed prefix.
+const MAX_CONTROL_RATIO: f64 = 0.05;
+
+/// Why `bytes` should not be parsed as source text, if anything.
+///
+/// Providers sometimes label binary or binary-ish files as text; parsing them
+/// wastes time and yields garbage symbols. Checks a NUL byte or excess control
+/// characters in the first `BINARY_SNIFF_BYTES`, and the invalid UTF-8 ratio
+/// over the whole blob.
+fn binary_content_reason(bytes: &[u8]) -> Option<&'static str> {
+    let head = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if head.contains(&0) {
+        return Some("nul byte");
+    }
+
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c))
+        .count();
+    if !head.is_empty() && control as f64 / head.len() as f64 > MAX_CONTROL_RATIO {
+        return Some("control characters");
+    }
+
+    let invalid: usize = bytes.utf8_chunks().map(|c| c.invalid().len()).sum();
+    if !bytes.is_empty() && invalid as f64 / bytes.len() as f64 > MAX_NON_UTF8_RATIO {
+        return Some("non-utf8");
+    }
+    None
+}
+
+/// Decode the raw content of `repo_relative_path` fetched at the head ref.
+///
+/// `raw` is `None` for files missing at the ref (404). Content that looks
+/// binary (see `binary_content_reason`) is skipped with its reason; a few
+/// stray invalid bytes are replaced lossily. With `lossy` set, mostly
+/// non-UTF-8 text (e.g. Latin-1) is decoded lossily too instead of skipped.
+fn decode_source(
     repo_relative_path: &str,
-    git_ref: &str,
-) -> MrResult<Option<String>> {
-    let raw = match client
-        .fetch_file_raw_at_ref(id, repo_relative_path, git_ref)
-        .await?
-    {
-        Some(b) => b,
-        None => return Ok(None),
+    raw: Option<Vec<u8>>,
+    lossy: bool,
+) -> Result<String, SkippedFile> {
+    let skip = |reason: &str| SkippedFile {
+        path: repo_relative_path.to_string(),
+        reason: reason.to_string(),
+    };
+    let Some(raw) = raw else {
+        warn!("step2: skip {}: missing at ref", repo_relative_path);
+        return Err(skip("missing at ref"));
     };
+    match binary_content_reason(&raw) {
+        Some("non-utf8") if lossy => {
+            warn!(
+                "step2: {} is not UTF-8; decoding lossily",
+                repo_relative_path
+            );
+        }
+        Some(reason) => {
+            warn!(
+                "step2: skip {} ({} bytes): looks binary ({})",
+                repo_relative_path,
+                raw.len(),
+                reason
+            );
+            return Err(skip(reason));
+        }
+        None => {}
+    }
     match String::from_utf8(raw) {
-        Ok(s) => Ok(Some(s)),
-        Err(_) => Ok(None),
+        Ok(s) => Ok(s),
+        Err(e) => Ok(String::from_utf8_lossy(e.as_bytes()).into_owned()),
     }
 }
 
+/// Outcome of parsing one changed file.
+#[derive(Debug)]
+enum FileParse {
+    /// Symbols extracted; `syntax_error_line` is set when the parser had to
+    /// recover from broken syntax.
+    Parsed {
+        symbols: Vec<SymbolRecord>,
+        syntax_error_line: Option<usize>,
+        /// Dart `part` / `part of` directives that resolve to a repo path.
+        parts: Vec<PartLink>,
+    },
+    /// Parser or extractor error; no symbols.
+    Failed,
+    /// Not parsed on purpose (binary-looking content, with the reason).
+    Skipped(&'static str),
+}
+
 /// Parse a single file and extract declarative symbols as `SymbolRecord`s.
 /// The extractor reads from FS, so we mirror the repository layout under `tmp_root`.
 fn parse_one_file_and_extract(
@@ -412,7 +741,15 @@ fn parse_one_file_and_extract(
     code: &str,
     lang: LanguageKind,
     cfg: &GraphConfig,
-) -> MrResult<Option<Vec<SymbolRecord>>> {
+) -> MrResult<FileParse> {
+    if let Some(reason) = binary_content_reason(code.as_bytes()) {
+        warn!(
+            "step2: skip parsing {}: looks binary ({})",
+            repo_rel, reason
+        );
+        return Ok(FileParse::Skipped(reason));
+    }
+
     let abs = write_temp_file(tmp_root, repo_rel, code)?;
 
     let scanned = ScannedFile {
@@ -423,17 +760,27 @@ fn parse_one_file_and_extract(
     };
 
     let mut nodes: Vec<AstNode> = Vec::new();
-    if let Err(e) = parse::parse_and_extract(&scanned, lang, &mut nodes, cfg) {
-        warn!("step2: parse failed for {}: {}", repo_rel, e);
-        return Ok(None);
-    }
+    let report = match parse::parse_and_extract(&scanned, lang, &mut nodes, cfg) {
+        Ok(r) => r,
+        Err(e) => {
+            warn!("step2: parse failed for {} ({:?}): {}", repo_rel, lang, e);
+            return Ok(FileParse::Failed);
+        }
+    };
 
     // Optional in-memory prints (no files are written).
     maybe_print_ast_nodes(repo_rel, &nodes);
     maybe_print_symbol_summary(repo_rel, &nodes);
 
     let mut out: Vec<SymbolRecord> = Vec::new();
+    let mut parts: Vec<PartLink> = Vec::new();
     for n in nodes {
+        let part = match n.kind {
+            AstKind::Part => resolve_dart_uri(repo_rel, &n.name).map(PartLink::Part),
+            AstKind::PartOf => resolve_dart_uri(repo_rel, &n.name).map(PartLink::PartOf),
+            _ => None,
+        };
+        parts.extend(part);
         if !is_symbolic_kind(&n.kind) {
             continue;
         }
@@ -448,11 +795,18 @@ fn parse_one_file_and_extract(
             body_span,
         });
     }
-    Ok(Some(out))
+    Ok(FileParse::Parsed {
+        symbols: out,
+        syntax_error_line: report.first_error_line,
+        parts,
+    })
 }
 
 /// Build fast lookup maps for the `SymbolIndex`.
-fn build_index_maps(records: Vec<SymbolRecord>) -> SymbolIndex {
+pub(crate) fn build_index_maps(
+    records: Vec<SymbolRecord>,
+    parse_failures: Vec<String>,
+) -> SymbolIndex {
     let mut by_path: BTreeMap<String, Vec<usize>> = BTreeMap::new();
     let mut by_name: BTreeMap<String, Vec<usize>> = BTreeMap::new();
     let mut by_id: HashMap<String, usize> = HashMap::new();
@@ -470,6 +824,10 @@ fn build_index_maps(records: Vec<SymbolRecord>) -> SymbolIndex {
         by_path,
         by_name,
         by_id,
+        parse_failures,
+        tmp_root: None,
+        skipped_files: Vec::new(),
+        library_of: BTreeMap::new(),
     }
 }
 
@@ -548,7 +906,7 @@ fn tmp_root_for(head_sha: &str) -> PathBuf {
     } else {
         head_sha
     };
-    PathBuf::from("code_data").join("mr_tmp").join(short)
+    services::data_root::mr_tmp_dir().join(short)
 }
 
 /// Write `code` to a temp file that mirrors the repository layout.
@@ -670,3 +1028,204 @@ fn maybe_print_symbol_summary(repo_rel: &str, nodes: &[codegraph_prep::model::as
     }
     debug!("step2: total symbols={} for {}", count, repo_rel);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pseudo_binary_blob_is_skipped() {
+        let mut blob = b"PK\x03\x04 minified?".to_vec();
+        blob.extend((0u8..=255).cycle().take(4096));
+        assert!(binary_content_reason(&blob).is_some());
+
+        // Text with control noise but no NUL.
+        let noisy: Vec<u8> = (0..2000)
+            .map(|i| if i % 4 == 0 { 0x01 } else { b'a' })
+            .collect();
+        assert_eq!(binary_content_reason(&noisy), Some("control characters"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let tmp = dir.path();
+        let code = String::from_utf8_lossy(&blob).into_owned();
+        let out = parse_one_file_and_extract(
+            tmp,
+            "lib/blob.dart",
+            &code,
+            LanguageKind::Dart,
+            &GraphConfig::default(),
+        )
+        .unwrap();
+        assert!(matches!(out, FileParse::Skipped(_)));
+        assert!(!tmp.join("lib/blob.dart").exists());
+    }
+
+    #[test]
+    fn source_text_passes() {
+        let src = "class A {\n\tvoid f() {}\r\n}\n// caf\u{e9}\n";
+        assert_eq!(binary_content_reason(src.as_bytes()), None);
+        assert_eq!(binary_content_reason(b""), None);
+    }
+
+    #[test]
+    fn latin1_file_is_recorded_as_skipped() {
+        // "Café: déjà vu à la crème" in Latin-1: accented letters are lone bytes.
+        let latin1: Vec<u8> = "// Caf\u{e9}: d\u{e9}j\u{e0} vu \u{e0} la cr\u{e8}me\nclass A {}\n"
+            .chars()
+            .map(|c| c as u8)
+            .collect();
+        assert!(std::str::from_utf8(&latin1).is_err());
+
+        let skipped = decode_source("lib/legacy.dart", Some(latin1.clone()), false).unwrap_err();
+        assert_eq!(
+            skipped,
+            SkippedFile {
+                path: "lib/legacy.dart".into(),
+                reason: "non-utf8".into(),
+            }
+        );
+        let missing = decode_source("lib/gone.dart", None, false).unwrap_err();
+        assert_eq!(missing.reason, "missing at ref");
+
+        let text = decode_source("lib/legacy.dart", Some(latin1), true).unwrap();
+        assert!(text.starts_with("// Caf\u{fffd}:"));
+        assert!(text.ends_with("class A {}\n"));
+    }
+
+    #[test]
+    fn snippet_uses_materialized_signature() {
+        let dir = tempfile::tempdir().unwrap();
+        let tmp = dir.path();
+        let code = "class Repo {\n  Future<User> loadUser(\n    String id, {\n    bool cached = true,\n  }) async {\n    return api.get(id);\n  }\n}\n";
+        write_temp_file(tmp, "lib/repo.dart", code).unwrap();
+        let span = |start_line, end_line| Span {
+            bytes: ByteSpan {
+                start_byte: 0,
+                end_byte: 0,
+            },
+            lines: Some(LineSpan {
+                start_line,
+                end_line,
+            }),
+        };
+        let method = SymbolRecord {
+            symbol_id: "m".into(),
+            path: "lib/repo.dart".into(),
+            language: LanguageKind::Dart,
+            kind: SymbolKind::Method,
+            name: "loadUser".into(),
+            decl_span: span(2, 2),
+            body_span: span(2, 7),
+        };
+        let mut index = build_index_maps(vec![method.clone()], Vec::new());
+
+        assert!(
+            index
+                .symbol_snippet(&method)
+                .ends_with("// synthetic header only")
+        );
+
+        index.tmp_root = Some(tmp.to_path_buf());
+        let snippet = index.symbol_snippet(&method);
+        assert_eq!(
+            snippet,
+            "method loadUser  (lines: 2..7)\nfile: lib/repo.dart\n  Future<User> loadUser(\n    String id, {\n    bool cached = true,\n  }) async {"
+        );
+    }
+
+    #[test]
+    fn malformed_file_is_recorded_as_parse_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let tmp = dir.path();
+        let files = vec![
+            (
+                "src/ok.rs".to_string(),
+                "fn good() -> u32 {\n    1\n}\n".to_string(),
+            ),
+            (
+                "src/broken.rs".to_string(),
+                "fn fine() {}\n\nfn broken( {\n    let x = ;\n".to_string(),
+            ),
+        ];
+        let index = parse_files(tmp, files, &GraphConfig::default()).unwrap();
+
+        assert_eq!(index.parse_failures, vec!["src/broken.rs".to_string()]);
+        assert!(!index.symbols_in_file("src/ok.rs").is_empty());
+    }
+
+    #[test]
+    fn dart_parts_are_merged_under_their_library() {
+        let dir = tempfile::tempdir().unwrap();
+        let tmp = dir.path();
+        let files = vec![
+            (
+                "lib/shop.dart".to_string(),
+                "part 'src/cart.dart';\n\nclass Shop {\n  void open() {}\n}\n".to_string(),
+            ),
+            (
+                "lib/src/cart.dart".to_string(),
+                "part of '../shop.dart';\n\nclass Cart {\n  void add() {}\n}\n".to_string(),
+            ),
+            (
+                "lib/src/price.dart".to_string(),
+                "part of '../shop.dart';\n\nint price(int cents) {\n  return cents;\n}\n"
+                    .to_string(),
+            ),
+        ];
+        let index = parse_files(tmp, files, &GraphConfig::default()).unwrap();
+
+        assert_eq!(index.library_path("lib/src/cart.dart"), "lib/shop.dart");
+        assert_eq!(index.library_path("lib/src/price.dart"), "lib/shop.dart");
+        assert_eq!(index.library_path("lib/shop.dart"), "lib/shop.dart");
+
+        let names = |path: &str| {
+            index
+                .symbols_in_library(path)
+                .into_iter()
+                .map(|i| index.symbols[i].name.clone())
+                .collect::<BTreeSet<_>>()
+        };
+        let library = names("lib/shop.dart");
+        for name in ["Shop", "open", "Cart", "add", "price"] {
+            assert!(library.contains(name), "{name} missing from {library:?}");
+        }
+        assert_eq!(names("lib/src/cart.dart"), library);
+        // Enclosing lookups stay per file.
+        let add = index
+            .find_enclosing_by_line("lib/src/cart.dart", 4)
+            .unwrap();
+        assert_eq!(add.name, "add");
+    }
+
+    #[test]
+    fn part_uris_resolve_relative_to_the_declaring_file() {
+        assert_eq!(
+            resolve_dart_uri("lib/src/cart.dart", "../shop.dart").as_deref(),
+            Some("lib/shop.dart")
+        );
+        assert_eq!(
+            resolve_dart_uri("lib/shop.dart", "./src/cart.dart").as_deref(),
+            Some("lib/src/cart.dart")
+        );
+        assert_eq!(resolve_dart_uri("lib/a.dart", "package:app/a.dart"), None);
+        assert_eq!(resolve_dart_uri("a.dart", "../../b.dart"), None);
+
+        let parsed: BTreeSet<String> = ["lib/a.dart", "lib/a.g.dart", "lib/b.dart"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let links = vec![
+            (
+                "lib/a.dart".to_string(),
+                PartLink::Part("lib/a.g.dart".into()),
+            ),
+            (
+                "lib/b.dart".to_string(),
+                PartLink::PartOf("lib/gone.dart".into()),
+            ),
+        ];
+        let library_of = link_parts(&links, &parsed);
+        assert_eq!(library_of.len(), 1);
+        assert_eq!(library_of["lib/a.g.dart"], "lib/a.dart");
+    }
+}
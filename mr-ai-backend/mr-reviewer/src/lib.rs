@@ -12,6 +12,12 @@
 //!
 //! This crate exposes a single high-level entry `run_review` that executes
 //! steps 1–4 and returns both the plan and draft comments.
+//!
+//! Two ways to avoid posting:
+//! - [`ReviewOptions::preview_only`] skips step 5 entirely — no provider calls,
+//!   no duplicate lookups. Use it to show drafts in a UI before a human approves.
+//! - [`publish::PublishConfig::dry_run`] runs step 5 (ranking, dedup, logging)
+//!   but simulates the POST/PUT requests.
 
 pub mod cache;
 pub mod errors;
@@ -22,19 +28,29 @@ pub mod parser; // step 1 helpers
 pub mod review; // step 4
 
 pub mod publish; // step 5
+pub mod repo_config;
+#[cfg(any(test, feature = "replay"))]
+pub mod snapshot; // offline steps 1–4 for regression tests
 
 mod telemetry;
+#[cfg(test)]
+mod test_support;
 
 use ai_llm_service::service_profiles::LlmServiceProfiles;
-use std::{sync::Arc, time::Instant};
-use tracing::debug;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
 
 use errors::MrResult;
 use git_providers::{ChangeRequestId, CrBundle, ProviderClient};
 use lang::SymbolIndex;
-use map::MappedTarget;
+use map::{MappedTarget, TouchedSymbol};
+use repo_config::RepoReviewConfig;
 
-use crate::git_providers::ProviderConfig;
+use crate::errors::ConfigError;
+use crate::git_providers::{ProviderConfig, ProviderKind, TlsConfig};
 
 /// Final output of steps 1–3 (plan for step 4).
 #[derive(Debug, Clone)]
@@ -42,31 +58,465 @@ pub struct ReviewPlan {
     pub bundle: CrBundle,
     pub symbols: SymbolIndex,
     pub targets: Vec<MappedTarget>,
+    /// Effective review config (`.mrai.toml` > env > defaults).
+    pub repo_config: RepoReviewConfig,
+}
+
+impl ReviewPlan {
+    /// Functions/classes touched by the change request (deduplicated, path-ordered).
+    pub fn touched_symbols(&self) -> Vec<TouchedSymbol> {
+        map::touched_symbols(&self.targets)
+    }
+}
+
+/// Default test-file globs for `ReviewOptions::skip_test_paths` (comma-separated).
+pub const DEFAULT_TEST_GLOBS: &str = "**/test/**,**/tests/**,**/__tests__/**,*_test.dart,\
+                                      *_test.go,*_test.py,**/test_*.py,*.spec.ts,*.test.ts,\
+                                      *.spec.js,*.test.js";
+
+/// Per-run switches for `run_review`.
+#[derive(Clone)]
+pub struct ReviewOptions {
+    /// Review draft/WIP change requests too (skipped by default).
+    pub review_drafts: bool,
+    /// Keep drafts anchored only on context (unchanged) lines (dropped by default).
+    pub allow_context_anchors: bool,
+    /// Organization rules applied to every parsed finding (default: no-op).
+    pub finding_policy: Arc<dyn review::policy::FindingPolicy>,
+    /// Run steps 1–4 (including real LLM calls) and return the drafts without
+    /// running step 5 at all. Unlike `PublishConfig::dry_run`, which still
+    /// talks to the provider to look up existing comments and only simulates
+    /// posting, nothing is sent to or read back from the provider after step 4.
+    /// Set by the caller; never read from the environment (default: false).
+    pub preview_only: bool,
+    /// Only review changes in paths matching one of these globs (empty = all),
+    /// e.g. `lib/**`. Out-of-scope files are not parsed and get no targets.
+    pub include_globs: Vec<String>,
+    /// Skip changes in paths matching any of these globs (e.g. `test/**`);
+    /// wins over `include_globs`. Per-run, unlike `.mrai.toml` `ignore`.
+    pub exclude_globs: Vec<String>,
+    /// Build no review targets in test files (matched by `test_globs`). They
+    /// are still parsed for context and indexed for RAG (default: false).
+    pub skip_test_paths: bool,
+    /// Globs recognizing test files for `skip_test_paths`
+    /// (default: [`DEFAULT_TEST_GLOBS`]).
+    pub test_globs: Vec<String>,
+    /// Decode changed files that aren't valid UTF-8 (e.g. Latin-1) lossily
+    /// instead of skipping them in step 2 (default: false). Skipped files are
+    /// listed in `Step4Summary::skipped_files` either way.
+    pub lossy_decode: bool,
+    /// Change requests carrying any of these labels are not reviewed
+    /// (case-insensitive; default: `skip-ai-review`).
+    pub skip_labels: Vec<String>,
+    /// Any of these labels routes every target to the SLOW model with a
+    /// security-focused prompt and applies the security severity floor, as if
+    /// `focus` listed security (case-insensitive; default: `needs-security-review`).
+    pub security_labels: Vec<String>,
+    /// Add the MR title/description to step-4 prompts as read-only author
+    /// intent, so findings are judged against the stated goal (default: true).
+    pub include_mr_description: bool,
+    /// Post a short status note when a change request is skipped for having no
+    /// reviewable changes (default: false). Never with `preview_only`.
+    pub note_when_nothing_to_review: bool,
+    /// Anchor preference for findings on symbol targets (default: the
+    /// declaration line when it changed, e.g. a signature change).
+    pub symbol_anchor: review::context::AnchorPreference,
+    /// Anchor preference for findings on line/range targets (default: the
+    /// first added line).
+    pub line_anchor: review::context::AnchorPreference,
+    /// Areas to emphasize in step-4 prompts; findings on a focus topic are
+    /// raised to its severity floor (default: empty = balanced).
+    pub focus: Vec<review::focus::ReviewFocus>,
+    /// Skip LLM review (step 4) when fewer lines than this were added across
+    /// all files; steps 1–3 still run (default: 0 = review everything).
+    pub min_changed_lines: usize,
+    /// Per-call limit for step-4 generations; a target whose call exceeds it
+    /// is reported as `llm_timeout` and the review moves on (default: 300 s;
+    /// `None` = no limit).
+    pub llm_call_timeout: Option<Duration>,
+    /// Also build file-level targets for pure removals of public declarations
+    /// (e.g. a removed function), so findings like "you removed X; is that
+    /// intended?" can be posted (default: false). See
+    /// [`map::map_removals_to_targets`].
+    pub review_removals: bool,
+}
+
+/// Per-MR decision driven by [`git_providers::ChangeRequest::labels`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelGate {
+    /// No configured label is present.
+    Default,
+    /// Skip the review; holds the matching label.
+    Skip(String),
+    /// Force SLOW routing and a security focus, severity floor included;
+    /// holds the matching label.
+    Security(String),
+}
+
+impl ReviewOptions {
+    /// Gate for `labels`; a skip label wins over a security label.
+    pub fn label_gate(&self, labels: &[String]) -> LabelGate {
+        let find = |wanted: &[String]| {
+            labels
+                .iter()
+                .find(|l| wanted.iter().any(|w| w.eq_ignore_ascii_case(l.trim())))
+                .cloned()
+        };
+        if let Some(l) = find(&self.skip_labels) {
+            LabelGate::Skip(l)
+        } else if let Some(l) = find(&self.security_labels) {
+            LabelGate::Security(l)
+        } else {
+            LabelGate::Default
+        }
+    }
+}
+
+impl std::fmt::Debug for ReviewOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReviewOptions")
+            .field("review_drafts", &self.review_drafts)
+            .field("allow_context_anchors", &self.allow_context_anchors)
+            .field("preview_only", &self.preview_only)
+            .field("include_globs", &self.include_globs)
+            .field("exclude_globs", &self.exclude_globs)
+            .field("skip_test_paths", &self.skip_test_paths)
+            .field("test_globs", &self.test_globs)
+            .field("lossy_decode", &self.lossy_decode)
+            .field("skip_labels", &self.skip_labels)
+            .field("security_labels", &self.security_labels)
+            .field("include_mr_description", &self.include_mr_description)
+            .field(
+                "note_when_nothing_to_review",
+                &self.note_when_nothing_to_review,
+            )
+            .field("symbol_anchor", &self.symbol_anchor)
+            .field("line_anchor", &self.line_anchor)
+            .field("focus", &self.focus)
+            .field("min_changed_lines", &self.min_changed_lines)
+            .field("llm_call_timeout", &self.llm_call_timeout)
+            .field("review_removals", &self.review_removals)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ReviewOptions {
+    /// Environment variables:
+    /// - `MR_REVIEWER_REVIEW_DRAFTS` (default: false)
+    /// - `MR_REVIEWER_ALLOW_CONTEXT_ANCHORS` (default: false)
+    /// - `MR_REVIEWER_INCLUDE_GLOBS`, `MR_REVIEWER_EXCLUDE_GLOBS` (comma-separated; default: empty)
+    /// - `MR_REVIEWER_SKIP_TEST_PATHS` (default: false)
+    /// - `MR_REVIEWER_TEST_GLOBS` (comma-separated; default: [`DEFAULT_TEST_GLOBS`])
+    /// - `MR_REVIEWER_LOSSY_DECODE` (default: false)
+    /// - `MR_REVIEWER_SKIP_LABELS` (comma-separated; default: `skip-ai-review`; blank disables)
+    /// - `MR_REVIEWER_SECURITY_LABELS` (comma-separated; default: `needs-security-review`; blank disables)
+    /// - `MR_REVIEWER_INCLUDE_MR_DESCRIPTION` (default: true)
+    /// - `MR_REVIEWER_NOTE_WHEN_NOTHING_TO_REVIEW` (default: false)
+    /// - `MR_REVIEWER_SYMBOL_ANCHOR`, `MR_REVIEWER_LINE_ANCHOR` (`added` | `declaration`;
+    ///   defaults: `declaration`, `added`)
+    /// - `MR_REVIEWER_FOCUS` (comma-separated `security`, `performance`, `correctness`,
+    ///   `style`; default: empty = balanced)
+    /// - `MR_REVIEWER_MIN_CHANGED_LINES` (default: 0)
+    /// - `MR_REVIEWER_LLM_CALL_TIMEOUT_SECS` (default: 300; 0 disables)
+    /// - `MR_REVIEWER_REVIEW_REMOVALS` (default: false)
+    fn default() -> Self {
+        Self {
+            review_drafts: env_flag("MR_REVIEWER_REVIEW_DRAFTS"),
+            allow_context_anchors: env_flag("MR_REVIEWER_ALLOW_CONTEXT_ANCHORS"),
+            finding_policy: Arc::new(review::policy::NoopPolicy),
+            preview_only: false,
+            include_globs: env_list("MR_REVIEWER_INCLUDE_GLOBS"),
+            exclude_globs: env_list("MR_REVIEWER_EXCLUDE_GLOBS"),
+            skip_test_paths: env_flag("MR_REVIEWER_SKIP_TEST_PATHS"),
+            test_globs: env_list_or("MR_REVIEWER_TEST_GLOBS", DEFAULT_TEST_GLOBS),
+            lossy_decode: env_flag("MR_REVIEWER_LOSSY_DECODE"),
+            skip_labels: env_list_or("MR_REVIEWER_SKIP_LABELS", "skip-ai-review"),
+            security_labels: env_list_or("MR_REVIEWER_SECURITY_LABELS", "needs-security-review"),
+            include_mr_description: env_flag_or("MR_REVIEWER_INCLUDE_MR_DESCRIPTION", true),
+            note_when_nothing_to_review: env_flag("MR_REVIEWER_NOTE_WHEN_NOTHING_TO_REVIEW"),
+            symbol_anchor: env_anchor_or(
+                "MR_REVIEWER_SYMBOL_ANCHOR",
+                review::context::AnchorPreference::Declaration,
+            ),
+            line_anchor: env_anchor_or(
+                "MR_REVIEWER_LINE_ANCHOR",
+                review::context::AnchorPreference::AddedLine,
+            ),
+            focus: env_focus("MR_REVIEWER_FOCUS"),
+            min_changed_lines: env_usize_or("MR_REVIEWER_MIN_CHANGED_LINES", 0),
+            llm_call_timeout: match env_usize_or("MR_REVIEWER_LLM_CALL_TIMEOUT_SECS", 300) {
+                0 => None,
+                secs => Some(Duration::from_secs(secs as u64)),
+            },
+            review_removals: env_flag("MR_REVIEWER_REVIEW_REMOVALS"),
+        }
+    }
+}
+
+/// Truthy env flag (`1`/`true`/`yes`/`on`); unset means false.
+fn env_flag(key: &str) -> bool {
+    env_flag_or(key, false)
+}
+
+/// Like [`env_flag`], but unset means `default`.
+fn env_flag_or(key: &str, default: bool) -> bool {
+    std::env::var(key)
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(default)
+}
+
+/// Comma-separated env list; unset or blank means empty.
+fn env_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .map(|v| split_list(&v))
+        .unwrap_or_default()
+}
+
+/// Like [`env_list`], but unset means `default` (blank still means empty).
+fn env_list_or(key: &str, default: &str) -> Vec<String> {
+    split_list(&std::env::var(key).unwrap_or_else(|_| default.to_string()))
+}
+
+/// Non-negative integer from env; unset or unparsable means `default`.
+fn env_usize_or(key: &str, default: usize) -> usize {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+/// Anchor preference from env; unset or unknown values mean `default`.
+fn env_anchor_or(
+    key: &str,
+    default: review::context::AnchorPreference,
+) -> review::context::AnchorPreference {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| review::context::AnchorPreference::parse(&v))
+        .unwrap_or(default)
+}
+
+/// Review focus list from env; unknown names are logged and ignored.
+fn env_focus(key: &str) -> Vec<review::focus::ReviewFocus> {
+    let mut out = Vec::new();
+    for name in env_list(key) {
+        match review::focus::ReviewFocus::parse(&name) {
+            Some(f) if !out.contains(&f) => out.push(f),
+            Some(_) => {}
+            None => warn!("config: unknown review focus '{}' in {}", name, key),
+        }
+    }
+    out
+}
+
+fn split_list(v: &str) -> Vec<String> {
+    v.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect()
 }
 
-/// Run steps 1–4 and return both the plan and draft comments.
+/// Outcome of `run_review`.
+#[derive(Debug)]
+pub enum RunReview {
+    /// Steps 1–5 executed (step 5 skipped with `preview_only`); plan, drafts
+    /// and the step-4 summary are returned.
+    Completed {
+        plan: Box<ReviewPlan>,
+        drafts: Vec<review::DraftComment>,
+        /// Step-4 summary, including the `step4_report.json` location.
+        report: Box<review::Step4Summary>,
+        /// Overview of symbols the change request touched, independent of findings.
+        touched_symbols: Vec<TouchedSymbol>,
+    },
+    /// Review was intentionally not performed (e.g. draft MR).
+    Skipped { reason: SkipReason },
+}
+
+/// Why a change request was not reviewed (see [`RunReview::Skipped`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Draft/WIP change request and `ReviewOptions::review_drafts` is off.
+    Draft,
+    /// A skip label is present; holds the label.
+    Label(String),
+    /// Only binary files, lockfiles, deletions or out-of-scope paths changed.
+    NoReviewableChanges,
+    /// Fewer lines were added than `ReviewOptions::min_changed_lines`.
+    BelowMinChangedLines,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Draft => f.write_str("draft change request"),
+            Self::Label(label) => write!(f, "label '{label}'"),
+            Self::NoReviewableChanges => f.write_str("no reviewable changes"),
+            Self::BelowMinChangedLines => f.write_str("below min_changed_lines"),
+        }
+    }
+}
+
+/// Marker keeping the "nothing to review" note to one per change request.
+const NOTHING_TO_REVIEW_MARKER: &str = "nothing-to-review";
+
+/// Run steps 1–5 and return both the plan and draft comments.
+///
+/// Returns `RunReview::Skipped` for draft/WIP change requests unless
+/// `opts.review_drafts` is set, and for change requests without reviewable
+/// changes ([`SkipReason::NoReviewableChanges`]; a status note is posted with
+/// `opts.note_when_nothing_to_review`) or with fewer added lines than
+/// `opts.min_changed_lines` ([`SkipReason::BelowMinChangedLines`]). With
+/// `opts.preview_only` step 5 is not run and `pub_cfg` is ignored.
 ///
 /// You supply `llm_cfg` from your API. For CLI/experiments you can use
-/// `run_review_from_env`.
+/// [`run_review_from_env`].
 pub async fn run_review(
     cfg: ProviderConfig,
     id: ChangeRequestId,
     svc: Arc<LlmServiceProfiles>,
     pub_cfg: publish::PublishConfig,
-) -> MrResult<(ReviewPlan, Vec<review::DraftComment>)> {
-    // --- Step 1: bundle fetch with cache ------------------------------------
-    let t0 = Instant::now();
+    opts: ReviewOptions,
+) -> MrResult<RunReview> {
     debug!("step1: init provider client");
     let client = ProviderClient::from_config(cfg.clone())?;
     debug!("step1: client ready");
 
+    let (plan, drafts, report, touched_symbols) =
+        match draft_review(&client, cfg.kind, &id, svc, &opts).await? {
+            RunReview::Completed {
+                plan,
+                drafts,
+                report,
+                touched_symbols,
+            } => (plan, drafts, report, touched_symbols),
+            RunReview::Skipped { reason } => {
+                if reason == SkipReason::NoReviewableChanges
+                    && opts.note_when_nothing_to_review
+                    && !opts.preview_only
+                {
+                    post_nothing_to_review_note(&client, &id, pub_cfg.dry_run).await;
+                }
+                return Ok(RunReview::Skipped { reason });
+            }
+        };
+
+    if opts.preview_only {
+        info!(
+            "step5: skipped (preview_only), returning {} draft(s)",
+            drafts.len()
+        );
+    } else {
+        let t5 = Instant::now();
+        let results = publish::publish(&cfg, &id, &plan, &drafts, pub_cfg).await?;
+        let created = results
+            .iter()
+            .filter(|r| r.performed && r.created_new)
+            .count();
+        let skipped = results
+            .iter()
+            .filter(|r| r.skipped_reason.is_some())
+            .count();
+        debug!(
+            "step5: published created={} skipped={} in {} ms",
+            created,
+            skipped,
+            t5.elapsed().as_millis()
+        );
+    }
+
+    metrics::counter!("reviews_total").increment(1);
+
+    Ok(RunReview::Completed {
+        plan,
+        drafts,
+        report,
+        touched_symbols,
+    })
+}
+
+/// Best-effort status note for a skipped change request; failures are logged.
+async fn post_nothing_to_review_note(client: &ProviderClient, id: &ChangeRequestId, dry_run: bool) {
+    const BODY: &str = "AI review skipped: this change only touches binary files, \
+                        lockfiles, deletions or paths outside the review scope.";
+    if dry_run {
+        info!(
+            "step5: dry-run, would post note on {}!{}: {}",
+            id.project, id.iid, BODY
+        );
+        return;
+    }
+    let posted: MrResult<bool> = async {
+        let meta = client.fetch_meta(id).await?;
+        client
+            .post_general_comment(&meta, BODY, Some(NOTHING_TO_REVIEW_MARKER))
+            .await
+    }
+    .await;
+    match posted {
+        Ok(true) => info!(
+            "step5: posted 'nothing to review' note on {}!{}",
+            id.project, id.iid
+        ),
+        Ok(false) => debug!("step5: 'nothing to review' note already present"),
+        Err(e) => warn!("step5: couldn't post 'nothing to review' note: {}", e),
+    }
+}
+
+/// Steps 1–4 with a ready provider client: fetch, index, map, draft.
+///
+/// Never publishes. `kind` keys the large-diff cache. With the `replay`
+/// feature, use `ProviderClient::from_recorded` (or `from_recorded_file` for a
+/// recording captured with `ProviderClient::record`) and a replay LLM backend
+/// to run offline (see the `snapshot` module).
+pub async fn draft_review(
+    client: &ProviderClient,
+    kind: ProviderKind,
+    id: &ChangeRequestId,
+    svc: Arc<LlmServiceProfiles>,
+    opts: &ReviewOptions,
+) -> MrResult<RunReview> {
+    // --- Step 1: bundle fetch with cache ------------------------------------
+    let t0 = Instant::now();
     debug!("step1: fetch meta to obtain head_sha");
-    let meta = client.fetch_meta(&id).await?;
+    let meta = client.fetch_meta(id).await?;
     let head_sha = meta.diff_refs.head_sha.clone();
     debug!("step1: meta ok, head_sha={}", head_sha);
 
+    if meta.is_draft && !opts.review_drafts {
+        info!(
+            "step1: skip draft/WIP change request {}!{} (review_drafts=false)",
+            id.project, id.iid
+        );
+        return Ok(RunReview::Skipped {
+            reason: SkipReason::Draft,
+        });
+    }
+
+    match opts.label_gate(&meta.labels) {
+        LabelGate::Skip(label) => {
+            info!(
+                "step1: skip change request {}!{}: label '{}'",
+                id.project, id.iid, label
+            );
+            return Ok(RunReview::Skipped {
+                reason: SkipReason::Label(label),
+            });
+        }
+        LabelGate::Security(label) => info!(
+            "step1: label '{}' → SLOW routing with security focus",
+            label
+        ),
+        LabelGate::Default => {}
+    }
+
+    debug!("step1: load repo review config at base");
+    let repo_config = repo_config::load_repo_config(client, id, &meta.diff_refs.base_sha).await?;
+    debug!("step1: repo config = {:?}", repo_config);
+
     debug!("step1: check large-diff cache");
-    let bundle = if let Some(bundle) = cache::load_bundle(&cfg.kind, &id, &head_sha).await? {
+    let bundle = if let Some(bundle) = cache::load_bundle(&kind, id, &head_sha).await? {
         debug!(
             "step1: cache hit → commits={}, files={} ({} ms)",
             bundle.commits.len(),
@@ -76,21 +526,18 @@ pub async fn run_review(
         bundle
     } else {
         debug!("step1: cache miss — proceed to fetch");
-        debug!("step1: fetch commits");
-        let commits = client.fetch_commits(&id).await?;
-        debug!("step1: commits fetched, count={}", commits.len());
-
-        debug!("step1: fetch changes (diffs)");
-        let mut changes = client.fetch_changes(&id).await?;
+        debug!("step1: fetch commits and changes (diffs)");
+        let (commits, mut changes) = client.fetch_commits_and_changes(&meta).await?;
         debug!(
-            "step1: changes fetched, files={}, truncated={}",
+            "step1: fetched commits={}, files={}, truncated={}",
+            commits.len(),
             changes.files.len(),
             changes.is_truncated
         );
 
         if changes.is_truncated {
             debug!("step1: provider reported truncation → try enrich");
-            if let Some(enriched) = client.try_enrich_changes(&id).await? {
+            if let Some(enriched) = client.try_enrich_changes(id).await? {
                 debug!(
                     "step1: enrich success, files={} (was {})",
                     enriched.files.len(),
@@ -109,7 +556,7 @@ pub async fn run_review(
         };
 
         debug!("step1: maybe store bundle to cache (large diffs only)");
-        cache::maybe_store_bundle(&cfg.kind, &id, &head_sha, &bundle).await?;
+        cache::maybe_store_bundle(&kind, id, &head_sha, &bundle).await?;
         debug!(
             "step1: done in {} ms (files={}, commits={})",
             t0.elapsed().as_millis(),
@@ -119,58 +566,427 @@ pub async fn run_review(
         bundle
     };
 
+    draft_from_bundle(client, id, bundle, repo_config, svc, opts).await
+}
+
+/// Steps 2–4 for a fetched bundle: index, map, draft.
+async fn draft_from_bundle(
+    client: &ProviderClient,
+    id: &ChangeRequestId,
+    bundle: CrBundle,
+    repo_config: RepoReviewConfig,
+    svc: Arc<LlmServiceProfiles>,
+    opts: &ReviewOptions,
+) -> MrResult<RunReview> {
+    let mut scope = map::PathScope::new(&opts.include_globs, &opts.exclude_globs);
+    if opts.skip_test_paths {
+        scope = scope.with_test_globs(&opts.test_globs);
+    }
+    if !lang::has_reviewable_changes(&bundle, &scope, opts.review_removals) {
+        info!(
+            "step1: skip {}!{}: no reviewable changes among {} file(s)",
+            id.project,
+            id.iid,
+            bundle.changes.files.len()
+        );
+        return Ok(RunReview::Skipped {
+            reason: SkipReason::NoReviewableChanges,
+        });
+    }
+
     // --- Step 2: delta AST / SymbolIndex ------------------------------------
     let t2 = Instant::now();
     debug!("step2: build delta symbol index for changed files");
-    let symbols = lang::build_delta_symbol_index_for_changed_files(&cfg, &id, &bundle).await?;
+    let symbols = lang::build_delta_symbol_index_for_changed_files(
+        client,
+        id,
+        &bundle,
+        &scope,
+        opts.lossy_decode,
+        opts.review_removals,
+    )
+    .await?;
     debug!(
         "step2: delta index built, symbols={} ({} ms)",
         symbols.symbols.len(),
         t2.elapsed().as_millis()
     );
+    if !symbols.parse_failures.is_empty() {
+        info!(
+            "step2: {} file(s) couldn't be parsed; comments on them may be imprecise",
+            symbols.parse_failures.len()
+        );
+    }
+    if !symbols.skipped_files.is_empty() {
+        info!(
+            "step2: {} changed file(s) skipped; their added lines are not reviewed: {:?}",
+            symbols.skipped_files.len(),
+            symbols.skipped_files
+        );
+    }
 
     // --- Step 3: map diff lines → targets -----------------------------------
     let t3 = Instant::now();
     debug!("step3: map changes to semantic targets");
-    let targets = map::map_changes_to_targets(&bundle, &symbols)?;
+    let mut targets = map::map_changes_to_targets(&bundle, &symbols, &scope)?;
+    if opts.review_removals {
+        let removals = map::map_removals_to_targets(&bundle, &symbols, &scope);
+        debug!("step3: removal targets={}", removals.len());
+        targets.extend(removals);
+    }
+    let before = targets.len();
+    targets.retain(|t| {
+        map::target_path(&t.target).is_empty()
+            || repo_config.allows_path(map::target_path(&t.target))
+    });
     debug!(
-        "step3: targets mapped, count={} ({} ms)",
+        "step3: targets mapped, count={} (filtered by repo config: {}) ({} ms)",
         targets.len(),
+        before - targets.len(),
         t3.elapsed().as_millis()
     );
 
+    let added = lang::added_line_count(&bundle, &scope);
+    if added < opts.min_changed_lines {
+        info!(
+            "step4: skip {}!{}: {} added line(s) < min_changed_lines={} ({} target(s))",
+            id.project,
+            id.iid,
+            added,
+            opts.min_changed_lines,
+            targets.len()
+        );
+        return Ok(RunReview::Skipped {
+            reason: SkipReason::BelowMinChangedLines,
+        });
+    }
+
     let plan = ReviewPlan {
         bundle,
         symbols,
         targets,
+        repo_config,
     };
 
     // --- Step 4: context → prompt → LLM (dual-model) → policy ---------------
     let t4 = Instant::now();
     debug!("step4: build draft comments (context → prompt → llm → policy)");
-    let drafts = review::build_draft_comments(&plan, svc).await?;
+    let review::Step4Output {
+        mut drafts,
+        summary: report,
+    } = review::build_draft_comments(&plan, svc, opts).await?;
+    drafts.retain(|d| plan.repo_config.severity_allows(d.severity));
     debug!(
         "step4: drafts built (count={}) in {} ms",
         drafts.len(),
         t4.elapsed().as_millis()
     );
 
-    let t5 = Instant::now();
-    let results = publish::publish(&cfg, &id, &plan, &drafts, pub_cfg).await?;
-    let created = results
-        .iter()
-        .filter(|r| r.performed && r.created_new)
-        .count();
-    let skipped = results
-        .iter()
-        .filter(|r| r.skipped_reason.is_some())
-        .count();
+    let touched_symbols = plan.touched_symbols();
+    debug!("review: touched symbols={}", touched_symbols.len());
+
+    Ok(RunReview::Completed {
+        plan: Box::new(plan),
+        drafts,
+        report: Box::new(report),
+        touched_symbols,
+    })
+}
+
+/// Review a raw commit range `base_ref..head_ref` (e.g. commits pushed to a
+/// branch without an MR/PR): steps 1–4 over the provider's compare API.
+///
+/// Never publishes; findings are logged and returned as drafts. The bundle
+/// carries synthetic metadata with `iid` 0 (see
+/// [`git_providers::ChangeRequest::commit_range`]), so draft and label gates
+/// and the large-diff cache don't apply. `.mrai.toml` is read at the head commit.
+///
+/// # Errors
+/// Provider errors from the compare call (unsupported for Bitbucket), then
+/// whatever steps 2–4 return.
+pub async fn run_review_for_commit_range(
+    cfg: ProviderConfig,
+    project: &str,
+    base_ref: &str,
+    head_ref: &str,
+    svc: Arc<LlmServiceProfiles>,
+    opts: ReviewOptions,
+) -> MrResult<RunReview> {
+    let t0 = Instant::now();
+    debug!("step1: init provider client");
+    let client = ProviderClient::from_config(cfg)?;
+
+    debug!("step1: compare {}..{} in {}", base_ref, head_ref, project);
+    let bundle = client.fetch_compare(project, base_ref, head_ref).await?;
+    let id = bundle.meta.id.clone();
+    let head_sha = bundle.meta.diff_refs.head_sha.clone();
     debug!(
-        "step5: published created={} skipped={} in {} ms",
-        created,
-        skipped,
-        t5.elapsed().as_millis()
+        "step1: compare ok, head_sha={}, commits={}, files={} ({} ms)",
+        head_sha,
+        bundle.commits.len(),
+        bundle.changes.files.len(),
+        t0.elapsed().as_millis()
+    );
+
+    let base_sha = bundle.meta.diff_refs.base_sha.clone();
+    let repo_config = repo_config::load_repo_config(&client, &id, &base_sha).await?;
+    debug!("step1: repo config = {:?}", repo_config);
+
+    let outcome = draft_from_bundle(&client, &id, bundle, repo_config, svc, &opts).await?;
+    if let RunReview::Completed { drafts, .. } = &outcome {
+        info!(
+            "step5: skipped (commit range {}..{}), {} finding(s)",
+            base_ref,
+            head_ref,
+            drafts.len()
+        );
+        for d in drafts {
+            info!(
+                "finding [{:?}] {}: {}",
+                d.severity,
+                map::target_path(&d.target),
+                d.preview
+            );
+        }
+    }
+    metrics::counter!("reviews_total").increment(1);
+    Ok(outcome)
+}
+
+/// Env keys required by [`run_review_from_env`]; each group is satisfied by any one key.
+const REQUIRED_ENV: &[&[&str]] = &[
+    &["GIT_API_BASE"],
+    &["GIT_TOKEN"],
+    &["OLLAMA_URL", "OLLAMA_PORT"],
+    &["OLLAMA_MODEL"],
+    &["OLLAMA_MODEL_FAST_MODEL", "OLLAMA_MODEL_FAST"],
+    &["EMBEDDING_MODEL"],
+];
+
+/// Run the full pipeline (steps 1–5) for one change request with every
+/// config taken from the environment — for testing a single MR from a
+/// terminal without the HTTP layer.
+///
+/// - Provider: `GIT_API_BASE`, `GIT_TOKEN` (kind inferred from the URL), TLS via
+///   [`TlsConfig::from_env`], extra headers via
+///   [`ProviderConfig::extra_headers_from_env`].
+/// - LLM: Ollama slow/fast/embedding profiles from `ai_llm_service::config::default_config`.
+/// - Publishing and run options: [`publish::PublishConfig::default`] and
+///   [`ReviewOptions::default`] (set `MR_REVIEWER_PUBLISH_DRY_RUN=true` to
+///   avoid posting).
+///
+/// # Errors
+/// [`ConfigError::MissingEnv`] naming every missing required variable, checked
+/// before any network call; otherwise whatever [`run_review`] returns.
+///
+/// # Example
+/// ```no_run
+/// # #[tokio::main] async fn main() {
+/// let outcome = mr_reviewer::run_review_from_env("group/app", 42).await.unwrap();
+/// println!("{outcome:?}");
+/// # }
+/// ```
+pub async fn run_review_from_env(project: &str, iid: u64) -> MrResult<RunReview> {
+    let missing = missing_required_env(env_present);
+    if !missing.is_empty() {
+        return Err(ConfigError::MissingEnv(missing).into());
+    }
+
+    let cfg = ProviderConfig::new(
+        None,
+        std::env::var("GIT_API_BASE").unwrap_or_default(),
+        std::env::var("GIT_TOKEN").unwrap_or_default(),
+        TlsConfig::from_env(),
+    )?;
+    let (extra_headers, allow_auth) = ProviderConfig::extra_headers_from_env();
+    let cfg = cfg.with_extra_headers(extra_headers, allow_auth);
+
+    let llm = |e: ai_llm_service::error_handler::AiLlmError| ConfigError::Llm(e.to_string());
+    let slow = ai_llm_service::config::default_config::config_ollama_slow().map_err(llm)?;
+    let fast = ai_llm_service::config::default_config::config_ollama_fast().map_err(llm)?;
+    let embedding =
+        ai_llm_service::config::default_config::config_ollama_embedding().map_err(llm)?;
+    let svc =
+        Arc::new(LlmServiceProfiles::new(fast, Some(slow), embedding, Some(10)).map_err(llm)?);
+
+    let id = ChangeRequestId {
+        project: project.to_string(),
+        iid,
+    };
+    info!(
+        "review_from_env: {}!{} via {:?} {}",
+        id.project, id.iid, cfg.kind, cfg.base_api
     );
 
-    Ok((plan, drafts))
+    run_review(
+        cfg,
+        id,
+        svc,
+        publish::PublishConfig::default(),
+        ReviewOptions::default(),
+    )
+    .await
+}
+
+/// Groups of [`REQUIRED_ENV`] with no key present, rendered as `A or B`.
+fn missing_required_env(present: impl Fn(&str) -> bool) -> Vec<String> {
+    REQUIRED_ENV
+        .iter()
+        .filter(|keys| !keys.iter().any(|k| present(k)))
+        .map(|keys| keys.join(" or "))
+        .collect()
+}
+
+/// Set and non-blank.
+fn env_present(key: &str) -> bool {
+    std::env::var(key).is_ok_and(|v| !v.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git_providers::{ChangeRequest, ChangeSet, DiffHunk, DiffLine, DiffRefs, FileChange};
+    use std::collections::HashMap;
+
+    fn file(path: &str, is_binary: bool, is_deleted: bool) -> FileChange {
+        FileChange {
+            old_path: Some(path.into()),
+            new_path: (!is_deleted).then(|| path.into()),
+            is_new: false,
+            is_deleted,
+            is_renamed: false,
+            is_binary,
+            hunks: vec![DiffHunk {
+                old_start: 1,
+                old_lines: 0,
+                new_start: 1,
+                new_lines: 1,
+                lines: vec![DiffLine::Added {
+                    new_line: 1,
+                    content: "added".into(),
+                }],
+            }],
+            raw_unidiff: None,
+        }
+    }
+
+    /// Steps 1–4 over a recorded change request with `files` (replay LLM).
+    async fn draft_files(
+        files: Vec<FileChange>,
+        head_files: HashMap<String, Vec<u8>>,
+        opts: &ReviewOptions,
+    ) -> RunReview {
+        let diff_refs = DiffRefs {
+            base_sha: "a".repeat(40),
+            start_sha: None,
+            head_sha: "b".repeat(40),
+        };
+        let mut meta = ChangeRequest::commit_range(
+            ProviderKind::GitLab,
+            "group/app",
+            "main",
+            "feature",
+            diff_refs,
+            String::new(),
+        );
+        meta.id.iid = 7;
+        let id = meta.id.clone();
+        let bundle = CrBundle {
+            meta,
+            commits: Vec::new(),
+            changes: ChangeSet {
+                files,
+                is_truncated: false,
+            },
+        };
+        test_support::temp_data_root();
+        let client = ProviderClient::from_recorded(bundle.clone(), head_files);
+        let profile = snapshot::replay_profile();
+        let svc = Arc::new(LlmServiceProfiles::new(profile.clone(), None, profile, None).unwrap());
+
+        draft_from_bundle(&client, &id, bundle, RepoReviewConfig::default(), svc, opts)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn binary_only_change_request_is_skipped() {
+        let out = draft_files(
+            vec![
+                file("assets/logo.png", true, false),
+                file("pubspec.lock", false, false),
+                file("lib/old.dart", false, true),
+            ],
+            HashMap::new(),
+            &ReviewOptions::default(),
+        )
+        .await;
+        assert!(
+            matches!(&out, RunReview::Skipped { reason } if *reason == SkipReason::NoReviewableChanges),
+            "{out:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn one_line_change_is_skipped_below_min_changed_lines() {
+        let opts = ReviewOptions {
+            min_changed_lines: 5,
+            ..ReviewOptions::default()
+        };
+        // Lockfile lines aren't reviewed, so they don't count towards the size.
+        let mut lock = file("pubspec.lock", false, false);
+        lock.hunks[0].lines = (1..=10)
+            .map(|n| DiffLine::Added {
+                new_line: n,
+                content: "dep".into(),
+            })
+            .collect();
+        let head = HashMap::from([("lib/main.dart".to_string(), b"added\n".to_vec())]);
+        let out = draft_files(vec![file("lib/main.dart", false, false), lock], head, &opts).await;
+        assert!(
+            matches!(&out, RunReview::Skipped { reason } if *reason == SkipReason::BelowMinChangedLines),
+            "{out:?}"
+        );
+    }
+
+    #[test]
+    fn missing_env_names_every_unsatisfied_key() {
+        let set = ["GIT_API_BASE", "OLLAMA_PORT", "OLLAMA_MODEL_FAST"];
+        let missing = missing_required_env(|k| set.contains(&k));
+        assert_eq!(
+            missing,
+            vec!["GIT_TOKEN", "OLLAMA_MODEL", "EMBEDDING_MODEL"]
+        );
+
+        let err = errors::Error::from(ConfigError::MissingEnv(missing));
+        assert_eq!(
+            err.to_string(),
+            "missing required environment variable(s): GIT_TOKEN, OLLAMA_MODEL, EMBEDDING_MODEL"
+        );
+    }
+
+    #[test]
+    fn labels_gate_skip_and_security_review() {
+        let opts = ReviewOptions {
+            skip_labels: vec!["skip-ai-review".into()],
+            security_labels: vec!["needs-security-review".into()],
+            ..ReviewOptions::default()
+        };
+        let labels = |ls: &[&str]| ls.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(opts.label_gate(&labels(&["frontend"])), LabelGate::Default);
+        assert_eq!(
+            opts.label_gate(&labels(&["Skip-AI-Review"])),
+            LabelGate::Skip("Skip-AI-Review".into())
+        );
+        assert_eq!(
+            opts.label_gate(&labels(&["needs-security-review", "backend"])),
+            LabelGate::Security("needs-security-review".into())
+        );
+        // Skipping wins: no point forcing SLOW on a review that won't run.
+        assert_eq!(
+            opts.label_gate(&labels(&["needs-security-review", "skip-ai-review"])),
+            LabelGate::Skip("skip-ai-review".into())
+        );
+    }
 }
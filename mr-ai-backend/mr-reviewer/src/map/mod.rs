@@ -13,6 +13,11 @@
 //! 5) Compute `snippet_hash` from the materialized file at MR `head_sha`;
 //! 6) Return `MappedTarget[]` for downstream prompt building and publishing.
 
+mod removals;
+mod scope;
+pub use removals::map_removals_to_targets;
+pub use scope::PathScope;
+
 use std::{
     cmp::{max, min},
     collections::BTreeMap,
@@ -20,11 +25,13 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use serde::Serialize;
 use sha2::{Digest, Sha256};
+use tracing::info;
 
 use crate::errors::MrResult;
 use crate::git_providers::types::{CrBundle, DiffLine};
-use crate::lang::{SymbolIndex, SymbolKind, SymbolRecord};
+use crate::lang::{SymbolIndex, SymbolKind, SymbolRecord, is_lockfile};
 
 /// Maximum allowed gap between consecutive lines (inclusive) to merge them into
 /// a single range cluster. Example: gap=2 merges 10,11,13 (since 13-11=2).
@@ -67,6 +74,36 @@ pub struct OwnerSymbol {
     pub body_end: usize,
 }
 
+/// A function/class touched by the change request, for a findings-independent overview.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct TouchedSymbol {
+    pub path: String,
+    pub name: String,
+    pub kind: SymbolKind,
+    /// Symbol body span `[start, end]` (1-based, inclusive, HEAD).
+    pub lines: (usize, usize),
+}
+
+/// Collect unique owner symbols of `targets`, ordered by `(path, start line)`.
+pub fn touched_symbols(targets: &[MappedTarget]) -> Vec<TouchedSymbol> {
+    let mut by_key: BTreeMap<(String, usize, String), TouchedSymbol> = BTreeMap::new();
+    for t in targets {
+        let Some(owner) = &t.owner else { continue };
+        let path = target_path(&t.target).to_string();
+        let start = owner.body_start.min(owner.decl_line).max(1);
+        let end = owner.body_end.max(start);
+        by_key
+            .entry((path.clone(), start, owner.symbol_id.clone()))
+            .or_insert_with(|| TouchedSymbol {
+                path,
+                name: owner.name.clone(),
+                kind: owner.kind,
+                lines: (start, end),
+            });
+    }
+    by_key.into_values().collect()
+}
+
 /// Evidence that led to building this target (useful for prompts/debugging).
 #[derive(Debug, Clone)]
 pub struct Evidence {
@@ -74,6 +111,11 @@ pub struct Evidence {
     pub added_lines: Vec<usize>,
     /// True if at least one added line hits the symbol declaration line.
     pub touches_decl: bool,
+    /// Removed line numbers (1-based, old file) of a removal target.
+    pub removed_lines: Vec<usize>,
+    /// Removed code with old-side line numbers, for removal targets only
+    /// (see [`map_removals_to_targets`]).
+    pub removed_code: Option<String>,
 }
 
 /// Final mapping result for a commentable target.
@@ -97,15 +139,27 @@ pub struct MappedTarget {
 /// This function is synchronous; it performs a small amount of filesystem IO
 /// to read materialized files under `code_data/mr_tmp/<head12>/...` so it can
 /// compute snippet hashes and previews from the **new content** at `head_sha`.
+///
+/// Files outside `scope` and test files marked by the scope (see
+/// [`PathScope::is_test`]) produce no targets.
 pub fn map_changes_to_targets(
     bundle: &CrBundle,
     index: &SymbolIndex,
+    scope: &PathScope,
 ) -> MrResult<Vec<MappedTarget>> {
     let head_sha = &bundle.meta.diff_refs.head_sha;
     let tmp_root = tmp_root_for(head_sha);
 
     // 1) Collect all added lines keyed by (path, optional symbol_id).
-    let clusters = collect_and_cluster_added_lines(bundle, index);
+    let mut clusters = collect_and_cluster_added_lines(bundle, index, scope);
+    let before = clusters.len();
+    clusters.retain(|c| !scope.is_test(&c.path));
+    if clusters.len() < before {
+        info!(
+            "step3: skipped {} target(s) in test files",
+            before - clusters.len()
+        );
+    }
 
     // 2) Convert clusters to TargetRefs and compute hashes.
     let mut out: Vec<MappedTarget> = Vec::new();
@@ -160,8 +214,13 @@ struct LineCluster {
 
 /// Collect added lines per file, resolve owning symbols, and cluster lines by
 /// path + symbol with small gaps merged. This reduces noise and provides
-/// tight ranges for LLM prompts and inline comments.
-fn collect_and_cluster_added_lines(bundle: &CrBundle, index: &SymbolIndex) -> Vec<LineCluster> {
+/// tight ranges for LLM prompts and inline comments. Files outside `scope`
+/// and files step 2 skipped ([`SymbolIndex::skipped_files`]) are skipped.
+fn collect_and_cluster_added_lines(
+    bundle: &CrBundle,
+    index: &SymbolIndex,
+    scope: &PathScope,
+) -> Vec<LineCluster> {
     // For each (path, symbol_id) keep the current open cluster.
     let mut open: BTreeMap<(String, Option<String>), LineCluster> = BTreeMap::new();
     let mut finished: Vec<LineCluster> = Vec::new();
@@ -175,6 +234,12 @@ fn collect_and_cluster_added_lines(bundle: &CrBundle, index: &SymbolIndex) -> Ve
         let Some(path) = fc.new_path.as_ref().or(fc.old_path.as_ref()) else {
             continue;
         };
+        if is_lockfile(path)
+            || !scope.allows(path)
+            || index.skipped_files.iter().any(|s| &s.path == path)
+        {
+            continue;
+        }
 
         for h in &fc.hunks {
             for ln in &h.lines {
@@ -265,6 +330,8 @@ fn classify_cluster_to_target(
     let evidence = Evidence {
         added_lines: c.added_lines.clone(),
         touches_decl: c.touches_decl,
+        removed_lines: Vec::new(),
+        removed_code: None,
     };
 
     // Prefer Symbol if the declaration was touched (signature/header change).
@@ -400,7 +467,7 @@ fn tmp_root_for(head_sha: &str) -> PathBuf {
     } else {
         head_sha
     };
-    PathBuf::from("code_data").join("mr_tmp").join(short)
+    services::data_root::mr_tmp_dir().join(short)
 }
 
 fn target_start_line(t: &TargetRef) -> usize {
@@ -421,7 +488,8 @@ fn target_end_line(t: &TargetRef) -> usize {
     }
 }
 
-fn target_path(t: &TargetRef) -> &str {
+/// Repo-relative path of a target (`""` for `Global`).
+pub fn target_path(t: &TargetRef) -> &str {
     match t {
         TargetRef::Line { path, .. }
         | TargetRef::Range { path, .. }
@@ -430,3 +498,165 @@ fn target_path(t: &TargetRef) -> &str {
         TargetRef::Global => "",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(path: &str, line: usize, owner: Option<(&str, &str, usize, usize)>) -> MappedTarget {
+        MappedTarget {
+            target: TargetRef::Line {
+                path: path.to_string(),
+                line,
+            },
+            owner: owner.map(|(id, name, start, end)| OwnerSymbol {
+                symbol_id: id.to_string(),
+                kind: SymbolKind::Method,
+                name: name.to_string(),
+                decl_line: start,
+                body_start: start,
+                body_end: end,
+            }),
+            snippet_hash: String::new(),
+            preview: String::new(),
+            evidence: Evidence {
+                added_lines: vec![line],
+                touches_decl: false,
+                removed_lines: Vec::new(),
+                removed_code: None,
+            },
+        }
+    }
+
+    #[test]
+    fn touched_symbols_are_unique_and_ordered() {
+        let targets = vec![
+            target("lib/b.dart", 12, Some(("b#run", "run", 10, 20))),
+            target("lib/a.dart", 40, Some(("a#build", "build", 30, 50))),
+            target("lib/b.dart", 15, Some(("b#run", "run", 10, 20))),
+            target("lib/a.dart", 3, None),
+        ];
+        let got = touched_symbols(&targets);
+        let names: Vec<_> = got
+            .iter()
+            .map(|s| (s.path.as_str(), s.name.as_str()))
+            .collect();
+        assert_eq!(names, vec![("lib/a.dart", "build"), ("lib/b.dart", "run")]);
+        assert_eq!(got[1].lines, (10, 20));
+    }
+
+    fn bundle_with_changes(paths: &[&str]) -> CrBundle {
+        let files: Vec<_> = paths
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "old_path": p, "new_path": p,
+                    "is_new": false, "is_deleted": false, "is_renamed": false, "is_binary": false,
+                    "hunks": [{
+                        "old_start": 1, "old_lines": 0, "new_start": 1, "new_lines": 2,
+                        "lines": [
+                            { "Added": { "new_line": 1, "content": "a" } },
+                            { "Added": { "new_line": 2, "content": "b" } }
+                        ]
+                    }],
+                    "raw_unidiff": null
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "meta": {
+                "provider": "GitLab",
+                "id": { "project": "g/app", "iid": 1 },
+                "title": "t", "description": null,
+                "author": { "id": "1", "username": null, "name": null, "web_url": null, "avatar_url": null },
+                "state": "opened", "web_url": "",
+                "created_at": "2025-01-01T00:00:00Z", "updated_at": "2025-01-01T00:00:00Z",
+                "source_branch": null, "target_branch": null,
+                "diff_refs": { "base_sha": "b", "start_sha": null, "head_sha": "scope-test-head" }
+            },
+            "commits": [],
+            "changes": { "files": files, "is_truncated": false }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn excluded_paths_produce_no_targets() {
+        let bundle = bundle_with_changes(&["lib/a.dart", "test/a_test.dart", "tool/gen.dart"]);
+        let index = SymbolIndex {
+            symbols: Vec::new(),
+            by_path: BTreeMap::new(),
+            by_name: BTreeMap::new(),
+            by_id: Default::default(),
+            parse_failures: Vec::new(),
+            tmp_root: None,
+            skipped_files: Vec::new(),
+            library_of: BTreeMap::new(),
+        };
+        let paths = |scope: &PathScope| {
+            map_changes_to_targets(&bundle, &index, scope)
+                .unwrap()
+                .into_iter()
+                .map(|t| target_path(&t.target).to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(paths(&PathScope::default()).len(), 3);
+
+        let only_lib = PathScope::new(&["lib/**".into()], &["test/**".into()]);
+        assert_eq!(paths(&only_lib), vec!["lib/a.dart"]);
+
+        let no_tests = PathScope::new(&[], &["test/**".into()]);
+        assert!(paths(&no_tests).iter().all(|p| !p.starts_with("test/")));
+        assert_eq!(paths(&no_tests).len(), 2);
+
+        let nothing = PathScope::new(&[], &["**".into()]);
+        assert!(paths(&nothing).is_empty());
+    }
+
+    #[test]
+    fn test_files_get_no_targets_when_skipped() {
+        let bundle = bundle_with_changes(&[
+            "lib/a.dart",
+            "test/a_test.dart",
+            "lib/src/b_test.dart",
+            "web/app.spec.ts",
+        ]);
+        let index = crate::lang::build_index_maps(Vec::new(), Vec::new());
+        let globs: Vec<String> = crate::DEFAULT_TEST_GLOBS
+            .split(',')
+            .map(String::from)
+            .collect();
+        let scope = PathScope::default().with_test_globs(&globs);
+
+        // Still in scope (parsed in step 2), just not targeted.
+        assert!(scope.allows("test/a_test.dart") && scope.is_test("test/a_test.dart"));
+        let targets = map_changes_to_targets(&bundle, &index, &scope).unwrap();
+        let paths: Vec<&str> = targets.iter().map(|t| target_path(&t.target)).collect();
+        assert_eq!(paths, vec!["lib/a.dart"]);
+
+        let all = map_changes_to_targets(&bundle, &index, &PathScope::default()).unwrap();
+        assert_eq!(all.len(), 4);
+    }
+
+    #[test]
+    fn lockfiles_get_no_targets() {
+        let bundle = bundle_with_changes(&["lib/a.dart", "pubspec.lock", "web/package-lock.json"]);
+        let index = crate::lang::build_index_maps(Vec::new(), Vec::new());
+
+        let targets = map_changes_to_targets(&bundle, &index, &PathScope::default()).unwrap();
+        let paths: Vec<&str> = targets.iter().map(|t| target_path(&t.target)).collect();
+        assert_eq!(paths, vec!["lib/a.dart"]);
+    }
+
+    #[test]
+    fn skipped_files_get_no_targets() {
+        let bundle = bundle_with_changes(&["lib/a.dart", "lib/legacy.dart"]);
+        let mut index = crate::lang::build_index_maps(Vec::new(), Vec::new());
+        index.skipped_files = vec![crate::lang::SkippedFile::new("lib/legacy.dart", "non-utf8")];
+
+        let targets = map_changes_to_targets(&bundle, &index, &PathScope::default()).unwrap();
+        let paths: Vec<&str> = targets.iter().map(|t| target_path(&t.target)).collect();
+        assert_eq!(paths, vec!["lib/a.dart"]);
+    }
+}
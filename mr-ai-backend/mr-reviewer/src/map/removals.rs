@@ -0,0 +1,242 @@
+//! Removal targets (`ReviewOptions::review_removals`).
+//!
+//! Added-line clustering never sees code that was only deleted, so a finding
+//! like "you removed `fetchUser`; is that intended?" has nowhere to anchor.
+//! Here each pure removal (a run of removed lines with no added lines in the
+//! same hunk) that deletes a public declaration becomes a `File` target. The
+//! removed code travels in [`Evidence::removed_code`] with its old-side line
+//! numbers; the owner is the HEAD symbol enclosing the removal site (e.g. the
+//! class a method was removed from).
+//!
+//! Deleted files get no removal targets: there is no HEAD file to review.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use super::{Evidence, MappedTarget, PathScope, TargetRef, symbol_to_owner};
+use crate::git_providers::types::{CrBundle, DiffLine};
+use crate::lang::{SymbolIndex, is_lockfile};
+
+/// Declaration keywords shared by the supported languages, followed by the name.
+const DECL_KEYWORDS: &str = r"^\s*(?:export\s+|pub(?:\([^)]*\))?\s+|public\s+|protected\s+)?(?:(?:static|async|abstract|final|sealed|base|const|default)\s+)*(?:fn|function|class|def|func|interface|struct|enum|trait|mixin|extension|typedef|type)\s+([A-Za-z_$][\w$]*)";
+
+/// Typed function/method declarations (Dart, Java, Kotlin, C#), e.g.
+/// `Future<User> fetchUser(String id) async {`.
+const TYPED_DECL: &str = r"^\s*(?:(?:static|external|override)\s+)*[A-Za-z_][\w<>?,.\[\] ]*\s+([A-Za-z_$][\w$]*)\s*\([^;]*\)\s*(?:async\s*)?(?:\{|=>)";
+
+static KEYWORD_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(DECL_KEYWORDS).unwrap());
+static TYPED_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(TYPED_DECL).unwrap());
+
+/// Statement keywords that look like a typed declaration's return type.
+const NOT_A_TYPE: &[&str] = &["return", "await", "throw", "new", "else", "yield", "case"];
+
+/// Build `File` targets for pure removals of public declarations in `bundle`.
+///
+/// Files outside `scope`, test files marked by it, deleted and binary files
+/// produce no targets. Output is ordered by path, then old-side line.
+pub fn map_removals_to_targets(
+    bundle: &CrBundle,
+    index: &SymbolIndex,
+    scope: &PathScope,
+) -> Vec<MappedTarget> {
+    let mut out = Vec::new();
+    for fc in &bundle.changes.files {
+        if fc.is_binary || fc.is_deleted {
+            continue;
+        }
+        let Some(path) = fc.new_path.as_ref().or(fc.old_path.as_ref()) else {
+            continue;
+        };
+        if is_lockfile(path) || !scope.allows(path) || scope.is_test(path) {
+            continue;
+        }
+
+        for h in &fc.hunks {
+            if h.lines.iter().any(|l| matches!(l, DiffLine::Added { .. })) {
+                continue; // a rewrite, already covered by added-line targets
+            }
+            let mut site = h.new_start.max(1);
+            let mut block: Vec<(u32, &str)> = Vec::new();
+            for ln in &h.lines {
+                match ln {
+                    DiffLine::Removed { old_line, content } => block.push((*old_line, content)),
+                    DiffLine::Context { new_line, .. } | DiffLine::Added { new_line, .. } => {
+                        if !block.is_empty() {
+                            out.extend(removal_target(path, site, &block, index));
+                            block.clear();
+                        }
+                        site = *new_line;
+                    }
+                }
+            }
+            if !block.is_empty() {
+                out.extend(removal_target(path, site, &block, index));
+            }
+        }
+    }
+    out.sort_by(|a, b| {
+        let line = |t: &MappedTarget| t.evidence.removed_lines.first().copied();
+        (super::target_path(&a.target), line(a)).cmp(&(super::target_path(&b.target), line(b)))
+    });
+    out
+}
+
+/// Target for one removed block, if it deletes a public declaration.
+///
+/// `site` is the last HEAD line before the removal (used to find the owner).
+fn removal_target(
+    path: &str,
+    site: u32,
+    block: &[(u32, &str)],
+    index: &SymbolIndex,
+) -> Option<MappedTarget> {
+    let decl = block
+        .iter()
+        .find_map(|(_, text)| public_declaration(path, text))?;
+
+    let removed_code: String = block
+        .iter()
+        .map(|(n, text)| format!("{n:>5} | {text}\n"))
+        .collect();
+    let mut hasher = Sha256::new();
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    for (_, text) in block {
+        hasher.update(text.as_bytes());
+        hasher.update(b"\n");
+    }
+
+    Some(MappedTarget {
+        target: TargetRef::File {
+            path: path.to_string(),
+        },
+        owner: index
+            .find_enclosing_by_line(path, site)
+            .map(symbol_to_owner),
+        snippet_hash: format!("{:x}", hasher.finalize()),
+        preview: format!("removed `{decl}`"),
+        evidence: Evidence {
+            added_lines: Vec::new(),
+            touches_decl: false,
+            removed_lines: block.iter().map(|(n, _)| *n as usize).collect(),
+            removed_code: Some(removed_code),
+        },
+    })
+}
+
+/// Name declared on `line` when it looks like a public declaration.
+///
+/// Names with a leading `_`, `private` members and Rust items without `pub`
+/// are not public.
+fn public_declaration(path: &str, line: &str) -> Option<String> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with("private ") || (path.ends_with(".rs") && !trimmed.starts_with("pub")) {
+        return None;
+    }
+    let name = KEYWORD_RE
+        .captures(line)
+        .or_else(|| {
+            let first = line.split_whitespace().next().unwrap_or("");
+            TYPED_RE
+                .captures(line)
+                .filter(|_| !NOT_A_TYPE.contains(&first))
+        })?
+        .get(1)?
+        .as_str();
+    (!name.starts_with('_')).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pure_removal_bundle() -> CrBundle {
+        let removed = |n: u32, text: &str| serde_json::json!({ "Removed": { "old_line": n, "content": text } });
+        let context = |old: u32, new: u32, text: &str| serde_json::json!({ "Context": { "old_line": old, "new_line": new, "content": text } });
+        serde_json::from_value(serde_json::json!({
+            "meta": {
+                "provider": "GitLab",
+                "id": { "project": "g/app", "iid": 1 },
+                "title": "t", "description": null,
+                "author": { "id": "1", "username": null, "name": null, "web_url": null, "avatar_url": null },
+                "state": "opened", "web_url": "",
+                "created_at": "2025-01-01T00:00:00Z", "updated_at": "2025-01-01T00:00:00Z",
+                "source_branch": null, "target_branch": null,
+                "diff_refs": { "base_sha": "b", "start_sha": null, "head_sha": "removal-test-head" }
+            },
+            "commits": [],
+            "changes": { "files": [{
+                "old_path": "lib/api.dart", "new_path": "lib/api.dart",
+                "is_new": false, "is_deleted": false, "is_renamed": false, "is_binary": false,
+                "hunks": [
+                    { "old_start": 9, "old_lines": 6, "new_start": 9, "new_lines": 2, "lines": [
+                        context(9, 9, "class Api {"),
+                        removed(10, "  Future<User> fetchUser(String id) async {"),
+                        removed(11, "    return _get('/users/$id');"),
+                        removed(12, "  }"),
+                        removed(13, ""),
+                        context(14, 10, "}")
+                    ]},
+                    { "old_start": 30, "old_lines": 2, "new_start": 26, "new_lines": 1, "lines": [
+                        removed(30, "  void _log(String m) => print(m);"),
+                        context(31, 26, "")
+                    ]}
+                ],
+                "raw_unidiff": null
+            }], "is_truncated": false }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn pure_removal_of_public_function_produces_file_target() {
+        let bundle = pure_removal_bundle();
+        let index = crate::lang::build_index_maps(Vec::new(), Vec::new());
+
+        let targets = map_removals_to_targets(&bundle, &index, &PathScope::default());
+        assert_eq!(targets.len(), 1, "private `_log` removal is not a target");
+        let t = &targets[0];
+        assert_eq!(
+            t.target,
+            TargetRef::File {
+                path: "lib/api.dart".into()
+            }
+        );
+        assert_eq!(t.preview, "removed `fetchUser`");
+        assert_eq!(t.evidence.removed_lines, vec![10, 11, 12, 13]);
+        let code = t.evidence.removed_code.as_deref().unwrap();
+        assert!(code.contains("   10 |   Future<User> fetchUser(String id) async {"));
+        assert!(t.evidence.added_lines.is_empty());
+
+        // Added-line mapping alone never sees the removal.
+        let added = crate::map::map_changes_to_targets(&bundle, &index, &PathScope::default());
+        assert!(added.unwrap().is_empty());
+
+        let excluded = PathScope::new(&[], &["lib/**".into()]);
+        assert!(map_removals_to_targets(&bundle, &index, &excluded).is_empty());
+    }
+
+    #[test]
+    fn public_declarations_are_recognized_across_languages() {
+        let decl = |path: &str, line: &str| public_declaration(path, line);
+        assert_eq!(
+            decl("a.dart", "class UserRepo {").as_deref(),
+            Some("UserRepo")
+        );
+        assert_eq!(decl("a.dart", "  int get size => 1;"), None);
+        assert_eq!(decl("a.dart", "  return fetch(id) {"), None);
+        assert_eq!(
+            decl("a.ts", "export async function load(id) {").as_deref(),
+            Some("load")
+        );
+        assert_eq!(
+            decl("a.rs", "pub fn parse(s: &str) -> u8 {").as_deref(),
+            Some("parse")
+        );
+        assert_eq!(decl("a.rs", "fn helper() {"), None);
+        assert_eq!(decl("a.py", "def _private():"), None);
+        assert_eq!(decl("A.java", "private void save() {"), None);
+    }
+}
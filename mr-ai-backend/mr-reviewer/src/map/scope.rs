@@ -0,0 +1,69 @@
+//! Per-review path scoping (`ReviewOptions::include_globs` / `exclude_globs`).
+//!
+//! Applied before step 2 parses files and before step 3 builds targets, so
+//! out-of-scope changes never reach the LLM. Unlike `.mrai.toml` `ignore`
+//! (a repository setting), this narrows a single review run.
+//!
+//! Test files (`ReviewOptions::skip_test_paths`) stay in scope, so step 2
+//! still parses them, but step 3 builds no targets for them.
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use tracing::warn;
+
+/// Compiled include/exclude globs; the default scope allows every path.
+#[derive(Debug, Clone, Default)]
+pub struct PathScope {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+    tests: Option<GlobSet>,
+}
+
+impl PathScope {
+    /// Builds a scope from glob patterns (e.g. `lib/**`, `test/**`).
+    ///
+    /// Empty `include` means "everything"; `exclude` wins over `include`.
+    /// Invalid patterns are logged and ignored.
+    pub fn new(include: &[String], exclude: &[String]) -> Self {
+        Self {
+            include: compile(include),
+            exclude: compile(exclude),
+            tests: None,
+        }
+    }
+
+    /// Treats paths matching `patterns` (e.g. `**/test/**`, `*_test.dart`)
+    /// as test files; see [`Self::is_test`].
+    pub fn with_test_globs(mut self, patterns: &[String]) -> Self {
+        self.tests = compile(patterns);
+        self
+    }
+
+    /// True if `path` is a test file that should get no review targets.
+    pub fn is_test(&self, path: &str) -> bool {
+        self.tests.as_ref().is_some_and(|s| s.is_match(path))
+    }
+
+    /// True if changes in `path` should be reviewed.
+    pub fn allows(&self, path: &str) -> bool {
+        if self.exclude.as_ref().is_some_and(|s| s.is_match(path)) {
+            return false;
+        }
+        self.include.as_ref().is_none_or(|s| s.is_match(path))
+    }
+}
+
+fn compile(patterns: &[String]) -> Option<GlobSet> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut b = GlobSetBuilder::new();
+    for p in patterns {
+        match Glob::new(p) {
+            Ok(g) => {
+                b.add(g);
+            }
+            Err(e) => warn!("scope: bad glob '{}': {}", p, e),
+        }
+    }
+    b.build().ok()
+}
@@ -8,18 +8,29 @@
 //! - GET  /projects/:id/merge_requests/:iid/discussions   (for idempotency)
 //! - GET  /projects/:id/merge_requests/:iid/notes         (for idempotency, fallback)
 //!
-//! Position requires `head_sha` + `base_sha` + (usually) `start_sha` from MR meta.
+//! Position requires `head_sha` + `base_sha` + (usually) `start_sha` from MR meta;
+//! the caller passes diff refs already revalidated against the current head.
 //!
 //! Notable fixes & improvements:
-//! - URL-encodes `project` segments in all endpoints.
+//! - Normalizes and URL-encodes `project` segments in all endpoints
+//!   (numeric id or path, see [`project_segment`]).
 //! - Posts the full markdown body and appends a hidden idempotency marker.
-//! - Loads existing markers from both discussions and notes.
+//! - Loads existing markers from both discussions and notes, following pagination.
 //! - Supports both `new_*` and `old_*` inline positions (with auto-retry).
+//! - Posts `Range` targets as multi-line comments (`line_range`), falling back to
+//!   a single-line anchor on `start_line` if GitLab rejects the range. Range ends
+//!   carry GitLab's `line_code` (`<sha1(path)>_<old>_<new>`), with old-side
+//!   positions taken from the planned diff.
 //! - Passes `start_sha` when available.
 //! - Applies robust HTTP timeouts and limited concurrency.
 //! - Retries transient errors (5xx/429) with exponential backoff honoring `Retry-After`.
+//! - Shares the per-host provider rate limit with reads (`git_providers::rate_limit`).
 
-use std::{collections::HashSet, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 
 use regex::Regex;
 use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderValue, USER_AGENT};
@@ -27,14 +38,12 @@ use tokio::sync::Semaphore;
 use tracing::{debug, info, warn};
 
 use crate::errors::{Error, MrResult};
-use crate::git_providers::ChangeRequestId;
+use crate::git_providers::gitlab::{next_page_url, project_segment};
+use crate::git_providers::rate_limit;
+use crate::git_providers::{ChangeRequestId, ChangeSet, DiffHunk, DiffLine, DiffRefs};
 use crate::map::TargetRef;
+use crate::publish::{ProviderIds, PublishConfig, PublishedComment, render_comment_body};
 use crate::review::DraftComment;
-use crate::{
-    ReviewPlan,
-    publish::{ProviderIds, PublishConfig, PublishedComment},
-};
-use urlencoding::encode;
 
 /// Hidden marker we embed into comment body to detect duplicates.
 /// Example: `<!-- mrai:key=packages/a.dart:42|line;hash=abcdef;ver=1 -->`
@@ -46,6 +55,9 @@ const MAX_RETRIES: usize = 3;
 /// Initial backoff for transient failures.
 const INITIAL_BACKOFF_MS: u64 = 400;
 
+/// Upper bound on pages read from one list endpoint (100 items per page).
+const MAX_LIST_PAGES: usize = 50;
+
 /// Publish all drafts to GitLab.
 ///
 /// Loads existing markers (from both discussions and notes) to enforce idempotency,
@@ -54,8 +66,10 @@ const INITIAL_BACKOFF_MS: u64 = 400;
 /// # Parameters
 /// - `cfg`: Provider configuration (token, base API).
 /// - `id`: MR identifier (project path or id, IID).
-/// - `plan`: Review plan (used for MR diff refs).
+/// - `diff_refs`: MR diff refs for inline positions (revalidated by the caller).
 /// - `drafts`: Draft comments to publish.
+/// - `changes`: Diff at `diff_refs.head_sha`, used for `line_range` line codes;
+///   `None` when unknown (old positions then equal new ones).
 /// - `pcfg`: Publish configuration (dry-run, concurrency, etc.).
 ///
 /// # Returns
@@ -63,12 +77,13 @@ const INITIAL_BACKOFF_MS: u64 = 400;
 pub async fn publish_gitlab(
     cfg: &crate::git_providers::ProviderConfig,
     id: &ChangeRequestId,
-    plan: &ReviewPlan,
+    diff_refs: &DiffRefs,
     drafts: &[DraftComment],
+    changes: Option<&ChangeSet>,
     pcfg: &PublishConfig,
 ) -> MrResult<Vec<PublishedComment>> {
-    let http = build_http_client()?;
-    let headers = build_gitlab_headers(&cfg.token)?;
+    let http = build_http_client(&cfg.tls)?;
+    let headers = build_gitlab_headers(&cfg.token, &cfg.extra_header_map()?)?;
     let base = cfg.base_api.trim_end_matches('/');
 
     // Load existing markers to enforce idempotency (from discussions and notes)
@@ -86,9 +101,20 @@ pub async fn publish_gitlab(
     );
 
     // Extract SHAs for inline comment positions (pass start_sha when available)
-    let head = plan.bundle.meta.diff_refs.head_sha.clone();
-    let base_sha = plan.bundle.meta.diff_refs.base_sha.clone();
-    let start_sha_opt = plan.bundle.meta.diff_refs.start_sha.clone();
+    let head = diff_refs.head_sha.clone();
+    let base_sha = diff_refs.base_sha.clone();
+    let start_sha_opt = diff_refs.start_sha.clone();
+
+    let hunks: Arc<HashMap<String, Vec<DiffHunk>>> = Arc::new(
+        changes
+            .map(|c| {
+                c.files
+                    .iter()
+                    .filter_map(|f| Some((f.new_path.clone()?, f.hunks.clone())))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    );
 
     // Concurrency guard
     let sem = Arc::new(Semaphore::new(pcfg.max_concurrency.max(1)));
@@ -102,9 +128,9 @@ pub async fn publish_gitlab(
         let head = head.clone();
         let base_sha = base_sha.clone();
         let start_sha_opt = start_sha_opt.clone();
-        let dry_run = pcfg.dry_run;
-        let allow_edit = pcfg.allow_edit;
+        let pcfg = pcfg.clone();
         let existing = existing.clone();
+        let hunks = hunks.clone();
         let sem_cloned = sem.clone();
 
         futs.push(tokio::spawn(async move {
@@ -118,9 +144,9 @@ pub async fn publish_gitlab(
                 &head,
                 &base_sha,
                 start_sha_opt.as_deref(),
-                dry_run,
-                allow_edit,
+                &pcfg,
                 &existing,
+                &hunks,
             )
             .await
         }));
@@ -149,17 +175,13 @@ async fn publish_one(
     head_sha: &str,
     base_sha: &str,
     start_sha_opt: Option<&str>,
-    dry_run: bool,
-    _allow_edit: bool,
+    pcfg: &PublishConfig,
     existing: &HashSet<String>,
+    hunks: &HashMap<String, Vec<DiffHunk>>,
 ) -> MrResult<PublishedComment> {
     let (marker, key, _) = make_marker_and_key(draft);
-
-    let body = if draft.body_markdown.trim().is_empty() {
-        format!("Review note\n\n{}", marker)
-    } else {
-        format!("{}\n\n{}", draft.body_markdown.trim(), marker)
-    };
+    let body = render_comment_body(draft, &marker, pcfg);
+    let dry_run = pcfg.dry_run;
 
     // Idempotency: skip if key is present
     if existing.contains(&key) {
@@ -183,16 +205,20 @@ async fn publish_one(
                 id,
                 path,
                 *line,
+                None,
                 body,
                 head_sha,
                 base_sha,
                 start_sha_opt,
+                hunks.get(path.as_str()).map_or(&[][..], Vec::as_slice),
                 dry_run,
             )
             .await
         }
         TargetRef::Range {
-            path, start_line, ..
+            path,
+            start_line,
+            end_line,
         } => {
             publish_inline(
                 http,
@@ -201,10 +227,12 @@ async fn publish_one(
                 id,
                 path,
                 *start_line,
+                Some(*end_line),
                 body,
                 head_sha,
                 base_sha,
                 start_sha_opt,
+                hunks.get(path.as_str()).map_or(&[][..], Vec::as_slice),
                 dry_run,
             )
             .await
@@ -219,10 +247,12 @@ async fn publish_one(
                 id,
                 path,
                 *decl_line,
+                None,
                 body,
                 head_sha,
                 base_sha,
                 start_sha_opt,
+                hunks.get(path.as_str()).map_or(&[][..], Vec::as_slice),
                 dry_run,
             )
             .await
@@ -233,9 +263,140 @@ async fn publish_one(
     }
 }
 
+/// Inline position payload for the Discussions API.
+#[derive(Debug, serde::Serialize)]
+struct Position<'a> {
+    /// Must be "text" for textual diffs.
+    position_type: &'a str,
+    /// Old (base) side. Provide either the old_* pair or the new_* pair.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_line: Option<usize>,
+    /// New (head) side. Provide either the old_* pair or the new_* pair.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_path: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    new_line: Option<usize>,
+    /// MR diff refs.
+    head_sha: &'a str,
+    base_sha: &'a str,
+    /// Some GitLab versions require start_sha for valid positions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_sha: Option<&'a str>,
+    /// Multi-line highlight (new side); `new_line` then points at the range end.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_range: Option<LineRange>,
+}
+
+/// `position[line_range]` — inclusive start/end on the new side.
+#[derive(Debug, serde::Serialize)]
+struct LineRange {
+    start: LineRangeEnd,
+    end: LineRangeEnd,
+}
+
+/// One end of a `line_range`: `type` is `new` for added lines, else `old`.
+#[derive(Debug, serde::Serialize)]
+struct LineRangeEnd {
+    line_code: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    old_line: Option<usize>,
+    new_line: usize,
+}
+
+impl LineRangeEnd {
+    fn at(path: &str, new_line: usize, hunks: &[DiffHunk]) -> Self {
+        let (old, added) = old_position(hunks, new_line);
+        LineRangeEnd {
+            line_code: line_code(path, old, new_line),
+            kind: if added { "new" } else { "old" },
+            old_line: (!added).then_some(old),
+            new_line,
+        }
+    }
+}
+
+/// GitLab's diff line code: `<sha1(path)>_<old>_<new>`.
+fn line_code(path: &str, old_line: usize, new_line: usize) -> String {
+    let digest = sha1_smol::Sha1::from(path).digest();
+    format!("{digest}_{old_line}_{new_line}")
+}
+
+/// Old-side position GitLab assigns to new-side `line`, and whether the line
+/// was added. An added line takes the old line it was inserted before; lines
+/// outside hunks are shifted by the hunks above them.
+fn old_position(hunks: &[DiffHunk], line: usize) -> (usize, bool) {
+    let mut delta: i64 = 0;
+    for h in hunks {
+        // A zero-length side (`-5,0` / `+4,0`) sits after its start line.
+        if line < h.new_start as usize || (h.new_lines == 0 && line == h.new_start as usize) {
+            break;
+        }
+        let mut old = h.old_start as usize;
+        for l in &h.lines {
+            match l {
+                DiffLine::Added { new_line, .. } if *new_line as usize == line => {
+                    return (old, true);
+                }
+                DiffLine::Context {
+                    old_line, new_line, ..
+                } if *new_line as usize == line => return (*old_line as usize, false),
+                DiffLine::Context { old_line, .. } | DiffLine::Removed { old_line, .. } => {
+                    old = *old_line as usize + 1;
+                }
+                DiffLine::Added { .. } => {}
+            }
+        }
+        let old_end = h.old_start + h.old_lines.saturating_sub(1);
+        let new_end = h.new_start + h.new_lines.saturating_sub(1);
+        delta = i64::from(old_end) - i64::from(new_end);
+    }
+    ((line as i64 + delta).max(1) as usize, false)
+}
+
+/// Multi-line position covering new-side `start..=end` (1-based).
+fn range_position<'a>(
+    path: &'a str,
+    start: usize,
+    end: usize,
+    hunks: &[DiffHunk],
+    head_sha: &'a str,
+    base_sha: &'a str,
+    start_sha: Option<&'a str>,
+) -> Position<'a> {
+    Position {
+        position_type: "text",
+        old_path: None,
+        old_line: None,
+        new_path: Some(path),
+        new_line: Some(end),
+        head_sha,
+        base_sha,
+        start_sha,
+        line_range: Some(LineRange {
+            start: LineRangeEnd::at(path, start, hunks),
+            end: LineRangeEnd::at(path, end, hunks),
+        }),
+    }
+}
+
+/// Re-anchors ```` ```suggestion:-{span}+0 ```` blocks (counted up from a range's
+/// end line) to the start line as `-0+{span}`.
+fn suggestion_from_start_line(body: &str, span: usize) -> String {
+    body.replace(
+        &format!("```suggestion:-{span}+0\n"),
+        &format!("```suggestion:-0+{span}\n"),
+    )
+}
+
 /// Construct inline discussion and POST to GitLab with robust behavior.
 ///
 /// Strategy:
+/// 0) With `end_line` past `line`, attempt a multi-line `line_range` comment; on a
+///    validation error continue with the single-line strategy on `line`.
 /// 1) Attempt as `new_*` side (line exists in head).
 /// 2) If GitLab rejects with a line_code-like error, retry as `old_*` side (line exists in base).
 ///
@@ -248,60 +409,53 @@ async fn publish_inline(
     id: &ChangeRequestId,
     path: &str,
     line: usize,
+    end_line: Option<usize>,
     body: String,
     head_sha: &str,
     base_sha: &str,
     start_sha_opt: Option<&str>,
+    hunks: &[DiffHunk],
     dry_run: bool,
 ) -> MrResult<PublishedComment> {
     let url = format!(
         "{}/projects/{}/merge_requests/{}/discussions",
         base_api,
-        encode(&id.project),
+        project_segment(&id.project),
         id.iid
     );
 
-    #[derive(serde::Serialize)]
-    struct Position<'a> {
-        /// Must be "text" for textual diffs.
-        position_type: &'a str,
-        /// Old (base) side. Provide either the old_* pair or the new_* pair.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        old_path: Option<&'a str>,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        old_line: Option<usize>,
-        /// New (head) side. Provide either the old_* pair or the new_* pair.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        new_path: Option<&'a str>,
-        #[serde(skip_serializing_if = "Option::is_none")]
-        new_line: Option<usize>,
-        /// MR diff refs.
-        head_sha: &'a str,
-        base_sha: &'a str,
-        /// Some GitLab versions require start_sha for valid positions.
-        #[serde(skip_serializing_if = "Option::is_none")]
-        start_sha: Option<&'a str>,
-    }
-
     #[derive(serde::Serialize)]
     struct Req<'a> {
         body: &'a str,
         position: Position<'a>,
     }
 
+    #[derive(serde::Deserialize)]
+    struct DiscussionResp {
+        id: String,
+    }
+
     // GitLab expects 1-based line numbers.
     let line_1b = line.max(1);
+    let range_end = end_line.filter(|&end| end > line_1b);
 
     debug!(
-        "step5: inline POST path={} line={} (1b={}) dry_run={}",
-        path, line, line_1b, dry_run
+        "step5: inline POST path={} line={} (1b={}) end={:?} dry_run={}",
+        path, line, line_1b, range_end, dry_run
     );
 
     if dry_run {
         return Ok(PublishedComment {
-            target: TargetRef::Line {
-                path: path.to_string(),
-                line: line_1b,
+            target: match range_end {
+                Some(end) => TargetRef::Range {
+                    path: path.to_string(),
+                    start_line: line_1b,
+                    end_line: end,
+                },
+                None => TargetRef::Line {
+                    path: path.to_string(),
+                    line: line_1b,
+                },
             },
             performed: false,
             created_new: true,
@@ -310,6 +464,50 @@ async fn publish_inline(
         });
     }
 
+    // 0) Multi-line comment for ranges.
+    if let Some(end) = range_end {
+        let req_range = Req {
+            body: &body,
+            position: range_position(path, line_1b, end, hunks, head_sha, base_sha, start_sha_opt),
+        };
+        match post_with_retries(http, headers, &url, &req_range).await {
+            Ok(resp) => {
+                let disc: DiscussionResp = resp
+                    .json()
+                    .await
+                    .unwrap_or(DiscussionResp { id: String::new() });
+                return Ok(PublishedComment {
+                    target: TargetRef::Range {
+                        path: path.to_string(),
+                        start_line: line_1b,
+                        end_line: end,
+                    },
+                    performed: true,
+                    created_new: true,
+                    skipped_reason: None,
+                    provider_ids: Some(ProviderIds {
+                        discussion_id: Some(disc.id),
+                        note_id: None,
+                    }),
+                });
+            }
+            Err(Error::Validation(msg)) => {
+                warn!(
+                    "step5: line_range rejected, falling back to single line {}:{}: {}",
+                    path, line_1b, msg
+                );
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    // Single-line fallback of a range: suggestion offsets counted from the end
+    // line must now count down from the start line.
+    let body = match range_end {
+        Some(end) => suggestion_from_start_line(&body, end - line_1b),
+        None => body,
+    };
+
     // 1) Try as new_* side.
     let req_new = Req {
         body: &body,
@@ -322,15 +520,12 @@ async fn publish_inline(
             head_sha,
             base_sha,
             start_sha: start_sha_opt,
+            line_range: None,
         },
     };
 
     match post_with_retries(http, headers, &url, &req_new).await {
         Ok(resp) => {
-            #[derive(serde::Deserialize)]
-            struct DiscussionResp {
-                id: String,
-            }
             let disc: DiscussionResp = resp
                 .json()
                 .await
@@ -372,14 +567,11 @@ async fn publish_inline(
             head_sha,
             base_sha,
             start_sha: start_sha_opt,
+            line_range: None,
         },
     };
 
     let resp = post_with_retries(http, headers, &url, &req_old).await?;
-    #[derive(serde::Deserialize)]
-    struct DiscussionResp {
-        id: String,
-    }
     let disc: DiscussionResp = resp
         .json()
         .await
@@ -412,7 +604,7 @@ async fn publish_general(
     let url = format!(
         "{}/projects/{}/merge_requests/{}/notes",
         base_api,
-        encode(&id.project),
+        project_segment(&id.project),
         id.iid
     );
 
@@ -452,7 +644,7 @@ async fn publish_general(
     })
 }
 
-/// Load existing discussion bodies and extract mrai markers for idempotency.
+/// Load existing discussion bodies (all pages) and extract mrai markers for idempotency.
 async fn load_existing_markers_from_discussions(
     http: &reqwest::Client,
     headers: &HeaderMap,
@@ -462,7 +654,7 @@ async fn load_existing_markers_from_discussions(
     let url = format!(
         "{}/projects/{}/merge_requests/{}/discussions?per_page=100",
         base_api,
-        encode(&id.project),
+        project_segment(&id.project),
         id.iid
     );
     #[derive(serde::Deserialize)]
@@ -474,8 +666,7 @@ async fn load_existing_markers_from_discussions(
         notes: Vec<Note>,
     }
 
-    let resp = get_with_retries(http, headers, &url).await?;
-    let discussions: Vec<Discussion> = resp.json().await.unwrap_or_default();
+    let discussions: Vec<Discussion> = get_all_pages(http, headers, &url).await?;
     Ok(extract_markers_from_bodies(
         discussions
             .into_iter()
@@ -484,7 +675,7 @@ async fn load_existing_markers_from_discussions(
     ))
 }
 
-/// Load existing MR notes and extract mrai markers (complements discussions).
+/// Load existing MR notes (all pages) and extract mrai markers (complements discussions).
 async fn load_existing_markers_from_notes(
     http: &reqwest::Client,
     headers: &HeaderMap,
@@ -494,7 +685,7 @@ async fn load_existing_markers_from_notes(
     let url = format!(
         "{}/projects/{}/merge_requests/{}/notes?per_page=100",
         base_api,
-        encode(&id.project),
+        project_segment(&id.project),
         id.iid
     );
     #[derive(serde::Deserialize)]
@@ -502,13 +693,43 @@ async fn load_existing_markers_from_notes(
         body: Option<String>,
     }
 
-    let resp = get_with_retries(http, headers, &url).await?;
-    let notes: Vec<Note> = resp.json().await.unwrap_or_default();
+    let notes: Vec<Note> = get_all_pages(http, headers, &url).await?;
     Ok(extract_markers_from_bodies(
         notes.into_iter().filter_map(|n| n.body).collect(),
     ))
 }
 
+/// GET every page of a GitLab list endpoint and concatenate the items.
+///
+/// Follows `Link: <…>; rel="next"` when present (keyset pagination), otherwise
+/// `X-Next-Page` (offset pagination). Stops at [`MAX_LIST_PAGES`]; a page that
+/// fails to parse is treated as empty, like the single-page loaders did.
+async fn get_all_pages<T: serde::de::DeserializeOwned>(
+    http: &reqwest::Client,
+    headers: &HeaderMap,
+    first_url: &str,
+) -> MrResult<Vec<T>> {
+    let mut out = Vec::new();
+    let mut next = Some(first_url.to_string());
+    let mut pages = 0usize;
+    while let Some(url) = next.take() {
+        pages += 1;
+        let resp = get_with_retries(http, headers, &url).await?;
+        let following = next_page_url(&url, resp.headers());
+        let items: Vec<T> = resp.json().await.unwrap_or_default();
+        out.extend(items);
+        if pages >= MAX_LIST_PAGES {
+            if following.is_some() {
+                warn!("step5: stopping after {} pages of {}", pages, first_url);
+            }
+            break;
+        }
+        next = following;
+    }
+    debug!("step5: loaded {} item(s) in {} page(s)", out.len(), pages);
+    Ok(out)
+}
+
 /// Extract idempotency markers from a list of HTML/Markdown bodies.
 ///
 /// Marker format: `<!-- mrai:key=<key>;hash=<hex>;ver=<int> -->`
@@ -556,18 +777,21 @@ fn make_marker_and_key(d: &DraftComment) -> (String, String, Option<usize>) {
 }
 
 /// Build a tuned HTTP client with sane timeouts and pooling.
-fn build_http_client() -> MrResult<reqwest::Client> {
-    let client = reqwest::Client::builder()
+fn build_http_client(tls: &crate::git_providers::TlsConfig) -> MrResult<reqwest::Client> {
+    let builder = reqwest::Client::builder()
         .connect_timeout(Duration::from_secs(10))
         .timeout(Duration::from_secs(30))
         .pool_idle_timeout(Some(Duration::from_secs(90)))
-        .pool_max_idle_per_host(8)
+        .pool_max_idle_per_host(8);
+    let client = tls
+        .apply(services::http_proxy::apply_proxy_env(builder)?)?
         .build()?;
     Ok(client)
 }
 
-/// Build GitLab headers, including Private Token.
-fn build_gitlab_headers(token: &str) -> MrResult<HeaderMap> {
+/// Build GitLab headers, including Private Token; `extra` is applied last and
+/// replaces same-named headers.
+fn build_gitlab_headers(token: &str, extra: &HeaderMap) -> MrResult<HeaderMap> {
     let mut h = HeaderMap::new();
     h.insert(USER_AGENT, HeaderValue::from_static("mr-reviewer/1.0"));
     h.insert(ACCEPT, HeaderValue::from_static("application/json"));
@@ -576,6 +800,7 @@ fn build_gitlab_headers(token: &str) -> MrResult<HeaderMap> {
         "PRIVATE-TOKEN",
         HeaderValue::from_str(token).map_err(|e| Error::Validation(format!("bad token: {e}")))?,
     );
+    h.extend(extra.clone());
     Ok(h)
 }
 
@@ -598,7 +823,7 @@ async fn post_with_retries<T: serde::Serialize>(
     url: &str,
     body: &T,
 ) -> MrResult<reqwest::Response> {
-    request_with_retries(http, headers, |c| c.post(url).json(body)).await
+    request_with_retries(http, headers, url, |c| c.post(url).json(body)).await
 }
 
 /// GET with retries for transient failures.
@@ -607,16 +832,18 @@ async fn get_with_retries(
     headers: &HeaderMap,
     url: &str,
 ) -> MrResult<reqwest::Response> {
-    request_with_retries(http, headers, |c| c.get(url)).await
+    request_with_retries(http, headers, url, |c| c.get(url)).await
 }
 
 /// Shared retry helper for reqwest requests.
 ///
 /// Accepts a closure that builds a `RequestBuilder` (e.g., POST with JSON or GET),
 /// executes it with retries on 429/5xx, and returns the final `Response` on success.
+/// Every attempt waits for the host's shared rate limiter (see `rate_limit`).
 async fn request_with_retries(
     http: &reqwest::Client,
     headers: &HeaderMap,
+    url: &str,
     mut build: impl FnMut(&reqwest::Client) -> reqwest::RequestBuilder,
 ) -> MrResult<reqwest::Response> {
     let mut attempt = 0;
@@ -624,6 +851,7 @@ async fn request_with_retries(
 
     loop {
         attempt += 1;
+        rate_limit::acquire_for(url).await;
         let req = build(http).headers(headers.clone());
         let resp = req.send().await;
 
@@ -686,3 +914,269 @@ async fn request_with_retries(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `@@ -8,4 +8,6 @@`: line 9 removed, lines 10..=12 added.
+    fn hunk() -> DiffHunk {
+        let ctx = |old_line, new_line| DiffLine::Context {
+            old_line,
+            new_line,
+            content: String::new(),
+        };
+        let add = |new_line| DiffLine::Added {
+            new_line,
+            content: String::new(),
+        };
+        DiffHunk {
+            old_start: 8,
+            old_lines: 4,
+            new_start: 8,
+            new_lines: 6,
+            lines: vec![
+                ctx(8, 8),
+                DiffLine::Removed {
+                    old_line: 9,
+                    content: String::new(),
+                },
+                add(9),
+                add(10),
+                add(11),
+                ctx(10, 12),
+                ctx(11, 13),
+            ],
+        }
+    }
+
+    #[test]
+    fn range_position_includes_line_range() {
+        let hunks = [hunk()];
+        let pos = range_position("lib/a.dart", 10, 14, &hunks, "head", "base", Some("start"));
+        let v = serde_json::to_value(&pos).unwrap();
+
+        assert_eq!(v["new_path"], "lib/a.dart");
+        assert_eq!(v["new_line"], 14);
+        assert_eq!(v["line_range"]["start"]["type"], "new");
+        assert_eq!(v["line_range"]["start"]["new_line"], 10);
+        assert_eq!(v["line_range"]["end"]["new_line"], 14);
+        assert!(v.get("old_line").is_none());
+    }
+
+    #[test]
+    fn line_range_matches_gitlab_payload_shape() {
+        let hunks = [hunk()];
+        let pos = range_position("lib/a.dart", 10, 14, &hunks, "head", "base", Some("start"));
+        let v = serde_json::to_value(&pos).unwrap();
+        let sha = "b1a3f9fd4347059a1b69010644664db2f419b316";
+        assert_eq!(line_code("lib/a.dart", 1, 1), format!("{sha}_1_1"));
+
+        // Added start: `type: new`, no old_line, code uses the insertion point.
+        assert_eq!(
+            v["line_range"]["start"],
+            serde_json::json!({
+                "line_code": format!("{sha}_10_10"),
+                "type": "new",
+                "new_line": 10,
+            })
+        );
+        // Unchanged end below the hunk: shifted by the two extra lines.
+        assert_eq!(
+            v["line_range"]["end"],
+            serde_json::json!({
+                "line_code": format!("{sha}_12_14"),
+                "type": "old",
+                "old_line": 12,
+                "new_line": 14,
+            })
+        );
+    }
+
+    #[test]
+    fn old_positions_follow_hunks() {
+        let hunks = [hunk()];
+        assert_eq!(old_position(&hunks, 5), (5, false));
+        assert_eq!(old_position(&hunks, 8), (8, false));
+        assert_eq!(old_position(&hunks, 9), (10, true));
+        assert_eq!(old_position(&hunks, 12), (10, false));
+        assert_eq!(old_position(&hunks, 20), (18, false));
+        // Without a diff old positions mirror new ones.
+        assert_eq!(old_position(&[], 7), (7, false));
+
+        let insert = DiffHunk {
+            old_start: 5,
+            old_lines: 0,
+            new_start: 6,
+            new_lines: 2,
+            lines: vec![
+                DiffLine::Added {
+                    new_line: 6,
+                    content: String::new(),
+                },
+                DiffLine::Added {
+                    new_line: 7,
+                    content: String::new(),
+                },
+            ],
+        };
+        assert_eq!(old_position(std::slice::from_ref(&insert), 5), (5, false));
+        assert_eq!(old_position(&[insert], 8), (6, false));
+    }
+
+    #[test]
+    fn single_line_fallback_moves_suggestion_to_start_line() {
+        let body = "**t**\n\n```suggestion:-2+0\nif (a) run();\n```\n";
+        assert_eq!(
+            suggestion_from_start_line(body, 2),
+            "**t**\n\n```suggestion:-0+2\nif (a) run();\n```\n"
+        );
+        assert_eq!(suggestion_from_start_line(body, 3), body);
+    }
+
+    /// Serves canned GitLab list pages; `X-Next-Page` links page 1 to page 2.
+    async fn mock_gitlab() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut sock, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut buf = vec![0u8; 8192];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let req = String::from_utf8_lossy(&buf[..n]);
+                let target = req.split_whitespace().nth(1).unwrap_or("").to_string();
+                let (next, body) = if target.contains("/notes") {
+                    ("", "[]")
+                } else if target.contains("page=2") {
+                    (
+                        "",
+                        r#"[{"notes":[{"body":"old\n\n<!-- mrai:key=lib/a.dart:10|line;hash=abc;ver=1 -->"}]}]"#,
+                    )
+                } else {
+                    ("2", r#"[{"notes":[{"body":"unrelated"}]}]"#)
+                };
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nX-Next-Page: {next}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = sock.write_all(resp.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}/api/v4")
+    }
+
+    #[tokio::test]
+    async fn marker_on_second_page_is_detected() {
+        let base = mock_gitlab().await;
+        let cfg = crate::git_providers::ProviderConfig::new(
+            Some(crate::git_providers::ProviderKind::GitLab),
+            base,
+            "t".into(),
+            Default::default(),
+        )
+        .unwrap();
+        let id = ChangeRequestId {
+            project: "g/app".into(),
+            iid: 7,
+        };
+        let refs = DiffRefs {
+            base_sha: "b".into(),
+            start_sha: None,
+            head_sha: "h".into(),
+        };
+        let draft = |line: usize| DraftComment {
+            target: TargetRef::Line {
+                path: "lib/a.dart".into(),
+                line,
+            },
+            snippet_hash: "abc".into(),
+            body_markdown: "body".into(),
+            severity: crate::review::policy::Severity::Medium,
+            confidence: 0.8,
+            preview: String::new(),
+        };
+        let pcfg = PublishConfig {
+            dry_run: true,
+            allow_edit: false,
+            max_concurrency: 1,
+            show_confidence: false,
+            max_comments: 0,
+            reanchor_on_head_move: false,
+            comment_prefix: None,
+            comment_footer: None,
+        };
+
+        let out = publish_gitlab(&cfg, &id, &refs, &[draft(10), draft(11)], None, &pcfg)
+            .await
+            .unwrap();
+        assert_eq!(out[0].skipped_reason.as_deref(), Some("duplicate"));
+        assert_ne!(out[1].skipped_reason.as_deref(), Some("duplicate"));
+    }
+
+    #[test]
+    fn branding_wraps_content_and_keeps_marker_detectable() {
+        let draft = DraftComment {
+            target: TargetRef::Line {
+                path: "lib/a.dart".into(),
+                line: 10,
+            },
+            snippet_hash: "abc".into(),
+            body_markdown: "Null check missing.".into(),
+            severity: crate::review::policy::Severity::Medium,
+            confidence: 0.8,
+            preview: String::new(),
+        };
+        let pcfg = PublishConfig {
+            dry_run: true,
+            allow_edit: false,
+            max_concurrency: 1,
+            show_confidence: false,
+            max_comments: 0,
+            reanchor_on_head_move: false,
+            comment_prefix: Some("🤖 mr-ai:".into()),
+            comment_footer: Some("[Feedback](https://x/f) <!-- mrai:key=x;hash=0;ver=1 -->".into()),
+        };
+        let (marker, key, _) = make_marker_and_key(&draft);
+        let body = render_comment_body(&draft, &marker, &pcfg);
+
+        let at = |s: &str| {
+            body.find(s)
+                .unwrap_or_else(|| panic!("{s} missing: {body}"))
+        };
+        assert!(at("🤖 mr-ai:") < at("Null check missing."));
+        assert!(at("Null check missing.") < at("[Feedback]"));
+        assert!(at("[Feedback]") < at(&marker));
+        assert!(body.ends_with(&marker));
+
+        let found = extract_markers_from_bodies(vec![body]);
+        assert_eq!(found, HashSet::from([key]));
+    }
+
+    #[test]
+    fn next_page_prefers_link_header_then_x_next_page() {
+        let url = "https://gl/api/v4/projects/1/merge_requests/2/notes?per_page=100&page=1";
+        let mut h = HeaderMap::new();
+        assert_eq!(next_page_url(url, &h), None);
+
+        h.insert("x-next-page", HeaderValue::from_static("3"));
+        assert_eq!(
+            next_page_url(url, &h).as_deref(),
+            Some("https://gl/api/v4/projects/1/merge_requests/2/notes?per_page=100&page=3")
+        );
+
+        h.insert(
+            reqwest::header::LINK,
+            HeaderValue::from_static(
+                "<https://gl/a?id_after=9>; rel=\"next\", <https://gl/a?id_after=0>; rel=\"first\"",
+            ),
+        );
+        assert_eq!(
+            next_page_url(url, &h).as_deref(),
+            Some("https://gl/a?id_after=9")
+        );
+    }
+}
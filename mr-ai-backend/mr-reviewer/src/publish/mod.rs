@@ -5,6 +5,8 @@
 //! - GitLab: inline discussions for text diffs, or MR notes for file/global.
 //! - Idempotency: embeds a hidden marker in the body and skips duplicates.
 //! - Dry-run: compute and log actions without actually calling the API.
+//! - Head revalidation: refuses to post against a stale `head_sha`, or
+//!   re-anchors drafts to the new head when configured (see [`revalidate`]).
 //! - No async-trait, no Box<dyn ...>; uses plain async fn + enum dispatch.
 //!
 //! Notable improvements:
@@ -13,6 +15,7 @@
 //! - Richer docs and small quality-of-life logging.
 
 pub mod gitlab;
+pub mod revalidate;
 
 use std::time::Instant;
 
@@ -20,6 +23,7 @@ use crate::errors::{Error, MrResult};
 use crate::git_providers::{ChangeRequestId, ProviderConfig, ProviderKind};
 use crate::map::TargetRef;
 use crate::review::DraftComment;
+use crate::review::policy::Severity;
 use tracing::{debug, info};
 
 /// Configuration for publishing step.
@@ -32,6 +36,19 @@ pub struct PublishConfig {
     pub allow_edit: bool,
     /// Concurrency for posting/editing requests.
     pub max_concurrency: usize,
+    /// If true, append a small "(confidence: 0.70)" footer to each comment body.
+    pub show_confidence: bool,
+    /// Upper bound on published comments (0 = unlimited). When exceeded, drafts
+    /// are ranked by severity, then confidence, and only the top ones are posted.
+    pub max_comments: usize,
+    /// If the MR head moved since planning, move drafts to matching lines of
+    /// the new head instead of failing with `Error::HeadMoved`.
+    pub reanchor_on_head_move: bool,
+    /// Label put in front of every comment body, e.g. `🤖 mr-ai:`.
+    pub comment_prefix: Option<String>,
+    /// Markdown appended to every comment body (above the hidden marker),
+    /// e.g. a link to docs or a feedback form.
+    pub comment_footer: Option<String>,
 }
 
 impl Default for PublishConfig {
@@ -41,15 +58,30 @@ impl Default for PublishConfig {
     /// - `MR_REVIEWER_PUBLISH_DRY_RUN` (default: **false**)
     /// - `MR_REVIEWER_PUBLISH_EDIT` (default: false)
     /// - `MR_REVIEWER_PUBLISH_CONCURRENCY` (default: 2)
+    /// - `MR_REVIEWER_PUBLISH_SHOW_CONFIDENCE` (default: false)
+    /// - `MR_REVIEWER_PUBLISH_MAX_COMMENTS` (default: 0 = unlimited)
+    /// - `MR_REVIEWER_PUBLISH_REANCHOR_ON_HEAD_MOVE` (default: false)
+    /// - `MR_REVIEWER_PUBLISH_COMMENT_PREFIX`, `MR_REVIEWER_PUBLISH_COMMENT_FOOTER`
+    ///   (default: none; blank means none)
     fn default() -> Self {
         Self {
             dry_run: env_bool("MR_REVIEWER_PUBLISH_DRY_RUN", false),
             allow_edit: env_bool("MR_REVIEWER_PUBLISH_EDIT", false),
             max_concurrency: env_usize("MR_REVIEWER_PUBLISH_CONCURRENCY", 2),
+            show_confidence: env_bool("MR_REVIEWER_PUBLISH_SHOW_CONFIDENCE", false),
+            max_comments: env_usize("MR_REVIEWER_PUBLISH_MAX_COMMENTS", 0),
+            reanchor_on_head_move: env_bool("MR_REVIEWER_PUBLISH_REANCHOR_ON_HEAD_MOVE", false),
+            comment_prefix: env_text("MR_REVIEWER_PUBLISH_COMMENT_PREFIX"),
+            comment_footer: env_text("MR_REVIEWER_PUBLISH_COMMENT_FOOTER"),
         }
     }
 }
 
+/// Non-blank env value.
+fn env_text(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
+
 fn env_bool(key: &str, default: bool) -> bool {
     std::env::var(key)
         .ok()
@@ -85,9 +117,71 @@ pub struct ProviderIds {
     pub note_id: Option<u64>,
 }
 
+/// Rank drafts by severity, then confidence, and keep the top `max_comments`
+/// (0 = keep all). Original order is preserved among the kept drafts.
+fn select_for_publish(drafts: &[DraftComment], max_comments: usize) -> Vec<DraftComment> {
+    if max_comments == 0 || drafts.len() <= max_comments {
+        return drafts.to_vec();
+    }
+    let rank = |s: Severity| match s {
+        Severity::High => 3,
+        Severity::Medium => 2,
+        Severity::Low => 1,
+    };
+    let mut order: Vec<usize> = (0..drafts.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (da, db) = (&drafts[a], &drafts[b]);
+        rank(db.severity)
+            .cmp(&rank(da.severity))
+            .then(db.confidence.total_cmp(&da.confidence))
+    });
+    let mut keep: Vec<usize> = order.into_iter().take(max_comments).collect();
+    keep.sort_unstable();
+    keep.into_iter().map(|i| drafts[i].clone()).collect()
+}
+
+/// Render the optional confidence footer appended to published bodies.
+pub(crate) fn confidence_footer(d: &DraftComment) -> String {
+    format!("<sub>(confidence: {:.2})</sub>", d.confidence)
+}
+
+/// Full comment body: `prefix content`, confidence, footer, then `marker`.
+///
+/// The marker stays last and HTML comments are neutralized in the configured
+/// prefix/footer, so branding can't inject or shadow an idempotency marker.
+pub(crate) fn render_comment_body(d: &DraftComment, marker: &str, cfg: &PublishConfig) -> String {
+    let content = d.body_markdown.trim();
+    let mut text = if content.is_empty() {
+        "Review note".to_string()
+    } else {
+        content.to_string()
+    };
+    if let Some(prefix) = cfg.comment_prefix.as_deref().map(branding_text) {
+        text = format!("{prefix} {text}");
+    }
+    if cfg.show_confidence {
+        text.push_str("\n\n");
+        text.push_str(&confidence_footer(d));
+    }
+    if let Some(footer) = cfg.comment_footer.as_deref().map(branding_text) {
+        text.push_str("\n\n");
+        text.push_str(&footer);
+    }
+    format!("{text}\n\n{marker}")
+}
+
+/// Configured branding text with HTML comment openers escaped.
+fn branding_text(s: &str) -> String {
+    s.trim().replace("<!--", "&lt;!--")
+}
+
 /// Publish all drafts for given MR/PR.
 ///
+/// The MR head is revalidated first; see [`revalidate::revalidate_head`].
 /// Returns per-draft results and logs summary (`INFO`).
+///
+/// # Errors
+/// [`Error::HeadMoved`] if the head moved and `cfg.reanchor_on_head_move` is off.
 pub async fn publish(
     provider_cfg: &ProviderConfig,
     id: &ChangeRequestId,
@@ -103,9 +197,38 @@ pub async fn publish(
         cfg.dry_run
     );
 
-    let results = match provider_cfg.kind {
+    let selected = select_for_publish(drafts, cfg.max_comments);
+    if selected.len() < drafts.len() {
+        info!(
+            "step5: max_comments={} → publishing top {} of {} drafts",
+            cfg.max_comments,
+            selected.len(),
+            drafts.len()
+        );
+    }
+
+    let (results, dropped) = match provider_cfg.kind {
         ProviderKind::GitLab => {
-            gitlab::publish_gitlab(provider_cfg, id, plan, drafts, &cfg).await?
+            let checked = revalidate::revalidate_head(
+                provider_cfg,
+                id,
+                &plan.bundle.meta.diff_refs,
+                &selected,
+                cfg.reanchor_on_head_move,
+            )
+            .await?;
+            // The planned diff only describes the head it was fetched at.
+            let changes = checked.changes.as_ref().unwrap_or(&plan.bundle.changes);
+            let results = gitlab::publish_gitlab(
+                provider_cfg,
+                id,
+                &checked.diff_refs,
+                &checked.drafts,
+                Some(changes),
+                &cfg,
+            )
+            .await?;
+            (results, checked.dropped)
         }
         // You can implement for GitHub/Bitbucket later:
         _ => {
@@ -128,12 +251,14 @@ pub async fn publish(
         .iter()
         .filter(|r| r.skipped_reason.is_some())
         .count();
+    metrics::counter!("comments_posted_total").increment(created as u64);
 
     info!(
-        "step5: publish done created={} edited={} skipped={} in {} ms",
+        "step5: publish done created={} edited={} skipped={} dropped_on_head_move={} in {} ms",
         created,
         edited,
         skipped,
+        dropped,
         t0.elapsed().as_millis()
     );
 
@@ -144,3 +269,70 @@ pub async fn publish(
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(line: usize, severity: Severity, confidence: f32) -> DraftComment {
+        DraftComment {
+            target: TargetRef::Line {
+                path: "lib/a.dart".into(),
+                line,
+            },
+            snippet_hash: format!("h{line}"),
+            body_markdown: "Null check missing.".into(),
+            severity,
+            confidence,
+            preview: String::new(),
+        }
+    }
+
+    fn pcfg(show_confidence: bool) -> PublishConfig {
+        PublishConfig {
+            dry_run: true,
+            allow_edit: false,
+            max_concurrency: 1,
+            show_confidence,
+            max_comments: 0,
+            reanchor_on_head_move: false,
+            comment_prefix: None,
+            comment_footer: None,
+        }
+    }
+
+    #[test]
+    fn max_comments_ranks_by_severity_then_confidence() {
+        let drafts = [
+            draft(1, Severity::Low, 0.9),
+            draft(2, Severity::Medium, 0.4),
+            draft(3, Severity::High, 0.5),
+            draft(4, Severity::Medium, 0.8),
+        ];
+
+        let kept = select_for_publish(&drafts, 2);
+        let lines: Vec<usize> = kept
+            .iter()
+            .map(|d| match d.target {
+                TargetRef::Line { line, .. } => line,
+                _ => unreachable!(),
+            })
+            .collect();
+        // High first, then the more confident Medium; original order is kept.
+        assert_eq!(lines, vec![3, 4]);
+        assert_eq!(select_for_publish(&drafts, 0).len(), 4);
+    }
+
+    #[test]
+    fn confidence_footer_is_rendered_only_when_enabled() {
+        let d = draft(1, Severity::Medium, 0.7);
+
+        let shown = render_comment_body(&d, "<!-- m -->", &pcfg(true));
+        assert_eq!(
+            shown,
+            "Null check missing.\n\n<sub>(confidence: 0.70)</sub>\n\n<!-- m -->"
+        );
+        let hidden = render_comment_body(&d, "<!-- m -->", &pcfg(false));
+        assert!(!hidden.contains("confidence"), "{hidden}");
+    }
+}
@@ -0,0 +1,408 @@
+//! Pre-publish head revalidation.
+//!
+//! Inline positions are bound to the diff refs captured in step 1. If the
+//! source branch was pushed to while steps 2–4 ran, posting against the old
+//! `head_sha` either gets rejected or lands on a stale diff. Before publishing
+//! we re-fetch MR meta and compare heads:
+//!
+//! - unchanged → publish as planned;
+//! - moved, re-anchoring disabled → [`Error::HeadMoved`];
+//! - moved, re-anchoring enabled → each inline draft is moved to the line with
+//!   the same text in the new head (nearest match, ties broken by surrounding
+//!   lines). Drafts whose lines are gone are dropped: the code they commented
+//!   on no longer exists. The diff at the new head is fetched too, so line
+//!   codes of re-anchored context lines get their new old-side numbers.
+
+use std::collections::HashMap;
+
+use tracing::{debug, info, warn};
+
+use crate::errors::{Error, MrResult};
+use crate::git_providers::{ChangeRequestId, ChangeSet, DiffRefs, ProviderClient, ProviderConfig};
+use crate::map::TargetRef;
+use crate::review::DraftComment;
+
+/// Lines on each side compared when several candidates match.
+const CONTEXT_LINES: usize = 2;
+
+/// Diff refs and drafts to publish after revalidation.
+#[derive(Debug, Clone)]
+pub struct Revalidated {
+    pub diff_refs: DiffRefs,
+    pub drafts: Vec<DraftComment>,
+    /// Drafts dropped because their lines no longer exist at the new head.
+    pub dropped: usize,
+    /// Diff at the new head when it moved; `None` when the planned diff
+    /// still applies.
+    pub changes: Option<ChangeSet>,
+}
+
+/// Confirm the MR head still matches `planned`, re-anchoring drafts if allowed.
+///
+/// # Errors
+/// [`Error::HeadMoved`] when the head moved and `reanchor` is false; provider
+/// errors from fetching meta or files.
+pub async fn revalidate_head(
+    provider_cfg: &ProviderConfig,
+    id: &ChangeRequestId,
+    planned: &DiffRefs,
+    drafts: &[DraftComment],
+    reanchor: bool,
+) -> MrResult<Revalidated> {
+    let client = ProviderClient::from_config(provider_cfg.clone())?;
+    let current = client.fetch_meta(id).await?.diff_refs;
+
+    if current.head_sha == planned.head_sha {
+        debug!("step5: head unchanged ({})", short(&planned.head_sha));
+        return Ok(Revalidated {
+            diff_refs: planned.clone(),
+            drafts: drafts.to_vec(),
+            dropped: 0,
+            changes: None,
+        });
+    }
+
+    if !reanchor {
+        return Err(Error::HeadMoved {
+            expected: planned.head_sha.clone(),
+            actual: current.head_sha,
+        });
+    }
+
+    info!(
+        "step5: head moved {} → {}, re-anchoring {} draft(s)",
+        short(&planned.head_sha),
+        short(&current.head_sha),
+        drafts.len()
+    );
+
+    // (old, new) text per path; `None` when the file is gone at either head.
+    let mut files: HashMap<String, Option<(String, String)>> = HashMap::new();
+    let mut out = Vec::with_capacity(drafts.len());
+    for d in drafts {
+        let Some(path) = inline_path(&d.target) else {
+            out.push(d.clone());
+            continue;
+        };
+        if !files.contains_key(path) {
+            let old = client
+                .fetch_file_raw_at_ref(id, path, &planned.head_sha)
+                .await?;
+            let new = client
+                .fetch_file_raw_at_ref(id, path, &current.head_sha)
+                .await?;
+            let pair = old.zip(new).map(|(o, n)| {
+                (
+                    String::from_utf8_lossy(&o).into_owned(),
+                    String::from_utf8_lossy(&n).into_owned(),
+                )
+            });
+            files.insert(path.to_string(), pair);
+        }
+        let moved = files[path]
+            .as_ref()
+            .and_then(|(old, new)| reanchor_draft(d, old, new));
+        match moved {
+            Some(m) => out.push(m),
+            None => warn!(
+                "step5: drop draft {:?}: anchored lines not found at new head",
+                d.target
+            ),
+        }
+    }
+
+    let dropped = drafts.len() - out.len();
+    info!("step5: re-anchored kept={} dropped={}", out.len(), dropped);
+    let changes = client.fetch_changes(id).await?;
+    Ok(Revalidated {
+        diff_refs: current,
+        drafts: out,
+        dropped,
+        changes: Some(changes),
+    })
+}
+
+/// Path of an inline target; file/global notes are not bound to lines.
+fn inline_path(t: &TargetRef) -> Option<&str> {
+    match t {
+        TargetRef::Line { path, .. }
+        | TargetRef::Range { path, .. }
+        | TargetRef::Symbol { path, .. } => Some(path),
+        TargetRef::File { .. } | TargetRef::Global => None,
+    }
+}
+
+/// Move `d` from `old` to `new` file text. A range whose length changed
+/// collapses to a single line at its new start.
+fn reanchor_draft(d: &DraftComment, old: &str, new: &str) -> Option<DraftComment> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let target = match &d.target {
+        TargetRef::Line { path, line } => TargetRef::Line {
+            path: path.clone(),
+            line: reanchor_line(&old, &new, *line)?,
+        },
+        TargetRef::Range {
+            path,
+            start_line,
+            end_line,
+        } => {
+            let start = reanchor_line(&old, &new, *start_line)?;
+            match reanchor_line(&old, &new, *end_line) {
+                Some(end) if end.checked_sub(start) == end_line.checked_sub(*start_line) => {
+                    TargetRef::Range {
+                        path: path.clone(),
+                        start_line: start,
+                        end_line: end,
+                    }
+                }
+                _ => TargetRef::Line {
+                    path: path.clone(),
+                    line: start,
+                },
+            }
+        }
+        TargetRef::Symbol {
+            path,
+            symbol_id,
+            decl_line,
+        } => TargetRef::Symbol {
+            path: path.clone(),
+            symbol_id: symbol_id.clone(),
+            decl_line: reanchor_line(&old, &new, *decl_line)?,
+        },
+        TargetRef::File { .. } | TargetRef::Global => d.target.clone(),
+    };
+    Some(DraftComment {
+        target,
+        ..d.clone()
+    })
+}
+
+/// New 1-based position of `old[line - 1]` in `new`.
+///
+/// Candidates are lines with identical text (trailing whitespace ignored);
+/// the one with the most matching neighbours wins, then the one nearest to the
+/// original position. Blank lines are too ambiguous and never re-anchor.
+fn reanchor_line(old: &[&str], new: &[&str], line: usize) -> Option<usize> {
+    let idx = line.checked_sub(1)?;
+    let text = old.get(idx)?.trim_end();
+    if text.trim().is_empty() {
+        return None;
+    }
+    new.iter()
+        .enumerate()
+        .filter(|(_, l)| l.trim_end() == text)
+        .map(|(j, _)| {
+            let n = CONTEXT_LINES as isize;
+            let matches = (-n..=n)
+                .filter(|&off| off != 0)
+                .filter(|&off| {
+                    let o = context(old, idx, off);
+                    o.is_some() && o == context(new, j, off)
+                })
+                .count();
+            (j, matches, j.abs_diff(idx))
+        })
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)))
+        .map(|(j, _, _)| j + 1)
+}
+
+/// Line at `at + off` (trailing whitespace trimmed), if in bounds.
+fn context<'a>(lines: &[&'a str], at: usize, off: isize) -> Option<&'a str> {
+    at.checked_add_signed(off)
+        .and_then(|i| lines.get(i))
+        .map(|l| l.trim_end())
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::policy::Severity;
+
+    fn draft(target: TargetRef) -> DraftComment {
+        DraftComment {
+            target,
+            snippet_hash: "h".into(),
+            body_markdown: "body".into(),
+            severity: Severity::Medium,
+            confidence: 0.5,
+            preview: String::new(),
+        }
+    }
+
+    #[test]
+    fn drafts_follow_their_lines_to_the_new_head() {
+        let old = "fn a() {\n    let x = 1;\n    call(x);\n}\n\nfn b() {\n    call(x);\n}\n";
+        // Two lines inserted at the top; `fn a` body otherwise unchanged.
+        let new = format!("use std::io;\n\n{old}");
+
+        let line = draft(TargetRef::Line {
+            path: "src/a.rs".into(),
+            line: 3,
+        });
+        let moved = reanchor_draft(&line, old, &new).unwrap();
+        assert_eq!(
+            moved.target,
+            TargetRef::Line {
+                path: "src/a.rs".into(),
+                line: 5
+            }
+        );
+
+        // `call(x);` also appears in `fn b`; neighbours pick the right one.
+        let other = draft(TargetRef::Line {
+            path: "src/a.rs".into(),
+            line: 7,
+        });
+        let moved = reanchor_draft(&other, old, &new).unwrap();
+        assert!(matches!(moved.target, TargetRef::Line { line: 9, .. }));
+
+        let range = draft(TargetRef::Range {
+            path: "src/a.rs".into(),
+            start_line: 2,
+            end_line: 3,
+        });
+        let moved = reanchor_draft(&range, old, &new).unwrap();
+        assert!(matches!(
+            moved.target,
+            TargetRef::Range {
+                start_line: 4,
+                end_line: 5,
+                ..
+            }
+        ));
+        assert_eq!(moved.body_markdown, "body");
+
+        // The commented line was removed by the new push.
+        let fixed = old.replace("    let x = 1;\n", "");
+        assert!(reanchor_draft(&range, old, &fixed).is_none());
+    }
+
+    const OLD_A: &str = "fn a() {\n    let x = 1;\n    call(x);\n}\n";
+    const NEW_A: &str = "use std::io;\n\nfn a() {\n    call(x);\n}\n";
+
+    /// GitLab stand-in whose MR head is now `h2` (`src/a.rs` was `OLD_A` at `h1`).
+    async fn mock_moved_head() -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut sock, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut buf = vec![0u8; 8192];
+                let n = sock.read(&mut buf).await.unwrap_or(0);
+                let req = String::from_utf8_lossy(&buf[..n]);
+                let target = req.split_whitespace().nth(1).unwrap_or("").to_string();
+                let body = if target.contains("/repository/files/") {
+                    if target.contains("ref=h1") {
+                        OLD_A
+                    } else {
+                        NEW_A
+                    }
+                    .to_string()
+                } else if target.ends_with("/diffs") {
+                    serde_json::json!([{
+                        "old_path": "src/a.rs", "new_path": "src/a.rs",
+                        "new_file": false, "renamed_file": false, "deleted_file": false,
+                        "diff": "@@ -1,2 +1,3 @@\n+use std::io;\n+\n fn a() {\n-    let x = 1;\n"
+                    }])
+                    .to_string()
+                } else {
+                    serde_json::json!({
+                        "title": "t", "description": null, "web_url": "", "state": "opened",
+                        "created_at": "2025-01-01T00:00:00Z", "updated_at": "2025-01-01T00:00:00Z",
+                        "source_branch": "f", "target_branch": "main", "sha": "h2",
+                        "diff_refs": { "base_sha": "b", "start_sha": "b", "head_sha": "h2" },
+                        "author": { "id": 1, "username": "dev", "name": "Dev", "web_url": null, "avatar_url": null }
+                    })
+                    .to_string()
+                };
+                let resp = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = sock.write_all(resp.as_bytes()).await;
+            }
+        });
+        format!("http://{addr}/api/v4")
+    }
+
+    fn moved_head_setup(base: String) -> (ProviderConfig, ChangeRequestId) {
+        let cfg = ProviderConfig::new(
+            Some(crate::git_providers::ProviderKind::GitLab),
+            base,
+            "t".into(),
+            Default::default(),
+        )
+        .unwrap();
+        let id = ChangeRequestId {
+            project: "g/app".into(),
+            iid: 7,
+        };
+        (cfg, id)
+    }
+
+    fn refs(head: &str) -> DiffRefs {
+        DiffRefs {
+            base_sha: "b".into(),
+            start_sha: Some("b".into()),
+            head_sha: head.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn moved_head_is_rejected_unless_reanchoring() {
+        let (cfg, id) = moved_head_setup(mock_moved_head().await);
+        let drafts = [draft(TargetRef::Line {
+            path: "src/a.rs".into(),
+            line: 3,
+        })];
+
+        let err = revalidate_head(&cfg, &id, &refs("h1"), &drafts, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            Error::HeadMoved { ref expected, ref actual } if expected == "h1" && actual == "h2"
+        ));
+
+        let same = revalidate_head(&cfg, &id, &refs("h2"), &drafts, false)
+            .await
+            .unwrap();
+        assert_eq!(same.drafts.len(), 1);
+        assert!(same.changes.is_none());
+    }
+
+    #[tokio::test]
+    async fn reanchoring_drops_vanished_lines_and_fetches_the_new_diff() {
+        let (cfg, id) = moved_head_setup(mock_moved_head().await);
+        let line = |line| {
+            draft(TargetRef::Line {
+                path: "src/a.rs".into(),
+                line,
+            })
+        };
+
+        // Line 2 (`let x = 1;`) is gone at h2; line 3 (`call(x);`) moved to 4.
+        let out = revalidate_head(&cfg, &id, &refs("h1"), &[line(2), line(3)], true)
+            .await
+            .unwrap();
+        assert_eq!(out.diff_refs.head_sha, "h2");
+        assert_eq!(out.dropped, 1);
+        assert_eq!(out.drafts.len(), 1);
+        assert!(matches!(
+            out.drafts[0].target,
+            TargetRef::Line { line: 4, .. }
+        ));
+        let changes = out.changes.expect("diff at the new head");
+        assert_eq!(changes.files[0].hunks.len(), 1);
+    }
+}
@@ -0,0 +1,321 @@
+//! Per-repository review configuration (`.mrai.toml`).
+//!
+//! Teams can commit a `.mrai.toml` at the repository root to tune the reviewer
+//! for their project. The file is fetched at the target (`base_sha`) during
+//! step 1, so a change request cannot relax its own review by editing it.
+//!
+//! Precedence (highest first):
+//! 1) `.mrai.toml` in the repository (at `base_sha`),
+//! 2) server environment variables,
+//! 3) built-in defaults.
+//!
+//! Example:
+//! ```toml
+//! min_severity = "Medium"
+//! ignore = ["**/*.g.dart", "generated/**"]
+//! focus = "security and error handling"
+//! languages = ["dart", "kotlin"]
+//! ```
+//!
+//! Unknown keys are logged as warnings and otherwise ignored; a malformed file
+//! falls back to env/defaults instead of failing the review.
+//!
+//! `focus` names such as `security` join the run's [`ReviewFocus`] list
+//! (instructions and severity floors, see [`crate::review::focus`]); any
+//! other wording is quoted to the model as free-form guidance.
+
+use std::sync::OnceLock;
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use tracing::{debug, info, warn};
+
+use crate::errors::MrResult;
+use crate::git_providers::{ChangeRequestId, ProviderClient};
+use crate::review::focus::ReviewFocus;
+use crate::review::policy::Severity;
+
+/// Repository-relative path of the config file.
+pub const REPO_CONFIG_PATH: &str = ".mrai.toml";
+
+/// Keys accepted in `.mrai.toml`.
+const KNOWN_KEYS: &[&str] = &["min_severity", "ignore", "focus", "languages"];
+
+/// Review knobs that can be set per repository.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RepoReviewConfig {
+    /// Minimum severity to keep (`"High" | "Medium" | "Low"`).
+    pub min_severity: Option<String>,
+    /// Glob patterns of repo-relative paths to skip.
+    pub ignore: Vec<String>,
+    /// Free-form review focus appended to prompts (e.g. "security").
+    pub focus: Option<String>,
+    /// Languages to review (e.g. `["dart", "rust"]`); empty = all.
+    pub languages: Vec<String>,
+    /// `ignore` compiled on first use; reset whenever `ignore` is replaced.
+    #[serde(skip)]
+    ignore_set: OnceLock<Option<GlobSet>>,
+}
+
+impl RepoReviewConfig {
+    /// Loads server-side defaults from environment variables.
+    ///
+    /// - `MR_REVIEWER_MIN_SEVERITY` (default: unset → keep all)
+    /// - `MR_REVIEWER_IGNORE_GLOBS` (comma-separated; default: empty)
+    /// - `MR_REVIEWER_LANGUAGES` (comma-separated; default: empty → all)
+    ///
+    /// The server-wide focus (`MR_REVIEWER_FOCUS`) belongs to `ReviewOptions::focus`.
+    pub fn from_env() -> Self {
+        let list = |key: &str| -> Vec<String> {
+            std::env::var(key)
+                .ok()
+                .map(|v| {
+                    v.split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let opt = |key: &str| -> Option<String> {
+            std::env::var(key)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Self {
+            min_severity: opt("MR_REVIEWER_MIN_SEVERITY"),
+            ignore: list("MR_REVIEWER_IGNORE_GLOBS"),
+            focus: None,
+            languages: list("MR_REVIEWER_LANGUAGES"),
+            ignore_set: OnceLock::new(),
+        }
+    }
+
+    /// Parses `.mrai.toml` content. Unknown keys are warned about, not rejected.
+    pub fn parse_toml(text: &str) -> Option<Self> {
+        let table: toml::Table = match text.parse() {
+            Ok(t) => t,
+            Err(e) => {
+                warn!("repo_config: invalid {}: {}", REPO_CONFIG_PATH, e);
+                return None;
+            }
+        };
+        for key in table.keys() {
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                warn!("repo_config: unknown key '{}' in {}", key, REPO_CONFIG_PATH);
+            }
+        }
+        match toml::Value::Table(table).try_into::<Self>() {
+            Ok(cfg) => Some(cfg),
+            Err(e) => {
+                warn!("repo_config: bad value in {}: {}", REPO_CONFIG_PATH, e);
+                None
+            }
+        }
+    }
+
+    /// Returns `self` with every field set in `repo` taking precedence.
+    pub fn overlay(mut self, repo: RepoReviewConfig) -> Self {
+        if repo.min_severity.is_some() {
+            self.min_severity = repo.min_severity;
+        }
+        if !repo.ignore.is_empty() {
+            self.ignore = repo.ignore;
+            self.ignore_set = OnceLock::new();
+        }
+        if repo.focus.is_some() {
+            self.focus = repo.focus;
+        }
+        if !repo.languages.is_empty() {
+            self.languages = repo.languages;
+        }
+        self
+    }
+
+    /// Parsed minimum severity, if configured.
+    pub fn min_severity(&self) -> Option<Severity> {
+        match self.min_severity.as_deref()?.to_ascii_lowercase().as_str() {
+            "high" => Some(Severity::High),
+            "medium" => Some(Severity::Medium),
+            "low" => Some(Severity::Low),
+            other => {
+                warn!("repo_config: unknown min_severity '{}'", other);
+                None
+            }
+        }
+    }
+
+    /// True if a finding of severity `s` passes the configured threshold.
+    pub fn severity_allows(&self, s: Severity) -> bool {
+        let rank = |s: Severity| match s {
+            Severity::High => 3,
+            Severity::Medium => 2,
+            Severity::Low => 1,
+        };
+        self.min_severity()
+            .map(|min| rank(s) >= rank(min))
+            .unwrap_or(true)
+    }
+
+    /// Known focus areas named in `focus`, plus the whole text when it says
+    /// more than those names (e.g. `"security and error handling"`).
+    pub fn focus_areas(&self) -> (Vec<ReviewFocus>, Option<&str>) {
+        let Some(text) = self
+            .focus
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+        else {
+            return (Vec::new(), None);
+        };
+        let mut areas = Vec::new();
+        let mut free_form = false;
+        let words = text
+            .split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '/'))
+            .filter(|w| !w.is_empty() && !w.eq_ignore_ascii_case("and"));
+        for word in words {
+            match ReviewFocus::parse(word) {
+                Some(f) if !areas.contains(&f) => areas.push(f),
+                Some(_) => {}
+                None => free_form = true,
+            }
+        }
+        (areas, free_form.then_some(text))
+    }
+
+    /// True if `path` is neither ignored nor excluded by the language allowlist.
+    pub fn allows_path(&self, path: &str) -> bool {
+        let ignored = self.ignore_set.get_or_init(|| build_globset(&self.ignore));
+        if ignored.as_ref().is_some_and(|set| set.is_match(path)) {
+            return false;
+        }
+        if self.languages.is_empty() {
+            return true;
+        }
+        match crate::review::lang_from_path(Some(path)) {
+            Some(lang) => self.languages.iter().any(|l| l.eq_ignore_ascii_case(lang)),
+            None => false,
+        }
+    }
+}
+
+/// Fetches `.mrai.toml` at the target's `base_sha` and overlays it on env/defaults.
+///
+/// Missing file or provider errors are not fatal: the env/default config is returned.
+pub async fn load_repo_config(
+    client: &ProviderClient,
+    id: &ChangeRequestId,
+    base_sha: &str,
+) -> MrResult<RepoReviewConfig> {
+    let base = RepoReviewConfig::from_env();
+    let raw = match client
+        .fetch_file_raw_at_ref(id, REPO_CONFIG_PATH, base_sha)
+        .await
+    {
+        Ok(Some(bytes)) => bytes,
+        Ok(None) => {
+            debug!(
+                "repo_config: {} not present at {}",
+                REPO_CONFIG_PATH, base_sha
+            );
+            return Ok(base);
+        }
+        Err(e) => {
+            warn!("repo_config: failed to fetch {}: {}", REPO_CONFIG_PATH, e);
+            return Ok(base);
+        }
+    };
+
+    let text = String::from_utf8_lossy(&raw);
+    match RepoReviewConfig::parse_toml(&text) {
+        Some(repo) => {
+            info!("repo_config: loaded {} from repository", REPO_CONFIG_PATH);
+            Ok(base.overlay(repo))
+        }
+        None => Ok(base),
+    }
+}
+
+fn build_globset(patterns: &[String]) -> Option<GlobSet> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let mut b = GlobSetBuilder::new();
+    for p in patterns {
+        match Glob::new(p) {
+            Ok(g) => {
+                b.add(g);
+            }
+            Err(e) => warn!("repo_config: bad glob '{}': {}", p, e),
+        }
+    }
+    b.build().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repo_file_overrides_env_and_ignores_unknown_keys() {
+        let env = RepoReviewConfig {
+            min_severity: Some("Low".into()),
+            ignore: vec!["vendor/**".into()],
+            languages: vec!["rust".into()],
+            ..RepoReviewConfig::default()
+        };
+        let repo = RepoReviewConfig::parse_toml(
+            "min_severity = \"High\"\nignore = [\"**/*.g.dart\"]\nextra = 1\n",
+        )
+        .unwrap();
+        let cfg = env.overlay(repo);
+
+        assert_eq!(cfg.min_severity(), Some(Severity::High));
+        assert!(!cfg.severity_allows(Severity::Medium));
+        assert!(!cfg.allows_path("lib/a.g.dart"));
+        // The replaced env glob no longer applies; the env language list still does.
+        assert!(cfg.allows_path("vendor/x.rs"));
+        assert!(!cfg.allows_path("lib/a.dart"));
+        assert!(RepoReviewConfig::parse_toml("ignore = 3").is_none());
+    }
+
+    #[test]
+    fn ignore_globs_are_compiled_once_and_reset_by_overlay() {
+        let cfg = RepoReviewConfig {
+            ignore: vec!["generated/**".into()],
+            ..RepoReviewConfig::default()
+        };
+        assert!(!cfg.allows_path("generated/a.rs"));
+        assert!(cfg.ignore_set.get().is_some());
+
+        let cfg = cfg.overlay(RepoReviewConfig {
+            ignore: vec!["docs/**".into()],
+            ..RepoReviewConfig::default()
+        });
+        assert!(cfg.ignore_set.get().is_none());
+        assert!(cfg.allows_path("generated/a.rs"));
+        assert!(!cfg.allows_path("docs/a.md"));
+    }
+
+    #[test]
+    fn focus_names_become_areas_and_other_wording_stays_free_form() {
+        let focus = |text: &str| RepoReviewConfig {
+            focus: Some(text.into()),
+            ..RepoReviewConfig::default()
+        };
+        assert_eq!(
+            focus("security, perf").focus_areas(),
+            (vec![ReviewFocus::Security, ReviewFocus::Performance], None)
+        );
+        assert_eq!(
+            focus("security and error handling").focus_areas(),
+            (
+                vec![ReviewFocus::Security],
+                Some("security and error handling")
+            )
+        );
+        assert_eq!(focus("  ").focus_areas(), (Vec::new(), None));
+    }
+}
@@ -0,0 +1,150 @@
+//! Read-only access to a review's debug output on disk, for operator routes.
+//!
+//! Step 4 writes `step4_report.json` under [`report_dir`](super::report_dir);
+//! pre-query, RAG and prompt dumps land in
+//! `code_data/mr_tmp/<head12>/{preq,rag,prompts}/`. Lookups accept a hex
+//! head SHA only, and every resolved path must stay under its root (symlinks
+//! leading elsewhere are ignored), so request input can't reach other files.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Dump directories listed by [`list_artifacts`], relative to the review dir.
+const ARTIFACT_DIRS: &[&str] = &["preq", "rag", "prompts"];
+
+/// One dump file of a review.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReviewArtifact {
+    /// Path relative to `mr_tmp/<head12>/`, e.g. `rag/0_rag_chunks.json`.
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// Whether `s` is a hex commit id of 7 to 64 characters.
+pub fn is_head_sha(s: &str) -> bool {
+    (7..=64).contains(&s.len()) && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Parsed `step4_report.json` of `head_sha`; `Ok(None)` when the SHA is
+/// invalid or was never reviewed.
+pub fn read_report(head_sha: &str) -> io::Result<Option<serde_json::Value>> {
+    read_report_in(&super::report_base(), head_sha)
+}
+
+/// Dump files of `head_sha`, sorted by path; `None` when the SHA is invalid
+/// or has no review directory.
+pub fn list_artifacts(head_sha: &str) -> Option<Vec<ReviewArtifact>> {
+    list_artifacts_in(&services::data_root::mr_tmp_dir(), head_sha)
+}
+
+fn read_report_in(root: &Path, head_sha: &str) -> io::Result<Option<serde_json::Value>> {
+    let Some(dir) = review_dir(root, head_sha) else {
+        return Ok(None);
+    };
+    let Some(path) = contained(root, &dir.join("step4_report.json")) else {
+        return Ok(None);
+    };
+    let data = fs::read(path)?;
+    serde_json::from_slice(&data)
+        .map(Some)
+        .map_err(io::Error::other)
+}
+
+fn list_artifacts_in(root: &Path, head_sha: &str) -> Option<Vec<ReviewArtifact>> {
+    let dir = review_dir(root, head_sha)?;
+    let mut out = Vec::new();
+    for sub in ARTIFACT_DIRS {
+        collect_files(&dir, &dir.join(sub), &mut out);
+    }
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    Some(out)
+}
+
+/// Canonical `<root>/<head12>` if `head_sha` is valid and the directory exists.
+fn review_dir(root: &Path, head_sha: &str) -> Option<PathBuf> {
+    if !is_head_sha(head_sha) {
+        return None;
+    }
+    let short = &head_sha[..head_sha.len().min(12)];
+    contained(root, &root.join(short.to_ascii_lowercase())).filter(|p| p.is_dir())
+}
+
+/// Canonical `path` if it exists and resolves under `root`.
+fn contained(root: &Path, path: &Path) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    path.starts_with(&root).then_some(path)
+}
+
+/// Regular files below `dir` (recursively), relative to `base`; symlinks are
+/// skipped.
+fn collect_files(base: &Path, dir: &Path, out: &mut Vec<ReviewArtifact>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            collect_files(base, &path, out);
+        } else if meta.is_file() {
+            let Ok(rel) = path.strip_prefix(base) else {
+                continue;
+            };
+            out.push(ReviewArtifact {
+                path: rel
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                bytes: meta.len(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serves_report_and_dumps_of_a_known_sha_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let dir = root.join("0123456789ab");
+        fs::create_dir_all(dir.join("preq/0")).unwrap();
+        fs::create_dir_all(dir.join("rag")).unwrap();
+        fs::write(dir.join("step4_report.json"), r#"{"drafts":[]}"#).unwrap();
+        fs::write(dir.join("preq/0/query.json"), "{}").unwrap();
+        fs::write(dir.join("rag/0_rag_chunks.json"), "[]").unwrap();
+        fs::write(dir.join("src.dart"), "materialized, not a dump").unwrap();
+        fs::write(root.join("secret.txt"), "outside any review").unwrap();
+
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        let report = read_report_in(root, sha).unwrap().unwrap();
+        assert_eq!(report["drafts"], serde_json::json!([]));
+        let listed = list_artifacts_in(root, sha).unwrap();
+        let paths: Vec<_> = listed.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(paths, ["preq/0/query.json", "rag/0_rag_chunks.json"]);
+        assert_eq!(listed[1].bytes, 2);
+
+        // Unknown SHAs are not found; anything but hex never reaches the disk.
+        assert!(read_report_in(root, "fedcba9876543210").unwrap().is_none());
+        assert!(list_artifacts_in(root, "fedcba9876543210").is_none());
+        for bad in [
+            "..",
+            "../secret.txt",
+            "0123456789ab/..",
+            "abc",
+            "0123456789ag",
+        ] {
+            assert!(!is_head_sha(bad), "{bad}");
+            assert!(list_artifacts_in(root, bad).is_none(), "{bad}");
+        }
+    }
+}
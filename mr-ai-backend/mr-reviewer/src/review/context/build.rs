@@ -1,28 +1,32 @@
 //! Build `PrimaryCtx`: materialize HEAD, cut a numbered window, derive allowed anchors,
 //! optionally attach full-file (read-only) when import-like constructs are probable.
+//! Oversized full-file context is compacted (see `compact`).
 
 use crate::errors::Error;
 use crate::lang::SymbolIndex;
 use crate::map::{MappedTarget, TargetRef};
 use crate::review::context::types::{ChunkInfo, CodeFacts, EnclosingInfo};
 
+use super::compact::compact_full_file;
 use super::fs::read_materialized;
 use super::imports::contains_import_like;
-use super::types::{AnchorRange, PrimaryCtx};
+use super::types::{AnchorRange, ContextOptions, PrimaryCtx};
 use regex::Regex;
 
-const PRIMARY_PAD_LINES: i32 = 20;
-
 /// Build `PrimaryCtx` by materializing HEAD file, taking a window around the target,
 /// and deciding whether to include full-file read-only context.
 ///
 /// Read-only full-file is added if either:
 /// - the target is near top-of-file (imports are typically at the top), or
 /// - the snippet contains tokens suggesting import/include style constructs.
+///
+/// `opts.context_radius` controls how many unchanged lines surround the target;
+/// full-file context over `opts.full_file_compact_chars` has bodies stubbed.
 pub fn build_primary_ctx(
     head_sha: &str,
     tgt: &MappedTarget,
     symbols: &SymbolIndex,
+    opts: &ContextOptions,
 ) -> Result<PrimaryCtx, Error> {
     let path = match &tgt.target {
         TargetRef::Line { path, .. }
@@ -39,15 +43,8 @@ pub fn build_primary_ctx(
         String::new()
     };
 
-    let (ts, te) = target_line_window(tgt);
-    let (s, e) = window_bounds(
-        ts as i32,
-        te as i32,
-        code.lines().count() as i32,
-        PRIMARY_PAD_LINES,
-    );
-
-    let numbered_snippet = render_numbered(&code, s as usize, e as usize);
+    let (s, e) = primary_window(tgt, code.lines().count(), opts);
+    let numbered_snippet = render_numbered(&code, s, e);
     // Derive coarse allowed anchors. For Line/Symbol targets we expand to the
     // enclosing symbol body when available so the model can fix issues that lie
     // a few lines away from the exact mapped line (e.g., resource creation in initState).
@@ -57,10 +54,20 @@ pub fn build_primary_ctx(
     let mentions_import_like = contains_import_like(&numbered_snippet);
 
     let full_file_readonly = if !path.is_empty() && (near_top || mentions_import_like) {
-        Some(code.clone())
+        Some(compact_full_file(
+            &code,
+            &path,
+            symbols,
+            opts.full_file_compact_chars,
+        ))
     } else {
         None
     };
+    // Usage checks need the bodies the compacted copy elides.
+    let full_file_raw = full_file_readonly
+        .as_ref()
+        .filter(|f| f.len() != code.len())
+        .map(|_| code.clone());
 
     // Build compact, language-agnostic facts near the first allowed anchor.
     // The facts block now includes:
@@ -84,10 +91,19 @@ pub fn build_primary_ctx(
         numbered_snippet,
         allowed_anchors,
         full_file_readonly,
+        full_file_raw,
         code_facts,
     })
 }
 
+/// Numbered snippet window (1-based inclusive) around the target.
+fn primary_window(tgt: &MappedTarget, total_lines: usize, opts: &ContextOptions) -> (usize, usize) {
+    let (ts, te) = target_line_window(tgt);
+    let pad = opts.context_radius.min(i32::MAX as usize) as i32;
+    let (s, e) = window_bounds(ts as i32, te as i32, total_lines as i32, pad);
+    (s as usize, e as usize)
+}
+
 /// Inclusive window bounds with padding and clamping to file size.
 fn window_bounds(start: i32, end: i32, total: i32, pad: i32) -> (i32, i32) {
     let s = (start - pad).max(1);
@@ -420,3 +436,66 @@ fn cleanup_like_present(s: &str) -> Vec<String> {
     }
     found
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::Evidence;
+
+    fn line_target(line: usize) -> MappedTarget {
+        MappedTarget {
+            target: TargetRef::Line {
+                path: "lib/a.dart".into(),
+                line,
+            },
+            owner: None,
+            snippet_hash: "h".into(),
+            preview: String::new(),
+            evidence: Evidence {
+                added_lines: vec![line],
+                touches_decl: false,
+                removed_lines: Vec::new(),
+                removed_code: None,
+            },
+        }
+    }
+
+    #[test]
+    fn window_grows_with_radius() {
+        let tgt = line_target(100);
+        let narrow = primary_window(
+            &tgt,
+            500,
+            &ContextOptions {
+                context_radius: 5,
+                ..Default::default()
+            },
+        );
+        let wide = primary_window(
+            &tgt,
+            500,
+            &ContextOptions {
+                context_radius: 40,
+                ..Default::default()
+            },
+        );
+        assert_eq!(narrow, (95, 105));
+        assert_eq!(wide, (60, 140));
+        assert_eq!(
+            primary_window(&tgt, 500, &ContextOptions::default()),
+            (80, 120)
+        );
+        // Clamped to the file.
+        assert_eq!(
+            primary_window(
+                &tgt,
+                110,
+                &ContextOptions {
+                    context_radius: 200,
+                    ..Default::default()
+                }
+            ),
+            (1, 110)
+        );
+    }
+}
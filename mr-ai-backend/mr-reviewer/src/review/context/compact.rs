@@ -0,0 +1,194 @@
+//! Compaction of oversized read-only full-file context.
+//!
+//! Big files blow the prompt budget when attached as `full_file_readonly`.
+//! Function and method bodies (from the `SymbolIndex`) are replaced with a
+//! one-line stub; imports, class headers, fields and signatures stay intact,
+//! so the model still sees the file structure.
+
+use crate::lang::{SymbolIndex, SymbolKind};
+
+/// Lines scanned after a declaration line to find the opening `{`.
+const SIGNATURE_MAX_LINES: usize = 5;
+
+/// Returns `code` unchanged when it fits `max_chars` (or `max_chars == 0`),
+/// otherwise a copy with function/method bodies stubbed out.
+pub fn compact_full_file(
+    code: &str,
+    path: &str,
+    symbols: &SymbolIndex,
+    max_chars: usize,
+) -> String {
+    if max_chars == 0 || code.len() <= max_chars {
+        return code.to_string();
+    }
+    let lines: Vec<&str> = code.lines().collect();
+
+    // Interior (1-based inclusive) line ranges to elide, outermost first.
+    let mut bodies: Vec<(usize, usize)> = symbols
+        .symbols_in_file(path)
+        .iter()
+        .map(|&i| &symbols.symbols[i])
+        .filter(|s| matches!(s.kind, SymbolKind::Function | SymbolKind::Method))
+        .filter_map(|s| s.body_span.lines)
+        .filter_map(|ls| body_interior(&lines, ls.start_line as usize, ls.end_line as usize))
+        .collect();
+    bodies.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+    let mut out = String::with_capacity(max_chars.min(code.len()));
+    let mut next = 1usize;
+    for (start, end) in bodies {
+        if start < next {
+            continue; // nested in an already elided body
+        }
+        for line in &lines[next - 1..start - 1] {
+            out.push_str(line);
+            out.push('\n');
+        }
+        let indent: String = lines[start - 1]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+        out.push_str(&format!(
+            "{indent}… body elided ({} lines) …\n",
+            end - start + 1
+        ));
+        next = end + 1;
+    }
+    for line in &lines[(next - 1).min(lines.len())..] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Lines strictly between the opening `{` and the closing line of a body.
+///
+/// `None` for one-liners, expression bodies (`=>`) and bodies whose brace
+/// isn't found within [`SIGNATURE_MAX_LINES`] of the declaration.
+fn body_interior(lines: &[&str], decl: usize, end: usize) -> Option<(usize, usize)> {
+    if decl == 0 || end > lines.len() || end <= decl + 1 {
+        return None;
+    }
+    let open = (decl..=end.min(decl + SIGNATURE_MAX_LINES))
+        .find(|&l| lines[l - 1].contains('{') || lines[l - 1].contains("=>"))
+        .filter(|&l| lines[l - 1].contains('{'))?;
+    let (start, last) = (open + 1, end - 1);
+    (start <= last).then_some((start, last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::{ByteSpan, LineSpan, Span, SymbolRecord, build_index_maps};
+    use codegraph_prep::model::language::LanguageKind;
+
+    fn symbol(name: &str, kind: SymbolKind, start: u32, end: u32) -> SymbolRecord {
+        let span = |end_line| Span {
+            bytes: ByteSpan {
+                start_byte: 0,
+                end_byte: 0,
+            },
+            lines: Some(LineSpan {
+                start_line: start,
+                end_line,
+            }),
+        };
+        SymbolRecord {
+            symbol_id: name.into(),
+            path: "lib/svc.dart".into(),
+            language: LanguageKind::Dart,
+            kind,
+            name: name.into(),
+            decl_span: span(start),
+            body_span: span(end),
+        }
+    }
+
+    #[test]
+    fn bodies_are_stubbed_once_file_exceeds_threshold() {
+        let code = "import 'package:http/http.dart';\n\
+                    \n\
+                    class Svc {\n\
+                    \x20 final int retries = 3;\n\
+                    \n\
+                    \x20 Future<void> load(String id) async {\n\
+                    \x20   final r = await http.get(Uri.parse(id));\n\
+                    \x20   if (r.statusCode != 200) {\n\
+                    \x20     throw Exception('failed');\n\
+                    \x20   }\n\
+                    \x20 }\n\
+                    \n\
+                    \x20 int get size => retries * 2;\n\
+                    }\n";
+        let index = build_index_maps(
+            vec![
+                symbol("Svc", SymbolKind::Class, 3, 14),
+                symbol("load", SymbolKind::Method, 6, 11),
+                symbol("size", SymbolKind::Method, 13, 13),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(compact_full_file(code, "lib/svc.dart", &index, 0), code);
+        assert_eq!(
+            compact_full_file(code, "lib/svc.dart", &index, code.len()),
+            code
+        );
+
+        let compact = compact_full_file(code, "lib/svc.dart", &index, 100);
+        assert!(compact.len() < code.len());
+        assert!(compact.contains("import 'package:http/http.dart';"));
+        assert!(compact.contains("  final int retries = 3;"));
+        assert!(compact.contains(
+            "  Future<void> load(String id) async {\n    … body elided (4 lines) …\n  }\n"
+        ));
+        assert!(compact.contains("  int get size => retries * 2;"));
+        assert!(!compact.contains("statusCode"));
+        assert!(compact.ends_with("}\n"));
+    }
+
+    #[test]
+    fn unused_import_guard_sees_usages_in_elided_bodies() {
+        use crate::review::context::{PrimaryCtx, unused_import_claim_is_false_positive};
+
+        let code = "import 'package:http/http.dart' as http;\n\
+                    \n\
+                    class Svc {\n\
+                    \x20 Future<void> load(String id) async {\n\
+                    \x20   final r = await http.get(Uri.parse(id));\n\
+                    \x20   print(r.body);\n\
+                    \x20 }\n\
+                    }\n";
+        let index = build_index_maps(
+            vec![
+                symbol("Svc", SymbolKind::Class, 3, 8),
+                symbol("load", SymbolKind::Method, 4, 7),
+            ],
+            Vec::new(),
+        );
+        let compact = compact_full_file(code, "lib/svc.dart", &index, 60);
+        assert!(!compact.contains("http.get"));
+
+        let ctx = PrimaryCtx {
+            path: "lib/svc.dart".into(),
+            numbered_snippet: "1 | import 'package:http/http.dart' as http;".into(),
+            allowed_anchors: Vec::new(),
+            full_file_readonly: Some(compact),
+            full_file_raw: Some(code.to_string()),
+            code_facts: None,
+        };
+        let guard = |full: Option<&str>| {
+            unused_import_claim_is_false_positive(
+                "sha",
+                &ctx.path,
+                full,
+                &ctx.numbered_snippet,
+                None,
+            )
+        };
+        // The compacted prompt copy hides the only usage...
+        assert!(!guard(ctx.full_file_readonly.as_deref()));
+        // ...so the guard is handed the raw file.
+        assert!(guard(ctx.full_file_for_evidence()));
+    }
+}
@@ -1,7 +1,7 @@
 //! Access to materialized HEAD files and conservative patch checks.
 
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 /// Build path to materialized HEAD file under `code_data/mr_tmp/<short_sha>/...`.
 fn materialized_path(head_sha: &str, repo_rel: &str) -> PathBuf {
@@ -10,10 +10,7 @@ fn materialized_path(head_sha: &str, repo_rel: &str) -> PathBuf {
     } else {
         head_sha
     };
-    Path::new("code_data")
-        .join("mr_tmp")
-        .join(short)
-        .join(repo_rel)
+    services::data_root::mr_tmp_dir().join(short).join(repo_rel)
 }
 
 /// Read materialized file text if it exists.
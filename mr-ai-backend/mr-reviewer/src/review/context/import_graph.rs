@@ -0,0 +1,220 @@
+//! Import-graph usage evidence from the code indexer's `code_chunks.jsonl`.
+//!
+//! Each chunk carries graph edges (`graph.calls_out`, `graph.uses_types`) and
+//! the imports it resolved (`lsp.imports_used`, with re-export flags). For an
+//! "unused import" claim this gives real usage evidence that a textual scan
+//! misses: conditional imports whose line names no symbol, barrels that only
+//! re-export, and aliases resolved by the language server.
+//!
+//! Only chunks of the reviewed files are kept in memory.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+/// One import edge of a file (`file --imports--> identifier`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportEdge {
+    /// Normalized import label, e.g. `file:lib/src/client_stub.dart`, `pkg:lodash`.
+    pub label: String,
+    /// Imported identifier (`*` for import-all).
+    pub identifier: String,
+    pub alias: Option<String>,
+    /// The file re-exports this import (barrel / `pub use` / `export ... from`).
+    pub is_reexport: bool,
+}
+
+/// Usage facts collected for one file.
+#[derive(Debug, Clone, Default)]
+pub struct FileImportUsage {
+    pub imports: Vec<ImportEdge>,
+    /// Last path segment of every `calls_out` / `uses_types` target in the file.
+    pub referenced: BTreeSet<String>,
+}
+
+/// Per-file import edges and references, keyed by the indexer's `file` path.
+#[derive(Debug, Clone, Default)]
+pub struct ImportGraph {
+    files: BTreeMap<String, FileImportUsage>,
+}
+
+/// Subset of the indexer's `CodeChunk` this module reads.
+#[derive(Deserialize)]
+struct ChunkRow {
+    file: String,
+    #[serde(default)]
+    graph: Option<GraphRow>,
+    #[serde(default)]
+    lsp: Option<LspRow>,
+}
+
+#[derive(Deserialize, Default)]
+struct GraphRow {
+    #[serde(default)]
+    calls_out: Vec<String>,
+    #[serde(default)]
+    uses_types: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct LspRow {
+    #[serde(default)]
+    imports_used: Vec<ImportUseRow>,
+}
+
+#[derive(Deserialize)]
+struct ImportUseRow {
+    #[serde(default)]
+    label: String,
+    identifier: String,
+    #[serde(default)]
+    alias: Option<String>,
+    #[serde(default)]
+    is_reexport: Option<bool>,
+}
+
+impl ImportGraph {
+    /// Loads the graph for `paths` from the indexer output, if it exists.
+    ///
+    /// Location: `INDEX_JSONL_PATH`, else `code_data/out/<PROJECT_NAME>/code_chunks.jsonl`
+    /// (same as rag-base). Returns `None` when the file is missing or unreadable;
+    /// the guard then falls back to the textual heuristic.
+    pub fn from_env(paths: &[&str]) -> Option<Self> {
+        let jsonl = std::env::var("INDEX_JSONL_PATH")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                let name = std::env::var("PROJECT_NAME").unwrap_or_else(|_| "project_x".into());
+                services::data_root::code_chunks_path(&name)
+            });
+        if !jsonl.exists() {
+            debug!("import_graph: {} not found, skipping", jsonl.display());
+            return None;
+        }
+        match Self::load(&jsonl, paths) {
+            Ok(g) => {
+                debug!(
+                    "import_graph: loaded files={} from {}",
+                    g.files.len(),
+                    jsonl.display()
+                );
+                Some(g)
+            }
+            Err(e) => {
+                warn!("import_graph: failed to read {}: {}", jsonl.display(), e);
+                None
+            }
+        }
+    }
+
+    /// Reads chunks of `paths` (repo-relative; matched as a path suffix) from `jsonl`.
+    /// Malformed lines are skipped.
+    pub fn load(jsonl: &Path, paths: &[&str]) -> std::io::Result<Self> {
+        let reader = BufReader::new(std::fs::File::open(jsonl)?);
+        let mut graph = Self::default();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Ok(row) = serde_json::from_str::<ChunkRow>(&line) else {
+                continue;
+            };
+            if paths.iter().any(|p| same_file(&row.file, p)) {
+                graph.add_chunk(row);
+            }
+        }
+        Ok(graph)
+    }
+
+    fn add_chunk(&mut self, row: ChunkRow) {
+        let usage = self.files.entry(row.file).or_default();
+        let graph = row.graph.unwrap_or_default();
+        for target in graph.calls_out.iter().chain(graph.uses_types.iter()) {
+            usage.referenced.insert(last_segment(target).to_string());
+        }
+        for u in row.lsp.unwrap_or_default().imports_used {
+            let edge = ImportEdge {
+                label: u.label,
+                identifier: u.identifier,
+                alias: u.alias,
+                is_reexport: u.is_reexport.unwrap_or(false),
+            };
+            if !usage.imports.contains(&edge) {
+                usage.imports.push(edge);
+            }
+        }
+    }
+
+    /// Adds usage facts for `file` directly (tests, callers with their own graph source).
+    pub fn insert(&mut self, file: impl Into<String>, usage: FileImportUsage) {
+        self.files.insert(file.into(), usage);
+    }
+
+    /// Usage facts of `path` (repo-relative), merged across matching indexer paths.
+    pub fn usage_for(&self, path: &str) -> Option<FileImportUsage> {
+        let mut merged: Option<FileImportUsage> = None;
+        for (_, usage) in self.files.iter().filter(|(f, _)| same_file(f, path)) {
+            let m = merged.get_or_insert_with(FileImportUsage::default);
+            m.referenced.extend(usage.referenced.iter().cloned());
+            m.imports.extend(usage.imports.iter().cloned());
+        }
+        merged
+    }
+
+    /// True if the graph shows that an import on one of `import_lines` (or one of
+    /// the textual `candidates`) is re-exported or referenced by the file's symbols.
+    pub fn has_usage_evidence(
+        &self,
+        path: &str,
+        import_lines: &[&str],
+        candidates: &[String],
+    ) -> bool {
+        let Some(usage) = self.usage_for(path) else {
+            return false;
+        };
+        usage.imports.iter().any(|e| {
+            let names_match = candidates
+                .iter()
+                .any(|c| *c == e.identifier || e.alias.as_deref() == Some(c.as_str()));
+            let line_match = import_lines.iter().any(|l| line_imports_label(l, &e.label));
+            if !(names_match || line_match) {
+                return false;
+            }
+            e.is_reexport
+                || usage.referenced.contains(&e.identifier)
+                || e.alias
+                    .as_ref()
+                    .is_some_and(|a| usage.referenced.contains(a))
+        }) || candidates.iter().any(|c| usage.referenced.contains(c))
+    }
+}
+
+/// `indexer_file` (e.g. `code_data/app/repo/lib/a.dart`) is the repo-relative `path`.
+fn same_file(indexer_file: &str, path: &str) -> bool {
+    let path = path.trim_start_matches("./");
+    !path.is_empty()
+        && (indexer_file == path
+            || indexer_file
+                .strip_suffix(path)
+                .is_some_and(|prefix| prefix.ends_with('/')))
+}
+
+/// `a.b.C`, `a::b::C`, `lib/x.dart::C` → `C`.
+fn last_segment(s: &str) -> &str {
+    s.rsplit(['.', ':', '/', '#']).next().unwrap_or(s)
+}
+
+/// The import line mentions the label's module path (prefix like `file:` dropped).
+fn line_imports_label(line: &str, label: &str) -> bool {
+    let module = label.split_once(':').map_or(label, |(_, rest)| rest);
+    let module = module.trim_start_matches("./");
+    if module.is_empty() {
+        return false;
+    }
+    // Labels may be resolved (`lib/src/x.dart`) while the line is relative (`src/x.dart`).
+    let tail = module.rsplit('/').next().unwrap_or(module);
+    line.contains(module) || (tail.contains('.') && line.contains(tail))
+}
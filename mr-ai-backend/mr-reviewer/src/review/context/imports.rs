@@ -4,6 +4,7 @@
 use regex::Regex;
 
 use super::fs::read_materialized;
+use super::import_graph::ImportGraph;
 
 /// Detects import-like constructs in a snippet or body.
 pub fn contains_import_like(s: &str) -> bool {
@@ -18,7 +19,11 @@ pub fn contains_import_like(s: &str) -> bool {
 
 /// Check if "unused import" claim is likely **false positive** by scanning full file use.
 /// The function extracts candidate symbols from the import line(s) present in the snippet,
-/// then searches the rest of the file for their usage.
+/// then looks for usage evidence, strongest first:
+/// 1. the import line is a re-export (`pub use`, `export ... from`, Dart `export '...'`);
+/// 2. the indexer's import graph (`graph`) shows the imported symbol is re-exported or
+///    referenced by the file's symbols (`calls_out` / `uses_types`);
+/// 3. the rest of the file mentions a candidate as a standalone token.
 ///
 /// This is **language-agnostic** and heuristic by design.
 pub fn unused_import_claim_is_false_positive(
@@ -26,15 +31,8 @@ pub fn unused_import_claim_is_false_positive(
     path: &str,
     full_file_opt: Option<&str>,
     snippet: &str,
+    graph: Option<&ImportGraph>,
 ) -> bool {
-    let full = match full_file_opt {
-        Some(s) => s.to_string(),
-        None => match read_materialized(head_sha, path) {
-            Some(s) => s,
-            None => return false,
-        },
-    };
-
     // Gather import-like lines from the snippet window to focus the check.
     let import_lines: Vec<&str> = snippet
         .lines()
@@ -46,16 +44,33 @@ pub fn unused_import_claim_is_false_positive(
         return false;
     }
 
+    // A re-exported import is part of the module's API, not dead code.
+    if import_lines.iter().any(|l| is_reexport_line(l)) {
+        return true;
+    }
+
     // Extract candidate identifiers from import lines.
     let mut candidates: Vec<String> = Vec::new();
-    for l in import_lines {
+    for l in &import_lines {
         candidates.extend(extract_import_symbols(l));
     }
+    candidates.sort();
+    candidates.dedup();
+
+    if graph.is_some_and(|g| g.has_usage_evidence(path, &import_lines, &candidates)) {
+        return true;
+    }
     if candidates.is_empty() {
         return false;
     }
-    candidates.sort();
-    candidates.dedup();
+
+    let full = match full_file_opt {
+        Some(s) => s.to_string(),
+        None => match read_materialized(head_sha, path) {
+            Some(s) => s,
+            None => return false,
+        },
+    };
 
     // Build a "non-import" body for search (strip import/include/use headers from the full file).
     let non_import_body = strip_import_section(&full);
@@ -84,6 +99,17 @@ fn is_import_like(s: &str) -> bool {
         || st.starts_with("require(")
         || st.starts_with("from ")
         || st.contains(" import ")
+        || is_reexport_line(st)
+}
+
+/// Re-export forms: Rust `pub use` / `pub(crate) use`, ES `export { A } from` /
+/// `export * from`, Dart `export 'pkg.dart'`.
+fn is_reexport_line(s: &str) -> bool {
+    let st = s.trim_start();
+    st.starts_with("pub use ")
+        || (st.starts_with("pub(") && st.contains(") use "))
+        || (st.starts_with("export ")
+            && (st.contains(" from ") || st.starts_with("export '") || st.starts_with("export \"")))
 }
 
 /// Extract plausible symbol tokens from a single import-like line.
@@ -249,3 +275,107 @@ fn strip_import_section(full: &str) -> String {
         out
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::context::import_graph::{FileImportUsage, ImportEdge};
+
+    fn graph_for(file: &str, imports: Vec<ImportEdge>, referenced: &[&str]) -> ImportGraph {
+        let mut g = ImportGraph::default();
+        g.insert(
+            file,
+            FileImportUsage {
+                imports,
+                referenced: referenced.iter().map(|s| s.to_string()).collect(),
+            },
+        );
+        g
+    }
+
+    #[test]
+    fn reexported_import_is_not_unused() {
+        // Rust re-export: the symbol is never used in the file itself.
+        let rust = "pub use crate::ui::button::Button;\n\npub fn version() -> u32 {\n    1\n}\n";
+        assert!(unused_import_claim_is_false_positive(
+            "sha",
+            "src/ui/mod.rs",
+            Some(rust),
+            "1 | pub use crate::ui::button::Button;",
+            None,
+        ));
+
+        // TS barrel where only the indexer knows the import is re-exported.
+        let ts =
+            "import { Button } from './button';\n\nconst a = 1;\nconst b = 2;\nconst c = a + b;\n";
+        let snippet = "1 | import { Button } from './button';";
+        assert!(!unused_import_claim_is_false_positive(
+            "sha",
+            "src/index.ts",
+            Some(ts),
+            snippet,
+            None,
+        ));
+        let graph = graph_for(
+            "code_data/app/web/src/index.ts",
+            vec![ImportEdge {
+                label: "file:src/button.ts".into(),
+                identifier: "Button".into(),
+                alias: None,
+                is_reexport: true,
+            }],
+            &[],
+        );
+        assert!(unused_import_claim_is_false_positive(
+            "sha",
+            "src/index.ts",
+            Some(ts),
+            snippet,
+            Some(&graph),
+        ));
+    }
+
+    #[test]
+    fn conditional_import_usage_comes_from_graph() {
+        // Dart conditional import: the line names no symbol, so text alone finds nothing.
+        let dart = "import 'src/client_stub.dart' if (dart.library.io) 'src/client_io.dart';\n\nclass Api {\n  final client = createClient();\n}\n";
+        let snippet =
+            "1 | import 'src/client_stub.dart' if (dart.library.io) 'src/client_io.dart';";
+        assert!(!unused_import_claim_is_false_positive(
+            "sha",
+            "lib/api.dart",
+            Some(dart),
+            snippet,
+            None,
+        ));
+
+        let edge = ImportEdge {
+            label: "file:lib/src/client_stub.dart".into(),
+            identifier: "createClient".into(),
+            alias: None,
+            is_reexport: false,
+        };
+        let used = graph_for(
+            "code_data/app/lib/api.dart",
+            vec![edge.clone()],
+            &["createClient"],
+        );
+        assert!(unused_import_claim_is_false_positive(
+            "sha",
+            "lib/api.dart",
+            Some(dart),
+            snippet,
+            Some(&used),
+        ));
+
+        // Same import edge but no symbol of the file references it → claim stands.
+        let unused = graph_for("code_data/app/lib/api.dart", vec![edge], &["Api"]);
+        assert!(!unused_import_claim_is_false_positive(
+            "sha",
+            "lib/api.dart",
+            Some(dart),
+            snippet,
+            Some(&unused),
+        ));
+    }
+}
@@ -1,7 +1,9 @@
 //! Context assembly for step 4 (mod):
 //! - Primary context (numbered snippet, allowed anchors, optional full-file).
+//! - Compaction of oversized full-file context (bodies stubbed, structure kept).
 //! - Re-anchoring via patch and signature scanning (prefers ADDED lines).
-//! - Heuristics for generic import/include/using to avoid false "unused import".
+//! - Heuristics for generic import/include/using to avoid false "unused import",
+//!   backed by the indexer's import graph when available.
 //! - Read-only RAG for related context.
 //! - Helpers to read materialized HEAD and check patch applicability.
 //! - Utilities to collect ADDED line numbers from provider hunks.
@@ -9,7 +11,9 @@
 pub mod added;
 pub mod build;
 pub mod chunk;
+pub mod compact;
 pub mod fs;
+pub mod import_graph;
 pub mod imports;
 pub mod rag;
 pub mod reanchor;
@@ -18,8 +22,10 @@ pub mod types;
 // Re-export primary API for external users of `crate::review::context`.
 pub use added::collect_added_lines;
 pub use build::build_primary_ctx;
+pub use compact::compact_full_file;
 pub use fs::{patch_applies_to_head, read_materialized};
+pub use import_graph::ImportGraph;
 pub use imports::{contains_import_like, unused_import_claim_is_false_positive};
 pub use rag::fetch_related_context;
 pub use reanchor::{infer_anchor_by_signature, infer_anchor_prefer_added, reanchor_via_patch};
-pub use types::{AnchorRange, PrimaryCtx};
+pub use types::{AnchorPreference, AnchorRange, ContextOptions, PrimaryCtx};
@@ -132,25 +132,37 @@ fn ast_facts_for(symbols: &SymbolIndex, path: &str, anchor_line: Option<u32>) ->
     // Enclosing symbol around the anchor, if any.
     let enclosing = anchor_line.and_then(|ln| symbols.find_enclosing_by_line(path, ln));
 
+    let library = symbols.library_path(path);
+
     if enclosing.is_none() {
-        // Fallback: list top-level symbols in the file (bounded).
-        let file_syms = symbols.symbols_in_file(path);
+        // Fallback: list top-level symbols in the file's library (bounded);
+        // for a Dart part this includes the library file and sibling parts.
+        let file_syms = symbols.symbols_in_library(path);
         if file_syms.is_empty() {
             return None;
         }
         let mut out = String::new();
         out.push_str("AST FACTS (read-only; from global index)\n");
         out.push_str(&format!("file: {}\n", path));
+        if library != path {
+            out.push_str(&format!("part_of_library: {}\n", library));
+        }
         out.push_str("file_symbols:\n");
         for &i in file_syms.iter().take(12) {
             if let Some(s) = symbols.symbols.get(i) {
+                // Lines of symbols from another file of the library are in that file.
+                let origin = if s.path == path {
+                    String::new()
+                } else {
+                    format!(" in {}", s.path)
+                };
                 if let Some(ls) = s.body_span.lines {
                     out.push_str(&format!(
-                        "  - {:?} {} [{}..{}]\n",
-                        s.kind, s.name, ls.start_line, ls.end_line
+                        "  - {:?} {} [{}..{}]{}\n",
+                        s.kind, s.name, ls.start_line, ls.end_line, origin
                     ));
                 } else {
-                    out.push_str(&format!("  - {:?} {}\n", s.kind, s.name));
+                    out.push_str(&format!("  - {:?} {}{}\n", s.kind, s.name, origin));
                 }
             }
         }
@@ -185,6 +197,9 @@ fn ast_facts_for(symbols: &SymbolIndex, path: &str, anchor_line: Option<u32>) ->
     let mut out = String::new();
     out.push_str("AST FACTS (read-only; from global index)\n");
     out.push_str(&format!("file: {}\n", path));
+    if library != path {
+        out.push_str(&format!("part_of_library: {}\n", library));
+    }
     if let Some(ln) = anchor_line {
         out.push_str(&format!("anchor_line: {}\n", ln));
     }
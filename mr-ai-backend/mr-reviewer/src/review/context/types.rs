@@ -7,6 +7,28 @@ pub struct AnchorRange {
     pub end: usize,
 }
 
+/// Which line a finding lands on inside its anchor (see `ReviewOptions`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AnchorPreference {
+    /// First added line inside a multi-line anchor.
+    #[default]
+    AddedLine,
+    /// The owning symbol's declaration line when that line was changed (e.g. a
+    /// signature change); otherwise as `AddedLine`.
+    Declaration,
+}
+
+impl AnchorPreference {
+    /// Parses `added` / `declaration` (case-insensitive); `None` otherwise.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "added" | "added_line" => Some(Self::AddedLine),
+            "decl" | "declaration" => Some(Self::Declaration),
+            _ => None,
+        }
+    }
+}
+
 /// Serializable reference to a chunk of a parent entity (used by chunk.rs).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ChunkRef {
@@ -69,6 +91,60 @@ pub struct CodeFacts {
     pub cleanup_like: Vec<String>,
 }
 
+/// Knobs for building `PrimaryCtx`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextOptions {
+    /// Unchanged lines shown above/below the changed region in the numbered snippet.
+    /// Only widens what the model sees; allowed anchors stay on changed lines.
+    pub context_radius: usize,
+    /// Full-file context longer than this (bytes) gets function/method bodies
+    /// stubbed out, keeping imports and declarations; `0` never compacts.
+    pub full_file_compact_chars: usize,
+    /// Prompt size budget (chars). Over it, RAG/RELATED blocks and then the
+    /// full file are dropped (see `prompt::trim_to_prompt_budget`); `0` disables.
+    pub max_prompt_chars: usize,
+}
+
+impl ContextOptions {
+    /// Default radius of the numbered snippet window.
+    pub const DEFAULT_RADIUS: usize = 20;
+    /// Default full-file size above which bodies are stubbed.
+    pub const DEFAULT_FULL_FILE_COMPACT_CHARS: usize = 12_000;
+    /// Default prompt budget (~12k tokens at 4 chars/token).
+    pub const DEFAULT_MAX_PROMPT_CHARS: usize = 48_000;
+
+    /// Reads `REVIEW_CONTEXT_RADIUS` (default: 20),
+    /// `REVIEW_FULL_FILE_COMPACT_CHARS` (default: 12000) and
+    /// `REVIEW_MAX_PROMPT_CHARS` (default: 48000).
+    pub fn from_env() -> Self {
+        Self {
+            context_radius: env_usize("REVIEW_CONTEXT_RADIUS", Self::DEFAULT_RADIUS),
+            full_file_compact_chars: env_usize(
+                "REVIEW_FULL_FILE_COMPACT_CHARS",
+                Self::DEFAULT_FULL_FILE_COMPACT_CHARS,
+            ),
+            max_prompt_chars: env_usize("REVIEW_MAX_PROMPT_CHARS", Self::DEFAULT_MAX_PROMPT_CHARS),
+        }
+    }
+}
+
+impl Default for ContextOptions {
+    fn default() -> Self {
+        Self {
+            context_radius: Self::DEFAULT_RADIUS,
+            full_file_compact_chars: Self::DEFAULT_FULL_FILE_COMPACT_CHARS,
+            max_prompt_chars: Self::DEFAULT_MAX_PROMPT_CHARS,
+        }
+    }
+}
+
+fn env_usize(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(default)
+}
+
 /// Primary per-target context packaged for prompting.
 #[derive(Debug, Clone)]
 pub struct PrimaryCtx {
@@ -79,11 +155,24 @@ pub struct PrimaryCtx {
     /// Coarse allowed anchors derived from the target mapping (Line/Range/Symbol).
     pub allowed_anchors: Vec<AnchorRange>,
     /// Optional full-file read-only body for side checks (imports, symbol presence).
+    /// May be compacted (function bodies elided) to fit the prompt budget.
     pub full_file_readonly: Option<String>,
+    /// The uncompacted file when `full_file_readonly` was compacted.
+    pub full_file_raw: Option<String>,
     /// Structured code facts near the anchor (HEAD authoritative).
     pub code_facts: Option<CodeFacts>,
 }
 
+impl PrimaryCtx {
+    /// Full file to search for usages: never the compacted prompt copy, whose
+    /// elided bodies would hide them. `None` means read the materialized file.
+    pub fn full_file_for_evidence(&self) -> Option<&str> {
+        self.full_file_raw
+            .as_deref()
+            .or(self.full_file_readonly.as_deref())
+    }
+}
+
 /// Strict output spec injected into the prompt to enforce deterministic JSON.
 pub const STRICT_OUTPUT_SPEC: &str = r#"
 OUTPUT FORMAT (STRICT JSON):
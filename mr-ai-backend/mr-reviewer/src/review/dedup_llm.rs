@@ -63,7 +63,7 @@ pub async fn dedup_drafts_llm_async(
 
     // Whether body contains an explicit patch block.
     fn has_patch(d: &DraftComment) -> bool {
-        d.body_markdown.contains("```diff")
+        d.body_markdown.contains("```diff") || d.body_markdown.contains("```suggestion")
     }
 
     // Coarse thematic bucket to avoid mixing intents.
@@ -185,7 +185,7 @@ pub async fn dedup_drafts_llm_async(
 You are deduplicating code review comments about the SAME code region.
 Pick ONE option index only:
 - Prefer higher severity.
-- Prefer comments with an explicit patch (```diff).
+- Prefer comments with an explicit patch (```diff or ```suggestion).
 - Prefer narrower anchors and clearer rationale.
 Return ONLY the index digit (e.g., 0).";
         let prompt = format!("File: {path}\nTheme: {theme}\nOptions:\n{options}\n{guidance}");
@@ -0,0 +1,249 @@
+//! Cheap, LLM-free pre-dedup pass for review drafts.
+//!
+//! Runs before `dedup_llm` so the FAST budget is only spent on clusters that
+//! are genuinely ambiguous. Two drafts in the same file are duplicates when:
+//! - they share the exact same anchor (`path` + line span), or
+//! - their normalized titles or bodies have a token-set (Jaccard) similarity
+//!   at or above the configured threshold.
+//!
+//! Within each duplicate group the strongest draft survives
+//! (severity > has patch > longer body).
+
+use std::collections::HashSet;
+
+use crate::map::TargetRef;
+use crate::review::DraftComment;
+use crate::review::policy::Severity;
+
+/// Default token-set similarity threshold (0..1) for near-identical drafts.
+pub const DEFAULT_SIM_THRESHOLD: f32 = 0.8;
+
+/// Reads the similarity threshold from `REVIEW_DEDUP_SIM_THRESHOLD`
+/// (default: `0.8`, clamped to `0..=1`).
+pub fn sim_threshold_from_env() -> f32 {
+    std::env::var("REVIEW_DEDUP_SIM_THRESHOLD")
+        .ok()
+        .and_then(|s| s.parse::<f32>().ok())
+        .map(|t| t.clamp(0.0, 1.0))
+        .unwrap_or(DEFAULT_SIM_THRESHOLD)
+}
+
+/// Mutates `drafts` in place, dropping local duplicates. Returns the number of removed drafts.
+pub fn dedup_drafts_local(drafts: &mut Vec<DraftComment>, threshold: f32) -> usize {
+    struct Rep {
+        idx: usize,
+        path: String,
+        anchor: Option<(usize, usize)>,
+        title: HashSet<String>,
+        body: HashSet<String>,
+    }
+
+    let mut reps: Vec<Rep> = Vec::new();
+    let mut keep = vec![true; drafts.len()];
+
+    for (i, d) in drafts.iter().enumerate() {
+        let path = draft_path(d);
+        let anchor = draft_anchor(d);
+        let title = token_set(draft_title(&d.body_markdown));
+        let body = token_set(&d.body_markdown);
+
+        let dup_of = reps.iter().position(|r| {
+            r.path == path
+                && ((anchor.is_some() && r.anchor == anchor)
+                    || jaccard(&r.title, &title) >= threshold
+                    || jaccard(&r.body, &body) >= threshold)
+        });
+
+        match dup_of {
+            Some(k) => {
+                let rep = &mut reps[k];
+                if stronger(d, &drafts[rep.idx]) {
+                    keep[rep.idx] = false;
+                    *rep = Rep {
+                        idx: i,
+                        path,
+                        anchor,
+                        title,
+                        body,
+                    };
+                } else {
+                    keep[i] = false;
+                }
+            }
+            None => reps.push(Rep {
+                idx: i,
+                path,
+                anchor,
+                title,
+                body,
+            }),
+        }
+    }
+
+    let before = drafts.len();
+    let mut it = keep.iter();
+    drafts.retain(|_| *it.next().unwrap_or(&true));
+    before - drafts.len()
+}
+
+/// True when `a` should replace `b` as the representative of a duplicate group.
+fn stronger(a: &DraftComment, b: &DraftComment) -> bool {
+    let rank = |s: Severity| match s {
+        Severity::High => 3,
+        Severity::Medium => 2,
+        Severity::Low => 1,
+    };
+    let key = |d: &DraftComment| {
+        (
+            rank(d.severity),
+            d.body_markdown.contains("```diff") || d.body_markdown.contains("```suggestion"),
+            d.body_markdown.len(),
+        )
+    };
+    key(a) > key(b)
+}
+
+fn draft_path(d: &DraftComment) -> String {
+    match &d.target {
+        TargetRef::Line { path, .. }
+        | TargetRef::Range { path, .. }
+        | TargetRef::Symbol { path, .. }
+        | TargetRef::File { path } => path.clone(),
+        TargetRef::Global => String::new(),
+    }
+}
+
+fn draft_anchor(d: &DraftComment) -> Option<(usize, usize)> {
+    match &d.target {
+        TargetRef::Line { line, .. } => Some((*line, *line)),
+        TargetRef::Range {
+            start_line,
+            end_line,
+            ..
+        } => Some((*start_line, *end_line)),
+        TargetRef::Symbol { decl_line, .. } => Some((*decl_line, *decl_line)),
+        _ => None,
+    }
+}
+
+/// Title is the first non-empty line of the rendered body (`**Title**`).
+fn draft_title(body: &str) -> &str {
+    body.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or("")
+        .trim_matches('*')
+}
+
+/// Lowercased alphanumeric tokens (len >= 3) as a set.
+fn token_set(s: &str) -> HashSet<String> {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| t.len() >= 3)
+        .map(str::to_string)
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let inter = a.intersection(b).count();
+    let union = a.len() + b.len() - inter;
+    inter as f32 / union as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(path: &str, line: usize, sev: Severity, body: &str) -> DraftComment {
+        DraftComment {
+            target: TargetRef::Line {
+                path: path.to_string(),
+                line,
+            },
+            snippet_hash: "h".to_string(),
+            body_markdown: body.to_string(),
+            severity: sev,
+            confidence: 0.6,
+            preview: String::new(),
+        }
+    }
+
+    #[test]
+    fn same_anchor_keeps_strongest() {
+        let mut drafts = vec![
+            draft(
+                "lib/a.dart",
+                10,
+                Severity::Low,
+                "**Naming**\n\nRename variable.",
+            ),
+            draft(
+                "lib/a.dart",
+                10,
+                Severity::High,
+                "**Null check missing**\n\nValue may be null.",
+            ),
+        ];
+        let removed = dedup_drafts_local(&mut drafts, DEFAULT_SIM_THRESHOLD);
+        assert_eq!(removed, 1);
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].severity, Severity::High);
+    }
+
+    #[test]
+    fn near_identical_titles_are_merged() {
+        let mut drafts = vec![
+            draft(
+                "lib/a.dart",
+                10,
+                Severity::Medium,
+                "**Controller is never disposed**\n\nCall dispose in the widget.",
+            ),
+            draft(
+                "lib/a.dart",
+                40,
+                Severity::Medium,
+                "**Controller is never disposed**\n\nThe controller leaks; dispose it in dispose().",
+            ),
+        ];
+        let removed = dedup_drafts_local(&mut drafts, DEFAULT_SIM_THRESHOLD);
+        assert_eq!(removed, 1);
+        assert_eq!(
+            drafts[0].target,
+            TargetRef::Line {
+                path: "lib/a.dart".into(),
+                line: 40
+            }
+        );
+    }
+
+    #[test]
+    fn distinct_findings_survive() {
+        let mut drafts = vec![
+            draft(
+                "lib/a.dart",
+                10,
+                Severity::Medium,
+                "**Controller is never disposed**\n\nCall dispose.",
+            ),
+            draft(
+                "lib/a.dart",
+                40,
+                Severity::Medium,
+                "**Blocking IO on main isolate**\n\nMove file reads to a compute isolate.",
+            ),
+            draft(
+                "lib/b.dart",
+                10,
+                Severity::Medium,
+                "**Controller is never disposed**\n\nCall dispose.",
+            ),
+        ];
+        let removed = dedup_drafts_local(&mut drafts, DEFAULT_SIM_THRESHOLD);
+        assert_eq!(removed, 0);
+        assert_eq!(drafts.len(), 3);
+    }
+}
@@ -0,0 +1,233 @@
+//! Review focus: per-run emphasis (`ReviewOptions::focus`).
+//!
+//! Each focus appends instructions to the strict/refine prompts and may set a
+//! severity floor for findings on its topic (matched by keywords in the title
+//! and body, on word boundaries). An empty focus list is the balanced default:
+//! no extra instructions, no floors. A security label
+//! (`ReviewOptions::security_labels`) applies the security floor even when
+//! security isn't in the focus list.
+
+use super::policy::{ParsedFinding, Severity};
+
+/// Area a review should emphasize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReviewFocus {
+    Security,
+    Performance,
+    Correctness,
+    Style,
+}
+
+impl ReviewFocus {
+    /// Parses a focus name (case-insensitive); `None` for unknown names.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "security" | "sec" => Some(Self::Security),
+            "performance" | "perf" => Some(Self::Performance),
+            "correctness" | "bugs" => Some(Self::Correctness),
+            "style" => Some(Self::Style),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Security => "security",
+            Self::Performance => "performance",
+            Self::Correctness => "correctness",
+            Self::Style => "style",
+        }
+    }
+
+    /// Prompt guidance for this focus.
+    fn instructions(self) -> &'static str {
+        match self {
+            Self::Security => {
+                "Prioritize injection, authentication/authorization gaps, secrets in code \
+                 or logs, unsafe deserialization, path traversal and weak cryptography."
+            }
+            Self::Performance => {
+                "Prioritize work repeated in loops or rebuilds, blocking I/O on hot or UI \
+                 paths, unbounded allocations or caches, N+1 queries and missing pagination."
+            }
+            Self::Correctness => {
+                "Prioritize logic errors, null/empty handling, off-by-one bounds, error \
+                 paths that are swallowed, races and resource leaks."
+            }
+            Self::Style => {
+                "Also report naming, readability, dead code and deviations from the \
+                 project's conventions; keep such findings at low severity."
+            }
+        }
+    }
+
+    /// Minimum severity for findings on this topic and the keywords that mark
+    /// them (whole words, plural `s` allowed; a trailing `*` marks a stem).
+    fn floor(self) -> Option<(Severity, &'static [&'static str])> {
+        match self {
+            Self::Security => Some((
+                Severity::Medium,
+                &[
+                    "security",
+                    "injection",
+                    "xss",
+                    "csrf",
+                    "secret",
+                    "credential",
+                    "password",
+                    "token",
+                    "authenticat*",
+                    "authoriz*",
+                    "permission",
+                    "traversal",
+                    "deserializ*",
+                    "crypto*",
+                    "vulnerab*",
+                ],
+            )),
+            Self::Performance => Some((
+                Severity::Medium,
+                &[
+                    "performance",
+                    "slow",
+                    "n+1",
+                    "quadratic",
+                    "allocation",
+                    "blocking",
+                    "memory leak",
+                    "unbounded",
+                ],
+            )),
+            Self::Correctness | Self::Style => None,
+        }
+    }
+}
+
+/// Appends a REVIEW FOCUS section for `focus`; balanced (empty) adds nothing.
+pub fn push_review_focus(prompt: &mut String, focus: &[ReviewFocus]) {
+    if focus.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = focus.iter().map(|f| f.label()).collect();
+    prompt.push_str(&format!("\n\nREVIEW FOCUS: {}.", names.join(", ")));
+    for f in focus {
+        prompt.push(' ');
+        prompt.push_str(f.instructions());
+    }
+    prompt.push('\n');
+}
+
+/// Raises `finding` to the floor of any focus whose keywords it mentions.
+pub fn apply_severity_floor(finding: &mut ParsedFinding, focus: &[ReviewFocus]) {
+    let text = format!("{}\n{}", finding.title, finding.body_markdown).to_ascii_lowercase();
+    for (floor, keywords) in focus.iter().filter_map(|f| f.floor()) {
+        if rank(finding.severity) < rank(floor) && keywords.iter().any(|k| mentions(&text, k)) {
+            finding.severity = floor;
+        }
+    }
+}
+
+/// True when `text` has `keyword` starting at a word boundary and, unless it
+/// is a `*` stem, ending at one (after an optional plural `s`).
+fn mentions(text: &str, keyword: &str) -> bool {
+    let (word, stem) = match keyword.strip_suffix('*') {
+        Some(w) => (w, true),
+        None => (keyword, false),
+    };
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(word).any(|(i, _)| {
+        let starts = !text[..i].chars().next_back().is_some_and(is_word);
+        let rest = &text[i + word.len()..];
+        let rest = rest.strip_prefix('s').unwrap_or(rest);
+        starts && (stem || !rest.chars().next().is_some_and(is_word))
+    })
+}
+
+fn rank(s: Severity) -> u8 {
+    match s {
+        Severity::Low => 0,
+        Severity::Medium => 1,
+        Severity::High => 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding(severity: Severity, title: &str) -> ParsedFinding {
+        ParsedFinding {
+            anchor: None,
+            severity,
+            title: title.into(),
+            body_markdown: "details".into(),
+            patch: None,
+            raw_block: String::new(),
+        }
+    }
+
+    #[test]
+    fn focus_changes_prompt_content() {
+        let mut balanced = String::from("base");
+        push_review_focus(&mut balanced, &[]);
+        assert_eq!(balanced, "base");
+
+        let mut security = String::from("base");
+        push_review_focus(&mut security, &[ReviewFocus::Security]);
+        assert!(security.contains("REVIEW FOCUS: security."));
+        assert!(security.contains("path traversal"));
+
+        let mut mixed = String::from("base");
+        push_review_focus(&mut mixed, &[ReviewFocus::Performance, ReviewFocus::Style]);
+        assert!(mixed.contains("REVIEW FOCUS: performance, style."));
+        assert!(mixed.contains("N+1 queries"));
+        assert!(!mixed.contains("path traversal"));
+    }
+
+    #[test]
+    fn performance_keywords_match_on_word_boundaries() {
+        let perf = [ReviewFocus::Performance];
+        for (title, expected) in [
+            ("Blocking call on the UI thread", Severity::Medium),
+            ("Slow query inside a loop", Severity::Medium),
+            ("Rename slowMode flag", Severity::Low),
+            ("Use BlockingQueue wrapper name", Severity::Low),
+            ("Avoid N+1 queries", Severity::Medium),
+        ] {
+            let mut f = finding(Severity::Low, title);
+            apply_severity_floor(&mut f, &perf);
+            assert_eq!(f.severity, expected, "{title}");
+        }
+    }
+
+    #[test]
+    fn security_focus_raises_security_findings_to_medium() {
+        let mut leak = finding(Severity::Low, "API token written to logs");
+        apply_severity_floor(&mut leak, &[]);
+        assert_eq!(leak.severity, Severity::Low);
+        apply_severity_floor(&mut leak, &[ReviewFocus::Security]);
+        assert_eq!(leak.severity, Severity::Medium);
+
+        let mut naming = finding(Severity::Low, "Rename variable");
+        apply_severity_floor(&mut naming, &[ReviewFocus::Security]);
+        assert_eq!(naming.severity, Severity::Low);
+
+        let mut high = finding(Severity::High, "SQL injection");
+        apply_severity_floor(&mut high, &[ReviewFocus::Security]);
+        assert_eq!(high.severity, Severity::High);
+
+        // Keywords match whole words: a tokenizer isn't a token leak.
+        let mut lexer = finding(Severity::Low, "Tokenizer drops trailing whitespace");
+        apply_severity_floor(&mut lexer, &[ReviewFocus::Security]);
+        assert_eq!(lexer.severity, Severity::Low);
+        let mut auth = finding(
+            Severity::Low,
+            "Unauthenticated route; missing authorization",
+        );
+        apply_severity_floor(&mut auth, &[ReviewFocus::Security]);
+        assert_eq!(auth.severity, Severity::Medium);
+
+        assert_eq!(ReviewFocus::parse(" Perf "), Some(ReviewFocus::Performance));
+        assert_eq!(ReviewFocus::parse("vibes"), None);
+    }
+}
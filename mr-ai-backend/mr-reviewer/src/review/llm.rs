@@ -33,11 +33,12 @@
 //! }
 //! ```
 
+use ai_llm_service::error_handler::{AiLlmError, ProviderErrorKind};
 use ai_llm_service::service_profiles::LlmServiceProfiles;
-use std::sync::Arc;
-use tracing::debug;
+use std::{future::Future, sync::Arc, time::Duration};
+use tracing::{debug, warn};
 
-use crate::errors::ProviderError;
+use crate::errors::{Error, ProviderError};
 
 /// Routing hint target granularity.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -152,38 +153,57 @@ pub struct LlmRouter {
     pub svc: Arc<LlmServiceProfiles>,
     /// Escalation policy knobs.
     pub policy: EscalationPolicy,
+    /// Upper bound for one generation, retry included (`None`: no limit).
+    pub call_timeout: Option<Duration>,
 }
 
 impl LlmRouter {
     /// Creates a new router using the provided shared profiles service.
     pub fn new(svc: Arc<LlmServiceProfiles>, policy: EscalationPolicy) -> Self {
-        Self { svc, policy }
+        Self {
+            svc,
+            policy,
+            call_timeout: None,
+        }
+    }
+
+    /// Fails generations that take longer than `timeout` with [`Error::LlmTimeout`].
+    pub fn with_call_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.call_timeout = timeout;
+        self
     }
 
     /// Generates with the **fast** profile.
     ///
+    /// An empty/whitespace-only or truncated completion is retried once with a
+    /// shortened prompt (see [`generate_non_empty`]).
+    ///
     /// # Errors
-    /// Maps [`AiLlmError`] into your crate's `Error` via `From`.
-    pub async fn generate_fast(&self, prompt: &str) -> Result<String, crate::errors::Error> {
+    /// - [`Error::LlmEmptyResponse`] if the retry is empty/truncated as well
+    /// - [`Error::LlmTimeout`] if [`LlmRouter::call_timeout`] elapsed
+    /// - transport failures map to `Provider` via `From<reqwest::Error>`
+    /// - other [`AiLlmError`]s map to `Provider(Forbidden)`
+    pub async fn generate_fast(&self, prompt: &str) -> Result<String, Error> {
         debug!("router: generate_fast");
-        self.svc
-            .generate_fast(prompt, None)
-            .await
-            .map_err(|_| crate::errors::Error::Provider(ProviderError::Forbidden))
+        let call = generate_non_empty("fast", prompt, |p| async move {
+            self.svc.generate_fast(&p, None).await
+        });
+        with_call_timeout("fast", self.call_timeout, call).await
     }
 
     /// Generates with the **slow** profile.
     ///
     /// If slow profile is not configured, the profiles service falls back to fast.
+    /// Empty completions are handled like in [`LlmRouter::generate_fast`].
     ///
     /// # Errors
-    /// Maps [`AiLlmError`] into your crate's `Error` via `From`.
-    pub async fn generate_slow(&self, prompt: &str) -> Result<String, crate::errors::Error> {
+    /// Same as [`LlmRouter::generate_fast`].
+    pub async fn generate_slow(&self, prompt: &str) -> Result<String, Error> {
         debug!("router: generate_slow");
-        self.svc
-            .generate_slow(prompt, None)
-            .await
-            .map_err(|_| crate::errors::Error::Provider(ProviderError::Forbidden))
+        let call = generate_non_empty("slow", prompt, |p| async move {
+            self.svc.generate_slow(&p, None).await
+        });
+        with_call_timeout("slow", self.call_timeout, call).await
     }
 
     /// Decide whether to escalate **after** FAST (legacy path).
@@ -266,3 +286,202 @@ fn rank(s: crate::review::policy::Severity) -> u8 {
         crate::review::policy::Severity::Low => 1,
     }
 }
+
+/// One generation attempt, classified.
+enum Attempt {
+    Text(String),
+    /// Empty/whitespace-only content or a stream cut mid-response.
+    Empty(String),
+    Failed(Error),
+}
+
+fn classify(res: Result<String, AiLlmError>) -> Attempt {
+    match res {
+        Ok(text) if text.trim().is_empty() => Attempt::Empty("empty completion".into()),
+        Ok(text) => Attempt::Text(text),
+        Err(AiLlmError::Provider(e))
+            if matches!(
+                e.kind,
+                ProviderErrorKind::Decode(_) | ProviderErrorKind::EmptyChoices
+            ) =>
+        {
+            Attempt::Empty(e.to_string())
+        }
+        Err(AiLlmError::Provider(e)) => match e.kind {
+            ProviderErrorKind::Transport(t) => transport(t),
+            _ => Attempt::Failed(Error::Provider(ProviderError::Forbidden)),
+        },
+        Err(AiLlmError::HttpTransport(e)) => transport(e),
+        Err(_) => Attempt::Failed(Error::Provider(ProviderError::Forbidden)),
+    }
+}
+
+/// A body cut mid-stream is a truncated completion; anything else (connect,
+/// DNS, timeout, status) is a real failure and must not be retried as empty.
+fn transport(e: reqwest::Error) -> Attempt {
+    if e.is_body() || e.is_decode() {
+        Attempt::Empty(format!("stream error: {e}"))
+    } else {
+        Attempt::Failed(Error::Provider(ProviderError::from(e)))
+    }
+}
+
+/// Run `call(prompt)`; on an empty or truncated completion retry once with
+/// [`shorten_prompt`], then give up with [`Error::LlmEmptyResponse`].
+pub(crate) async fn generate_non_empty<F, Fut>(
+    profile: &str,
+    prompt: &str,
+    mut call: F,
+) -> Result<String, Error>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<String, AiLlmError>>,
+{
+    let reason = match classify(call(prompt.to_string()).await) {
+        Attempt::Text(text) => return Ok(text),
+        Attempt::Failed(e) => return Err(e),
+        Attempt::Empty(reason) => reason,
+    };
+
+    let shorter = shorten_prompt(prompt);
+    warn!(
+        "router: {} returned no content ({}); retrying with shorter prompt ({} → {} chars)",
+        profile,
+        reason,
+        prompt.chars().count(),
+        shorter.chars().count()
+    );
+
+    match classify(call(shorter).await) {
+        Attempt::Text(text) => Ok(text),
+        Attempt::Failed(e) => Err(e),
+        Attempt::Empty(reason) => Err(Error::LlmEmptyResponse(format!("{profile}: {reason}"))),
+    }
+}
+
+/// Awaits `call`, giving up with [`Error::LlmTimeout`] once `timeout` elapses.
+pub(crate) async fn with_call_timeout<Fut>(
+    profile: &str,
+    timeout: Option<Duration>,
+    call: Fut,
+) -> Result<String, Error>
+where
+    Fut: Future<Output = Result<String, Error>>,
+{
+    let Some(limit) = timeout else {
+        return call.await;
+    };
+    match tokio::time::timeout(limit, call).await {
+        Ok(res) => res,
+        Err(_) => {
+            warn!(
+                "router: {} call exceeded {} ms, abandoning it",
+                profile,
+                limit.as_millis()
+            );
+            Err(Error::LlmTimeout(format!(
+                "{profile} after {} ms",
+                limit.as_millis()
+            )))
+        }
+    }
+}
+
+/// Marker inserted where the middle of a prompt was cut for the retry.
+const TRUNCATION_MARKER: &str = "\n[... context truncated ...]\n";
+
+/// Drop the middle third of `prompt` (usually retrieved context) while keeping the
+/// instructions at the head and the target/output contract at the tail.
+fn shorten_prompt(prompt: &str) -> String {
+    let chars: Vec<char> = prompt.chars().collect();
+    let third = chars.len() / 3;
+    if third == 0 {
+        return prompt.to_string();
+    }
+    let head: String = chars[..third].iter().collect();
+    let tail: String = chars[chars.len() - third..].iter().collect();
+    format!("{head}{TRUNCATION_MARKER}{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn empty_completion_is_retried_with_shorter_prompt() {
+        let prompts = Mutex::new(Vec::<String>::new());
+        let prompt = "instructions\n".to_string() + &"context line\n".repeat(50) + "answer as JSON";
+
+        let out = generate_non_empty("fast", &prompt, |p| {
+            let n = {
+                let mut seen = prompts.lock().unwrap();
+                seen.push(p);
+                seen.len()
+            };
+            async move { Ok(if n == 1 { "  \n".into() } else { "{}".into() }) }
+        })
+        .await
+        .unwrap();
+
+        let seen = prompts.lock().unwrap();
+        assert_eq!(out, "{}");
+        assert_eq!(seen.len(), 2);
+        assert!(seen[1].len() < seen[0].len());
+        assert!(seen[1].starts_with("instructions"));
+        assert!(seen[1].ends_with("answer as JSON"));
+    }
+
+    #[tokio::test]
+    async fn persistent_empty_completion_is_llm_empty_response() {
+        let mut calls = 0;
+        let err = generate_non_empty("slow", "prompt", |_| {
+            calls += 1;
+            async { Ok(String::new()) }
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(calls, 2);
+        assert!(matches!(err, Error::LlmEmptyResponse(_)));
+    }
+
+    #[tokio::test]
+    async fn transport_failure_is_not_retried_as_empty() {
+        let refused = reqwest::Client::new()
+            .get("http://127.0.0.1:1/")
+            .send()
+            .await
+            .unwrap_err();
+        let mut err = Some(AiLlmError::HttpTransport(refused));
+        let mut calls = 0;
+        let out = generate_non_empty("fast", "prompt", |_| {
+            calls += 1;
+            let res = err.take().map_or(Ok("{}".to_string()), Err);
+            async move { res }
+        })
+        .await;
+
+        assert_eq!(calls, 1);
+        assert!(matches!(
+            out,
+            Err(Error::Provider(ProviderError::Network(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn hung_generation_times_out() {
+        let hung = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok("late".to_string())
+        };
+        let err = with_call_timeout("fast", Some(Duration::from_millis(20)), hung)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::LlmTimeout(ref m) if m.starts_with("fast")));
+
+        let quick = async { Ok("ok".to_string()) };
+        let out = with_call_timeout("slow", Some(Duration::from_secs(5)), quick).await;
+        assert_eq!(out.unwrap(), "ok");
+    }
+}
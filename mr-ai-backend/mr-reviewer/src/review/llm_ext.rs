@@ -3,8 +3,8 @@
 
 use ai_llm_service::service_profiles::LlmServiceProfiles;
 use serde::{Deserialize, Serialize};
+use std::fs;
 use std::sync::Arc;
-use std::{fs, path::PathBuf};
 use tracing::{debug, warn};
 
 use crate::errors::Error;
@@ -176,10 +176,7 @@ fn cleanup_json_like(s: &str) -> String {
 /// Write bytes into code_data/mr_tmp/<short_sha>/rag/<idx>_<name>
 fn dump_bytes(trace: &TraceCtx, name: &str, data: &[u8]) -> std::io::Result<()> {
     let short = short_sha(&trace.head_sha);
-    let dir = PathBuf::from("code_data")
-        .join("mr_tmp")
-        .join(short)
-        .join("rag");
+    let dir = services::data_root::mr_tmp_dir().join(short).join("rag");
     fs::create_dir_all(&dir)?;
     let file = dir.join(format!("{}_{name}", trace.item_idx));
     fs::write(file, data)?;
@@ -3,38 +3,57 @@
 //! Improvements (language-agnostic):
 //! - Pre-routing: optionally go directly to the SLOW model (skips FAST) for risky targets.
 //! - Better re-anchoring using patch blocks and signature scanning.
-//! - **Prefer ADDED lines** → exact single-line anchors where possible.
+//! - **Prefer ADDED lines** → exact single-line anchors where possible; symbol
+//!   targets prefer a changed declaration line (`ReviewOptions::symbol_anchor`).
 //! - Full-file read-only context for global checks (imports/symbols).
 //! - Generic "unused import" false-positive guard based on usage evidence.
 //! - Patch sanity check: strip non-applicable PATCH blocks.
-//! - Deduplication of overlapping/duplicate issues.
+//! - SLOW outages degrade to the FAST finding (lower confidence) instead of failing.
+//! - Unparseable FAST output gets one lean format-reminder retry before escalation.
+//! - Deduplication of overlapping/duplicate issues (local pass first, then LLM).
 
+pub mod artifacts;
 pub mod context;
 mod dedup_llm;
+mod dedup_local;
+pub mod focus;
 pub mod llm;
 mod llm_ext;
 pub mod policy;
 mod preq;
 pub mod prompt;
 mod rag_support;
+pub mod sarif;
+mod suggestion;
 mod util;
 
-use crate::errors::MrResult;
+pub(crate) use util::{fence_lang, lang_from_path};
+
+use crate::errors::{Error, MrResult};
+use crate::git_providers::ProviderKind;
+use crate::lang::SkippedFile;
 use crate::map::TargetRef;
 use crate::review::dedup_llm::dedup_drafts_llm_async;
 use crate::review::llm::EscalationPolicy;
 use crate::review::llm_ext::TraceCtx;
-use crate::{ReviewPlan, telemetry::prompt_dump::dump_prompt_for_target};
+use crate::{LabelGate, ReviewOptions, ReviewPlan, telemetry::prompt_dump::dump_prompt_for_target};
 
 use ai_llm_service::service_profiles::LlmServiceProfiles;
 use context::{
-    AnchorRange, PrimaryCtx, collect_added_lines, infer_anchor_by_signature,
+    AnchorPreference, AnchorRange, ImportGraph, collect_added_lines, infer_anchor_by_signature,
     infer_anchor_prefer_added, patch_applies_to_head, reanchor_via_patch,
     unused_import_claim_is_false_positive,
 };
+use focus::{ReviewFocus, apply_severity_floor, push_review_focus};
 use llm::LlmRouter;
-use policy::{ParsedFinding, Severity, parse_and_validate};
-use prompt::{build_refine_prompt, build_strict_prompt};
+use policy::{
+    ParsedFinding, Severity, SeverityMap, apply_finding_policy, is_malformed_output,
+    parse_and_validate,
+};
+use prompt::{
+    PromptTrim, build_format_retry_prompt, build_intent_section, build_refine_prompt,
+    build_strict_prompt, trim_to_prompt_budget,
+};
 use serde::Serialize;
 
 use std::sync::Arc;
@@ -52,12 +71,14 @@ pub struct DraftComment {
     pub body_markdown: String,
     /// Normalized severity.
     pub severity: Severity,
+    /// Heuristic confidence in [0..1] (same value as in the step-4 report).
+    pub confidence: f32,
     /// Short preview for logs/telemetry.
     pub preview: String,
 }
 
 /// Read-only related code chunk (goes into the RELATED section of the prompt).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RelatedBlock {
     /// Repo-relative path of the snippet source.
     pub path: String,
@@ -93,6 +114,22 @@ struct Step4ItemReport {
     body_len: usize,
     body_markdown: String,
     preview: String,
+    /// Why the draft was dropped after the model produced it (if it was).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drop_reason: Option<String>,
+    /// Read-only context dropped to fit `ContextOptions::max_prompt_chars`.
+    #[serde(flatten, skip_serializing_if = "PromptTrim::is_empty")]
+    context_trim: PromptTrim,
+    /// true if an unparseable FAST answer was retried with the format reminder.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    format_retry: bool,
+}
+
+/// Per-target facts that apply to all of its report rows.
+#[derive(Debug, Clone, Copy, Default)]
+struct ItemNotes {
+    context_trim: PromptTrim,
+    format_retry: bool,
 }
 
 #[derive(Serialize)]
@@ -106,6 +143,48 @@ struct Step4Report {
     items: Vec<Step4ItemReport>,
 }
 
+/// Machine-readable summary of the step-4 run (mirrors the report header).
+#[derive(Debug, Clone, Serialize)]
+pub struct Step4Summary {
+    pub head_sha: String,
+    pub targets_total: usize,
+    pub drafts_total: usize,
+    pub escalated_total: usize,
+    pub fast_only_total: usize,
+    pub elapsed_ms: u128,
+    /// Where `step4_report.json` was written (`None` if writing failed).
+    pub report_path: Option<PathBuf>,
+    /// Where the SARIF export of the drafts was written (see [`sarif_path`]).
+    pub sarif_path: Option<PathBuf>,
+    /// Changed files step 2 couldn't parse cleanly; comments on them may be imprecise.
+    pub parse_failures: Vec<String>,
+    /// Changed files step 2 didn't index (missing, binary or non-UTF-8), with
+    /// the reason. Step 3 builds no added-line targets for them, so only
+    /// their removals (if any) are reviewed.
+    pub skipped_files: Vec<SkippedFile>,
+}
+
+/// Step-4 output: draft comments plus the run summary.
+#[derive(Debug, Clone)]
+pub struct Step4Output {
+    pub drafts: Vec<DraftComment>,
+    pub summary: Step4Summary,
+}
+
+/// Context actually sent to the model for one target (`target_context.json`).
+#[derive(Serialize)]
+struct TargetContextDump<'a> {
+    idx: usize,
+    path: Option<&'a str>,
+    snippet_hash: &'a str,
+    /// RELATED blocks (symbol neighbours + preq RAG hits).
+    related_blocks: &'a [RelatedBlock],
+    /// RAG chunks appended to the strict prompt.
+    rag_chunks: &'a [crate::review::rag_support::RagChunk],
+    /// True if the whole HEAD file was included read-only.
+    full_file_included: bool,
+}
+
 /// Light hint about the target to drive pre-routing.
 #[derive(Debug, Clone, Copy)]
 enum TargetKindHint {
@@ -116,6 +195,20 @@ enum TargetKindHint {
     Global,
 }
 
+/// Confidence cut for a FAST finding kept because the SLOW refine failed.
+const SLOW_FAILED_CONF_PENALTY: f32 = 0.15;
+
+/// What a SLOW refine contributed to a target (see [`merge_slow_refine`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SlowOutcome {
+    /// SLOW returned a finding; the better of FAST and SLOW was kept.
+    Found,
+    /// SLOW found nothing; the FAST finding (if any) stands.
+    Empty,
+    /// SLOW errored; the FAST finding (if any) stands.
+    Failed,
+}
+
 /// Local router decision used by this orchestrator.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum RouteDecision {
@@ -124,11 +217,20 @@ enum RouteDecision {
 }
 
 /// Build draft comments (step 4).
+///
+/// Parsed findings go through `opts.finding_policy` before selection. Drafts
+/// whose final anchor covers no added line are dropped unless
+/// `opts.allow_context_anchors` is set.
+///
+/// Also writes `step4_report.json` (see [`report_dir`]) and the drafts as
+/// SARIF (see [`sarif_path`]) and returns both paths in the summary.
 pub async fn build_draft_comments(
     plan: &ReviewPlan,
     svc: Arc<LlmServiceProfiles>,
-) -> MrResult<Vec<DraftComment>> {
-    let router = LlmRouter::new(svc.clone(), EscalationPolicy::from_env());
+    opts: &ReviewOptions,
+) -> MrResult<Step4Output> {
+    let router = LlmRouter::new(svc.clone(), EscalationPolicy::from_env())
+        .with_call_timeout(opts.llm_call_timeout);
 
     let t0 = Instant::now();
     debug!("step4: build draft comments (context → prompt → llm → policy)");
@@ -136,8 +238,61 @@ pub async fn build_draft_comments(
     let mut drafts: Vec<DraftComment> = Vec::new();
     let mut used_slow = 0usize;
     let head_sha = plan.bundle.meta.diff_refs.head_sha.clone();
+    // GitLab renders ```suggestion blocks as one-click apply.
+    let use_suggestions = plan.bundle.meta.provider == ProviderKind::GitLab;
+    let ctx_opts = context::ContextOptions::from_env();
+    debug!("step4: context options {:?}", ctx_opts);
+    let severity_map = SeverityMap::from_env();
+    // A security label forces SLOW for every target (see `decide_initial_route`).
+    let security_label = match opts.label_gate(&plan.bundle.meta.labels) {
+        LabelGate::Security(label) => Some(label),
+        LabelGate::Skip(_) | LabelGate::Default => None,
+    };
+    // One focus list for prompts and severity floors: the requested focus, the
+    // areas named in `.mrai.toml`, and security when the MR carries the label.
+    let (repo_focus, repo_focus_text) = plan.repo_config.focus_areas();
+    let mut focus = opts.focus.clone();
+    for f in repo_focus
+        .into_iter()
+        .chain(security_label.is_some().then_some(ReviewFocus::Security))
+    {
+        if !focus.contains(&f) {
+            focus.push(f);
+        }
+    }
+    let push_focus = |prompt: &mut String| {
+        push_review_focus(prompt, &focus);
+        push_security_focus(prompt, security_label.as_deref());
+        // Repository-configured wording beyond the known focus names.
+        if let Some(text) = repo_focus_text {
+            prompt.push_str("\n\nREVIEW FOCUS (from repository config): ");
+            prompt.push_str(text);
+            prompt.push('\n');
+        }
+    };
+    let intent = if opts.include_mr_description {
+        let meta = &plan.bundle.meta;
+        build_intent_section(&meta.title, meta.description.as_deref())
+    } else {
+        None
+    };
+    debug!("step4: MR intent section included={}", intent.is_some());
+
+    // Indexer import graph for the reviewed files (usage evidence for the
+    // "unused import" guard); absent when the project has not been indexed.
+    let mut target_paths: Vec<&str> = plan
+        .targets
+        .iter()
+        .map(|t| crate::map::target_path(&t.target))
+        .filter(|p| !p.is_empty())
+        .collect();
+    target_paths.sort_unstable();
+    target_paths.dedup();
+    let import_graph = ImportGraph::from_env(&target_paths);
 
     let mut rows: Vec<Step4ItemReport> = Vec::with_capacity(plan.targets.len());
+    // Per-target facts copied into every report row of that target at the end.
+    let mut notes: Vec<ItemNotes> = vec![ItemNotes::default(); plan.targets.len()];
 
     for (idx, tgt) in plan.targets.iter().enumerate() {
         let t_item = Instant::now();
@@ -147,7 +302,7 @@ pub async fn build_draft_comments(
         };
 
         // 1) Build context (HEAD/PRIMARY).
-        let ctx: PrimaryCtx = match context::build_primary_ctx(&head_sha, tgt, &plan.symbols) {
+        let mut ctx = match context::build_primary_ctx(&head_sha, tgt, &plan.symbols, &ctx_opts) {
             Ok(c) => c,
             Err(e) => {
                 // Gracefully drop only this target when the HEAD file wasn't materialized.
@@ -196,6 +351,7 @@ pub async fn build_draft_comments(
             head_sha: &head_sha,
             idx,
             ctx: &ctx,
+            target: &tgt.target,
             local_window_numbered: &ctx.numbered_snippet, // already numbered HEAD
             allowed_anchors: &allowed,
             target_path: crate::review::target_path(&tgt.target),
@@ -219,12 +375,9 @@ pub async fn build_draft_comments(
                 why: Some(h.why),
             });
         }
-        let related_present = !related.is_empty() || ctx.full_file_readonly.is_some();
-
-        // 2) Build initial strict prompt (FAST flavor; reused for confidence scoring).
-        //    Optionally augment with RAG based on FAST hint (ask first, then rebuild prompt).
-        let mut base_prompt = build_strict_prompt(tgt, &ctx, &related);
 
+        // 2) Build the strict prompt (FAST flavor; reused for confidence scoring),
+        //    augmented with RAG based on a FAST hint (ask first, then build).
         // Ask FAST for RAG hints (safe to run in build-only mode; we skip only final generations).
         let rag_hints = match crate::review::llm_ext::ask_rag_hints_fast(
             svc.clone(),
@@ -239,7 +392,7 @@ pub async fn build_draft_comments(
         };
 
         // Try fetching small RAG chunks (replace NoopRag with a real searcher later).
-        let rag_chunks = crate::review::rag_support::search_with_hints(
+        let mut rag_chunks = crate::review::rag_support::search_with_hints(
             &crate::review::rag_support::NoopRag,
             &rag_hints,
             6,
@@ -248,13 +401,58 @@ pub async fn build_draft_comments(
             // Dump chosen chunks for traceability
             let _ = crate::review::rag_support::dump_rag_chunks(&head_sha, idx, &rag_chunks);
         }
-        if !rag_chunks.is_empty() {
-            let rag_block = crate::review::rag_support::format_rag_chunks_for_prompt(&rag_chunks);
-            base_prompt.push_str("\n\n");
-            base_prompt.push_str(&rag_block);
+
+        let fast_prompt =
+            |ctx: &context::PrimaryCtx,
+             related: &[RelatedBlock],
+             rag_chunks: &[crate::review::rag_support::RagChunk]| {
+                let mut p = build_strict_prompt(tgt, ctx, related, intent.as_deref());
+                if !rag_chunks.is_empty() {
+                    p.push_str("\n\n");
+                    p.push_str(&crate::review::rag_support::format_rag_chunks_for_prompt(
+                        rag_chunks,
+                    ));
+                }
+                push_focus(&mut p);
+                p
+            };
+        let mut prompt = fast_prompt(&ctx, &related, &rag_chunks);
+
+        // Keep the prompt within budget by shedding the least useful read-only context.
+        let trim = trim_to_prompt_budget(
+            prompt.chars().count(),
+            ctx_opts.max_prompt_chars,
+            &mut related,
+            &mut rag_chunks,
+            &mut ctx.full_file_readonly,
+        );
+        if !trim.is_empty() {
+            info!(
+                "step4: target #{} over prompt budget ({} chars): dropped rag={} related={} full_file={}",
+                idx,
+                ctx_opts.max_prompt_chars,
+                trim.rag_dropped,
+                trim.related_dropped,
+                trim.full_file_dropped
+            );
+            prompt = fast_prompt(&ctx, &related, &rag_chunks);
+        }
+        notes[idx].context_trim = trim;
+        let related_present = !related.is_empty() || ctx.full_file_readonly.is_some();
+
+        // Persist exactly what context goes into the prompts for this target (audit trail).
+        let dump = TargetContextDump {
+            idx,
+            path: target_path(&tgt.target),
+            snippet_hash: &tgt.snippet_hash,
+            related_blocks: &related,
+            rag_chunks: &rag_chunks,
+            full_file_included: ctx.full_file_readonly.is_some(),
+        };
+        if let Err(e) = write_target_context(&head_sha, idx, &dump) {
+            warn!("step4: failed to write target context #{}: {}", idx, e);
         }
 
-        let prompt = base_prompt;
         let prompt_chars = prompt.chars().count();
         let prompt_tokens_approx = prompt_chars / 4;
 
@@ -262,7 +460,8 @@ pub async fn build_draft_comments(
         dump_prompt_for_target(&head_sha, idx, "fast", tgt, &prompt, prompt_tokens_approx);
 
         // We don't have a previous draft here; build a generic refine prompt.
-        let refine = build_refine_prompt(None, tgt, &ctx, &related);
+        let mut refine = build_refine_prompt(None, tgt, &ctx, &related, intent.as_deref());
+        push_focus(&mut refine);
         let refine_tokens = refine.chars().count() / 4;
         dump_prompt_for_target(&head_sha, idx, "slow", tgt, &refine, refine_tokens);
 
@@ -298,7 +497,13 @@ pub async fn build_draft_comments(
             TargetRef::File { .. } => TargetKindHint::File,
             TargetRef::Global => TargetKindHint::Global,
         };
-        let pre_route = decide_initial_route(&router, tk_hint, prompt_tokens_approx, used_slow);
+        let pre_route = decide_initial_route(
+            &router.policy,
+            tk_hint,
+            prompt_tokens_approx,
+            used_slow,
+            security_label.is_some(),
+        );
 
         // 3) Run LLM(s) according to the route.
         let mut fast_ms: u128 = 0;
@@ -307,32 +512,96 @@ pub async fn build_draft_comments(
         let mut best: Option<ParsedFinding> = None;
 
         let mut slow_invoked_for_item = false; // true if SLOW was called in any mode
+        // Why a model call produced no content (`llm_empty` / `llm_timeout`).
+        let mut llm_gap: Option<&'static str> = None;
+        let mut slow_failed = false; // true if SLOW errored and FAST's answer stands
 
         match pre_route {
             RouteDecision::Slow => {
                 slow_invoked_for_item = true;
                 used_slow += 1;
+                metrics::counter!("slow_escalations_total", "reason" => "pre_route").increment(1);
                 // Direct to SLOW: we don't have a previous draft, so pass None to refine.
-                let refine = build_refine_prompt(None, tgt, &ctx, &related);
+                let mut refine = build_refine_prompt(None, tgt, &ctx, &related, intent.as_deref());
+                push_focus(&mut refine);
                 let refine_tokens = refine.chars().count() / 4;
                 dump_prompt_for_target(&head_sha, idx, "slow", tgt, &refine, refine_tokens);
 
                 let t_slow = Instant::now();
-                let slow_raw = router.generate_slow(&refine).await?;
+                let slow_res =
+                    empty_as_blank(idx, router.generate_slow(&refine).await, &mut llm_gap);
                 slow_ms = Some(t_slow.elapsed().as_millis());
 
-                best = pick_best(parse_and_validate(&slow_raw, &ctx.allowed_anchors));
-                if best.is_some() {
-                    escalated = true;
-                    used_slow += 1;
+                match slow_res {
+                    Ok(slow_raw) => {
+                        best = pick_best(apply_finding_policy(
+                            parse_and_validate(&slow_raw, &ctx.allowed_anchors, &severity_map),
+                            opts.finding_policy.as_ref(),
+                        ));
+                        if best.is_some() {
+                            escalated = true;
+                            used_slow += 1;
+                        }
+                    }
+                    Err(e) => {
+                        // SLOW is down: answer with FAST; fail only if FAST is down too.
+                        warn!(
+                            "step4: target #{} SLOW failed, falling back to FAST: {}",
+                            idx, e
+                        );
+                        slow_failed = true;
+                        let t_fast = Instant::now();
+                        let fast_raw =
+                            empty_as_blank(idx, router.generate_fast(&prompt).await, &mut llm_gap)?;
+                        fast_ms = t_fast.elapsed().as_millis();
+                        best = pick_best(apply_finding_policy(
+                            parse_and_validate(&fast_raw, &ctx.allowed_anchors, &severity_map),
+                            opts.finding_policy.as_ref(),
+                        ));
+                    }
                 }
             }
             RouteDecision::Fast => {
-                // Regular FAST path.
+                // Regular FAST path; a FAST error leaves SLOW to answer alone.
                 let t_fast = Instant::now();
-                let fast_raw = router.generate_fast(&prompt).await?;
+                let fast_res =
+                    empty_as_blank(idx, router.generate_fast(&prompt).await, &mut llm_gap);
                 fast_ms = t_fast.elapsed().as_millis();
-                best = pick_best(parse_and_validate(&fast_raw, &ctx.allowed_anchors));
+                let fast_failed = match fast_res {
+                    Ok(fast_raw) => {
+                        // A formatting slip gets one lean retry before SLOW is considered.
+                        let router = &router;
+                        let (fast_raw, retried) = retry_if_malformed(
+                            idx,
+                            fast_raw,
+                            matches!(tgt.target, TargetRef::File { .. }),
+                            || {
+                                let p = build_format_retry_prompt(tgt, &ctx);
+                                dump_prompt_for_target(
+                                    &head_sha,
+                                    idx,
+                                    "fast_retry",
+                                    tgt,
+                                    &p,
+                                    p.chars().count() / 4,
+                                );
+                                p
+                            },
+                            |p| async move { router.generate_fast(&p).await },
+                        )
+                        .await;
+                        notes[idx].format_retry = retried;
+                        best = pick_best(apply_finding_policy(
+                            parse_and_validate(&fast_raw, &ctx.allowed_anchors, &severity_map),
+                            opts.finding_policy.as_ref(),
+                        ));
+                        None
+                    }
+                    Err(e) => {
+                        warn!("step4: target #{} FAST failed, trying SLOW: {}", idx, e);
+                        Some(e)
+                    }
+                };
 
                 // Optional SLOW refine if policy requires it.
                 let should_escalate = || {
@@ -346,32 +615,48 @@ pub async fn build_draft_comments(
                     router.should_escalate(sev, conf, prompt_tokens_approx, used_slow)
                 };
 
-                if best.is_none() || should_escalate() {
+                // A timed-out FAST call isn't followed by SLOW: move on to the next target.
+                let timed_out = llm_gap == Some(LLM_TIMEOUT);
+                if !timed_out && (best.is_none() || should_escalate()) {
                     slow_invoked_for_item = true;
                     used_slow += 1; // we write off the budget for the call
+                    metrics::counter!("slow_escalations_total", "reason" => "refine").increment(1);
 
-                    let refine = build_refine_prompt(best.as_ref(), tgt, &ctx, &related);
+                    let mut refine =
+                        build_refine_prompt(best.as_ref(), tgt, &ctx, &related, intent.as_deref());
+                    push_focus(&mut refine);
                     let refine_tokens = refine.chars().count() / 4;
                     dump_prompt_for_target(&head_sha, idx, "slow", tgt, &refine, refine_tokens);
 
                     let t_slow = Instant::now();
-                    let slow_raw = router.generate_slow(&refine).await?;
+                    let slow_res =
+                        empty_as_blank(idx, router.generate_slow(&refine).await, &mut llm_gap);
                     slow_ms = Some(t_slow.elapsed().as_millis());
 
-                    let refined = pick_best(parse_and_validate(&slow_raw, &ctx.allowed_anchors));
-                    match (best.take(), refined) {
-                        (None, Some(r)) => {
-                            best = Some(r);
-                            escalated = true;
-                            used_slow += 1;
+                    let refined = match (slow_res, fast_failed) {
+                        // Both models unreachable: nothing to degrade to.
+                        (Err(e), Some(_)) => return Err(e),
+                        (Err(e), None) => {
+                            warn!(
+                                "step4: target #{} SLOW failed, keeping FAST result: {}",
+                                idx, e
+                            );
+                            Err(e)
                         }
-                        (Some(a), Some(b)) => {
-                            best = Some(if better(&a, &b) { b } else { a });
+                        (Ok(slow_raw), _) => Ok(pick_best(apply_finding_policy(
+                            parse_and_validate(&slow_raw, &ctx.allowed_anchors, &severity_map),
+                            opts.finding_policy.as_ref(),
+                        ))),
+                    };
+                    let (merged, outcome) = merge_slow_refine(best.take(), refined);
+                    best = merged;
+                    match outcome {
+                        SlowOutcome::Found => {
                             escalated = true;
                             used_slow += 1;
                         }
-                        (Some(a), None) => best = Some(a),
-                        (None, None) => {}
+                        SlowOutcome::Empty => {}
+                        SlowOutcome::Failed => slow_failed = true,
                     }
                 }
             }
@@ -379,7 +664,7 @@ pub async fn build_draft_comments(
 
         // 4) Drop when nothing valid came back.
         let Some(mut finding) = best else {
-            rows.push(make_report_row(
+            let mut row = make_report_row(
                 idx,
                 &tgt.target,
                 &tgt.snippet_hash,
@@ -394,10 +679,18 @@ pub async fn build_draft_comments(
                 0,
                 String::new(),
                 &tgt.preview,
-            ));
+            );
+            if slow_failed {
+                row.drop_reason = Some("slow_unavailable".into());
+            } else if let Some(gap) = llm_gap {
+                row.drop_reason = Some(gap.into());
+            }
+            rows.push(row);
             continue;
         };
 
+        apply_severity_floor(&mut finding, &focus);
+
         // 5) Anchoring: patch → prefer added → signature.
         let path_opt = target_path(&tgt.target);
         let mut anchor: Option<AnchorRange> = finding.anchor;
@@ -425,20 +718,10 @@ pub async fn build_draft_comments(
                 );
             }
 
-            // Prefer a *single added line* if possible.
+            // Prefer a *single line*: the changed declaration or the first added line.
             let added = collect_added_lines(&plan.bundle.changes, path);
-            if let Some(a) = anchor {
-                if a.start < a.end {
-                    if let Some(&first_added) =
-                        added.iter().find(|&&ln| ln >= a.start && ln <= a.end)
-                    {
-                        anchor = Some(AnchorRange {
-                            start: first_added,
-                            end: first_added,
-                        });
-                    }
-                }
-            }
+            let (pref, decl_line) = anchor_preference_for(tgt, opts);
+            anchor = preferred_anchor(anchor, &added, decl_line, pref);
             if anchor.is_none() {
                 anchor = infer_anchor_prefer_added(
                     &head_sha,
@@ -453,6 +736,40 @@ pub async fn build_draft_comments(
 
         finding.anchor = anchor;
 
+        // 5.1) Never comment on code the author didn't touch.
+        if let Some(path) = path_opt.filter(|_| !opts.allow_context_anchors) {
+            let effective = finding.anchor.or(match &tgt.target {
+                TargetRef::Line { line, .. } => Some(AnchorRange {
+                    start: *line,
+                    end: *line,
+                }),
+                _ => None,
+            });
+            let added = collect_added_lines(&plan.bundle.changes, path);
+            if let Some(reason) = context_anchor_drop_reason(effective, &added) {
+                debug!("step4: drop idx={} for {}: {}", idx, path, reason);
+                let mut row = make_report_row(
+                    idx,
+                    &tgt.target,
+                    &tgt.snippet_hash,
+                    finding.anchor,
+                    "Dropped",
+                    0.0,
+                    prompt_tokens_approx,
+                    slow_invoked_for_item,
+                    fast_ms,
+                    slow_ms,
+                    related_present,
+                    finding.body_markdown.len(),
+                    finding.body_markdown.clone(),
+                    &tgt.preview,
+                );
+                row.drop_reason = Some(reason);
+                rows.push(row);
+                continue;
+            }
+        }
+
         // 6) Generic "unused import" false-positive guard.
         if finding.title.to_ascii_lowercase().contains("unused import")
             || finding
@@ -464,8 +781,9 @@ pub async fn build_draft_comments(
                 if unused_import_claim_is_false_positive(
                     &head_sha,
                     path,
-                    ctx.full_file_readonly.as_deref(),
+                    ctx.full_file_for_evidence(),
                     &ctx.numbered_snippet,
+                    import_graph.as_ref(),
                 ) {
                     debug!("step4: drop false-positive 'unused import' for {}", path);
                     rows.push(make_report_row(
@@ -498,10 +816,13 @@ pub async fn build_draft_comments(
                 conf = (conf - 0.2).max(0.0);
             }
         }
+        if slow_failed {
+            conf = (conf - SLOW_FAILED_CONF_PENALTY).max(0.0);
+        }
 
         // 8) Build final target ref:
         //    - single-line anchor → TargetRef::Line
-        //    - range anchor → TargetRef::Range (publisher will post on start_line)
+        //    - range anchor → TargetRef::Range (publisher anchors on end_line)
         let (final_target, anchor_start, anchor_end) =
             match (path_opt.map(|s| s.to_string()), finding.anchor) {
                 (Some(p), Some(a)) if a.start == a.end => (
@@ -536,7 +857,7 @@ pub async fn build_draft_comments(
             };
 
         // 9) Final draft.
-        let body_md = to_markdown(&finding);
+        let body_md = to_markdown(&finding, use_suggestions, fence_lang(path_opt).as_deref());
         let preview = truncate(&body_md, 140);
 
         drafts.push(DraftComment {
@@ -544,6 +865,7 @@ pub async fn build_draft_comments(
             snippet_hash: tgt.snippet_hash.clone(),
             body_markdown: body_md.clone(),
             severity: finding.severity,
+            confidence: conf,
             preview: preview.clone(),
         });
 
@@ -574,6 +896,11 @@ pub async fn build_draft_comments(
         );
     }
 
+    // Cheap local dedup first (same anchor / near-identical text), no LLM calls.
+    let removed =
+        dedup_local::dedup_drafts_local(&mut drafts, dedup_local::sim_threshold_from_env());
+    debug!("step4: local dedup removed {} drafts", removed);
+
     // LLM-assisted deduplication (FAST model). Budget keeps it cheap.
     let dedup_budget: usize = std::env::var("REVIEW_DEDUP_LLM_BUDGET")
         .ok()
@@ -603,6 +930,11 @@ pub async fn build_draft_comments(
         .filter(|r| r.severity != "Dropped" && !r.escalated)
         .count();
 
+    for row in &mut rows {
+        row.context_trim = notes[row.idx].context_trim;
+        row.format_retry = notes[row.idx].format_retry;
+    }
+
     // Persist JSON report for operator insight.
     let report = Step4Report {
         head_sha: head_sha.clone(),
@@ -613,30 +945,70 @@ pub async fn build_draft_comments(
         elapsed_ms: elapsed,
         items: rows,
     };
-    if let Err(e) = write_report(&head_sha, &report) {
-        warn!("step4: failed to write report: {}", e);
-    }
+    let report_path = match write_report(&head_sha, &report) {
+        Ok(p) => Some(p),
+        Err(e) => {
+            warn!("step4: failed to write report: {}", e);
+            None
+        }
+    };
+
+    let sarif_path = match write_sarif(&head_sha, &drafts) {
+        Ok(p) => Some(p),
+        Err(e) => {
+            warn!("step4: failed to write SARIF report: {}", e);
+            None
+        }
+    };
+
+    let summary = Step4Summary {
+        head_sha: report.head_sha,
+        targets_total: report.targets_total,
+        drafts_total: report.drafts_total,
+        escalated_total: report.escalated_total,
+        fast_only_total: report.fast_only_total,
+        elapsed_ms: report.elapsed_ms,
+        report_path,
+        sarif_path,
+        parse_failures: plan.symbols.parse_failures.clone(),
+        skipped_files: plan.symbols.skipped_files.clone(),
+    };
+
+    Ok(Step4Output { drafts, summary })
+}
 
-    Ok(drafts)
+/// Notes which MR label requested the security focus; its instructions come
+/// with the focus list (see [`push_review_focus`]).
+fn push_security_focus(prompt: &mut String, label: Option<&str>) {
+    if let Some(label) = label {
+        prompt.push_str(&format!(
+            "Security focus requested by MR label '{label}'.\n"
+        ));
+    }
 }
 
 // ---------------- pre-routing logic ----------------
 
 /// Decide whether to go directly to SLOW before running FAST.
 /// Heuristics:
+/// - `force_slow` (security label on the MR) → SLOW, regardless of budget.
 /// - Respect the router policy gate (min severity).
 /// - Very long prompts (tokens > policy.long_prompt_tokens) → SLOW.
 /// - Symbol targets are more error-prone → prefer SLOW when the gate passes.
 /// - Wide ranges (span_lines >= 80) also prefer SLOW when the gate passes.
 /// - Otherwise default to FAST.
 fn decide_initial_route(
-    router: &LlmRouter,
+    policy: &EscalationPolicy,
     hint: TargetKindHint,
     prompt_tokens_approx: usize,
     used_slow: usize,
+    force_slow: bool,
 ) -> RouteDecision {
+    if force_slow {
+        return RouteDecision::Slow;
+    }
     // If escalation disabled or budget exhausted → always FAST.
-    if !router.policy.enabled || used_slow >= router.policy.max_escalations {
+    if !policy.enabled || used_slow >= policy.max_escalations {
         return RouteDecision::Fast;
     }
 
@@ -656,14 +1028,14 @@ fn decide_initial_route(
             Severity::Medium => 2,
             Severity::Low => 1,
         };
-        gate_rank(expected_sev) >= gate_rank(router.policy.min_severity)
+        gate_rank(expected_sev) >= gate_rank(policy.min_severity)
     };
     if !sev_gate {
         return RouteDecision::Fast;
     }
 
     // Clear signals for SLOW:
-    let too_long = prompt_tokens_approx > router.policy.long_prompt_tokens;
+    let too_long = prompt_tokens_approx > policy.long_prompt_tokens;
     let prefer_symbol = matches!(hint, TargetKindHint::Symbol);
     let prefer_wide_range =
         matches!(hint, TargetKindHint::Range { span_lines } if span_lines >= 80);
@@ -718,31 +1090,91 @@ fn truncate(s: &str, n: usize) -> String {
     s.chars().take(n).collect::<String>() + "…"
 }
 
-fn write_report(head_sha: &str, rep: &Step4Report) -> std::io::Result<()> {
+/// Directory for the step-4 report of `head_sha`.
+///
+/// `MR_REVIEWER_REPORT_DIR` overrides the base (default: `code_data/mr_tmp`);
+/// the report lands in `<base>/<head12>/step4_report.json`.
+pub fn report_dir(head_sha: &str) -> PathBuf {
     let short = if head_sha.len() >= 12 {
         &head_sha[..12]
     } else {
         head_sha
     };
-    let path = PathBuf::from("code_data")
-        .join("mr_tmp")
-        .join(short)
-        .join("step4_report.json");
+    report_base().join(short)
+}
+
+/// Base of [`report_dir`]: `MR_REVIEWER_REPORT_DIR` or `code_data/mr_tmp`.
+fn report_base() -> PathBuf {
+    std::env::var("MR_REVIEWER_REPORT_DIR")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(services::data_root::mr_tmp_dir)
+}
+
+/// SARIF export of the step-4 drafts: `<report_dir>/step4_report.sarif`.
+pub fn sarif_path(head_sha: &str) -> PathBuf {
+    report_dir(head_sha).join("step4_report.sarif")
+}
+
+/// Writes `<report_dir>/targets/<idx>/target_context.json`.
+fn write_target_context(
+    head_sha: &str,
+    idx: usize,
+    dump: &TargetContextDump<'_>,
+) -> std::io::Result<PathBuf> {
+    let dir = report_dir(head_sha).join("targets").join(idx.to_string());
+    fs::create_dir_all(&dir)?;
+    let path = dir.join("target_context.json");
+    let data = serde_json::to_vec_pretty(dump).unwrap_or_else(|_| b"{}".to_vec());
+    fs::write(&path, data)?;
+    debug!("step4: target context written → {}", path.display());
+    Ok(path)
+}
+
+fn write_report(head_sha: &str, rep: &Step4Report) -> std::io::Result<PathBuf> {
+    let path = report_dir(head_sha).join("step4_report.json");
     if let Some(dir) = path.parent() {
         fs::create_dir_all(dir)?;
     }
     let data = serde_json::to_vec_pretty(rep).unwrap_or_else(|_| b"{}".to_vec());
     fs::write(&path, data)?;
     info!("step4: report written → {}", path.display());
-    Ok(())
+    Ok(path)
+}
+
+fn write_sarif(head_sha: &str, drafts: &[DraftComment]) -> std::io::Result<PathBuf> {
+    let path = sarif_path(head_sha);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, sarif::render_sarif(drafts, head_sha))?;
+    debug!("step4: SARIF report written → {}", path.display());
+    Ok(path)
 }
 
-fn to_markdown(f: &ParsedFinding) -> String {
+/// Renders a finding; with `suggestions`, an anchor-aligned single-hunk patch
+/// becomes a GitLab ```suggestion block (offsets counted from the anchor's end
+/// line, where the publisher posts), otherwise a plain ```diff block.
+/// Untagged code blocks in the body are fenced with `lang` (see [`fence_lang`]).
+fn to_markdown(f: &ParsedFinding, suggestions: bool, lang: Option<&str>) -> String {
     let mut md = String::new();
     md.push_str(&format!("**{}**\n\n", f.title.trim()));
-    md.push_str(f.body_markdown.trim());
+    match lang {
+        Some(lang) => md.push_str(&util::tag_bare_fences(f.body_markdown.trim(), lang)),
+        None => md.push_str(f.body_markdown.trim()),
+    }
     md.push('\n');
     if let Some(patch) = &f.patch {
+        let suggestion = f
+            .anchor
+            .filter(|_| suggestions)
+            .and_then(|a| suggestion::render_gitlab_suggestion(patch, a, a.end));
+        if let Some(s) = suggestion {
+            md.push('\n');
+            md.push_str(&s);
+            return md;
+        }
         md.push_str("\n```diff\n");
         md.push_str(patch.trim());
         md.push_str("\n```\n");
@@ -820,6 +1252,143 @@ fn make_report_row(
         body_len,
         body_markdown,
         preview: preview.to_string(),
+        drop_reason: None,
+        context_trim: PromptTrim::default(),
+        format_retry: false,
+    }
+}
+
+/// Drop reason for a target whose model call hit `ReviewOptions::llm_call_timeout`.
+const LLM_TIMEOUT: &str = "llm_timeout";
+
+/// Treat `LlmEmptyResponse` and `LlmTimeout` as a blank completion (no findings)
+/// and record why, so the target is reported as `llm_empty` / `llm_timeout`
+/// instead of failing the whole run.
+fn empty_as_blank(
+    idx: usize,
+    res: MrResult<String>,
+    llm_gap: &mut Option<&'static str>,
+) -> MrResult<String> {
+    match res {
+        Err(Error::LlmEmptyResponse(reason)) => {
+            warn!("step4: target #{} got no model content: {}", idx, reason);
+            *llm_gap = Some("llm_empty");
+            Ok(String::new())
+        }
+        Err(Error::LlmTimeout(reason)) => {
+            warn!("step4: target #{} model call timed out: {}", idx, reason);
+            *llm_gap = Some(LLM_TIMEOUT);
+            Ok(String::new())
+        }
+        other => other,
+    }
+}
+
+/// Returns FAST output `raw`, or the answer to one `retry_prompt` sent via
+/// `generate` when `raw` is malformed (see [`is_malformed_output`];
+/// `anchorless` for file-level targets); the flag is true if the retry was
+/// sent. A failed or blank retry keeps `raw`.
+async fn retry_if_malformed<P, F, Fut>(
+    idx: usize,
+    raw: String,
+    anchorless: bool,
+    retry_prompt: P,
+    generate: F,
+) -> (String, bool)
+where
+    P: FnOnce() -> String,
+    F: FnOnce(String) -> Fut,
+    Fut: std::future::Future<Output = MrResult<String>>,
+{
+    if !is_malformed_output(&raw, anchorless) {
+        return (raw, false);
+    }
+    debug!(
+        "step4: target #{} FAST output unparseable, retrying once",
+        idx
+    );
+    match generate(retry_prompt()).await {
+        Ok(text) if !text.trim().is_empty() => (text, true),
+        Ok(_) => (raw, true),
+        Err(e) => {
+            warn!("step4: target #{} format retry failed: {}", idx, e);
+            (raw, true)
+        }
+    }
+}
+
+/// Anchor preference for `tgt` and the declaration line it may use:
+/// `opts.symbol_anchor` for symbol targets, `opts.line_anchor` for the rest
+/// (with the owning symbol's declaration, if any).
+fn anchor_preference_for(
+    tgt: &crate::map::MappedTarget,
+    opts: &ReviewOptions,
+) -> (AnchorPreference, Option<usize>) {
+    match &tgt.target {
+        TargetRef::Symbol { decl_line, .. } => (opts.symbol_anchor, Some(*decl_line)),
+        _ => (opts.line_anchor, tgt.owner.as_ref().map(|o| o.decl_line)),
+    }
+}
+
+/// Narrows `anchor` to a single line.
+///
+/// With `Declaration`, an added `decl_line` wins when the anchor is missing or
+/// covers it. Otherwise a multi-line anchor collapses to its first added line;
+/// anchors without added lines (and `None`) are returned unchanged.
+fn preferred_anchor(
+    anchor: Option<AnchorRange>,
+    added: &[usize],
+    decl_line: Option<usize>,
+    pref: AnchorPreference,
+) -> Option<AnchorRange> {
+    let covers = |a: &AnchorRange, ln: usize| a.start <= ln && ln <= a.end;
+    let decl = decl_line
+        .filter(|d| pref == AnchorPreference::Declaration && added.contains(d))
+        .filter(|&d| anchor.is_none_or(|a| covers(&a, d)));
+    if let Some(d) = decl {
+        return Some(AnchorRange { start: d, end: d });
+    }
+    match anchor {
+        Some(a) if a.start < a.end => added
+            .iter()
+            .find(|&&ln| covers(&a, ln))
+            .map(|&ln| AnchorRange { start: ln, end: ln })
+            .or(anchor),
+        other => other,
+    }
+}
+
+/// Reason to drop a draft whose anchor lies only on context (unchanged) lines.
+///
+/// `added` must be sorted (as returned by `collect_added_lines`). Drafts without
+/// a line anchor (file/global level) are kept.
+fn context_anchor_drop_reason(anchor: Option<AnchorRange>, added: &[usize]) -> Option<String> {
+    let a = anchor?;
+    let first_ge = added.partition_point(|&ln| ln < a.start);
+    match added.get(first_ge) {
+        Some(&ln) if ln <= a.end => None,
+        _ => Some(format!(
+            "anchor {}..{} covers no added line (context-only)",
+            a.start, a.end
+        )),
+    }
+}
+
+/// Combines the FAST finding with the SLOW refine result.
+///
+/// A SLOW error keeps the FAST finding instead of dropping the target.
+fn merge_slow_refine(
+    fast: Option<ParsedFinding>,
+    refined: MrResult<Option<ParsedFinding>>,
+) -> (Option<ParsedFinding>, SlowOutcome) {
+    match (fast, refined) {
+        (fast, Err(_)) => (fast, SlowOutcome::Failed),
+        (fast, Ok(None)) => (fast, SlowOutcome::Empty),
+        (None, Ok(Some(b))) => (Some(b), SlowOutcome::Found),
+        (Some(a), Ok(Some(b))) => {
+            let best = if better(&a, &b) { b } else { a };
+            (Some(best), SlowOutcome::Found)
+        }
     }
 }
 
@@ -847,3 +1416,224 @@ fn sev_rank(s: Severity) -> u8 {
         Severity::Low => 2,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git_providers::types::{ChangeSet, DiffHunk, DiffLine, FileChange};
+
+    fn changes() -> ChangeSet {
+        let ctx = |n: u32| DiffLine::Context {
+            old_line: n,
+            new_line: n,
+            content: format!("ctx {n}"),
+        };
+        ChangeSet {
+            files: vec![FileChange {
+                old_path: Some("lib/a.dart".into()),
+                new_path: Some("lib/a.dart".into()),
+                is_new: false,
+                is_deleted: false,
+                is_renamed: false,
+                is_binary: false,
+                hunks: vec![DiffHunk {
+                    old_start: 10,
+                    old_lines: 3,
+                    new_start: 10,
+                    new_lines: 4,
+                    lines: vec![
+                        ctx(10),
+                        ctx(11),
+                        DiffLine::Added {
+                            new_line: 12,
+                            content: "added".into(),
+                        },
+                        ctx(13),
+                    ],
+                }],
+                raw_unidiff: None,
+            }],
+            is_truncated: false,
+        }
+    }
+
+    #[test]
+    fn context_only_anchor_is_dropped() {
+        let added = collect_added_lines(&changes(), "lib/a.dart");
+        let on_context = Some(AnchorRange { start: 11, end: 11 });
+
+        let reason = context_anchor_drop_reason(on_context, &added);
+        assert!(reason.is_some_and(|r| r.contains("context-only")));
+    }
+
+    #[test]
+    fn anchors_touching_added_lines_are_kept() {
+        let added = collect_added_lines(&changes(), "lib/a.dart");
+
+        assert!(
+            context_anchor_drop_reason(Some(AnchorRange { start: 12, end: 12 }), &added).is_none()
+        );
+        assert!(
+            context_anchor_drop_reason(Some(AnchorRange { start: 10, end: 13 }), &added).is_none()
+        );
+        assert!(context_anchor_drop_reason(None, &added).is_none());
+    }
+
+    #[test]
+    fn symbol_signature_change_anchors_on_declaration() {
+        // Added doc line 20, changed signature on 21, changed body line 25.
+        let added = [20, 21, 25];
+        let range = Some(AnchorRange { start: 20, end: 26 });
+        let decl = Some(21);
+        let line = |n| Some(AnchorRange { start: n, end: n });
+
+        let pick = |anchor, pref| preferred_anchor(anchor, &added, decl, pref);
+        assert_eq!(pick(range, AnchorPreference::Declaration), line(21));
+        assert_eq!(pick(None, AnchorPreference::Declaration), line(21));
+        assert_eq!(pick(range, AnchorPreference::AddedLine), line(20));
+        // An explicit anchor elsewhere in the body is respected.
+        assert_eq!(pick(line(25), AnchorPreference::Declaration), line(25));
+    }
+
+    #[test]
+    fn symbol_body_change_keeps_added_line_anchor() {
+        // Declaration on 21 unchanged; only body line 25 was added.
+        let added = [25];
+        let range = Some(AnchorRange { start: 21, end: 26 });
+        let line = |n| Some(AnchorRange { start: n, end: n });
+
+        let pick =
+            |anchor| preferred_anchor(anchor, &added, Some(21), AnchorPreference::Declaration);
+        assert_eq!(pick(range), line(25));
+        assert_eq!(pick(None), None);
+        assert_eq!(
+            preferred_anchor(range, &added, Some(21), AnchorPreference::AddedLine),
+            line(25)
+        );
+    }
+
+    fn finding(severity: Severity, body: &str) -> ParsedFinding {
+        ParsedFinding {
+            anchor: Some(AnchorRange { start: 12, end: 12 }),
+            severity,
+            title: "t".into(),
+            body_markdown: body.into(),
+            patch: None,
+            raw_block: String::new(),
+        }
+    }
+
+    #[test]
+    fn dart_finding_code_block_is_fenced_as_dart() {
+        let mut f = finding(
+            Severity::Medium,
+            "`user` may be null here:\n```\nfinal name = user!.name;\n```",
+        );
+        f.patch = Some("-final name = user!.name;\n+final name = user?.name ?? '';".into());
+
+        let md = to_markdown(&f, false, fence_lang(Some("lib/profile.dart")).as_deref());
+        assert!(
+            md.contains("```dart\nfinal name = user!.name;\n```"),
+            "{md}"
+        );
+        assert!(
+            md.contains("```diff\n-final name"),
+            "patch keeps its diff fence: {md}"
+        );
+        assert!(!md.contains("```\nfinal"));
+    }
+
+    #[test]
+    fn range_finding_suggestion_counts_up_from_the_end_line() {
+        let mut f = finding(Severity::Medium, "collapse the branches");
+        f.anchor = Some(AnchorRange { start: 10, end: 12 });
+        f.patch = Some("-if (a) {\n-  run();\n-}\n+if (a) run();".into());
+
+        let md = to_markdown(&f, true, None);
+        assert!(
+            md.contains("```suggestion:-2+0\nif (a) run();\n```"),
+            "{md}"
+        );
+    }
+
+    #[test]
+    fn fast_finding_survives_failing_slow() {
+        let fast = finding(Severity::Medium, "fast says null deref");
+        let slow_down = Err(Error::Validation("slow endpoint unreachable".into()));
+
+        let (kept, outcome) = merge_slow_refine(Some(fast), slow_down);
+        assert_eq!(outcome, SlowOutcome::Failed);
+        assert_eq!(kept.unwrap().body_markdown, "fast says null deref");
+
+        let (none, outcome) = merge_slow_refine(None, Err(Error::Validation("down".into())));
+        assert!(none.is_none());
+        assert_eq!(outcome, SlowOutcome::Failed);
+
+        let fast = finding(Severity::Low, "fast");
+        let slow = finding(Severity::High, "slow");
+        let (kept, outcome) = merge_slow_refine(Some(fast), Ok(Some(slow)));
+        assert_eq!(outcome, SlowOutcome::Found);
+        assert_eq!(kept.unwrap().body_markdown, "slow");
+    }
+
+    #[test]
+    fn security_label_forces_slow_route() {
+        let policy = EscalationPolicy {
+            enabled: false,
+            max_escalations: 0,
+            min_severity: Severity::High,
+            min_confidence: 0.55,
+            long_prompt_tokens: 2500,
+        };
+        let line = TargetKindHint::Line;
+        assert_eq!(
+            decide_initial_route(&policy, line, 10, 0, false),
+            RouteDecision::Fast
+        );
+        assert_eq!(
+            decide_initial_route(&policy, line, 10, 0, true),
+            RouteDecision::Slow
+        );
+
+        let mut prompt = String::from("base");
+        push_security_focus(&mut prompt, None);
+        assert_eq!(prompt, "base");
+        push_security_focus(&mut prompt, Some("needs-security-review"));
+        assert!(prompt.contains("MR label 'needs-security-review'"));
+    }
+
+    #[tokio::test]
+    async fn junk_fast_output_is_retried_once_with_format_reminder() {
+        let valid = "<<<BEGIN_STRICT>>>\nANCHOR: 2-2\nSEVERITY: High\n\
+                     TITLE: Null dereference\nBODY: `user` may be null here.\n<<<END_STRICT>>>";
+        let calls = std::sync::Mutex::new(Vec::<String>::new());
+        // Mock model: junk for the regular prompt, a valid block for the reminder.
+        let model = |prompt: String| {
+            calls.lock().unwrap().push(prompt.clone());
+            let answer = if prompt.starts_with("REMINDER") {
+                valid.to_string()
+            } else {
+                "Sure! Here is my review: looks mostly fine, maybe check nulls.".to_string()
+            };
+            async move { Ok::<_, Error>(answer) }
+        };
+
+        let first = model("PROMPT".into()).await.unwrap();
+        let (raw, retried) =
+            retry_if_malformed(0, first, false, || "REMINDER".into(), &model).await;
+        assert!(retried);
+        assert_eq!(calls.lock().unwrap().len(), 2);
+        let found = parse_and_validate(&raw, &[], &SeverityMap::default());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].title, "Null dereference");
+
+        // Well-formed answers (findings or an explicit no-issues) are not retried.
+        for ok in [valid, "NO_ISSUES"] {
+            let (raw, retried) =
+                retry_if_malformed(0, ok.to_string(), false, || "REMINDER".into(), &model).await;
+            assert!(!retried);
+            assert_eq!(raw, ok);
+        }
+        assert_eq!(calls.lock().unwrap().len(), 2);
+    }
+}
@@ -5,9 +5,13 @@
 //! - Anchor validation against allowed ranges.
 //! - BODY sanitizer replaces inconsistent "lines X[-Y]" mentions with neutral wording.
 //! - Lightweight deduplication by (title, anchor).
+//! - Pluggable [`FindingPolicy`] hook for organization-specific rules.
+//! - Configurable [`SeverityMap`] for non-standard severity labels.
+
+use std::collections::HashMap;
 
 use regex::Regex;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use super::context::AnchorRange;
 
@@ -19,6 +23,105 @@ pub enum Severity {
     Low,
 }
 
+/// Maps the model's severity label to [`Severity`] (case-insensitive).
+///
+/// Built-in aliases cover common labels from other models/tools
+/// (`critical`/`blocker` → High, `warning` → Medium, `info`/`nit` → Low).
+/// Unmapped labels are logged and fall back to `Low`.
+#[derive(Debug, Clone)]
+pub struct SeverityMap {
+    aliases: HashMap<String, Severity>,
+}
+
+impl Default for SeverityMap {
+    fn default() -> Self {
+        let mut aliases = HashMap::new();
+        for (sev, labels) in [
+            (
+                Severity::High,
+                &["high", "critical", "blocker", "major", "severe", "error"][..],
+            ),
+            (
+                Severity::Medium,
+                &["medium", "moderate", "warning", "warn"][..],
+            ),
+            (
+                Severity::Low,
+                &[
+                    "low",
+                    "minor",
+                    "info",
+                    "trivial",
+                    "nit",
+                    "suggestion",
+                    "note",
+                ][..],
+            ),
+        ] {
+            for l in labels {
+                aliases.insert(l.to_string(), sev);
+            }
+        }
+        Self { aliases }
+    }
+}
+
+impl SeverityMap {
+    /// Built-in aliases extended/overridden by `MR_REVIEWER_SEVERITY_ALIASES`,
+    /// a comma-separated `label=High|Medium|Low` list
+    /// (e.g. `urgent=High,heads-up=Low`). Malformed entries are logged and skipped.
+    pub fn from_env() -> Self {
+        let mut map = Self::default();
+        if let Ok(spec) = std::env::var("MR_REVIEWER_SEVERITY_ALIASES") {
+            map.extend_from_spec(&spec);
+        }
+        map
+    }
+
+    /// Adds `label=Severity` pairs from a comma-separated spec.
+    pub fn extend_from_spec(&mut self, spec: &str) {
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let parsed = entry.split_once('=').and_then(|(label, sev)| {
+                let sev = match sev.trim().to_ascii_lowercase().as_str() {
+                    "high" => Severity::High,
+                    "medium" => Severity::Medium,
+                    "low" => Severity::Low,
+                    _ => return None,
+                };
+                Some((normalize_label(label), sev))
+            });
+            match parsed {
+                Some((label, sev)) if !label.is_empty() => {
+                    self.aliases.insert(label, sev);
+                }
+                _ => warn!(
+                    "policy: bad severity alias '{}' (expected label=High|Medium|Low)",
+                    entry
+                ),
+            }
+        }
+    }
+
+    /// Resolves `label`; unknown labels are logged and mapped to `Low`.
+    pub fn resolve(&self, label: &str) -> Severity {
+        let key = normalize_label(label);
+        match self.aliases.get(&key) {
+            Some(sev) => *sev,
+            None => {
+                warn!("policy: unmapped severity label '{}' → Low", label.trim());
+                Severity::Low
+            }
+        }
+    }
+}
+
+/// Lowercase, surrounding punctuation/markup removed (`**Critical**` → `critical`).
+fn normalize_label(s: &str) -> String {
+    s.trim()
+        .trim_matches(|c: char| !c.is_alphanumeric())
+        .to_ascii_lowercase()
+}
+
 /// One validated/parsed finding.
 #[derive(Debug, Clone)]
 pub struct ParsedFinding {
@@ -31,15 +134,71 @@ pub struct ParsedFinding {
     pub raw_block: String,
 }
 
+/// Verdict of a [`FindingPolicy`] for one finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// Keep the finding unchanged.
+    Keep,
+    /// Keep the finding; the policy modified it in place.
+    Modify,
+    /// Discard the finding (reason is logged).
+    Drop(String),
+}
+
+/// Post-processing hook invoked on every parsed finding.
+///
+/// Implement it to enforce review standards without forking, e.g. downgrade
+/// style nits, reject banned terms, or require a CWE tag on security findings.
+pub trait FindingPolicy: Send + Sync {
+    fn apply(&self, f: &mut ParsedFinding) -> PolicyDecision;
+}
+
+/// Default policy: keeps every finding as is.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopPolicy;
+
+impl FindingPolicy for NoopPolicy {
+    fn apply(&self, _f: &mut ParsedFinding) -> PolicyDecision {
+        PolicyDecision::Keep
+    }
+}
+
+/// Run `policy` over parsed findings, dropping those it rejects.
+pub fn apply_finding_policy(
+    findings: Vec<ParsedFinding>,
+    policy: &dyn FindingPolicy,
+) -> Vec<ParsedFinding> {
+    findings
+        .into_iter()
+        .filter_map(|mut f| match policy.apply(&mut f) {
+            PolicyDecision::Keep => Some(f),
+            PolicyDecision::Modify => {
+                debug!("policy: finding modified — {}", f.title);
+                Some(f)
+            }
+            PolicyDecision::Drop(reason) => {
+                debug!("policy: finding dropped — {} ({})", f.title, reason);
+                None
+            }
+        })
+        .collect()
+}
+
 /// Parse raw model text into validated findings. Invalid blocks are dropped.
-pub fn parse_and_validate(raw: &str, allowed: &[AnchorRange]) -> Vec<ParsedFinding> {
+///
+/// Severity labels are resolved through `severities`.
+pub fn parse_and_validate(
+    raw: &str,
+    allowed: &[AnchorRange],
+    severities: &SeverityMap,
+) -> Vec<ParsedFinding> {
     let cleaned = strip_think(raw);
     let cleaned = extract_strict_segment(&cleaned);
     let blocks = split_blocks(cleaned.trim());
     let mut out = Vec::new();
 
     for b in blocks {
-        if let Some(mut f) = parse_block(&b, allowed) {
+        if let Some(mut f) = parse_block(&b, allowed, severities) {
             f.body_markdown = sanitize_line_mentions(&f.body_markdown, f.anchor);
             out.push(f);
         }
@@ -60,6 +219,21 @@ pub fn parse_and_validate(raw: &str, allowed: &[AnchorRange]) -> Vec<ParsedFindi
     out
 }
 
+/// True when `raw` has content but neither an `ANCHOR:` block nor a
+/// no-issues marker: the model ignored the output format rather than finding
+/// nothing. With `anchorless` (file-level targets, e.g. removals, whose
+/// findings have no anchor) a `TITLE:` line counts as a block too.
+pub fn is_malformed_output(raw: &str, anchorless: bool) -> bool {
+    let cleaned = extract_strict_segment(&strip_think(raw));
+    !cleaned.is_empty()
+        && !cleaned.contains("NO_ISSUES")
+        && !cleaned.contains("NoIssues")
+        && !cleaned.lines().any(|l| {
+            let l = l.trim_start().to_ascii_uppercase();
+            l.starts_with("ANCHOR:") || (anchorless && l.starts_with("TITLE:"))
+        })
+}
+
 fn extract_strict_segment(s: &str) -> String {
     let start = "<<<BEGIN_STRICT>>>";
     let end = "<<<END_STRICT>>>";
@@ -91,9 +265,13 @@ fn split_blocks(s: &str) -> Vec<String> {
     out
 }
 
-fn parse_block(block: &str, allowed: &[AnchorRange]) -> Option<ParsedFinding> {
+fn parse_block(
+    block: &str,
+    allowed: &[AnchorRange],
+    severities: &SeverityMap,
+) -> Option<ParsedFinding> {
     let anchor_re = Regex::new(r"(?mi)^ANCHOR:\s*(\d+)\s*-\s*(\d+)\s*$").unwrap();
-    let severity_re = Regex::new(r"(?mi)^SEVERITY:\s*(High|Medium|Low)\s*$").unwrap();
+    let severity_re = Regex::new(r"(?mi)^SEVERITY:\s*(.+?)\s*$").unwrap();
     let title_re = Regex::new(r"(?mi)^TITLE:\s*(.+)$").unwrap();
     let body_re = Regex::new(r"(?ms)^BODY:\s*(.+?)(?:\n[A-Z]{2,}:\s*|$)").unwrap();
     let patch_re = Regex::new(r"(?ms)^PATCH:\s*```diff\s*(.+?)\s*```\s*$").unwrap();
@@ -121,8 +299,7 @@ fn parse_block(block: &str, allowed: &[AnchorRange]) -> Option<ParsedFinding> {
     let sev = severity_re
         .captures(block)
         .and_then(|c| c.get(1))
-        .map(|m| m.as_str())
-        .map(severity_from_str)
+        .map(|m| severities.resolve(m.as_str()))
         .unwrap_or(Severity::Low);
 
     let title = title_re
@@ -159,14 +336,6 @@ fn is_within_allowed(a: AnchorRange, allowed: &[AnchorRange]) -> bool {
     allowed.iter().any(|w| a.start >= w.start && a.end <= w.end)
 }
 
-fn severity_from_str(s: &str) -> Severity {
-    match s {
-        "High" | "high" => Severity::High,
-        "Medium" | "medium" => Severity::Medium,
-        _ => Severity::Low,
-    }
-}
-
 fn strip_think(s: &str) -> String {
     let mut out = s
         .replace("<think>", "")
@@ -202,3 +371,93 @@ fn sanitize_line_mentions(body: &str, anchor: Option<AnchorRange>) -> String {
             .into_owned(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sample org policy: style nits are never more than Low; banned terms drop the finding.
+    struct DowngradeNits;
+
+    impl FindingPolicy for DowngradeNits {
+        fn apply(&self, f: &mut ParsedFinding) -> PolicyDecision {
+            if f.body_markdown.contains("TODO(ban)") {
+                return PolicyDecision::Drop("banned term".into());
+            }
+            let title = f.title.to_ascii_lowercase();
+            if (title.contains("nit") || title.contains("style")) && f.severity != Severity::Low {
+                f.severity = Severity::Low;
+                return PolicyDecision::Modify;
+            }
+            PolicyDecision::Keep
+        }
+    }
+
+    const RAW: &str = "ANCHOR: 3-3\nSEVERITY: High\nTITLE: Style nit: naming\nBODY: Prefer camelCase.\n\nANCHOR: 5-5\nSEVERITY: High\nTITLE: Null dereference\nBODY: `user` may be null here.\n\nANCHOR: 7-7\nSEVERITY: Medium\nTITLE: Leftover marker\nBODY: Remove TODO(ban) before merge.\n";
+
+    #[test]
+    fn downgrade_nits_policy_lowers_severity_and_drops_banned() {
+        let parsed = parse_and_validate(RAW, &[], &SeverityMap::default());
+        assert_eq!(parsed.len(), 3);
+
+        let out = apply_finding_policy(parsed, &DowngradeNits);
+        let by_title: Vec<_> = out.iter().map(|f| (f.title.as_str(), f.severity)).collect();
+
+        assert_eq!(
+            by_title,
+            vec![
+                ("Null dereference", Severity::High),
+                ("Style nit: naming", Severity::Low),
+            ]
+        );
+    }
+
+    #[test]
+    fn title_only_output_is_well_formed_only_for_anchorless_targets() {
+        let title_only = "TITLE: Public API removed\nBODY: `fetchUser` had callers.";
+        assert!(is_malformed_output(title_only, false));
+        assert!(!is_malformed_output(title_only, true));
+        assert!(!is_malformed_output(
+            "ANCHOR: 3-3\nTITLE: x\nBODY: y",
+            false
+        ));
+        assert!(!is_malformed_output("NO_ISSUES", false));
+    }
+
+    #[test]
+    fn noop_policy_keeps_everything() {
+        let parsed = parse_and_validate(RAW, &[], &SeverityMap::default());
+        let n = parsed.len();
+        assert_eq!(apply_finding_policy(parsed, &NoopPolicy).len(), n);
+    }
+
+    #[test]
+    fn unusual_severity_labels_map_predictably() {
+        let map = SeverityMap::default();
+        let cases = [
+            ("critical", Severity::High),
+            ("BLOCKER", Severity::High),
+            ("**Critical**", Severity::High),
+            ("Major", Severity::High),
+            ("warning", Severity::Medium),
+            ("Moderate", Severity::Medium),
+            ("info", Severity::Low),
+            ("nit", Severity::Low),
+            ("Medium", Severity::Medium),
+            ("whatever", Severity::Low),
+        ];
+        for (label, want) in cases {
+            assert_eq!(map.resolve(label), want, "{label}");
+        }
+
+        let mut custom = SeverityMap::default();
+        custom.extend_from_spec("urgent=High, info=Medium, broken, x=Extreme");
+        assert_eq!(custom.resolve("Urgent"), Severity::High);
+        assert_eq!(custom.resolve("info"), Severity::Medium);
+
+        let raw = "ANCHOR: 2-2\nSEVERITY: Critical\nTITLE: SQL injection\nBODY: Query is built from user input.\n";
+        let parsed = parse_and_validate(raw, &[], &map);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].severity, Severity::High);
+    }
+}
@@ -3,7 +3,6 @@
 
 use serde::Serialize;
 use std::fs;
-use std::path::PathBuf;
 
 pub fn write_raw(head_sha: &str, idx: usize, name: &str, data: &str) {
     if let Err(e) = write_bytes(head_sha, idx, name, data.as_bytes()) {
@@ -28,8 +27,7 @@ fn write_bytes(head_sha: &str, idx: usize, name: &str, bytes: &[u8]) -> std::io:
     } else {
         head_sha
     };
-    let dir = PathBuf::from("code_data")
-        .join("mr_tmp")
+    let dir = services::data_root::mr_tmp_dir()
         .join(short)
         .join("preq")
         .join(format!("{}", idx));
@@ -16,6 +16,7 @@ mod prompt;
 mod rag;
 
 use crate::errors::MrResult;
+use crate::map::TargetRef;
 use crate::review::context::PrimaryCtx;
 use crate::review::llm::LlmRouter;
 use crate::review::preq::rag::UseChannels;
@@ -65,6 +66,8 @@ pub struct PreqInput<'a> {
     pub idx: usize,
     /// Primary per-target ctx (already built).
     pub ctx: &'a PrimaryCtx,
+    /// Review target; its kind selects which RAG channels are queried.
+    pub target: &'a TargetRef,
     /// Original diff "local window" lines (already prepared for your prompts).
     pub local_window_numbered: &'a str,
     /// Allowed anchors (printed as part of prompt to narrow scope).
@@ -114,16 +117,19 @@ pub async fn run_preq_agent(
     log::write_raw(&input.head_sha, input.idx, "preq_need_raw.txt", &raw);
     log::write_json(&input.head_sha, input.idx, "preq_need_cleaned.json", &needs);
 
-    // 3) Query RAG using the normalized needs
+    // 3) Query RAG using the normalized needs (channels chosen by target kind)
+    let channels = UseChannels::for_target(input.target);
+    tracing::debug!(
+        "preq: target #{} {:?} → channels {:?}",
+        input.idx,
+        input.target_path,
+        channels
+    );
     let hits = rag::fetch_context_flexible(
         &needs.queries,
         &needs.need_paths_like,
         &needs.need_symbols_like,
-        UseChannels {
-            use_queries: true,
-            use_paths: true,
-            use_symbols: true,
-        },
+        channels,
         8,
         router.svc.clone(),
     )
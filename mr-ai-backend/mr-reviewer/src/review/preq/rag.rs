@@ -8,6 +8,7 @@ use std::{
 
 use super::RagHit;
 use crate::errors::MrResult;
+use crate::map::TargetRef;
 use ai_llm_service::service_profiles::LlmServiceProfiles;
 use contextor::{RetrieveOptions, retrieve_with_opts};
 use serde::{Deserialize, Serialize};
@@ -26,6 +27,7 @@ struct IndexDoc {
 }
 
 /// Control which channels (queries/paths/symbols) are used for retrieval.
+#[derive(Debug, Clone, Copy)]
 pub struct UseChannels {
     pub use_queries: bool,
     pub use_paths: bool,
@@ -42,6 +44,46 @@ impl Default for UseChannels {
     }
 }
 
+impl UseChannels {
+    /// Picks channels by target kind so RAG calls go where they help most:
+    /// - `Symbol` → symbols + queries;
+    /// - `File` → paths + queries;
+    /// - `Line`/`Range` → queries + symbols (changed code references symbols);
+    /// - `Global` → all channels.
+    ///
+    /// `REVIEW_PREQ_ALL_CHANNELS=true` forces all channels for every target.
+    pub fn for_target(target: &TargetRef) -> Self {
+        let force_all = std::env::var("REVIEW_PREQ_ALL_CHANNELS")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        Self::pick(target, force_all)
+    }
+
+    fn pick(target: &TargetRef, force_all: bool) -> Self {
+        if force_all {
+            return Self::default();
+        }
+        match target {
+            TargetRef::Symbol { .. } => Self {
+                use_queries: true,
+                use_paths: false,
+                use_symbols: true,
+            },
+            TargetRef::File { .. } => Self {
+                use_queries: true,
+                use_paths: true,
+                use_symbols: false,
+            },
+            TargetRef::Line { .. } | TargetRef::Range { .. } => Self {
+                use_queries: true,
+                use_paths: false,
+                use_symbols: true,
+            },
+            TargetRef::Global => Self::default(),
+        }
+    }
+}
+
 /// High-level fetch that can run channels separately and merge results.
 /// Set `UseChannels { use_symbols: true, use_queries: false, use_paths: false }`
 /// to run symbols-only retrieval.
@@ -232,3 +274,37 @@ fn extract_score(why: &str) -> Option<f32> {
         .unwrap_or(s.len());
     s[..end].parse().ok()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn on(ch: UseChannels) -> (bool, bool, bool) {
+        (ch.use_queries, ch.use_paths, ch.use_symbols)
+    }
+
+    #[test]
+    fn channels_follow_target_kind_unless_forced() {
+        let symbol = TargetRef::Symbol {
+            path: "lib/a.dart".into(),
+            symbol_id: "lib/a.dart::A".into(),
+            decl_line: 3,
+        };
+        let file = TargetRef::File {
+            path: "lib/a.dart".into(),
+        };
+        let line = TargetRef::Line {
+            path: "lib/a.dart".into(),
+            line: 7,
+        };
+
+        assert_eq!(on(UseChannels::pick(&symbol, false)), (true, false, true));
+        assert_eq!(on(UseChannels::pick(&file, false)), (true, true, false));
+        assert_eq!(on(UseChannels::pick(&line, false)), (true, false, true));
+        assert_eq!(
+            on(UseChannels::pick(&TargetRef::Global, false)),
+            (true, true, true)
+        );
+        assert_eq!(on(UseChannels::pick(&file, true)), (true, true, true));
+    }
+}
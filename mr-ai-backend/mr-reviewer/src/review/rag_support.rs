@@ -2,9 +2,10 @@
 // Minimal RAG plumbing with dumps.
 
 use serde::Serialize;
-use std::{fs, path::PathBuf};
+use std::fs;
 use tracing::debug;
 
+use crate::review::fence_lang;
 use crate::review::llm_ext::RagHints;
 
 /// One RAG chunk to inject into prompt as read-only.
@@ -102,7 +103,8 @@ pub fn format_rag_chunks_for_prompt(chunks: &[RagChunk]) -> String {
     s.push_str("RAG (read-only context):\n");
     for (i, c) in chunks.iter().enumerate() {
         s.push_str(&format!("--- [{}] path: {}\n", i + 1, c.path));
-        s.push_str("```code\n");
+        let lang = fence_lang(Some(&c.path)).unwrap_or_else(|| "code".into());
+        s.push_str(&format!("```{lang}\n"));
         s.push_str(c.snippet.trim());
         s.push_str("\n```\n");
     }
@@ -120,10 +122,7 @@ pub fn dump_rag_chunks(
     } else {
         head_sha
     };
-    let dir = PathBuf::from("code_data")
-        .join("mr_tmp")
-        .join(short)
-        .join("rag");
+    let dir = services::data_root::mr_tmp_dir().join(short).join("rag");
     fs::create_dir_all(&dir)?;
 
     // JSON dump
@@ -0,0 +1,228 @@
+//! SARIF 2.1.0 export of step-4 findings for code-scanning dashboards
+//! (GitHub code scanning, GitLab SAST report ingestion).
+//!
+//! One `result` per draft: `ruleId` is a slug of the finding title, `level`
+//! follows [`Severity`], and the location is the draft's anchor (path plus
+//! line range; file-level drafts carry no region, global drafts no location).
+//! The snippet hash goes into `partialFingerprints`, so dashboards can track a
+//! finding across runs.
+
+use std::collections::BTreeMap;
+
+use serde_json::{Value, json};
+
+use super::DraftComment;
+use super::policy::Severity;
+use crate::map::TargetRef;
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Longest rule id slug (after the `mrai/` prefix).
+const RULE_ID_MAX_CHARS: usize = 64;
+
+/// Renders `drafts` as a pretty-printed SARIF 2.1.0 log with a single run.
+pub fn render_sarif(drafts: &[DraftComment], head_sha: &str) -> String {
+    let mut rules = BTreeMap::<String, String>::new();
+    let results: Vec<Value> = drafts
+        .iter()
+        .map(|d| {
+            let title = draft_title(&d.body_markdown);
+            let rule_id = rule_id(title);
+            rules
+                .entry(rule_id.clone())
+                .or_insert_with(|| title.to_string());
+            let mut result = json!({
+                "ruleId": rule_id,
+                "level": level(d.severity),
+                "message": { "text": title, "markdown": d.body_markdown },
+                "partialFingerprints": { "mrai/snippetHash": d.snippet_hash },
+                "properties": { "confidence": d.confidence },
+            });
+            if let Some(location) = location(&d.target) {
+                result["locations"] = json!([location]);
+            }
+            result
+        })
+        .collect();
+
+    let rules: Vec<Value> = rules
+        .into_iter()
+        .map(|(id, title)| json!({ "id": id, "shortDescription": { "text": title } }))
+        .collect();
+    let log = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": { "driver": {
+                "name": "mr-reviewer",
+                "version": env!("CARGO_PKG_VERSION"),
+                "rules": rules,
+            }},
+            "properties": { "headSha": head_sha },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).unwrap_or_else(|_| "{}".into())
+}
+
+/// Title of a draft body rendered as `**title**\n\nbody…`; else its first line.
+fn draft_title(body: &str) -> &str {
+    let first = body.lines().next().unwrap_or("").trim();
+    let title = first
+        .strip_prefix("**")
+        .and_then(|t| t.strip_suffix("**"))
+        .unwrap_or(first)
+        .trim();
+    if title.is_empty() { "finding" } else { title }
+}
+
+/// `mrai/<kebab-case title>`, e.g. `mrai/api-token-written-to-logs`.
+fn rule_id(title: &str) -> String {
+    let slug = title
+        .to_ascii_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(RULE_ID_MAX_CHARS).collect();
+    let slug = slug.trim_end_matches('-');
+    format!("mrai/{}", if slug.is_empty() { "finding" } else { slug })
+}
+
+fn level(s: Severity) -> &'static str {
+    match s {
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low => "note",
+    }
+}
+
+/// `physicalLocation` for the draft's anchor; `None` for global drafts.
+fn location(target: &TargetRef) -> Option<Value> {
+    let (path, lines) = match target {
+        TargetRef::Line { path, line } => (path, Some((*line, *line))),
+        TargetRef::Range {
+            path,
+            start_line,
+            end_line,
+        } => (path, Some((*start_line, *end_line))),
+        TargetRef::Symbol {
+            path, decl_line, ..
+        } => (path, Some((*decl_line, *decl_line))),
+        TargetRef::File { path } => (path, None),
+        TargetRef::Global => return None,
+    };
+    let mut physical = json!({
+        "artifactLocation": { "uri": path, "uriBaseId": "%SRCROOT%" },
+    });
+    if let Some((start, end)) = lines.filter(|(start, _)| *start > 0) {
+        physical["region"] = json!({ "startLine": start, "endLine": end.max(start) });
+    }
+    Some(json!({ "physicalLocation": physical }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(target: TargetRef, severity: Severity, title: &str) -> DraftComment {
+        DraftComment {
+            target,
+            snippet_hash: "h1".into(),
+            body_markdown: format!("**{title}**\n\nDetails."),
+            severity,
+            confidence: 0.7,
+            preview: String::new(),
+        }
+    }
+
+    /// Required properties and enums of the SARIF 2.1.0 schema for what we emit.
+    fn assert_schema_valid(log: &Value) {
+        assert_eq!(log["$schema"], SARIF_SCHEMA);
+        assert_eq!(log["version"], "2.1.0");
+        let runs = log["runs"].as_array().expect("runs array");
+        for run in runs {
+            let driver = &run["tool"]["driver"];
+            assert!(driver["name"].as_str().is_some_and(|n| !n.is_empty()));
+            for rule in driver["rules"].as_array().expect("rules array") {
+                assert!(rule["id"].is_string());
+            }
+            for r in run["results"].as_array().expect("results array") {
+                assert!(r["message"]["text"].is_string(), "{r}");
+                assert!(r["ruleId"].is_string());
+                assert!(matches!(
+                    r["level"].as_str(),
+                    Some("none" | "note" | "warning" | "error")
+                ));
+                let Some(locations) = r.get("locations") else {
+                    continue;
+                };
+                for loc in locations.as_array().expect("locations array") {
+                    let phys = &loc["physicalLocation"];
+                    assert!(phys["artifactLocation"]["uri"].is_string());
+                    if let Some(region) = phys.get("region") {
+                        let start = region["startLine"].as_u64().expect("startLine");
+                        let end = region["endLine"].as_u64().expect("endLine");
+                        assert!(start >= 1 && end >= start, "{region}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn findings_render_as_schema_valid_sarif() {
+        let drafts = vec![
+            draft(
+                TargetRef::Range {
+                    path: "lib/auth.dart".into(),
+                    start_line: 10,
+                    end_line: 12,
+                },
+                Severity::High,
+                "API token written to logs",
+            ),
+            draft(
+                TargetRef::Line {
+                    path: "lib/a.dart".into(),
+                    line: 3,
+                },
+                Severity::Low,
+                "API token written to logs!",
+            ),
+            draft(
+                TargetRef::File {
+                    path: "pubspec.yaml".into(),
+                },
+                Severity::Medium,
+                "Pin dependency",
+            ),
+            draft(TargetRef::Global, Severity::Low, "Add CI"),
+        ];
+        let log: Value = serde_json::from_str(&render_sarif(&drafts, "abc123")).unwrap();
+        assert_schema_valid(&log);
+
+        let run = &log["runs"][0];
+        assert_eq!(run["properties"]["headSha"], "abc123");
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 3);
+
+        let r = &run["results"];
+        assert_eq!(r[0]["ruleId"], "mrai/api-token-written-to-logs");
+        assert_eq!(r[1]["ruleId"], r[0]["ruleId"]);
+        assert_eq!(r[0]["level"], "error");
+        assert_eq!(r[2]["level"], "warning");
+        assert_eq!(r[3]["level"], "note");
+        assert_eq!(r[0]["message"]["text"], "API token written to logs");
+        assert_eq!(r[0]["partialFingerprints"]["mrai/snippetHash"], "h1");
+        let phys = &r[0]["locations"][0]["physicalLocation"];
+        assert_eq!(phys["artifactLocation"]["uri"], "lib/auth.dart");
+        assert_eq!(phys["region"]["startLine"], 10);
+        assert_eq!(phys["region"]["endLine"], 12);
+        assert!(r[2]["locations"][0]["physicalLocation"]["region"].is_null());
+        assert!(r[3].get("locations").is_none());
+
+        let empty: Value = serde_json::from_str(&render_sarif(&[], "abc123")).unwrap();
+        assert_schema_valid(&empty);
+        assert!(empty["runs"][0]["results"].as_array().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+//! GitLab one-click "suggestion" rendering for finding patches.
+//!
+//! GitLab applies a ```` ```suggestion:-A+B ```` block by replacing the commented line
+//! (plus `A` lines above and `B` lines below) with the block content. The publisher
+//! anchors range comments on the end line (with a `line_range`), so offsets are
+//! counted from there. A patch qualifies only when it is a single hunk that removes
+//! exactly the anchored lines and adds the replacement.
+
+use super::context::AnchorRange;
+
+/// Render `patch` as a GitLab suggestion block for `anchor`, with offsets
+/// relative to `comment_line`, the line the comment is posted on (the range end
+/// for multi-line comments).
+///
+/// Returns `None` when the patch spans several hunks, carries context lines,
+/// interleaves removals and additions, or removes a different number of lines
+/// than the anchor covers — the caller then falls back to a ```` ```diff ```` block.
+pub fn render_gitlab_suggestion(
+    patch: &str,
+    anchor: AnchorRange,
+    comment_line: usize,
+) -> Option<String> {
+    if anchor.end < anchor.start || !(anchor.start..=anchor.end).contains(&comment_line) {
+        return None;
+    }
+
+    let mut hunks = 0usize;
+    let mut removed = 0usize;
+    let mut added: Vec<&str> = Vec::new();
+
+    for line in patch.trim_matches('\n').lines() {
+        if line.starts_with("--- ") || line.starts_with("+++ ") {
+            continue;
+        }
+        if line.starts_with("@@") {
+            hunks += 1;
+            if hunks > 1 {
+                return None;
+            }
+            continue;
+        }
+        if line.starts_with('-') {
+            // Removals after additions mean a non-contiguous replacement.
+            if !added.is_empty() {
+                return None;
+            }
+            removed += 1;
+        } else if let Some(rest) = line.strip_prefix('+') {
+            added.push(rest);
+        } else if line.trim().is_empty() && removed == 0 && added.is_empty() {
+            // Leading blank noise from the model.
+            continue;
+        } else {
+            // Context lines extend the hunk beyond the anchor.
+            return None;
+        }
+    }
+
+    let span = anchor.end - anchor.start + 1;
+    if removed == 0 || removed != span {
+        return None;
+    }
+
+    let mut md = format!(
+        "```suggestion:-{}+{}\n",
+        comment_line - anchor.start,
+        anchor.end - comment_line
+    );
+    for l in &added {
+        md.push_str(l);
+        md.push('\n');
+    }
+    md.push_str("```\n");
+    Some(md)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(start: usize, end: usize) -> AnchorRange {
+        AnchorRange { start, end }
+    }
+
+    #[test]
+    fn single_line_replacement_becomes_suggestion() {
+        let patch = "@@ -12 +12 @@\n-  final x = a!;\n+  final x = a ?? 0;\n";
+        let md = render_gitlab_suggestion(patch, at(12, 12), 12).unwrap();
+        assert_eq!(md, "```suggestion:-0+0\n  final x = a ?? 0;\n```\n");
+    }
+
+    #[test]
+    fn range_replacement_counts_from_the_comment_line() {
+        let patch = "-a\n-b\n-c\n+abc";
+        // Range comments sit on the end line: replace it and the 2 lines above.
+        let md = render_gitlab_suggestion(patch, at(4, 6), 6).unwrap();
+        assert_eq!(md, "```suggestion:-2+0\nabc\n```\n");
+        // On the start line the same lines are the 2 below.
+        let md = render_gitlab_suggestion(patch, at(4, 6), 4).unwrap();
+        assert_eq!(md, "```suggestion:-0+2\nabc\n```\n");
+        assert!(render_gitlab_suggestion(patch, at(4, 6), 7).is_none());
+    }
+
+    #[test]
+    fn falls_back_on_multiple_hunks_or_misalignment() {
+        let two_hunks = "@@ -1 +1 @@\n-a\n+b\n@@ -9 +9 @@\n-c\n+d";
+        assert!(render_gitlab_suggestion(two_hunks, at(1, 1), 1).is_none());
+
+        let with_context = " keep\n-a\n+b";
+        assert!(render_gitlab_suggestion(with_context, at(1, 1), 1).is_none());
+
+        let wrong_span = "-a\n+b";
+        assert!(render_gitlab_suggestion(wrong_span, at(1, 2), 2).is_none());
+    }
+}
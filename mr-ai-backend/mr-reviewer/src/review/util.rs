@@ -17,3 +17,64 @@ pub fn lang_from_path(path_opt: Option<&str>) -> Option<&'static str> {
     }
     None
 }
+
+/// Markdown fence tag for `path`.
+///
+/// `MR_REVIEWER_FENCE_LANGUAGES` adds or overrides `ext=lang` pairs
+/// (e.g. `gradle=groovy,yml=yaml`); otherwise falls back to [`lang_from_path`].
+pub fn fence_lang(path_opt: Option<&str>) -> Option<String> {
+    let path = path_opt?;
+    if let Ok(spec) = std::env::var("MR_REVIEWER_FENCE_LANGUAGES") {
+        let ext = path.rsplit('.').next().unwrap_or("");
+        let custom = spec.split(',').find_map(|pair| {
+            let (e, lang) = pair.split_once('=')?;
+            (e.trim().trim_start_matches('.') == ext && !lang.trim().is_empty())
+                .then(|| lang.trim().to_string())
+        });
+        if custom.is_some() {
+            return custom;
+        }
+    }
+    lang_from_path(Some(path)).map(str::to_string)
+}
+
+/// Tags untagged opening fences (a bare ```` ``` ```` line) in `md` with
+/// `lang`; tagged fences (```` ```diff ````, ```` ```suggestion ````) and
+/// closing fences are kept as is.
+pub fn tag_bare_fences(md: &str, lang: &str) -> String {
+    let mut out = String::with_capacity(md.len() + lang.len());
+    let mut in_fence = false;
+    for line in md.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix("```") {
+            if !in_fence && rest.trim().is_empty() {
+                out.push_str(&line[..line.len() - trimmed.len()]);
+                out.push_str("```");
+                out.push_str(lang);
+                out.push_str(rest);
+                in_fence = true;
+                continue;
+            }
+            in_fence = !in_fence;
+        }
+        out.push_str(line);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_opening_fences_get_the_language() {
+        let md = "Null check:\n```\nfinal x = a!;\n```\n\n```diff\n-a\n+b\n```\n";
+        assert_eq!(
+            tag_bare_fences(md, "dart"),
+            "Null check:\n```dart\nfinal x = a!;\n```\n\n```diff\n-a\n+b\n```\n"
+        );
+        assert_eq!(fence_lang(Some("lib/app.dart")).as_deref(), Some("dart"));
+        assert_eq!(fence_lang(Some("build.kts")).as_deref(), Some("kotlin"));
+        assert_eq!(fence_lang(None), None);
+    }
+}
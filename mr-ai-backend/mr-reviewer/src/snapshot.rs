@@ -0,0 +1,321 @@
+//! Snapshot harness for regression-testing review output (steps 1–4).
+//!
+//! A fixture directory holds a recorded change request and model answers:
+//! - `bundle.json` — the step-1 [`CrBundle`];
+//! - `files/<repo-relative path>` — file contents at the head commit;
+//! - `responses.json` — prompt hash → completion (see
+//!   [`ai_llm_service::chat_backend`]);
+//! - `golden.json` — expected [`DraftSnapshot`]s.
+//!
+//! [`replay_fixture`] runs steps 1–4 offline (recorded provider, replayed
+//! LLM); [`assert_golden`] compares the drafts with `golden.json`. Set
+//! `MR_REVIEWER_UPDATE_GOLDEN=true` to rewrite the golden file instead, e.g.
+//! after an intended prompt or anchoring change.
+//!
+//! Prompts without a recorded answer get `{}` (no finding, no extra context)
+//! and are reported by hash, so a fixture can be completed from the
+//! prompts dumped under `code_data/mr_tmp/<sha12>/prompts/`.
+//!
+//! `fixtures/retry_backoff/` is a complete example, replayed by this
+//! module's tests.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ai_llm_service::chat_backend::{ChatBackend, ReplayChat};
+use ai_llm_service::config::{llm_model_config::LlmModelConfig, llm_provider::LlmProvider};
+use ai_llm_service::service_profiles::LlmServiceProfiles;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::errors::{ConfigError, MrResult};
+use crate::git_providers::{CrBundle, ProviderClient};
+use crate::map::TargetRef;
+use crate::review::{DraftComment, Step4Summary};
+use crate::{ReviewOptions, RunReview, draft_review};
+
+/// Completion for prompts missing from `responses.json`.
+const MISS_RESPONSE: &str = "{}";
+
+/// Comparable part of a [`DraftComment`]: where it lands and how severe it is.
+///
+/// Bodies and snippet hashes are left out on purpose; wording or context
+/// window changes shouldn't break snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DraftSnapshot {
+    /// `line`, `range`, `symbol`, `file` or `global`.
+    pub kind: String,
+    pub path: Option<String>,
+    /// First anchored line (1-based); `None` for file/global drafts.
+    pub start_line: Option<usize>,
+    /// Last anchored line (inclusive).
+    pub end_line: Option<usize>,
+    pub severity: String,
+}
+
+impl From<&DraftComment> for DraftSnapshot {
+    fn from(d: &DraftComment) -> Self {
+        let (kind, path, start_line, end_line) = match &d.target {
+            TargetRef::Line { path, line } => ("line", Some(path), Some(*line), Some(*line)),
+            TargetRef::Range {
+                path,
+                start_line,
+                end_line,
+            } => ("range", Some(path), Some(*start_line), Some(*end_line)),
+            TargetRef::Symbol {
+                path, decl_line, ..
+            } => ("symbol", Some(path), Some(*decl_line), Some(*decl_line)),
+            TargetRef::File { path } => ("file", Some(path), None, None),
+            TargetRef::Global => ("global", None, None, None),
+        };
+        Self {
+            kind: kind.to_string(),
+            path: path.cloned(),
+            start_line,
+            end_line,
+            severity: format!("{:?}", d.severity),
+        }
+    }
+}
+
+/// Result of [`replay_fixture`].
+#[derive(Debug)]
+pub struct FixtureRun {
+    pub drafts: Vec<DraftComment>,
+    /// Step-4 summary (`None` when the change request was skipped).
+    pub report: Option<Step4Summary>,
+    /// Hashes of prompts that had no recorded response, in call order.
+    pub missing_prompts: Vec<String>,
+}
+
+impl FixtureRun {
+    /// Drafts as snapshots, in output order.
+    pub fn snapshots(&self) -> Vec<DraftSnapshot> {
+        self.drafts.iter().map(DraftSnapshot::from).collect()
+    }
+}
+
+/// Run steps 1–4 for the fixture in `dir` without network access.
+///
+/// # Errors
+/// I/O or JSON errors reading the fixture; pipeline errors from steps 1–4.
+pub async fn replay_fixture(dir: &Path, opts: &ReviewOptions) -> MrResult<FixtureRun> {
+    let bundle: CrBundle = serde_json::from_slice(&std::fs::read(dir.join("bundle.json"))?)?;
+    let files = read_files(&dir.join("files"))?;
+
+    let llm = |e: ai_llm_service::error_handler::AiLlmError| ConfigError::Llm(e.to_string());
+    let replay = ReplayChat::from_json_file(&dir.join("responses.json"))
+        .map_err(llm)?
+        .with_fallback(MISS_RESPONSE);
+    let profile = replay_profile();
+    let svc = Arc::new(
+        LlmServiceProfiles::new(profile.clone(), None, profile, None)
+            .map_err(llm)?
+            .with_chat_backend(ChatBackend::Replay(replay)),
+    );
+
+    let id = bundle.meta.id.clone();
+    let kind = bundle.meta.provider;
+    let client = ProviderClient::from_recorded(bundle, files);
+    let (drafts, report) = match draft_review(&client, kind, &id, svc.clone(), opts).await? {
+        RunReview::Completed { drafts, report, .. } => (drafts, Some(*report)),
+        RunReview::Skipped { .. } => (Vec::new(), None),
+    };
+
+    let missing_prompts = match svc.chat_backend() {
+        ChatBackend::Replay(chat) => chat.misses(),
+        ChatBackend::Http => Vec::new(),
+    };
+    info!(
+        "snapshot: {} draft(s), {} prompt(s) without recorded response",
+        drafts.len(),
+        missing_prompts.len()
+    );
+    Ok(FixtureRun {
+        drafts,
+        report,
+        missing_prompts,
+    })
+}
+
+/// Panics unless `run` matches `<dir>/golden.json` and every prompt was recorded.
+///
+/// With `MR_REVIEWER_UPDATE_GOLDEN=true` the golden file is rewritten instead.
+pub fn assert_golden(dir: &Path, run: &FixtureRun) {
+    let golden_path = dir.join("golden.json");
+    let actual = run.snapshots();
+    if crate::env_flag("MR_REVIEWER_UPDATE_GOLDEN") {
+        let json = serde_json::to_string_pretty(&actual).expect("serialize snapshots");
+        std::fs::write(&golden_path, json + "\n").expect("write golden.json");
+        return;
+    }
+
+    assert!(
+        run.missing_prompts.is_empty(),
+        "no recorded response for prompt(s) {:?} in {}",
+        run.missing_prompts,
+        dir.join("responses.json").display()
+    );
+    let raw = std::fs::read(&golden_path)
+        .unwrap_or_else(|e| panic!("read {}: {e}", golden_path.display()));
+    let expected: Vec<DraftSnapshot> = serde_json::from_slice(&raw).expect("parse golden.json");
+    if let Some(diff) = diff_snapshots(&expected, &actual) {
+        panic!("{} mismatch:\n{diff}", golden_path.display());
+    }
+}
+
+/// Human-readable differences, or `None` when both lists are equal.
+fn diff_snapshots(expected: &[DraftSnapshot], actual: &[DraftSnapshot]) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let mut out = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => {}
+            (e, a) => out.push_str(&format!("#{i}: expected {e:?}, got {a:?}\n")),
+        }
+    }
+    Some(out)
+}
+
+/// Collects `root/**` as repo-relative path → bytes (`/`-separated).
+fn read_files(root: &Path) -> MrResult<HashMap<String, Vec<u8>>> {
+    let mut out = HashMap::new();
+    let mut stack: Vec<PathBuf> = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            let rel = path
+                .strip_prefix(root)
+                .expect("path under fixture root")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            out.insert(rel, std::fs::read(&path)?);
+        }
+    }
+    Ok(out)
+}
+
+/// Placeholder profile; never contacted while the replay backend is active.
+pub(crate) fn replay_profile() -> LlmModelConfig {
+    LlmModelConfig {
+        provider: LlmProvider::Ollama,
+        model: "replay".into(),
+        endpoint: "http://replay.invalid".into(),
+        api_key: None,
+        max_tokens: None,
+        temperature: Some(0.0),
+        top_p: None,
+        num_ctx: None,
+        stop: Vec::new(),
+        timeout_secs: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::review::policy::Severity;
+
+    fn draft(target: TargetRef, severity: Severity) -> DraftComment {
+        DraftComment {
+            target,
+            snippet_hash: "h1".into(),
+            body_markdown: "body".into(),
+            severity,
+            confidence: 0.8,
+            preview: String::new(),
+        }
+    }
+
+    #[test]
+    fn snapshots_compare_targets_and_severity_not_bodies() {
+        let range = draft(
+            TargetRef::Range {
+                path: "lib/a.dart".into(),
+                start_line: 10,
+                end_line: 12,
+            },
+            Severity::High,
+        );
+        let snap = DraftSnapshot::from(&range);
+        assert_eq!(
+            snap,
+            DraftSnapshot {
+                kind: "range".into(),
+                path: Some("lib/a.dart".into()),
+                start_line: Some(10),
+                end_line: Some(12),
+                severity: "High".into(),
+            }
+        );
+
+        let reworded = DraftComment {
+            body_markdown: "different wording".into(),
+            ..range.clone()
+        };
+        assert!(
+            diff_snapshots(
+                std::slice::from_ref(&snap),
+                &[DraftSnapshot::from(&reworded)]
+            )
+            .is_none()
+        );
+
+        let moved = draft(
+            TargetRef::Line {
+                path: "lib/a.dart".into(),
+                line: 11,
+            },
+            Severity::Low,
+        );
+        let diff = diff_snapshots(&[snap], &[DraftSnapshot::from(&moved)]).unwrap();
+        assert!(diff.starts_with("#0: expected"));
+
+        let missing = diff_snapshots(&[], &[DraftSnapshot::from(&range)]).unwrap();
+        assert!(missing.contains("expected None"));
+    }
+
+    #[tokio::test]
+    async fn checked_in_fixture_replays_offline_to_its_golden() {
+        crate::test_support::temp_data_root();
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/retry_backoff");
+        let opts = ReviewOptions {
+            preview_only: true,
+            ..ReviewOptions::default()
+        };
+        let run = replay_fixture(&dir, &opts).await.unwrap();
+        assert!(!run.drafts.is_empty(), "fixture should produce a finding");
+        assert_golden(&dir, &run);
+
+        // The returned summary points at the report it mirrors.
+        let summary = run.report.as_ref().expect("completed run has a summary");
+        let path = summary.report_path.as_ref().expect("report written");
+        assert!(path.starts_with(crate::review::report_dir(&summary.head_sha)));
+        let written: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+        assert_eq!(written["head_sha"], summary.head_sha.as_str());
+        assert_eq!(written["drafts_total"], summary.drafts_total);
+        assert_eq!(written["targets_total"], summary.targets_total);
+
+        // Every target gets an audit file of the context sent to the model.
+        let targets = crate::review::report_dir(&summary.head_sha).join("targets");
+        for idx in 0..summary.targets_total {
+            let path = targets.join(idx.to_string()).join("target_context.json");
+            let ctx: serde_json::Value =
+                serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+            assert_eq!(ctx["idx"], idx);
+            assert!(ctx["related_blocks"].is_array(), "{ctx}");
+            assert!(ctx["rag_chunks"].is_array(), "{ctx}");
+            assert!(ctx["full_file_included"].is_boolean(), "{ctx}");
+        }
+    }
+}
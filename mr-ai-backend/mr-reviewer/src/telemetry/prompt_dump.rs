@@ -14,7 +14,7 @@
 
 use regex::Regex;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use tracing::debug;
 
 use crate::map::MappedTarget;
@@ -62,8 +62,7 @@ fn dump_dir(head_sha: &str, stage: &str) -> PathBuf {
     } else {
         head_sha
     };
-    Path::new("code_data")
-        .join("mr_tmp")
+    services::data_root::mr_tmp_dir()
         .join(short)
         .join("prompts")
         .join(stage)
@@ -0,0 +1,13 @@
+//! Shared test setup.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+static DATA_ROOT: LazyLock<tempfile::TempDir> =
+    LazyLock::new(|| tempfile::tempdir().expect("temp data root"));
+
+/// Pins `services::data_root` to a temp dir shared by this test binary, so
+/// tests materializing files or writing reports stay out of `code_data`.
+pub fn temp_data_root() -> &'static Path {
+    services::data_root::pin_data_root(DATA_ROOT.path().to_path_buf())
+}
@@ -12,4 +12,12 @@ pub enum GitCloneError {
 
     #[error("git error: {0}")]
     Git(#[from] git2::Error),
+
+    /// `CloneOptions::ca_cert_path` can't be read or holds no PEM certificate.
+    #[error("CA bundle: {0}")]
+    CaBundle(String),
+
+    /// The linked libgit2 can't serve this request (e.g. no HTTPS/SSH transport).
+    #[error("unsupported: {0}")]
+    Unsupported(String),
 }
@@ -3,65 +3,428 @@
 //! - Concurrency via `tokio::Semaphore` + `spawn_blocking`.
 //! - SSH auth: `SSH_KEY_PATH` (private key) or ssh-agent fallback.
 //! - HTTPS auth: `GIT_HTTP_TOKEN` (+ `GIT_HTTP_USER`, default `oauth2`).
-//! - Repos are cloned to `code_data/{project_name}/{repo_name}`; target dir removed if exists.
+//! - HTTPS trust: `GIT_CA_CERT_PATH` (PEM bundle for internal CAs), checked per
+//!   connection in the certificate callback; the
+//!   `GIT_DANGER_ACCEPT_INVALID_CERTS=true` escape hatch skips validation (debug only).
+//! - Repos are cloned to `<data root>/{project_name}/{repo_name}` (`MRAI_DATA_ROOT`,
+//!   default `code_data`); target dir removed if exists.
+//! - [`sync_list`] keeps existing checkouts and updates them with fetch + hard reset
+//!   unless `force` asks for a full reclone.
+//! - Transient fetch failures (network/timeout) are retried with exponential backoff
+//!   (`GIT_CLONE_RETRIES`, `GIT_CLONE_BACKOFF_MS`); auth/not-found errors are not.
+//! - Clones fail fast with [`GitCloneError::Unsupported`] when libgit2 was built
+//!   without the transport the URL needs (`git2` features `https` / `ssh`).
+//! - [`clone_list_with_progress`] reports per-repo start/finish to a [`Progress`]
+//!   and returns per-repo timing (see [`clone_summary_table`]).
 
 use std::{
     fs,
+    net::IpAddr,
     path::{Path, PathBuf},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
-use git2::{Cred, CredentialType, FetchOptions, RemoteCallbacks, build::RepoBuilder};
+use git2::{
+    CertificateCheckStatus, Cred, CredentialType, ErrorClass, ErrorCode, FetchOptions,
+    RemoteCallbacks, Repository, ResetType, build::RepoBuilder,
+};
+use openssl::{
+    error::ErrorStack,
+    stack::Stack,
+    x509::{X509, X509StoreContext, store::X509StoreBuilder, verify::X509VerifyParam},
+};
 use tokio::{sync::Semaphore, task};
 use tracing::{debug, error, info, instrument, warn};
 
 pub mod errors;
-use errors::Result;
+use errors::{GitCloneError, Result};
+pub use progress::{IndicatifProgress, NoopProgress, Progress};
+pub use services::progress;
+
+/// Retry and TLS policy for a single repository clone.
+#[derive(Debug, Clone)]
+pub struct CloneOptions {
+    /// Extra attempts after the first failure (0 = no retry).
+    pub retries: u32,
+    /// Delay before the first retry; doubled on each subsequent retry.
+    pub backoff: Duration,
+    /// PEM bundle trusted for HTTPS remotes on top of libgit2's defaults
+    /// (internal CAs, plus any intermediates the server doesn't send).
+    pub ca_cert_path: Option<PathBuf>,
+    /// Skip certificate validation entirely (debug only).
+    pub danger_accept_invalid_certs: bool,
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            ca_cert_path: None,
+            danger_accept_invalid_certs: false,
+        }
+    }
+}
+
+impl CloneOptions {
+    /// Reads `GIT_CLONE_RETRIES`, `GIT_CLONE_BACKOFF_MS`, `GIT_CA_CERT_PATH` and
+    /// `GIT_DANGER_ACCEPT_INVALID_CERTS`, falling back to defaults.
+    pub fn from_env() -> Self {
+        let d = Self::default();
+        Self {
+            retries: std::env::var("GIT_CLONE_RETRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(d.retries),
+            backoff: std::env::var("GIT_CLONE_BACKOFF_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_millis)
+                .unwrap_or(d.backoff),
+            ca_cert_path: std::env::var("GIT_CA_CERT_PATH")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map(PathBuf::from),
+            danger_accept_invalid_certs: std::env::var("GIT_DANGER_ACCEPT_INVALID_CERTS")
+                .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+        }
+    }
+}
 
 /// Clone multiple repositories concurrently (bounded by `max_concurrency`).
 ///
 /// Target path for each repo: `code_data/{project_name}/{repo_name}`.
 /// The per-repo directory is removed before cloning.
-#[instrument(skip_all, fields(project = %project_name, max = max_concurrency, total = urls.len()))]
+/// Retry policy comes from [`CloneOptions::from_env`].
 pub async fn clone_list(
     urls: Vec<String>,
     max_concurrency: usize,
-    project_name: &String,
+    project_name: &str,
 ) -> Result<()> {
-    let base_dir = PathBuf::from(format!("code_data/{project_name}"));
+    clone_list_with_options(
+        urls,
+        max_concurrency,
+        project_name,
+        CloneOptions::from_env(),
+    )
+    .await
+}
+
+/// Same as [`clone_list`] with an explicit retry policy.
+///
+/// Fails with the first repository error (in input order) after all clones ran.
+pub async fn clone_list_with_options(
+    urls: Vec<String>,
+    max_concurrency: usize,
+    project_name: &str,
+    opts: CloneOptions,
+) -> Result<()> {
+    let results = clone_all(
+        urls,
+        max_concurrency,
+        project_name,
+        opts,
+        Arc::new(NoopProgress),
+    )
+    .await?;
+    results.into_iter().try_for_each(|(_, res)| res)
+}
+
+/// Per-repository result of [`clone_list_with_progress`].
+#[derive(Debug, Clone)]
+pub struct RepoCloneStatus {
+    pub url: String,
+    /// Directory name under `code_data/{project_name}`.
+    pub repo: String,
+    /// Error message on failure (after retries).
+    pub error: Option<String>,
+    /// Wall time including retries.
+    pub duration_ms: u128,
+}
+
+/// Same as [`clone_list_with_options`], reporting each finished repository to
+/// `progress` (e.g. [`IndicatifProgress`] for CLIs).
+///
+/// Per-repository failures do not fail the call; they are returned in
+/// [`RepoCloneStatus::error`], one status per URL in input order.
+pub async fn clone_list_with_progress(
+    urls: Vec<String>,
+    max_concurrency: usize,
+    project_name: &str,
+    opts: CloneOptions,
+    progress: Arc<dyn Progress>,
+) -> Result<Vec<RepoCloneStatus>> {
+    let results = clone_all(urls, max_concurrency, project_name, opts, progress).await?;
+    Ok(results.into_iter().map(|(status, _)| status).collect())
+}
+
+/// Plain-text `repo | status | duration_ms` table, one row per repository.
+pub fn clone_summary_table(statuses: &[RepoCloneStatus]) -> String {
+    let width = statuses
+        .iter()
+        .map(|s| s.repo.len())
+        .chain(["repo".len()])
+        .max()
+        .unwrap_or(0);
+    let mut out = format!(
+        "{:<width$}  {:<6}  {:>11}\n",
+        "repo", "status", "duration_ms"
+    );
+    for s in statuses {
+        let status = if s.error.is_some() { "failed" } else { "ok" };
+        out.push_str(&format!(
+            "{:<width$}  {:<6}  {:>11}\n",
+            s.repo, status, s.duration_ms
+        ));
+    }
+    out
+}
+
+/// Clone every URL with bounded concurrency; per-repo results in input order.
+#[instrument(skip_all, fields(project = %project_name, max = max_concurrency, total = urls.len(), retries = opts.retries))]
+async fn clone_all(
+    urls: Vec<String>,
+    max_concurrency: usize,
+    project_name: &str,
+    opts: CloneOptions,
+    progress: Arc<dyn Progress>,
+) -> Result<Vec<(RepoCloneStatus, Result<()>)>> {
+    let base_dir = services::data_root::project_dir(project_name);
     ensure_dir(&base_dir)?;
 
+    progress.set_total(urls.len() as u64);
     let sem = Arc::new(Semaphore::new(max_concurrency.max(1)));
     let mut tasks = Vec::with_capacity(urls.len());
 
     for url in urls {
         let base_dir = base_dir.clone();
+        let progress = progress.clone();
+        let opts = opts.clone();
         let permit = sem.clone().acquire_owned().await.unwrap();
 
         tasks.push(task::spawn_blocking(move || {
             let _span = tracing::info_span!("clone_task", repo = %url).entered();
-            let res = clone_one_blocking(&url, &base_dir);
+            let repo = extract_repo_name(&url).unwrap_or_else(|| "unnamed_repo".into());
+            progress.message(&format!("cloning {repo}"));
+            let started = Instant::now();
+            let res = with_retries(&opts, |_| clone_one_blocking(&url, &base_dir, &opts));
+            drop(permit);
+
+            let duration_ms = started.elapsed().as_millis();
+            let status = if res.is_ok() { "ok" } else { "failed" };
+            info!(%repo, status, duration_ms, "clone task finished");
+            progress.step(&format!("{repo} {status} ({duration_ms} ms)"));
+            let error = res.as_ref().err().map(|e| e.to_string());
+            (
+                RepoCloneStatus {
+                    url,
+                    repo,
+                    error,
+                    duration_ms,
+                },
+                res,
+            )
+        }));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for t in tasks {
+        results.push(t.await?);
+    }
+
+    let statuses: Vec<RepoCloneStatus> = results.iter().map(|(s, _)| s.clone()).collect();
+    let failed = statuses.iter().filter(|s| s.error.is_some()).count();
+    info!(
+        failed,
+        "all clones finished\n{}",
+        clone_summary_table(&statuses)
+    );
+    progress.finish(&format!(
+        "cloned {}/{} repo(s)",
+        statuses.len() - failed,
+        statuses.len()
+    ));
+    Ok(results)
+}
+
+/// What [`sync_list`] did with a repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    /// Existing checkout fetched and hard-reset to its remote branch.
+    Updated,
+    /// No checkout existed; cloned fresh.
+    Cloned,
+    /// Existing checkout removed and cloned again (`force`, or not a valid repo).
+    Recloned,
+}
+
+impl SyncAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SyncAction::Updated => "updated",
+            SyncAction::Cloned => "cloned",
+            SyncAction::Recloned => "recloned",
+        }
+    }
+}
+
+/// Per-repository result of [`sync_list`].
+#[derive(Debug, Clone)]
+pub struct RepoSyncStatus {
+    pub url: String,
+    /// Directory name under `code_data/{project_name}`.
+    pub repo: String,
+    /// What was attempted; decided before any network call.
+    pub action: SyncAction,
+    /// Error message on failure (after retries).
+    pub error: Option<String>,
+}
+
+/// Synchronize multiple repositories concurrently (bounded by `max_concurrency`).
+///
+/// Unlike [`clone_list`], the project directory is not wiped: an existing valid
+/// checkout is updated with fetch + hard reset to `origin/<current branch>`
+/// (untracked files are kept). With `force = true` every repository is removed
+/// and cloned again. Missing or broken checkouts are always (re)cloned.
+///
+/// Per-repository failures do not fail the call; they are returned in
+/// [`RepoSyncStatus::error`], one status per URL in input order.
+#[instrument(skip_all, fields(project = %project_name, max = max_concurrency, total = urls.len(), force))]
+pub async fn sync_list(
+    urls: Vec<String>,
+    max_concurrency: usize,
+    project_name: &str,
+    force: bool,
+) -> Result<Vec<RepoSyncStatus>> {
+    let base_dir = services::data_root::project_dir(project_name);
+    fs::create_dir_all(&base_dir)?;
+
+    let opts = CloneOptions::from_env();
+    let sem = Arc::new(Semaphore::new(max_concurrency.max(1)));
+    let mut tasks = Vec::with_capacity(urls.len());
+
+    for url in urls {
+        let base_dir = base_dir.clone();
+        let opts = opts.clone();
+        let permit = sem.clone().acquire_owned().await.unwrap();
+
+        tasks.push(task::spawn_blocking(move || {
+            let _span = tracing::info_span!("sync_task", repo = %url).entered();
+            let res = sync_one_blocking(&url, &base_dir, force, &opts);
             drop(permit);
             res
         }));
     }
 
+    let mut statuses = Vec::with_capacity(tasks.len());
     for t in tasks {
-        t.await??;
+        statuses.push(t.await?);
+    }
+
+    let ok = |s: &&RepoSyncStatus| s.error.is_none();
+    info!(
+        updated = statuses
+            .iter()
+            .filter(ok)
+            .filter(|s| s.action == SyncAction::Updated)
+            .count(),
+        cloned = statuses
+            .iter()
+            .filter(ok)
+            .filter(|s| s.action != SyncAction::Updated)
+            .count(),
+        failed = statuses.iter().filter(|s| s.error.is_some()).count(),
+        "all syncs finished"
+    );
+    Ok(statuses)
+}
+
+/// Blocking update-or-clone of one repository (runs inside `spawn_blocking`).
+///
+/// The action is decided once up front so a retried clone is not mistaken for
+/// an existing checkout. A failure is recorded in the status, not returned.
+fn sync_one_blocking(
+    url: &str,
+    base_dir: &Path,
+    force: bool,
+    opts: &CloneOptions,
+) -> RepoSyncStatus {
+    let repo = extract_repo_name(url).unwrap_or_else(|| "unnamed_repo".into());
+    let target = base_dir.join(&repo);
+    let existed = target.exists();
+
+    let update = !force && existed && {
+        let valid = Repository::open(&target).is_ok();
+        if !valid {
+            warn!(path = %target.display(), "existing dir is not a git repository, recloning");
+        }
+        valid
+    };
+
+    let (action, res) = if update {
+        (
+            SyncAction::Updated,
+            with_retries(opts, |_| fetch_and_reset_blocking(&target, opts)),
+        )
+    } else if existed {
+        (
+            SyncAction::Recloned,
+            with_retries(opts, |_| clone_one_blocking(url, base_dir, opts)),
+        )
+    } else {
+        (
+            SyncAction::Cloned,
+            with_retries(opts, |_| clone_one_blocking(url, base_dir, opts)),
+        )
+    };
+    if let Err(e) = &res {
+        error!(%repo, action = action.as_str(), error = %e, "sync failed");
+    }
+
+    RepoSyncStatus {
+        url: url.to_string(),
+        repo,
+        action,
+        error: res.err().map(|e| e.to_string()),
+    }
+}
+
+/// Fetch `origin` and hard-reset the checkout to `origin/<current branch>`
+/// (falls back to `FETCH_HEAD` on a detached HEAD or a branch without a remote counterpart).
+#[instrument(skip(opts), fields(path = %target.display()))]
+fn fetch_and_reset_blocking(target: &Path, opts: &CloneOptions) -> Result<()> {
+    let repo = Repository::open(target)?;
+    let mut remote = repo.find_remote("origin")?;
+    let mut fetch_opts = fetch_options(opts)?;
+
+    info!("begin fetch");
+    if let Err(e) = remote.fetch::<&str>(&[], Some(&mut fetch_opts), None) {
+        error!(error = %e, "fetch failed");
+        return Err(e.into());
     }
 
-    info!("all clones finished");
+    let branch = repo.head()?.shorthand().unwrap_or("HEAD").to_string();
+    let upstream = repo
+        .revparse_single(&format!("refs/remotes/origin/{branch}"))
+        .or_else(|_| repo.revparse_single("FETCH_HEAD"))?;
+    repo.reset(&upstream, ResetType::Hard, None)?;
+
+    info!(%branch, commit = %upstream.id(), "checkout reset to remote");
     Ok(())
 }
 
 /// Blocking clone (runs inside `spawn_blocking`).
 ///
+/// - Checks that libgit2 supports the URL's transport (before touching disk).
 /// - Creates/cleans `<base_dir>/<repo_name>`.
 /// - Configures libgit2 credential callbacks for SSH/HTTPS.
 /// - Clones with `RepoBuilder`.
-#[instrument(skip(base_dir), fields(repo = %url))]
-fn clone_one_blocking(url: &str, base_dir: &Path) -> Result<()> {
+#[instrument(skip(base_dir, opts), fields(repo = %url))]
+fn clone_one_blocking(url: &str, base_dir: &Path, opts: &CloneOptions) -> Result<()> {
     info!("start clone");
+    ensure_transport(url)?;
 
     let repo_name = extract_repo_name(url).unwrap_or_else(|| "unnamed_repo".into());
     let target = base_dir.join(&repo_name);
@@ -72,7 +435,64 @@ fn clone_one_blocking(url: &str, base_dir: &Path) -> Result<()> {
         fs::remove_dir_all(&target)?;
     }
 
-    // --- credentials callback ---
+    let mut builder = RepoBuilder::new();
+    builder.fetch_options(fetch_options(opts)?);
+
+    // Shallow clone example (optional):
+    // use git2::RepositoryInitOptions;
+    // fetch_opts.download_tags(git2::AutotagOption::All);
+    // builder.branch("main"); // checkout 'main'
+
+    info!(path = %target.display(), "begin clone");
+    match builder.clone(url, &target) {
+        Ok(_) => {
+            info!(path = %target.display(), "clone completed");
+            Ok(())
+        }
+        Err(e) => {
+            error!(error = %e, "clone failed");
+            Err(e.into())
+        }
+    }
+}
+
+/// Fails with [`GitCloneError::Unsupported`] if the linked libgit2 lacks the
+/// transport `url` needs; otherwise libgit2 reports a cryptic
+/// "unsupported URL protocol" only after the target dir was wiped.
+fn ensure_transport(url: &str) -> Result<()> {
+    let v = git2::Version::get();
+    match missing_transport(url, v.https(), v.ssh()) {
+        Some(msg) => {
+            error!(%msg, "clone transport unavailable");
+            Err(GitCloneError::Unsupported(msg.into()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Error text for a transport the build can't handle, `None` if supported.
+fn missing_transport(url: &str, https: bool, ssh: bool) -> Option<&'static str> {
+    let lower = url.to_ascii_lowercase();
+    if lower.starts_with("https://") && !https {
+        return Some(
+            "git2 built without HTTPS support; enable the `https` feature of the `git2` crate \
+             (libgit2 with OpenSSL/TLS) or use an SSH URL",
+        );
+    }
+    let is_ssh = lower.starts_with("ssh://")
+        || (!lower.contains("://") && lower.contains('@') && lower.contains(':'));
+    if is_ssh && !ssh {
+        return Some(
+            "git2 built without SSH support; enable the `ssh` feature of the `git2` crate \
+             (libgit2 with libssh2) or use an HTTPS URL",
+        );
+    }
+    None
+}
+
+/// Fetch options with libgit2 credential and certificate callbacks for
+/// SSH/HTTPS (shared by clone and update).
+fn fetch_options(opts: &CloneOptions) -> Result<FetchOptions<'static>> {
     let key_path_env = std::env::var("SSH_KEY_PATH").ok();
     let key_path_disk = Path::new("ssh_keys/bot_key");
     let have_disk_key = key_path_disk.exists();
@@ -127,33 +547,155 @@ fn clone_one_blocking(url: &str, base_dir: &Path) -> Result<()> {
         Err(git2::Error::from_str("no usable credentials"))
     });
 
-    // You *may* want to relax TLS/host checks, but better keep defaults.
-    // callbacks.certificate_check(|_cert, _host| Ok(())); // <- not recommended for prod
+    if opts.danger_accept_invalid_certs {
+        // Escape hatch for broken internal PKI; prefer `ca_cert_path`.
+        warn!("TLS certificate validation DISABLED (GIT_DANGER_ACCEPT_INVALID_CERTS)");
+        callbacks.certificate_check(|_cert, _host| Ok(CertificateCheckStatus::CertificateOk));
+    } else if let Some(path) = &opts.ca_cert_path {
+        // Certificates the bundle doesn't vouch for fall through to libgit2's
+        // own validation, so public hosts keep working.
+        let roots = load_ca_bundle(path)?;
+        callbacks.certificate_check(move |cert, host| {
+            let Some(x509) = cert.as_x509() else {
+                return Ok(CertificateCheckStatus::CertificatePassthrough);
+            };
+            Ok(match verify_with_bundle(&roots, x509.data(), host) {
+                Ok(()) => CertificateCheckStatus::CertificateOk,
+                Err(reason) => {
+                    debug!(%host, %reason, "certificate not issued by custom CA bundle");
+                    CertificateCheckStatus::CertificatePassthrough
+                }
+            })
+        });
+    }
 
     let mut fetch_opts = FetchOptions::new();
     fetch_opts.remote_callbacks(callbacks);
+    Ok(fetch_opts)
+}
 
-    let mut builder = RepoBuilder::new();
-    builder.fetch_options(fetch_opts);
+/// Certificates of the PEM bundle at `path`.
+fn load_ca_bundle(path: &Path) -> Result<Vec<X509>> {
+    let pem =
+        fs::read(path).map_err(|e| GitCloneError::CaBundle(format!("{}: {e}", path.display())))?;
+    match X509::stack_from_pem(&pem) {
+        Ok(certs) if !certs.is_empty() => Ok(certs),
+        Ok(_) => Err(GitCloneError::CaBundle(format!(
+            "{}: no PEM certificate found",
+            path.display()
+        ))),
+        Err(e) => Err(GitCloneError::CaBundle(format!("{}: {e}", path.display()))),
+    }
+}
 
-    // Shallow clone example (optional):
-    // use git2::RepositoryInitOptions;
-    // fetch_opts.download_tags(git2::AutotagOption::All);
-    // builder.branch("main"); // checkout 'main'
+/// Checks the server certificate `der` for `host` against `roots` only.
+///
+/// `Err` carries the OpenSSL verification failure (unknown issuer, expired,
+/// host mismatch, ...).
+fn verify_with_bundle(roots: &[X509], der: &[u8], host: &str) -> std::result::Result<(), String> {
+    let ssl = |e: ErrorStack| e.to_string();
+    let leaf = X509::from_der(der).map_err(ssl)?;
 
-    info!(path = %target.display(), "begin clone");
-    match builder.clone(url, &target) {
-        Ok(_) => {
-            info!(path = %target.display(), "clone completed");
-            Ok(())
-        }
-        Err(e) => {
-            error!(error = %e, "clone failed");
-            Err(e.into())
+    let mut param = X509VerifyParam::new().map_err(ssl)?;
+    match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) => param.set_ip(ip).map_err(ssl)?,
+        Err(_) => param.set_host(host).map_err(ssl)?,
+    }
+    let mut store = X509StoreBuilder::new().map_err(ssl)?;
+    for cert in roots {
+        store.add_cert(cert.clone()).map_err(ssl)?;
+    }
+    store.set_param(&param).map_err(ssl)?;
+    let store = store.build();
+
+    let chain = Stack::new().map_err(ssl)?;
+    let mut ctx = X509StoreContext::new().map_err(ssl)?;
+    let verified = ctx
+        .init(&store, &leaf, &chain, |c| {
+            Ok(c.verify_cert()?.then_some(()).ok_or_else(|| c.error()))
+        })
+        .map_err(ssl)?;
+    verified.map_err(|e| e.error_string().to_string())
+}
+
+/// Runs `op` until it succeeds, fails with a non-transient error, or retries are exhausted.
+///
+/// Sleeps `backoff * 2^(attempt-1)` between attempts (blocking; called inside `spawn_blocking`).
+fn with_retries<F>(opts: &CloneOptions, mut op: F) -> Result<()>
+where
+    F: FnMut(u32) -> Result<()>,
+{
+    let mut attempt = 0u32;
+    loop {
+        match op(attempt) {
+            Ok(()) => return Ok(()),
+            Err(e) if attempt < opts.retries && is_transient(&e) => {
+                attempt += 1;
+                let delay = opts.backoff.saturating_mul(1 << (attempt - 1).min(16));
+                warn!(
+                    attempt,
+                    max = opts.retries,
+                    delay_ms = delay.as_millis() as u64,
+                    error = %e,
+                    "transient clone failure, retrying"
+                );
+                std::thread::sleep(delay);
+            }
+            Err(e) => return Err(e),
         }
     }
 }
 
+/// Message fragments of network failures, whatever class libgit2 reports.
+const NETWORK_FAILURES: &[&str] = &[
+    "timed out",
+    "connection refused",
+    "connection reset",
+    "could not resolve",
+    "failed to connect",
+    "broken pipe",
+    "early eof",
+];
+
+/// True for network/timeout-like failures worth retrying.
+///
+/// Auth failures, missing refs/repos, HTTP 4xx responses and local I/O
+/// errors are permanent; HTTP 408/429/5xx responses are retried.
+fn is_transient(err: &GitCloneError) -> bool {
+    let GitCloneError::Git(e) = err else {
+        return false;
+    };
+    if matches!(
+        e.code(),
+        ErrorCode::Auth | ErrorCode::NotFound | ErrorCode::Certificate
+    ) {
+        return false;
+    }
+    let msg = e.message().to_lowercase();
+    if let Some(status) = http_status(&msg) {
+        return status == 408 || status == 429 || status >= 500;
+    }
+    if msg.contains("authentication") {
+        return false;
+    }
+    NETWORK_FAILURES.iter().any(|m| msg.contains(m))
+        || matches!(
+            e.class(),
+            ErrorClass::Net | ErrorClass::Ssl | ErrorClass::Ssh
+        )
+}
+
+/// HTTP status from libgit2's "unexpected http status code: 404" messages.
+fn http_status(msg: &str) -> Option<u16> {
+    let (_, rest) = msg.split_once("status code")?;
+    let digits: String = rest
+        .trim_start_matches(|c: char| c == ':' || c.is_whitespace())
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
 /// Extract repository name from common Git URL forms:
 /// - https://host/org/repo.git
 /// - ssh://git@host/org/repo.git
@@ -182,3 +724,274 @@ fn ensure_dir(dir: &Path) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use openssl::{
+        asn1::Asn1Time,
+        bn::BigNum,
+        ec::{EcGroup, EcKey},
+        hash::MessageDigest,
+        nid::Nid,
+        pkey::{PKey, Private},
+        x509::{
+            X509Builder, X509NameBuilder,
+            extension::{BasicConstraints, SubjectAlternativeName},
+        },
+    };
+
+    fn fast() -> CloneOptions {
+        CloneOptions {
+            retries: 3,
+            backoff: Duration::from_millis(1),
+            ..CloneOptions::default()
+        }
+    }
+
+    /// Certificate for `cn` signed by `issuer` (self-signed CA when `None`).
+    fn cert(cn: &str, issuer: Option<(&X509, &PKey<Private>)>) -> (X509, PKey<Private>) {
+        let key = PKey::from_ec_key(
+            EcKey::generate(&EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap()).unwrap(),
+        )
+        .unwrap();
+        let mut name = X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", cn).unwrap();
+        let name = name.build();
+
+        let mut b = X509Builder::new().unwrap();
+        b.set_version(2).unwrap();
+        b.set_serial_number(&BigNum::from_u32(1).unwrap().to_asn1_integer().unwrap())
+            .unwrap();
+        b.set_subject_name(&name).unwrap();
+        b.set_pubkey(&key).unwrap();
+        b.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+            .unwrap();
+        b.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+            .unwrap();
+        match issuer {
+            None => {
+                b.set_issuer_name(&name).unwrap();
+                b.append_extension(BasicConstraints::new().critical().ca().build().unwrap())
+                    .unwrap();
+                b.sign(&key, MessageDigest::sha256()).unwrap();
+            }
+            Some((ca, ca_key)) => {
+                b.set_issuer_name(ca.subject_name()).unwrap();
+                let san = SubjectAlternativeName::new()
+                    .dns(cn)
+                    .build(&b.x509v3_context(Some(ca), None))
+                    .unwrap();
+                b.append_extension(san).unwrap();
+                b.sign(ca_key, MessageDigest::sha256()).unwrap();
+            }
+        }
+        (b.build(), key)
+    }
+
+    #[test]
+    fn server_certificates_are_checked_against_the_bundle_and_host() {
+        let (ca, ca_key) = cert("Internal CA", None);
+        let (other_ca, _) = cert("Other CA", None);
+        let (leaf, _) = cert("git.internal", Some((&ca, &ca_key)));
+        let der = leaf.to_der().unwrap();
+
+        assert_eq!(
+            verify_with_bundle(std::slice::from_ref(&ca), &der, "git.internal"),
+            Ok(())
+        );
+        assert!(verify_with_bundle(&[ca], &der, "evil.example").is_err());
+        assert!(verify_with_bundle(&[other_ca], &der, "git.internal").is_err());
+    }
+
+    #[test]
+    fn unreadable_or_empty_bundle_fails_the_fetch_setup() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty.pem");
+        fs::write(&empty, "not a certificate").unwrap();
+
+        for path in [dir.path().join("missing.pem"), empty] {
+            let opts = CloneOptions {
+                ca_cert_path: Some(path),
+                ..fast()
+            };
+            assert!(matches!(
+                fetch_options(&opts),
+                Err(GitCloneError::CaBundle(_))
+            ));
+        }
+
+        let (ca, _) = cert("Internal CA", None);
+        let bundle = dir.path().join("ca.pem");
+        fs::write(&bundle, ca.to_pem().unwrap()).unwrap();
+        assert_eq!(load_ca_bundle(&bundle).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn missing_transport_names_the_required_feature() {
+        let https = "https://gitlab.example.com/org/app.git";
+        let scp = "git@gitlab.example.com:org/app.git";
+        let ssh = "ssh://git@gitlab.example.com/org/app.git";
+
+        assert_eq!(missing_transport(https, true, true), None);
+        assert_eq!(missing_transport(scp, true, true), None);
+        assert!(
+            missing_transport(https, false, true)
+                .unwrap()
+                .contains("`https` feature")
+        );
+        assert_eq!(missing_transport(https, true, false), None);
+        assert!(
+            missing_transport(scp, true, false)
+                .unwrap()
+                .contains("`ssh` feature")
+        );
+        assert!(
+            missing_transport(ssh, true, false)
+                .unwrap()
+                .contains("`ssh` feature")
+        );
+        assert_eq!(missing_transport("/srv/git/app.git", false, false), None);
+        assert!(!is_transient(&GitCloneError::Unsupported("x".into())));
+    }
+
+    fn git_err(code: ErrorCode, class: ErrorClass, msg: &str) -> GitCloneError {
+        git2::Error::new(code, class, msg).into()
+    }
+
+    #[test]
+    fn flaky_transport_succeeds_on_second_attempt() {
+        let mut calls = 0;
+        let res = with_retries(&fast(), |attempt| {
+            calls += 1;
+            if attempt == 0 {
+                Err(git_err(
+                    ErrorCode::GenericError,
+                    ErrorClass::Net,
+                    "connection reset by peer",
+                ))
+            } else {
+                Ok(())
+            }
+        });
+        assert!(res.is_ok());
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn auth_and_not_found_are_not_retried() {
+        for (code, class) in [
+            (ErrorCode::Auth, ErrorClass::Http),
+            (ErrorCode::NotFound, ErrorClass::Reference),
+        ] {
+            let mut calls = 0;
+            let res = with_retries(&fast(), |_| {
+                calls += 1;
+                Err(git_err(code, class, "permanent"))
+            });
+            assert!(res.is_err());
+            assert_eq!(calls, 1);
+        }
+    }
+
+    #[test]
+    fn only_network_failures_are_transient() {
+        let transient = |class, msg| is_transient(&git_err(ErrorCode::GenericError, class, msg));
+
+        assert!(!transient(
+            ErrorClass::Http,
+            "unexpected http status code: 404"
+        ));
+        assert!(!transient(
+            ErrorClass::Http,
+            "unexpected http status code: 403"
+        ));
+        assert!(!transient(
+            ErrorClass::Http,
+            "too many redirects or authentication replays"
+        ));
+        assert!(transient(
+            ErrorClass::Http,
+            "unexpected http status code: 503"
+        ));
+        assert!(transient(
+            ErrorClass::Http,
+            "unexpected http status code: 429"
+        ));
+
+        assert!(!transient(
+            ErrorClass::Os,
+            "could not write file: No space left on device"
+        ));
+        assert!(!transient(ErrorClass::Os, "permission denied"));
+        assert!(transient(
+            ErrorClass::Os,
+            "failed to connect to host: Connection refused"
+        ));
+        assert!(transient(ErrorClass::Os, "operation timed out"));
+        assert!(transient(ErrorClass::Net, "SSL error: unexpected eof"));
+    }
+
+    #[test]
+    fn gives_up_after_configured_retries() {
+        let mut calls = 0;
+        let res = with_retries(&fast(), |_| {
+            calls += 1;
+            Err(git_err(
+                ErrorCode::GenericError,
+                ErrorClass::Net,
+                "timed out",
+            ))
+        });
+        assert!(res.is_err());
+        assert_eq!(calls, 4);
+    }
+
+    fn commit_file(repo: &Repository, name: &str, body: &str) {
+        let root = repo.workdir().unwrap();
+        fs::write(root.join(name), body).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(name)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = git2::Signature::now("t", "t@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, name, &tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn sync_updates_existing_checkout_and_reclones_on_force() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let origin_dir = root.join("remote/app");
+        let base = root.join("code_data");
+        fs::create_dir_all(&origin_dir).unwrap();
+        fs::create_dir_all(&base).unwrap();
+        let origin = Repository::init(&origin_dir).unwrap();
+        commit_file(&origin, "a.txt", "one");
+        let url = origin_dir.to_string_lossy().to_string();
+
+        let first = sync_one_blocking(&url, &base, false, &fast());
+        assert_eq!(first.action, SyncAction::Cloned);
+        assert_eq!(first.repo, "app");
+        assert_eq!(first.error, None);
+
+        commit_file(&origin, "b.txt", "two");
+        fs::write(base.join("app/a.txt"), "local edit").unwrap();
+        let second = sync_one_blocking(&url, &base, false, &fast());
+        assert_eq!(second.action, SyncAction::Updated);
+        assert_eq!(second.error, None);
+        assert_eq!(fs::read_to_string(base.join("app/b.txt")).unwrap(), "two");
+        assert_eq!(fs::read_to_string(base.join("app/a.txt")).unwrap(), "one");
+
+        let third = sync_one_blocking(&url, &base, true, &fast());
+        let missing = root.join("remote/missing").to_string_lossy().to_string();
+        let failed = sync_one_blocking(&missing, &base, false, &fast());
+        assert_eq!(third.action, SyncAction::Recloned);
+        assert_eq!(third.error, None);
+        assert_eq!(failed.action, SyncAction::Cloned);
+        assert!(failed.error.is_some());
+    }
+}
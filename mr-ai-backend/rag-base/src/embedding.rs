@@ -1,12 +1,90 @@
 //! Text helpers and Ollama-based embedding utilities.
+//!
+//! Texts longer than `EmbeddingConfig::window_chars` are either truncated
+//! upstream (default) or, with `LongTextMode::Mean`/`Max`, split into
+//! overlapping windows whose vectors are pooled into one.
 
 use std::time::Duration;
 
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use services::http_proxy::apply_proxy_env;
 
 use crate::errors::rag_base_error::RagBaseError;
-use crate::structs::rag_base_config::RagConfig;
+use crate::jsonl_reader::MappedChunk;
+use crate::structs::rag_base_config::{ChunkClampConfig, RagConfig};
+use crate::structs::rag_store::VectorPayload;
+use code_indexer::LanguageKind;
+use services::embed_window::{pool_vectors, split_windows};
+
+/// Snippet budget for the embedding text of one chunk.
+#[derive(Debug, Clone, Copy)]
+pub struct EmbedSnippetBudget {
+    pub max_chars: usize,
+    /// 0 = no line limit.
+    pub max_lines: usize,
+    /// Embed the chunk's full snippet instead of the payload preview.
+    pub full_snippet: bool,
+}
+
+impl EmbedSnippetBudget {
+    /// Preview snippet clamped to `embed_max_chars`, or the whole snippet (up
+    /// to `max_windows` windows) when a windowed mode will split it later.
+    pub fn from_config(cfg: &RagConfig) -> Self {
+        let e = &cfg.embedding;
+        if e.long_text.is_windowed() {
+            Self {
+                max_chars: e.window_chars.saturating_mul(e.max_windows),
+                max_lines: 0,
+                full_snippet: true,
+            }
+        } else {
+            Self {
+                max_chars: cfg.clamp.embed_max_chars,
+                max_lines: 50,
+                full_snippet: false,
+            }
+        }
+    }
+}
+
+/// Preview and embedding budgets, resolved per chunk language.
+#[derive(Debug, Clone)]
+pub struct SnippetBudgets {
+    pub clamp: ChunkClampConfig,
+    /// Embedding budget for languages without an override.
+    pub embed: EmbedSnippetBudget,
+}
+
+impl SnippetBudgets {
+    pub fn from_config(cfg: &RagConfig) -> Self {
+        Self {
+            clamp: cfg.clamp.clone(),
+            embed: EmbedSnippetBudget::from_config(cfg),
+        }
+    }
+
+    /// Payload preview char budget for `lang`.
+    pub fn preview_max_chars_for(&self, lang: LanguageKind) -> usize {
+        self.clamp.preview_max_chars_for(lang)
+    }
+
+    /// Embedding budget for `lang`.
+    ///
+    /// A language with its own `embed_max_chars` embeds from the full snippet,
+    /// so its budget isn't capped by the (smaller) preview. Windowed modes keep
+    /// the window budget for every language.
+    pub fn embed_for(&self, lang: LanguageKind) -> EmbedSnippetBudget {
+        if self.embed.full_snippet || !self.clamp.has_embed_override(lang) {
+            return self.embed;
+        }
+        EmbedSnippetBudget {
+            max_chars: self.clamp.embed_max_chars_for(lang),
+            full_snippet: true,
+            ..self.embed
+        }
+    }
+}
 
 /// Returns a clamped copy of `s` limited by `max_chars` and `max_lines`.
 pub fn clamp_snippet_ex(s: &str, max_chars: usize, max_lines: usize, add_ellipsis: bool) -> String {
@@ -71,7 +149,7 @@ pub fn build_embedding_text(
     imports_top: &[String],
     routes: &[String],
     keywords: &[String],
-    max_snippet_chars: usize,
+    budget: EmbedSnippetBudget,
 ) -> String {
     // 1) Structural header
     let mut parts: Vec<String> = vec![format!("{language} | {kind} | {symbol_path}")];
@@ -113,7 +191,7 @@ pub fn build_embedding_text(
 
     // 6) Clamped snippet
     if let Some(sn) = snippet {
-        let clamp = clamp_snippet_ex(sn, max_snippet_chars, 50, true);
+        let clamp = clamp_snippet_ex(sn, budget.max_chars, budget.max_lines, true);
         if !clamp.is_empty() {
             parts.push("Snippet:".into());
             parts.push(clamp);
@@ -135,59 +213,188 @@ struct OllamaEmbedResponse {
 }
 
 /// Embed texts via Ollama `/api/embeddings`.
+///
+/// In the windowed modes each oversized text costs one request per window.
 pub async fn embed_texts_ollama(
     cfg: &RagConfig,
     texts: &[String],
 ) -> Result<Vec<Vec<f32>>, RagBaseError> {
     let base = std::env::var("OLLAMA_URL").unwrap_or_else(|_| "http://localhost:11434".into());
     let url = format!("{base}/api/embeddings");
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(60))
-        .build()
+    let client = apply_proxy_env(reqwest::Client::builder().timeout(Duration::from_secs(60)))
+        .and_then(|b| b.build())
         .map_err(|e| RagBaseError::Embedding(format!("http client build: {e}")))?;
 
     let mut out = Vec::with_capacity(texts.len());
-
     for text in texts {
-        let req = OllamaEmbedRequest {
-            model: &cfg.embedding.model,
-            prompt: text,
-        };
-
-        let resp = client
-            .post(&url)
-            .json(&req)
-            .send()
-            .await
-            .map_err(|e| RagBaseError::Embedding(format!("POST {url}: {e}")))?;
-
-        if resp.status() != StatusCode::OK {
-            let code = resp.status();
-            let body = resp
-                .text()
-                .await
-                .unwrap_or_else(|_| "<failed to read body>".into());
-            return Err(RagBaseError::Embedding(format!(
-                "ollama embeddings non-200: {code}; body: {body}"
-            )));
+        out.push(embed_long_with(cfg, text, |w| embed_one(&client, &url, cfg, w)).await?);
+    }
+    Ok(out)
+}
+
+/// Resolve one vector per row, in order: precomputed vectors are used as-is
+/// (after checking they have `dim` components) and only the remaining rows'
+/// texts are passed to `embed`, in a single call.
+pub async fn fill_missing_vectors<F, Fut>(
+    dim: usize,
+    rows: Vec<MappedChunk>,
+    embed: F,
+) -> Result<Vec<(String, Vec<f32>, VectorPayload)>, RagBaseError>
+where
+    F: FnOnce(Vec<String>) -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<Vec<f32>>, RagBaseError>>,
+{
+    for r in &rows {
+        if let Some(v) = r.vector.as_ref().filter(|v| v.len() != dim) {
+            return Err(RagBaseError::VectorDim {
+                id: r.id.clone(),
+                got: v.len(),
+                want: dim,
+            });
         }
+    }
+
+    let texts: Vec<String> = rows
+        .iter()
+        .filter(|r| r.vector.is_none())
+        .map(|r| r.embed_text.clone())
+        .collect();
+    let mut embedded = if texts.is_empty() {
+        Vec::new().into_iter()
+    } else {
+        embed(texts).await?.into_iter()
+    };
 
-        let parsed: OllamaEmbedResponse = resp
-            .json()
+    rows.into_iter()
+        .map(|r| {
+            let vec = match r.vector {
+                Some(v) => v,
+                None => embedded.next().ok_or_else(|| {
+                    RagBaseError::Embedding("embedder returned fewer vectors than texts".into())
+                })?,
+            };
+            Ok((r.id, vec, r.payload))
+        })
+        .collect()
+}
+
+/// Embeds `text` with `embed`, pooling sliding-window vectors when `cfg`
+/// selects a windowed mode and the text exceeds one window.
+async fn embed_long_with<'a, F, Fut>(
+    cfg: &RagConfig,
+    text: &'a str,
+    mut embed: F,
+) -> Result<Vec<f32>, RagBaseError>
+where
+    F: FnMut(&'a str) -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<f32>, RagBaseError>>,
+{
+    let e = &cfg.embedding;
+    if !e.long_text.is_windowed() || text.chars().count() <= e.window_chars {
+        return embed(text).await;
+    }
+
+    let windows = split_windows(text, e.window_chars, e.window_overlap_chars, e.max_windows);
+    let mut vecs = Vec::with_capacity(windows.len());
+    for w in windows {
+        vecs.push(embed(w).await?);
+    }
+    Ok(pool_vectors(&vecs, e.long_text.pooling()))
+}
+
+/// One `/api/embeddings` call with a dimension check.
+async fn embed_one(
+    client: &reqwest::Client,
+    url: &str,
+    cfg: &RagConfig,
+    text: &str,
+) -> Result<Vec<f32>, RagBaseError> {
+    let req = OllamaEmbedRequest {
+        model: &cfg.embedding.model,
+        prompt: text,
+    };
+
+    let resp = client
+        .post(url)
+        .json(&req)
+        .send()
+        .await
+        .map_err(|e| RagBaseError::Embedding(format!("POST {url}: {e}")))?;
+
+    if resp.status() != StatusCode::OK {
+        let code = resp.status();
+        let body = resp
+            .text()
             .await
-            .map_err(|e| RagBaseError::Embedding(format!("parse embeddings json: {e}")))?;
-
-        if parsed.embedding.len() != cfg.embedding.dim {
-            return Err(RagBaseError::Embedding(format!(
-                "embedding dim {} != expected {} (model: {})",
-                parsed.embedding.len(),
-                cfg.embedding.dim,
-                cfg.embedding.model
-            )));
+            .unwrap_or_else(|_| "<failed to read body>".into());
+        return Err(RagBaseError::Embedding(format!(
+            "ollama embeddings non-200: {code}; body: {body}"
+        )));
+    }
+
+    let parsed: OllamaEmbedResponse = resp
+        .json()
+        .await
+        .map_err(|e| RagBaseError::Embedding(format!("parse embeddings json: {e}")))?;
+
+    if parsed.embedding.len() != cfg.embedding.dim {
+        return Err(RagBaseError::Embedding(format!(
+            "embedding dim {} != expected {} (model: {})",
+            parsed.embedding.len(),
+            cfg.embedding.dim,
+            cfg.embedding.model
+        )));
+    }
+
+    Ok(parsed.embedding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::rag_base_config::LongTextMode;
+
+    /// Deterministic stand-in for a model: letter histogram of the input.
+    async fn fake_embed(text: &str) -> Result<Vec<f32>, RagBaseError> {
+        let mut v = vec![0f32; 26];
+        for b in text.bytes().filter(u8::is_ascii_lowercase) {
+            v[(b - b'a') as usize] += 1.0;
         }
+        Ok(v)
+    }
 
-        out.push(parsed.embedding);
+    fn cfg(mode: LongTextMode) -> RagConfig {
+        let mut cfg = RagConfig::from_env(Some("test")).unwrap();
+        cfg.embedding.long_text = mode;
+        cfg.embedding.window_chars = 100;
+        cfg.embedding.window_overlap_chars = 20;
+        cfg.embedding.max_windows = 8;
+        cfg
     }
 
-    Ok(out)
+    #[tokio::test]
+    async fn windowed_embedding_keeps_the_tail() {
+        let text = format!("{}{}", "a".repeat(100), "z".repeat(150));
+
+        let truncated = embed_long_with(&cfg(LongTextMode::Truncate), &text[..100], fake_embed)
+            .await
+            .unwrap();
+        let mean = embed_long_with(&cfg(LongTextMode::Mean), &text, fake_embed)
+            .await
+            .unwrap();
+        let max = embed_long_with(&cfg(LongTextMode::Max), &text, fake_embed)
+            .await
+            .unwrap();
+
+        assert_eq!(truncated[25], 0.0);
+        assert_ne!(mean, truncated);
+        assert!(mean[25] > 0.0, "tail signal lost: {mean:?}");
+        assert_eq!(max[25], 90.0);
+
+        // Short texts are embedded as-is in every mode.
+        let short = embed_long_with(&cfg(LongTextMode::Mean), "abc", fake_embed)
+            .await
+            .unwrap();
+        assert_eq!(short, fake_embed("abc").await.unwrap());
+    }
 }
@@ -1,5 +1,6 @@
 //! Unified error type for the rag-base crate.
 
+use services::qdrant_error::{self, QdrantFailure};
 use thiserror::Error;
 
 /// Errors produced by the RAG base module.
@@ -33,13 +34,116 @@ pub enum RagBaseError {
     #[error("qdrant error: {0}")]
     Qdrant(String),
 
+    /// Qdrant can't be reached (connection refused, DNS failure, transport down).
+    #[error("qdrant unreachable at {endpoint} (collection '{collection}'): {message}")]
+    Unreachable {
+        endpoint: String,
+        collection: String,
+        message: String,
+    },
+
+    /// Qdrant didn't answer within the deadline.
+    #[error("qdrant timed out at {endpoint} (collection '{collection}'): {message}")]
+    Timeout {
+        endpoint: String,
+        collection: String,
+        message: String,
+    },
+
+    /// The collection doesn't exist (not indexed yet, or dropped).
+    #[error("collection '{collection}' not found at {endpoint}")]
+    CollectionMissing {
+        endpoint: String,
+        collection: String,
+    },
+
+    /// The collection stores vectors of another size than the embedding model
+    /// produces, i.e. it was built with a different model.
+    #[error(
+        "collection '{collection}' at {endpoint} stores {got}-dim vectors but the embedding \
+         model produces {want}; run a full rebuild"
+    )]
+    DimensionMismatch {
+        endpoint: String,
+        collection: String,
+        got: usize,
+        want: usize,
+    },
+
+    // ── Git ─────────────────────────────────────────────────────────────────
+    /// Repository discovery or diff computation failed.
+    #[error("git error: {0}")]
+    Git(String),
+
+    /// A git ref (e.g. the base of an incremental reindex) doesn't resolve.
+    #[error("cannot resolve {reference} in {repo}: {message}")]
+    UnresolvedRef {
+        reference: String,
+        repo: String,
+        message: String,
+    },
+
     // ── Embeddings backend ──────────────────────────────────────────────────
     /// Embedding backend failed to initialize or to embed inputs.
     #[error("embedding error: {0}")]
     Embedding(String),
 
+    /// A precomputed vector doesn't match the configured embedding dimension.
+    #[error("vector for chunk {id} has {got} dims, expected {want}")]
+    VectorDim { id: String, got: usize, want: usize },
+
     // ── Generic operation errors ────────────────────────────────────────────
     /// A requested operation is not implemented (placeholder for TODOs).
     #[error("not implemented: {0}")]
     NotImplemented(&'static str),
 }
+
+impl RagBaseError {
+    /// Classifies a failed Qdrant call `op` on `collection` at `endpoint`.
+    ///
+    /// See [`services::qdrant_error::classify`]; anything unrecognized becomes
+    /// [`RagBaseError::Qdrant`].
+    pub fn from_qdrant<E>(endpoint: &str, collection: &str, op: &str, err: E) -> Self
+    where
+        E: std::fmt::Display + std::fmt::Debug,
+    {
+        let message = err.to_string();
+        let endpoint = endpoint.to_string();
+        let collection = collection.to_string();
+        match qdrant_error::classify(&err) {
+            QdrantFailure::DimensionMismatch { got, want } => Self::DimensionMismatch {
+                endpoint,
+                collection,
+                got,
+                want,
+            },
+            QdrantFailure::Timeout => Self::Timeout {
+                endpoint,
+                collection,
+                message,
+            },
+            QdrantFailure::Unreachable => Self::Unreachable {
+                endpoint,
+                collection,
+                message,
+            },
+            QdrantFailure::CollectionMissing => Self::CollectionMissing {
+                endpoint,
+                collection,
+            },
+            QdrantFailure::Other => Self::Qdrant(format!("{op}[{collection}]: {message}")),
+        }
+    }
+
+    /// True for transient availability failures (`Unreachable`, `Timeout`):
+    /// worth a retry, and a 503 rather than a client error for API callers.
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, Self::Unreachable { .. } | Self::Timeout { .. })
+    }
+
+    /// True when the connection itself failed (`Unreachable`), the only case
+    /// a fresh client can fix; a timeout would just time out again.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self, Self::Unreachable { .. })
+    }
+}
@@ -0,0 +1,148 @@
+//! Changed-file discovery for incremental indexing (libgit2).
+//!
+//! A project lives under `code_data/<project>` and holds either a single
+//! repository or several cloned repositories one level down
+//! (`code_data/<project>/<repo>`). Paths are returned in the same form the
+//! indexer writes into `CodeChunk.file`, e.g. `code_data/app/repo/lib/a.dart`.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use git2::{DiffOptions, Repository};
+use tracing::{debug, info};
+
+use crate::errors::rag_base_error::RagBaseError;
+
+/// Files that differ between `base_ref` and the working tree (index included),
+/// across every repository under `project_root`.
+///
+/// Both sides of a rename are reported so stale points of the old path are removed.
+pub fn changed_files_since(
+    project_root: &Path,
+    base_ref: &str,
+) -> Result<BTreeSet<String>, RagBaseError> {
+    let repos = discover_repos(project_root)?;
+    if repos.is_empty() {
+        return Err(RagBaseError::Git(format!(
+            "no git repository under {}",
+            project_root.display()
+        )));
+    }
+
+    let mut out = BTreeSet::new();
+    for dir in repos {
+        let repo = Repository::open(&dir)
+            .map_err(|e| RagBaseError::Git(format!("open {}: {e}", dir.display())))?;
+        let base_tree = repo
+            .revparse_single(base_ref)
+            .and_then(|o| o.peel_to_tree())
+            .map_err(|e| RagBaseError::UnresolvedRef {
+                reference: base_ref.to_string(),
+                repo: dir.display().to_string(),
+                message: e.to_string(),
+            })?;
+
+        let mut opts = DiffOptions::new();
+        opts.include_untracked(true).recurse_untracked_dirs(true);
+        let diff = repo
+            .diff_tree_to_workdir_with_index(Some(&base_tree), Some(&mut opts))
+            .map_err(|e| RagBaseError::Git(format!("diff {}: {e}", dir.display())))?;
+
+        let before = out.len();
+        for delta in diff.deltas() {
+            for f in [delta.old_file(), delta.new_file()] {
+                if let Some(p) = f.path() {
+                    out.insert(normalize(&dir.join(p)));
+                }
+            }
+        }
+        debug!(
+            target: "rag_base::git_changes",
+            repo = %dir.display(),
+            changed = out.len() - before,
+            "changed_files_since: repo diffed"
+        );
+    }
+
+    info!(
+        target: "rag_base::git_changes",
+        root = %project_root.display(),
+        base_ref,
+        changed = out.len(),
+        "changed_files_since: done"
+    );
+    Ok(out)
+}
+
+/// `project_root` itself if it is a repository, otherwise its direct child repositories.
+fn discover_repos(project_root: &Path) -> Result<Vec<PathBuf>, RagBaseError> {
+    if project_root.join(".git").exists() {
+        return Ok(vec![project_root.to_path_buf()]);
+    }
+    let mut repos: Vec<PathBuf> = std::fs::read_dir(project_root)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.join(".git").exists())
+        .collect();
+    repos.sort();
+    Ok(repos)
+}
+
+/// Forward slashes, no leading `./` — the shape used in chunk `file` fields.
+pub fn normalize(p: &Path) -> String {
+    let s = p.to_string_lossy().replace('\\', "/");
+    s.strip_prefix("./").map(str::to_string).unwrap_or(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit_all(repo: &Repository, msg: &str) {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("t", "t@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<_> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, msg, &tree, &parents)
+            .unwrap();
+    }
+
+    #[test]
+    fn reports_modified_added_and_deleted_files_per_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let repo_dir = root.join("app");
+        std::fs::create_dir_all(repo_dir.join("lib")).unwrap();
+        let repo = Repository::init(&repo_dir).unwrap();
+
+        std::fs::write(repo_dir.join("lib/a.dart"), "class A {}\n").unwrap();
+        std::fs::write(repo_dir.join("lib/b.dart"), "class B {}\n").unwrap();
+        std::fs::write(repo_dir.join("lib/keep.dart"), "class K {}\n").unwrap();
+        commit_all(&repo, "base");
+
+        std::fs::write(repo_dir.join("lib/a.dart"), "class A { int x = 1; }\n").unwrap();
+        std::fs::remove_file(repo_dir.join("lib/b.dart")).unwrap();
+        std::fs::write(repo_dir.join("lib/c.dart"), "class C {}\n").unwrap();
+        commit_all(&repo, "change");
+
+        let changed = changed_files_since(root, "HEAD~1").unwrap();
+        let unknown = changed_files_since(root, "HEAD~5").unwrap_err();
+
+        assert!(
+            matches!(&unknown, RagBaseError::UnresolvedRef { reference, .. } if reference == "HEAD~5"),
+            "{unknown:?}"
+        );
+
+        let rel: Vec<String> = changed
+            .iter()
+            .map(|p| p.rsplit('/').next().unwrap().to_string())
+            .collect();
+        assert_eq!(rel, vec!["a.dart", "b.dart", "c.dart"]);
+        assert!(changed.iter().all(|p| p.contains("/app/lib/")));
+    }
+}
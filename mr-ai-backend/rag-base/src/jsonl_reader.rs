@@ -1,102 +1,192 @@
-//! Async JSONL reader → `(id, embed_text, VectorPayload)` tuples.
+//! Async JSONL reader → [`MappedChunk`]s.
 //! Streams `code_chunks.jsonl`, builds compact payload + high-signal embed text.
+//! Rows may carry a precomputed `vector` (e.g. from an offline embedding job),
+//! which is passed through so ingest can skip embedding them.
 
 use std::collections::BTreeSet;
 use std::path::Path;
+use std::sync::{
+    Arc, OnceLock,
+    atomic::{AtomicUsize, Ordering},
+};
 
 use code_indexer::CodeChunk;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tokio::fs::File;
 use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, mpsc};
 use tracing::{debug, info};
 
-use crate::embedding::{build_embedding_text, clamp_snippet_ex};
+use crate::embedding::{SnippetBudgets, build_embedding_text, clamp_snippet_ex};
 use crate::errors::rag_base_error::RagBaseError;
 use crate::structs::rag_store::VectorPayload;
 
+/// One JSONL row ready for embedding/upsert.
+#[derive(Debug, Clone)]
+pub struct MappedChunk {
+    pub id: String,
+    pub embed_text: String,
+    pub payload: VectorPayload,
+    /// Precomputed embedding from the row's `vector` field, if any.
+    pub vector: Option<Vec<f32>>,
+}
+
+/// Counters reported by [`read_jsonl_map_to_ingest_batched`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReaderStats {
+    pub total_lines: usize,
+    pub mapped_lines: usize,
+    pub batches: usize,
+    /// Highest number of batches handed off but not yet consumed at the same time.
+    pub peak_in_flight: usize,
+}
+
 /// Stream a JSONL file in batches and invoke `on_batch` for each non-empty batch.
+///
+/// Reading/mapping runs on a separate task and overlaps with `on_batch`. A batch is
+/// in flight from hand-off until `on_batch` returns; at most `max_in_flight` batches
+/// are in flight, so the reader blocks instead of buffering ahead of slow
+/// embedding/upsert and memory stays flat regardless of file size.
 pub async fn read_jsonl_map_to_ingest_batched<P, F, Fut>(
     path: P,
     batch_size: usize,
-    preview_max_snippet_chars: usize,
-    embed_max_snippet_chars: usize,
+    max_in_flight: usize,
+    budgets: SnippetBudgets,
     mut on_batch: F,
-) -> Result<(), RagBaseError>
+) -> Result<ReaderStats, RagBaseError>
 where
     P: AsRef<Path>,
-    F: FnMut(Vec<(String, String, VectorPayload)>) -> Fut,
+    F: FnMut(Vec<MappedChunk>) -> Fut,
     Fut: std::future::Future<Output = Result<(), RagBaseError>>,
 {
-    let path_ref = path.as_ref();
+    let path_buf = path.as_ref().to_path_buf();
+    let batch_size = batch_size.max(1);
+    let max_in_flight = max_in_flight.max(1);
     info!(
         target: "rag_base::jsonl_reader",
-        path = %path_ref.display(),
+        path = %path_buf.display(),
         batch_size,
+        max_in_flight,
         "read_jsonl_map_to_ingest_batched: start"
     );
 
-    let file = File::open(path_ref).await?;
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
-
-    let mut buf = Vec::with_capacity(batch_size.max(1));
-    let mut total_lines: usize = 0;
-    let mut mapped_lines: usize = 0;
+    let file = File::open(&path_buf).await?;
+    let permits = Arc::new(Semaphore::new(max_in_flight));
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let (tx, mut rx) = mpsc::channel::<(Batch, OwnedSemaphorePermit)>(max_in_flight);
+
+    let producer = tokio::spawn({
+        let in_flight = Arc::clone(&in_flight);
+        let peak = Arc::clone(&peak);
+        async move {
+            let mut lines = BufReader::new(file).lines();
+            let mut buf = Vec::with_capacity(batch_size);
+            let mut total_lines: usize = 0;
+            let mut mapped_lines: usize = 0;
+
+            // Hand a batch to the consumer once a permit frees up; `false` if it hung up.
+            let send = |batch: Batch| {
+                let permits = Arc::clone(&permits);
+                let tx = tx.clone();
+                let in_flight = Arc::clone(&in_flight);
+                let peak = Arc::clone(&peak);
+                async move {
+                    let Ok(permit) = permits.acquire_owned().await else {
+                        return false;
+                    };
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    debug!(
+                        target: "rag_base::jsonl_reader",
+                        buffered = batch.len(),
+                        in_flight = now,
+                        "read_jsonl_map_to_ingest_batched: flushing batch"
+                    );
+                    tx.send((batch, permit)).await.is_ok()
+                }
+            };
 
-    while let Some(line) = lines.next_line().await? {
-        total_lines += 1;
-        if let Some(triple) =
-            map_line_to_triple(&line, preview_max_snippet_chars, embed_max_snippet_chars)
-        {
-            mapped_lines += 1;
-            buf.push(triple);
+            while let Some(line) = lines.next_line().await? {
+                total_lines += 1;
+                if let Some(mapped) = map_line(&line, &budgets) {
+                    mapped_lines += 1;
+                    buf.push(mapped);
+                }
+                if buf.len() >= batch_size && !send(std::mem::take(&mut buf)).await {
+                    return Ok((total_lines, mapped_lines));
+                }
+            }
+            if !buf.is_empty() {
+                send(buf).await;
+            }
+            Ok::<_, RagBaseError>((total_lines, mapped_lines))
         }
-        if buf.len() >= batch_size {
-            debug!(
-                target: "rag_base::jsonl_reader",
-                buffered = buf.len(),
-                "read_jsonl_map_to_ingest_batched: flushing batch"
-            );
-            on_batch(std::mem::take(&mut buf)).await?;
+    });
+
+    let mut batches: usize = 0;
+    while let Some((batch, permit)) = rx.recv().await {
+        batches += 1;
+        let res = on_batch(batch).await;
+        in_flight.fetch_sub(1, Ordering::SeqCst);
+        drop(permit);
+        if let Err(e) = res {
+            producer.abort();
+            return Err(e);
         }
     }
 
-    if !buf.is_empty() {
-        debug!(
-            target: "rag_base::jsonl_reader",
-            buffered = buf.len(),
-            "read_jsonl_map_to_ingest_batched: flushing final batch"
-        );
-        on_batch(buf).await?;
-    }
+    let (total_lines, mapped_lines) = producer
+        .await
+        .map_err(|e| RagBaseError::Io(std::io::Error::other(e)))??;
 
-    info!(
-        target: "rag_base::jsonl_reader",
+    let stats = ReaderStats {
         total_lines,
         mapped_lines,
+        batches,
+        peak_in_flight: peak.load(Ordering::SeqCst),
+    };
+    info!(
+        target: "rag_base::jsonl_reader",
+        total_lines = stats.total_lines,
+        mapped_lines = stats.mapped_lines,
+        batches = stats.batches,
+        peak_in_flight = stats.peak_in_flight,
         "read_jsonl_map_to_ingest_batched: finished"
     );
 
-    Ok(())
+    Ok(stats)
 }
 
-/// Map one JSONL line (parsed as `CodeChunk`) into `(id, embed_text, VectorPayload)`.
-fn map_line_to_triple(
-    line: &str,
-    preview_max_snippet_chars: usize,
-    embed_max_snippet_chars: usize,
-) -> Option<(String, String, VectorPayload)> {
+type Batch = Vec<MappedChunk>;
+
+/// A `CodeChunk` line with an optional precomputed embedding next to it.
+#[derive(Deserialize)]
+struct JsonlRow {
+    #[serde(flatten)]
+    chunk: CodeChunk,
+    #[serde(default)]
+    vector: Option<Vec<f32>>,
+}
+
+/// Map one JSONL line (parsed as `CodeChunk`) into a [`MappedChunk`].
+///
+/// Preview and embedding budgets are resolved for the chunk's language.
+fn map_line(line: &str, budgets: &SnippetBudgets) -> Option<MappedChunk> {
     let trimmed = line.trim();
     if trimmed.is_empty() {
         return None;
     }
 
-    let chunk: CodeChunk = serde_json::from_str(trimmed).ok()?;
+    let JsonlRow { chunk, vector } = serde_json::from_str(trimmed).ok()?;
     if chunk.id.is_empty() {
         return None;
     }
 
+    let preview_max_snippet_chars = budgets.preview_max_chars_for(chunk.language);
+    let embed_budget = budgets.embed_for(chunk.language);
+
     // language/kind → stable snake_case via serde
     let language = enum_to_snake(&chunk.language);
     let kind = enum_to_snake(&chunk.kind);
@@ -211,21 +301,31 @@ fn map_line_to_triple(
         search_blob,
     };
 
-    // Embedding text (uses embed_max_snippet_chars)
+    // Embedding text (uses embed_budget)
+    let embed_snippet = if embed_budget.full_snippet {
+        chunk.snippet.as_deref()
+    } else {
+        payload.snippet.as_deref()
+    };
     let embed_text = build_embedding_text(
         &language,
         &kind,
         &payload.symbol_path,
         payload.signature.as_deref(),
         payload.doc.as_deref(),
-        payload.snippet.as_deref(),
+        embed_snippet,
         &imports_top,
         &routes,
         &keywords,
-        embed_max_snippet_chars,
+        embed_budget,
     );
 
-    Some((chunk.id, embed_text, payload))
+    Some(MappedChunk {
+        id: chunk.id,
+        embed_text,
+        payload,
+        vector,
+    })
 }
 
 #[inline]
@@ -242,26 +342,32 @@ fn set_to_vec(set: &BTreeSet<String>) -> Vec<String> {
 fn extract_routes_from_text(s: &str) -> Vec<String> {
     let mut out = Vec::new();
 
+    // Compiled once: this runs for every chunk of the JSONL.
+    static QUOTED: OnceLock<Regex> = OnceLock::new();
+    static BARE: OnceLock<Regex> = OnceLock::new();
+
     // Quoted path literals
-    if let Ok(re_q) = Regex::new(r#"['"](/[\w\-./:?=&%+*@!$',\[\]{}:]*?)['"]"#) {
-        for cap in re_q.captures_iter(s) {
-            if let Some(m) = cap.get(1) {
-                let p = m.as_str();
-                if looks_like_route(p) {
-                    out.push(p.to_string());
-                }
+    let re_q = QUOTED.get_or_init(|| {
+        Regex::new(r#"['"](/[\w\-./:?=&%+*@!$',\[\]{}:]*?)['"]"#).expect("valid route regex")
+    });
+    for cap in re_q.captures_iter(s) {
+        if let Some(m) = cap.get(1) {
+            let p = m.as_str();
+            if looks_like_route(p) {
+                out.push(p.to_string());
             }
         }
     }
 
     // Bare paths starting with `/`
-    if let Ok(re_bare) = Regex::new(r"(?P<p>/[\w\-./:?=&%+*@!$'\[\]{}:]+)") {
-        for cap in re_bare.captures_iter(s) {
-            if let Some(m) = cap.name("p") {
-                let p = m.as_str();
-                if looks_like_route(p) {
-                    out.push(p.to_string());
-                }
+    let re_bare = BARE.get_or_init(|| {
+        Regex::new(r"(?P<p>/[\w\-./:?=&%+*@!$'\[\]{}:]+)").expect("valid route regex")
+    });
+    for cap in re_bare.captures_iter(s) {
+        if let Some(m) = cap.name("p") {
+            let p = m.as_str();
+            if looks_like_route(p) {
+                out.push(p.to_string());
             }
         }
     }
@@ -287,26 +393,26 @@ fn extract_keywords_from_text(s: &str) -> Vec<String> {
     let mut toks: Vec<String> = Vec::new();
 
     // Raw token detection: identifiers, paths, etc.
-    if let Ok(re) = Regex::new(r"[A-Za-z0-9_./-]{3,}") {
-        for m in re.find_iter(s) {
-            let raw = m.as_str();
-            let raw_lower = raw.to_lowercase();
-
-            // Keep the full raw token as-is (normalized to lower-case).
-            if raw_lower.len() >= 3 && !raw_lower.chars().all(|c| c.is_ascii_digit()) {
-                toks.push(raw_lower.clone());
-            }
+    static TOKEN: OnceLock<Regex> = OnceLock::new();
+    let re = TOKEN.get_or_init(|| Regex::new(r"[A-Za-z0-9_./-]{3,}").expect("valid token regex"));
+    for m in re.find_iter(s) {
+        let raw = m.as_str();
+        let raw_lower = raw.to_lowercase();
+
+        // Keep the full raw token as-is (normalized to lower-case).
+        if raw_lower.len() >= 3 && !raw_lower.chars().all(|c| c.is_ascii_digit()) {
+            toks.push(raw_lower.clone());
+        }
 
-            // Split by structural delimiters and camelCase.
-            for piece in raw.split(|c| c == '/' || c == '.' || c == '-' || c == '_') {
-                if piece.len() < 3 {
-                    continue;
-                }
-                for part in split_camel_case(piece) {
-                    let t = part.to_lowercase();
-                    if t.len() >= 3 && !t.chars().all(|c| c.is_ascii_digit()) {
-                        toks.push(t);
-                    }
+        // Split by structural delimiters and camelCase.
+        for piece in raw.split(|c| c == '/' || c == '.' || c == '-' || c == '_') {
+            if piece.len() < 3 {
+                continue;
+            }
+            for part in split_camel_case(piece) {
+                let t = part.to_lowercase();
+                if t.len() >= 3 && !t.chars().all(|c| c.is_ascii_digit()) {
+                    toks.push(t);
                 }
             }
         }
@@ -385,3 +491,189 @@ fn split_camel_case(s: &str) -> Vec<&str> {
     parts.push(&s[start..]);
     parts
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embedding::{EmbedSnippetBudget, fill_missing_vectors};
+    use crate::structs::rag_base_config::{ChunkClampConfig, LanguageClamp};
+    use code_indexer::LanguageKind;
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::time::Duration;
+
+    fn chunk_line(i: usize) -> String {
+        serde_json::json!({
+            "id": format!("chunk-{i}"),
+            "language": "dart",
+            "file": format!("lib/f{}.dart", i % 97),
+            "symbol": format!("fn{i}"),
+            "symbol_path": format!("lib/f{}.dart::fn{i}", i % 97),
+            "kind": "function",
+            "span": { "start_byte": 0, "end_byte": 40, "start_row": 0, "start_col": 0, "end_row": 2, "end_col": 1 },
+            "owner_path": [],
+            "annotations": [],
+            "imports": [],
+            "is_definition": true,
+            "is_generated": false,
+            "snippet": format!("void fn{i}() {{\n  print('{i}');\n}}"),
+            "features": { "byte_len": 40, "line_count": 3, "has_doc": false, "has_annotations": false },
+            "content_sha256": format!("{i:064x}"),
+            "identifiers": [],
+            "anchors": []
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn reader_blocks_at_in_flight_limit() {
+        const LINES: usize = 20_000;
+        const BATCH: usize = 64;
+        const LIMIT: usize = 3;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("code_chunks.jsonl");
+        {
+            let mut f = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+            for i in 0..LINES {
+                writeln!(f, "{}", chunk_line(i)).unwrap();
+            }
+        }
+
+        let consumed = Arc::new(AtomicUsize::new(0));
+        let budgets = SnippetBudgets {
+            clamp: ChunkClampConfig::default(),
+            embed: EmbedSnippetBudget {
+                max_chars: 1200,
+                max_lines: 50,
+                full_snippet: false,
+            },
+        };
+        let stats = read_jsonl_map_to_ingest_batched(&path, BATCH, LIMIT, budgets, {
+            let consumed = Arc::clone(&consumed);
+            move |batch| {
+                let consumed = Arc::clone(&consumed);
+                async move {
+                    // Slow consumer: the reader must wait rather than buffer ahead.
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    consumed.fetch_add(batch.len(), Ordering::SeqCst);
+                    Ok(())
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(stats.total_lines, LINES);
+        assert_eq!(stats.mapped_lines, LINES);
+        assert_eq!(consumed.load(Ordering::SeqCst), LINES);
+        assert_eq!(stats.batches, LINES.div_ceil(BATCH));
+        // The consumer is the bottleneck, so the reader fills every slot but no more.
+        assert_eq!(stats.peak_in_flight, LIMIT);
+    }
+
+    #[tokio::test]
+    async fn precomputed_vectors_skip_embedding() {
+        let budgets = SnippetBudgets {
+            clamp: ChunkClampConfig::default(),
+            embed: EmbedSnippetBudget {
+                max_chars: 1200,
+                max_lines: 50,
+                full_snippet: false,
+            },
+        };
+        let with_vector = |i: usize, v: Vec<f32>| {
+            let mut row: serde_json::Value = serde_json::from_str(&chunk_line(i)).unwrap();
+            row["vector"] = serde_json::json!(v);
+            row.to_string()
+        };
+        let lines = [
+            with_vector(0, vec![1.0, 0.0, 0.0]),
+            chunk_line(1),
+            with_vector(2, vec![0.0, 1.0, 0.0]),
+            chunk_line(3),
+        ];
+        let rows: Vec<MappedChunk> = lines.iter().filter_map(|l| map_line(l, &budgets)).collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].vector.as_deref(), Some(&[1.0, 0.0, 0.0][..]));
+        assert!(rows[1].vector.is_none());
+
+        let points = fill_missing_vectors(3, rows.clone(), |texts| async move {
+            assert_eq!(texts.len(), 2, "only vectorless rows are embedded");
+            assert!(texts[0].contains("fn1") && texts[1].contains("fn3"));
+            Ok(vec![vec![0.5; 3], vec![0.25; 3]])
+        })
+        .await
+        .unwrap();
+        let got: Vec<(&str, &[f32])> = points
+            .iter()
+            .map(|(id, v, _)| (id.as_str(), v.as_slice()))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("chunk-0", &[1.0, 0.0, 0.0][..]),
+                ("chunk-1", &[0.5; 3][..]),
+                ("chunk-2", &[0.0, 1.0, 0.0][..]),
+                ("chunk-3", &[0.25; 3][..]),
+            ]
+        );
+
+        let err = fill_missing_vectors(4, rows, |_| async { Ok(Vec::new()) })
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RagBaseError::VectorDim { ref id, got: 3, want: 4 } if id == "chunk-0"
+        ));
+    }
+
+    #[test]
+    fn markdown_uses_doc_clamp_and_dart_uses_code_clamp() {
+        let budgets = SnippetBudgets {
+            clamp: ChunkClampConfig {
+                embed_max_chars: 200,
+                preview_max_chars: 80,
+                per_language: HashMap::from([(
+                    LanguageKind::Markdown,
+                    LanguageClamp {
+                        embed_max_chars: Some(2000),
+                        preview_max_chars: Some(400),
+                    },
+                )]),
+                ..ChunkClampConfig::default()
+            },
+            embed: EmbedSnippetBudget {
+                max_chars: 200,
+                max_lines: 50,
+                full_snippet: false,
+            },
+        };
+        // 40 lines of 39 chars: longer than every budget above.
+        let long_text: String = (0..40)
+            .map(|i| format!("line {i:02} of a fairly long paragraph.."))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let line_for = |language: &str, file: &str| {
+            let mut v: serde_json::Value = serde_json::from_str(&chunk_line(1)).unwrap();
+            v["language"] = language.into();
+            v["file"] = file.into();
+            v["snippet"] = long_text.as_str().into();
+            v.to_string()
+        };
+        let snippet_of = |embed: &str| embed.split_once("Snippet:\n").unwrap().1.chars().count();
+
+        let md = map_line(&line_for("markdown", "README.md"), &budgets).unwrap();
+        let md_embed = md.embed_text;
+        let md_preview = md.payload.snippet.unwrap().chars().count();
+        assert!(
+            md_preview > 80 && md_preview <= 400,
+            "md preview {md_preview}"
+        );
+        assert!(snippet_of(&md_embed) > 400 && snippet_of(&md_embed) <= 2000);
+
+        let dart = map_line(&line_for("dart", "lib/a.dart"), &budgets).unwrap();
+        assert!(dart.payload.snippet.unwrap().chars().count() <= 80);
+        assert!(snippet_of(&dart.embed_text) <= 80);
+    }
+}
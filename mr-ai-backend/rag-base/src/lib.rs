@@ -1,8 +1,33 @@
 //! Public API:
 //! - `load_fresh_index`: drop+create collection, ingest JSONL, create payload indexes.
-//! - `search_code`: semantic search with lexical re-ranking and stitched code blocks.
+//! - `reindex_blue_green`: build a new collection generation and atomically swap
+//!   the search alias to it (no downtime, previous generation kept for rollback).
+//! - `rollback_blue_green`: point the search alias back to a kept generation.
+//! - `index_changed_since`: incremental update — re-ingest only files changed
+//!   since a git ref (full rebuild stays available via the entries above);
+//!   `changed_files_since` lists those files.
+//! - `drop_index`: drop the project's collection (and its payload indexes), or
+//!   the alias and all of its blue/green generations.
+//! - `search_code`: semantic search with lexical re-ranking, stitched code blocks
+//!   and optional language/kind facets; `structs::search_result::as_plain_text`
+//!   renders the blocks as raw source.
+//! - `payload_schema` / `point_id`: indexed payload keys and the chunk id → point id
+//!   derivation, for querying Qdrant directly.
+//!
+//! Namespaces: with `RAG_NAMESPACES` set, every entry point works on one
+//! collection per namespace (`<QDRANT_COLLECTION>__ns_<name>`), each embedded
+//! with the namespace's model and dimension. Collections record the model and
+//! dimension they were built with, and searches embed the query per namespace
+//! accordingly. Blue/green reindexing covers the single-collection layout only.
+//!
+//! Alias naming: searches always address `QDRANT_COLLECTION` (e.g. `mr_ai_code`).
+//! With blue/green reindexing that name is a Qdrant *alias* pointing at a physical
+//! collection `<QDRANT_COLLECTION>__v<generation>` (e.g. `mr_ai_code__v1760600000`),
+//! where the generation is the Unix second of the rebuild, bumped past any
+//! existing generation so it only ever grows.
 
 mod embedding;
+mod git_changes;
 mod jsonl_reader;
 mod search;
 mod stitcher;
@@ -11,28 +36,40 @@ mod vector_db;
 pub mod errors;
 pub mod structs;
 
+use std::collections::BTreeSet;
+use std::path::Path;
 use std::sync::{
     Arc,
     atomic::{AtomicUsize, Ordering},
 };
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use tracing::info;
+use qdrant_client::Qdrant;
+use tracing::{info, warn};
 
-use embedding::embed_texts_ollama;
+use embedding::{SnippetBudgets, embed_texts_ollama, fill_missing_vectors};
 use errors::rag_base_error::RagBaseError;
 use jsonl_reader::read_jsonl_map_to_ingest_batched;
 use structs::rag_base_config::RagConfig;
-use structs::rag_store::IndexStats;
-use vector_db::{connect, reset_collection, upsert_batch};
+use structs::rag_store::{BlueGreenReport, IncrementalIndexStats, IndexStats};
+use vector_db::{
+    connect, count_points, delete_by_source, drop_collection, ensure_collection_dim,
+    list_collections_with_prefix, next_generation, point_alias, qdrant_err, reset_collection,
+    resolve_alias, upsert_batch,
+};
+
+use crate::structs::search_result::{CodeSearchResults, SearchFacets, SearchParams};
 
-use crate::structs::search_result::CodeSearchResult;
+pub use vector_db::{payload_schema, point_id};
 
 /// Rebuild Qdrant index for the given project:
 /// - drop collection;
 /// - create collection with fresh vector configuration;
 /// - create payload indexes;
 /// - read JSONL and push all chunks to Qdrant.
+///
+/// With namespaces, each namespace's collection is rebuilt from the chunks
+/// of its languages, and the stats are summed.
 pub async fn load_fresh_index(project_name: &str) -> Result<IndexStats, RagBaseError> {
     info!(
         target: "rag_base::index",
@@ -44,8 +81,39 @@ pub async fn load_fresh_index(project_name: &str) -> Result<IndexStats, RagBaseE
 
     // Connect to Qdrant and guarantee a fresh collection.
     let client = connect(&cfg).await?;
-    reset_collection(&client, &cfg).await?;
+    let mut stats = IndexStats::default();
+    for scope in cfg.scopes() {
+        reset_collection(&client, &scope).await?;
+        let s = ingest_jsonl(&client, &scope, None).await?;
+        stats.indexed += s.indexed;
+        stats.skipped += s.skipped;
+        stats.duration_ms += s.duration_ms;
+    }
+
+    info!(
+        target: "rag_base::index",
+        project = project_name,
+        indexed = stats.indexed,
+        skipped = stats.skipped,
+        duration_ms = stats.duration_ms,
+        "load_fresh_index: finished"
+    );
 
+    Ok(stats)
+}
+
+/// Stream `cfg.code_jsonl` in batches → embed → upsert into `cfg.qdrant.collection`.
+///
+/// Rows that carry a precomputed `vector` are upserted without calling Ollama;
+/// a vector whose length isn't `cfg.embedding.dim` fails the ingest.
+///
+/// With `only_files`, chunks whose (normalized) `file` is not in the set are skipped
+/// before embedding.
+async fn ingest_jsonl(
+    client: &Qdrant,
+    cfg: &RagConfig,
+    only_files: Option<Arc<BTreeSet<String>>>,
+) -> Result<IndexStats, RagBaseError> {
     let started = Instant::now();
 
     // Count indexed points during ingestion (no second pass).
@@ -56,31 +124,35 @@ pub async fn load_fresh_index(project_name: &str) -> Result<IndexStats, RagBaseE
     read_jsonl_map_to_ingest_batched(
         cfg.code_jsonl.as_path(),
         cfg.qdrant.batch_size,
-        cfg.clamp.preview_max_chars,
-        cfg.clamp.embed_max_chars,
+        cfg.qdrant.max_in_flight_batches,
+        SnippetBudgets::from_config(cfg),
         {
             let cfg = cfg.clone();
             let client = client.clone();
             let indexed_counter = Arc::clone(&indexed_counter);
 
-            move |batch| {
+            move |mut batch| {
                 let cfg = cfg.clone();
                 let client = client.clone();
                 let indexed_counter = Arc::clone(&indexed_counter);
 
+                if let Some(only) = only_files.as_deref() {
+                    batch.retain(|m| {
+                        only.contains(&git_changes::normalize(Path::new(&m.payload.file)))
+                    });
+                }
+                batch.retain(|m| cfg.owns_language(&m.payload.language));
+
                 async move {
                     if batch.is_empty() {
                         return Ok(());
                     }
 
-                    let texts: Vec<String> = batch.iter().map(|(_, t, _)| t.clone()).collect();
-                    let vectors = embed_texts_ollama(&cfg, &texts).await?;
-
-                    let points = batch
-                        .into_iter()
-                        .zip(vectors.into_iter())
-                        .map(|((id, _text, payload), vec)| (id, vec, payload))
-                        .collect::<Vec<_>>();
+                    let points = fill_missing_vectors(cfg.embedding.dim, batch, |texts| {
+                        let cfg = &cfg;
+                        async move { embed_texts_ollama(cfg, &texts).await }
+                    })
+                    .await?;
 
                     let written = upsert_batch(&client, &cfg, points).await?;
                     indexed_counter.fetch_add(written, Ordering::Relaxed);
@@ -91,39 +163,337 @@ pub async fn load_fresh_index(project_name: &str) -> Result<IndexStats, RagBaseE
     )
     .await?;
 
-    let duration_ms = started.elapsed().as_millis();
-    let stats = IndexStats {
+    Ok(IndexStats {
         indexed: indexed_counter.load(Ordering::Relaxed),
         skipped,
-        duration_ms,
-    };
+        duration_ms: started.elapsed().as_millis(),
+    })
+}
 
+/// Rebuild the project's index without downtime (blue/green):
+/// - ingest into a new generation `<QDRANT_COLLECTION>__v<generation>`;
+/// - verify the new collection is non-empty (a point count below the number of
+///   ingested chunks only means duplicate chunk ids collapsed, and is logged);
+/// - atomically point the `QDRANT_COLLECTION` alias at it;
+/// - keep the previous generation for [`rollback_blue_green`], delete older ones.
+///
+/// A legacy physical collection named `QDRANT_COLLECTION` (from
+/// [`load_fresh_index`]) is dropped right before the first alias is created,
+/// since an alias cannot shadow a collection.
+///
+/// On verification failure the new collection is deleted and the alias is untouched.
+pub async fn reindex_blue_green(project_name: &str) -> Result<BlueGreenReport, RagBaseError> {
     info!(
         target: "rag_base::index",
         project = project_name,
+        "reindex_blue_green: start"
+    );
+
+    let cfg: RagConfig = RagConfig::from_env(Some(project_name))?;
+    reject_namespaces(&cfg)?;
+    let client = connect(&cfg).await?;
+
+    let alias = cfg.qdrant.collection.clone();
+    let previous = resolve_alias(&client, &alias).await?;
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let generations = list_collections_with_prefix(&client, &format!("{alias}__v")).await?;
+    let mut next_cfg = cfg.clone();
+    next_cfg.qdrant.collection = next_generation(&alias, &generations, secs);
+    let next = next_cfg.qdrant.collection.clone();
+
+    reset_collection(&client, &next_cfg).await?;
+    let stats = ingest_jsonl(&client, &next_cfg, None).await?;
+
+    // Health check before cutover: never swap searches onto an empty collection.
+    let points = count_points(&client, &next).await?;
+    if points != stats.indexed as u64 {
+        warn!(
+            target: "rag_base::index",
+            collection = %next,
+            indexed = stats.indexed,
+            points,
+            "reindex_blue_green: point count differs from ingested chunks (duplicate ids?)"
+        );
+    }
+    if points == 0 {
+        warn!(
+            target: "rag_base::index",
+            collection = %next,
+            indexed = stats.indexed,
+            points,
+            "reindex_blue_green: new collection is empty, discarding it"
+        );
+        let _ = client.delete_collection(&next).await;
+        return Err(RagBaseError::Qdrant(format!(
+            "verification failed for {next}: indexed={} points={points}",
+            stats.indexed
+        )));
+    }
+
+    let legacy = previous.is_none()
+        && client.collection_exists(&alias).await.map_err(qdrant_err(
+            &cfg,
+            &alias,
+            "collection_exists",
+        ))?;
+    if legacy {
+        client.delete_collection(&alias).await.map_err(qdrant_err(
+            &cfg,
+            &alias,
+            "delete_collection",
+        ))?;
+        warn!(
+            target: "rag_base::index",
+            collection = %alias,
+            "reindex_blue_green: dropped legacy collection to make room for the alias"
+        );
+    }
+    point_alias(&cfg, &alias, &next, previous.is_some()).await?;
+
+    // Keep `previous` for rollback; older generations are garbage.
+    let mut pruned = Vec::new();
+    for name in list_collections_with_prefix(&client, &format!("{alias}__v")).await? {
+        if name == next || Some(&name) == previous.as_ref() {
+            continue;
+        }
+        match client.delete_collection(&name).await {
+            Ok(_) => pruned.push(name),
+            Err(e) => warn!(
+                target: "rag_base::index",
+                collection = %name,
+                error = %e,
+                "reindex_blue_green: failed to prune old generation"
+            ),
+        }
+    }
+
+    info!(
+        target: "rag_base::index",
+        project = project_name,
+        collection = %next,
+        previous = ?previous,
+        pruned = pruned.len(),
         indexed = stats.indexed,
-        skipped = stats.skipped,
         duration_ms = stats.duration_ms,
-        "load_fresh_index: finished"
+        "reindex_blue_green: finished"
     );
 
-    Ok(stats)
+    Ok(BlueGreenReport {
+        alias,
+        collection: next,
+        previous,
+        pruned,
+        stats,
+    })
+}
+
+/// Point the project's search alias back to `collection` (typically
+/// [`BlueGreenReport::previous`]). The collection must still exist.
+pub async fn rollback_blue_green(project_name: &str, collection: &str) -> Result<(), RagBaseError> {
+    let cfg: RagConfig = RagConfig::from_env(Some(project_name))?;
+    reject_namespaces(&cfg)?;
+    let client = connect(&cfg).await?;
+
+    let exists = client
+        .collection_exists(collection)
+        .await
+        .map_err(qdrant_err(&cfg, collection, "collection_exists"))?;
+    if !exists {
+        return Err(RagBaseError::InvalidConfig(format!(
+            "rollback target {collection} does not exist"
+        )));
+    }
+
+    let alias = &cfg.qdrant.collection;
+    let live = resolve_alias(&client, alias).await?;
+    point_alias(&cfg, alias, collection, live.is_some()).await?;
+    info!(
+        target: "rag_base::index",
+        project = project_name,
+        alias = %cfg.qdrant.collection,
+        collection,
+        "rollback_blue_green: alias restored"
+    );
+    Ok(())
+}
+
+/// Blue/green generations are built per collection alias; namespaced indexes
+/// are rebuilt with [`load_fresh_index`] instead.
+fn reject_namespaces(cfg: &RagConfig) -> Result<(), RagBaseError> {
+    if cfg.namespaces.is_empty() {
+        return Ok(());
+    }
+    Err(RagBaseError::InvalidConfig(
+        "blue/green reindexing does not support RAG_NAMESPACES; use a fresh index".into(),
+    ))
+}
+
+/// Incrementally update the project's index with files changed since `base_ref`:
+/// - diff every repository under `code_data/<project>` against `base_ref`
+///   (committed, staged and working-tree changes, untracked files included);
+/// - delete existing points of those files (removed files simply stay deleted);
+/// - re-ingest their chunks from the current JSONL.
+///
+/// The JSONL must already be regenerated from the current checkout. The collection
+/// must exist ([`RagBaseError::CollectionMissing`] otherwise); use
+/// [`load_fresh_index`] or [`reindex_blue_green`] for a full rebuild.
+pub async fn index_changed_since(
+    project_name: &str,
+    base_ref: &str,
+) -> Result<IncrementalIndexStats, RagBaseError> {
+    info!(
+        target: "rag_base::index",
+        project = project_name,
+        base_ref,
+        "index_changed_since: start"
+    );
+
+    let cfg: RagConfig = RagConfig::from_env(Some(project_name))?;
+    let changed = changed_files_since(project_name, base_ref).await?;
+
+    if changed.is_empty() {
+        info!(
+            target: "rag_base::index",
+            project = project_name,
+            base_ref,
+            "index_changed_since: nothing changed"
+        );
+        return Ok(IncrementalIndexStats::default());
+    }
+
+    let client = connect(&cfg).await?;
+    let scopes = cfg.scopes();
+    // Validate every namespace before touching any of them.
+    for scope in &scopes {
+        let exists = client
+            .collection_exists(&scope.qdrant.collection)
+            .await
+            .map_err(qdrant_err(
+                scope,
+                &scope.qdrant.collection,
+                "collection_exists",
+            ))?;
+        if !exists {
+            return Err(RagBaseError::CollectionMissing {
+                endpoint: scope.qdrant.url.clone(),
+                collection: scope.qdrant.collection.clone(),
+            });
+        }
+        ensure_collection_dim(&client, scope, &scope.qdrant.collection).await?;
+    }
+
+    let files: Vec<String> = changed.iter().cloned().collect();
+    let changed = Arc::new(changed);
+    let mut stats = IndexStats::default();
+    for scope in &scopes {
+        delete_by_source(&client, scope, &files).await?;
+        let s = ingest_jsonl(&client, scope, Some(changed.clone())).await?;
+        stats.indexed += s.indexed;
+        stats.skipped += s.skipped;
+        stats.duration_ms += s.duration_ms;
+    }
+
+    info!(
+        target: "rag_base::index",
+        project = project_name,
+        base_ref,
+        changed_files = files.len(),
+        indexed = stats.indexed,
+        duration_ms = stats.duration_ms,
+        "index_changed_since: finished"
+    );
+
+    Ok(IncrementalIndexStats {
+        changed_files: files.len(),
+        stats,
+    })
+}
+
+/// Files under `code_data/<project>` that differ from `base_ref` (working tree
+/// included), in the `CodeChunk::file` form, e.g. `code_data/app/repo/lib/a.dart`.
+/// Both sides of a rename are listed.
+///
+/// [`RagBaseError::UnresolvedRef`] when `base_ref` doesn't resolve in one of the
+/// repositories (e.g. `HEAD@{1}` right after a fresh clone).
+pub async fn changed_files_since(
+    project_name: &str,
+    base_ref: &str,
+) -> Result<BTreeSet<String>, RagBaseError> {
+    let root = services::data_root::project_dir(project_name);
+    let base = base_ref.to_string();
+    tokio::task::spawn_blocking(move || git_changes::changed_files_since(&root, &base))
+        .await
+        .map_err(|e| RagBaseError::Git(format!("diff task failed: {e}")))?
+}
+
+/// Drop the Qdrant collection for the given project to reclaim space. A
+/// blue/green alias is dropped together with every generation behind it,
+/// and every namespace collection goes as well.
+///
+/// Idempotent: returns `Ok(false)` if the collection did not exist,
+/// `Ok(true)` if it was removed.
+pub async fn drop_index(project_name: &str) -> Result<bool, RagBaseError> {
+    info!(
+        target: "rag_base::index",
+        project = project_name,
+        "drop_index: start"
+    );
+
+    let cfg: RagConfig = RagConfig::from_env(Some(project_name))?;
+    let client = connect(&cfg).await?;
+    let mut existed = drop_collection(&client, &cfg).await?;
+    if !cfg.namespaces.is_empty() {
+        for scope in cfg.scopes() {
+            existed |= drop_collection(&client, &scope).await?;
+        }
+    }
+
+    info!(
+        target: "rag_base::index",
+        project = project_name,
+        existed,
+        "drop_index: finished"
+    );
+    Ok(existed)
 }
 
 /// Perform semantic search and return stitched code blocks.
 ///
-/// This is the **only public search entry point**:
+/// This is the **only public search entry point**. It queries `QDRANT_COLLECTION`,
+/// which is either a plain collection ([`load_fresh_index`]) or an alias to the
+/// live `<QDRANT_COLLECTION>__v<generation>` generation ([`reindex_blue_green`]):
 /// - performs vector search with lexical re-ranking and fallback scroll;
 /// - hydrates hits from JSONL to restore exact spans;
 /// - merges overlapping spans and returns stitched code blocks with full code.
 ///
+/// With `with_facets`, also counts results per language and symbol kind
+/// (computed from the returned hits; no extra round trip). With
+/// `with_score_breakdown`, each result reports how vector similarity and the
+/// lexical re-rank contributed to its rank. With `explain`, the query
+/// embedding size/norm, raw nearest-neighbor distances and lexical terms are
+/// attached as [`ExplainInfo`](structs::search_result::ExplainInfo). With
+/// `definitions_only`, only chunks that declare a symbol (`is_definition`)
+/// are returned; usage sites are filtered out in Qdrant and after re-ranking.
+///
 /// The result is JSON-serializable and can be returned directly from an HTTP API.
 pub async fn search_code(
     project_name: &str,
     query: &str,
-    k: Option<usize>,
-) -> Result<Vec<CodeSearchResult>, RagBaseError> {
-    let hits = search::search_hits(project_name, query, k).await?;
-    let results = stitcher::search_hits_to_code_results(project_name, &hits, k).await?;
-    Ok(results)
+    params: &SearchParams,
+) -> Result<CodeSearchResults, RagBaseError> {
+    metrics::counter!("search_requests_total").increment(1);
+    let (hits, explain) = search::search_hits(project_name, query, params).await?;
+    let results = stitcher::search_hits_to_code_results(project_name, &hits, params.k).await?;
+    let facets = params
+        .with_facets
+        .then(|| SearchFacets::from_results(&results));
+    Ok(CodeSearchResults {
+        results,
+        facets,
+        explain,
+    })
 }
@@ -1,27 +1,41 @@
 //! Search pipeline: vector search, lexical re-ranking and fallback scroll.
 
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
+use qdrant_client::Qdrant;
 use qdrant_client::qdrant::{Condition, FieldCondition, Filter, Match, MinShould};
 use regex::Regex;
 use tracing::{debug, info, warn};
 
 use crate::embedding::embed_texts_ollama;
 use crate::errors::rag_base_error::RagBaseError;
-use crate::structs::rag_base_config::RagConfig;
-use crate::structs::rag_store::SearchHit;
-use crate::vector_db::{connect, scroll_points_filtered, search_top_k as db_search_top_k};
+use crate::structs::rag_base_config::{DistanceMetric, RagConfig};
+use crate::structs::rag_store::{ScoreBreakdown, SearchHit};
+use crate::structs::search_result::{ExplainInfo, SearchParams};
+use crate::vector_db::{
+    collection_embedding, connect, reconnect, scroll_points_filtered,
+    search_top_k as db_search_top_k,
+};
 
 /// Perform semantic search (top-k) with lexical re-ranking and a robust fallback
 /// for short or code-like queries.
 ///
 /// This function returns raw `SearchHit` items without stitched code.
 /// Stitched code blocks are produced separately in the `stitcher` module.
+/// With `params.with_score_breakdown`, each hit carries its [`ScoreBreakdown`];
+/// with `params.explain`, the query's [`ExplainInfo`] is returned alongside.
+/// Payload conditions from [`payload_must`] (e.g. `params.definitions_only`)
+/// apply to both the vector search and the fallback scroll.
+///
+/// With namespaces, every namespace collection is searched with the query
+/// embedded by the model recorded on that collection, and the hits are
+/// merged by score (the explain info is the first namespace's).
 pub async fn search_hits(
     project_name: &str,
     query: &str,
-    k: Option<usize>,
-) -> Result<Vec<SearchHit>, RagBaseError> {
+    params: &SearchParams,
+) -> Result<(Vec<SearchHit>, Option<ExplainInfo>), RagBaseError> {
     info!(
         target: "rag_base::search",
         project = project_name,
@@ -36,24 +50,91 @@ pub async fn search_hits(
             target: "rag_base::search",
             "search_hits: search disabled by config"
         );
-        return Ok(Vec::new());
+        return Ok((Vec::new(), None));
+    }
+
+    // Shared Qdrant client (pooled across requests).
+    let mut client = connect(&cfg).await?;
+    if cfg.namespaces.is_empty() {
+        return search_collection(&cfg, &mut client, query, params).await;
     }
 
-    // Connect to Qdrant.
-    let client = connect(&cfg).await?;
+    let want = params.k.unwrap_or(cfg.search.top_k);
+    let mut per_namespace = Vec::with_capacity(cfg.namespaces.len());
+    let mut explain = None;
+    for mut scope in cfg.scopes() {
+        collection_embedding(&client, &scope, &scope.qdrant.collection)
+            .await?
+            .apply(&mut scope);
+        debug!(
+            target: "rag_base::search",
+            namespace = ?scope.namespace,
+            model = %scope.embedding.model,
+            dim = scope.embedding.dim,
+            "search_hits: searching namespace"
+        );
+        let (hits, ex) = search_collection(&scope, &mut client, query, params).await?;
+        explain = explain.or(ex);
+        per_namespace.push(hits);
+    }
+    Ok((merge_namespace_hits(per_namespace, want), explain))
+}
+
+/// Hits of all namespaces, best score first, capped at `want`.
+fn merge_namespace_hits(per_namespace: Vec<Vec<SearchHit>>, want: usize) -> Vec<SearchHit> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut merged: Vec<SearchHit> = per_namespace
+        .into_iter()
+        .flatten()
+        .filter(|h| seen.insert(h.id.clone()))
+        .collect();
+    merged.sort_by(|a, b| b.score.total_cmp(&a.score));
+    merged.truncate(want);
+    merged
+}
+
+/// [`search_hits`] against the single collection of `cfg`.
+async fn search_collection(
+    cfg: &RagConfig,
+    client: &mut Arc<Qdrant>,
+    query: &str,
+    params: &SearchParams,
+) -> Result<(Vec<SearchHit>, Option<ExplainInfo>), RagBaseError> {
+    let breakdown = params.with_score_breakdown;
 
     // Embed the query using the same model/dimension.
-    let query_vecs = embed_texts_ollama(&cfg, &[query.to_string()]).await?;
+    let query_vecs = embed_texts_ollama(cfg, &[query.to_string()]).await?;
     let query_vec = query_vecs
         .into_iter()
         .next()
         .ok_or_else(|| RagBaseError::Embedding("empty embedding response".into()))?;
 
-    let want = k.unwrap_or(cfg.search.top_k);
-
-    // 1) Primary vector search without payload filter.
-    let mut primary_hits = db_search_top_k(&client, &cfg, query_vec.clone(), want).await?;
-    lexical_rerank(query, &mut primary_hits);
+    let want = params.k.unwrap_or(cfg.search.top_k);
+    let must = payload_must(params);
+    let primary_filter = (!must.is_empty()).then(|| Filter::must(must.clone()));
+
+    // 1) Primary vector search (payload filter only for explicit options).
+    // A pooled client can go stale (e.g. Qdrant restarted): reconnect once on
+    // a connection failure. Timeouts and query errors are returned as-is.
+    let mut primary_hits =
+        match db_search_top_k(client, cfg, query_vec.clone(), want, primary_filter.clone()).await {
+            Ok(hits) => hits,
+            Err(e) if e.is_connection_error() => {
+                warn!(
+                    target: "rag_base::search",
+                    error = %e,
+                    "search_hits: qdrant connection failed, retrying with a fresh client"
+                );
+                *client = reconnect(cfg).await?;
+                db_search_top_k(client, cfg, query_vec.clone(), want, primary_filter).await?
+            }
+            Err(e) => return Err(e),
+        };
+    let explain = params
+        .explain
+        .then(|| explain_info(query, &query_vec, &primary_hits, cfg.qdrant.distance));
+    lexical_rerank(query, &mut primary_hits, breakdown);
+    retain_requested(&mut primary_hits, params);
 
     if let Some(min_s) = cfg.search.min_score {
         primary_hits.retain(|h| h.score >= min_s);
@@ -67,9 +148,10 @@ pub async fn search_hits(
             target: "rag_base::search",
             "search_hits: no search_terms filter from query, returning primary hits"
         );
-        return Ok(primary_hits);
+        return Ok((primary_hits, explain));
     }
-    let filter = filter_opt.unwrap();
+    let mut filter = filter_opt.unwrap();
+    filter.must.extend(must);
 
     let scroll_limit = cfg
         .search
@@ -84,10 +166,11 @@ pub async fn search_hits(
         "search_hits: running fallback scroll with search_terms filter"
     );
 
-    let mut fallback_hits = scroll_points_filtered(&client, &cfg, filter, scroll_limit).await?;
+    let mut fallback_hits = scroll_points_filtered(client, cfg, filter, scroll_limit).await?;
 
     // Lexical rerank for fallback hits.
-    lexical_rerank(query, &mut fallback_hits);
+    lexical_rerank(query, &mut fallback_hits, breakdown);
+    retain_requested(&mut fallback_hits, params);
 
     if let Some(min_s) = cfg.search.min_score {
         fallback_hits.retain(|h| h.score >= min_s);
@@ -115,7 +198,7 @@ pub async fn search_hits(
     }
 
     // Final rerank on combined list.
-    lexical_rerank(query, &mut merged);
+    lexical_rerank(query, &mut merged, breakdown);
 
     merged.truncate(want);
 
@@ -132,44 +215,78 @@ pub async fn search_hits(
         );
     }
 
-    Ok(merged)
+    Ok((merged, explain))
 }
 
-/// Lexical re-ranking with IDF-like boosts and key:"value" proximity.
-fn lexical_rerank(query: &str, hits: &mut [SearchHit]) {
-    let q = query.to_lowercase();
+/// Query embedding stats, primary-search distances (in Qdrant's order) and
+/// the lexical terms of `query`.
+fn explain_info(
+    query: &str,
+    query_vec: &[f32],
+    primary: &[SearchHit],
+    distance: DistanceMetric,
+) -> ExplainInfo {
+    let (quoted, tokens) = query_terms(&query.to_lowercase());
+    let mut lexical_terms = quoted;
+    for t in tokens {
+        if !lexical_terms.contains(&t) {
+            lexical_terms.push(t);
+        }
+    }
+    ExplainInfo {
+        query_dim: query_vec.len(),
+        query_norm: query_vec.iter().map(|x| x * x).sum::<f32>().sqrt(),
+        raw_distances: primary
+            .iter()
+            .map(|h| match distance {
+                DistanceMetric::Cosine => 1.0 - h.score,
+                DistanceMetric::Dot | DistanceMetric::Euclid => h.score,
+            })
+            .collect(),
+        lexical_terms,
+    }
+}
 
-    // Extract quoted substrings.
-    let quoted: Vec<String> = {
-        let mut out = Vec::new();
-        let mut cur = String::new();
-        let mut in_quote: Option<char> = None;
-        for ch in q.chars() {
-            match (in_quote, ch) {
-                (None, '\'' | '"') => {
-                    in_quote = Some(ch);
-                    cur.clear();
-                }
-                (Some(qc), c) if c == qc => {
-                    if !cur.is_empty() {
-                        out.push(cur.clone());
-                    }
-                    cur.clear();
-                    in_quote = None;
+/// Quoted substrings and tokens (2+ chars) of a lowercased query.
+fn query_terms(q: &str) -> (Vec<String>, Vec<String>) {
+    let mut quoted = Vec::new();
+    let mut cur = String::new();
+    let mut in_quote: Option<char> = None;
+    for ch in q.chars() {
+        match (in_quote, ch) {
+            (None, '\'' | '"') => {
+                in_quote = Some(ch);
+                cur.clear();
+            }
+            (Some(qc), c) if c == qc => {
+                if !cur.is_empty() {
+                    quoted.push(cur.clone());
                 }
-                (Some(_), c) => cur.push(c),
-                _ => {}
+                cur.clear();
+                in_quote = None;
             }
+            (Some(_), c) => cur.push(c),
+            _ => {}
         }
-        out
-    };
+    }
 
-    // Tokenize query.
-    let tokens: Vec<String> = q
+    let tokens = q
         .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '/' || c == ':'))
         .filter(|t| t.len() >= 2)
         .map(|s| s.to_string())
         .collect();
+    (quoted, tokens)
+}
+
+/// Lexical re-ranking with IDF-like boosts and key:"value" proximity.
+///
+/// Hits are ordered by vector score + lexical boost; `score` itself is left
+/// unchanged. With `explain`, both parts are recorded in `score_breakdown`.
+fn lexical_rerank(query: &str, hits: &mut [SearchHit], explain: bool) {
+    let q = query.to_lowercase();
+
+    // Quoted substrings and query tokens.
+    let (quoted, tokens) = query_terms(&q);
 
     // Optional language hint.
     let lang_hint = tokens.get(0).and_then(|t| match t.as_str() {
@@ -204,13 +321,6 @@ fn lexical_rerank(query: &str, hits: &mut [SearchHit]) {
     // Build haystacks in the same order as current hits.
     let haystacks: Vec<String> = hits.iter().map(build_haystack).collect();
 
-    // Map from id to index.
-    let id_to_idx: HashMap<String, usize> = hits
-        .iter()
-        .enumerate()
-        .map(|(i, h)| (h.id.clone(), i))
-        .collect();
-
     // Document frequency for tokens across haystacks.
     let mut df = HashMap::<String, usize>::new();
     for h in &haystacks {
@@ -231,49 +341,52 @@ fn lexical_rerank(query: &str, hits: &mut [SearchHit]) {
     let w_kv_near = 0.70_f32;
     let w_kv_any = 0.30_f32;
 
-    hits.sort_by(|a, b| {
-        let ia = *id_to_idx.get(&a.id).unwrap_or(&0);
-        let ib = *id_to_idx.get(&b.id).unwrap_or(&0);
-
-        let sa = combined_score_advanced(
-            a,
-            &haystacks[ia],
-            &tokens,
-            &quoted,
-            &q,
-            &key_val_pairs,
-            lang_hint,
-            n_docs,
-            &df,
-            w_token_base,
-            w_sub,
-            w_full,
-            w_all_subs,
-            w_lang,
-            w_kv_near,
-            w_kv_any,
-        );
-        let sb = combined_score_advanced(
-            b,
-            &haystacks[ib],
-            &tokens,
-            &quoted,
-            &q,
-            &key_val_pairs,
-            lang_hint,
-            n_docs,
-            &df,
-            w_token_base,
-            w_sub,
-            w_full,
-            w_all_subs,
-            w_lang,
-            w_kv_near,
-            w_kv_any,
-        );
+    // Score every hit once, then reorder by fused score (stable for ties).
+    let lexical: Vec<f32> = hits
+        .iter()
+        .zip(&haystacks)
+        .map(|(h, hay)| {
+            lexical_boost(
+                h,
+                hay,
+                &tokens,
+                &quoted,
+                &q,
+                &key_val_pairs,
+                lang_hint,
+                n_docs,
+                &df,
+                w_token_base,
+                w_sub,
+                w_full,
+                w_all_subs,
+                w_lang,
+                w_kv_near,
+                w_kv_any,
+            )
+        })
+        .collect();
 
+    let mut order: Vec<usize> = (0..hits.len()).collect();
+    order.sort_by(|&a, &b| {
+        let sa = hits[a].score + lexical[a];
+        let sb = hits[b].score + lexical[b];
         sb.partial_cmp(&sa).unwrap_or(std::cmp::Ordering::Equal)
     });
+
+    let reordered: Vec<SearchHit> = order
+        .into_iter()
+        .map(|i| {
+            let mut h = hits[i].clone();
+            h.score_breakdown = explain.then(|| ScoreBreakdown {
+                vector: h.score,
+                lexical: lexical[i],
+                fused: h.score + lexical[i],
+            });
+            h
+        })
+        .collect();
+    hits.clone_from_slice(&reordered);
 }
 
 /// Build lexical haystack from hit fields.
@@ -294,7 +407,8 @@ fn build_haystack(hit: &SearchHit) -> String {
     buf.to_lowercase()
 }
 
-fn combined_score_advanced(
+/// Lexical boost of `hit` for the query (added on top of its vector score).
+fn lexical_boost(
     hit: &SearchHit,
     hay: &str,
     tokens: &[String],
@@ -369,7 +483,25 @@ fn combined_score_advanced(
         }
     }
 
-    hit.score + boost
+    boost
+}
+
+/// Payload conditions every hit must satisfy for `params`; more filters
+/// (language, path) compose by adding conditions here.
+fn payload_must(params: &SearchParams) -> Vec<Condition> {
+    let mut must = Vec::new();
+    if params.definitions_only {
+        must.push(Condition::matches("is_definition", true));
+    }
+    must
+}
+
+/// Drops hits that don't satisfy [`payload_must`]; guards points indexed
+/// before a payload field existed.
+fn retain_requested(hits: &mut Vec<SearchHit>, params: &SearchParams) {
+    if params.definitions_only {
+        hits.retain(|h| h.is_definition);
+    }
 }
 
 /// Build a `Filter` over `search_terms` based on the query text.
@@ -468,3 +600,107 @@ fn has_strong_lexical_match(query: &str, hits: &[SearchHit]) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(id: &str, score: f32, symbol_path: &str) -> SearchHit {
+        SearchHit {
+            score,
+            id: id.into(),
+            file: "lib/a.dart".into(),
+            language: "dart".into(),
+            kind: "function".into(),
+            symbol_path: symbol_path.into(),
+            symbol: id.into(),
+            signature: None,
+            snippet: None,
+            is_definition: false,
+            score_breakdown: None,
+        }
+    }
+
+    #[test]
+    fn definitions_only_returns_the_class_definition_not_a_usage() {
+        let usage = SearchHit {
+            snippet: Some("final repo = UserRepo(api);".into()),
+            ..hit("usage", 0.9, "lib/profile_page.dart::ProfilePage.build")
+        };
+        let definition = SearchHit {
+            kind: "class".into(),
+            is_definition: true,
+            ..hit("definition", 0.6, "lib/user_repo.dart::UserRepo")
+        };
+        let run = |params: &SearchParams| {
+            let mut hits = vec![usage.clone(), definition.clone()];
+            lexical_rerank("UserRepo", &mut hits, false);
+            retain_requested(&mut hits, params);
+            hits.into_iter().map(|h| h.id).collect::<Vec<_>>()
+        };
+
+        let all = SearchParams::default();
+        assert!(payload_must(&all).is_empty());
+        assert_eq!(run(&all).len(), 2);
+
+        let defs = SearchParams {
+            definitions_only: true,
+            ..SearchParams::default()
+        };
+        assert_eq!(payload_must(&defs).len(), 1);
+        assert_eq!(run(&defs), ["definition"]);
+    }
+
+    #[test]
+    fn namespace_hits_are_merged_by_score() {
+        let code = vec![hit("c1", 0.9, "a::b"), hit("c2", 0.4, "a::c")];
+        let docs = vec![hit("d1", 0.7, "README"), hit("d2", 0.1, "GUIDE")];
+        let merged = merge_namespace_hits(vec![code, docs], 3);
+        let ids: Vec<&str> = merged.iter().map(|h| h.id.as_str()).collect();
+        assert_eq!(ids, ["c1", "d1", "c2"]);
+    }
+
+    #[test]
+    fn rerank_explains_vector_and_lexical_parts() {
+        let mut hits = vec![
+            hit("near", 0.80, "lib/a.dart::unrelated"),
+            hit("exact", 0.60, "lib/a.dart::loadProfile"),
+        ];
+        lexical_rerank("loadprofile", &mut hits, true);
+
+        assert_eq!(hits[0].id, "exact");
+        let b = hits[0].score_breakdown.unwrap();
+        assert_eq!(b.vector, 0.60);
+        assert!(b.lexical > 0.0);
+        assert_eq!(b.fused, b.vector + b.lexical);
+        assert_eq!(hits[1].score_breakdown.unwrap().lexical, 0.0);
+        // Ranking never rewrites the vector score itself.
+        assert_eq!(hits[0].score, 0.60);
+
+        lexical_rerank("loadprofile", &mut hits, false);
+        assert!(hits.iter().all(|h| h.score_breakdown.is_none()));
+    }
+
+    #[test]
+    fn explain_reports_query_vector_distances_and_terms() {
+        let primary = vec![hit("a", 0.9, "x"), hit("b", 0.25, "y")];
+        let info = explain_info(
+            r#"Dart "load profile" loadProfile"#,
+            &[3.0, 4.0],
+            &primary,
+            DistanceMetric::Cosine,
+        );
+        assert_eq!(info.query_dim, 2);
+        assert_eq!(info.query_norm, 5.0);
+        assert!((info.raw_distances[0] - 0.1).abs() < 1e-6);
+        assert_eq!(info.raw_distances[1], 0.75);
+        assert_eq!(
+            info.lexical_terms,
+            ["load profile", "dart", "load", "profile", "loadprofile"]
+        );
+
+        let dot = explain_info("q", &[], &primary, DistanceMetric::Dot);
+        assert_eq!(dot.raw_distances, [0.9, 0.25]);
+        assert!(dot.lexical_terms.is_empty());
+    }
+}
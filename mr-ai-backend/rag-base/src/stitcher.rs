@@ -1,6 +1,7 @@
 //! Hydration and stitching of search hits into contiguous code blocks.
 
 use std::collections::HashMap;
+use std::path::Path;
 
 use code_indexer::CodeChunk;
 use tokio::fs::File;
@@ -8,9 +9,9 @@ use tokio::io::{AsyncBufReadExt, BufReader};
 use tracing::{debug, error, info, warn};
 
 use crate::errors::rag_base_error::RagBaseError;
-use crate::structs::rag_base_config::RagConfig;
-use crate::structs::rag_store::SearchHit;
-use crate::structs::search_result::CodeSearchResult;
+use crate::structs::rag_base_config::{RagConfig, StitchConfig};
+use crate::structs::rag_store::{ScoreBreakdown, SearchHit};
+use crate::structs::search_result::{CodeContext, CodeSearchResult};
 
 #[derive(Debug, Clone)]
 struct ChunkPiece {
@@ -22,15 +23,20 @@ struct ChunkPiece {
     symbol: String,
     signature: Option<String>,
     snippet: Option<String>,
+    /// Zero-based, inclusive.
     start_row: u32,
+    /// Zero-based, exclusive (like [`CodeSearchResult::end_row`]).
     end_row: u32,
     score: f32,
+    score_breakdown: Option<ScoreBreakdown>,
 }
 
 /// Convert raw `SearchHit` items into stitched code results:
 /// - resolve hit IDs back to `CodeChunk` entries in JSONL to get spans;
 /// - group chunks by file and merge overlapping/adjacent spans;
 /// - read original files and slice lines by merged spans;
+/// - cap oversized blocks around the best hit (`RagConfig::stitch`), flagging `truncated`;
+/// - attach one-based line numbers and optional context lines for UI gutters;
 /// - return JSON-friendly `CodeSearchResult` items sorted by score.
 pub async fn search_hits_to_code_results(
     project_name: &str,
@@ -56,7 +62,7 @@ pub async fn search_hits_to_code_results(
         hit_map.insert(h.id.clone(), h.clone());
     }
 
-    let by_file = load_pieces_from_jsonl(&cfg, &hit_map).await?;
+    let by_file = load_pieces_from_jsonl(&cfg.code_jsonl, &hit_map).await?;
     if by_file.is_empty() {
         warn!(
             target: "rag_base::stitcher",
@@ -67,24 +73,11 @@ pub async fn search_hits_to_code_results(
 
     let mut results: Vec<CodeSearchResult> = Vec::new();
 
-    for (file, mut pieces) in by_file {
+    for (file, pieces) in by_file {
         if pieces.is_empty() {
             continue;
         }
 
-        // Sort by start_row to make merging deterministic.
-        pieces.sort_by_key(|p| p.start_row);
-
-        debug!(
-            target: "rag_base::stitcher",
-            file = %file,
-            chunk_count = pieces.len(),
-            "search_hits_to_code_results: merging spans for file"
-        );
-
-        // Build merged blocks: each block keeps best-scoring piece for metadata.
-        let blocks = merge_pieces_into_blocks(&file, pieces);
-
         // Read source file once per file.
         let source = match tokio::fs::read_to_string(&file).await {
             Ok(s) => s,
@@ -100,28 +93,7 @@ pub async fn search_hits_to_code_results(
         };
         let lines: Vec<&str> = source.lines().collect();
 
-        for block in blocks {
-            let code = slice_lines(&lines, block.start_row, block.end_row);
-            if code.is_empty() {
-                continue;
-            }
-
-            let best = block.best_piece;
-
-            results.push(CodeSearchResult {
-                score: best.score,
-                file: file.clone(),
-                language: best.language,
-                kind: best.kind,
-                symbol_path: best.symbol_path,
-                symbol: best.symbol,
-                signature: best.signature,
-                snippet: best.snippet,
-                code,
-                start_row: block.start_row,
-                end_row: block.end_row,
-            });
-        }
+        results.extend(stitch_file(&file, pieces, &lines, &cfg.stitch));
     }
 
     // Sort by score descending.
@@ -146,6 +118,92 @@ pub async fn search_hits_to_code_results(
     Ok(results)
 }
 
+/// Merge, cap and slice the hit pieces of one file into results.
+///
+/// `lines` is the current source of `file`.
+fn stitch_file(
+    file: &str,
+    mut pieces: Vec<ChunkPiece>,
+    lines: &[&str],
+    caps: &StitchConfig,
+) -> Vec<CodeSearchResult> {
+    // Sort by start_row to make merging deterministic.
+    pieces.sort_by_key(|p| p.start_row);
+
+    debug!(
+        target: "rag_base::stitcher",
+        file = %file,
+        chunk_count = pieces.len(),
+        "search_hits_to_code_results: merging spans for file"
+    );
+
+    // Build merged blocks: each block keeps best-scoring piece for metadata.
+    let blocks = merge_pieces_into_blocks(file, pieces);
+
+    let mut out = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        let (start_row, end_row, code, truncated) = cap_block(lines, &block, caps);
+        if truncated {
+            debug!(
+                target: "rag_base::stitcher",
+                file = %file,
+                from = ?(block.start_row, block.end_row),
+                to = ?(start_row, end_row),
+                "search_hits_to_code_results: block truncated"
+            );
+        }
+
+        if code.is_empty() {
+            continue;
+        }
+        // `slice_lines` clamps to the file; keep numbers consistent with `code`.
+        let end_row = end_row.min(lines.len() as u32);
+        let context =
+            (caps.context_lines > 0).then(|| context_around(lines, start_row, end_row, caps));
+
+        let best = block.best_piece;
+
+        out.push(CodeSearchResult {
+            score: best.score,
+            file: file.to_string(),
+            language: best.language,
+            kind: best.kind,
+            symbol_path: best.symbol_path,
+            symbol: best.symbol,
+            signature: best.signature,
+            snippet: best.snippet,
+            code,
+            start_row,
+            end_row,
+            truncated,
+            start_line: start_row + 1,
+            end_line: end_row,
+            context,
+            score_breakdown: best.score_breakdown,
+        });
+    }
+    out
+}
+
+/// Up to `caps.context_lines` lines on each side of `start_row..end_row`.
+fn context_around(
+    lines: &[&str],
+    start_row: u32,
+    end_row: u32,
+    caps: &StitchConfig,
+) -> CodeContext {
+    let n = caps.context_lines as u32;
+    let from = start_row.saturating_sub(n);
+    let to = end_row.saturating_add(n).min(lines.len() as u32);
+    CodeContext {
+        before: slice_lines(lines, from, start_row),
+        after: slice_lines(lines, end_row, to),
+        start_line: from + 1,
+        end_line: to,
+    }
+}
+
+/// Merged span; rows as in [`ChunkPiece`] (`end_row` exclusive).
 #[derive(Debug, Clone)]
 struct Block {
     file: String,
@@ -170,7 +228,8 @@ fn merge_pieces_into_blocks(file: &str, pieces: Vec<ChunkPiece>) -> Vec<Block> {
     let mut best_piece = first;
 
     for piece in iter {
-        if piece.start_row <= current_end + 1 {
+        // `end_row` is exclusive: a piece starting there is directly adjacent.
+        if piece.start_row <= current_end {
             // Overlapping or directly adjacent span -> extend current block.
             if piece.end_row > current_end {
                 current_end = piece.end_row;
@@ -204,21 +263,79 @@ fn merge_pieces_into_blocks(file: &str, pieces: Vec<ChunkPiece>) -> Vec<Block> {
     blocks
 }
 
+/// Shrink `block` to fit `caps`, keeping the best-scoring piece in view.
+///
+/// First cuts to `max_block_lines` centered on the best piece, then drops
+/// lines from the side farther from it until `max_total_chars` fits; a single
+/// line still over budget (e.g. minified code) is cut at `max_total_chars`.
+/// Returns `(start_row, end_row, code, truncated)`.
+fn cap_block(lines: &[&str], block: &Block, caps: &StitchConfig) -> (u32, u32, String, bool) {
+    let len = lines.len() as u32;
+    let mut start = block.start_row.min(len);
+    let mut end = block.end_row.min(len);
+    let mut truncated = false;
+
+    let best = &block.best_piece;
+    let focus = ((best.start_row.max(start) + best.end_row.min(end)) / 2)
+        .clamp(start, end.saturating_sub(1).max(start));
+
+    let max_lines = caps.max_block_lines.max(1) as u32;
+    if end - start > max_lines {
+        let half = max_lines / 2;
+        start = focus.saturating_sub(half).max(start);
+        end = (start + max_lines).min(end);
+        start = end - max_lines;
+        truncated = true;
+    }
+
+    // Line length + newline separator.
+    let chars = |s: u32, e: u32| -> usize {
+        (s..e)
+            .map(|i| lines[i as usize].len() + 1)
+            .sum::<usize>()
+            .saturating_sub(1)
+    };
+    let mut total = chars(start, end);
+    while total > caps.max_total_chars && end - start > 1 {
+        if focus - start > end.saturating_sub(1) - focus {
+            total -= lines[start as usize].len() + 1;
+            start += 1;
+        } else {
+            end -= 1;
+            total -= lines[end as usize].len() + 1;
+        }
+        truncated = true;
+    }
+
+    let mut code = slice_lines(lines, start, end);
+    if code.len() > caps.max_total_chars {
+        let mut cut = caps.max_total_chars;
+        while !code.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        code.truncate(cut);
+        truncated = true;
+    }
+
+    (start, end, code, truncated)
+}
+
 /// Load `ChunkPiece` entries from JSONL grouped by file.
 ///
-/// Only chunks whose id appears in `hit_map` are loaded.
+/// Only chunks whose id appears in `hit_map` are loaded. JSONL spans are
+/// inclusive (`end_row` is the last line); pieces use an exclusive `end_row`.
 async fn load_pieces_from_jsonl(
-    cfg: &RagConfig,
+    code_jsonl: &Path,
     hit_map: &HashMap<String, SearchHit>,
 ) -> Result<HashMap<String, Vec<ChunkPiece>>, RagBaseError> {
     info!(
         target: "rag_base::stitcher",
-        path = %cfg.code_jsonl.display(),
+        path = %code_jsonl.display(),
         wanted = hit_map.len(),
         "load_pieces_from_jsonl: start"
     );
 
-    let file = File::open(cfg.code_jsonl.as_path()).await?;
+    let file = File::open(code_jsonl).await?;
     let reader = BufReader::new(file);
     let mut lines = reader.lines();
 
@@ -263,8 +380,9 @@ async fn load_pieces_from_jsonl(
             signature: hit.signature.clone(),
             snippet: hit.snippet.clone(),
             start_row: span.start_row as u32,
-            end_row: span.end_row as u32,
+            end_row: span.end_row as u32 + 1,
             score: hit.score,
+            score_breakdown: hit.score_breakdown,
         };
 
         by_file.entry(piece.file.clone()).or_default().push(piece);
@@ -304,3 +422,192 @@ fn slice_lines(lines: &[&str], start_row: u32, end_row: u32) -> String {
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece(start_row: u32, end_row: u32, score: f32) -> ChunkPiece {
+        ChunkPiece {
+            id: format!("id-{start_row}"),
+            file: "big.dart".into(),
+            language: "dart".into(),
+            kind: "function".into(),
+            symbol_path: "big.dart::f".into(),
+            symbol: "f".into(),
+            signature: None,
+            snippet: None,
+            start_row,
+            end_row,
+            score,
+            score_breakdown: None,
+        }
+    }
+
+    #[test]
+    fn oversized_block_is_truncated_around_best_hit() {
+        let source: Vec<String> = (0..5000).map(|i| format!("line {i}")).collect();
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+
+        // One huge merged span with the best hit deep inside it.
+        let blocks = merge_pieces_into_blocks(
+            "big.dart",
+            vec![
+                piece(0, 3000, 0.2),
+                piece(2500, 2520, 0.9),
+                piece(2990, 5000, 0.1),
+            ],
+        );
+        assert_eq!(blocks.len(), 1);
+
+        let caps = StitchConfig {
+            max_block_lines: 100,
+            max_total_chars: 600,
+            context_lines: 0,
+        };
+        let (start, end, code, truncated) = cap_block(&lines, &blocks[0], &caps);
+
+        assert!(truncated);
+        assert!(start <= 2510 && 2510 < end, "best hit must stay in view");
+        assert_eq!(code, slice_lines(&lines, start, end));
+        assert!(code.len() <= caps.max_total_chars);
+        assert!(code.contains("line 2510"));
+    }
+
+    #[test]
+    fn only_overlapping_or_adjacent_pieces_merge() {
+        let spans = |pieces| {
+            merge_pieces_into_blocks("a.dart", pieces)
+                .iter()
+                .map(|b| (b.start_row, b.end_row))
+                .collect::<Vec<_>>()
+        };
+        // Rows 4..10 then 10..15: adjacent, one block.
+        assert_eq!(
+            spans(vec![piece(4, 10, 0.5), piece(10, 15, 0.4)]),
+            vec![(4, 15)]
+        );
+        // Row 10 belongs to neither piece: it must not be pulled in.
+        assert_eq!(
+            spans(vec![piece(4, 10, 0.5), piece(11, 15, 0.4)]),
+            vec![(4, 10), (11, 15)]
+        );
+    }
+
+    #[test]
+    fn small_block_is_untouched() {
+        let source: Vec<String> = (0..50).map(|i| format!("line {i}")).collect();
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+        let blocks = merge_pieces_into_blocks("small.dart", vec![piece(10, 20, 0.5)]);
+
+        let (start, end, code, truncated) = cap_block(&lines, &blocks[0], &StitchConfig::default());
+        assert_eq!((start, end, truncated), (10, 20, false));
+        assert_eq!(code, slice_lines(&lines, 10, 20));
+    }
+
+    #[test]
+    fn best_piece_score_breakdown_reaches_the_result() {
+        let source: Vec<String> = (0..50).map(|i| format!("line {i}")).collect();
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+        let breakdown = ScoreBreakdown {
+            vector: 0.6,
+            lexical: 0.3,
+            fused: 0.9,
+        };
+        let best = ChunkPiece {
+            score_breakdown: Some(breakdown),
+            ..piece(12, 18, 0.9)
+        };
+
+        let out = stitch_file(
+            "a.dart",
+            vec![piece(10, 14, 0.4), best],
+            &lines,
+            &StitchConfig::default(),
+        );
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].score_breakdown, Some(breakdown));
+        let json = serde_json::to_value(&out[0]).unwrap();
+        assert_eq!(json["score_breakdown"]["fused"], 0.9f32 as f64);
+    }
+
+    #[test]
+    fn single_huge_line_is_cut_to_the_char_budget() {
+        let minified = "é".repeat(5_000); // 10,000 bytes on one line
+        let lines = vec!["// header", minified.as_str(), "// footer"];
+        let blocks = merge_pieces_into_blocks("bundle.min.js", vec![piece(1, 2, 0.9)]);
+        let caps = StitchConfig {
+            max_block_lines: 100,
+            max_total_chars: 601,
+            context_lines: 0,
+        };
+
+        let (start, end, code, truncated) = cap_block(&lines, &blocks[0], &caps);
+        assert_eq!((start, end), (1, 2));
+        assert!(truncated);
+        assert_eq!(code.len(), 600, "cut back to a char boundary");
+        assert!(minified.starts_with(&code));
+    }
+
+    #[tokio::test]
+    async fn line_numbers_match_hydrated_spans() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("a.dart");
+        let source: Vec<String> = (1..=40).map(|i| format!("line {i}")).collect();
+        std::fs::write(&src, source.join("\n")).unwrap();
+        let file = src.to_string_lossy().into_owned();
+
+        // Inclusive zero-based rows, as written by code-indexer.
+        let chunk = |id: &str, start_row: usize, end_row: usize| {
+            serde_json::json!({
+                "id": id, "language": "dart", "file": file, "symbol": "f",
+                "symbol_path": "a.dart::f", "kind": "function",
+                "span": {
+                    "start_byte": 0, "end_byte": 0, "start_row": start_row,
+                    "start_col": 0, "end_row": end_row, "end_col": 0
+                },
+                "owner_path": [], "annotations": [], "imports": [],
+                "is_definition": true, "is_generated": false,
+                "features": { "byte_len": 0, "line_count": 0, "has_doc": false, "has_annotations": false },
+                "content_sha256": "", "identifiers": [], "anchors": []
+            })
+            .to_string()
+        };
+        let jsonl = dir.path().join("chunks.jsonl");
+        std::fs::write(&jsonl, [chunk("a", 4, 9), chunk("b", 30, 31)].join("\n")).unwrap();
+
+        let hit = |id: &str| SearchHit {
+            score: 0.5,
+            id: id.into(),
+            file: file.clone(),
+            language: "dart".into(),
+            kind: "function".into(),
+            symbol_path: "a.dart::f".into(),
+            symbol: "f".into(),
+            signature: None,
+            snippet: None,
+            is_definition: true,
+            score_breakdown: None,
+        };
+        let hit_map: HashMap<String, SearchHit> =
+            [("a".to_string(), hit("a")), ("b".to_string(), hit("b"))].into();
+
+        let by_file = load_pieces_from_jsonl(&jsonl, &hit_map).await.unwrap();
+        let pieces = by_file.into_values().next().unwrap();
+        let lines: Vec<&str> = source.iter().map(String::as_str).collect();
+        let caps = StitchConfig {
+            context_lines: 2,
+            ..StitchConfig::default()
+        };
+        let results = stitch_file(&file, pieces, &lines, &caps);
+
+        let spans: Vec<(u32, u32)> = results.iter().map(|r| (r.start_line, r.end_line)).collect();
+        assert_eq!(spans, vec![(5, 10), (31, 32)]);
+        let first = &results[0];
+        assert!(first.code.starts_with("line 5") && first.code.ends_with("line 10"));
+        let ctx = first.context.as_ref().unwrap();
+        assert_eq!((ctx.start_line, ctx.end_line), (3, 12));
+        assert_eq!(ctx.before, "line 3\nline 4");
+        assert_eq!(ctx.after, "line 11\nline 12");
+    }
+}
@@ -1,9 +1,14 @@
 //! Configuration layer: reads runtime settings from environment variables
 //! and exposes strongly typed configs for embeddings, Qdrant, and search.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use code_indexer::LanguageKind;
 use serde::{Deserialize, Serialize};
+use services::namespaces::{self, EmbeddingNamespace};
+
+pub use services::embed_window::LongTextMode;
 
 use crate::errors::rag_base_error::RagBaseError;
 
@@ -41,6 +46,14 @@ pub struct EmbeddingConfig {
     pub dim: usize,
     /// Max concurrent embedding workers.
     pub concurrency: usize,
+    /// Oversized input handling (truncate by default).
+    pub long_text: LongTextMode,
+    /// Window size in chars for the windowed modes (fits the model context).
+    pub window_chars: usize,
+    /// Overlap between consecutive windows in chars.
+    pub window_overlap_chars: usize,
+    /// Max windows per text; anything beyond is dropped.
+    pub max_windows: usize,
 }
 
 impl Default for EmbeddingConfig {
@@ -49,6 +62,10 @@ impl Default for EmbeddingConfig {
             model: "bge-m3".to_string(),
             dim: 1024,
             concurrency: 4,
+            long_text: LongTextMode::Truncate,
+            window_chars: 1200,
+            window_overlap_chars: 200,
+            max_windows: 8,
         }
     }
 }
@@ -64,6 +81,8 @@ pub struct QdrantConfig {
     pub distance: DistanceMetric,
     /// Batch size for upserts (vectors + payloads).
     pub batch_size: usize,
+    /// Max batches read from JSONL but not yet embedded/upserted (ingest backpressure).
+    pub max_in_flight_batches: usize,
 }
 
 impl Default for QdrantConfig {
@@ -73,6 +92,7 @@ impl Default for QdrantConfig {
             collection: "mr_ai_code".to_string(),
             distance: DistanceMetric::Cosine,
             batch_size: 256,
+            max_in_flight_batches: 2,
         }
     }
 }
@@ -118,6 +138,42 @@ pub struct ChunkClampConfig {
     pub preview_max_lines: usize,
     /// Ignore ultra-short chunks below this many characters (applies to both).
     pub min_chars: usize,
+    /// Per-language char budgets, e.g. roomier embeds for markdown prose.
+    #[serde(default)]
+    pub per_language: HashMap<LanguageKind, LanguageClamp>,
+}
+
+/// Char budgets for one language; `None` falls back to the global value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageClamp {
+    pub embed_max_chars: Option<usize>,
+    pub preview_max_chars: Option<usize>,
+}
+
+impl ChunkClampConfig {
+    /// Embedding char budget for `lang`.
+    pub fn embed_max_chars_for(&self, lang: LanguageKind) -> usize {
+        self.override_for(lang)
+            .and_then(|o| o.embed_max_chars)
+            .unwrap_or(self.embed_max_chars)
+    }
+
+    /// Preview char budget for `lang`.
+    pub fn preview_max_chars_for(&self, lang: LanguageKind) -> usize {
+        self.override_for(lang)
+            .and_then(|o| o.preview_max_chars)
+            .unwrap_or(self.preview_max_chars)
+    }
+
+    /// True if `lang` has its own embedding budget.
+    pub fn has_embed_override(&self, lang: LanguageKind) -> bool {
+        self.override_for(lang)
+            .is_some_and(|o| o.embed_max_chars.is_some())
+    }
+
+    fn override_for(&self, lang: LanguageKind) -> Option<&LanguageClamp> {
+        self.per_language.get(&lang)
+    }
 }
 
 impl Default for ChunkClampConfig {
@@ -128,6 +184,28 @@ impl Default for ChunkClampConfig {
             preview_max_chars: 320,
             preview_max_lines: 50,
             min_chars: 16,
+            per_language: HashMap::new(),
+        }
+    }
+}
+
+/// Size caps for stitched code blocks returned by search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StitchConfig {
+    /// Max lines per stitched block; larger blocks are cut around the best hit.
+    pub max_block_lines: usize,
+    /// Max characters per stitched block (applied after the line cap).
+    pub max_total_chars: usize,
+    /// Lines of leading/trailing context attached to each block (0 = none).
+    pub context_lines: usize,
+}
+
+impl Default for StitchConfig {
+    fn default() -> Self {
+        Self {
+            max_block_lines: 200,
+            max_total_chars: 16_000,
+            context_lines: 0,
         }
     }
 }
@@ -147,6 +225,14 @@ pub struct RagConfig {
     pub search: SearchConfig,
     /// Snippet clamping bounds.
     pub clamp: ChunkClampConfig,
+    /// Stitched block size caps.
+    pub stitch: StitchConfig,
+    /// Embedding namespaces; empty means one collection for everything.
+    #[serde(default)]
+    pub namespaces: Vec<EmbeddingNamespace>,
+    /// Namespace this config is scoped to (see [`RagConfig::scopes`]).
+    #[serde(default)]
+    pub namespace: Option<String>,
 }
 
 impl RagConfig {
@@ -158,9 +244,14 @@ impl RagConfig {
     /// - `QDRANT_COLLECTION` (default: "mr_ai_code")
     /// - `QDRANT_DISTANCE` (values: "Cosine" | "Dot" | "Euclid"; default: "Cosine")
     /// - `QDRANT_BATCH_SIZE` (default: 256)
+    /// - `QDRANT_MAX_IN_FLIGHT_BATCHES` (default: 2)
     /// - `EMBEDDING_MODEL` (default: "bge-m3")
     /// - `EMBEDDING_DIM` (default: 1024)
     /// - `EMBEDDING_CONCURRENCY` (default: 4)
+    /// - `EMBEDDING_LONG_TEXT` (values: "truncate" | "mean" | "max"; default: "truncate")
+    /// - `EMBEDDING_WINDOW_CHARS` (default: 1200)
+    /// - `EMBEDDING_WINDOW_OVERLAP` (default: 200)
+    /// - `EMBEDDING_MAX_WINDOWS` (default: 8)
     /// - `RAG_DISABLE` (default: false)
     /// - `RAG_TOP_K` (default: 20)
     /// - `RAG_MIN_SCORE` (default: 0.0)
@@ -171,7 +262,15 @@ impl RagConfig {
     /// - `CLAMP_PREVIEW_MAX_LINES` (default: 50)
     /// - `CLAMP_EMBED_MAX_LINES` (default: 80)
     /// - `CHUNK_MIN_CHARS` (default: 16)
-    /// - `INDEX_JSONL_PATH` (default: `code_data/out/<PROJECT_NAME>/code_chunks.jsonl`)
+    /// - `CLAMP_EMBED_MAX_CHARS_BY_LANG` (e.g. "markdown=4000,yaml=600"; optional)
+    /// - `CLAMP_PREVIEW_MAX_CHARS_BY_LANG` (same format; optional)
+    /// - `RAG_STITCH_MAX_BLOCK_LINES` (default: 200)
+    /// - `RAG_STITCH_MAX_TOTAL_CHARS` (default: 16000)
+    /// - `RAG_STITCH_CONTEXT_LINES` (default: 0)
+    /// - `RAG_NAMESPACES` (e.g. "code|bge-m3|1024;docs|nomic-embed-text|768|markdown";
+    ///   optional, see `services::namespaces`)
+    /// - `INDEX_JSONL_PATH` (default: `<MRAI_DATA_ROOT>/out/<PROJECT_NAME>/code_chunks.jsonl`,
+    ///   root defaults to `code_data`)
     pub fn from_env(project_name: Option<&str>) -> Result<Self, RagBaseError> {
         let name = project_name
             .map(|s| s.to_string())
@@ -180,13 +279,17 @@ impl RagConfig {
 
         let code_jsonl = std::env::var("INDEX_JSONL_PATH")
             .map(PathBuf::from)
-            .unwrap_or_else(|_| PathBuf::from(format!("code_data/out/{name}/code_chunks.jsonl")));
+            .unwrap_or_else(|_| services::data_root::code_chunks_path(&name));
 
         // Embedding
         let embedding = EmbeddingConfig {
             model: std::env::var("EMBEDDING_MODEL").unwrap_or_else(|_| "bge-m3".into()),
             dim: read_usize_env("EMBEDDING_DIM").unwrap_or(1024),
             concurrency: read_usize_env("EMBEDDING_CONCURRENCY").unwrap_or(4),
+            long_text: LongTextMode::from_env(std::env::var("EMBEDDING_LONG_TEXT").ok()),
+            window_chars: read_usize_env("EMBEDDING_WINDOW_CHARS").unwrap_or(1200),
+            window_overlap_chars: read_usize_env("EMBEDDING_WINDOW_OVERLAP").unwrap_or(200),
+            max_windows: read_usize_env("EMBEDDING_MAX_WINDOWS").unwrap_or(8),
         };
 
         // Qdrant
@@ -195,6 +298,7 @@ impl RagConfig {
             collection: std::env::var("QDRANT_COLLECTION").unwrap_or_else(|_| "mr_ai_code".into()),
             distance: DistanceMetric::from_env(std::env::var("QDRANT_DISTANCE").ok()),
             batch_size: read_usize_env("QDRANT_BATCH_SIZE").unwrap_or(256),
+            max_in_flight_batches: read_usize_env("QDRANT_MAX_IN_FLIGHT_BATCHES").unwrap_or(2),
         };
 
         // Search
@@ -216,6 +320,14 @@ impl RagConfig {
                 .or_else(|_| read_usize_env("CHUNK_MAX_CHARS"))
                 .unwrap_or(1200);
 
+            let mut per_language: HashMap<LanguageKind, LanguageClamp> = HashMap::new();
+            for (lang, n) in read_lang_usize_env("CLAMP_EMBED_MAX_CHARS_BY_LANG")? {
+                per_language.entry(lang).or_default().embed_max_chars = Some(n);
+            }
+            for (lang, n) in read_lang_usize_env("CLAMP_PREVIEW_MAX_CHARS_BY_LANG")? {
+                per_language.entry(lang).or_default().preview_max_chars = Some(n);
+            }
+
             ChunkClampConfig {
                 // Embedding budgets
                 embed_max_chars,
@@ -227,18 +339,45 @@ impl RagConfig {
 
                 // Minimum useful chunk size
                 min_chars: read_usize_env("CHUNK_MIN_CHARS").unwrap_or(16),
+
+                per_language,
             }
         };
 
+        // Stitch caps
+        let stitch = StitchConfig {
+            max_block_lines: read_usize_env("RAG_STITCH_MAX_BLOCK_LINES").unwrap_or(200),
+            max_total_chars: read_usize_env("RAG_STITCH_MAX_TOTAL_CHARS").unwrap_or(16_000),
+            context_lines: read_usize_env("RAG_STITCH_CONTEXT_LINES").unwrap_or(0),
+        };
+
         // Basic validations
         if embedding.dim == 0 {
             return Err(RagBaseError::InvalidConfig(
                 "EMBEDDING_DIM must be > 0".into(),
             ));
         }
+        if embedding.long_text.is_windowed()
+            && (embedding.max_windows == 0
+                || embedding.window_overlap_chars >= embedding.window_chars)
+        {
+            return Err(RagBaseError::InvalidConfig(
+                "EMBEDDING_WINDOW_OVERLAP must be < EMBEDDING_WINDOW_CHARS and EMBEDDING_MAX_WINDOWS > 0"
+                    .into(),
+            ));
+        }
+        if qdrant.max_in_flight_batches == 0 {
+            return Err(RagBaseError::InvalidConfig(
+                "QDRANT_MAX_IN_FLIGHT_BATCHES must be > 0".into(),
+            ));
+        }
         if search.top_k == 0 {
             return Err(RagBaseError::InvalidConfig("RAG_TOP_K must be > 0".into()));
         }
+        let namespaces = namespaces::from_env().map_err(|value| RagBaseError::EnvParse {
+            key: namespaces::NAMESPACES_ENV.into(),
+            value,
+        })?;
 
         Ok(Self {
             project_name: name,
@@ -247,8 +386,39 @@ impl RagConfig {
             qdrant,
             search,
             clamp,
+            stitch,
+            namespaces,
+            namespace: None,
         })
     }
+
+    /// One config per collection to index or search: a copy scoped to each
+    /// namespace (its collection, model and dim), or `self` without namespaces.
+    pub fn scopes(&self) -> Vec<RagConfig> {
+        if self.namespaces.is_empty() {
+            return vec![self.clone()];
+        }
+        self.namespaces
+            .iter()
+            .map(|ns| {
+                let mut cfg = self.clone();
+                cfg.qdrant.collection = ns.collection(&self.qdrant.collection);
+                cfg.embedding.model = ns.model.clone();
+                cfg.embedding.dim = ns.dim;
+                cfg.namespace = Some(ns.name.clone());
+                cfg
+            })
+            .collect()
+    }
+
+    /// True if chunks of `language` are indexed into this config's collection.
+    pub fn owns_language(&self, language: &str) -> bool {
+        match &self.namespace {
+            None => true,
+            Some(name) => namespaces::for_language(&self.namespaces, language)
+                .is_some_and(|ns| &ns.name == name),
+        }
+    }
 }
 
 /// Read a `usize` from env, with error mapped to `RagBaseError`.
@@ -262,6 +432,33 @@ fn read_usize_env(key: &str) -> Result<usize, RagBaseError> {
     }
 }
 
+/// Read optional `lang=n` pairs (comma-separated, snake_case language names).
+///
+/// Missing or empty variables yield no pairs; malformed entries are an error.
+fn read_lang_usize_env(key: &str) -> Result<Vec<(LanguageKind, usize)>, RagBaseError> {
+    let Ok(raw) = std::env::var(key) else {
+        return Ok(Vec::new());
+    };
+    parse_lang_usize_list(&raw).ok_or(RagBaseError::EnvParse {
+        key: key.into(),
+        value: raw,
+    })
+}
+
+fn parse_lang_usize_list(raw: &str) -> Option<Vec<(LanguageKind, usize)>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|pair| {
+            let (lang, n) = pair.split_once('=')?;
+            let lang: LanguageKind =
+                serde_json::from_value(serde_json::Value::String(lang.trim().to_lowercase()))
+                    .ok()?;
+            Some((lang, n.trim().parse().ok()?))
+        })
+        .collect()
+}
+
 /// Read an optional `bool` from env.
 fn read_bool_env(key: &str) -> Result<bool, RagBaseError> {
     match std::env::var(key) {
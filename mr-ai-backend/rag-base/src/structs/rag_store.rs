@@ -3,6 +3,8 @@
 
 use serde::{Deserialize, Serialize};
 
+pub use services::score_breakdown::ScoreBreakdown;
+
 /// Minimal payload stored alongside the vector in Qdrant.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VectorPayload {
@@ -52,6 +54,45 @@ pub struct SearchHit {
     pub symbol: String,
     pub signature: Option<String>,
     pub snippet: Option<String>,
+    /// The chunk declares `symbol` (vs. a reference/usage slice).
+    #[serde(default)]
+    pub is_definition: bool,
+
+    /// How `score` and the lexical re-rank combined; set only when requested.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<ScoreBreakdown>,
+}
+
+/// Qdrant payload index type (`FieldType`) created for a payload field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadIndexKind {
+    Keyword,
+    Bool,
+    Text,
+}
+
+/// One payload index created at collection setup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadIndex {
+    /// Payload key (a field of [`VectorPayload`]).
+    pub field: String,
+    pub kind: PayloadIndexKind,
+}
+
+/// How points are addressed and which payload keys are indexed, for tools that
+/// query Qdrant directly alongside `search_code`.
+///
+/// Point ids: `u64::from_le_bytes(blake3(chunk_id)[..8])`, where `chunk_id` is
+/// the indexer's chunk id (also stored as payload `id`). The derivation is part
+/// of the public contract; see `rag_base::point_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadSchema {
+    /// Payload key holding the chunk id the point id is derived from.
+    pub id_field: String,
+    /// Indexed payload keys in creation order; a key may carry several indexes
+    /// (e.g. `search_terms` is both keyword and text).
+    pub indexes: Vec<PayloadIndex>,
 }
 
 /// Summary statistics for a full reindex operation.
@@ -61,3 +102,27 @@ pub struct IndexStats {
     pub skipped: usize,
     pub duration_ms: u128,
 }
+
+/// Summary of an incremental (changed-files-only) reindex.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IncrementalIndexStats {
+    /// Files changed since the base ref (added, modified, deleted or renamed).
+    pub changed_files: usize,
+    /// Upsert stats for chunks of those files.
+    pub stats: IndexStats,
+}
+
+/// Outcome of a blue/green reindex (see `reindex_blue_green`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlueGreenReport {
+    /// Alias searches go through (`QDRANT_COLLECTION`).
+    pub alias: String,
+    /// Freshly built collection the alias now points to.
+    pub collection: String,
+    /// Collection the alias pointed to before the swap; kept for rollback.
+    pub previous: Option<String>,
+    /// Older generations deleted after the swap.
+    pub pruned: Vec<String>,
+    /// Ingestion stats for the new collection.
+    pub stats: IndexStats,
+}
@@ -1,5 +1,9 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::structs::rag_store::ScoreBreakdown;
+
 /// This struct is intended to be returned from the public search API and
 /// serialized to JSON for HTTP responses or logging.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,4 +40,227 @@ pub struct CodeSearchResult {
 
     /// Zero-based end line (exclusive) of the stitched block.
     pub end_row: u32,
+
+    /// True if the merged block exceeded the stitch caps and was cut
+    /// around the highest-scoring hit.
+    #[serde(default)]
+    pub truncated: bool,
+
+    /// One-based number of the first line of `code` (for line gutters).
+    #[serde(default)]
+    pub start_line: u32,
+
+    /// One-based number of the last line of `code` (inclusive).
+    #[serde(default)]
+    pub end_line: u32,
+
+    /// Surrounding lines for code viewers; present when
+    /// `StitchConfig::context_lines` > 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<CodeContext>,
+
+    /// Ranking components of the best hit in the block; present only when
+    /// requested via `with_score_breakdown`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<ScoreBreakdown>,
+}
+
+/// Leading/trailing lines around a stitched block. `code` itself is not
+/// repeated, so `before + code + after` spans `start_line..=end_line`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CodeContext {
+    /// Lines directly above `code` (may be empty at the top of the file).
+    pub before: String,
+
+    /// Lines directly below `code` (may be empty at the end of the file).
+    pub after: String,
+
+    /// One-based number of the first line of `before` (or of `code` if empty).
+    pub start_line: u32,
+
+    /// One-based number of the last line of `after` (or of `code` if empty).
+    pub end_line: u32,
+}
+
+/// Facet counts over the returned results (for search UIs).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchFacets {
+    /// Result count per language, e.g. `{"dart": 7, "kotlin": 2}`.
+    pub language: HashMap<String, usize>,
+    /// Result count per symbol kind, e.g. `{"function": 5, "class": 4}`.
+    pub kind: HashMap<String, usize>,
+}
+
+impl SearchFacets {
+    /// Counts languages and kinds across `results`.
+    pub fn from_results(results: &[CodeSearchResult]) -> Self {
+        let mut facets = Self::default();
+        for r in results {
+            *facets.language.entry(r.language.clone()).or_default() += 1;
+            *facets.kind.entry(r.kind.clone()).or_default() += 1;
+        }
+        facets
+    }
+}
+
+/// Renders stitched blocks as plain source, in result order, each preceded
+/// by a `// file: <path>` line and separated by a blank line.
+///
+/// For terminals and editors that want code rather than JSON.
+pub fn as_plain_text(results: &[CodeSearchResult]) -> String {
+    let mut out = String::new();
+    for (i, r) in results.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str("// file: ");
+        out.push_str(&r.file);
+        out.push('\n');
+        out.push_str(&r.code);
+        if !r.code.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Options for [`crate::search_code`]; everything beyond `k` is off by default.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchParams {
+    /// Number of results (`RagConfig::search.top_k` when `None`).
+    #[serde(default)]
+    pub k: Option<usize>,
+
+    /// Count results per language and symbol kind.
+    #[serde(default)]
+    pub with_facets: bool,
+
+    /// Report vector/lexical/fused scores per result.
+    #[serde(default)]
+    pub with_score_breakdown: bool,
+
+    /// Attach [`ExplainInfo`] for debugging retrieval quality.
+    #[serde(default)]
+    pub explain: bool,
+
+    /// Only return definition chunks (`is_definition`), e.g. for
+    /// "where is X defined" queries; usage/reference chunks are dropped.
+    #[serde(default)]
+    pub definitions_only: bool,
+}
+
+/// Raw retrieval signals of a query, to diagnose why something didn't match.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExplainInfo {
+    /// Dimension of the query embedding.
+    pub query_dim: usize,
+
+    /// L2 norm of the query embedding (near 0 means a degenerate embedding).
+    pub query_norm: f32,
+
+    /// Nearest-neighbor distances of the primary vector search, nearest
+    /// first, before lexical re-ranking: `1 - similarity` for Cosine
+    /// collections, the raw Qdrant score for Dot/Euclid.
+    pub raw_distances: Vec<f32>,
+
+    /// Quoted phrases and tokens the lexical re-rank matched against.
+    pub lexical_terms: Vec<String>,
+}
+
+/// Search output: stitched results plus optional facet counts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeSearchResults {
+    pub results: Vec<CodeSearchResult>,
+
+    /// Present only when requested via `with_facets`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facets: Option<SearchFacets>,
+
+    /// Present only when requested via `explain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explain: Option<ExplainInfo>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(file: &str, code: &str) -> CodeSearchResult {
+        CodeSearchResult {
+            score: 0.5,
+            file: file.into(),
+            language: "dart".into(),
+            kind: "function".into(),
+            symbol_path: format!("{file}::f"),
+            symbol: "f".into(),
+            signature: None,
+            snippet: None,
+            code: code.into(),
+            start_row: 0,
+            end_row: 1,
+            truncated: false,
+            start_line: 1,
+            end_line: 1,
+            context: None,
+            score_breakdown: None,
+        }
+    }
+
+    #[test]
+    fn plain_text_keeps_result_order_with_file_separators() {
+        let results = vec![
+            result("lib/b.dart", "void b() {}\n"),
+            result("lib/a.dart", "void a() {}"),
+        ];
+
+        let text = as_plain_text(&results);
+        assert_eq!(
+            text,
+            "// file: lib/b.dart\nvoid b() {}\n\n// file: lib/a.dart\nvoid a() {}\n"
+        );
+
+        let json = serde_json::to_value(&results).unwrap();
+        let json_order: Vec<&str> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["file"].as_str().unwrap())
+            .collect();
+        let text_order: Vec<&str> = text
+            .lines()
+            .filter_map(|l| l.strip_prefix("// file: "))
+            .collect();
+        assert_eq!(text_order, json_order);
+        assert!(as_plain_text(&[]).is_empty());
+    }
+
+    #[test]
+    fn facets_count_languages_and_kinds_and_are_omitted_when_off() {
+        let mut kt = result("app/Main.kt", "fun main() {}");
+        kt.language = "kotlin".into();
+        kt.kind = "class".into();
+        let results = vec![
+            result("lib/a.dart", "void a() {}"),
+            result("lib/b.dart", "void b() {}"),
+            kt,
+        ];
+
+        let facets = SearchFacets::from_results(&results);
+        assert_eq!(
+            facets.language,
+            HashMap::from([("dart".to_string(), 2), ("kotlin".to_string(), 1)])
+        );
+        assert_eq!(
+            facets.kind,
+            HashMap::from([("function".to_string(), 2), ("class".to_string(), 1)])
+        );
+
+        let off = CodeSearchResults {
+            results,
+            facets: None,
+            explain: None,
+        };
+        let json = serde_json::to_value(&off).unwrap();
+        assert!(json.get("facets").is_none());
+    }
 }
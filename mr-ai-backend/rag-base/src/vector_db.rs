@@ -1,28 +1,117 @@
 //! Qdrant vector DB helpers: connection lifecycle, collection reset,
 //! batched upserts, creating payload indexes, and top-K search using the modern `qdrant_client` API.
+//!
+//! Clients are pooled per Qdrant URL for the whole process: a client
+//! multiplexes requests over one gRPC channel, so concurrent API requests and
+//! all collections on a server share it instead of reconnecting per call.
 
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use qdrant_client::qdrant::collections_client::CollectionsClient;
 use qdrant_client::qdrant::{
-    CreateCollectionBuilder, CreateFieldIndexCollectionBuilder, Distance, FieldType, Filter,
-    PointStruct, RetrievedPoint, ScrollPointsBuilder, SearchPointsBuilder, VectorParamsBuilder,
+    AliasOperations, ChangeAliases, CollectionConfig, Condition, CountPointsBuilder, CreateAlias,
+    CreateCollectionBuilder, CreateFieldIndexCollectionBuilder, DeleteAlias, DeletePointsBuilder,
+    Distance, FieldType, Filter, PointStruct, RetrievedPoint, ScrollPointsBuilder,
+    SearchPointsBuilder, VectorParamsBuilder, alias_operations, vectors_config,
 };
 use qdrant_client::{Payload, Qdrant};
 use serde_json::Value as JsonValue;
-use tracing::{debug, error, info};
+use services::namespaces::{DIM_KEY, MODEL_KEY};
+use tracing::{debug, error, info, warn};
 
 use crate::errors::rag_base_error::RagBaseError;
 use crate::structs::rag_base_config::{DistanceMetric, RagConfig};
-use crate::structs::rag_store::{SearchHit, VectorPayload};
+use crate::structs::rag_store::{
+    PayloadIndex, PayloadIndexKind, PayloadSchema, SearchHit, VectorPayload,
+};
+
+/// Payload indexes created by [`reset_collection`], in creation order.
+const PAYLOAD_INDEXES: &[(&str, PayloadIndexKind)] = &[
+    // Filterable fields.
+    ("id", PayloadIndexKind::Keyword),
+    ("file", PayloadIndexKind::Keyword),
+    ("language", PayloadIndexKind::Keyword),
+    ("kind", PayloadIndexKind::Keyword),
+    ("symbol", PayloadIndexKind::Keyword),
+    ("symbol_path", PayloadIndexKind::Keyword),
+    ("content_sha256", PayloadIndexKind::Keyword),
+    ("tags", PayloadIndexKind::Keyword),
+    ("is_definition", PayloadIndexKind::Bool),
+    ("routes", PayloadIndexKind::Keyword),
+    ("search_terms", PayloadIndexKind::Keyword),
+    // Full-text style lexical search.
+    ("search_blob", PayloadIndexKind::Text),
+    ("search_terms", PayloadIndexKind::Text),
+];
+
+/// Payload key schema of collections created by [`reset_collection`].
+pub fn payload_schema() -> PayloadSchema {
+    PayloadSchema {
+        id_field: "id".to_string(),
+        indexes: PAYLOAD_INDEXES
+            .iter()
+            .map(|&(field, kind)| PayloadIndex {
+                field: field.to_string(),
+                kind,
+            })
+            .collect(),
+    }
+}
 
-/// Establish a gRPC connection to Qdrant using `cfg.qdrant.url`.
-pub async fn connect(cfg: &RagConfig) -> Result<Qdrant, RagBaseError> {
+/// Process-wide clients keyed by Qdrant URL.
+fn client_pool() -> &'static Mutex<HashMap<String, Arc<Qdrant>>> {
+    static POOL: OnceLock<Mutex<HashMap<String, Arc<Qdrant>>>> = OnceLock::new();
+    POOL.get_or_init(Default::default)
+}
+
+/// Shared gRPC client for `cfg.qdrant.url`, built on first use and reused
+/// by later calls (see the module docs). Use [`reconnect`] once it goes stale.
+pub async fn connect(cfg: &RagConfig) -> Result<Arc<Qdrant>, RagBaseError> {
+    let mut pool = client_pool().lock().unwrap_or_else(|p| p.into_inner());
+    if let Some(client) = pool.get(&cfg.qdrant.url) {
+        return Ok(client.clone());
+    }
     info!(
         target: "rag_base::vector_db",
         url = %cfg.qdrant.url,
         "connect: creating Qdrant client"
     );
-    Qdrant::from_url(&cfg.qdrant.url)
+    let client = Qdrant::from_url(&cfg.qdrant.url)
         .build()
-        .map_err(|e| RagBaseError::Qdrant(format!("client build: {e}")))
+        .map_err(|e| RagBaseError::Qdrant(format!("client build: {e}")))?;
+    let client = Arc::new(client);
+    pool.insert(cfg.qdrant.url.clone(), client.clone());
+    Ok(client)
+}
+
+/// Maps a failed Qdrant call `op` on `collection` into a structured error
+/// (see [`RagBaseError::from_qdrant`]).
+pub(crate) fn qdrant_err<'a, E>(
+    cfg: &'a RagConfig,
+    collection: &'a str,
+    op: &'a str,
+) -> impl FnOnce(E) -> RagBaseError + 'a
+where
+    E: std::fmt::Display + std::fmt::Debug,
+{
+    move |e| RagBaseError::from_qdrant(&cfg.qdrant.url, collection, op, e)
+}
+
+/// Drops the pooled client for `cfg.qdrant.url` (e.g. after a transport
+/// error) and builds a fresh one for this and later calls.
+pub async fn reconnect(cfg: &RagConfig) -> Result<Arc<Qdrant>, RagBaseError> {
+    warn!(
+        target: "rag_base::vector_db",
+        url = %cfg.qdrant.url,
+        "reconnect: dropping pooled Qdrant client"
+    );
+    client_pool()
+        .lock()
+        .unwrap_or_else(|p| p.into_inner())
+        .remove(&cfg.qdrant.url);
+    connect(cfg).await
 }
 
 /// Drop the collection (if present), create a fresh one, and create payload indexes.
@@ -33,8 +122,9 @@ pub async fn reset_collection(client: &Qdrant, cfg: &RagConfig) -> Result<(), Ra
         "reset_collection: dropping collection if exists"
     );
 
-    // Best-effort delete: ignore errors like "not found".
-    let _ = client.delete_collection(&cfg.qdrant.collection).await;
+    // An alias with the same name (left by `reindex_blue_green`) would block
+    // creation, so it goes together with every generation behind it.
+    drop_collection(client, cfg).await?;
 
     let distance = match cfg.qdrant.distance {
         DistanceMetric::Cosine => Distance::Cosine,
@@ -53,27 +143,26 @@ pub async fn reset_collection(client: &Qdrant, cfg: &RagConfig) -> Result<(), Ra
     client
         .create_collection(
             CreateCollectionBuilder::new(&cfg.qdrant.collection)
-                .vectors_config(VectorParamsBuilder::new(cfg.embedding.dim as u64, distance)),
+                .vectors_config(VectorParamsBuilder::new(cfg.embedding.dim as u64, distance))
+                .metadata(embedding_metadata(cfg)),
         )
         .await
-        .map_err(|e| RagBaseError::Qdrant(format!("create_collection: {e}")))?;
-
-    // Payload indexes for filterable fields.
-    create_keyword_index(client, &cfg.qdrant.collection, "id").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "file").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "language").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "kind").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "symbol").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "symbol_path").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "content_sha256").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "tags").await?;
-    create_bool_index(client, &cfg.qdrant.collection, "is_definition").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "routes").await?;
-    create_keyword_index(client, &cfg.qdrant.collection, "search_terms").await?;
-
-    // Text indexes for full-text style lexical search.
-    create_text_index(client, &cfg.qdrant.collection, "search_blob").await?;
-    create_text_index(client, &cfg.qdrant.collection, "search_terms").await?;
+        .map_err(qdrant_err(cfg, &cfg.qdrant.collection, "create_collection"))?;
+
+    // Payload indexes (see `payload_schema`).
+    for &(field, kind) in PAYLOAD_INDEXES {
+        match kind {
+            PayloadIndexKind::Keyword => {
+                create_keyword_index(client, &cfg.qdrant.collection, field).await?
+            }
+            PayloadIndexKind::Bool => {
+                create_bool_index(client, &cfg.qdrant.collection, field).await?
+            }
+            PayloadIndexKind::Text => {
+                create_text_index(client, &cfg.qdrant.collection, field).await?
+            }
+        }
+    }
 
     info!(
         target: "rag_base::vector_db",
@@ -84,6 +173,321 @@ pub async fn reset_collection(client: &Qdrant, cfg: &RagConfig) -> Result<(), Ra
     Ok(())
 }
 
+/// Drop the collection together with its payload indexes.
+///
+/// When `cfg.qdrant.collection` is a blue/green alias, the alias and every
+/// `<name>__v<generation>` collection are dropped as well.
+///
+/// Idempotent: returns `Ok(false)` when neither the collection nor the alias
+/// existed.
+pub async fn drop_collection(client: &Qdrant, cfg: &RagConfig) -> Result<bool, RagBaseError> {
+    let name = &cfg.qdrant.collection;
+    let mut existed = false;
+
+    if resolve_alias(client, name).await?.is_some() {
+        client
+            .delete_alias(name)
+            .await
+            .map_err(qdrant_err(cfg, name, "delete_alias"))?;
+        existed = true;
+    }
+
+    let all = list_collections_with_prefix(client, name).await?;
+    for collection in all
+        .iter()
+        .filter(|c| *c == name || generation_of(name, c).is_some())
+    {
+        client
+            .delete_collection(collection)
+            .await
+            .map_err(qdrant_err(cfg, collection, "delete_collection"))?;
+        existed = true;
+    }
+
+    if existed {
+        info!(
+            target: "rag_base::vector_db",
+            collection = %name,
+            "drop_collection: collection dropped"
+        );
+    } else {
+        info!(
+            target: "rag_base::vector_db",
+            collection = %name,
+            "drop_collection: collection does not exist, nothing to do"
+        );
+    }
+    Ok(existed)
+}
+
+/// Generation number of `collection` if it is a blue/green generation of
+/// `alias` (`<alias>__v<generation>`).
+pub fn generation_of(alias: &str, collection: &str) -> Option<u64> {
+    collection
+        .strip_prefix(alias)?
+        .strip_prefix("__v")?
+        .parse()
+        .ok()
+}
+
+/// Name of the next blue/green generation of `alias`.
+///
+/// Generations are numbered by Unix seconds but always move past the highest
+/// generation in `existing` and the last one handed out by this process, so
+/// two rebuilds within the same second never share a collection.
+pub fn next_generation(alias: &str, existing: &[String], now_secs: u64) -> String {
+    static LAST: AtomicU64 = AtomicU64::new(0);
+
+    let floor = existing
+        .iter()
+        .filter_map(|c| generation_of(alias, c))
+        .max()
+        .map_or(now_secs, |g| now_secs.max(g + 1));
+    let previous = LAST
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+            Some(floor.max(last + 1))
+        })
+        .unwrap_or_else(|last| last);
+    format!("{alias}__v{}", floor.max(previous + 1))
+}
+
+/// Delete every point whose `file` payload is one of `files` (the chunk source).
+///
+/// Requests are split into groups of 256 paths to keep filters small.
+pub async fn delete_by_source(
+    client: &Qdrant,
+    cfg: &RagConfig,
+    files: &[String],
+) -> Result<(), RagBaseError> {
+    for group in files.chunks(256) {
+        info!(
+            target: "rag_base::vector_db",
+            collection = %cfg.qdrant.collection,
+            files = group.len(),
+            "delete_by_source: deleting points"
+        );
+        let filter = Filter::must([Condition::matches("file", group.to_vec())]);
+        client
+            .delete_points(
+                DeletePointsBuilder::new(&cfg.qdrant.collection)
+                    .points(filter)
+                    .wait(true),
+            )
+            .await
+            .map_err(qdrant_err(cfg, &cfg.qdrant.collection, "delete_points"))?;
+    }
+    Ok(())
+}
+
+/// Exact number of points stored in `collection`.
+pub async fn count_points(client: &Qdrant, collection: &str) -> Result<u64, RagBaseError> {
+    let resp = client
+        .count(CountPointsBuilder::new(collection).exact(true))
+        .await
+        .map_err(|e| RagBaseError::Qdrant(format!("count[{collection}]: {e}")))?;
+    Ok(resp.result.map(|r| r.count).unwrap_or(0))
+}
+
+/// Collection metadata recording the embedding model and dimension the
+/// collection is built with (see `services::namespaces`).
+fn embedding_metadata(cfg: &RagConfig) -> HashMap<String, JsonValue> {
+    HashMap::from([
+        (
+            MODEL_KEY.to_string(),
+            JsonValue::from(cfg.embedding.model.clone()),
+        ),
+        (DIM_KEY.to_string(), JsonValue::from(cfg.embedding.dim)),
+    ])
+}
+
+/// Embedding model and vector size a collection was built with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectionEmbedding {
+    /// Model recorded by [`reset_collection`] (`None` for older collections).
+    pub model: Option<String>,
+    /// Vector size (`None` for named multi-vector collections).
+    pub dim: Option<usize>,
+}
+
+impl CollectionEmbedding {
+    fn from_config(config: Option<CollectionConfig>) -> Self {
+        let Some(config) = config else {
+            return Self::default();
+        };
+        let model = config
+            .metadata
+            .get(MODEL_KEY)
+            .and_then(|v| v.clone().into_json().as_str().map(str::to_owned));
+        let recorded_dim = config
+            .metadata
+            .get(DIM_KEY)
+            .and_then(|v| v.clone().into_json().as_u64())
+            .map(|d| d as usize);
+        let size = config
+            .params
+            .and_then(|p| p.vectors_config)
+            .and_then(|v| v.config)
+            .and_then(|c| match c {
+                vectors_config::Config::Params(p) => Some(p.size as usize),
+                vectors_config::Config::ParamsMap(_) => None,
+            });
+        Self {
+            model,
+            dim: size.or(recorded_dim),
+        }
+    }
+
+    /// `cfg` with the recorded model and size, so queries are embedded the
+    /// same way the collection was.
+    pub fn apply(&self, cfg: &mut RagConfig) {
+        if let Some(model) = &self.model {
+            cfg.embedding.model = model.clone();
+        }
+        if let Some(dim) = self.dim {
+            cfg.embedding.dim = dim;
+        }
+    }
+}
+
+/// Reads the embedding model and vector size of `collection`.
+pub async fn collection_embedding(
+    client: &Qdrant,
+    cfg: &RagConfig,
+    collection: &str,
+) -> Result<CollectionEmbedding, RagBaseError> {
+    let resp = client
+        .collection_info(collection)
+        .await
+        .map_err(qdrant_err(cfg, collection, "collection_info"))?;
+    Ok(CollectionEmbedding::from_config(
+        resp.result.and_then(|i| i.config),
+    ))
+}
+
+/// Fails when `collection` stores vectors of a different size than
+/// `cfg.embedding.dim`, i.e. it was built with another embedding model.
+///
+/// Collections with named vectors (or without readable params) are not checked.
+pub async fn ensure_collection_dim(
+    client: &Qdrant,
+    cfg: &RagConfig,
+    collection: &str,
+) -> Result<(), RagBaseError> {
+    let recorded = collection_embedding(client, cfg, collection).await?;
+    check_collection_dim(collection, recorded.dim.map(|d| d as u64), cfg)
+}
+
+fn check_collection_dim(
+    collection: &str,
+    size: Option<u64>,
+    cfg: &RagConfig,
+) -> Result<(), RagBaseError> {
+    match size {
+        Some(size) if size != cfg.embedding.dim as u64 => Err(RagBaseError::DimensionMismatch {
+            endpoint: cfg.qdrant.url.clone(),
+            collection: collection.to_string(),
+            got: size as usize,
+            want: cfg.embedding.dim,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Collection currently behind `alias`, if the alias exists.
+pub async fn resolve_alias(client: &Qdrant, alias: &str) -> Result<Option<String>, RagBaseError> {
+    let resp = client
+        .list_aliases()
+        .await
+        .map_err(|e| RagBaseError::Qdrant(format!("list_aliases: {e}")))?;
+    Ok(resp
+        .aliases
+        .into_iter()
+        .find(|a| a.alias_name == alias)
+        .map(|a| a.collection_name))
+}
+
+/// Alias actions that move `alias` onto `collection`: delete the current
+/// alias (when `replace` is set) and create it again, in one request.
+fn swap_alias_request(alias: &str, collection: &str, replace: bool) -> ChangeAliases {
+    let delete = replace.then(|| {
+        alias_operations::Action::DeleteAlias(DeleteAlias {
+            alias_name: alias.to_string(),
+        })
+    });
+    let create = alias_operations::Action::CreateAlias(CreateAlias {
+        collection_name: collection.to_string(),
+        alias_name: alias.to_string(),
+    });
+    ChangeAliases {
+        actions: delete
+            .into_iter()
+            .chain([create])
+            .map(|action| AliasOperations {
+                action: Some(action),
+            })
+            .collect(),
+        timeout: None,
+    }
+}
+
+/// Point `alias` at `collection`.
+///
+/// Removing the old alias and creating the new one go out as a single
+/// `UpdateAliases` request, which Qdrant applies atomically: readers never
+/// observe a missing alias during the switch, and a failed create leaves the
+/// old alias in place. `replace` must be set when the alias already exists.
+///
+/// `qdrant_client` only sends single-action alias updates, so the request
+/// is issued on a dedicated gRPC channel to `cfg.qdrant.url`.
+pub async fn point_alias(
+    cfg: &RagConfig,
+    alias: &str,
+    collection: &str,
+    replace: bool,
+) -> Result<(), RagBaseError> {
+    info!(
+        target: "rag_base::vector_db",
+        alias,
+        collection,
+        replace,
+        "point_alias: switching alias"
+    );
+    let err = |e: &dyn std::fmt::Display| {
+        RagBaseError::Qdrant(format!("update_aliases[{alias}->{collection}]: {e}"))
+    };
+
+    let mut endpoint =
+        tonic::transport::Endpoint::from_shared(cfg.qdrant.url.clone()).map_err(|e| err(&e))?;
+    if cfg.qdrant.url.starts_with("https://") {
+        endpoint = endpoint
+            .tls_config(tonic::transport::ClientTlsConfig::new().with_native_roots())
+            .map_err(|e| err(&e))?;
+    }
+    let channel = endpoint.connect().await.map_err(|e| err(&e))?;
+    CollectionsClient::new(channel)
+        .update_aliases(swap_alias_request(alias, collection, replace))
+        .await
+        .map_err(|e| err(&e))?;
+    Ok(())
+}
+
+/// Names of all collections whose name starts with `prefix`.
+pub async fn list_collections_with_prefix(
+    client: &Qdrant,
+    prefix: &str,
+) -> Result<Vec<String>, RagBaseError> {
+    let resp = client
+        .list_collections()
+        .await
+        .map_err(|e| RagBaseError::Qdrant(format!("list_collections: {e}")))?;
+    Ok(resp
+        .collections
+        .into_iter()
+        .map(|c| c.name)
+        .filter(|n| n.starts_with(prefix))
+        .collect())
+}
+
 /// Helper: create a Keyword payload index for a given field.
 async fn create_keyword_index(
     client: &Qdrant,
@@ -187,7 +591,7 @@ pub async fn upsert_batch(
         }
 
         // Derive stable numeric id from external id.
-        let numeric_id = hash_to_u64(&id);
+        let numeric_id = point_id(&id);
 
         // Convert payload.
         let q_payload = payload_to_qdrant(&payload)?;
@@ -218,7 +622,7 @@ pub async fn upsert_batch(
                 error = %e,
                 "upsert_batch: qdrant upsert failed"
             );
-            RagBaseError::Qdrant(format!("upsert_points: {e}"))
+            RagBaseError::from_qdrant(&cfg.qdrant.url, &cfg.qdrant.collection, "upsert_points", e)
         })?;
 
     Ok(point_len)
@@ -226,11 +630,13 @@ pub async fn upsert_batch(
 
 /// Run k-NN search and return preview-friendly hits.
 /// IMPORTANT: No server-side score threshold — fetch a wide pool for local reranking.
+/// An optional payload `filter` narrows the candidates (e.g. definitions only).
 pub async fn search_top_k(
     client: &Qdrant,
     cfg: &RagConfig,
     query_vec: Vec<f32>,
     k: usize,
+    filter: Option<Filter>,
 ) -> Result<Vec<SearchHit>, RagBaseError> {
     if query_vec.len() != cfg.embedding.dim {
         return Err(RagBaseError::InvalidConfig(format!(
@@ -252,8 +658,11 @@ pub async fn search_top_k(
     );
 
     // Do NOT set score_threshold here — it might hide relevant candidates before rerank.
-    let builder = SearchPointsBuilder::new(&cfg.qdrant.collection, query_vec, fetch_k as u64)
+    let mut builder = SearchPointsBuilder::new(&cfg.qdrant.collection, query_vec, fetch_k as u64)
         .with_payload(true);
+    if let Some(filter) = filter {
+        builder = builder.filter(filter);
+    }
 
     let resp = client.search_points(builder).await.map_err(|e| {
         error!(
@@ -261,7 +670,7 @@ pub async fn search_top_k(
             error = %e,
             "search_top_k: qdrant search failed"
         );
-        RagBaseError::Qdrant(format!("search_points: {e}"))
+        RagBaseError::from_qdrant(&cfg.qdrant.url, &cfg.qdrant.collection, "search_points", e)
     })?;
 
     debug!(
@@ -309,7 +718,7 @@ pub async fn scroll_with_filter(
             error = %e,
             "scroll_with_filter: qdrant scroll failed"
         );
-        RagBaseError::Qdrant(format!("scroll: {e}"))
+        RagBaseError::from_qdrant(&cfg.qdrant.url, &cfg.qdrant.collection, "scroll", e)
     })?;
 
     let count = response.result.len();
@@ -390,6 +799,7 @@ fn map_scored_point_to_hit(sp: qdrant_client::qdrant::ScoredPoint) -> SearchHit
     let mut symbol = String::new();
     let mut signature: Option<String> = None;
     let mut snippet: Option<String> = None;
+    let mut is_definition = false;
 
     if !sp.payload.is_empty() {
         if let Some(v) = sp.payload.get("file") {
@@ -431,6 +841,9 @@ fn map_scored_point_to_hit(sp: qdrant_client::qdrant::ScoredPoint) -> SearchHit
                 }
             }
         }
+        if let Some(v) = sp.payload.get("is_definition") {
+            is_definition = v.clone().into_json().as_bool().unwrap_or(false);
+        }
     }
 
     SearchHit {
@@ -443,6 +856,8 @@ fn map_scored_point_to_hit(sp: qdrant_client::qdrant::ScoredPoint) -> SearchHit
         symbol,
         signature,
         snippet,
+        is_definition,
+        score_breakdown: None,
     }
 }
 
@@ -477,6 +892,7 @@ fn map_retrieved_point_to_hit(rp: RetrievedPoint) -> SearchHit {
     let mut symbol = String::new();
     let mut signature: Option<String> = None;
     let mut snippet: Option<String> = None;
+    let mut is_definition = false;
 
     if !rp.payload.is_empty() {
         if let Some(v) = rp.payload.get("file") {
@@ -518,6 +934,9 @@ fn map_retrieved_point_to_hit(rp: RetrievedPoint) -> SearchHit {
                 }
             }
         }
+        if let Some(v) = rp.payload.get("is_definition") {
+            is_definition = v.clone().into_json().as_bool().unwrap_or(false);
+        }
     }
 
     SearchHit {
@@ -530,12 +949,304 @@ fn map_retrieved_point_to_hit(rp: RetrievedPoint) -> SearchHit {
         symbol,
         signature,
         snippet,
+        is_definition,
+        score_breakdown: None,
     }
 }
 
-/// Deterministically hash an arbitrary string to a u64 ID.
-fn hash_to_u64(s: &str) -> u64 {
-    let digest = blake3::hash(s.as_bytes());
+/// Qdrant point id for a chunk id: the first 8 bytes of `blake3(chunk_id)`
+/// read as a little-endian `u64`.
+///
+/// Stable across releases: external tools rely on it to address chunks.
+pub fn point_id(chunk_id: &str) -> u64 {
+    let digest = blake3::hash(chunk_id.as_bytes());
     let bytes = &digest.as_bytes()[..8];
     u64::from_le_bytes(bytes.try_into().expect("slice with incorrect length"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::rag_base_config::QdrantConfig;
+
+    /// Defaults only (no env), pointed at `url`.
+    fn test_cfg(url: String) -> RagConfig {
+        RagConfig {
+            project_name: "test".into(),
+            code_jsonl: Default::default(),
+            embedding: Default::default(),
+            qdrant: QdrantConfig {
+                url,
+                ..Default::default()
+            },
+            search: Default::default(),
+            clamp: Default::default(),
+            stitch: Default::default(),
+            namespaces: Vec::new(),
+            namespace: None,
+        }
+    }
+
+    /// URL of a local port nothing listens on.
+    fn closed_port_url() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        format!("http://127.0.0.1:{port}")
+    }
+
+    #[test]
+    fn schema_fields_exist_in_payload() {
+        let payload = VectorPayload {
+            id: "lib/a.dart#1".into(),
+            file: "lib/a.dart".into(),
+            language: "dart".into(),
+            kind: "method".into(),
+            symbol: "run".into(),
+            symbol_path: "lib/a.dart::A::run".into(),
+            signature: None,
+            doc: None,
+            snippet: None,
+            content_sha256: "00".into(),
+            imports_top: Vec::new(),
+            tags: Vec::new(),
+            lsp_fqn: None,
+            is_definition: true,
+            routes: Vec::new(),
+            search_terms: Vec::new(),
+            search_blob: String::new(),
+        };
+        let json = serde_json::to_value(&payload).unwrap();
+
+        let schema = payload_schema();
+        assert!(json.get(&schema.id_field).is_some());
+        for idx in &schema.indexes {
+            assert!(
+                json.get(&idx.field).is_some(),
+                "{} not in payload",
+                idx.field
+            );
+        }
+        assert_eq!(point_id(&payload.id), point_id("lib/a.dart#1"));
+        assert_ne!(point_id(&payload.id), point_id("lib/a.dart#2"));
+    }
+
+    #[test]
+    fn collection_dim_must_match_embedding_dim() {
+        let mut cfg = RagConfig::from_env(Some("test")).unwrap();
+        cfg.embedding.dim = 768;
+        assert!(check_collection_dim("code", Some(768), &cfg).is_ok());
+        assert!(check_collection_dim("code", None, &cfg).is_ok());
+
+        let err = check_collection_dim("code", Some(1024), &cfg).unwrap_err();
+        assert!(err.to_string().contains("1024-dim"), "{err}");
+        assert!(matches!(
+            err,
+            RagBaseError::DimensionMismatch {
+                got: 1024,
+                want: 768,
+                ..
+            }
+        ));
+    }
+
+    fn actions(req: &ChangeAliases) -> Vec<alias_operations::Action> {
+        req.actions
+            .iter()
+            .filter_map(|a| a.action.clone())
+            .collect()
+    }
+
+    #[test]
+    fn alias_swap_is_one_delete_and_create_request() {
+        let req = swap_alias_request("code", "code__v2", true);
+        assert_eq!(
+            actions(&req),
+            vec![
+                alias_operations::Action::DeleteAlias(DeleteAlias {
+                    alias_name: "code".into(),
+                }),
+                alias_operations::Action::CreateAlias(CreateAlias {
+                    collection_name: "code__v2".into(),
+                    alias_name: "code".into(),
+                }),
+            ]
+        );
+
+        // Rollback re-points the live alias the same way.
+        let req = swap_alias_request("code", "code__v1", true);
+        assert_eq!(req.actions.len(), 2);
+        assert!(matches!(
+            &actions(&req)[1],
+            alias_operations::Action::CreateAlias(c) if c.collection_name == "code__v1"
+        ));
+    }
+
+    #[test]
+    fn first_alias_is_created_without_a_delete() {
+        let req = swap_alias_request("code", "code__v1", false);
+        assert!(matches!(
+            actions(&req).as_slice(),
+            [alias_operations::Action::CreateAlias(_)]
+        ));
+    }
+
+    #[test]
+    fn generations_never_collide_within_a_second() {
+        let a = next_generation("code", &[], 100);
+        let b = next_generation("code", &[], 100);
+        let (ga, gb) = (
+            generation_of("code", &a).unwrap(),
+            generation_of("code", &b).unwrap(),
+        );
+        assert!(gb > ga, "{a} then {b}");
+
+        let existing = vec!["code__v9000000000".to_string()];
+        let c = next_generation("code", &existing, 100);
+        assert!(generation_of("code", &c).unwrap() > 9_000_000_000, "{c}");
+    }
+
+    #[test]
+    fn drop_targets_only_generations_of_the_alias() {
+        assert_eq!(generation_of("code", "code__v17"), Some(17));
+        assert_eq!(generation_of("code", "code"), None);
+        assert_eq!(generation_of("code", "code__v17x"), None);
+        assert_eq!(generation_of("code", "code_other__v3"), None);
+        assert_eq!(generation_of("code", "codebase__v3"), None);
+        assert_eq!(generation_of("code__v1", "code__v17"), None);
+    }
+
+    #[test]
+    fn two_namespaces_keep_their_own_model_and_dim() {
+        use qdrant_client::qdrant::{CollectionParams, VectorParams, VectorsConfig};
+
+        let mut cfg = RagConfig::from_env(Some("test")).unwrap();
+        cfg.namespaces =
+            services::namespaces::parse("code|bge-m3|1024;docs|nomic-embed-text|768|markdown")
+                .unwrap();
+        let scopes = cfg.scopes();
+        let (code, docs) = (&scopes[0], &scopes[1]);
+        assert_eq!(
+            docs.qdrant.collection,
+            format!("{}__ns_docs", cfg.qdrant.collection)
+        );
+        assert_eq!(
+            (code.embedding.model.as_str(), code.embedding.dim),
+            ("bge-m3", 1024)
+        );
+        assert_eq!(
+            (docs.embedding.model.as_str(), docs.embedding.dim),
+            ("nomic-embed-text", 768)
+        );
+        assert!(docs.owns_language("markdown") && !docs.owns_language("rust"));
+        assert!(code.owns_language("rust") && !code.owns_language("markdown"));
+
+        // What `reset_collection` records for the docs namespace...
+        let config = CollectionConfig {
+            params: Some(CollectionParams {
+                vectors_config: Some(VectorsConfig {
+                    config: Some(vectors_config::Config::Params(VectorParams {
+                        size: 768,
+                        ..Default::default()
+                    })),
+                }),
+                ..Default::default()
+            }),
+            metadata: embedding_metadata(docs)
+                .into_iter()
+                .map(|(k, v)| (k, v.into()))
+                .collect(),
+            ..Default::default()
+        };
+        let recorded = CollectionEmbedding::from_config(Some(config));
+
+        // ...decides the query embedder, whatever the env says.
+        let mut query_cfg = code.clone();
+        recorded.apply(&mut query_cfg);
+        assert_eq!(query_cfg.embedding.model, "nomic-embed-text");
+        assert_eq!(query_cfg.embedding.dim, 768);
+
+        // Ingest into a namespace is checked against its own collection.
+        let size = recorded.dim.map(|d| d as u64);
+        assert!(check_collection_dim(&docs.qdrant.collection, size, docs).is_ok());
+        assert!(check_collection_dim(&docs.qdrant.collection, size, code).is_err());
+    }
+
+    #[test]
+    fn qdrant_failures_are_classified() {
+        let classify =
+            |msg: &str| RagBaseError::from_qdrant("http://q:6334", "code", "search", msg);
+        assert!(matches!(
+            classify("status: Unavailable, message: \"tcp connect error: Connection refused\""),
+            RagBaseError::Unreachable { .. }
+        ));
+        let timeout = classify("status: DeadlineExceeded, message: \"Timeout expired\"");
+        assert!(matches!(timeout, RagBaseError::Timeout { .. }));
+        // Worth a 503, but a fresh client wouldn't help.
+        assert!(timeout.is_unavailable() && !timeout.is_connection_error());
+        assert!(matches!(
+            classify("status: NotFound, message: \"Not found: Collection `code` doesn't exist!\""),
+            RagBaseError::CollectionMissing { .. }
+        ));
+        assert!(matches!(
+            classify("Wrong input: Vector dimension error: expected dim: 768, got 1024"),
+            RagBaseError::DimensionMismatch {
+                got: 1024,
+                want: 768,
+                ..
+            }
+        ));
+        let other = classify("status: InvalidArgument, message: \"bad filter\"");
+        assert!(matches!(other, RagBaseError::Qdrant(_)) && !other.is_unavailable());
+    }
+
+    #[tokio::test]
+    async fn closed_port_is_unreachable() {
+        let cfg = test_cfg(closed_port_url());
+        let client = connect(&cfg).await.unwrap();
+        let query = vec![0.0; cfg.embedding.dim];
+        let err = search_top_k(&client, &cfg, query, 5, None)
+            .await
+            .unwrap_err();
+
+        match &err {
+            RagBaseError::Unreachable {
+                endpoint,
+                collection,
+                ..
+            } => {
+                assert_eq!(endpoint, &cfg.qdrant.url);
+                assert_eq!(collection, &cfg.qdrant.collection);
+            }
+            other => panic!("expected Unreachable, got {other:?}"),
+        }
+        assert!(err.is_unavailable() && err.is_connection_error());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_searches_share_one_client() {
+        let cfg = test_cfg(closed_port_url());
+
+        let tasks: Vec<_> = (0..64)
+            .map(|_| {
+                let cfg = cfg.clone();
+                tokio::spawn(async move {
+                    let client = connect(&cfg).await.unwrap();
+                    let query = vec![0.0; cfg.embedding.dim];
+                    let res = search_top_k(&client, &cfg, query, 5, None).await;
+                    (client, res)
+                })
+            })
+            .collect();
+        let mut clients = Vec::new();
+        for t in tasks {
+            let (client, res) = t.await.unwrap();
+            assert!(res.unwrap_err().is_connection_error());
+            clients.push(client);
+        }
+        assert!(clients.iter().all(|c| Arc::ptr_eq(c, &clients[0])));
+
+        let fresh = reconnect(&cfg).await.unwrap();
+        assert!(!Arc::ptr_eq(&fresh, &clients[0]));
+        assert!(Arc::ptr_eq(&fresh, &connect(&cfg).await.unwrap()));
+    }
+}
@@ -1,5 +1,6 @@
 //! Library configuration and distance kinds.
 
+use crate::embed::window::EmbedWindowing;
 use crate::errors::RagError;
 
 /// Distance metric kind for Qdrant collection.
@@ -23,6 +24,11 @@ pub struct RagConfig {
     pub embedding_dim: Option<usize>,
     /// Parallelism for embedding provider calls (EMBEDDING_CONCURRENCY).
     pub embedding_concurrency: Option<usize>,
+    /// Cosine threshold for per-file near-duplicate suppression at ingest
+    /// (INGEST_NEAR_DUP_THRESHOLD). `None` disables it.
+    pub near_dup_threshold: Option<f32>,
+    /// Oversized-text handling for `OllamaEmbedder` (EMBEDDING_LONG_TEXT etc.).
+    pub embed_windowing: EmbedWindowing,
 }
 
 impl RagConfig {
@@ -36,6 +42,9 @@ impl RagConfig {
     /// - EXACT_SEARCH=true/false (default: false)
     /// - EMBEDDING_DIM (optional)
     /// - EMBEDDING_CONCURRENCY (optional)
+    /// - INGEST_NEAR_DUP_THRESHOLD in (0, 1] (optional; off by default)
+    /// - EMBEDDING_LONG_TEXT = truncate|mean|max (default: truncate), with
+    ///   EMBEDDING_WINDOW_CHARS / EMBEDDING_WINDOW_OVERLAP / EMBEDDING_MAX_WINDOWS
     pub fn from_env() -> Result<Self, RagError> {
         use std::env;
         let url = env::var("QDRANT_URL")
@@ -77,6 +86,21 @@ impl RagConfig {
             .ok()
             .and_then(|s| s.parse::<usize>().ok());
 
+        let near_dup_threshold = match env::var("INGEST_NEAR_DUP_THRESHOLD") {
+            Ok(s) if !s.trim().is_empty() => {
+                let t = s.trim().parse::<f32>().map_err(|_| {
+                    RagError::Config(format!("Invalid INGEST_NEAR_DUP_THRESHOLD: {s}"))
+                })?;
+                if !(t > 0.0 && t <= 1.0) {
+                    return Err(RagError::Config(format!(
+                        "INGEST_NEAR_DUP_THRESHOLD must be in (0, 1], got {t}"
+                    )));
+                }
+                Some(t)
+            }
+            _ => None,
+        };
+
         Ok(Self {
             qdrant_url: url,
             qdrant_api_key: api_key,
@@ -86,6 +110,8 @@ impl RagConfig {
             exact_search,
             embedding_dim,
             embedding_concurrency,
+            near_dup_threshold,
+            embed_windowing: EmbedWindowing::from_env(),
         })
     }
 
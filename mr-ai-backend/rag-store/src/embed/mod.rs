@@ -27,3 +27,4 @@ pub enum EmbeddingPolicy<'a> {
 
 pub mod noop_embedder;
 pub mod ollama;
+pub mod window;
@@ -5,6 +5,7 @@
 
 use std::sync::Arc;
 
+use super::window::EmbedWindowing;
 use crate::{EmbeddingsProvider, RagError};
 use ai_llm_service::service_profiles::LlmServiceProfiles;
 
@@ -21,6 +22,8 @@ pub struct OllamaConfig {
 pub struct OllamaEmbedder {
     pub svc: Arc<LlmServiceProfiles>,
     dim: usize,
+    windowing: EmbedWindowing,
+    model: Option<String>,
 }
 
 impl OllamaEmbedder {
@@ -29,8 +32,29 @@ impl OllamaEmbedder {
         Self {
             svc: cfg.svc,
             dim: cfg.dim,
+            windowing: EmbedWindowing::default(),
+            model: None,
         }
     }
+
+    /// Embed with `model` instead of the embedding profile's model (on the
+    /// same provider), e.g. to match the model a namespace was indexed with.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Model used for embedding, if overridden with [`Self::with_model`].
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Embed oversized texts as pooled sliding windows instead of letting the
+    /// model truncate them.
+    pub fn with_windowing(mut self, windowing: EmbedWindowing) -> Self {
+        self.windowing = windowing;
+        self
+    }
 }
 
 impl EmbeddingsProvider for OllamaEmbedder {
@@ -40,17 +64,26 @@ impl EmbeddingsProvider for OllamaEmbedder {
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<f32>, RagError>> + Send + 'a>>
     {
         Box::pin(async move {
-            let resp = self.svc.embed(&text).await.expect("Embed failed");
-
-            if resp.len() != self.dim {
-                println!("[SOME_TEST]: Len{}", resp.len());
-                return Err(RagError::VectorSizeMismatch {
-                    got: resp.len(),
-                    want: self.dim,
-                });
+            let windows = self.windowing.split(text);
+            let mut vecs = Vec::with_capacity(windows.len());
+            for w in windows {
+                let resp = match &self.model {
+                    Some(model) => self.svc.embed_with_model(model, w).await,
+                    None => self.svc.embed(w).await,
+                }
+                .map_err(|e| RagError::Provider(format!("embed failed: {e}")))?;
+
+                if resp.len() != self.dim {
+                    return Err(RagError::Provider(format!(
+                        "embedding has {} dims, expected {}",
+                        resp.len(),
+                        self.dim
+                    )));
+                }
+                vecs.push(resp);
             }
 
-            Ok(resp)
+            Ok(self.windowing.pool(vecs))
         })
     }
 }
@@ -0,0 +1,78 @@
+//! Sliding-window embedding for texts longer than the model context.
+//!
+//! With [`LongTextMode::Truncate`] (default) texts are sent as-is and the
+//! model/server clamps them. The windowed modes split oversized texts into
+//! overlapping windows and pool the window vectors into one (see
+//! [`services::embed_window`]).
+
+use services::embed_window::{pool_vectors, split_windows};
+
+pub use services::embed_window::LongTextMode;
+
+/// Window settings for [`OllamaEmbedder`](super::ollama::OllamaEmbedder).
+#[derive(Clone, Copy, Debug)]
+pub struct EmbedWindowing {
+    pub mode: LongTextMode,
+    /// Window size in chars (should fit the model context).
+    pub window_chars: usize,
+    /// Overlap between consecutive windows in chars (< `window_chars`).
+    pub overlap_chars: usize,
+    /// Max windows per text; anything beyond is dropped.
+    pub max_windows: usize,
+}
+
+impl Default for EmbedWindowing {
+    fn default() -> Self {
+        Self {
+            mode: LongTextMode::Truncate,
+            window_chars: 1200,
+            overlap_chars: 200,
+            max_windows: 8,
+        }
+    }
+}
+
+impl EmbedWindowing {
+    /// Reads `EMBEDDING_LONG_TEXT` (truncate|mean|max), `EMBEDDING_WINDOW_CHARS`,
+    /// `EMBEDDING_WINDOW_OVERLAP` and `EMBEDDING_MAX_WINDOWS`.
+    pub fn from_env() -> Self {
+        use std::env;
+        let d = Self::default();
+        let num = |k: &str, dflt: usize| {
+            env::var(k)
+                .ok()
+                .and_then(|s| s.trim().parse::<usize>().ok())
+                .unwrap_or(dflt)
+        };
+        let mode = LongTextMode::from_env(env::var("EMBEDDING_LONG_TEXT").ok());
+        let window_chars = num("EMBEDDING_WINDOW_CHARS", d.window_chars).max(1);
+        Self {
+            mode,
+            window_chars,
+            overlap_chars: num("EMBEDDING_WINDOW_OVERLAP", d.overlap_chars).min(window_chars - 1),
+            max_windows: num("EMBEDDING_MAX_WINDOWS", d.max_windows).max(1),
+        }
+    }
+
+    /// Windows to embed for `text`: the text itself unless a windowed mode is
+    /// active and it is longer than one window.
+    pub fn split<'a>(&self, text: &'a str) -> Vec<&'a str> {
+        if !self.mode.is_windowed() || text.chars().count() <= self.window_chars {
+            return vec![text];
+        }
+        split_windows(
+            text,
+            self.window_chars,
+            self.overlap_chars,
+            self.max_windows,
+        )
+    }
+
+    /// Pools window vectors according to the mode (a single vector is returned as-is).
+    pub fn pool(&self, mut vecs: Vec<Vec<f32>>) -> Vec<f32> {
+        if vecs.len() <= 1 {
+            return vecs.pop().unwrap_or_default();
+        }
+        pool_vectors(&vecs, self.mode.pooling())
+    }
+}
@@ -13,8 +13,8 @@ use tracing::{debug, info};
 /// - `concurrency`: maximum number of concurrent embedding tasks.
 ///
 /// # Errors
-/// Returns [`RagError::VectorSizeMismatch`] if dimensions mismatch,
-/// or [`RagError::Provider`] if the provider fails.
+/// Returns [`RagError::Provider`] if the provider fails or returns a vector
+/// of another size than `expected_dim`.
 pub async fn embed_missing(
     records: &mut [RagRecord],
     provider: &dyn EmbeddingsProvider,
@@ -55,7 +55,10 @@ pub async fn embed_missing(
     for (i, v) in results {
         if let Some(want) = expected_dim {
             if v.len() != want {
-                return Err(RagError::VectorSizeMismatch { got: v.len(), want });
+                return Err(RagError::Provider(format!(
+                    "embedding has {} dims, expected {want}",
+                    v.len()
+                )));
             }
         }
         records[i].embedding = Some(v);
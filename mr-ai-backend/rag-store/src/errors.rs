@@ -1,5 +1,6 @@
 //! Error types used across the RAG library.
 
+use services::qdrant_error::{self, QdrantFailure};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,9 +20,111 @@ pub enum RagError {
     #[error("qdrant error: {0}")]
     Qdrant(String),
 
+    #[error("qdrant unreachable at {endpoint} (collection '{collection}'): {message}")]
+    Unreachable {
+        endpoint: String,
+        collection: String,
+        message: String,
+    },
+
+    #[error("qdrant timed out at {endpoint} (collection '{collection}'): {message}")]
+    Timeout {
+        endpoint: String,
+        collection: String,
+        message: String,
+    },
+
+    #[error("collection '{collection}' not found at {endpoint}")]
+    CollectionMissing {
+        endpoint: String,
+        collection: String,
+    },
+
+    #[error("collection '{collection}' at {endpoint} stores {got}-dim vectors, expected {want}")]
+    DimensionMismatch {
+        endpoint: String,
+        collection: String,
+        got: usize,
+        want: usize,
+    },
+
     #[error("missing embedding")]
     MissingEmbedding,
+}
+
+impl RagError {
+    /// Classifies a failed Qdrant call on `collection` at `endpoint` (see
+    /// [`services::qdrant_error::classify`]); unrecognized errors stay `Qdrant`.
+    pub fn from_qdrant<E>(endpoint: &str, collection: &str, err: E) -> Self
+    where
+        E: std::fmt::Display + std::fmt::Debug,
+    {
+        let message = err.to_string();
+        let endpoint = endpoint.to_string();
+        let collection = collection.to_string();
+        match qdrant_error::classify(&err) {
+            QdrantFailure::DimensionMismatch { got, want } => Self::DimensionMismatch {
+                endpoint,
+                collection,
+                got,
+                want,
+            },
+            QdrantFailure::Timeout => Self::Timeout {
+                endpoint,
+                collection,
+                message,
+            },
+            QdrantFailure::Unreachable => Self::Unreachable {
+                endpoint,
+                collection,
+                message,
+            },
+            QdrantFailure::CollectionMissing => Self::CollectionMissing {
+                endpoint,
+                collection,
+            },
+            QdrantFailure::Other => Self::Qdrant(message),
+        }
+    }
+
+    /// True for transient availability failures (`Unreachable`, `Timeout`).
+    pub fn is_unavailable(&self) -> bool {
+        matches!(self, Self::Unreachable { .. } | Self::Timeout { .. })
+    }
+
+    /// True when the connection itself failed (`Unreachable`), the only case
+    /// a fresh client can fix.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(self, Self::Unreachable { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    #[error("vector size mismatch: got={got}, want={want}")]
-    VectorSizeMismatch { got: usize, want: usize },
+    #[test]
+    fn qdrant_failures_are_classified() {
+        let classify = |msg: &str| RagError::from_qdrant("http://q:6334", "code", msg);
+        let down =
+            classify("status: Unavailable, message: \"tcp connect error: Connection refused\"");
+        assert!(matches!(down, RagError::Unreachable { .. }) && down.is_unavailable());
+        assert!(matches!(
+            classify("status: DeadlineExceeded, message: \"Timeout expired\""),
+            RagError::Timeout { .. }
+        ));
+        assert!(matches!(
+            classify("Not found: Collection `code` doesn't exist!"),
+            RagError::CollectionMissing { .. }
+        ));
+        assert!(matches!(
+            classify("Wrong input: Vector dimension error: expected dim: 768, got 1024"),
+            RagError::DimensionMismatch {
+                got: 1024,
+                want: 768,
+                ..
+            }
+        ));
+        assert!(matches!(classify("bad filter"), RagError::Qdrant(_)));
+    }
 }
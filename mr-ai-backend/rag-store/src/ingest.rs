@@ -11,7 +11,7 @@ use crate::embed_pool::embed_missing;
 use crate::errors::RagError;
 use crate::io_jsonl::{read_all_jsonl, read_all_records};
 use crate::mappers::{map_ast_node, map_graph_edge, map_graph_node};
-use crate::normalize::normalize_code_light;
+use crate::normalize::{NearDuplicateFilter, normalize_code_light, suppress_near_duplicates};
 use crate::qdrant_facade::QdrantFacade;
 use crate::record::{RagRecord, clamp_snippet};
 
@@ -25,6 +25,15 @@ use std::collections::{BTreeMap, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use tracing::{debug, info, warn};
 
+/// Outcome of an ingestion run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IngestReport {
+    /// Points upserted into Qdrant.
+    pub upserted: u64,
+    /// Chunks dropped as per-file near-duplicates (see `INGEST_NEAR_DUP_THRESHOLD`).
+    pub near_duplicates_suppressed: usize,
+}
+
 /// Ingest the latest dump under `<root>/project_x/graphs_data/<timestamp>`.
 /// Uses [`ingest_file`] internally.
 pub async fn ingest_latest_from(
@@ -32,7 +41,7 @@ pub async fn ingest_latest_from(
     root: impl AsRef<std::path::Path>,
     policy: EmbeddingPolicy<'_>,
     client: &QdrantFacade,
-) -> Result<u64, RagError> {
+) -> Result<IngestReport, RagError> {
     let dir = latest_dump_dir(root)?;
     let jsonl = rag_records_path(&dir);
     ingest_file(cfg, jsonl, policy, client).await
@@ -48,13 +57,13 @@ pub async fn ingest_file(
     jsonl_path: impl AsRef<std::path::Path>,
     policy: EmbeddingPolicy<'_>,
     client: &QdrantFacade,
-) -> Result<u64, RagError> {
+) -> Result<IngestReport, RagError> {
     info!("Ingesting file {:?}", jsonl_path.as_ref());
 
     let mut records = read_strict_or_fallback(&jsonl_path)?;
     if records.is_empty() {
         debug!("No records found in file");
-        return Ok(0);
+        return Ok(IngestReport::default());
     }
 
     // Normalize text for compact embeddings
@@ -73,16 +82,36 @@ pub async fn ingest_file(
         })
         .await?;
 
-    // Upsert points in batches
+    // Embed, drop near-duplicates (needs the vectors) and upsert in batches
     let mut total: u64 = 0;
+    let mut near_duplicates_suppressed = 0;
+    let mut near_dups = cfg.near_dup_threshold.map(NearDuplicateFilter::new);
     let batch_size = cfg.upsert_batch.max(1);
-    for chunk in records.chunks(batch_size) {
-        let points = build_points(chunk, vector_size, &policy).await?;
+    let mut records = records.into_iter();
+    loop {
+        let mut batch: Vec<RagRecord> = records.by_ref().take(batch_size).collect();
+        if batch.is_empty() {
+            break;
+        }
+        embed_batch(&mut batch, &policy).await?;
+        if let Some(filter) = near_dups.as_mut() {
+            near_duplicates_suppressed += filter.retain(&mut batch);
+            if batch.is_empty() {
+                continue;
+            }
+        }
+        let points = build_points(&batch, vector_size, &policy).await?;
         total += client.upsert_points(points).await?;
     }
 
-    info!("Ingested {} records from file", total);
-    Ok(total)
+    info!(
+        "Ingested {} records from file (near-duplicates suppressed: {})",
+        total, near_duplicates_suppressed
+    );
+    Ok(IngestReport {
+        upserted: total,
+        near_duplicates_suppressed,
+    })
 }
 
 /// Ingests **all files** from the latest dump and computes embeddings for everything.
@@ -98,7 +127,7 @@ pub async fn ingest_latest_all_embedded(
     root: impl AsRef<std::path::Path>,
     provider: &(dyn EmbeddingsProvider + Send + Sync),
     client: &QdrantFacade,
-) -> Result<u64, RagError> {
+) -> Result<IngestReport, RagError> {
     info!(
         "Ingesting latest dump with embeddings from {:?}",
         root.as_ref()
@@ -143,7 +172,7 @@ pub async fn ingest_latest_all_embedded(
 
     if records.is_empty() {
         warn!("No records collected from dump");
-        return Ok(0);
+        return Ok(IngestReport::default());
     }
 
     dedup_in_place(&mut records);
@@ -152,6 +181,10 @@ pub async fn ingest_latest_all_embedded(
     let conc = cfg.embedding_concurrency.unwrap_or(4);
     embed_missing(&mut records, provider, want_dim, conc).await?;
 
+    let near_duplicates_suppressed = cfg
+        .near_dup_threshold
+        .map_or(0, |t| suppress_near_duplicates(&mut records, t));
+
     let vector_size = determine_vector_size(
         &records,
         &EmbeddingPolicy::PrecomputedOr(provider),
@@ -190,9 +223,15 @@ pub async fn ingest_latest_all_embedded(
     }
 
     pb.finish_with_message("Ingestion complete ✔");
-    info!("Ingested {} records total", total);
+    info!(
+        "Ingested {} records total (near-duplicates suppressed: {})",
+        total, near_duplicates_suppressed
+    );
 
-    Ok(total)
+    Ok(IngestReport {
+        upserted: total,
+        near_duplicates_suppressed,
+    })
 }
 
 // ---------- helpers ----------
@@ -229,10 +268,10 @@ async fn determine_vector_size(
     if let Some(dim) = expected_dim {
         if let Some(v) = records.iter().find_map(|r| r.embedding.as_ref()) {
             if v.len() != dim {
-                return Err(RagError::VectorSizeMismatch {
-                    got: v.len(),
-                    want: dim,
-                });
+                return Err(RagError::Parse(format!(
+                    "precomputed embedding has {} dims, expected {dim}",
+                    v.len()
+                )));
             }
         }
         return Ok(dim);
@@ -250,6 +289,18 @@ async fn determine_vector_size(
     }
 }
 
+/// Fills missing embeddings of `batch` via the provider of `policy`.
+async fn embed_batch(
+    batch: &mut [RagRecord],
+    policy: &EmbeddingPolicy<'_>,
+) -> Result<(), RagError> {
+    let (EmbeddingPolicy::PrecomputedOr(p) | EmbeddingPolicy::ProviderOnly(p)) = policy;
+    for r in batch.iter_mut().filter(|r| r.embedding.is_none()) {
+        r.embedding = Some(p.embed(&r.text).await?);
+    }
+    Ok(())
+}
+
 /// Builds Qdrant points for a batch of records.
 /// Embedding is resolved via policy. Payload is compact and consistent.
 async fn build_points(
@@ -267,10 +318,15 @@ async fn build_points(
             (None, EmbeddingPolicy::ProviderOnly(p)) => p.embed(&r.text).await?,
         };
         if vector.len() != vector_size {
-            return Err(RagError::VectorSizeMismatch {
-                got: vector.len(),
-                want: vector_size,
-            });
+            let source = if r.embedding.is_some() {
+                "precomputed"
+            } else {
+                "provider"
+            };
+            return Err(RagError::Parse(format!(
+                "{source} embedding has {} dims, expected {vector_size}",
+                vector.len()
+            )));
         }
 
         // --- payload ---
@@ -26,9 +26,11 @@ pub use config::{DistanceKind, RagConfig, VectorSpace};
 pub use embed::ollama::{OllamaConfig, OllamaEmbedder};
 pub use embed::{EmbeddingPolicy, EmbeddingsProvider};
 pub use errors::RagError;
-pub use record::{RagFilter, RagHit, RagQuery, RagRecord};
+pub use ingest::IngestReport;
+pub use record::{CollectionInfo, RagFilter, RagHit, RagQuery, RagRecord, ScoreBreakdown};
 
-use tracing::{debug, info};
+use std::time::Instant;
+use tracing::{debug, info, instrument};
 
 /// High-level facade that wires configuration and Qdrant client.
 ///
@@ -62,7 +64,7 @@ impl RagStore {
         &self,
         root: impl AsRef<std::path::Path>,
         policy: EmbeddingPolicy<'_>,
-    ) -> Result<u64, RagError> {
+    ) -> Result<IngestReport, RagError> {
         info!("RagStore::ingest_latest_from root={:?}", root.as_ref());
         ingest::ingest_latest_from(&self.cfg, root, policy, &self.client).await
     }
@@ -71,13 +73,26 @@ impl RagStore {
     ///
     /// # Errors
     /// Returns errors on I/O, parse, vector size mismatch, or Qdrant failures.
+    #[instrument(
+        name = "rag_store.ingest_file",
+        skip_all,
+        fields(collection = %self.cfg.collection, path = ?jsonl_path.as_ref())
+    )]
     pub async fn ingest_file(
         &self,
         jsonl_path: impl AsRef<std::path::Path>,
         policy: EmbeddingPolicy<'_>,
-    ) -> Result<u64, RagError> {
+    ) -> Result<IngestReport, RagError> {
+        let t0 = Instant::now();
         info!("RagStore::ingest_file path={:?}", jsonl_path.as_ref());
-        ingest::ingest_file(&self.cfg, jsonl_path, policy, &self.client).await
+        let report = ingest::ingest_file(&self.cfg, jsonl_path, policy, &self.client).await?;
+        info!(
+            upserted = report.upserted,
+            near_duplicates_suppressed = report.near_duplicates_suppressed,
+            elapsed_ms = t0.elapsed().as_millis() as u64,
+            "RagStore::ingest_file done"
+        );
+        Ok(report)
     }
 
     /// Ingests **all** supported files (rag+ast+graph) from the latest dump directory,
@@ -89,7 +104,7 @@ impl RagStore {
         &self,
         root: impl AsRef<std::path::Path>,
         provider: &dyn EmbeddingsProvider,
-    ) -> Result<u64, RagError> {
+    ) -> Result<IngestReport, RagError> {
         info!(
             "RagStore::ingest_latest_all_embedded root={:?}",
             root.as_ref()
@@ -97,10 +112,56 @@ impl RagStore {
         ingest::ingest_latest_all_embedded(&self.cfg, root, provider, &self.client).await
     }
 
+    /// Drops the configured collection and its payload indexes.
+    ///
+    /// Idempotent: returns `Ok(false)` if the collection was already missing.
+    ///
+    /// # Errors
+    /// Returns `RagError::Unreachable`/`Timeout` if Qdrant is down, or
+    /// `RagError::Qdrant` if the existence check or deletion fails.
+    pub async fn drop_collection(&self) -> Result<bool, RagError> {
+        info!(
+            "RagStore::drop_collection collection={}",
+            self.cfg.collection
+        );
+        self.client.drop_collection().await
+    }
+
+    /// Counts points in the collection, optionally restricted by `filter`.
+    ///
+    /// # Errors
+    /// Returns `RagError::CollectionMissing` if the collection is missing,
+    /// `RagError::Unreachable`/`Timeout` if Qdrant is down, or `RagError::Qdrant`.
+    pub async fn count(&self, filter: Option<RagFilter>) -> Result<u64, RagError> {
+        debug!("RagStore::count collection={}", self.cfg.collection);
+        let qfilter = filter.as_ref().map(filters::to_qdrant_filter);
+        self.client.count(qfilter).await
+    }
+
+    /// Returns point count, vector dimensionality and distance of the collection.
+    ///
+    /// # Errors
+    /// Returns `RagError::CollectionMissing` if the collection is missing,
+    /// `RagError::Unreachable`/`Timeout` if Qdrant is down, or `RagError::Qdrant`.
+    pub async fn collection_info(&self) -> Result<CollectionInfo, RagError> {
+        debug!(
+            "RagStore::collection_info collection={}",
+            self.cfg.collection
+        );
+        self.client.collection_info().await
+    }
+
     /// Performs a low-level vector search and returns `(score, payload)` tuples.
     ///
     /// # Errors
-    /// Returns `RagError::Qdrant` if search fails.
+    /// Returns `RagError::Unreachable`/`Timeout` if Qdrant is down,
+    /// `RagError::CollectionMissing`/`DimensionMismatch` for a missing or
+    /// incompatible collection, or `RagError::Qdrant` if search fails.
+    #[instrument(
+        name = "rag_store.search_by_vector",
+        skip_all,
+        fields(collection = %self.cfg.collection, top_k = top_k, with_payload = with_payload)
+    )]
     pub async fn search_by_vector(
         &self,
         query_vector: Vec<f32>,
@@ -112,8 +173,9 @@ impl RagStore {
             "RagStore::search_by_vector top_k={} with_payload={}",
             top_k, with_payload
         );
+        let t0 = Instant::now();
         let qfilter = filter.as_ref().map(filters::to_qdrant_filter);
-        retrieve::search_by_vector(
+        let hits = retrieve::search_by_vector(
             &self.cfg,
             &self.client,
             query_vector,
@@ -122,19 +184,100 @@ impl RagStore {
             with_payload,
             self.cfg.exact_search,
         )
-        .await
+        .await?;
+        debug!(
+            hits = hits.len(),
+            elapsed_ms = t0.elapsed().as_millis() as u64,
+            "RagStore::search_by_vector done"
+        );
+        Ok(hits)
     }
 
     /// Builds RAG context for a textual query using the provided embedding provider.
     ///
     /// # Errors
     /// Returns embedding errors or Qdrant failures.
+    #[instrument(
+        name = "rag_store.rag_context",
+        skip_all,
+        fields(collection = %self.cfg.collection, top_k = query.top_k)
+    )]
     pub async fn rag_context(
         &self,
         query: RagQuery<'_>,
         provider: &dyn EmbeddingsProvider,
     ) -> Result<Vec<RagHit>, RagError> {
+        let t0 = Instant::now();
         debug!("RagStore::rag_context top_k={}", query.top_k);
-        retrieve::rag_context(&self.cfg, &self.client, query, provider).await
+        let hits = retrieve::rag_context(&self.cfg, &self.client, query, provider).await?;
+        info!(
+            hits = hits.len(),
+            elapsed_ms = t0.elapsed().as_millis() as u64,
+            "RagStore::rag_context done"
+        );
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embed::noop_embedder::NoopEmbedder;
+
+    /// Requires a running Qdrant at `QDRANT_URL`; run with `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "requires a running Qdrant (QDRANT_URL)"]
+    async fn count_matches_ingested_records() {
+        let cfg = RagConfig {
+            qdrant_url: std::env::var("QDRANT_URL")
+                .unwrap_or_else(|_| "http://localhost:6334".into()),
+            qdrant_api_key: std::env::var("QDRANT_API_KEY").ok(),
+            collection: format!("rag_store_count_test_{}", std::process::id()),
+            distance: DistanceKind::Cosine,
+            upsert_batch: 2,
+            exact_search: true,
+            embedding_dim: Some(3),
+            embedding_concurrency: None,
+            near_dup_threshold: None,
+            embed_windowing: Default::default(),
+        };
+        let store = RagStore::new(cfg).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("records.jsonl");
+        let lines: Vec<String> = (0..5)
+            .map(|i| {
+                serde_json::json!({
+                    "id": format!("rec-{i}"),
+                    "text": format!("record {i}"),
+                    "source": "lib/a.dart",
+                    "embedding": [1.0, i as f32, 0.5],
+                })
+                .to_string()
+            })
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let embedder = NoopEmbedder;
+        store
+            .ingest_file(&path, EmbeddingPolicy::PrecomputedOr(&embedder))
+            .await
+            .unwrap();
+
+        // Upserts are not awaited server-side; give Qdrant a moment to apply them.
+        let mut count = 0;
+        for _ in 0..50 {
+            count = store.count(None).await.unwrap();
+            if count == 5 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        let info = store.collection_info().await.unwrap();
+        store.drop_collection().await.unwrap();
+
+        assert_eq!(count, 5);
+        assert_eq!(info.dim, Some(3));
+        assert!(matches!(info.distance, Some(DistanceKind::Cosine)));
     }
 }
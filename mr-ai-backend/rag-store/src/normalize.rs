@@ -3,6 +3,8 @@
 //! Provides lightweight transformations for code snippets and metadata fields,
 //! making them more suitable for vector embeddings.
 
+use crate::record::RagRecord;
+use std::collections::HashMap;
 use tracing::debug;
 
 /// Normalize code text with minimal layout disruption.
@@ -78,3 +80,135 @@ pub fn join_compact(parts: &[&str], max_chars: usize) -> String {
 
     out
 }
+
+/// Drop chunks that are near-duplicates of an already-kept chunk **of the same source file**.
+///
+/// Two chunks are near-duplicates when the cosine similarity of their embeddings is
+/// strictly greater than `threshold`. Order is preserved and the first occurrence wins.
+/// Records without a `source` or without an embedding are always kept.
+///
+/// Returns the number of suppressed records.
+pub fn suppress_near_duplicates(records: &mut Vec<RagRecord>, threshold: f32) -> usize {
+    NearDuplicateFilter::new(threshold).retain(records)
+}
+
+/// [`suppress_near_duplicates`] across batches: chunks kept by earlier
+/// [`retain`](Self::retain) calls still suppress later near-duplicates.
+#[derive(Debug)]
+pub struct NearDuplicateFilter {
+    threshold: f32,
+    /// source → unit-length vectors of kept chunks
+    kept: HashMap<String, Vec<Vec<f32>>>,
+}
+
+impl NearDuplicateFilter {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            kept: HashMap::new(),
+        }
+    }
+
+    /// Drops near-duplicates from `records`; returns how many were dropped.
+    pub fn retain(&mut self, records: &mut Vec<RagRecord>) -> usize {
+        let before = records.len();
+        records.retain(|r| {
+            let (Some(src), Some(v)) = (r.source.as_ref(), r.embedding.as_ref()) else {
+                return true;
+            };
+            let Some(u) = unit(v) else {
+                return true;
+            };
+            let group = self.kept.entry(src.clone()).or_default();
+            if group.iter().any(|k| dot(k, &u) > self.threshold) {
+                return false;
+            }
+            group.push(u);
+            true
+        });
+
+        let suppressed = before - records.len();
+        debug!(
+            "suppress_near_duplicates: threshold={} suppressed={}",
+            self.threshold, suppressed
+        );
+        suppressed
+    }
+}
+
+fn unit(v: &[f32]) -> Option<Vec<f32>> {
+    let norm = dot(v, v).sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return None;
+    }
+    Some(v.iter().map(|x| x / norm).collect())
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rec(id: &str, source: Option<&str>, embedding: Option<Vec<f32>>) -> RagRecord {
+        RagRecord {
+            id: id.into(),
+            text: id.into(),
+            source: source.map(Into::into),
+            embedding,
+            extra: Default::default(),
+        }
+    }
+
+    fn ids(records: &[RagRecord]) -> Vec<&str> {
+        records.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn near_duplicate_of_the_same_file_is_dropped() {
+        let mut records = vec![
+            rec("a", Some("lib/a.dart"), Some(vec![1.0, 0.0])),
+            rec("a_copy", Some("lib/a.dart"), Some(vec![0.99, 0.01])),
+            rec("b_same_vec", Some("lib/b.dart"), Some(vec![1.0, 0.0])),
+        ];
+        assert_eq!(suppress_near_duplicates(&mut records, 0.95), 1);
+        assert_eq!(ids(&records), ["a", "b_same_vec"]);
+    }
+
+    #[test]
+    fn chunks_below_the_threshold_are_kept() {
+        let mut records = vec![
+            rec("a", Some("lib/a.dart"), Some(vec![1.0, 0.0])),
+            rec("a_other", Some("lib/a.dart"), Some(vec![0.6, 0.8])),
+        ];
+        // cos = 0.6
+        assert_eq!(suppress_near_duplicates(&mut records, 0.95), 0);
+        assert_eq!(suppress_near_duplicates(&mut records, 0.6), 0);
+        assert_eq!(suppress_near_duplicates(&mut records, 0.5), 1);
+        assert_eq!(ids(&records), ["a"]);
+    }
+
+    #[test]
+    fn records_without_vectors_or_source_are_kept() {
+        let mut records = vec![
+            rec("a", Some("lib/a.dart"), Some(vec![1.0, 0.0])),
+            rec("no_vec", Some("lib/a.dart"), None),
+            rec("zero_vec", Some("lib/a.dart"), Some(vec![0.0, 0.0])),
+            rec("no_source", None, Some(vec![1.0, 0.0])),
+        ];
+        assert_eq!(suppress_near_duplicates(&mut records, 0.5), 0);
+        assert_eq!(records.len(), 4);
+    }
+
+    #[test]
+    fn filter_remembers_chunks_kept_in_earlier_batches() {
+        let mut filter = NearDuplicateFilter::new(0.95);
+        let mut first = vec![rec("a", Some("lib/a.dart"), Some(vec![1.0, 0.0]))];
+        let mut second = vec![rec("a_copy", Some("lib/a.dart"), Some(vec![1.0, 0.0]))];
+        assert_eq!(filter.retain(&mut first), 0);
+        assert_eq!(filter.retain(&mut second), 1);
+        assert!(second.is_empty());
+    }
+}
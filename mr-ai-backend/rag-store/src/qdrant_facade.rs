@@ -3,49 +3,116 @@
 //! This facade concentrates all Qdrant interactions behind a minimal API,
 //! hiding away the verbose builder pattern and keeping the rest of the
 //! application decoupled from `qdrant-client`.
+//!
+//! The underlying client is shared per `(url, api key)` for the whole process,
+//! so building a facade per request does not open a new gRPC channel. A search
+//! that fails to connect replaces the shared client and retries once, so a
+//! client gone stale (e.g. Qdrant restarted) recovers without a process restart.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
 use crate::config::{DistanceKind, RagConfig, VectorSpace};
 use crate::errors::RagError;
+use crate::record::CollectionInfo;
 
 use qdrant_client::Qdrant;
 use qdrant_client::qdrant::{
-    CreateCollectionBuilder, Distance, Filter, PointStruct, SearchParamsBuilder,
-    SearchPointsBuilder, UpsertPointsBuilder, Value as QValue, VectorParamsBuilder,
+    CollectionInfo as QCollectionInfo, CountPoints, CountPointsBuilder, CreateCollectionBuilder,
+    Distance, Filter, PointStruct, SearchParamsBuilder, SearchPointsBuilder, UpsertPointsBuilder,
+    Value as QValue, VectorParamsBuilder, vectors_config,
 };
 use tracing::{debug, info, warn};
 
 /// A facade over the Qdrant client to keep the rest of the code clean and stable.
 ///
 /// This struct encapsulates:
-/// - The underlying Qdrant client.
+/// - The underlying (shared) Qdrant client.
 /// - The target collection name.
 /// - The distance function used in the vector space.
 pub struct QdrantFacade {
-    pub(crate) client: Qdrant,
+    /// Shared client; swapped by [`QdrantFacade::reconnect`].
+    client: RwLock<Arc<Qdrant>>,
     pub(crate) collection: String,
     distance: DistanceKind,
+    /// Pool key: Qdrant URL (also reported in structured errors) and API key.
+    key: ClientKey,
+}
+
+type ClientKey = (String, Option<String>);
+
+fn client_pool() -> &'static Mutex<HashMap<ClientKey, Arc<Qdrant>>> {
+    static POOL: OnceLock<Mutex<HashMap<ClientKey, Arc<Qdrant>>>> = OnceLock::new();
+    POOL.get_or_init(Default::default)
+}
+
+/// Process-wide client for `key`. With `stale`, a pooled client that is still
+/// `stale` is replaced by a fresh one; a client another caller already
+/// replaced it with is returned as is.
+fn shared_client(key: &ClientKey, stale: Option<&Arc<Qdrant>>) -> Result<Arc<Qdrant>, RagError> {
+    let mut pool = client_pool().lock().unwrap_or_else(|p| p.into_inner());
+    let current = pool
+        .get(key)
+        .filter(|c| stale.is_none_or(|s| !Arc::ptr_eq(s, c)));
+    if let Some(client) = current {
+        return Ok(client.clone());
+    }
+
+    let (url, api_key) = key;
+    let mut builder = Qdrant::from_url(url);
+    if let Some(api_key) = api_key {
+        builder = builder.api_key(api_key.clone());
+    }
+    let client = Arc::new(
+        builder
+            .build()
+            .map_err(|e| RagError::Qdrant(e.to_string()))?,
+    );
+    pool.insert(key.clone(), client.clone());
+    Ok(client)
 }
 
 impl QdrantFacade {
+    /// Structured error for a failed call on this facade's collection.
+    fn qdrant_error<E>(&self, err: E) -> RagError
+    where
+        E: std::fmt::Display + std::fmt::Debug,
+    {
+        RagError::from_qdrant(&self.key.0, &self.collection, err)
+    }
+
+    /// The shared client this facade currently uses.
+    fn client(&self) -> Arc<Qdrant> {
+        self.client
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+    }
+
+    /// Replaces the shared client (for this facade and every later one with the
+    /// same URL and key) after a connection failure.
+    fn reconnect(&self) -> Result<Arc<Qdrant>, RagError> {
+        let stale = self.client();
+        warn!("Qdrant connection to {} failed, reconnecting", self.key.0);
+        let fresh = shared_client(&self.key, Some(&stale))?;
+        *self.client.write().unwrap_or_else(|p| p.into_inner()) = fresh.clone();
+        Ok(fresh)
+    }
+
     /// Creates a new facade from the given configuration.
     ///
     /// Uses the modern builder-based API of `qdrant-client` and supports
-    /// optional API key authentication.
+    /// optional API key authentication. The client is reused across facades
+    /// with the same URL and key.
     pub fn new(cfg: &RagConfig) -> Result<Self, RagError> {
         cfg.validate()?; // Early validation of config.
 
-        let mut builder = Qdrant::from_url(&cfg.qdrant_url);
-        if let Some(key) = &cfg.qdrant_api_key {
-            builder = builder.api_key(key.clone());
-        }
-        let client = builder
-            .build()
-            .map_err(|e| RagError::Qdrant(e.to_string()))?;
-
+        let key = (cfg.qdrant_url.clone(), cfg.qdrant_api_key.clone());
         Ok(Self {
-            client,
+            client: RwLock::new(shared_client(&key, None)?),
             collection: cfg.collection.clone(),
             distance: cfg.distance,
+            key,
         })
     }
 
@@ -60,7 +127,7 @@ impl QdrantFacade {
         );
 
         // Try to fetch collection info first.
-        match self.client.collection_info(&self.collection).await {
+        match self.client().collection_info(&self.collection).await {
             Ok(_) => {
                 debug!("Collection '{}' already exists", self.collection);
                 return Ok(());
@@ -80,18 +147,73 @@ impl QdrantFacade {
         };
 
         // Create collection with vector configuration.
-        self.client
+        self.client()
             .create_collection(
                 CreateCollectionBuilder::new(&self.collection)
                     .vectors_config(VectorParamsBuilder::new(space.size as u64, distance)),
             )
             .await
-            .map_err(|e| RagError::Qdrant(e.to_string()))?;
+            .map_err(|e| self.qdrant_error(e))?;
 
         info!("Collection '{}' created successfully", self.collection);
         Ok(())
     }
 
+    /// Drops the collection (payload indexes go with it).
+    ///
+    /// Idempotent: returns `false` when the collection did not exist.
+    pub async fn drop_collection(&self) -> Result<bool, RagError> {
+        let exists = self
+            .client()
+            .collection_exists(&self.collection)
+            .await
+            .map_err(|e| self.qdrant_error(e))?;
+        if !exists {
+            debug!(
+                "Collection '{}' does not exist, nothing to drop",
+                self.collection
+            );
+            return Ok(false);
+        }
+
+        self.client()
+            .delete_collection(&self.collection)
+            .await
+            .map_err(|e| self.qdrant_error(e))?;
+
+        info!("Collection '{}' dropped", self.collection);
+        Ok(true)
+    }
+
+    /// Counts points in the collection, optionally restricted by `filter`.
+    ///
+    /// Uses exact counting so the result can be used to verify ingestion.
+    pub async fn count(&self, filter: Option<Filter>) -> Result<u64, RagError> {
+        let res = self
+            .client()
+            .count(count_request(&self.collection, filter))
+            .await
+            .map_err(|e| self.qdrant_error(e))?;
+
+        let count = res.result.map(|r| r.count).unwrap_or(0);
+        debug!("Collection '{}' count={}", self.collection, count);
+        Ok(count)
+    }
+
+    /// Reads point count and single-vector configuration of the collection.
+    pub async fn collection_info(&self) -> Result<CollectionInfo, RagError> {
+        let res = self
+            .client()
+            .collection_info(&self.collection)
+            .await
+            .map_err(|e| self.qdrant_error(e))?;
+
+        let info = res.result.ok_or_else(|| {
+            RagError::Qdrant(format!("no info returned for '{}'", self.collection))
+        })?;
+        Ok(collection_info_from(info))
+    }
+
     /// Upserts (inserts or updates) a batch of points into the collection.
     ///
     /// Returns the number of points acknowledged by Qdrant.
@@ -108,10 +230,10 @@ impl QdrantFacade {
         );
 
         let res = self
-            .client
+            .client()
             .upsert_points(UpsertPointsBuilder::new(&self.collection, points))
             .await
-            .map_err(|e| RagError::Qdrant(e.to_string()))?;
+            .map_err(|e| self.qdrant_error(e))?;
 
         debug!("Upsert operation result={:?}", res.result);
 
@@ -120,7 +242,9 @@ impl QdrantFacade {
 
     /// Performs a similarity search in Qdrant.
     ///
-    /// Returns `(score, payload)` tuples with results sorted by score.
+    /// Returns `(score, payload)` tuples with results sorted by score. A
+    /// connection failure is retried once on a fresh client; timeouts and query
+    /// errors are returned as-is.
     pub async fn search(
         &self,
         vector: Vec<f32>,
@@ -144,11 +268,19 @@ impl QdrantFacade {
             builder = builder.params(SearchParamsBuilder::default().exact(true));
         }
 
-        let res = self
-            .client
-            .search_points(builder)
-            .await
-            .map_err(|e| RagError::Qdrant(e.to_string()))?;
+        let res = match self.client().search_points(builder.clone()).await {
+            Ok(res) => res,
+            Err(e) => {
+                let err = self.qdrant_error(e);
+                if !err.is_connection_error() {
+                    return Err(err);
+                }
+                self.reconnect()?
+                    .search_points(builder)
+                    .await
+                    .map_err(|e| self.qdrant_error(e))?
+            }
+        };
 
         // Convert raw Qdrant payloads into JSON.
         let mut out = Vec::with_capacity(res.result.len());
@@ -183,3 +315,157 @@ fn qpayload_to_json(mut p: std::collections::HashMap<String, QValue>) -> serde_j
     }
     serde_json::Value::Object(m)
 }
+
+/// Count request for `collection`; exact, so it can verify ingestion.
+fn count_request(collection: &str, filter: Option<Filter>) -> CountPoints {
+    let mut builder = CountPointsBuilder::new(collection).exact(true);
+    if let Some(f) = filter {
+        builder = builder.filter(f);
+    }
+    builder.build()
+}
+
+/// Point count and single-vector configuration from Qdrant's collection info.
+fn collection_info_from(info: QCollectionInfo) -> CollectionInfo {
+    let model = info.config.as_ref().and_then(|c| {
+        c.metadata
+            .get(services::namespaces::MODEL_KEY)
+            .and_then(|v| v.clone().into_json().as_str().map(str::to_owned))
+    });
+    let params = info
+        .config
+        .and_then(|c| c.params)
+        .and_then(|p| p.vectors_config)
+        .and_then(|v| v.config)
+        .and_then(|c| match c {
+            vectors_config::Config::Params(p) => Some(p),
+            vectors_config::Config::ParamsMap(_) => None,
+        });
+
+    let distance = params
+        .as_ref()
+        .and_then(|p| Distance::try_from(p.distance).ok())
+        .and_then(|d| match d {
+            Distance::Cosine => Some(DistanceKind::Cosine),
+            Distance::Dot => Some(DistanceKind::Dot),
+            Distance::Euclid => Some(DistanceKind::Euclid),
+            _ => None,
+        });
+
+    CollectionInfo {
+        points: info.points_count.unwrap_or(0),
+        dim: params.as_ref().map(|p| p.size as usize),
+        distance,
+        model,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::to_qdrant_filter;
+    use crate::record::RagFilter;
+    use qdrant_client::qdrant::{
+        CollectionConfig, CollectionParams, VectorParams, VectorParamsMap, VectorsConfig,
+    };
+
+    fn qdrant_info(vectors: vectors_config::Config, model: Option<&str>) -> QCollectionInfo {
+        let metadata = model
+            .map(|m| HashMap::from([(services::namespaces::MODEL_KEY.to_string(), m.into())]))
+            .unwrap_or_default();
+        QCollectionInfo {
+            points_count: Some(5),
+            config: Some(CollectionConfig {
+                params: Some(CollectionParams {
+                    vectors_config: Some(VectorsConfig {
+                        config: Some(vectors),
+                    }),
+                    ..Default::default()
+                }),
+                metadata,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn collection_info_reads_points_vector_params_and_model() {
+        let single = vectors_config::Config::Params(VectorParams {
+            size: 1024,
+            distance: Distance::Dot as i32,
+            ..Default::default()
+        });
+        let info = collection_info_from(qdrant_info(single, Some("bge-m3")));
+        assert_eq!(info.points, 5);
+        assert_eq!(info.dim, Some(1024));
+        assert!(matches!(info.distance, Some(DistanceKind::Dot)));
+        assert_eq!(info.model.as_deref(), Some("bge-m3"));
+
+        // Named vectors have no single dimension/distance to report.
+        let named = vectors_config::Config::ParamsMap(VectorParamsMap::default());
+        let info = collection_info_from(qdrant_info(named, None));
+        assert_eq!(info.points, 5);
+        assert_eq!((info.dim, info.model), (None, None));
+        assert!(info.distance.is_none());
+    }
+
+    #[test]
+    fn count_is_exact_and_carries_the_filter() {
+        let filter = RagFilter {
+            equals: vec![("source".into(), serde_json::json!("lib/a.dart"))],
+        };
+        let req = count_request("code", Some(to_qdrant_filter(&filter)));
+        assert_eq!(req.collection_name, "code");
+        assert_eq!(req.exact, Some(true));
+        assert_eq!(req.filter.unwrap().should.len(), 1);
+
+        assert!(count_request("code", None).filter.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_searches_share_a_client_and_reconnect_when_it_fails() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let cfg = RagConfig {
+            qdrant_url: format!("http://127.0.0.1:{port}"),
+            qdrant_api_key: None,
+            collection: "concurrent".into(),
+            distance: DistanceKind::Cosine,
+            upsert_batch: 8,
+            exact_search: false,
+            embedding_dim: Some(3),
+            embedding_concurrency: None,
+            near_dup_threshold: None,
+            embed_windowing: Default::default(),
+        };
+
+        let facades: Vec<_> = (0..32)
+            .map(|_| Arc::new(QdrantFacade::new(&cfg).unwrap()))
+            .collect();
+        let stale = facades[0].client();
+        assert!(facades.iter().all(|f| Arc::ptr_eq(&f.client(), &stale)));
+
+        let tasks: Vec<_> = facades
+            .iter()
+            .cloned()
+            .map(|f| {
+                tokio::spawn(async move { f.search(vec![0.0; 3], 5, None, true, false).await })
+            })
+            .collect();
+        for t in tasks {
+            let err = t.await.unwrap().unwrap_err();
+            assert!(err.is_connection_error(), "{err:?}");
+        }
+
+        // Every facade retried on a fresh client, and later facades pick it up.
+        assert!(facades.iter().all(|f| !Arc::ptr_eq(&f.client(), &stale)));
+        let later = QdrantFacade::new(&cfg).unwrap().client();
+        assert!(!Arc::ptr_eq(&later, &stale));
+        assert!(Arc::ptr_eq(
+            &later,
+            &QdrantFacade::new(&cfg).unwrap().client()
+        ));
+    }
+}
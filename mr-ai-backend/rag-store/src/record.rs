@@ -1,10 +1,13 @@
 //! Core data models used by the library.
 
+use crate::config::DistanceKind;
 use core::fmt;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::BTreeMap;
 
+pub use services::score_breakdown::ScoreBreakdown;
+
 /// Canonical record stored in Qdrant and used in ingestion.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RagRecord {
@@ -62,12 +65,23 @@ pub struct RagHit {
 
     /// Raw payload (for debugging or extra fields).
     pub raw_payload: serde_json::Value,
+
+    /// Vector/lexical contributions to `score` for hybrid ranking;
+    /// `None` for pure vector search.
+    pub score_breakdown: Option<ScoreBreakdown>,
 }
 
 /// Pretty printing for `RagHit` to keep logs readable.
 impl fmt::Display for RagHit {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "─ Hit (score={:.3})", self.score)?;
+        if let Some(b) = &self.score_breakdown {
+            writeln!(
+                f,
+                "  scores  : vector={:.3} lexical={:.3} fused={:.3}",
+                b.vector, b.lexical, b.fused
+            )?;
+        }
         if let (Some(k), Some(lang)) = (&self.kind, &self.language) {
             writeln!(f, "  kind    : {} ({})", k, lang)?;
         } else if let Some(k) = &self.kind {
@@ -128,3 +142,17 @@ pub struct RagFilter {
     /// Exact match on a field, e.g. {"source": "path/to/file.rs"}
     pub equals: Vec<(String, serde_json::Value)>,
 }
+
+/// Snapshot of a collection's size and vector configuration.
+#[derive(Clone, Debug)]
+pub struct CollectionInfo {
+    /// Approximate number of points reported by Qdrant.
+    pub points: u64,
+    /// Vector dimensionality (`None` for named multi-vector collections).
+    pub dim: Option<usize>,
+    /// Distance metric (`None` when not one of [`DistanceKind`]).
+    pub distance: Option<DistanceKind>,
+    /// Embedding model recorded in the collection metadata, if any
+    /// (see `services::namespaces`).
+    pub model: Option<String>,
+}
@@ -76,8 +76,13 @@ pub async fn rag_context(
     trace!("rag_context: raw query text={}", query.text);
 
     // Embed the query
+    let t_embed = std::time::Instant::now();
     let qvec = provider.embed(query.text).await?;
-    debug!("rag_context: query embedding length={}", qvec.len());
+    debug!(
+        "rag_context: query embedding length={} ({} ms)",
+        qvec.len(),
+        t_embed.elapsed().as_millis()
+    );
 
     // Build optional Qdrant filter
     let qfilter = query.filter.as_ref().map(crate::filters::to_qdrant_filter);
@@ -86,9 +91,14 @@ pub async fn rag_context(
     }
 
     // Perform vector search
+    let t_search = std::time::Instant::now();
     let hits = client
         .search(qvec, query.top_k, qfilter, true, cfg.exact_search)
         .await?;
+    debug!(
+        "rag_context: search done in {} ms",
+        t_search.elapsed().as_millis()
+    );
 
     if hits.is_empty() {
         warn!("rag_context: no hits found for query");
@@ -101,7 +111,7 @@ pub async fn rag_context(
     for (score, payload) in hits.into_iter() {
         let mut hit = extract_payload(&payload);
         hit.score = score;
-        debug!(
+        trace!(
             "rag_context: hit score={:.3}, text_len={}, source={:?}, kind={:?}",
             hit.score,
             hit.text.len(),
@@ -145,6 +155,8 @@ fn extract_payload(payload: &serde_json::Value) -> RagHit {
         metrics: None,
         raw_payload: payload.clone(),
         snippet: None,
+        // Pure vector search: nothing to break down.
+        score_breakdown: None,
     };
 
     if let J::Object(m) = payload {
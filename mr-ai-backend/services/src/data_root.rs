@@ -0,0 +1,85 @@
+//! Storage root for cloned repositories, indexes and review scratch files.
+//!
+//! Everything lives under `code_data` (relative to the working directory)
+//! unless `MRAI_DATA_ROOT` points elsewhere, e.g. a volume mounted into the
+//! container. Layout below the root is fixed:
+//! - `<root>/<project>/<repo>` — clones;
+//! - `<root>/out/<project>/code_chunks.jsonl` — index output;
+//! - `<root>/mr_tmp/<head12>/` — per-review materialized files, prompts and reports.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Env var overriding the storage root.
+pub const DATA_ROOT_ENV: &str = "MRAI_DATA_ROOT";
+
+/// Root used when [`DATA_ROOT_ENV`] is unset or blank.
+pub const DEFAULT_DATA_ROOT: &str = "code_data";
+
+/// Root set by [`pin_data_root`], ahead of the env var.
+static PINNED: OnceLock<PathBuf> = OnceLock::new();
+
+/// Storage root (`MRAI_DATA_ROOT`, default `code_data`).
+pub fn data_root() -> PathBuf {
+    match PINNED.get() {
+        Some(root) => root.clone(),
+        None => resolve(std::env::var_os(DATA_ROOT_ENV)),
+    }
+}
+
+/// Pins the storage root for the rest of the process, ahead of
+/// [`DATA_ROOT_ENV`] (e.g. a temp dir in tests). Only the first call takes
+/// effect; returns the root in use.
+pub fn pin_data_root(root: PathBuf) -> &'static Path {
+    PINNED.get_or_init(|| root)
+}
+
+/// Checkout directory of a project: `<root>/<project_name>`.
+pub fn project_dir(project_name: &str) -> PathBuf {
+    data_root().join(project_name)
+}
+
+/// Default index output: `<root>/out/<project_name>/code_chunks.jsonl`.
+pub fn code_chunks_path(project_name: &str) -> PathBuf {
+    data_root()
+        .join("out")
+        .join(project_name)
+        .join("code_chunks.jsonl")
+}
+
+/// Per-review scratch directory: `<root>/mr_tmp`.
+pub fn mr_tmp_dir() -> PathBuf {
+    data_root().join("mr_tmp")
+}
+
+fn resolve(value: Option<OsString>) -> PathBuf {
+    match value {
+        Some(v) if !v.to_string_lossy().trim().is_empty() => PathBuf::from(v),
+        _ => PathBuf::from(DEFAULT_DATA_ROOT),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn override_wins_and_blank_falls_back() {
+        assert_eq!(
+            resolve(Some("/mnt/mrai".into())),
+            PathBuf::from("/mnt/mrai")
+        );
+        assert_eq!(resolve(None), PathBuf::from("code_data"));
+        assert_eq!(resolve(Some("".into())), PathBuf::from("code_data"));
+        assert_eq!(resolve(Some("  ".into())), PathBuf::from("code_data"));
+    }
+
+    #[test]
+    fn first_pin_wins_over_env() {
+        let root = pin_data_root(PathBuf::from("/mnt/pinned"));
+        assert_eq!(root, Path::new("/mnt/pinned"));
+        assert_eq!(pin_data_root(PathBuf::from("/mnt/other")), root);
+        assert_eq!(mr_tmp_dir(), PathBuf::from("/mnt/pinned/mr_tmp"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Sliding windows for embedding texts longer than the model context.
+//!
+//! Used by `rag-base` and `rag-store`: an oversized text is split into
+//! overlapping windows, each window is embedded on its own, and the window
+//! vectors are pooled back into one. Window sizes are counted in chars.
+
+use serde::{Deserialize, Serialize};
+
+/// How embedding inputs longer than one window are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LongTextMode {
+    /// Sent as-is (or clamped upstream); the tail is dropped.
+    #[default]
+    Truncate,
+    /// Embed overlapping windows and average the vectors.
+    Mean,
+    /// Embed overlapping windows and take the element-wise maximum.
+    Max,
+}
+
+impl LongTextMode {
+    /// Parses `truncate|mean|max` (case-insensitive; `avg`/`average` mean
+    /// `mean`). Anything else, or `None`, is `Truncate`.
+    pub fn from_env(s: Option<String>) -> Self {
+        match s.unwrap_or_default().trim().to_lowercase().as_str() {
+            "mean" | "avg" | "average" => LongTextMode::Mean,
+            "max" => LongTextMode::Max,
+            _ => LongTextMode::Truncate,
+        }
+    }
+
+    /// True for the sliding-window modes.
+    pub fn is_windowed(self) -> bool {
+        self != LongTextMode::Truncate
+    }
+
+    /// Pooling for the window vectors (`Mean` unless the mode is `Max`).
+    pub fn pooling(self) -> Pooling {
+        match self {
+            LongTextMode::Max => Pooling::Max,
+            _ => Pooling::Mean,
+        }
+    }
+}
+
+/// How window vectors are combined by [`pool_vectors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pooling {
+    /// Element-wise mean.
+    Mean,
+    /// Element-wise maximum.
+    Max,
+}
+
+/// Splits `text` into at most `max_windows` windows of at most `window`
+/// chars, each starting `window - overlap` chars after the previous one.
+pub fn split_windows(text: &str, window: usize, overlap: usize, max_windows: usize) -> Vec<&str> {
+    // Byte offset of every char, plus the end of the text.
+    let bounds: Vec<usize> = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(text.len()))
+        .collect();
+    let chars = bounds.len() - 1;
+    let window = window.max(1);
+    let step = window.saturating_sub(overlap).max(1);
+    let mut out = Vec::new();
+    let mut start = 0usize;
+    while start < chars && out.len() < max_windows {
+        let end = (start + window).min(chars);
+        out.push(&text[bounds[start]..bounds[end]]);
+        if end == chars {
+            break;
+        }
+        start += step;
+    }
+    out
+}
+
+/// Element-wise mean or max of equally sized vectors (empty for no input).
+pub fn pool_vectors(vecs: &[Vec<f32>], pooling: Pooling) -> Vec<f32> {
+    let Some(first) = vecs.first() else {
+        return Vec::new();
+    };
+    let mut acc = first.clone();
+    for v in &vecs[1..] {
+        for (a, x) in acc.iter_mut().zip(v) {
+            *a = match pooling {
+                Pooling::Max => a.max(*x),
+                Pooling::Mean => *a + x,
+            };
+        }
+    }
+    if pooling == Pooling::Mean {
+        let n = vecs.len() as f32;
+        acc.iter_mut().for_each(|a| *a /= n);
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_are_counted_in_chars_and_overlap() {
+        let text = "é".repeat(120); // 240 bytes
+        let w = split_windows(&text, 50, 10, 8);
+        let lens: Vec<usize> = w.iter().map(|s| s.chars().count()).collect();
+        assert_eq!(lens, vec![50, 50, 40]);
+        assert_eq!(split_windows(&text, 50, 10, 2).len(), 2);
+        assert_eq!(split_windows("abc", 50, 10, 8), vec!["abc"]);
+    }
+
+    #[test]
+    fn long_text_mode_parses_aliases_and_defaults_to_truncate() {
+        let mode = |s: &str| LongTextMode::from_env(Some(s.to_string()));
+        assert_eq!(mode(" AVG "), LongTextMode::Mean);
+        assert_eq!(mode("max").pooling(), Pooling::Max);
+        assert_eq!(mode("bogus"), LongTextMode::Truncate);
+        assert!(!LongTextMode::from_env(None).is_windowed());
+    }
+
+    #[test]
+    fn pools_by_mean_or_max() {
+        let vecs = vec![vec![1.0, 4.0], vec![3.0, 0.0]];
+        assert_eq!(pool_vectors(&vecs, Pooling::Mean), vec![2.0, 2.0]);
+        assert_eq!(pool_vectors(&vecs, Pooling::Max), vec![3.0, 4.0]);
+        assert!(pool_vectors(&[], Pooling::Mean).is_empty());
+    }
+}
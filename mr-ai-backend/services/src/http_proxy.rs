@@ -0,0 +1,107 @@
+//! Explicit outbound proxy configuration shared by all HTTP clients.
+//!
+//! `reqwest` picks up system proxies implicitly, which makes behavior depend
+//! on the build and the environment. Clients built via [`apply_proxy_env`]
+//! disable that detection and apply proxies explicitly instead.
+//!
+//! Precedence (highest first):
+//! 1) `MR_AI_PROXY` — one proxy for all schemes;
+//! 2) `HTTPS_PROXY` / `https_proxy` for `https://` URLs and
+//!    `HTTP_PROXY` / `http_proxy` for `http://` URLs;
+//! 3) nothing set → direct connections.
+//!
+//! Bypass list: `NO_PROXY` / `no_proxy` (comma-separated hosts, domains, CIDRs).
+//! `localhost`, `127.0.0.1` and `::1` are always bypassed so a local Ollama
+//! keeps working behind a corporate proxy.
+
+use reqwest::{ClientBuilder, NoProxy, Proxy};
+use tracing::debug;
+
+/// Hosts that never go through a proxy.
+const ALWAYS_BYPASS: &str = "localhost,127.0.0.1,::1";
+
+/// Resolved proxy settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// Proxy URL for `https://` requests.
+    pub https: Option<String>,
+    /// Proxy URL for `http://` requests.
+    pub http: Option<String>,
+    /// Comma-separated bypass list (always includes loopback hosts).
+    pub no_proxy: String,
+}
+
+impl ProxyConfig {
+    /// Reads proxy settings from the environment (see module docs for precedence).
+    pub fn from_env() -> Self {
+        let var = |keys: &[&str]| -> Option<String> {
+            keys.iter()
+                .filter_map(|k| std::env::var(k).ok())
+                .map(|v| v.trim().to_string())
+                .find(|v| !v.is_empty())
+        };
+        let all = var(&["MR_AI_PROXY"]);
+        let no_proxy = match var(&["NO_PROXY", "no_proxy"]) {
+            Some(list) => format!("{list},{ALWAYS_BYPASS}"),
+            None => ALWAYS_BYPASS.to_string(),
+        };
+        Self {
+            https: all.clone().or_else(|| var(&["HTTPS_PROXY", "https_proxy"])),
+            http: all.or_else(|| var(&["HTTP_PROXY", "http_proxy"])),
+            no_proxy,
+        }
+    }
+
+    /// Applies these settings to `builder`, replacing reqwest's implicit detection.
+    pub fn apply(&self, builder: ClientBuilder) -> Result<ClientBuilder, reqwest::Error> {
+        let mut builder = builder.no_proxy();
+        if let Some(url) = &self.https {
+            builder =
+                builder.proxy(Proxy::https(url)?.no_proxy(NoProxy::from_string(&self.no_proxy)));
+        }
+        if let Some(url) = &self.http {
+            builder =
+                builder.proxy(Proxy::http(url)?.no_proxy(NoProxy::from_string(&self.no_proxy)));
+        }
+        if self.https.is_some() || self.http.is_some() {
+            debug!(
+                https = self.https.is_some(),
+                http = self.http.is_some(),
+                no_proxy = %self.no_proxy,
+                "http proxy configured"
+            );
+        }
+        Ok(builder)
+    }
+}
+
+/// Applies [`ProxyConfig::from_env`] to `builder`.
+pub fn apply_proxy_env(builder: ClientBuilder) -> Result<ClientBuilder, reqwest::Error> {
+    ProxyConfig::from_env().apply(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_config_builds_a_client_and_rejects_bad_urls() {
+        let cfg = ProxyConfig {
+            https: Some("http://proxy.internal:3128".into()),
+            http: None,
+            no_proxy: ALWAYS_BYPASS.into(),
+        };
+        assert!(
+            cfg.apply(reqwest::Client::builder())
+                .unwrap()
+                .build()
+                .is_ok()
+        );
+
+        let bad = ProxyConfig {
+            http: Some("not a url".into()),
+            ..ProxyConfig::default()
+        };
+        assert!(bad.apply(reqwest::Client::builder()).is_err());
+    }
+}
@@ -1 +1,9 @@
+pub mod data_root;
+pub mod embed_window;
+pub mod http_proxy;
+pub mod namespaces;
+pub mod progress;
+pub mod qdrant_error;
+pub mod score_breakdown;
+pub mod skipped_file;
 pub mod uuid;
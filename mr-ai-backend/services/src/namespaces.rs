@@ -0,0 +1,124 @@
+//! Embedding namespaces: one project indexed into several collections, each
+//! embedded with its own model and vector size (e.g. code and docs).
+//!
+//! Namespaces come from `RAG_NAMESPACES`, `;`-separated entries of
+//! `name|model|dim[|lang,lang]`, e.g.
+//! `code|bge-m3|1024;docs|nomic-embed-text:v1.5|768|markdown,plaintext`.
+//! A namespace without languages takes every language no other namespace
+//! lists. Each namespace lives in the collection `<base>__ns_<name>`, which
+//! records the model and dimension it was built with under [`MODEL_KEY`] and
+//! [`DIM_KEY`] so readers pick the matching embedder.
+
+use serde::{Deserialize, Serialize};
+
+/// Env var listing the namespaces (unset or blank: a single collection).
+pub const NAMESPACES_ENV: &str = "RAG_NAMESPACES";
+
+/// Collection metadata key holding the embedding model name.
+pub const MODEL_KEY: &str = "embedding_model";
+
+/// Collection metadata key holding the embedding dimension.
+pub const DIM_KEY: &str = "embedding_dim";
+
+/// One namespace: its name, embedding model and vector size.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingNamespace {
+    pub name: String,
+    pub model: String,
+    pub dim: usize,
+    /// Snake_case languages routed here; empty = every unlisted language.
+    #[serde(default)]
+    pub languages: Vec<String>,
+}
+
+impl EmbeddingNamespace {
+    /// Collection of this namespace under the `base` collection name.
+    pub fn collection(&self, base: &str) -> String {
+        format!("{base}__ns_{}", self.name)
+    }
+}
+
+/// Namespaces from [`NAMESPACES_ENV`]; `Err` carries the malformed value.
+pub fn from_env() -> Result<Vec<EmbeddingNamespace>, String> {
+    match std::env::var(NAMESPACES_ENV) {
+        Ok(raw) => parse(&raw).ok_or(raw),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// Parses a [`NAMESPACES_ENV`] value. Names must be unique and dims > 0.
+pub fn parse(raw: &str) -> Option<Vec<EmbeddingNamespace>> {
+    let mut out: Vec<EmbeddingNamespace> = Vec::new();
+    for entry in raw.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        let mut fields = entry.split('|').map(str::trim);
+        let name = fields.next().filter(|s| is_name(s))?;
+        let model = fields.next().filter(|s| !s.is_empty())?;
+        let dim = fields.next()?.parse().ok().filter(|&d| d > 0)?;
+        let languages = fields
+            .next()
+            .map(|l| {
+                l.split(',')
+                    .map(|s| s.trim().to_lowercase())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        if fields.next().is_some() || out.iter().any(|n| n.name == name) {
+            return None;
+        }
+        out.push(EmbeddingNamespace {
+            name: name.to_string(),
+            model: model.to_string(),
+            dim,
+            languages,
+        });
+    }
+    Some(out)
+}
+
+/// Namespace that chunks of `language` belong to: the one listing it, else
+/// the first catch-all namespace.
+pub fn for_language<'a>(
+    namespaces: &'a [EmbeddingNamespace],
+    language: &str,
+) -> Option<&'a EmbeddingNamespace> {
+    namespaces
+        .iter()
+        .find(|n| n.languages.iter().any(|l| l == language))
+        .or_else(|| namespaces.iter().find(|n| n.languages.is_empty()))
+}
+
+fn is_name(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_models_with_tags_and_language_lists() {
+        let ns =
+            parse("code|bge-m3|1024; docs|nomic-embed-text:v1.5|768|Markdown, plaintext").unwrap();
+        assert_eq!(ns.len(), 2);
+        assert_eq!(ns[1].model, "nomic-embed-text:v1.5");
+        assert_eq!(ns[1].dim, 768);
+        assert_eq!(ns[1].languages, vec!["markdown", "plaintext"]);
+        assert_eq!(ns[0].collection("mr_ai_code"), "mr_ai_code__ns_code");
+
+        assert_eq!(for_language(&ns, "markdown").unwrap().name, "docs");
+        assert_eq!(for_language(&ns, "rust").unwrap().name, "code");
+        assert_eq!(parse("").unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn rejects_malformed_entries() {
+        assert!(parse("code|bge-m3").is_none());
+        assert!(parse("code|bge-m3|0").is_none());
+        assert!(parse("code|bge-m3|1024;code|other|768").is_none());
+        assert!(parse("co de|bge-m3|1024").is_none());
+        assert!(parse("code|bge-m3|1024|rust|extra").is_none());
+    }
+}
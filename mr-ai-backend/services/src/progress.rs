@@ -0,0 +1,99 @@
+//! Progress reporting for batch jobs (same shape as `contextor::Progress`),
+//! shared by bulk indexing (`code_indexer::index_all_projects_with`) and batch
+//! clones (`project_code_store::clone_list_with_progress`).
+//!
+//! Library calls default to `NoopProgress`; CLIs can pass `IndicatifProgress`
+//! to get a live bar with one step per finished item.
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// Minimal progress interface used by the batch entrypoints.
+pub trait Progress: Send + Sync {
+    /// Set known total steps (optional).
+    fn set_total(&self, _n: u64) {}
+    /// Advance by one step and show a short message.
+    fn step(&self, _msg: &str) {}
+    /// Replace current message without advancing.
+    fn message(&self, _msg: &str) {}
+    /// Finish the UI.
+    fn finish(&self, _msg: &str) {}
+}
+
+/// No-op reporter for servers/headless runs.
+#[derive(Default, Clone, Copy)]
+pub struct NoopProgress;
+impl Progress for NoopProgress {}
+
+/// Indicatif-based bar for CLI/TTY.
+pub struct IndicatifProgress {
+    pb: ProgressBar,
+}
+
+impl IndicatifProgress {
+    /// Bounded bar; the total is set once the items are known.
+    pub fn bar() -> Self {
+        let pb = ProgressBar::new(0);
+        pb.set_style(
+            ProgressStyle::with_template("{bar:40.cyan/blue} {pos:>3}/{len:3} {msg}").unwrap(),
+        );
+        Self { pb }
+    }
+}
+
+impl Progress for IndicatifProgress {
+    fn set_total(&self, n: u64) {
+        self.pb.set_length(n);
+    }
+    fn step(&self, msg: &str) {
+        self.pb.inc(1);
+        self.pb.set_message(msg.to_string());
+    }
+    fn message(&self, msg: &str) {
+        self.pb.set_message(msg.to_string());
+    }
+    fn finish(&self, msg: &str) {
+        self.pb.finish_with_message(msg.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recording(Mutex<Vec<String>>);
+
+    impl Progress for Recording {
+        fn step(&self, msg: &str) {
+            self.0.lock().unwrap().push(msg.to_string());
+        }
+    }
+
+    #[test]
+    fn unset_hooks_default_to_no_ops() {
+        let rec = Recording::default();
+        let progress: &dyn Progress = &rec;
+        progress.set_total(2);
+        progress.message("working");
+        progress.step("a done");
+        progress.step("b done");
+        progress.finish("all done");
+        assert_eq!(*rec.0.lock().unwrap(), vec!["a done", "b done"]);
+
+        let noop: &dyn Progress = &NoopProgress;
+        noop.step("ignored");
+    }
+
+    #[test]
+    fn bar_counts_steps_against_the_total() {
+        let bar = IndicatifProgress::bar();
+        bar.set_total(3);
+        bar.step("one");
+        bar.step("two");
+        assert_eq!((bar.pb.position(), bar.pb.length()), (2, Some(3)));
+        bar.finish("done");
+        assert!(bar.pb.is_finished());
+        assert_eq!(bar.pb.message(), "done");
+    }
+}
@@ -0,0 +1,96 @@
+//! Classification of failed Qdrant calls, shared by `rag_base::RagBaseError`
+//! and `rag_store::RagError` so both crates map the same failures the same way.
+//!
+//! Works on the error text (gRPC status code and message), so it applies to
+//! any `qdrant_client` error without depending on the client here.
+
+/// What a failed Qdrant call means for the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QdrantFailure {
+    /// The collection stores `got`-dim vectors, the request carried `want`.
+    DimensionMismatch { got: usize, want: usize },
+    /// No answer within the deadline.
+    Timeout,
+    /// Connection refused, DNS failure or transport down.
+    Unreachable,
+    /// The collection doesn't exist.
+    CollectionMissing,
+    /// Anything else (bad filter, invalid payload, ...).
+    Other,
+}
+
+/// Classifies `err` by its `Display` and `Debug` text.
+pub fn classify<E>(err: &E) -> QdrantFailure
+where
+    E: std::fmt::Display + std::fmt::Debug,
+{
+    let text = format!("{err} {err:?}").to_ascii_lowercase();
+    if let Some((want, got)) = reported_dims(&text) {
+        return QdrantFailure::DimensionMismatch { got, want };
+    }
+    let has = |keys: &[&str]| keys.iter().any(|k| text.contains(k));
+    if has(&[
+        "deadlineexceeded",
+        "deadline exceeded",
+        "timed out",
+        "timeout",
+    ]) {
+        QdrantFailure::Timeout
+    } else if has(&[
+        "unavailable",
+        "connection refused",
+        "error trying to connect",
+        "tcp connect error",
+        "dns error",
+        "transport error",
+        "connection reset",
+        "broken pipe",
+    ]) {
+        QdrantFailure::Unreachable
+    } else if text.contains("doesn't exist")
+        || (text.contains("not found") && text.contains("collection"))
+    {
+        QdrantFailure::CollectionMissing
+    } else {
+        QdrantFailure::Other
+    }
+}
+
+/// `(expected, got)` from Qdrant's "expected dim: N, got M" message.
+fn reported_dims(text: &str) -> Option<(usize, usize)> {
+    let rest = &text[text.find("expected dim:")? + "expected dim:".len()..];
+    let (want, rest) = rest.split_once(',')?;
+    let got = rest.trim_start().strip_prefix("got")?.trim_start();
+    let got: String = got.chars().take_while(char::is_ascii_digit).collect();
+    Some((want.trim().parse().ok()?, got.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_are_classified_by_status_and_message() {
+        assert_eq!(
+            classify(&"status: Unavailable, message: \"tcp connect error: Connection refused\""),
+            QdrantFailure::Unreachable
+        );
+        assert_eq!(
+            classify(&"status: DeadlineExceeded, message: \"Timeout expired\""),
+            QdrantFailure::Timeout
+        );
+        assert_eq!(
+            classify(&"Not found: Collection `code` doesn't exist!"),
+            QdrantFailure::CollectionMissing
+        );
+        assert_eq!(
+            classify(&"Wrong input: Vector dimension error: expected dim: 768, got 1024"),
+            QdrantFailure::DimensionMismatch {
+                got: 1024,
+                want: 768
+            }
+        );
+        assert_eq!(classify(&"expected dim: 768, got x"), QdrantFailure::Other);
+        assert_eq!(classify(&"bad filter"), QdrantFailure::Other);
+    }
+}
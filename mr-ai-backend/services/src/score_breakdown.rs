@@ -0,0 +1,15 @@
+//! Ranking components of a hybrid search hit, shared by `rag-base` and
+//! `rag-store` so both explain scores the same way.
+
+use serde::{Deserialize, Serialize};
+
+/// How vector similarity and the lexical score combined into a hit's rank.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    /// Vector similarity (plus any fallback boost for lexical-recall hits).
+    pub vector: f32,
+    /// Lexical match score or re-rank boost.
+    pub lexical: f32,
+    /// Fused score; the value hits are ordered by.
+    pub fused: f32,
+}
@@ -0,0 +1,21 @@
+//! Files left out of an index, shared by the code indexer and the reviewer.
+
+use serde::Serialize;
+
+/// A file left out of an index, and why.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedFile {
+    /// Repo-relative, `/`-separated path.
+    pub path: String,
+    /// `missing at ref`, `nul byte`, `control characters` or `non-utf8`.
+    pub reason: String,
+}
+
+impl SkippedFile {
+    pub fn new(path: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            reason: reason.into(),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{error::Error, str::FromStr, sync::Arc};
+use std::{error::Error, str::FromStr, sync::Arc, time::Duration};
 
 use ai_llm_service::{config::default_config, service_profiles::LlmServiceProfiles};
 use api;
@@ -11,6 +11,9 @@ use tracing_subscriber::{
     util::SubscriberInitExt,
 };
 
+/// Startup health probes per profile before giving up (backoff 1s, 2s, 4s, …).
+const STARTUP_HEALTH_ATTEMPTS: u32 = 6;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     // Load environment variables from .env file.
@@ -30,9 +33,14 @@ async fn main() -> Result<(), Box<dyn Error>> {
         Some(10),
     )?);
 
-    let statuses = svc.health_all().await?;
-
-    println!("{:?}", statuses);
+    // Non-fatal and off the startup path: models that are down only affect the
+    // routes that need them, and `/readiness` reports them until they recover.
+    let startup_svc = svc.clone();
+    tokio::spawn(async move {
+        startup_svc
+            .health_with_retry(STARTUP_HEALTH_ATTEMPTS, Duration::from_secs(1))
+            .await;
+    });
 
     api::start(svc).await?;
     Ok(())